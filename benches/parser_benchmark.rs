@@ -3,6 +3,7 @@
 //! Run with: cargo bench
 
 use claude_usage::keeper_integration::KeeperIntegration;
+use claude_usage::timestamp_parser::TimestampParser;
 use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
 use std::fs;
 use std::io::Write;
@@ -89,10 +90,37 @@ fn benchmark_memory_usage(c: &mut Criterion) {
     group.finish();
 }
 
+fn benchmark_timestamp_parser(c: &mut Criterion) {
+    let mut group = c.benchmark_group("timestamp_parser");
+
+    // Logs repeat each timestamp for the entries written within it
+    let timestamps: Vec<String> = (0..10000)
+        .map(|i| format!("2025-01-15T10:{:02}:{:02}.123Z", i / 600 % 60, i / 10 % 60))
+        .collect();
+
+    group.bench_function("cached", |b| {
+        b.iter(|| {
+            for timestamp in &timestamps {
+                black_box(TimestampParser::parse(black_box(timestamp)).unwrap());
+            }
+        })
+    });
+    group.bench_function("uncached", |b| {
+        b.iter(|| {
+            for timestamp in &timestamps {
+                black_box(TimestampParser::parse_uncached(black_box(timestamp)).unwrap());
+            }
+        })
+    });
+
+    group.finish();
+}
+
 criterion_group!(
     benches,
     benchmark_keeper_parser,
     benchmark_error_handling,
-    benchmark_memory_usage
+    benchmark_memory_usage,
+    benchmark_timestamp_parser
 );
 criterion_main!(benches);
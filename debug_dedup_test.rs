@@ -51,6 +51,9 @@ async fn main() {
         until_date: None,
         snapshot: false,
         exclude_vms: false,
+        estimate_missing_usage: false,
+        provenance: false,
+        group_by: Default::default(),
     };
 
     println!("\nRunning analysis...");
@@ -0,0 +1,96 @@
+//! A custom aggregator
+//!
+//! Totals tokens and cost per model family across all logs with a
+//! [`JsonlProcessor`] of its own, pricing each entry with the same offline
+//! rates the reports use. Entries logged twice (resumed sessions, copies on
+//! VMs) are counted once, by their `messageId:requestId`.
+//!
+//! Run with: cargo run --example custom_aggregator
+
+use anyhow::Result;
+use claude_usage::models::{model_family, MicroDollars, UsageEntry};
+use claude_usage::parser::{FileParser, JsonlProcessor};
+use claude_usage::pricing::calculate_cost_simple;
+use std::collections::{BTreeMap, HashSet};
+
+/// Tokens and cost of one model family
+#[derive(Debug, Default)]
+struct FamilyTotals {
+    entries: u64,
+    tokens: u64,
+    cost: MicroDollars,
+}
+
+/// Adds the entries of each file it processes to shared totals
+struct ByFamily<'a> {
+    seen: &'a mut HashSet<String>,
+    totals: &'a mut BTreeMap<String, FamilyTotals>,
+}
+
+impl JsonlProcessor for ByFamily<'_> {
+    type Output = ();
+
+    fn process_entry(&mut self, entry: UsageEntry, _line_number: usize) -> Result<()> {
+        let Some(usage) = &entry.message.usage else {
+            return Ok(());
+        };
+        if !self
+            .seen
+            .insert(format!("{}:{}", entry.message.id, entry.request_id))
+        {
+            return Ok(());
+        }
+
+        let cost = calculate_cost_simple(
+            &entry.message.model,
+            usage.input_tokens,
+            usage.output_tokens,
+            usage.cache_creation_input_tokens,
+            usage.cache_read_input_tokens,
+        );
+        let totals = self
+            .totals
+            .entry(model_family(&entry.message.model))
+            .or_default();
+        totals.entries += 1;
+        totals.tokens += u64::from(usage.input_tokens)
+            + u64::from(usage.output_tokens)
+            + u64::from(usage.cache_creation_input_tokens)
+            + u64::from(usage.cache_read_input_tokens);
+        totals.cost += MicroDollars::from_dollars(entry.cost_usd.unwrap_or(cost));
+        Ok(())
+    }
+
+    fn finalize(self) -> Result<()> {
+        Ok(())
+    }
+}
+
+fn main() -> Result<()> {
+    let parser = FileParser::new();
+    let claude_paths = parser.discover_claude_paths(false)?;
+    let mut seen = HashSet::new();
+    let mut totals = BTreeMap::new();
+    for (file, _session_dir) in parser.find_jsonl_files(&claude_paths)? {
+        let processor = ByFamily {
+            seen: &mut seen,
+            totals: &mut totals,
+        };
+        parser.process_jsonl_file(&file, processor)?;
+    }
+
+    println!(
+        "{:<12} {:>9} {:>15} {:>11}",
+        "Family", "Entries", "Tokens", "Cost"
+    );
+    for (family, family_totals) in &totals {
+        println!(
+            "{:<12} {:>9} {:>15} {:>11}",
+            family,
+            family_totals.entries,
+            family_totals.tokens,
+            format!("${:.2}", family_totals.cost)
+        );
+    }
+    Ok(())
+}
@@ -0,0 +1,46 @@
+//! Embedding a report in another tool
+//!
+//! Asks the analyzer for the last week's daily report with a typed
+//! [`AnalyzerRequest`] and renders it as a Markdown table, the way a status
+//! page or chat bot might show it. Nothing is printed by the library itself:
+//! the [`Report`] rows are plain data, and `ReportDisplayManager::daily_json`
+//! builds the same document as `daily --json` when JSON is wanted instead.
+//!
+//! Run with: cargo run --example embed_report [-- --json]
+
+use anyhow::Result;
+use claude_usage::prelude::*;
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let json = std::env::args().any(|arg| arg == "--json");
+    let analyzer = ClaudeUsageAnalyzer::new();
+
+    if json {
+        let sessions = analyzer.sessions(AnalyzerRequest::daily()).await?;
+        let today = chrono::Local::now().date_naive();
+        let document = ReportDisplayManager::new().daily_json(&sessions, Some(7), None, today);
+        println!("{}", serde_json::to_string_pretty(&document)?);
+        return Ok(());
+    }
+
+    let Report::Daily(days) = analyzer.query(AnalyzerRequest::daily().limit(7)).await? else {
+        unreachable!("a daily request yields a daily report");
+    };
+    println!("| Date | Sessions | Top project | Cost |");
+    println!("|------|---------:|-------------|-----:|");
+    for day in &days {
+        let top = day
+            .projects
+            .iter()
+            .max_by_key(|project| project.total_cost)
+            .map_or("", |project| project.project.as_str());
+        println!(
+            "| {} | {} | {} | ${:.2} |",
+            day.date, day.total_sessions, top, day.total_cost
+        );
+    }
+    let total = days.iter().map(|day| day.total_cost).sum::<MicroDollars>();
+    println!("\nLast {} days: ${:.2}", days.len(), total);
+    Ok(())
+}
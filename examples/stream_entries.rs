@@ -0,0 +1,68 @@
+//! Streaming usage entries
+//!
+//! Walks every Claude log the CLI would read and prints each entry that
+//! carries usage, one line at a time, without collecting a file into memory
+//! first. `FileParser::process_jsonl_file` hands each parsed line to a
+//! [`JsonlProcessor`]. A closure wrapped in `StreamProcessor` is enough when
+//! nothing needs to be returned; this one hands back how many entries are
+//! still to print.
+//!
+//! Run with: cargo run --example stream_entries [-- <max entries>]
+
+use anyhow::Result;
+use claude_usage::models::UsageEntry;
+use claude_usage::parser::{FileParser, JsonlProcessor};
+
+/// Prints entries until `remaining` reaches zero
+struct PrintEntries {
+    remaining: usize,
+}
+
+impl JsonlProcessor for PrintEntries {
+    /// Entries still to print
+    type Output = usize;
+
+    fn process_entry(&mut self, entry: UsageEntry, _line_number: usize) -> Result<()> {
+        let Some(usage) = &entry.message.usage else {
+            return Ok(());
+        };
+        if self.remaining == 0 {
+            return Ok(());
+        }
+        self.remaining -= 1;
+        println!(
+            "{}  {:<28} in {:>7}  out {:>7}  cache {:>9}",
+            entry.timestamp,
+            entry.message.model,
+            usage.input_tokens,
+            usage.output_tokens,
+            usage.cache_creation_input_tokens + usage.cache_read_input_tokens
+        );
+        Ok(())
+    }
+
+    fn finalize(self) -> Result<usize> {
+        Ok(self.remaining)
+    }
+}
+
+fn main() -> Result<()> {
+    let max_entries = std::env::args()
+        .nth(1)
+        .map(|arg| arg.parse())
+        .transpose()?
+        .unwrap_or(50);
+
+    let parser = FileParser::new();
+    let claude_paths = parser.discover_claude_paths(false)?;
+    let mut remaining = max_entries;
+    for (file, _session_dir) in parser.find_jsonl_files(&claude_paths)? {
+        if remaining == 0 {
+            break;
+        }
+        remaining = parser.process_jsonl_file(&file, PrintEntries { remaining })?;
+    }
+
+    eprintln!("{} entries", max_entries - remaining);
+    Ok(())
+}
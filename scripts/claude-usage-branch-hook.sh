@@ -0,0 +1,46 @@
+#!/bin/bash
+
+# Record the git branch of each Claude Code session for `--group-by branch`
+#
+# Install as a SessionStart hook in ~/.claude/settings.json:
+#
+#   "hooks": {
+#     "SessionStart": [
+#       { "hooks": [{ "type": "command", "command": "/path/to/claude-usage-branch-hook.sh" }] }
+#     ]
+#   }
+#
+# Claude Code passes the session ID and working directory as JSON on stdin.
+# Sessions started outside a git repository are not recorded. The record is
+# appended to CLAUDE_USAGE_BRANCHES_FILE, or to branches.jsonl in the same
+# data directory claude-usage reads by default.
+
+input=$(cat)
+
+json_field() {
+    printf '%s' "$input" | sed -n "s/.*\"$1\"[[:space:]]*:[[:space:]]*\"\([^\"]*\)\".*/\1/p" | head -n 1
+}
+
+session_id=$(json_field session_id)
+cwd=$(json_field cwd)
+[ -n "$session_id" ] || exit 0
+
+branch=$(git -C "${cwd:-.}" rev-parse --abbrev-ref HEAD 2>/dev/null) || exit 0
+if [ "$branch" = "HEAD" ]; then
+    # Detached HEAD: record the commit instead
+    branch=$(git -C "${cwd:-.}" rev-parse --short HEAD 2>/dev/null) || exit 0
+fi
+
+if [ -z "$CLAUDE_USAGE_BRANCHES_FILE" ]; then
+    if [ "$(uname)" = "Darwin" ]; then
+        data_dir="$HOME/Library/Application Support"
+    else
+        data_dir="${XDG_DATA_HOME:-$HOME/.local/share}"
+    fi
+    CLAUDE_USAGE_BRANCHES_FILE="$data_dir/claude-usage/branches.jsonl"
+fi
+
+mkdir -p "$(dirname "$CLAUDE_USAGE_BRANCHES_FILE")"
+branch=$(printf '%s' "$branch" | sed 's/\\/\\\\/g; s/"/\\"/g')
+printf '{"sessionId":"%s","branch":"%s","timestamp":"%s"}\n' \
+    "$session_id" "$branch" "$(date -u +%Y-%m-%dT%H:%M:%SZ)" >> "$CLAUDE_USAGE_BRANCHES_FILE"
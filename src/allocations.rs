@@ -0,0 +1,136 @@
+//! Cost-center allocations for shared sessions
+//!
+//! A session that served several tickets can have its cost split across
+//! categories with an allocations file (`allocations.file`): a JSON object
+//! mapping a session ID (raw or display ID) to percentages per category.
+//!
+//! ```json
+//! {
+//!   "0b7e6f2a-1c3d-4e5f-8a9b-123456789abc": { "PROJ-101": 60, "PROJ-204": 40 }
+//! }
+//! ```
+//!
+//! Allocations are applied when reports aggregate with
+//! `--group-by cost_center`; sessions without an allocation are reported as
+//! [`UNALLOCATED`]. Every session's percentages must sum to 100.
+
+use crate::models::SessionOutput;
+use anyhow::{Context, Result};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::Path;
+
+/// Category for sessions that have no allocation
+pub const UNALLOCATED: &str = "unallocated";
+
+/// How far a session's percentages may stray from 100 (float input)
+const PERCENT_TOLERANCE: f64 = 0.01;
+
+/// Percentages per category, keyed by session ID
+#[derive(Debug, Clone, Default)]
+pub struct Allocations {
+    sessions: HashMap<String, BTreeMap<String, f64>>,
+}
+
+impl Allocations {
+    /// Build allocations, checking that each session's shares sum to 100%
+    pub fn new(sessions: HashMap<String, BTreeMap<String, f64>>) -> Result<Self> {
+        for (session_id, shares) in &sessions {
+            if shares.is_empty() {
+                return Err(anyhow::anyhow!(
+                    "Allocation for session {} has no categories",
+                    session_id
+                ));
+            }
+            if let Some((category, percent)) = shares
+                .iter()
+                .find(|(_, percent)| !percent.is_finite() || **percent <= 0.0)
+            {
+                return Err(anyhow::anyhow!(
+                    "Allocation for session {} gives {} an invalid share: {}",
+                    session_id,
+                    category,
+                    percent
+                ));
+            }
+            let total: f64 = shares.values().sum();
+            if (total - 100.0).abs() > PERCENT_TOLERANCE {
+                return Err(anyhow::anyhow!(
+                    "Allocation for session {} sums to {}%, expected 100%",
+                    session_id,
+                    total
+                ));
+            }
+        }
+        Ok(Self { sessions })
+    }
+
+    /// Load and validate an allocations file
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read allocations file: {}", path.display()))?;
+        let sessions = serde_json::from_str(&content)
+            .with_context(|| format!("Invalid allocations file: {}", path.display()))?;
+        Self::new(sessions).with_context(|| format!("Invalid allocations in {}", path.display()))
+    }
+
+    /// Category shares (percentages) for a session, or all of it unallocated
+    pub fn shares(&self, session: &SessionOutput) -> Vec<(String, f64)> {
+        self.sessions
+            .get(&session.session_id)
+            .or_else(|| self.sessions.get(&session.display_id))
+            .map(|shares| shares.iter().map(|(c, p)| (c.clone(), *p)).collect())
+            .unwrap_or_else(|| vec![(UNALLOCATED.to_string(), 100.0)])
+    }
+}
+
+/// Split `total` by percentage shares so the parts always add up to `total`
+///
+/// Each part is rounded down and the remainder goes to the last share, so
+/// splitting never creates or loses a micro-dollar or token.
+pub fn split_amount(total: i64, percentages: &[f64]) -> Vec<i64> {
+    let mut parts: Vec<i64> = percentages
+        .iter()
+        .map(|percent| (total as f64 * percent / 100.0).floor() as i64)
+        .collect();
+    let assigned: i64 = parts.iter().sum();
+    if let Some(last) = parts.last_mut() {
+        *last += total - assigned;
+    }
+    parts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn shares(pairs: &[(&str, f64)]) -> BTreeMap<String, f64> {
+        pairs.iter().map(|(c, p)| (c.to_string(), *p)).collect()
+    }
+
+    #[test]
+    fn test_allocations_must_sum_to_100() {
+        let valid = HashMap::from([("s1".to_string(), shares(&[("A", 60.0), ("B", 40.0)]))]);
+        assert!(Allocations::new(valid).is_ok());
+
+        let short = HashMap::from([("s1".to_string(), shares(&[("A", 60.0), ("B", 30.0)]))]);
+        let err = Allocations::new(short).unwrap_err().to_string();
+        assert!(err.contains("sums to 90%"), "{}", err);
+
+        let negative = HashMap::from([("s1".to_string(), shares(&[("A", 110.0), ("B", -10.0)]))]);
+        assert!(Allocations::new(negative).is_err());
+
+        let empty = HashMap::from([("s1".to_string(), BTreeMap::new())]);
+        assert!(Allocations::new(empty).is_err());
+    }
+
+    #[test]
+    fn test_split_amount_preserves_total() {
+        assert_eq!(split_amount(100, &[60.0, 40.0]), vec![60, 40]);
+        assert_eq!(split_amount(10, &[33.3, 33.3, 33.4]), vec![3, 3, 4]);
+
+        let parts = split_amount(1_000_001, &[33.3, 33.3, 33.4]);
+        assert_eq!(parts.iter().sum::<i64>(), 1_000_001);
+        assert_eq!(split_amount(0, &[50.0, 50.0]), vec![0, 0]);
+    }
+}
@@ -41,20 +41,12 @@
 //! ## Usage Example
 //!
 //! ```rust
-//! use claude_usage::{ClaudeUsageAnalyzer, ProcessOptions};
+//! use claude_usage::prelude::*;
 //!
 //! # async fn example() -> anyhow::Result<()> {
 //! let mut analyzer = ClaudeUsageAnalyzer::new();
 //!
-//! let options = ProcessOptions {
-//!     command: "daily".to_string(),
-//!     json_output: false,
-//!     limit: Some(30),
-//!     since_date: None,
-//!     until_date: None,
-//!     snapshot: false,
-//!     exclude_vms: false,
-//! };
+//! let options = ProcessOptions::new("daily").with_limit(30);
 //!
 //! // Run analysis command
 //! analyzer.run_command("daily", options).await?;
@@ -62,6 +54,42 @@
 //! # }
 //! ```
 //!
+//! ## Custom Aggregations
+//!
+//! [`ClaudeUsageAnalyzer::process_with`] runs your own [`JsonlProcessor`]
+//! over the same discovered, filtered and deduplicated entries the reports
+//! count, such as a histogram of requests per hour of the day:
+//!
+//! ```rust
+//! use claude_usage::prelude::*;
+//!
+//! #[derive(Default)]
+//! struct HourHistogram([u32; 24]);
+//!
+//! impl JsonlProcessor for HourHistogram {
+//!     type Output = [u32; 24];
+//!
+//!     fn process_entry(&mut self, entry: UsageEntry, _line: usize) -> anyhow::Result<()> {
+//!         // Timestamps are RFC 3339, e.g. 2025-06-01T14:05:00Z
+//!         if let Some(hour) = entry.timestamp.get(11..13).and_then(|h| h.parse::<usize>().ok()) {
+//!             self.0[hour % 24] += 1;
+//!         }
+//!         Ok(())
+//!     }
+//!
+//!     fn finalize(self) -> anyhow::Result<Self::Output> {
+//!         Ok(self.0)
+//!     }
+//! }
+//!
+//! # fn example() -> anyhow::Result<()> {
+//! let analyzer = ClaudeUsageAnalyzer::new();
+//! let options = ProcessOptions::new("daily").with_exclude_vms(true);
+//! let per_hour = analyzer.process_with(&options, HourHistogram::default())?;
+//! # Ok(())
+//! # }
+//! ```
+//!
 //! ## Performance Characteristics
 //!
 //! - **Parallel Processing**: Files are processed in parallel chunks for optimal performance
@@ -69,14 +97,47 @@
 //! - **Intelligent Caching**: Deduplication engine maintains time-windowed caches
 //! - **Early Exit Optimization**: Can stop processing early when limits are reached
 
-use crate::dedup::ProcessOptions;
-use crate::reports::ReportDisplayManager;
+use crate::config::get_config;
+use crate::allocations::Allocations;
+use crate::branches::SessionBranches;
+use crate::batch::{BatchQuery, BatchReport};
+use crate::blocks;
+use crate::cost_audit::{self, DEFAULT_TOLERANCE_PCT};
+use crate::dedup::{Command, CostMode, GroupBy, ProcessOptions};
+use crate::dedup_set::DedupSet;
+use crate::estimation::TokenEstimator;
+use crate::export::ExportRecord;
+use crate::export_manifest;
+use crate::file_discovery::ProjectDirFilter;
+use crate::forecast::{self, Forecast};
+use crate::limits::PlanLimits;
+use crate::line_reader;
+use crate::memory;
+use crate::notes;
+use crate::parse_cache::{self, ParseCache};
+use crate::parser::{FileParser, JsonlProcessor, ProcessedEntry};
+use crate::parquet::reader::{ParquetCache, ParquetSummaryReader};
+use crate::pipeline::{self, FailedFile};
+use crate::project_names;
+use crate::projects::{self, ProjectTotals};
+use crate::redaction::ProjectRedactor;
+use crate::report_diff::{self, ReportState};
+use crate::skipped::{SkipReason, SkippedData};
+use crate::top_k;
+use crate::reports::{self, ReportDisplayManager};
+use crate::request::{AnalyzerRequest, Report, ReportKind};
+use crate::sanitize::{self, SanitizedEntries};
 use crate::models::*;
-use anyhow::Result;
-use tracing::warn;
+use anyhow::{Context, Result};
+use chrono::{DateTime, NaiveDate, Utc};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use tracing::{info, warn};
 
 pub struct ClaudeUsageAnalyzer {
     display_manager: ReportDisplayManager,
+    sessions_processed: usize,
+    parquet_cache: Option<ParquetCache>,
 }
 
 impl Default for ClaudeUsageAnalyzer {
@@ -89,122 +150,1281 @@ impl ClaudeUsageAnalyzer {
     pub fn new() -> Self {
         Self {
             display_manager: ReportDisplayManager::new(),
+            sessions_processed: 0,
+            parquet_cache: None,
         }
     }
 
+    /// Keep the messages of backup files between runs, rereading only the
+    /// files that changed (used by `daily --watch`)
+    pub fn enable_parquet_cache(&mut self) {
+        self.parquet_cache.get_or_insert_with(ParquetCache::default);
+    }
+
+    /// Number of sessions aggregated by the last `run_command`
+    pub fn sessions_processed(&self) -> usize {
+        self.sessions_processed
+    }
+
+    /// Produce the report `request` asks for
+    ///
+    /// When the request's cancellation token is cancelled during the scan,
+    /// the report covers what was read so far.
+    #[allow(dead_code)]
+    pub async fn query(&self, request: AnalyzerRequest) -> Result<Report> {
+        let (kind, options) = request.build()?;
+        let limit = options.limit;
+        let mut display = ReportDisplayManager::new();
+        display.set_group_by(options.group_by);
+        display.set_order(options.order);
+        display.set_breakdown(options.breakdown);
+
+        Ok(match kind {
+            ReportKind::Daily => {
+                let sessions = self.aggregate_with_skipped(kind.as_str(), options).await?.0;
+                let today = chrono::Local::now().date_naive();
+                Report::Daily(display.process_daily_with_projects(&sessions, limit, today))
+            }
+            ReportKind::Monthly => {
+                let sessions = self.aggregate_with_skipped(kind.as_str(), options).await?.0;
+                Report::Monthly(display.process_monthly_data(&sessions, limit))
+            }
+            ReportKind::Session => {
+                let sessions = self.aggregate_with_skipped(kind.as_str(), options).await?.0;
+                let sorted = reports::sorted_sessions(&sessions, limit);
+                Report::Session(sorted.into_iter().cloned().collect())
+            }
+            ReportKind::Blocks => {
+                let (mut blocks, _) = self.session_blocks(&options)?;
+                if let Some(limit) = limit {
+                    blocks.drain(..blocks.len().saturating_sub(limit));
+                }
+                Report::Blocks(blocks)
+            }
+        })
+    }
+
+    /// Aggregate the sessions behind `request`'s report
+    ///
+    /// When the request's cancellation token is cancelled during the scan,
+    /// the sessions read so far are returned; [`ProcessOptions::is_cancelled`]
+    /// tells such partial results apart. Blocks are built from entries rather
+    /// than sessions, so a blocks request yields no sessions.
+    pub async fn sessions(&self, request: AnalyzerRequest) -> Result<Vec<SessionOutput>> {
+        let (kind, options) = request.build()?;
+        Ok(self.aggregate_with_skipped(kind.as_str(), options).await?.0)
+    }
+
+    /// Aggregate the sessions behind `request`'s report from the logs of the
+    /// instance roots `claude_paths` alone, leaving out the backups
+    ///
+    /// The request's date and project filters apply as in
+    /// [`sessions`](Self::sessions); instances are not discovered, so
+    /// `claude_paths` are read as given, each a directory holding `projects/`.
+    #[allow(dead_code)]
+    pub fn sessions_from_roots(
+        &self,
+        request: AnalyzerRequest,
+        claude_paths: &[PathBuf],
+    ) -> Result<Vec<SessionOutput>> {
+        let (_, options) = request.build()?;
+        Self::with_parse_cache(|cache| self.report_sessions(None, claude_paths, cache, &options))
+            .map(|(sessions, _, _)| sessions)
+    }
+
+    /// Aggregate the sessions of `command`'s report
+    #[deprecated(note = "use `sessions` or `query` with an `AnalyzerRequest`")]
+    #[allow(dead_code)]
     pub async fn aggregate_data(
         &self,
-        _command: &str,
+        command: &str,
         options: ProcessOptions,
     ) -> Result<Vec<SessionOutput>> {
+        Ok(self.aggregate_with_skipped(command, options).await?.0)
+    }
+
+    /// [`sessions`](Self::sessions), along with the logs that could not be
+    /// read and what the date and project filters left out
+    async fn aggregate_with_skipped(
+        &self,
+        _command: &str,
+        options: ProcessOptions,
+    ) -> Result<(Vec<SessionOutput>, Vec<FailedFile>, SkippedData)> {
         // Check and refresh baseline for daily/monthly commands
         use crate::live::baseline::{should_refresh_baseline, refresh_baseline};
-        use crate::parquet::reader::ParquetSummaryReader;
         use crate::config::get_config;
         
-        // Only use Parquet data for daily/monthly commands
-        let use_parquet = matches!(_command, "daily" | "monthly");
+        // Only use Parquet data for the report commands
+        let use_parquet = matches!(_command, "daily" | "monthly" | "session" | "top" | "forecast");
         
         if use_parquet {
-            // Check if we need to refresh the backup
-            if should_refresh_baseline() {
+            // Check if we need to refresh the backup; container mode only
+            // reads the backups, which are mounted read-only
+            let container = get_config().container.output_dir.is_some();
+            if !container && should_refresh_baseline() && !options.is_cancelled() {
                 // Run backup if needed (this is async)
                 refresh_baseline().await.unwrap_or_default();
             }
 
-            // Get backup directory from config
-            let _config = get_config();
             // Use ~/.claude-backup/ as the default backup location (claude-keeper default)
             let backup_dir = dirs::home_dir()
                 .unwrap_or_else(|| std::path::PathBuf::from("."))
                 .join(".claude-backup");
-            
-            // Use ParquetSummaryReader to get detailed session data
-            let reader = ParquetSummaryReader::new(backup_dir)?;
-            let sessions = reader.read_detailed_sessions()?;
-
-            if !options.json_output {
-                println!(
-                    "📊 Processed {} sessions from backup data",
-                    sessions.len()
+            let parser = FileParser::new();
+            let (backups, log_roots) = if backup_dir.exists() {
+                let log_roots = parser.discover_log_roots(options.exclude_vms)?;
+                (Some(backup_dir.as_path()), log_roots)
+            } else {
+                // Without backups (claude-keeper not installed, or never run
+                // yet) every instance is read from its logs
+                info!(
+                    backup_dir = %backup_dir.display(),
+                    "No backups found; reading the Claude logs only"
                 );
+                let log_roots = parser.discover_claude_paths(options.exclude_vms)?;
+                // With no logs either, the missing backups are the error
+                let backups = log_roots.is_empty().then_some(backup_dir.as_path());
+                (backups, log_roots)
+            };
+            if log_roots.is_empty() {
+                return self.report_sessions(backups, &log_roots, None, &options);
             }
-
-            // Filter sessions based on their daily_usage dates, not last_activity
-            // This ensures we include sessions that have activity in the date range
-            // even if their last activity was outside the range
-            let mut filtered_sessions = sessions;
-            if options.since_date.is_some() || options.until_date.is_some() {
-                filtered_sessions = filtered_sessions.into_iter()
-                    .filter(|session| {
-                        // Check if this session has any daily_usage entries within the date range
-                        for date_str in session.daily_usage.keys() {
-                            if let Ok(date) = chrono::NaiveDate::parse_from_str(date_str, "%Y-%m-%d") {
-                                let session_date = date.and_hms_opt(0, 0, 0)
-                                    .and_then(|dt| Some(chrono::DateTime::<chrono::Utc>::from_naive_utc_and_offset(dt, chrono::Utc)));
-                                
-                                if let Some(session_dt) = session_date {
-                                    // Check if this date is within our filter range
-                                    let within_range = match (&options.since_date, &options.until_date) {
-                                        (Some(since), Some(until)) => session_dt >= *since && session_dt <= *until,
-                                        (Some(since), None) => session_dt >= *since,
-                                        (None, Some(until)) => session_dt <= *until,
-                                        (None, None) => true,
-                                    };
-                                    
-                                    if within_range {
-                                        return true; // This session has activity in the date range
-                                    }
-                                }
-                            }
-                        }
-                        false // No activity in the date range
-                    })
-                    .collect();
-            }
-
-            // Apply limit if specified
-            if let Some(limit) = options.limit {
-                filtered_sessions.truncate(limit);
-            }
-
-            Ok(filtered_sessions)
+            Self::with_parse_cache(|cache| {
+                self.report_sessions(backups, &log_roots, cache, &options)
+            })
         } else {
             // For non-daily/monthly commands, return empty for now
             // This path could be extended later if needed
-            Ok(Vec::new())
+            Ok((Vec::new(), Vec::new(), SkippedData::default()))
+        }
+    }
+
+    /// Sessions of the backups in `backup_dir`, when given, and of the logs
+    /// under `log_roots`, with the date filter applied, and the logs that
+    /// could not be read
+    fn report_sessions(
+        &self,
+        backup_dir: Option<&Path>,
+        log_roots: &[PathBuf],
+        cache: Option<&mut ParseCache>,
+        options: &ProcessOptions,
+    ) -> Result<(Vec<SessionOutput>, Vec<FailedFile>, SkippedData)> {
+        let (mut sessions, mut skipped) = if let Some(backup_dir) = backup_dir {
+            // Use ParquetSummaryReader to get detailed session data
+            let estimator = options
+                .estimate_missing_usage
+                .then(TokenEstimator::from_config);
+            let reader = ParquetSummaryReader::new(backup_dir.to_path_buf())?
+                .with_estimator(estimator)
+                .with_cache(self.parquet_cache.clone())
+                .with_project_filter(project_filter(options)?)
+                .with_cost_mode(options.cost_mode)
+                .with_cancellation(options.cancel.clone());
+            let (sessions, skipped) = reader.read_detailed_sessions_with_skipped()?;
+
+            if !options.json_output && !options.csv_output {
+                println!("📊 Processed {} sessions from backup data", sessions.len());
+            }
+            (sessions, skipped)
+        } else {
+            (Vec::new(), SkippedData::default())
+        };
+        let (log_sessions, failed_files) = Self::log_root_sessions(log_roots, cache, options)?;
+        sessions.extend(log_sessions);
+
+        let session_count = sessions.len();
+        let filtered_sessions =
+            filter_sessions_by_date(sessions, options.since_date, options.until_date);
+        skipped.add_sessions(
+            SkipReason::DateFilter,
+            (session_count - filtered_sessions.len()) as u64,
+        );
+
+        // `limit` counts report periods, not sessions; the reports apply it
+        // when rendering so every session still contributes to the totals
+        Ok((filtered_sessions, failed_files, skipped))
+    }
+
+    /// Sessions of the instance roots without backups (the Claude config
+    /// directories and `paths.extra_roots`, or every instance before the first
+    /// backup), read from their logs, and the logs that could not be read
+    ///
+    /// An instance whose logs cannot be listed is reported under its root.
+    fn log_root_sessions(
+        claude_paths: &[PathBuf],
+        cache: Option<&mut ParseCache>,
+        options: &ProcessOptions,
+    ) -> Result<(Vec<SessionOutput>, Vec<FailedFile>)> {
+        if claude_paths.is_empty() || options.is_cancelled() {
+            return Ok((Vec::new(), Vec::new()));
+        }
+        let results = Self::process_logs(claude_paths, cache)?;
+        let mut sessions = results.sessions;
+        let mut failed_files = results.failed_files;
+        failed_files.extend(
+            results
+                .failed_instances
+                .into_iter()
+                .map(|(path, error)| FailedFile {
+                    path,
+                    error,
+                    attempts: 1,
+                }),
+        );
+        if let Some(project) = project_filter(options)? {
+            sessions.retain(|session| project.matches(&session.project_path));
+        }
+        if !options.json_output && !options.csv_output {
+            println!(
+                "📊 Processed {} sessions from {} Claude instance{} read from logs",
+                sessions.len(),
+                claude_paths.len(),
+                if claude_paths.len() == 1 { "" } else { "s" }
+            );
+        }
+        Ok((sessions, failed_files))
+    }
+
+    /// Collect individual entries from the JSONL logs, tagged with their provenance
+    ///
+    /// Applies the same instance and date filters as the aggregated reports and
+    /// drops duplicates by dedup hash, keeping the first occurrence.
+    #[allow(dead_code)]
+    pub fn collect_provenance_entries(&self, options: &ProcessOptions) -> Result<Vec<EntryExport>> {
+        let (entries, _, _) = self.collect_entries(options)?;
+        Ok(entries
+            .iter()
+            .map(|entry| entry.to_export_with(options.cost_mode))
+            .collect())
+    }
+
+    /// Collect the deduplicated entries as export records, oldest first,
+    /// along with what the filters left out
+    pub fn export_records(
+        &self,
+        options: &ProcessOptions,
+    ) -> Result<(Vec<ExportRecord>, SkippedData)> {
+        let (entries, _, skipped) = self.collect_entries(options)?;
+        let records = entries
+            .iter()
+            .map(|entry| ExportRecord::from_entry(entry, options.cost_mode))
+            .collect();
+        Ok((records, skipped))
+    }
+
+    /// Group the JSONL entries into 5-hour billing blocks, oldest first,
+    /// along with what the filters left out
+    pub fn session_blocks(
+        &self,
+        options: &ProcessOptions,
+    ) -> Result<(Vec<SessionBlock>, SkippedData)> {
+        let (entries, _, skipped) = self.collect_entries(options)?;
+        let blocks = blocks::build_blocks(&entries, Utc::now(), options.cost_mode);
+        Ok((blocks, skipped))
+    }
+
+    /// Every JSONL entry the filters select, duplicates included, oldest
+    /// first, along with what the filters left out
+    pub fn raw_entries(
+        &self,
+        options: &ProcessOptions,
+    ) -> Result<(Vec<ProcessedEntry>, SkippedData)> {
+        let (entries, _, skipped) = self.scan_entries(options, false)?;
+        Ok((entries, skipped))
+    }
+
+    /// Run a custom [`JsonlProcessor`] over the entries the reports count
+    ///
+    /// The entries are discovered, filtered by `options` (VMs, `--project`
+    /// and dates) and deduplicated as for [`Self::export_records`], then handed
+    /// to the processor oldest first, each with its line number in its file.
+    /// Files that cannot be read are skipped with a warning.
+    #[allow(dead_code)]
+    pub fn process_with<P: JsonlProcessor>(
+        &self,
+        options: &ProcessOptions,
+        processor: P,
+    ) -> Result<P::Output> {
+        let (entries, failed_files, _) = self.collect_entries(options)?;
+        if !failed_files.is_empty() {
+            warn!(
+                files = failed_files.len(),
+                "Skipped unreadable files for the custom processor"
+            );
+        }
+        Self::feed(entries, processor)
+    }
+
+    /// Hand the entries to the processor in order and finish it
+    fn feed<P: JsonlProcessor>(
+        entries: Vec<ProcessedEntry>,
+        mut processor: P,
+    ) -> Result<P::Output> {
+        for entry in entries {
+            processor.process_entry(entry.entry, entry.line_number)?;
+        }
+        processor.finalize()
+    }
+
+    /// Collect deduplicated entries from the JSONL logs, oldest first
+    ///
+    /// Files that still cannot be read after retrying are skipped and returned
+    /// alongside the entries, as is what the filters left out.
+    fn collect_entries(
+        &self,
+        options: &ProcessOptions,
+    ) -> Result<(Vec<ProcessedEntry>, Vec<FailedFile>, SkippedData)> {
+        self.scan_entries(options, true)
+    }
+
+    /// [`collect_entries`](Self::collect_entries), keeping duplicates unless
+    /// `dedup` is set
+    fn scan_entries(
+        &self,
+        options: &ProcessOptions,
+        dedup: bool,
+    ) -> Result<(Vec<ProcessedEntry>, Vec<FailedFile>, SkippedData)> {
+        let parser = FileParser::new();
+        let claude_paths = parser.discover_claude_paths(options.exclude_vms)?;
+        let project = project_filter(options)?;
+        let seen_hashes = DedupSet::from_config();
+        let mut entries: Vec<ProcessedEntry> = Vec::new();
+        let mut failed_files = Vec::new();
+        let mut skipped = SkippedData::default();
+
+        if options.exclude_vms {
+            let excluded: Vec<PathBuf> = parser
+                .discover_claude_paths(false)?
+                .into_iter()
+                .filter(|path| !claude_paths.contains(path))
+                .collect();
+            for (file_path, _session_dir) in parser.find_project_jsonl_files(&excluded, None)? {
+                skipped.add_file(SkipReason::VmExclusion, &file_path);
+            }
+        }
+        let duplicates_before = parser.duplicate_paths();
+
+        'paths: for claude_path in &claude_paths {
+            let instance = parser.instance_name(claude_path);
+            let instance_type = parser.instance_type(claude_path);
+            let files = parser.find_project_jsonl_files(std::slice::from_ref(claude_path), None)?;
+
+            for (file_path, session_dir) in files {
+                if options.is_cancelled() {
+                    warn!("Cancelled, keeping the entries read so far");
+                    break 'paths;
+                }
+                memory::check_memory_limit()?;
+                if project
+                    .as_ref()
+                    .is_some_and(|filter| !filter.selects_session_dir(&session_dir))
+                {
+                    skipped.add_file(SkipReason::ProjectExclusion, &file_path);
+                    continue;
+                }
+                if !parser.should_include_file(
+                    &file_path,
+                    options.since_date.as_ref(),
+                    options.until_date.as_ref(),
+                ) {
+                    skipped.add_file(SkipReason::DateFilter, &file_path);
+                    continue;
+                }
+
+                let file_entries = match pipeline::read_with_retries(&file_path, || {
+                    parser.collect_entries_with_provenance(&file_path, &instance, instance_type)
+                }) {
+                    Ok(file_entries) => file_entries,
+                    Err(failed) => {
+                        failed_files.push(failed);
+                        continue;
+                    }
+                };
+                for entry in file_entries {
+                    if options.since_date.is_some_and(|since| entry.timestamp < since)
+                        || options.until_date.is_some_and(|until| entry.timestamp > until)
+                    {
+                        skipped.add_entries(SkipReason::DateFilter, 1);
+                        continue;
+                    }
+
+                    let hash = entry
+                        .provenance
+                        .as_ref()
+                        .and_then(|p| p.dedup_hash.as_deref());
+                    if dedup && hash.is_some_and(|hash| !seen_hashes.insert(hash)) {
+                        continue;
+                    }
+
+                    entries.push(entry);
+                }
+            }
+        }
+        if dedup {
+            seen_hashes.log_usage("log entries");
+        }
+        skipped.add_files(
+            SkipReason::DuplicatePath,
+            parser.duplicate_paths() - duplicates_before,
+        );
+
+        entries.sort_by_key(|entry| entry.timestamp);
+        Ok((entries, failed_files, skipped))
+    }
+
+    /// Quickly total a single day's usage across all instances
+    ///
+    /// Reads the JSONL logs directly instead of the backups, and only opens
+    /// files whose creation/modification window overlaps the day, taking
+    /// unchanged ones from the parse cache, so a lookup stays fast enough for
+    /// shell prompts and similar integrations. Logs that cannot be read are
+    /// left out and listed in the totals' `failed_files`.
+    #[allow(dead_code)]
+    pub fn cost_for_date(&self, date: NaiveDate) -> Result<DayTotals> {
+        let parser = FileParser::new();
+        let claude_paths = parser.discover_claude_paths(false)?;
+        Self::with_parse_cache(|cache| Self::day_totals(&parser, &claude_paths, date, cache))
+    }
+
+    /// Total the usage of `project` since `since` across all instances
+    ///
+    /// Takes the same fast path as [`cost_for_date`](Self::cost_for_date),
+    /// only opening the project's logs active since then.
+    pub fn usage_since(
+        &self,
+        since: DateTime<Utc>,
+        project: &ProjectDirFilter,
+    ) -> Result<DayTotals> {
+        let parser = FileParser::new();
+        let claude_paths = parser.discover_claude_paths(false)?;
+        let mut totals = Self::with_parse_cache(|cache| {
+            Self::window_totals(
+                &parser,
+                &claude_paths,
+                Some(project),
+                since,
+                Utc::now(),
+                cache,
+                |e| e.timestamp >= since,
+            )
+        })?;
+        totals.date = since.format("%Y-%m-%d").to_string();
+        Ok(totals)
+    }
+
+    /// Run `read` with the parse cache, unless `parse_cache.enabled` is off,
+    /// and save the logs it read into the cache
+    fn with_parse_cache<T>(read: impl FnOnce(Option<&mut ParseCache>) -> Result<T>) -> Result<T> {
+        let config = &get_config().parse_cache;
+        let mut cache = config.enabled.then(|| ParseCache::load(&config.file));
+        let result = read(cache.as_mut());
+        if let Some(cache) = &cache {
+            if let Err(e) = cache.save(&config.file) {
+                warn!(error = %e, "Failed to save parse cache");
+            }
         }
+        result
+    }
+
+    fn day_totals(
+        parser: &FileParser,
+        claude_paths: &[PathBuf],
+        date: NaiveDate,
+        cache: Option<&mut ParseCache>,
+    ) -> Result<DayTotals> {
+        let since = date
+            .and_hms_opt(0, 0, 0)
+            .context("Failed to create start of day")?
+            .and_utc();
+        let until = date
+            .and_hms_opt(23, 59, 59)
+            .context("Failed to create end of day")?
+            .and_utc();
+        let date_str = date.format("%Y-%m-%d").to_string();
+
+        let mut totals =
+            Self::window_totals(parser, claude_paths, None, since, until, cache, |e| {
+                e.date == date_str
+            })?;
+        totals.date = date_str;
+        Ok(totals)
     }
 
-    pub async fn run_command(&mut self, command: &str, options: ProcessOptions) -> Result<()> {
-        let data = self.aggregate_data(command, options.clone()).await?;
+    /// Total the entries `keep` selects from the logs of `project` (or of
+    /// all projects) whose file dates overlap `since..=until`
+    ///
+    /// Logs `cache` holds unchanged are not read again, and the ones read are
+    /// added to it.
+    fn window_totals(
+        parser: &FileParser,
+        claude_paths: &[PathBuf],
+        project: Option<&ProjectDirFilter>,
+        since: DateTime<Utc>,
+        until: DateTime<Utc>,
+        mut cache: Option<&mut ParseCache>,
+        keep: impl Fn(&ProcessedEntry) -> bool,
+    ) -> Result<DayTotals> {
+        let mut totals = DayTotals::default();
+        let seen_hashes = DedupSet::from_config();
+        let mut sessions = HashSet::new();
+
+        for (file_path, _session_dir) in parser.find_project_jsonl_files(claude_paths, project)? {
+            if !parser.should_include_file(&file_path, Some(&since), Some(&until)) {
+                continue;
+            }
+
+            let entries = match pipeline::read_log(parser, &file_path, cache.as_deref_mut())? {
+                Ok(entries) => entries,
+                Err(failed) => {
+                    totals.failed_files.push(failed);
+                    continue;
+                }
+            };
+            for entry in entries.iter().filter(|e| keep(e)) {
+                if let Some(hash) = parser.create_unique_hash(&entry.entry) {
+                    if !seen_hashes.insert(&hash) {
+                        continue;
+                    }
+                }
 
-        if data.is_empty() {
+                let export = entry.to_export();
+                totals.total_cost += MicroDollars::from_dollars(export.cost);
+                totals.input_tokens += export.input_tokens as u64;
+                totals.output_tokens += export.output_tokens as u64;
+                totals.cache_creation_tokens += export.cache_creation_tokens as u64;
+                totals.cache_read_tokens += export.cache_read_tokens as u64;
+                totals.entries += 1;
+                sessions.insert(file_path.clone());
+            }
+        }
+
+        totals.sessions = sessions.len() as u32;
+        Ok(totals)
+    }
+
+    /// Aggregate the JSONL logs under `claude_paths` into per-session usage
+    ///
+    /// Produces the same shape as the backup reader: one session per log file,
+    /// grouped under its project directory. The logs of every instance root
+    /// are parsed by one shared pool of workers while this thread aggregates
+    /// them (see [`pipeline`]); instances and unreadable files that fail are
+    /// logged and skipped, and only going over the memory limit fails. Unless `parse_cache.enabled` is off, logs unchanged since an
+    /// earlier run are taken from the parse cache.
+    ///
+    /// Reports run the same pipeline over the Claude config directories and
+    /// the extra roots, and over every instance until the home has been
+    /// backed up; see [`sessions`](Self::sessions).
+    pub fn sessions_from_logs(claude_paths: &[PathBuf]) -> Result<Vec<SessionOutput>> {
+        Ok(Self::with_parse_cache(|cache| Self::process_logs(claude_paths, cache))?.sessions)
+    }
+
+    /// Run the pipeline over the logs under `claude_paths`, taking the logs
+    /// unchanged since they were cached from `cache`
+    fn process_logs(
+        claude_paths: &[PathBuf],
+        cache: Option<&mut ParseCache>,
+    ) -> Result<pipeline::PipelineResults> {
+        match cache {
+            Some(cache) => pipeline::process_instances_cached(claude_paths, cache),
+            None => pipeline::process_instances(claude_paths),
+        }
+    }
+
+    /// Answer every batch query from a single scan of the usage data
+    ///
+    /// Sessions are loaded once without date filters; each query then applies
+    /// its own range, limit and grouping. Returns a document keyed by query name.
+    pub async fn run_batch(
+        &mut self,
+        queries: &[BatchQuery],
+        options: ProcessOptions,
+    ) -> Result<serde_json::Value> {
+        let options = ProcessOptions {
+            since_date: None,
+            until_date: None,
+            ..options
+        };
+        let data = self
+            .sessions(AnalyzerRequest::with_options(ReportKind::Daily, options))
+            .await?;
+        self.sessions_processed = data.len();
+
+        let today = chrono::Local::now().date_naive();
+        let mut allocations = None;
+        let mut branches = None;
+        let mut day_notes = None;
+        let mut output = serde_json::Map::new();
+
+        for query in queries {
+            let sessions = filter_sessions_by_date(data.clone(), query.since, query.until);
+            let mut manager = ReportDisplayManager::new();
+            manager.set_group_by(query.group_by);
+            if query.group_by == GroupBy::CostCenter {
+                if allocations.is_none() {
+                    let file = get_config().allocations.file.as_ref().context(
+                        "group_by cost_center needs an allocations file; set [allocations] file or CLAUDE_USAGE_ALLOCATIONS_FILE",
+                    )?;
+                    allocations = Some(Allocations::load(file)?);
+                }
+                if let Some(allocations) = &allocations {
+                    manager.set_allocations(allocations.clone());
+                }
+            }
+            if query.group_by == GroupBy::Branch {
+                if branches.is_none() {
+                    branches = Some(SessionBranches::load(&get_config().branches.file)?);
+                }
+                if let Some(branches) = &branches {
+                    manager.set_branches(branches.clone());
+                }
+            }
+
+            let value = match query.report {
+                BatchReport::Daily => {
+                    let notes = day_notes.get_or_insert_with(|| {
+                        // Notes are informational, so an unreadable store only loses them
+                        notes::load_notes(&get_config().notes.file).unwrap_or_else(|e| {
+                            warn!(error = %e, "Failed to load day notes");
+                            Default::default()
+                        })
+                    });
+                    manager.set_notes(notes.clone());
+                    manager.daily_json(&sessions, query.limit, None, today)
+                }
+                BatchReport::Monthly => manager.monthly_json(&sessions, query.limit, None, today),
+                BatchReport::Session => manager.session_json(&sessions, query.limit),
+            };
+            output.insert(query.name.clone(), value);
+        }
+
+        Ok(serde_json::Value::Object(output))
+    }
+
+    pub async fn run_command(&mut self, command: &str, mut options: ProcessOptions) -> Result<()> {
+        let redactor = ProjectRedactor::for_run(options.redact_projects)?;
+        if redactor.is_some() && options.provenance {
+            anyhow::bail!(
+                "--provenance lists log paths, which name projects, so it cannot be combined with project redaction"
+            );
+        }
+        let (mut data, mut failed_files, skipped) = self
+            .aggregate_with_skipped(command, options.clone())
+            .await?;
+        if let Some(redactor) = &redactor {
+            redactor.redact_sessions(&mut data);
+        }
+        self.sessions_processed = data.len();
+        let partial = options.is_cancelled();
+        if partial {
+            reports::print_partial();
+        }
+        for (reason, volume) in skipped.reasons() {
+            info!(reason = reason.label(), skipped = %volume.describe(), "Filter left data out");
+        }
+        let print_skipped = options.verbose && !options.json_output && !options.csv_output;
+
+        // An export is written even when empty, so its manifest covers the period
+        if data.is_empty() && options.output.is_none() {
             warn!("No Claude usage data found across all instances");
             if options.json_output {
                 println!("[]");
-            } else {
+            } else if !options.csv_output {
                 println!("No Claude usage data found across all instances.");
             }
+            if print_skipped {
+                reports::print_skipped(&skipped);
+                reports::print_parse_cache(&parse_cache::stats());
+            }
             return Ok(());
         }
 
-        match command {
-            "daily" => self.display_manager.display_daily(
+        // Before collecting entries, which reads the logs again
+        let sanitized = sanitize::report();
+        let wants_entries = options.provenance && options.json_output;
+        let check_mode = options.cost_mode == CostMode::Auto
+            && (options.check_mode || options.audit_costs.is_some());
+        let collected = if wants_entries || options.audit_costs.is_some() || check_mode {
+            Some(self.collect_entries(&options)?)
+        } else {
+            None
+        };
+        if let (true, Some((collected, _, _))) = (check_mode, &collected) {
+            let tolerance_pct = options.audit_costs.unwrap_or(DEFAULT_TOLERANCE_PCT);
+            let mut advice = cost_audit::advise_cost_mode(collected, tolerance_pct);
+            if let Some(mode) = advice.recommended.filter(|_| options.check_mode) {
+                // Price the report again, every entry on the same basis
+                options.cost_mode = mode;
+                data = self
+                    .aggregate_with_skipped(command, options.clone())
+                    .await?
+                    .0;
+                if let Some(redactor) = &redactor {
+                    redactor.redact_sessions(&mut data);
+                }
+                advice.switched = true;
+            }
+            self.display_manager.set_cost_mode_advice(advice);
+        }
+        let entries: Option<Vec<EntryExport>> =
+            collected
+                .as_ref()
+                .filter(|_| wants_entries)
+                .map(|(entries, _, _)| {
+                    entries
+                        .iter()
+                        .map(|entry| entry.to_export_with(options.cost_mode))
+                        .collect()
+                });
+
+        self.display_manager.set_group_by(options.group_by);
+        self.display_manager.set_show_tokens(options.show_tokens);
+        self.display_manager.set_show_details(options.show_details);
+        self.display_manager.set_order(options.order);
+        self.display_manager.set_breakdown(options.breakdown);
+        self.display_manager
+            .set_output_version(options.output_version);
+        self.display_manager
+            .set_date_filter(options.since_date, options.until_date);
+        if let (Some(tolerance_pct), Some((collected, _, _))) = (options.audit_costs, &collected) {
+            self.display_manager
+                .set_cost_audit(cost_audit::audit_costs(collected, tolerance_pct));
+        }
+        if command == "daily" && options.json_output && options.ranges.is_empty() {
+            if let Some(plan_limits) = PlanLimits::from_config(&get_config().limits) {
+                let (blocks, _) = self.session_blocks(&options)?;
+                self.display_manager.set_limits(plan_limits, blocks);
+            }
+        }
+        if let Some((_, collected_failed, _)) = collected {
+            // Collecting the entries read the logs again, so a log may have failed twice
+            for failed in collected_failed {
+                if !failed_files.iter().any(|known| known.path == failed.path) {
+                    failed_files.push(failed);
+                }
+            }
+        }
+        self.display_manager.set_failed_files(failed_files);
+        self.display_manager.set_skipped(skipped.clone());
+        self.display_manager.set_sanitized(sanitized);
+        self.display_manager.set_parse_cache(parse_cache::stats());
+        self.display_manager.set_partial(partial);
+        if redactor.is_none() {
+            self.display_manager
+                .set_low_confidence_projects(project_names::low_confidence_projects());
+        }
+        if options.group_by == GroupBy::CostCenter {
+            let file = get_config().allocations.file.as_ref().context(
+                "--group-by cost_center needs an allocations file; set [allocations] file or CLAUDE_USAGE_ALLOCATIONS_FILE",
+            )?;
+            self.display_manager.set_allocations(Allocations::load(file)?);
+        }
+        if options.group_by == GroupBy::Branch {
+            let branches = SessionBranches::load(&get_config().branches.file)?;
+            self.display_manager.set_branches(branches);
+        }
+        if command == "daily" {
+            // Notes are informational, so an unreadable store only loses them
+            match notes::load_notes(&get_config().notes.file) {
+                Ok(notes) => self.display_manager.set_notes(notes),
+                Err(e) => warn!(error = %e, "Failed to load day notes"),
+            }
+        }
+
+        let today = chrono::Local::now().date_naive();
+        if let Some(n) = options.approx_top {
+            let report =
+                top_k::approx_top_projects(&data, n, options.since_date, options.until_date);
+            self.display_manager
+                .display_approx_top(&report, options.json_output);
+        } else if let Some(path) = &options.output {
+            let report = self.report_text(command, &data, &options, entries.as_deref(), today)?;
+            let manifest = export_manifest::write_export(path, &report, get_config())?;
+            println!(
+                "✅ Wrote {} report to {} (manifest: {})",
+                command,
+                path.display(),
+                manifest.display()
+            );
+        } else if options.diff_since_last_run {
+            self.print_changes_since_last_run(command, &data, &options, today)?;
+        } else if !options.ranges.is_empty() {
+            let report = match command {
+                "daily" => Command::Daily,
+                "monthly" => Command::Monthly,
+                _ => anyhow::bail!("--range only works with daily and monthly"),
+            };
+            self.display_manager.display_ranges(
+                report,
                 &data,
+                &options.ranges,
+                options.json_output,
+            );
+        } else {
+            self.print_report(command, &data, &options, entries.as_deref(), today)?;
+        }
+        if print_skipped {
+            reports::print_skipped(&skipped);
+            reports::print_parse_cache(&parse_cache::stats());
+        }
+
+        Self::report_parse_quality(options.json_output || options.csv_output);
+        Self::report_sanitized(&sanitized, options.json_output || options.csv_output);
+        Self::report_project_names(options.json_output || options.csv_output || redactor.is_some());
+        if let Some(max_error_rate) = options.max_error_rate {
+            line_reader::check_error_budget(max_error_rate)?;
+        }
+
+        Ok(())
+    }
+
+    /// Print what changed in a daily or monthly report since its last run,
+    /// and save this run's state unless it was interrupted
+    fn print_changes_since_last_run(
+        &self,
+        command: &str,
+        data: &[SessionOutput],
+        options: &ProcessOptions,
+        today: NaiveDate,
+    ) -> Result<()> {
+        let manager = &self.display_manager;
+        let current = match command {
+            "daily" => {
+                let days = manager.process_daily_with_projects(data, options.limit, today);
+                ReportState::from_daily(&days)
+            }
+            "monthly" => {
+                ReportState::from_monthly(&manager.process_monthly_data(data, options.limit))
+            }
+            _ => anyhow::bail!("--diff-since-last-run only works with daily and monthly"),
+        };
+        let path = report_diff::state_file(&get_config().last_run.dir, options);
+        let previous = ReportState::load(&path);
+        let changes = report_diff::diff(previous.as_ref(), &current);
+
+        if options.json_output {
+            let output = serde_json::json!({
+                "command": command,
+                "previousRun": previous.as_ref().map(|state| state.saved_at),
+                "changes": changes,
+            });
+            print!("{}", json_text(&output)?);
+        } else {
+            report_diff::print_changes(&changes, previous.as_ref());
+        }
+        if !options.is_cancelled() {
+            current.save(&path)?;
+        }
+        Ok(())
+    }
+
+    /// JSON or CSV text of a report, for `--output`
+    fn report_text(
+        &self,
+        command: &str,
+        data: &[SessionOutput],
+        options: &ProcessOptions,
+        entries: Option<&[EntryExport]>,
+        today: NaiveDate,
+    ) -> Result<String> {
+        let manager = &self.display_manager;
+        let limit = options.limit;
+        let text = match command {
+            "daily" if options.csv_output => manager.daily_csv(data, limit, today),
+            "monthly" if options.csv_output => manager.monthly_csv(data, limit),
+            "session" if options.csv_output => manager.session_csv(data, limit),
+            "top" if options.csv_output => {
+                manager.top_projects_csv(&Self::rank_projects(data, options), limit)
+            }
+            "daily" => json_text(&manager.daily_json(data, limit, entries, today))?,
+            "monthly" => json_text(&manager.monthly_json(data, limit, entries, today))?,
+            "session" => json_text(&manager.session_json(data, limit))?,
+            "top" => {
+                json_text(&manager.top_projects_json(&Self::rank_projects(data, options), limit))?
+            }
+            "forecast" => json_text(&manager.forecast_json(&Self::forecast(data, options, today)))?,
+            _ => anyhow::bail!("Unknown command: {}", command),
+        };
+        Ok(text)
+    }
+
+    /// Print a report to stdout in the requested format
+    fn print_report(
+        &self,
+        command: &str,
+        data: &[SessionOutput],
+        options: &ProcessOptions,
+        entries: Option<&[EntryExport]>,
+        today: NaiveDate,
+    ) -> Result<()> {
+        match command {
+            "daily" if options.csv_output => {
+                print!(
+                    "{}",
+                    self.display_manager.daily_csv(data, options.limit, today)
+                )
+            }
+            "monthly" if options.csv_output => {
+                print!("{}", self.display_manager.monthly_csv(data, options.limit))
+            }
+            "session" if options.csv_output => {
+                print!("{}", self.display_manager.session_csv(data, options.limit))
+            }
+            "daily" => self.display_manager.display_daily_with_entries(
+                data,
                 options.limit,
                 options.json_output,
+                entries,
             ),
-            "monthly" => self.display_manager.display_monthly(
-                &data,
+            "monthly" => self.display_manager.display_monthly_with_entries(
+                data,
                 options.limit,
                 options.json_output,
+                entries,
             ),
+            "session" => {
+                self.display_manager
+                    .display_sessions(data, options.limit, options.json_output)
+            }
+            "top" if options.csv_output => print!(
+                "{}",
+                self.display_manager
+                    .top_projects_csv(&Self::rank_projects(data, options), options.limit)
+            ),
+            "top" => self.display_manager.display_top_projects(
+                &Self::rank_projects(data, options),
+                options.limit,
+                options.json_output,
+            ),
+            "forecast" => self
+                .display_manager
+                .display_forecast(&Self::forecast(data, options, today), options.json_output),
             _ => {
                 anyhow::bail!("Unknown command: {}", command);
             }
         }
-
         Ok(())
     }
+
+    /// Projects of `data` ranked as `top` reports them
+    fn rank_projects(data: &[SessionOutput], options: &ProcessOptions) -> Vec<ProjectTotals> {
+        projects::rank_projects(
+            data,
+            options.since_date,
+            options.until_date,
+            options.project_sort,
+        )
+    }
+
+    /// Burn rate and month-end projection for the month of `today`
+    fn forecast(data: &[SessionOutput], options: &ProcessOptions, today: NaiveDate) -> Forecast {
+        forecast::forecast(
+            data,
+            today,
+            options.forecast_days,
+            get_config().budget.monthly_usd,
+        )
+    }
+
+    /// Report log lines that were skipped instead of parsed, or backed up
+    /// without a readable timestamp
+    fn report_parse_quality(quiet: bool) {
+        let quality = line_reader::quality_report();
+        if quality.failed_lines() == 0 {
+            return;
+        }
+
+        warn!(
+            lines_read = quality.lines_read,
+            oversized_lines = quality.oversized_lines,
+            invalid_lines = quality.invalid_lines,
+            parse_errors = quality.parse_errors,
+            undated_lines = quality.undated_lines,
+            "Skipped unreadable JSONL lines"
+        );
+        if !quiet {
+            println!(
+                "\n⚠️  Parse quality: {} of {} lines failed ({} oversized, {} invalid UTF-8, {} invalid JSON, {} backed up without a readable timestamp)",
+                quality.failed_lines(),
+                quality.lines_read,
+                quality.oversized_lines,
+                quality.invalid_lines,
+                quality.parse_errors,
+                quality.undated_lines
+            );
+        }
+    }
+
+    /// Report entries whose token counts the `[quality]` rules changed or left out
+    fn report_sanitized(sanitized: &SanitizedEntries, quiet: bool) {
+        if sanitized.is_empty() {
+            return;
+        }
+
+        warn!(
+            negative_tokens = sanitized.negative_tokens,
+            excessive_tokens = sanitized.excessive_tokens,
+            empty_usage = sanitized.empty_usage,
+            skipped = sanitized.skipped,
+            "Sanitized usage entries with corrupted token counts"
+        );
+        if !quiet {
+            println!(
+                "\n⚠️  Sanitized {} entries ({} negative, {} over the token limit, {} empty usage); {} left out",
+                sanitized.total(),
+                sanitized.negative_tokens,
+                sanitized.excessive_tokens,
+                sanitized.empty_usage,
+                sanitized.skipped
+            );
+        }
+    }
+
+    /// Report projects whose extracted name was not trusted
+    fn report_project_names(quiet: bool) {
+        let projects = project_names::low_confidence_projects();
+        if projects.is_empty() {
+            return;
+        }
+
+        warn!(
+            projects = projects.len(),
+            "Replaced low-confidence project names"
+        );
+        if !quiet {
+            println!(
+                "\n⚠️  Project names: {} low-confidence extraction(s)",
+                projects.len()
+            );
+            for project in &projects {
+                println!(
+                    "   {} ({} \"{}\")",
+                    project.project,
+                    project.reason.label(),
+                    project.extracted
+                );
+            }
+        }
+    }
+}
+
+/// Keep sessions with usage on at least one day within `since..=until`
+///
+/// Filters on the sessions' daily usage rather than their last activity, so a
+/// session that started in range but continued past it is still included.
+fn filter_sessions_by_date(
+    sessions: Vec<SessionOutput>,
+    since: Option<DateTime<Utc>>,
+    until: Option<DateTime<Utc>>,
+) -> Vec<SessionOutput> {
+    if since.is_none() && until.is_none() {
+        return sessions;
+    }
+
+    sessions
+        .into_iter()
+        .filter(|session| {
+            session.daily_usage.keys().any(|date_str| {
+                let Ok(date) = NaiveDate::parse_from_str(date_str, "%Y-%m-%d") else {
+                    return false;
+                };
+                let Some(session_dt) = date.and_hms_opt(0, 0, 0).map(|dt| dt.and_utc()) else {
+                    return false;
+                };
+                since.map_or(true, |since| session_dt >= since)
+                    && until.map_or(true, |until| session_dt <= until)
+            })
+        })
+        .collect()
+}
+
+/// The `--project` filter of `options`, if any
+fn project_filter(options: &ProcessOptions) -> Result<Option<ProjectDirFilter>> {
+    options
+        .project
+        .as_deref()
+        .map(ProjectDirFilter::new)
+        .transpose()
+}
+
+/// Pretty-printed JSON document with a trailing newline
+fn json_text(value: &serde_json::Value) -> Result<String> {
+    Ok(serde_json::to_string_pretty(value)? + "\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_day_totals_counts_only_requested_day() {
+        let temp = TempDir::new().unwrap();
+        let session_dir = temp.path().join("projects").join("-home-user-project");
+        std::fs::create_dir_all(&session_dir).unwrap();
+
+        let today = chrono::Utc::now().date_naive();
+        let yesterday = today - chrono::Duration::days(1);
+        let line = |date: NaiveDate, id: &str, cost: f64| {
+            format!(
+                r#"{{"timestamp":"{}T12:00:00Z","message":{{"id":"{}","model":"claude-3-5-sonnet-20241022","usage":{{"input_tokens":100,"output_tokens":50}}}},"requestId":"req_{}","costUSD":{}}}"#,
+                date, id, id, cost
+            )
+        };
+
+        let mut file = std::fs::File::create(session_dir.join("session.jsonl")).unwrap();
+        writeln!(file, "{}", line(today, "a", 1.0)).unwrap();
+        writeln!(file, "{}", line(today, "b", 0.5)).unwrap();
+        // Duplicate of "a" is counted once
+        writeln!(file, "{}", line(today, "a", 1.0)).unwrap();
+        writeln!(file, "{}", line(yesterday, "c", 9.0)).unwrap();
+        drop(file);
+
+        let parser = FileParser::new();
+        let totals =
+            ClaudeUsageAnalyzer::day_totals(&parser, &[temp.path().to_path_buf()], today, None)
+                .unwrap();
+
+        assert_eq!(totals.date, today.format("%Y-%m-%d").to_string());
+        assert_eq!(totals.entries, 2);
+        assert_eq!(totals.sessions, 1);
+        assert_eq!(totals.total_cost.to_dollars(), 1.5);
+        assert_eq!(totals.input_tokens, 200);
+        assert_eq!(totals.output_tokens, 100);
+    }
+
+    #[test]
+    fn test_day_totals_skips_unreadable_logs_and_reuses_cache() {
+        let temp = TempDir::new().unwrap();
+        let session_dir = temp.path().join("projects").join("-home-user-project");
+        std::fs::create_dir_all(&session_dir).unwrap();
+        // A session log that is a directory cannot be opened
+        std::fs::create_dir_all(session_dir.join("broken.jsonl")).unwrap();
+
+        let today = chrono::Utc::now().date_naive();
+        let mut file = std::fs::File::create(session_dir.join("session.jsonl")).unwrap();
+        writeln!(
+            file,
+            r#"{{"timestamp":"{}T12:00:00Z","message":{{"id":"a","model":"claude-3-5-sonnet-20241022","usage":{{"input_tokens":100,"output_tokens":50}}}},"requestId":"req_a","costUSD":1.0}}"#,
+            today
+        )
+        .unwrap();
+        drop(file);
+
+        let parser = FileParser::new();
+        let roots = [temp.path().to_path_buf()];
+        let mut cache = ParseCache::default();
+        let first =
+            ClaudeUsageAnalyzer::day_totals(&parser, &roots, today, Some(&mut cache)).unwrap();
+        assert_eq!(first.total_cost.to_dollars(), 1.0);
+        assert_eq!(first.failed_files.len(), 1);
+        assert!(first.failed_files[0].path.ends_with("broken.jsonl"));
+        assert_eq!(cache.len(), 1);
+
+        let cached_before = parse_cache::stats().cached;
+        let second =
+            ClaudeUsageAnalyzer::day_totals(&parser, &roots, today, Some(&mut cache)).unwrap();
+        assert_eq!(second.total_cost, first.total_cost);
+        assert!(parse_cache::stats().cached > cached_before);
+    }
+
+    #[test]
+    fn test_report_reads_config_dir_install_without_backups() {
+        let temp = TempDir::new().unwrap();
+        let config_dir = temp.path().join(".config").join("claude");
+        let session_dir = config_dir.join("projects").join("-home-user-project");
+        std::fs::create_dir_all(&session_dir).unwrap();
+        let mut file = std::fs::File::create(session_dir.join("session.jsonl")).unwrap();
+        writeln!(
+            file,
+            r#"{{"timestamp":"2025-06-01T12:00:00Z","message":{{"id":"a","model":"claude-3-5-sonnet-20241022","usage":{{"input_tokens":100,"output_tokens":50}}}},"requestId":"req_a","costUSD":1.0}}"#
+        )
+        .unwrap();
+        drop(file);
+
+        let options = ProcessOptions::new("daily").with_json_output(true);
+        let (sessions, _, _) = ClaudeUsageAnalyzer::new()
+            .report_sessions(None, &[config_dir], None, &options)
+            .unwrap();
+        let today = NaiveDate::from_ymd_opt(2025, 6, 2).unwrap();
+        let daily = ReportDisplayManager::new().process_daily_with_projects(&sessions, None, today);
+
+        let day = daily.iter().find(|day| day.date == "2025-06-01").unwrap();
+        assert_eq!(day.total_cost.to_dollars(), 1.0);
+        assert_eq!(day.total_sessions, 1);
+    }
+
+    #[test]
+    fn test_report_lists_unreadable_extra_root_logs() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path().join("extra");
+        let session_dir = root.join("projects").join("-home-user-project");
+        std::fs::create_dir_all(&session_dir).unwrap();
+        // A session log that is a directory cannot be opened
+        std::fs::create_dir_all(session_dir.join("broken.jsonl")).unwrap();
+        let mut file = std::fs::File::create(session_dir.join("session.jsonl")).unwrap();
+        writeln!(
+            file,
+            r#"{{"timestamp":"2025-06-01T12:00:00Z","message":{{"id":"a","model":"claude-3-5-sonnet-20241022","usage":{{"input_tokens":100,"output_tokens":50}}}},"requestId":"req_a","costUSD":1.0}}"#
+        )
+        .unwrap();
+        drop(file);
+
+        let options = ProcessOptions::new("daily").with_json_output(true);
+        let (sessions, failed_files, _) = ClaudeUsageAnalyzer::new()
+            .report_sessions(None, &[root], None, &options)
+            .unwrap();
+        assert_eq!(sessions.len(), 1);
+        assert_eq!(failed_files.len(), 1);
+        assert!(failed_files[0].path.ends_with("broken.jsonl"));
+
+        let mut display = ReportDisplayManager::new();
+        display.set_failed_files(failed_files);
+        let today = NaiveDate::from_ymd_opt(2025, 6, 2).unwrap();
+        let json = display.daily_json(&sessions, None, None, today);
+        let failed = &json["metadata"]["failedFiles"][0];
+        assert!(failed["path"].as_str().unwrap().ends_with("broken.jsonl"));
+        assert_eq!(failed["attempts"], get_config().processing.max_retries + 1);
+    }
+
+    #[test]
+    fn test_custom_processor_fed_entries_in_order() {
+        struct Requests(Vec<(String, usize)>);
+
+        impl JsonlProcessor for Requests {
+            type Output = Vec<(String, usize)>;
+
+            fn process_entry(&mut self, entry: UsageEntry, line_number: usize) -> Result<()> {
+                self.0.push((entry.request_id, line_number));
+                Ok(())
+            }
+
+            fn finalize(self) -> Result<Self::Output> {
+                Ok(self.0)
+            }
+        }
+
+        let temp = TempDir::new().unwrap();
+        let path = temp.path().join("session.jsonl");
+        let mut file = std::fs::File::create(&path).unwrap();
+        for (minute, id) in [(1, "a"), (0, "b")] {
+            writeln!(
+                file,
+                r#"{{"timestamp":"2025-06-01T12:0{}:00Z","message":{{"id":"{}","model":"claude-3-5-sonnet-20241022","usage":{{"input_tokens":100,"output_tokens":50}}}},"requestId":"req_{}"}}"#,
+                minute, id, id
+            )
+            .unwrap();
+        }
+        drop(file);
+
+        let mut entries = FileParser::new()
+            .collect_entries_with_provenance(&path, "main", InstanceType::Host)
+            .unwrap();
+        entries.sort_by_key(|entry| entry.timestamp);
+        let seen = ClaudeUsageAnalyzer::feed(entries, Requests(Vec::new())).unwrap();
+        assert_eq!(
+            seen,
+            vec![("req_b".to_string(), 2), ("req_a".to_string(), 1)]
+        );
+    }
 }
@@ -0,0 +1,565 @@
+//! Rolling archive of old session logs
+//!
+//! `claude-usage archive compact` rewrites JSONL logs that have not been
+//! touched for N months into per-month parquet archives. Entries are
+//! deduplicated by hash while compacting, grouped by the month of their
+//! timestamp, staged under the archive directory in the same
+//! `projects/<project>/<session>.jsonl` layout Claude uses, and converted by
+//! `claude-keeper backup` into `<archive.dir>/<YYYY-MM>/<part>/`.
+//!
+//! Each verified part is recorded in `<archive.dir>/index.json`; the parquet
+//! reader includes every indexed part, so daily and monthly reports keep
+//! seeing compacted usage. Original logs are only deleted on request, and only
+//! once every month they contributed to was read back with the expected
+//! number of entries. Each part also gets a `manifest.json` of SHA-256 hashes
+//! that `claude-usage verify-export` can check later.
+
+use crate::config::get_config;
+use crate::dedup_set::DedupSet;
+use crate::error::KeeperNotFound;
+use crate::export_manifest::{ExportManifest, PART_MANIFEST_FILE};
+use crate::parquet::reader::read_parquet_with_library;
+use crate::parser::FileParser;
+use anyhow::{Context, Result};
+use chrono::{DateTime, Datelike, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use tracing::{info, warn};
+
+/// Name of the index file inside the archive directory
+pub const INDEX_FILE: &str = "index.json";
+
+/// Staging area for logs waiting to be converted, inside the archive directory
+const STAGING_DIR: &str = ".staging";
+
+/// Compacted parts per month, as recorded in `index.json`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ArchiveIndex {
+    /// Parts keyed by `YYYY-MM`
+    pub months: BTreeMap<String, Vec<ArchivePart>>,
+}
+
+/// One compaction run's output for a month
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ArchivePart {
+    /// Directory holding the part's parquet files, relative to the archive directory
+    pub dir: PathBuf,
+    /// Entries written to the part
+    pub entries: usize,
+    /// Logs the entries came from
+    pub source_files: Vec<PathBuf>,
+    /// When the part was written (RFC 3339)
+    pub compacted_at: String,
+}
+
+impl ArchiveIndex {
+    /// Load the index of `archive_dir`; a missing index yields an empty one
+    pub fn load(archive_dir: &Path) -> Result<Self> {
+        let path = archive_dir.join(INDEX_FILE);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read archive index: {}", path.display()))?;
+        serde_json::from_str(&content)
+            .with_context(|| format!("Invalid archive index: {}", path.display()))
+    }
+
+    /// Save the index through a temporary file so a failed write cannot truncate it
+    pub fn save(&self, archive_dir: &Path) -> Result<()> {
+        fs::create_dir_all(archive_dir)
+            .with_context(|| format!("Failed to create {}", archive_dir.display()))?;
+        let path = archive_dir.join(INDEX_FILE);
+        let temp_path = path.with_extension("json.tmp");
+        fs::write(&temp_path, serde_json::to_string_pretty(self)? + "\n")
+            .with_context(|| format!("Failed to write archive index: {}", temp_path.display()))?;
+        fs::rename(&temp_path, &path)
+            .with_context(|| format!("Failed to save archive index: {}", path.display()))?;
+        Ok(())
+    }
+
+    /// Parquet files of every indexed part, in month order
+    pub fn parquet_files(&self, archive_dir: &Path) -> Vec<PathBuf> {
+        self.months
+            .values()
+            .flatten()
+            .flat_map(|part| parquet_files_in(&archive_dir.join(&part.dir)))
+            .collect()
+    }
+}
+
+/// Parquet files directly inside `dir`, sorted by name
+fn parquet_files_in(dir: &Path) -> Vec<PathBuf> {
+    let mut files: Vec<PathBuf> = fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.is_file()
+                && path
+                    .extension()
+                    .and_then(|ext| ext.to_str())
+                    .is_some_and(|ext| ext.eq_ignore_ascii_case("parquet"))
+        })
+        .collect();
+    files.sort();
+    files
+}
+
+/// First day of the month `months` before the month of `today`
+///
+/// Logs last modified before this date are old enough to compact.
+pub fn archive_cutoff(today: NaiveDate, months: u32) -> NaiveDate {
+    let month_index = today.year() * 12 + today.month0() as i32 - months as i32;
+    NaiveDate::from_ymd_opt(
+        month_index.div_euclid(12),
+        month_index.rem_euclid(12) as u32 + 1,
+        1,
+    )
+    .unwrap_or(today)
+}
+
+/// Deduplicated lines of one month, grouped into session logs
+#[derive(Debug, Default)]
+pub struct MonthBatch {
+    /// Lines per log, keyed by path relative to the staging root
+    pub logs: BTreeMap<PathBuf, Vec<String>>,
+    /// Lines in the batch
+    pub entries: usize,
+}
+
+/// A log selected for compaction and the months its lines went to
+#[derive(Debug)]
+pub struct PlannedFile {
+    pub path: PathBuf,
+    pub months: BTreeSet<String>,
+    /// Lines that could not be archived; such a log is never deleted
+    pub invalid_lines: usize,
+}
+
+/// What a compaction run will write
+#[derive(Debug, Default)]
+pub struct CompactionPlan {
+    /// Batches keyed by `YYYY-MM`
+    pub months: BTreeMap<String, MonthBatch>,
+    pub files: Vec<PlannedFile>,
+    /// Entries dropped because an earlier line had the same dedup hash
+    pub duplicates: usize,
+    /// Lines dropped because they are not valid JSON
+    pub invalid_lines: usize,
+}
+
+/// Group the lines of every log last modified before `cutoff` by month
+///
+/// `files` are `(log, session directory)` pairs as returned by file
+/// discovery. Lines without a timestamp go to the month the log was last
+/// modified in.
+pub fn plan_compaction(files: &[(PathBuf, PathBuf)], cutoff: NaiveDate) -> Result<CompactionPlan> {
+    let parser = FileParser::new();
+    let cutoff = cutoff.and_hms_opt(0, 0, 0).unwrap_or_default().and_utc();
+    let mut plan = CompactionPlan::default();
+    let seen_hashes = DedupSet::from_config();
+
+    let mut files: Vec<_> = files.iter().collect();
+    files.sort();
+
+    for (file_path, session_dir) in files {
+        let modified: DateTime<Utc> = fs::metadata(file_path)
+            .and_then(|metadata| metadata.modified())
+            .with_context(|| format!("Failed to read {}", file_path.display()))?
+            .into();
+        if modified >= cutoff {
+            continue;
+        }
+
+        let content = fs::read_to_string(file_path)
+            .with_context(|| format!("Failed to read {}", file_path.display()))?;
+        let log_path = Path::new("projects")
+            .join(session_dir.file_name().unwrap_or_default())
+            .join(file_path.file_name().unwrap_or_default());
+        let fallback_month = modified.format("%Y-%m").to_string();
+        let mut months = BTreeSet::new();
+        let mut invalid_lines = 0;
+
+        for line in content.lines().map(str::trim).filter(|l| !l.is_empty()) {
+            let Ok(value) = serde_json::from_str::<serde_json::Value>(line) else {
+                invalid_lines += 1;
+                continue;
+            };
+            let hash = parser
+                .parse_line(line)
+                .and_then(|entry| parser.create_unique_hash(&entry));
+            if hash.is_some_and(|hash| !seen_hashes.insert(&hash)) {
+                plan.duplicates += 1;
+                continue;
+            }
+
+            let month = value
+                .get("timestamp")
+                .and_then(|v| v.as_str())
+                .and_then(|ts| ts.get(..7))
+                .map(str::to_string)
+                .unwrap_or_else(|| fallback_month.clone());
+            let batch = plan.months.entry(month.clone()).or_default();
+            batch
+                .logs
+                .entry(log_path.clone())
+                .or_default()
+                .push(line.to_string());
+            batch.entries += 1;
+            months.insert(month);
+        }
+
+        plan.invalid_lines += invalid_lines;
+        plan.files.push(PlannedFile {
+            path: file_path.clone(),
+            months,
+            invalid_lines,
+        });
+    }
+    seen_hashes.log_usage("compaction entries");
+
+    Ok(plan)
+}
+
+/// Write a batch as session logs under `staging_dir`
+pub fn write_staging(batch: &MonthBatch, staging_dir: &Path) -> Result<()> {
+    for (log_path, lines) in &batch.logs {
+        let path = staging_dir.join(log_path);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create {}", parent.display()))?;
+        }
+        fs::write(&path, lines.join("\n") + "\n")
+            .with_context(|| format!("Failed to write {}", path.display()))?;
+    }
+    Ok(())
+}
+
+/// Options for `archive compact`
+#[derive(Debug, Clone)]
+pub struct CompactOptions {
+    /// Compact logs last modified before the start of the month this many months ago
+    pub older_than_months: u32,
+    /// Delete original logs once their months are verified (logs with invalid
+    /// lines are kept)
+    pub delete_originals: bool,
+    /// Only report what would be compacted
+    pub dry_run: bool,
+}
+
+/// Outcome of compacting one month
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MonthResult {
+    pub month: String,
+    pub entries: usize,
+    pub logs: usize,
+    /// Whether the written part read back with the expected entries
+    pub verified: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Outcome of a compaction run
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CompactSummary {
+    pub archive_dir: PathBuf,
+    pub cutoff: NaiveDate,
+    pub dry_run: bool,
+    pub files: usize,
+    pub duplicates: usize,
+    pub invalid_lines: usize,
+    pub months: Vec<MonthResult>,
+    pub deleted_files: Vec<PathBuf>,
+}
+
+/// Compact old logs of every discovered Claude instance into the archive
+pub fn compact(options: &CompactOptions) -> Result<CompactSummary> {
+    let archive_dir = get_config().archive.dir.clone();
+    let cutoff = archive_cutoff(Utc::now().date_naive(), options.older_than_months);
+
+    let parser = FileParser::new();
+    let claude_paths = parser.discover_claude_paths(false)?;
+    let files = parser.find_jsonl_files(&claude_paths)?;
+    let plan = plan_compaction(&files, cutoff)?;
+
+    let mut summary = CompactSummary {
+        archive_dir: archive_dir.clone(),
+        cutoff,
+        dry_run: options.dry_run,
+        files: plan.files.len(),
+        duplicates: plan.duplicates,
+        invalid_lines: plan.invalid_lines,
+        months: Vec::new(),
+        deleted_files: Vec::new(),
+    };
+
+    if options.dry_run {
+        summary.months = plan
+            .months
+            .iter()
+            .map(|(month, batch)| MonthResult {
+                month: month.clone(),
+                entries: batch.entries,
+                logs: batch.logs.len(),
+                verified: false,
+                error: None,
+            })
+            .collect();
+        return Ok(summary);
+    }
+
+    let mut index = ArchiveIndex::load(&archive_dir)?;
+    let part_name = Utc::now().format("%Y%m%dT%H%M%SZ").to_string();
+    let mut verified_months = HashSet::new();
+
+    for (month, batch) in &plan.months {
+        let part_dir = Path::new(month).join(&part_name);
+        let result = compact_month(&archive_dir, month, batch, &part_dir);
+        let mut month_result = MonthResult {
+            month: month.clone(),
+            entries: batch.entries,
+            logs: batch.logs.len(),
+            verified: result.is_ok(),
+            error: None,
+        };
+
+        match result {
+            Ok(()) => {
+                let source_files = plan
+                    .files
+                    .iter()
+                    .filter(|file| file.months.contains(month))
+                    .map(|file| file.path.clone())
+                    .collect();
+                index
+                    .months
+                    .entry(month.clone())
+                    .or_default()
+                    .push(ArchivePart {
+                        dir: part_dir,
+                        entries: batch.entries,
+                        source_files,
+                        compacted_at: Utc::now().to_rfc3339(),
+                    });
+                // Record each part as soon as it exists so an interrupted run
+                // never leaves unindexed archives behind
+                index.save(&archive_dir)?;
+                verified_months.insert(month.clone());
+            }
+            Err(e) => {
+                warn!(month = %month, error = %e, "Failed to compact month");
+                let _ = fs::remove_dir_all(archive_dir.join(&part_dir));
+                // Only succeeds when no earlier part exists for the month
+                let _ = fs::remove_dir(archive_dir.join(month));
+                month_result.error = Some(format!("{:#}", e));
+            }
+        }
+        summary.months.push(month_result);
+    }
+    let _ = fs::remove_dir_all(archive_dir.join(STAGING_DIR));
+
+    if options.delete_originals {
+        for file in &plan.files {
+            let verified = file
+                .months
+                .iter()
+                .all(|month| verified_months.contains(month));
+            if !verified || file.invalid_lines > 0 {
+                continue;
+            }
+            match fs::remove_file(&file.path) {
+                Ok(()) => summary.deleted_files.push(file.path.clone()),
+                Err(e) => warn!(
+                    file = %file.path.display(),
+                    error = %e,
+                    "Failed to delete compacted log"
+                ),
+            }
+        }
+    }
+
+    Ok(summary)
+}
+
+/// Stage, convert and verify one month's batch into `archive_dir/part_dir`
+fn compact_month(
+    archive_dir: &Path,
+    month: &str,
+    batch: &MonthBatch,
+    part_dir: &Path,
+) -> Result<()> {
+    let staging_dir = archive_dir.join(STAGING_DIR).join(month);
+    let out_dir = archive_dir.join(part_dir);
+    let _ = fs::remove_dir_all(&staging_dir);
+    write_staging(batch, &staging_dir)?;
+    fs::create_dir_all(&out_dir)
+        .with_context(|| format!("Failed to create {}", out_dir.display()))?;
+
+    let keeper = &get_config().live.claude_keeper_path;
+    info!(month = %month, entries = batch.entries, "Compacting month with claude-keeper");
+    let output = Command::new(keeper)
+        .arg("backup")
+        .arg(&staging_dir)
+        .arg("--out")
+        .arg(&out_dir)
+        .arg("--quiet")
+        .output()
+        .map_err(|e| {
+            KeeperNotFound::check(keeper, e, format!("Failed to execute {} backup", keeper))
+        })?;
+    if !output.status.success() {
+        return Err(anyhow::anyhow!(
+            "claude-keeper backup failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+
+    let written: usize = parquet_files_in(&out_dir)
+        .iter()
+        .map(|file| read_parquet_with_library(file).map(|objects| objects.len()))
+        .sum::<Result<usize>>()?;
+    if written != batch.entries {
+        return Err(anyhow::anyhow!(
+            "Verification failed: archive holds {} entries, expected {}",
+            written,
+            batch.entries
+        ));
+    }
+
+    let manifest = ExportManifest::new(&out_dir, &parquet_files_in(&out_dir), get_config())?;
+    manifest.save(&out_dir.join(PART_MANIFEST_FILE))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn line(id: &str, timestamp: &str) -> String {
+        format!(
+            r#"{{"timestamp":"{}","message":{{"id":"{}","model":"claude-3-5-sonnet-20241022","usage":{{"input_tokens":10,"output_tokens":5}}}},"requestId":"req_{}"}}"#,
+            timestamp, id, id
+        )
+    }
+
+    #[test]
+    fn test_archive_cutoff() {
+        let today = NaiveDate::from_ymd_opt(2025, 3, 17).unwrap();
+        assert_eq!(
+            archive_cutoff(today, 1),
+            NaiveDate::from_ymd_opt(2025, 2, 1).unwrap()
+        );
+        assert_eq!(
+            archive_cutoff(today, 3),
+            NaiveDate::from_ymd_opt(2024, 12, 1).unwrap()
+        );
+        assert_eq!(
+            archive_cutoff(today, 15),
+            NaiveDate::from_ymd_opt(2023, 12, 1).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_plan_groups_by_month_and_drops_duplicates() {
+        let temp = TempDir::new().unwrap();
+        let session_dir = temp.path().join("projects").join("proj");
+        fs::create_dir_all(&session_dir).unwrap();
+        let first = session_dir.join("a.jsonl");
+        let second = session_dir.join("b.jsonl");
+        fs::write(
+            &first,
+            [
+                line("m1", "2025-01-31T23:00:00Z"),
+                line("m2", "2025-02-01T01:00:00Z"),
+                "not json".to_string(),
+            ]
+            .join("\n"),
+        )
+        .unwrap();
+        fs::write(
+            &second,
+            [
+                line("m1", "2025-01-31T23:00:00Z"),
+                line("m3", "2025-02-03T10:00:00Z"),
+            ]
+            .join("\n"),
+        )
+        .unwrap();
+
+        let files = vec![
+            (second.clone(), session_dir.clone()),
+            (first.clone(), session_dir.clone()),
+        ];
+        let cutoff = NaiveDate::from_ymd_opt(9999, 1, 1).unwrap();
+        let plan = plan_compaction(&files, cutoff).unwrap();
+
+        assert_eq!(plan.files.len(), 2);
+        assert_eq!(plan.duplicates, 1);
+        assert_eq!(plan.invalid_lines, 1);
+        assert_eq!(plan.months["2025-01"].entries, 1);
+        assert_eq!(plan.months["2025-02"].entries, 2);
+        assert_eq!(plan.months["2025-02"].logs.len(), 2);
+        // The duplicate in b.jsonl came after a.jsonl's copy, so b only feeds February
+        assert_eq!(
+            plan.files[1].months,
+            BTreeSet::from(["2025-02".to_string()])
+        );
+        assert_eq!(plan.files[0].invalid_lines, 1);
+
+        let staging = temp.path().join("staging");
+        write_staging(&plan.months["2025-01"], &staging).unwrap();
+        assert!(staging
+            .join("projects")
+            .join("proj")
+            .join("a.jsonl")
+            .exists());
+
+        // Logs modified after the cutoff are left alone
+        let past = NaiveDate::from_ymd_opt(2000, 1, 1).unwrap();
+        assert!(plan_compaction(&files, past).unwrap().files.is_empty());
+    }
+
+    #[test]
+    fn test_index_round_trip_lists_part_files() {
+        let temp = TempDir::new().unwrap();
+        let part_dir = Path::new("2025-01").join("20250401T000000Z");
+        fs::create_dir_all(temp.path().join(&part_dir)).unwrap();
+        fs::write(
+            temp.path().join(&part_dir).join("conversations.parquet"),
+            b"",
+        )
+        .unwrap();
+        fs::write(temp.path().join(&part_dir).join("notes.txt"), b"").unwrap();
+
+        assert!(ArchiveIndex::load(temp.path()).unwrap().months.is_empty());
+
+        let mut index = ArchiveIndex::default();
+        index
+            .months
+            .entry("2025-01".to_string())
+            .or_default()
+            .push(ArchivePart {
+                dir: part_dir.clone(),
+                entries: 3,
+                source_files: vec![PathBuf::from("/logs/a.jsonl")],
+                compacted_at: "2025-04-01T00:00:00+00:00".to_string(),
+            });
+        index.save(temp.path()).unwrap();
+
+        let loaded = ArchiveIndex::load(temp.path()).unwrap();
+        assert_eq!(loaded.months["2025-01"][0].entries, 3);
+        assert_eq!(
+            loaded.parquet_files(temp.path()),
+            vec![temp.path().join(&part_dir).join("conversations.parquet")]
+        );
+    }
+}
@@ -0,0 +1,242 @@
+//! Batch Queries
+//!
+//! `claude-usage batch` answers several report queries from a single discovery
+//! and parse pass. Queries come from a JSON file or repeated `--query` flags;
+//! each names a report (`daily`, `monthly` or `session`) with its own date
+//! range, limit and grouping, and its answer is keyed by the query name.
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, NaiveDate, Utc};
+use clap::ValueEnum;
+use serde::Deserialize;
+use std::collections::HashSet;
+use std::path::Path;
+
+use crate::dedup::GroupBy;
+
+/// Report a batch query renders
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BatchReport {
+    Daily,
+    Monthly,
+    Session,
+}
+
+/// One query of a batch, with dates resolved to the report's time bounds
+#[derive(Debug, Clone, PartialEq)]
+pub struct BatchQuery {
+    pub name: String,
+    pub report: BatchReport,
+    pub since: Option<DateTime<Utc>>,
+    pub until: Option<DateTime<Utc>>,
+    pub limit: Option<usize>,
+    pub group_by: GroupBy,
+}
+
+/// A query as written in a batch file or `--query` flag
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+struct QuerySpec {
+    name: String,
+    report: String,
+    since: Option<String>,
+    until: Option<String>,
+    limit: Option<usize>,
+    group_by: Option<String>,
+}
+
+impl BatchQuery {
+    fn from_spec(spec: QuerySpec) -> Result<Self> {
+        let context = || format!("Invalid batch query '{}'", spec.name);
+        let report = match spec.report.as_str() {
+            "daily" => BatchReport::Daily,
+            "monthly" => BatchReport::Monthly,
+            "session" => BatchReport::Session,
+            other => {
+                return Err(anyhow::anyhow!(
+                    "Unknown report '{}', expected daily, monthly or session",
+                    other
+                ))
+                .with_context(context)
+            }
+        };
+        let since = spec
+            .since
+            .as_deref()
+            .map(|date| parse_date(date, 0, 0, 0))
+            .transpose()
+            .with_context(context)?;
+        let until = spec
+            .until
+            .as_deref()
+            .map(|date| parse_date(date, 23, 59, 59))
+            .transpose()
+            .with_context(context)?;
+        let group_by = match spec.group_by.as_deref() {
+            Some(group_by) => GroupBy::from_str(group_by, true)
+                .map_err(|e| anyhow::anyhow!(e))
+                .with_context(context)?,
+            None => GroupBy::default(),
+        };
+
+        Ok(Self {
+            name: spec.name,
+            report,
+            since,
+            until,
+            limit: spec.limit,
+            group_by,
+        })
+    }
+}
+
+fn parse_date(date: &str, hour: u32, min: u32, sec: u32) -> Result<DateTime<Utc>> {
+    NaiveDate::parse_from_str(date, "%Y-%m-%d")
+        .ok()
+        .and_then(|date| date.and_hms_opt(hour, min, sec))
+        .map(|time| time.and_utc())
+        .with_context(|| format!("Invalid date format: {}. Use YYYY-MM-DD", date))
+}
+
+/// Parse a `--query` flag: `NAME=REPORT[,since=DATE][,until=DATE][,limit=N][,group_by=G]`
+///
+/// For example `week=daily,limit=7` or `spend=monthly,since=2025-01-01,group_by=instance_type`.
+pub fn parse_query_flag(flag: &str) -> Result<BatchQuery> {
+    let (name, rest) = flag.split_once('=').with_context(|| {
+        format!(
+            "Invalid query '{}', expected NAME=REPORT[,key=value...]",
+            flag
+        )
+    })?;
+    let mut parts = rest.split(',');
+    let mut spec = QuerySpec {
+        name: name.to_string(),
+        report: parts.next().unwrap_or_default().to_string(),
+        ..QuerySpec::default()
+    };
+
+    for part in parts {
+        let (key, value) = part.split_once('=').with_context(|| {
+            format!(
+                "Invalid option '{}' in query '{}', expected key=value",
+                part, name
+            )
+        })?;
+        let value = value.to_string();
+        match key {
+            "since" => spec.since = Some(value),
+            "until" => spec.until = Some(value),
+            "limit" => {
+                spec.limit =
+                    Some(value.parse().with_context(|| {
+                        format!("Invalid limit '{}' in query '{}'", value, name)
+                    })?)
+            }
+            "group_by" => spec.group_by = Some(value),
+            other => {
+                return Err(anyhow::anyhow!(
+                    "Unknown option '{}' in query '{}', expected since, until, limit or group_by",
+                    other,
+                    name
+                ))
+            }
+        }
+    }
+
+    BatchQuery::from_spec(spec)
+}
+
+/// Load queries from a JSON file holding an array of query objects
+pub fn load_queries(path: &Path) -> Result<Vec<BatchQuery>> {
+    let content = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read batch file: {}", path.display()))?;
+    let specs: Vec<QuerySpec> = serde_json::from_str(&content)
+        .with_context(|| format!("Failed to parse batch file: {}", path.display()))?;
+    specs.into_iter().map(BatchQuery::from_spec).collect()
+}
+
+/// Queries from the batch file followed by the `--query` flags, validated
+pub fn collect_queries(file: Option<&Path>, flags: &[String]) -> Result<Vec<BatchQuery>> {
+    let mut queries = match file {
+        Some(file) => load_queries(file)?,
+        None => Vec::new(),
+    };
+    for flag in flags {
+        queries.push(parse_query_flag(flag)?);
+    }
+    validate_queries(&queries)?;
+    Ok(queries)
+}
+
+/// Check that there is at least one query and names are unique and non-empty
+pub fn validate_queries(queries: &[BatchQuery]) -> Result<()> {
+    if queries.is_empty() {
+        return Err(anyhow::anyhow!(
+            "No batch queries given; pass --file or --query"
+        ));
+    }
+
+    let mut names = HashSet::new();
+    for query in queries {
+        if query.name.trim().is_empty() {
+            return Err(anyhow::anyhow!("Batch queries need a name"));
+        }
+        if !names.insert(query.name.as_str()) {
+            return Err(anyhow::anyhow!(
+                "Batch query name '{}' is used more than once",
+                query.name
+            ));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    #[test]
+    fn test_parse_query_flag() {
+        let query =
+            parse_query_flag("week=daily,since=2025-01-01,limit=7,group_by=instance_type").unwrap();
+        assert_eq!(query.name, "week");
+        assert_eq!(query.report, BatchReport::Daily);
+        assert_eq!(
+            query.since.unwrap().to_rfc3339(),
+            "2025-01-01T00:00:00+00:00"
+        );
+        assert_eq!(query.limit, Some(7));
+        assert_eq!(query.group_by, GroupBy::InstanceType);
+
+        assert!(parse_query_flag("week").is_err());
+        assert!(parse_query_flag("week=weekly").is_err());
+        assert!(parse_query_flag("week=daily,limit=x").is_err());
+        assert!(parse_query_flag("week=daily,color=red").is_err());
+    }
+
+    #[test]
+    fn test_load_queries_and_validate() {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        write!(
+            file,
+            r#"[
+                {{"name": "recent", "report": "session", "limit": 5}},
+                {{"name": "january", "report": "monthly", "since": "2025-01-01", "until": "2025-01-31"}}
+            ]"#
+        )
+        .unwrap();
+
+        let queries = load_queries(file.path()).unwrap();
+        assert_eq!(queries.len(), 2);
+        assert_eq!(
+            queries[1].until.unwrap().to_rfc3339(),
+            "2025-01-31T23:59:59+00:00"
+        );
+        validate_queries(&queries).unwrap();
+
+        let duplicated = [queries[0].clone(), queries[0].clone()];
+        assert!(validate_queries(&duplicated).is_err());
+        assert!(validate_queries(&[]).is_err());
+    }
+}
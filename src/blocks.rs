@@ -0,0 +1,173 @@
+//! Billing Blocks
+//!
+//! Claude usage limits apply to 5-hour windows: a window opens with the first
+//! message after the previous one ran out, starting on the hour. `claude-usage
+//! blocks` rebuilds those windows from the JSONL entries so tokens and cost can
+//! be read per window, including the one that is still open.
+
+use chrono::{DateTime, DurationRound, SecondsFormat, Utc};
+use std::collections::BTreeSet;
+
+use crate::dedup::CostMode;
+use crate::models::{MicroDollars, SessionBlock, TokenCounts};
+use crate::parser::ProcessedEntry;
+
+/// Length of a billing window
+pub const BLOCK_HOURS: i64 = 5;
+
+/// A window still collecting entries
+struct OpenBlock {
+    start: DateTime<Utc>,
+    last_entry: DateTime<Utc>,
+    entries: u32,
+    models: BTreeSet<String>,
+    tokens: TokenCounts,
+    cost: MicroDollars,
+}
+
+impl OpenBlock {
+    fn new(first_entry: DateTime<Utc>) -> Self {
+        Self {
+            start: first_entry
+                .duration_trunc(chrono::Duration::hours(1))
+                .unwrap_or(first_entry),
+            last_entry: first_entry,
+            entries: 0,
+            models: BTreeSet::new(),
+            tokens: TokenCounts::default(),
+            cost: MicroDollars::ZERO,
+        }
+    }
+
+    fn end(&self) -> DateTime<Utc> {
+        self.start + chrono::Duration::hours(BLOCK_HOURS)
+    }
+
+    fn add(&mut self, entry: &ProcessedEntry, mode: CostMode) {
+        let export = entry.to_export_with(mode);
+        self.last_entry = entry.timestamp;
+        self.entries += 1;
+        self.models.insert(export.model.clone());
+        self.tokens += TokenCounts {
+            input_tokens: export.input_tokens,
+            output_tokens: export.output_tokens,
+            cache_creation_input_tokens: export.cache_creation_tokens,
+            cache_read_input_tokens: export.cache_read_tokens,
+        };
+        if let Some(secondary) = &export.secondary_usage {
+            self.models.insert(secondary.model.clone());
+            self.tokens += TokenCounts {
+                input_tokens: secondary.usage.input_tokens,
+                output_tokens: secondary.usage.output_tokens,
+                cache_creation_input_tokens: secondary.usage.cache_creation_input_tokens,
+                cache_read_input_tokens: secondary.usage.cache_read_input_tokens,
+            };
+        }
+        self.cost += MicroDollars::from_dollars(export.cost);
+    }
+
+    fn finish(self, now: DateTime<Utc>) -> SessionBlock {
+        let end = self.end();
+        SessionBlock {
+            start_time: self.start.to_rfc3339_opts(SecondsFormat::Secs, true),
+            end_time: end.to_rfc3339_opts(SecondsFormat::Secs, true),
+            actual_end_time: Some(self.last_entry.to_rfc3339_opts(SecondsFormat::Secs, true)),
+            is_active: now < end,
+            entries: self.entries,
+            models: self.models.into_iter().collect(),
+            token_counts: self.tokens,
+            cost_usd: self.cost.to_dollars(),
+        }
+    }
+}
+
+/// Group usage entries into billing windows, oldest first
+///
+/// A block is active when `now` is still inside its window. Entry costs are
+/// taken as `mode` says.
+pub fn build_blocks(
+    entries: &[ProcessedEntry],
+    now: DateTime<Utc>,
+    mode: CostMode,
+) -> Vec<SessionBlock> {
+    let mut ordered: Vec<&ProcessedEntry> = entries.iter().filter(|e| e.has_usage()).collect();
+    ordered.sort_by_key(|entry| entry.timestamp);
+
+    let mut blocks = Vec::new();
+    let mut current: Option<OpenBlock> = None;
+    for entry in ordered {
+        if current
+            .as_ref()
+            .is_some_and(|block| entry.timestamp >= block.end())
+        {
+            blocks.extend(current.take().map(|block| block.finish(now)));
+        }
+        current
+            .get_or_insert_with(|| OpenBlock::new(entry.timestamp))
+            .add(entry, mode);
+    }
+    blocks.extend(current.map(|block| block.finish(now)));
+    blocks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{MessageData, UsageData, UsageEntry};
+    use crate::parser::FileParser;
+
+    fn entry(timestamp: &str, cost: f64) -> ProcessedEntry {
+        let entry = UsageEntry {
+            timestamp: timestamp.to_string(),
+            message: MessageData {
+                id: format!("msg-{}", timestamp),
+                model: "claude-sonnet-4-20250514".to_string(),
+                usage: Some(UsageData {
+                    input_tokens: 100,
+                    output_tokens: 10,
+                    cache_creation_input_tokens: 0,
+                    cache_read_input_tokens: 0,
+                }),
+                secondary_usage: None,
+            },
+            cost_usd: Some(cost),
+            request_id: "req".to_string(),
+        };
+        ProcessedEntry::new(entry, &FileParser::new(), 1).unwrap()
+    }
+
+    fn at(timestamp: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(timestamp).unwrap().into()
+    }
+
+    #[test]
+    fn test_entries_group_into_five_hour_windows() {
+        let entries = [
+            entry("2025-01-10T09:40:00Z", 1.0),
+            entry("2025-01-10T13:59:00Z", 2.0),
+            // Past 14:00, the end of the window opened at 09:00
+            entry("2025-01-10T14:10:00Z", 4.0),
+            // After a quiet spell the next window starts with the next entry
+            entry("2025-01-10T19:30:00Z", 8.0),
+        ];
+
+        let blocks = build_blocks(&entries, at("2025-01-10T20:00:00Z"), CostMode::Auto);
+        assert_eq!(blocks.len(), 3);
+
+        assert_eq!(blocks[0].start_time, "2025-01-10T09:00:00Z");
+        assert_eq!(blocks[0].end_time, "2025-01-10T14:00:00Z");
+        assert_eq!(
+            blocks[0].actual_end_time.as_deref(),
+            Some("2025-01-10T13:59:00Z")
+        );
+        assert_eq!(blocks[0].entries, 2);
+        assert_eq!(blocks[0].token_counts.input_tokens, 200);
+        assert_eq!(blocks[0].cost_usd, 3.0);
+        assert!(!blocks[0].is_active);
+
+        assert_eq!(blocks[1].start_time, "2025-01-10T14:00:00Z");
+        assert_eq!(blocks[2].start_time, "2025-01-10T19:00:00Z");
+        assert!(blocks[2].is_active);
+        assert_eq!(blocks[2].models, ["claude-sonnet-4-20250514"]);
+    }
+}
@@ -0,0 +1,114 @@
+//! Git branches of sessions
+//!
+//! Claude's logs do not say which branch a session worked on, so
+//! `scripts/claude-usage-branch-hook.sh` records it: installed as a Claude
+//! Code `SessionStart` hook, it appends one JSON line per session start to the
+//! branches file (`branches.file`):
+//!
+//! ```json
+//! {"sessionId":"0b7e6f2a-1c3d-4e5f-8a9b-123456789abc","branch":"feature/search","timestamp":"2025-03-01T09:30:00Z"}
+//! ```
+//!
+//! Reports grouped with `--group-by branch` look sessions up here; a resumed
+//! session that started again on another branch is attributed to the latest
+//! one, and sessions without a record are reported as [`UNKNOWN_BRANCH`].
+
+use crate::models::SessionOutput;
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use tracing::warn;
+
+/// Branch for sessions the hook did not record
+pub const UNKNOWN_BRANCH: &str = "unknown";
+
+/// One line of the branches file
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct BranchRecord {
+    session_id: String,
+    branch: String,
+}
+
+/// Branch of each recorded session, keyed by session ID
+#[derive(Debug, Clone, Default)]
+pub struct SessionBranches {
+    sessions: HashMap<String, String>,
+}
+
+impl SessionBranches {
+    /// Load the branches file; a missing file records no branches
+    ///
+    /// Lines that are not valid records, such as one cut short by a crashed
+    /// hook, are skipped with a warning.
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read branches file: {}", path.display()))?;
+        Ok(Self::parse(&content, path))
+    }
+
+    fn parse(content: &str, path: &Path) -> Self {
+        let mut sessions = HashMap::new();
+        let mut invalid = 0;
+        for line in content.lines().filter(|line| !line.trim().is_empty()) {
+            match serde_json::from_str::<BranchRecord>(line) {
+                Ok(record) if !record.branch.is_empty() => {
+                    sessions.insert(record.session_id, record.branch);
+                }
+                _ => invalid += 1,
+            }
+        }
+        if invalid > 0 {
+            warn!(file = %path.display(), lines = invalid, "Skipped invalid branch records");
+        }
+        Self { sessions }
+    }
+
+    /// Branch a session started on, or [`UNKNOWN_BRANCH`]
+    pub fn branch(&self, session: &SessionOutput) -> &str {
+        self.sessions
+            .get(&session.session_id)
+            .or_else(|| self.sessions.get(&session.display_id))
+            .map_or(UNKNOWN_BRANCH, String::as_str)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::test_support::session;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_latest_record_wins_and_bad_lines_are_skipped() {
+        let temp = TempDir::new().unwrap();
+        let path = temp.path().join("branches.jsonl");
+        assert_eq!(
+            SessionBranches::load(&path)
+                .unwrap()
+                .branch(&session("s1").build()),
+            UNKNOWN_BRANCH
+        );
+
+        fs::write(
+            &path,
+            concat!(
+                "{\"sessionId\":\"s1\",\"branch\":\"main\",\"timestamp\":\"2025-03-01T09:00:00Z\"}\n",
+                "{\"sessionId\":\"s2\",\"branch\":\"feature/search\"}\n",
+                "{\"sessionId\":\"s1\",\"branch\":\"fix/login\"}\n",
+                "{\"sessionId\":\"s3\",\"bra\n",
+            ),
+        )
+        .unwrap();
+
+        let branches = SessionBranches::load(&path).unwrap();
+        assert_eq!(branches.branch(&session("s1").build()), "fix/login");
+        assert_eq!(branches.branch(&session("s2").build()), "feature/search");
+        assert_eq!(branches.branch(&session("s3").build()), UNKNOWN_BRANCH);
+    }
+}
@@ -11,9 +11,13 @@ use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::collections::{HashMap, HashSet};
 use std::fs;
+use std::io::Read;
 use std::path::{Path, PathBuf};
 use tracing::{debug, info, warn};
 
+use crate::dedup::{calculate_entry_cost, CostMode};
+use crate::io_throttle;
+
 /// CCUsage-compatible usage data structure
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CCUsageData {
@@ -106,10 +110,12 @@ fn format_date(timestamp: &str) -> String {
     }
 }
 
-/// Load daily usage data with ccusage-compatible algorithm
+/// Load daily usage data with ccusage-compatible algorithm, taking entry
+/// costs as `mode` says
 pub async fn load_daily_usage_cccompat(
     since: Option<&str>,
     until: Option<&str>,
+    mode: CostMode,
 ) -> Result<Vec<CCDailyUsage>> {
     info!("Loading daily usage data with ccusage compatibility mode");
     
@@ -156,7 +162,10 @@ pub async fn load_daily_usage_cccompat(
     let mut all_entries = Vec::new();
     
     for file_path in &all_files {
-        let content = fs::read_to_string(file_path)
+        let mut content = String::new();
+        io_throttle::global()
+            .open(file_path)
+            .and_then(|mut file| file.read_to_string(&mut content))
             .with_context(|| format!("Failed to read file: {}", file_path.display()))?;
         
         // Process each line (ccusage filters empty lines but still reads them)
@@ -186,12 +195,7 @@ pub async fn load_daily_usage_cccompat(
                     let date = format_date(&data.timestamp);
                     
                     // Calculate cost (ccusage uses pre-calculated costUSD when available)
-                    let cost = if let Some(cost_usd) = data.cost_usd {
-                        cost_usd
-                    } else {
-                        // Calculate from tokens using pricing
-                        calculate_cost_from_tokens(&data)
-                    };
+                    let cost = entry_cost(&data, mode);
                     
                     all_entries.push((date, data, cost));
                 }
@@ -264,6 +268,13 @@ pub async fn load_daily_usage_cccompat(
     Ok(results)
 }
 
+/// Cost of one entry under `mode`, like ccusage's `--mode`
+fn entry_cost(data: &CCUsageData, mode: CostMode) -> f64 {
+    let (cost, _) =
+        calculate_entry_cost(mode, data.cost_usd, calculate_cost_from_tokens(data), 0.0);
+    cost
+}
+
 /// Calculate cost from tokens (simplified version matching ccusage pricing)
 fn calculate_cost_from_tokens(data: &CCUsageData) -> f64 {
     let usage = match &data.message.usage {
@@ -300,8 +311,9 @@ fn calculate_cost_from_tokens(data: &CCUsageData) -> f64 {
 pub async fn get_ccusage_compatible_cost(
     since: Option<&str>,
     until: Option<&str>,
+    mode: CostMode,
 ) -> Result<f64> {
-    let daily_data = load_daily_usage_cccompat(since, until).await?;
+    let daily_data = load_daily_usage_cccompat(since, until, mode).await?;
     
     let total_cost: f64 = daily_data.iter()
         .map(|d| d.total_cost)
@@ -365,4 +377,32 @@ mod tests {
         // = (15 + 150 + 9.375 + 2.8125) / 1000 = 0.1771875
         assert!((cost - 0.177).abs() < 0.001);
     }
+
+    #[test]
+    fn test_entry_cost_follows_mode() {
+        let mut data = CCUsageData {
+            timestamp: "2025-08-20T10:30:00Z".to_string(),
+            message: CCMessage {
+                id: Some("msg_123".to_string()),
+                model: Some("claude-3-opus".to_string()),
+                usage: Some(CCUsage {
+                    input_tokens: Some(1000),
+                    output_tokens: Some(0),
+                    cache_creation_input_tokens: None,
+                    cache_read_input_tokens: None,
+                }),
+            },
+            cost_usd: Some(0.5),
+            request_id: Some("req_456".to_string()),
+            session_id: None,
+        };
+
+        assert_eq!(entry_cost(&data, CostMode::Auto), 0.5);
+        assert_eq!(entry_cost(&data, CostMode::Display), 0.5);
+        assert!((entry_cost(&data, CostMode::Calculate) - 0.015).abs() < 1e-9);
+
+        data.cost_usd = None;
+        assert!((entry_cost(&data, CostMode::Auto) - 0.015).abs() < 1e-9);
+        assert_eq!(entry_cost(&data, CostMode::Display), 0.0);
+    }
 }
\ No newline at end of file
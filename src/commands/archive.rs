@@ -0,0 +1,70 @@
+//! Archive command implementation
+//!
+//! Runs [`crate::archive::compact`] and prints what was compacted, verified
+//! and deleted.
+
+use anyhow::Result;
+use colored::Colorize;
+
+use crate::archive::{compact, CompactOptions};
+
+/// Compact old JSONL logs into per-month parquet archives
+pub fn run_archive_compact(options: &CompactOptions, json: bool) -> Result<()> {
+    let summary = compact(options)?;
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&summary)?);
+        return Ok(());
+    }
+
+    println!("\n{}", "Archive Compaction".bright_white().bold());
+    println!(
+        "   Archive: {}",
+        summary.archive_dir.display().to_string().bright_cyan()
+    );
+    println!(
+        "   Logs last modified before {}: {}",
+        summary.cutoff, summary.files
+    );
+    println!(
+        "   Dropped: {} duplicate entries, {} invalid lines",
+        summary.duplicates, summary.invalid_lines
+    );
+
+    if summary.months.is_empty() {
+        println!("\n{}", "Nothing to compact".bright_black());
+        return Ok(());
+    }
+
+    println!("\n{} Months:", "📦".bright_yellow());
+    for month in &summary.months {
+        let status = if summary.dry_run {
+            "would compact".bright_black()
+        } else if month.verified {
+            "✓ verified".bright_green()
+        } else {
+            "✗ failed".bright_red()
+        };
+        println!(
+            "   {}: {} entries from {} logs {}",
+            month.month.bright_cyan(),
+            month.entries,
+            month.logs,
+            status
+        );
+        if let Some(error) = &month.error {
+            println!("     {}", error);
+        }
+    }
+
+    if options.delete_originals && !summary.dry_run {
+        println!(
+            "\n{} Deleted {} of {} original logs",
+            "🗑".bright_red(),
+            summary.deleted_files.len(),
+            summary.files
+        );
+    }
+
+    Ok(())
+}
@@ -0,0 +1,112 @@
+//! Blocks command implementation
+//!
+//! Prints the 5-hour billing blocks built by [`crate::blocks::build_blocks`],
+//! most recent last, with the active block flagged.
+
+use anyhow::Result;
+use chrono::{DateTime, Local};
+use colored::Colorize;
+
+use crate::analyzer::ClaudeUsageAnalyzer;
+use crate::dedup::ProcessOptions;
+use crate::reports::{print_partial, print_skipped};
+
+/// Build the billing blocks and print the last `options.limit` of them
+pub fn run_blocks(analyzer: &ClaudeUsageAnalyzer, options: &ProcessOptions) -> Result<()> {
+    let (mut blocks, skipped) = analyzer.session_blocks(options)?;
+    let partial = options.is_cancelled();
+    if partial {
+        print_partial();
+    }
+    if let Some(limit) = options.limit {
+        blocks.drain(..blocks.len().saturating_sub(limit));
+    }
+
+    if options.json_output {
+        let mut output = serde_json::json!({ "blocks": blocks });
+        if !skipped.is_empty() {
+            output["metadata"]["skipped"] = serde_json::json!(skipped);
+        }
+        if partial {
+            output["metadata"]["partial"] = serde_json::Value::Bool(true);
+        }
+        println!("{}", serde_json::to_string_pretty(&output)?);
+        return Ok(());
+    }
+
+    println!("\n{}", "=".repeat(80).bright_cyan());
+    println!(
+        "{}",
+        "Claude Code Usage Report - 5-Hour Billing Blocks (All Instances)"
+            .bright_white()
+            .bold()
+    );
+    println!("{}", "=".repeat(80).bright_cyan());
+
+    if blocks.is_empty() {
+        println!("\nNo Claude usage data found across all instances.");
+        if options.verbose {
+            print_skipped(&skipped);
+        }
+        return Ok(());
+    }
+
+    let total_cost: f64 = blocks.iter().map(|block| block.cost_usd).sum();
+    println!(
+        "\n{} {} blocks • {} total\n",
+        "📊".bright_yellow(),
+        blocks.len().to_string().bright_white().bold(),
+        format!("${:.2}", total_cost).bright_green().bold()
+    );
+
+    for block in &blocks {
+        let status = if block.is_active {
+            format!(" {}", "● ACTIVE".bright_green().bold())
+        } else {
+            String::new()
+        };
+        println!(
+            "{} {} – {} — {} ({} entries){}",
+            "⏱️".bright_blue(),
+            local_time(&block.start_time, "%Y-%m-%d %H:%M")
+                .bright_white()
+                .bold(),
+            local_time(&block.end_time, "%H:%M").bright_white(),
+            format!("${:.2}", block.cost_usd).bright_green().bold(),
+            block.entries.to_string().bright_white(),
+            status
+        );
+        let tokens = &block.token_counts;
+        println!(
+            "   Tokens: {} input • {} output • {} cache read • {} cache write",
+            tokens.input_tokens.to_string().bright_white(),
+            tokens.output_tokens.to_string().bright_white(),
+            tokens.cache_read_input_tokens.to_string().bright_white(),
+            tokens
+                .cache_creation_input_tokens
+                .to_string()
+                .bright_white()
+        );
+        println!("   Models: {}", block.models.join(", ").bright_black());
+        if let (true, Some(last)) = (block.is_active, &block.actual_end_time) {
+            println!(
+                "   Last entry: {} • window closes at {}",
+                local_time(last, "%H:%M"),
+                local_time(&block.end_time, "%H:%M")
+            );
+        }
+        println!();
+    }
+
+    if options.verbose {
+        print_skipped(&skipped);
+    }
+    Ok(())
+}
+
+/// Render an RFC 3339 timestamp in local time, or as given if it does not parse
+fn local_time(timestamp: &str, format: &str) -> String {
+    DateTime::parse_from_rfc3339(timestamp)
+        .map(|time| time.with_timezone(&Local).format(format).to_string())
+        .unwrap_or_else(|_| timestamp.to_string())
+}
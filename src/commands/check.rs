@@ -0,0 +1,112 @@
+//! Check command implementation
+//!
+//! Aggregates usage once as the `daily` report does, from the claude-keeper
+//! backups (refreshed first when they are stale), for cron jobs and CI:
+//!
+//! - sends the spend notifications of [`crate::notify`] that have not been
+//!   sent yet, when they are configured. Only the alerts delivered to every
+//!   webhook are recorded as sent; the others are tried again on the next run.
+//! - with `--max-daily` or `--max-monthly`, fails with
+//!   [`SpendLimitExceeded`](crate::status::SpendLimitExceeded), exit code 10,
+//!   when today's or this month's cost is over the limit.
+
+use anyhow::Result;
+use serde_json::json;
+
+use crate::analyzer::ClaudeUsageAnalyzer;
+use crate::config::get_config;
+use crate::dedup::{Command, ProcessOptions};
+use crate::notify::{self, SentAlerts};
+use crate::request::{AnalyzerRequest, ReportKind};
+use crate::status::{check_limits, status, summary_line};
+
+/// Spending limits in dollars, unset ones unchecked
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SpendLimits {
+    pub max_daily: Option<f64>,
+    pub max_monthly: Option<f64>,
+}
+
+/// Send the spend alerts crossed today and not sent before, then check
+/// today's and this month's cost against the limits
+pub async fn run_check(
+    json: bool,
+    exclude_vms: bool,
+    dry_run: bool,
+    limits: SpendLimits,
+) -> Result<()> {
+    let config = &get_config().notify;
+    let notifying = notify::enabled(config);
+    if !notifying && limits.max_daily.is_none() && limits.max_monthly.is_none() {
+        anyhow::bail!(
+            "Nothing to check; pass --max-daily or --max-monthly, or set [notify] webhooks \
+             and daily_usd or session_usd"
+        );
+    }
+
+    let mut options = ProcessOptions::new(Command::Daily.as_str());
+    options.exclude_vms = exclude_vms;
+    // Keeps the report's progress line out of the summary
+    options.json_output = true;
+    let sessions = ClaudeUsageAnalyzer::new()
+        .sessions(AnalyzerRequest::with_options(ReportKind::Daily, options))
+        .await?;
+    let today = chrono::Local::now().date_naive();
+    let current = status(&sessions, today, chrono::Utc::now(), None);
+
+    let mut pending = Vec::new();
+    let mut failed = 0;
+    if notifying {
+        let mut sent = SentAlerts::load(&config.state_file)?;
+        pending = notify::current_alerts(config, &sessions, today)
+            .into_iter()
+            .filter(|alert| !sent.contains(alert))
+            .collect();
+        if !dry_run {
+            for alert in &pending {
+                match notify::send(&config.webhooks, alert).await {
+                    Ok(()) => sent.insert(alert),
+                    Err(_) => failed += 1,
+                }
+            }
+            sent.save(&config.state_file, today)?;
+        }
+    }
+
+    // A limit exceeded is the failure to report; its error document replaces
+    // the summary
+    let within_limits = check_limits(&current, limits.max_daily, limits.max_monthly);
+    if json && within_limits.is_ok() {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&json!({
+                "today": current.today,
+                "month": current.month,
+                "alerts": pending,
+                "sent": notifying && !dry_run,
+                "failed": failed,
+            }))?
+        );
+    } else if !json {
+        for alert in &pending {
+            println!("{}", alert.message());
+        }
+        if notifying && pending.is_empty() {
+            println!("No new spend thresholds crossed");
+        }
+        if dry_run && !pending.is_empty() {
+            println!("Dry run: nothing was sent");
+        }
+        println!("{}", summary_line(&current));
+    }
+    within_limits?;
+
+    if failed > 0 {
+        anyhow::bail!(
+            "{} of {} notifications could not be sent",
+            failed,
+            pending.len()
+        );
+    }
+    Ok(())
+}
@@ -0,0 +1,475 @@
+//! Doctor command implementation
+//!
+//! Checks everything reports and live mode depend on — the Claude home, its
+//! session logs, claude-keeper, the parquet backups and the pricing snapshot —
+//! and prints each problem with what to do about it. The guidance printed
+//! when live mode fails comes from the same fixes.
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use colored::Colorize;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::SystemTime;
+
+use crate::config::get_config;
+use crate::error::KeeperNotFound;
+use crate::file_discovery::FileDiscovery;
+use crate::inspect::inspect_file;
+use crate::parquet::reader::ParquetSummaryReader;
+use crate::pricing_snapshot::PricingSnapshot;
+
+/// Backups older than this are reported as stale
+const BACKUP_MAX_AGE_DAYS: i64 = 7;
+
+/// Session logs with malformed lines listed by the JSONL check
+const MAX_LISTED_FILES: usize = 5;
+
+const KEEPER_INSTALL_FIX: &str =
+    "Install claude-keeper with `cargo install claude-keeper` (see https://github.com/mufeedvh/claude-keeper)";
+const KEEPER_PERMISSION_FIX: &str =
+    "Make claude-keeper executable: chmod +x $(which claude-keeper), and check that it is in your PATH";
+const BACKUP_FIX: &str =
+    "Run `claude-keeper backup ~/.claude --out ~/.claude-backup`, or start live mode with --no-baseline";
+
+/// How a check came out
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CheckStatus {
+    Ok,
+    /// Works, but some data or feature is missing
+    Warn,
+    /// Reports cannot work until this is fixed
+    Fail,
+}
+
+/// Outcome of one check, with a fix unless it passed
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Check {
+    pub name: &'static str,
+    pub status: CheckStatus,
+    pub detail: String,
+    /// Files or directories the detail refers to
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub items: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fix: Option<String>,
+}
+
+impl Check {
+    fn ok(name: &'static str, detail: String) -> Self {
+        Self {
+            name,
+            status: CheckStatus::Ok,
+            detail,
+            items: Vec::new(),
+            fix: None,
+        }
+    }
+
+    fn warn(name: &'static str, detail: String, fix: impl Into<String>) -> Self {
+        Self {
+            name,
+            status: CheckStatus::Warn,
+            detail,
+            items: Vec::new(),
+            fix: Some(fix.into()),
+        }
+    }
+
+    fn fail(name: &'static str, detail: String, fix: impl Into<String>) -> Self {
+        Self {
+            name,
+            status: CheckStatus::Fail,
+            detail,
+            items: Vec::new(),
+            fix: Some(fix.into()),
+        }
+    }
+}
+
+/// Run every check and print the results, returning whether none failed
+pub fn run_doctor(json: bool) -> Result<bool> {
+    let config = get_config();
+    let discovery = FileDiscovery::new();
+    let roots = discovery.discover_claude_paths(false)?;
+    let files: Vec<PathBuf> = discovery
+        .find_jsonl_files(&roots)?
+        .into_iter()
+        .map(|(file, _)| file)
+        .collect();
+    let now = Utc::now();
+
+    let checks = vec![
+        check_claude_home(&config.paths.claude_home, &config.paths.config_dirs),
+        check_instances(&config.paths.vms_directory, &roots, files.len()),
+        check_keeper(&config.live.claude_keeper_path),
+        check_backups(&backup_dir(), now),
+        check_pricing(
+            &config.pricing.snapshot_file,
+            config.pricing.max_age_days,
+            now,
+        ),
+        check_session_logs(&files),
+    ];
+    let healthy = checks.iter().all(|check| check.status != CheckStatus::Fail);
+
+    if json {
+        let output = serde_json::json!({ "healthy": healthy, "checks": checks });
+        println!("{}", serde_json::to_string_pretty(&output)?);
+        return Ok(healthy);
+    }
+
+    println!("\n{}", "Claude Usage Doctor".bright_white().bold());
+    println!();
+    for check in &checks {
+        let status = match check.status {
+            CheckStatus::Ok => "✓".bright_green(),
+            CheckStatus::Warn => "⚠".bright_yellow(),
+            CheckStatus::Fail => "✗".bright_red(),
+        };
+        println!("   {} {:<18} {}", status, check.name, check.detail);
+        for item in &check.items {
+            println!("{:24}{}", "", item.bright_black());
+        }
+        if let Some(fix) = &check.fix {
+            println!("     {} {}", "→".bright_cyan(), fix);
+        }
+    }
+
+    let problems = checks
+        .iter()
+        .filter(|check| check.status != CheckStatus::Ok)
+        .count();
+    if problems == 0 {
+        println!("\n{} Everything looks healthy", "✅".bright_green());
+    } else if healthy {
+        println!(
+            "\n{} {} warning{}, reports still work",
+            "⚠️".bright_yellow(),
+            problems,
+            if problems == 1 { "" } else { "s" }
+        );
+    } else {
+        println!(
+            "\n{} Some checks failed, fix them before running reports",
+            "❌".bright_red()
+        );
+    }
+    Ok(healthy)
+}
+
+/// Print what to do about a failed `claude-usage live`
+pub fn print_live_failure(error: &anyhow::Error) {
+    let message = error.to_string();
+    let (title, summary, fixes) = if error.downcast_ref::<KeeperNotFound>().is_some() {
+        (
+            "Claude Keeper Not Found",
+            "Claude Usage Live requires claude-keeper to be installed.".to_string(),
+            vec![KEEPER_INSTALL_FIX],
+        )
+    } else if message.contains("permission") {
+        (
+            "Permission Error",
+            "Claude-keeper cannot be executed due to permission issues.".to_string(),
+            vec![KEEPER_PERMISSION_FIX],
+        )
+    } else if message.contains("backup directory") || message.contains("No such file") {
+        (
+            "Configuration Issue",
+            "Unable to access Claude conversation data.".to_string(),
+            vec![BACKUP_FIX],
+        )
+    } else {
+        (
+            "Live Mode Failed",
+            format!("Error: {}", error),
+            vec![
+                "Try running with --no-baseline",
+                "Check the logs for more details",
+            ],
+        )
+    };
+
+    eprintln!("❌ {}", title);
+    eprintln!();
+    eprintln!("{}", summary);
+    eprintln!();
+    eprintln!("💡 Try these solutions:");
+    for fix in fixes {
+        eprintln!("   • {}", fix);
+    }
+    eprintln!("   • Run `claude-usage doctor` to check the whole setup");
+}
+
+/// The claude-keeper backup directory reports and live mode read
+fn backup_dir() -> PathBuf {
+    dirs::home_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join(".claude-backup")
+}
+
+/// The Claude home, or a Claude config directory (`~/.config/claude`) used
+/// instead by newer Claude Code versions
+fn check_claude_home(claude_home: &Path, config_dirs: &[PathBuf]) -> Check {
+    const NAME: &str = "Claude home";
+    if claude_home.is_dir() {
+        Check::ok(NAME, claude_home.display().to_string())
+    } else if let Some(dir) = config_dirs.iter().find(|dir| dir.join("projects").is_dir()) {
+        Check::ok(NAME, dir.display().to_string())
+    } else {
+        Check::fail(
+            NAME,
+            format!("{} does not exist", claude_home.display()),
+            "Set CLAUDE_HOME or CLAUDE_CONFIG_DIR to the directory holding Claude's projects/ folder",
+        )
+    }
+}
+
+/// Instances found under the home and VMs directory, and VM directories
+/// skipped for having no `projects/` folder
+fn check_instances(vms_directory: &Path, roots: &[PathBuf], files: usize) -> Check {
+    const NAME: &str = "Session logs";
+    if roots.is_empty() {
+        return Check::fail(
+            NAME,
+            "no Claude instance with a projects/ directory".to_string(),
+            "Use Claude Code at least once, or point CLAUDE_HOME and CLAUDE_VMS_DIR at its data",
+        );
+    }
+
+    let detail = format!(
+        "{} session log{} in {} instance{}",
+        files,
+        if files == 1 { "" } else { "s" },
+        roots.len(),
+        if roots.len() == 1 { "" } else { "s" }
+    );
+    let skipped: Vec<String> = std::fs::read_dir(vms_directory)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.is_dir() && !path.join("projects").exists())
+        .map(|path| path.display().to_string())
+        .collect();
+    if !skipped.is_empty() {
+        let mut check = Check::warn(
+            NAME,
+            format!("{}, {} VM directories skipped", detail, skipped.len()),
+            "Give each VM directory a projects/ folder, or move it out of the VMs directory",
+        );
+        check.items = skipped;
+        return check;
+    }
+    if files == 0 {
+        return Check::warn(
+            NAME,
+            detail,
+            "Run a Claude Code session so there is usage to report",
+        );
+    }
+    Check::ok(NAME, detail)
+}
+
+fn check_keeper(program: &str) -> Check {
+    const NAME: &str = "claude-keeper";
+    match Command::new(program).arg("--version").output() {
+        Ok(output) if output.status.success() => {
+            let version = String::from_utf8_lossy(&output.stdout).trim().to_string();
+            if version.is_empty() {
+                Check::ok(NAME, format!("{} is installed", program))
+            } else {
+                Check::ok(NAME, version)
+            }
+        }
+        Ok(output) => Check::warn(
+            NAME,
+            format!("`{} --version` exited with {}", program, output.status),
+            "Reinstall claude-keeper; live mode and backups need it",
+        ),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Check::warn(
+            NAME,
+            format!("{} not found; live mode and backups need it", program),
+            KEEPER_INSTALL_FIX,
+        ),
+        Err(e) if e.kind() == std::io::ErrorKind::PermissionDenied => Check::fail(
+            NAME,
+            format!("{} cannot be executed", program),
+            KEEPER_PERMISSION_FIX,
+        ),
+        Err(e) => Check::warn(
+            NAME,
+            format!("failed to run {}: {}", program, e),
+            KEEPER_INSTALL_FIX,
+        ),
+    }
+}
+
+fn check_backups(dir: &Path, now: DateTime<Utc>) -> Check {
+    const NAME: &str = "Parquet backups";
+    let stats = match ParquetSummaryReader::new(dir.to_path_buf())
+        .and_then(|reader| reader.get_backup_stats())
+    {
+        Ok(stats) => stats,
+        Err(_) if !dir.exists() => {
+            return Check::warn(
+                NAME,
+                format!("{} does not exist", dir.display()),
+                BACKUP_FIX,
+            )
+        }
+        Err(e) => {
+            return Check::fail(
+                NAME,
+                format!("{} cannot be read: {}", dir.display(), e),
+                format!("Check the permissions of {}", dir.display()),
+            )
+        }
+    };
+    if stats.file_count == 0 {
+        return Check::warn(
+            NAME,
+            format!("no parquet files in {}", dir.display()),
+            BACKUP_FIX,
+        );
+    }
+
+    let age_days = (now - DateTime::<Utc>::from(stats.latest_modified)).num_days();
+    let detail = format!(
+        "{} file{}, {:.1} MB, newest {} day{} old",
+        stats.file_count,
+        if stats.file_count == 1 { "" } else { "s" },
+        stats.total_size_bytes as f64 / 1_048_576.0,
+        age_days,
+        if age_days == 1 { "" } else { "s" }
+    );
+    if stats.latest_modified == SystemTime::UNIX_EPOCH || age_days > BACKUP_MAX_AGE_DAYS {
+        return Check::warn(NAME, detail, BACKUP_FIX);
+    }
+    Check::ok(NAME, detail)
+}
+
+fn check_pricing(path: &Path, max_age_days: u32, now: DateTime<Utc>) -> Check {
+    const NAME: &str = "Pricing snapshot";
+    match PricingSnapshot::load(path) {
+        Ok(Some(snapshot)) => {
+            let detail = format!(
+                "{} models, fetched {} days ago",
+                snapshot.models.len(),
+                snapshot.age_days(now)
+            );
+            if snapshot.is_stale(now, max_age_days) {
+                Check::warn(
+                    NAME,
+                    format!("{}, over pricing.max_age_days ({})", detail, max_age_days),
+                    "Run `claude-usage pricing refresh`",
+                )
+            } else {
+                Check::ok(NAME, detail)
+            }
+        }
+        Ok(None) => Check::warn(
+            NAME,
+            format!("none at {}, built-in rates are used", path.display()),
+            "Run `claude-usage pricing refresh`",
+        ),
+        Err(e) => Check::fail(
+            NAME,
+            format!("{:#}", e),
+            format!(
+                "Delete {} and run `claude-usage pricing refresh`",
+                path.display()
+            ),
+        ),
+    }
+}
+
+/// Share of lines in `files` that are not valid JSON, too long or not UTF-8
+fn check_session_logs(files: &[PathBuf]) -> Check {
+    const NAME: &str = "Malformed JSONL";
+    let mut lines = 0;
+    let mut malformed = 0;
+    let mut unreadable = 0;
+    let mut worst: Vec<(usize, usize, &PathBuf)> = Vec::new();
+    for file in files {
+        match inspect_file(file) {
+            Ok(inspection) => {
+                lines += inspection.lines;
+                malformed += inspection.errors.len();
+                if !inspection.errors.is_empty() {
+                    worst.push((inspection.errors.len(), inspection.lines, file));
+                }
+            }
+            Err(_) => unreadable += 1,
+        }
+    }
+
+    let rate = if lines == 0 {
+        0.0
+    } else {
+        malformed as f64 / lines as f64 * 100.0
+    };
+    let mut detail = format!("{} of {} lines ({:.2}%)", malformed, lines, rate);
+    if unreadable > 0 {
+        detail.push_str(&format!(", {} files unreadable", unreadable));
+    }
+    if malformed == 0 && unreadable == 0 {
+        return Check::ok(NAME, detail);
+    }
+
+    worst.sort_by(|a, b| b.0.cmp(&a.0).then_with(|| a.2.cmp(b.2)));
+    let mut check = Check::warn(
+        NAME,
+        detail,
+        "These lines are skipped by every report; see why with `claude-usage inspect <file>`",
+    );
+    check.items = worst
+        .iter()
+        .take(MAX_LISTED_FILES)
+        .map(|(failed, total, file)| format!("{}: {} of {} lines", file.display(), failed, total))
+        .collect();
+    check
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_checks_report_missing_data_with_fixes() {
+        let temp = TempDir::new().unwrap();
+        let now = Utc::now();
+
+        let missing = temp.path().join("missing");
+        let home = check_claude_home(&missing, &[]);
+        assert_eq!(home.status, CheckStatus::Fail);
+        assert!(home.fix.is_some());
+        assert_eq!(check_claude_home(temp.path(), &[]).status, CheckStatus::Ok);
+        std::fs::create_dir_all(temp.path().join("projects")).unwrap();
+        let config_dir = check_claude_home(&missing, &[temp.path().to_path_buf()]);
+        assert_eq!(config_dir.status, CheckStatus::Ok);
+
+        let backups = check_backups(&temp.path().join("backup"), now);
+        assert_eq!(backups.status, CheckStatus::Warn);
+        assert_eq!(backups.fix.as_deref(), Some(BACKUP_FIX));
+
+        let pricing = check_pricing(&temp.path().join("pricing.json"), 7, now);
+        assert_eq!(pricing.status, CheckStatus::Warn);
+        std::fs::write(temp.path().join("pricing.json"), "not json").unwrap();
+        let pricing = check_pricing(&temp.path().join("pricing.json"), 7, now);
+        assert_eq!(pricing.status, CheckStatus::Fail);
+
+        let log = temp.path().join("session.jsonl");
+        std::fs::write(&log, "{\"type\":\"user\"}\n{not json\n\n").unwrap();
+        let logs = check_session_logs(&[log]);
+        assert_eq!(logs.status, CheckStatus::Warn);
+        assert!(logs.detail.starts_with("1 of 3 lines"));
+        assert_eq!(check_session_logs(&[]).status, CheckStatus::Ok);
+
+        let keeper = check_keeper("claude-keeper-that-does-not-exist");
+        assert_eq!(keeper.fix.as_deref(), Some(KEEPER_INSTALL_FIX));
+    }
+}
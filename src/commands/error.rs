@@ -0,0 +1,163 @@
+//! Command failures as exit codes and JSON
+//!
+//! Every failed command exits with a code naming the kind of failure, and
+//! with `--json` prints a JSON document instead of the plain message:
+//!
+//! ```json
+//! {"error": {"code": "no_data", "message": "Backup directory does not exist: /home/me/.claude-backup"}}
+//! ```
+//!
+//! A `spend_limit` error also lists the limits `check` found exceeded under
+//! `breaches`, each with its `limitKind`, `period`, `spent` and `limit`.
+//!
+//! The codes and the document layout are stable; messages are not.
+
+use serde::Serialize;
+
+use crate::error::{Error, LimitBreach};
+
+/// Kind of failure, each with its own exit code
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorCode {
+    /// Any failure without a more specific code
+    Failed,
+    /// The configuration could not be loaded or is invalid
+    Config,
+    /// More JSONL lines failed to parse than `--max-error-rate` allows
+    ParseFailure,
+    /// There is no usage data to report on
+    NoData,
+    /// The claude-keeper executable is not installed
+    KeeperMissing,
+    /// Memory use went over `memory.max_memory_mb`
+    MemoryLimit,
+    /// Spend went over a `check --max-daily` or `--max-monthly` limit
+    SpendLimit,
+}
+
+impl ErrorCode {
+    /// Process exit code; 2 is left to argument errors, 4 to `verify-export`
+    /// and 9 to `doctor`
+    pub fn exit_code(self) -> i32 {
+        match self {
+            ErrorCode::Failed => 1,
+            ErrorCode::ParseFailure => 3,
+            ErrorCode::Config => 5,
+            ErrorCode::NoData => 6,
+            ErrorCode::KeeperMissing => 7,
+            ErrorCode::MemoryLimit => 8,
+            ErrorCode::SpendLimit => 10,
+        }
+    }
+
+    /// Name used in the JSON error document
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ErrorCode::Failed => "failed",
+            ErrorCode::Config => "config",
+            ErrorCode::ParseFailure => "parse_failure",
+            ErrorCode::NoData => "no_data",
+            ErrorCode::KeeperMissing => "keeper_missing",
+            ErrorCode::MemoryLimit => "memory_limit",
+            ErrorCode::SpendLimit => "spend_limit",
+        }
+    }
+}
+
+/// A failed command and the kind of failure
+#[derive(Debug)]
+pub struct CliError {
+    pub code: ErrorCode,
+    pub error: Error,
+}
+
+impl CliError {
+    /// A configuration that failed to load
+    pub fn config(error: anyhow::Error) -> Self {
+        Self {
+            code: ErrorCode::Config,
+            error: Error::Other(error),
+        }
+    }
+
+    /// The error as `{"error": {"code": ..., "message": ...}}`
+    pub fn to_json(&self) -> String {
+        #[derive(Serialize)]
+        struct Body<'a> {
+            code: &'a str,
+            message: String,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            breaches: Option<&'a [LimitBreach]>,
+        }
+        #[derive(Serialize)]
+        struct Document<'a> {
+            error: Body<'a>,
+        }
+
+        let document = Document {
+            error: Body {
+                code: self.code.as_str(),
+                message: self.error.to_string(),
+                breaches: match &self.error {
+                    Error::SpendLimitExceeded(exceeded) => Some(&exceeded.breaches),
+                    _ => None,
+                },
+            },
+        };
+        serde_json::to_string(&document).expect("error document serializes")
+    }
+}
+
+impl From<anyhow::Error> for CliError {
+    fn from(error: anyhow::Error) -> Self {
+        let error = Error::from(error);
+        let code = match &error {
+            Error::ErrorBudgetExceeded(_) => ErrorCode::ParseFailure,
+            Error::NoDataFound(_) => ErrorCode::NoData,
+            Error::KeeperNotFound(_) => ErrorCode::KeeperMissing,
+            Error::MemoryLimitExceeded(_) => ErrorCode::MemoryLimit,
+            Error::SpendLimitExceeded(_) => ErrorCode::SpendLimit,
+            Error::Other(_) => ErrorCode::Failed,
+        };
+        Self { code, error }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::{NoDataFound, SpendLimitExceeded};
+    use crate::models::MicroDollars;
+
+    #[test]
+    fn test_errors_are_classified_and_rendered_as_json() {
+        let missing = anyhow::Error::new(NoDataFound {
+            message: "No \"backups\" yet".to_string(),
+        })
+        .context("Failed to load sessions");
+        let error = CliError::from(missing);
+        assert_eq!(error.code, ErrorCode::NoData);
+        assert_eq!(error.code.exit_code(), 6);
+        assert_eq!(
+            error.to_json(),
+            r#"{"error":{"code":"no_data","message":"No \"backups\" yet"}}"#
+        );
+
+        let over = CliError::from(anyhow::Error::new(SpendLimitExceeded {
+            breaches: vec![LimitBreach {
+                limit_kind: "daily",
+                period: "2025-06-01".to_string(),
+                spent: MicroDollars::from_dollars(30.0),
+                limit: MicroDollars::from_dollars(25.0),
+            }],
+        }));
+        assert_eq!(over.code.exit_code(), 10);
+        let json: serde_json::Value = serde_json::from_str(&over.to_json()).unwrap();
+        assert_eq!(json["error"]["code"], "spend_limit");
+        assert_eq!(json["error"]["breaches"][0]["spent"], 30.0);
+
+        let other = CliError::from(anyhow::anyhow!("boom"));
+        assert_eq!(other.code, ErrorCode::Failed);
+        assert_eq!(other.code.exit_code(), 1);
+    }
+}
@@ -0,0 +1,65 @@
+//! Export command implementation
+//!
+//! Writes the deduplicated usage entries to a Parquet, JSON Lines or SQLite
+//! file (see [`crate::export`]) with a manifest next to it, so the file can
+//! later be checked with `verify-export`.
+
+use anyhow::Result;
+use colored::Colorize;
+use std::path::Path;
+
+use crate::analyzer::ClaudeUsageAnalyzer;
+use crate::config::get_config;
+use crate::dedup::ProcessOptions;
+use crate::export::{write_records, ExportFormat};
+use crate::export_manifest::write_manifest;
+use crate::redaction::ProjectRedactor;
+use crate::reports::{print_partial, print_skipped};
+
+/// Export the entries `options` select to `out` in `format`
+pub fn run_export(
+    analyzer: &ClaudeUsageAnalyzer,
+    options: &ProcessOptions,
+    format: ExportFormat,
+    out: &Path,
+) -> Result<()> {
+    let (mut records, skipped) = analyzer.export_records(options)?;
+    if let Some(redactor) = ProjectRedactor::for_run(options.redact_projects)? {
+        redactor.redact_records(&mut records);
+    }
+    let partial = options.is_cancelled();
+    write_records(out, format, &records)?;
+    let manifest = write_manifest(out, get_config())?;
+
+    if options.json_output {
+        let mut output = serde_json::json!({
+            "path": out,
+            "format": format.as_str(),
+            "entries": records.len(),
+            "manifest": manifest,
+        });
+        if !skipped.is_empty() {
+            output["metadata"]["skipped"] = serde_json::json!(skipped);
+        }
+        if partial {
+            output["metadata"]["partial"] = serde_json::Value::Bool(true);
+        }
+        println!("{}", serde_json::to_string_pretty(&output)?);
+        return Ok(());
+    }
+
+    if partial {
+        print_partial();
+    }
+    println!(
+        "{} Exported {} entries to {}",
+        "✅".bright_green(),
+        records.len().to_string().bright_white().bold(),
+        out.display().to_string().bright_cyan()
+    );
+    println!("   Manifest: {}", manifest.display());
+    if options.verbose {
+        print_skipped(&skipped);
+    }
+    Ok(())
+}
@@ -0,0 +1,51 @@
+//! Hook command implementation
+//!
+//! Installs the git hook that adds a Claude usage trailer to commits, and
+//! prints the trailer for it (see [`crate::commit_trailer`]).
+
+use anyhow::{Context, Result};
+use chrono::{Local, Utc};
+use std::path::PathBuf;
+
+use crate::analyzer::ClaudeUsageAnalyzer;
+use crate::commit_trailer::{self, TRAILER_KEY};
+use crate::file_discovery::ProjectDirFilter;
+
+/// Install the `prepare-commit-msg` hook in the current repository
+pub fn run_hook_install(force: bool) -> Result<()> {
+    let cwd = std::env::current_dir().context("Failed to get the current directory")?;
+    let hooks_dir = commit_trailer::hooks_dir(&cwd)?;
+    // The hook runs this binary, so it keeps working without claude-usage on PATH
+    let program = std::env::current_exe().unwrap_or_else(|_| PathBuf::from("claude-usage"));
+    let path = commit_trailer::install_hook(&hooks_dir, &program, force)?;
+
+    println!("✅ Installed commit hook at {}", path.display());
+    println!(
+        "   Commits now get a {} trailer with the Claude cost since the previous commit",
+        TRAILER_KEY
+    );
+    Ok(())
+}
+
+/// Print the trailer for the current repository's usage since its last
+/// commit, or nothing when there was none
+pub fn run_hook_trailer() -> Result<()> {
+    let cwd = std::env::current_dir().context("Failed to get the current directory")?;
+    let root = commit_trailer::repo_root(&cwd)?;
+    // Before the first commit, count today's usage
+    let since = match commit_trailer::last_commit_time(&root)? {
+        Some(time) => time,
+        None => Local::now()
+            .date_naive()
+            .and_hms_opt(0, 0, 0)
+            .and_then(|start| start.and_local_timezone(Local).earliest())
+            .map_or_else(Utc::now, |start| start.with_timezone(&Utc)),
+    };
+    let project = ProjectDirFilter::new(&root.to_string_lossy())?;
+
+    let totals = ClaudeUsageAnalyzer::new().usage_since(since, &project)?;
+    if let Some(trailer) = commit_trailer::format_trailer(&totals) {
+        println!("{}", trailer);
+    }
+    Ok(())
+}
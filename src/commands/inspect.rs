@@ -0,0 +1,91 @@
+//! Inspect command implementation
+//!
+//! Prints the breakdown of a single JSONL file produced by
+//! [`crate::inspect::inspect_file`].
+
+use anyhow::Result;
+use colored::Colorize;
+use std::path::Path;
+
+use crate::inspect::inspect_file;
+
+/// Errors listed in text output before the rest are summarized
+const MAX_LISTED_ERRORS: usize = 20;
+
+/// Inspect one JSONL file and print what reports would see in it
+pub fn run_inspect(file: &Path, json: bool) -> Result<()> {
+    let inspection = inspect_file(file)?;
+
+    if json {
+        let mut output = serde_json::to_value(&inspection)?;
+        output["dedupCoverage"] = serde_json::json!(inspection.dedup_coverage());
+        println!("{}", serde_json::to_string_pretty(&output)?);
+        return Ok(());
+    }
+
+    println!("\n{}", "JSONL File Inspection".bright_white().bold());
+    println!("   File: {}", file.display().to_string().bright_cyan());
+
+    println!("\n{} Lines:", "📄".bright_yellow());
+    println!("   Total: {}", inspection.lines);
+    println!(
+        "   Usage entries: {}",
+        inspection.entries.to_string().bright_white().bold()
+    );
+    println!("   Without usage: {}", inspection.non_usage_lines);
+    println!("   Blank: {}", inspection.blank_lines);
+    println!(
+        "   Errors: {}",
+        if inspection.errors.is_empty() {
+            "0".bright_green()
+        } else {
+            inspection.errors.len().to_string().bright_red()
+        }
+    );
+    for error in inspection.errors.iter().take(MAX_LISTED_ERRORS) {
+        println!("     line {}: {}", error.line, error.reason);
+    }
+    if inspection.errors.len() > MAX_LISTED_ERRORS {
+        println!(
+            "     ... and {} more (use --json for the full list)",
+            inspection.errors.len() - MAX_LISTED_ERRORS
+        );
+    }
+
+    println!("\n{} Totals:", "📊".bright_blue());
+    println!(
+        "   Cost: {}",
+        format!("${:.4}", inspection.total_cost).bright_green().bold()
+    );
+    println!(
+        "   Tokens: {} input, {} output, {} cache write, {} cache read",
+        inspection.input_tokens,
+        inspection.output_tokens,
+        inspection.cache_creation_tokens,
+        inspection.cache_read_tokens
+    );
+    match (&inspection.first_timestamp, &inspection.last_timestamp) {
+        (Some(first), Some(last)) => println!("   Time range: {} → {}", first, last),
+        _ => println!("   Time range: {}", "n/a".bright_black()),
+    }
+
+    if !inspection.models.is_empty() {
+        println!("\n{} Models:", "🤖".bright_magenta());
+        for (model, count) in &inspection.models {
+            println!("   {}: {} entries", model.bright_cyan(), count);
+        }
+    }
+
+    println!("\n{} Deduplication:", "🔑".bright_yellow());
+    println!(
+        "   Entries with a dedup key: {} ({:.0}%)",
+        inspection.with_dedup_key,
+        inspection.dedup_coverage()
+    );
+    println!(
+        "   Repeated keys within this file: {}",
+        inspection.duplicate_keys
+    );
+
+    Ok(())
+}
@@ -4,19 +4,39 @@
 //! monitoring of Claude usage through integration with claude-keeper.
 
 use anyhow::Result;
+use colored::Colorize;
 use tokio::sync::mpsc;
-use tracing::{error, info};
+use tracing::{error, info, warn};
 
-use crate::live::orchestrator::LiveOrchestrator;
+use crate::live::baseline::refresh_baseline;
+use crate::live::filter::ProjectFilter;
+use crate::live::orchestrator::{save_dedup_state, LiveOrchestrator};
+use crate::live::reconcile::{self, ReconcileContext, Reconciliation};
 use crate::live::LiveUpdate;
 
-/// Run live mode with optional baseline
-pub async fn run_live_mode(no_baseline: bool) -> Result<()> {
+/// Run live mode with optional baseline, limited to projects matching `projects`
+///
+/// With `json`, the exit summary (totals and burn-rate history) is printed as
+/// JSON once the dashboard closes. With `reconcile_on_exit`, today's live
+/// totals are then compared with the daily report (see [`reconcile`]).
+pub async fn run_live_mode(
+    no_baseline: bool,
+    projects: &[String],
+    json: bool,
+    reconcile_on_exit: bool,
+) -> Result<()> {
+    let project_filter = ProjectFilter::new(projects)?;
+    let has_project_filter = !project_filter.is_empty();
+
+
     // Welcome message for users
     println!("🚀 Starting Claude Usage Live Monitor");
     println!();
     
-    if no_baseline {
+    if !project_filter.is_empty() {
+        println!("🔎 Watching projects matching: {}", projects.join(", "));
+        println!("💡 Baseline totals cover all projects, so only new usage is shown");
+    } else if no_baseline {
         println!("⚠️  Running without baseline data (--no-baseline specified)");
         println!("💡 This means you'll only see new usage from this point forward");
     } else {
@@ -25,16 +45,23 @@ pub async fn run_live_mode(no_baseline: bool) -> Result<()> {
     }
     println!();
 
-    info!(no_baseline, "Starting live mode");
+    info!(no_baseline, ?projects, "Starting live mode");
+
+    // Today's cost in the backups, which live updates are added to
+    let launch_day = chrono::Local::now().date_naive();
+    let launch_cost = reconcile_on_exit.then(|| reconcile::baseline_cost(launch_day));
 
     // Create communication channel for updates
     let (tx, rx) = mpsc::channel::<LiveUpdate>(100);
 
     // Create the orchestrator
-    let mut orchestrator = LiveOrchestrator::new(no_baseline).await?;
+    let mut orchestrator = LiveOrchestrator::new(no_baseline, project_filter).await?;
     
     // Extract baseline before moving orchestrator into spawn task
     let baseline = orchestrator.get_baseline();
+    let health = orchestrator.health();
+    let budget = orchestrator.budget();
+    let dedup_state = orchestrator.dedup_state();
     
     // Start the orchestrator in a background task
     tokio::spawn(async move {
@@ -48,10 +75,86 @@ pub async fn run_live_mode(no_baseline: bool) -> Result<()> {
     println!("💡 Use Ctrl+C to exit");
     println!();
 
-    // Run the display with baseline and receiver
-    crate::display::run_display(baseline, rx).await?;
+    // Run the display with baseline, receiver, instance health and budget
+    let mut summary = crate::display::run_display(baseline, rx, health, budget).await?;
+
+    // The orchestrator task is not waited for, so its keys are saved here
+    if let Some(state) = &dedup_state {
+        save_dedup_state(state);
+    }
 
-    println!("👋 Live monitoring stopped. Thank you for using Claude Usage!");
+    if let Some(launch_cost) = launch_cost {
+        if !json {
+            println!("🔄 Reconciling today's live totals with the daily report...");
+        }
+        let day = chrono::Local::now().date_naive();
+        let backup_refreshed = match refresh_baseline().await {
+            Ok(_) => true,
+            Err(e) => {
+                warn!(error = %e, "Failed to refresh backups before reconciling");
+                false
+            }
+        };
+        let daily_cost = reconcile::daily_cost(day).await?;
+        let baseline_cost = if day == launch_day { launch_cost } else { 0.0 };
+        summary.reconciliation = Some(reconcile::reconcile(
+            day,
+            baseline_cost,
+            &summary.today,
+            daily_cost,
+            ReconcileContext {
+                project_filter: has_project_filter,
+                backup_refreshed,
+            },
+        ));
+    }
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&summary)?);
+    } else {
+        if let Some(reconciliation) = &summary.reconciliation {
+            print_reconciliation(reconciliation);
+        }
+        println!("👋 Live monitoring stopped. Thank you for using Claude Usage!");
+    }
     info!("Live mode completed");
     Ok(())
-}
\ No newline at end of file
+}
+
+/// Print the live and daily totals of the day and any difference
+fn print_reconciliation(reconciliation: &Reconciliation) {
+    println!(
+        "\n{}",
+        format!("Reconciliation for {}", reconciliation.date)
+            .bright_white()
+            .bold()
+    );
+    println!(
+        "   Live:  ${:.2} (${:.2} in backups at launch + ${:.2} live updates)",
+        reconciliation.live_cost, reconciliation.baseline_cost, reconciliation.live_updates_cost
+    );
+    println!("   Daily: ${:.2}", reconciliation.daily_cost);
+
+    if reconciliation.matches {
+        println!("   {}", "✅ Live and daily totals match".bright_green());
+    } else {
+        let direction = if reconciliation.difference > 0.0 {
+            "higher"
+        } else {
+            "lower"
+        };
+        println!(
+            "   {}",
+            format!(
+                "⚠️  Live is ${:.2} {} than daily. Likely causes:",
+                reconciliation.difference.abs(),
+                direction
+            )
+            .bright_yellow()
+        );
+        for cause in &reconciliation.likely_causes {
+            println!("     - {}", cause);
+        }
+    }
+    println!();
+}
@@ -0,0 +1,76 @@
+//! Metrics command implementation
+//!
+//! Summarizes the local self-metrics file written when `metrics.enabled` is
+//! set, so the numbers can be shared when reporting performance problems.
+
+use anyhow::Result;
+use colored::Colorize;
+
+use crate::config::get_config;
+use crate::self_metrics::{feature_usage, load_metrics, summarize};
+
+/// Show a summary of recorded command metrics
+pub fn run_metrics_show(json: bool) -> Result<()> {
+    let config = &get_config().metrics;
+    let metrics = load_metrics(&config.file)?;
+    let summary = summarize(&metrics);
+    let features = feature_usage(&metrics);
+
+    if json {
+        let output = serde_json::json!({
+            "enabled": config.enabled,
+            "file": config.file,
+            "runs": metrics.len(),
+            "firstRecorded": metrics.first().map(|m| &m.timestamp),
+            "lastRecorded": metrics.last().map(|m| &m.timestamp),
+            "commands": summary,
+            "features": features,
+        });
+        println!("{}", serde_json::to_string_pretty(&output)?);
+        return Ok(());
+    }
+
+    println!("\n{}", "Claude Usage Self-Metrics".bright_white().bold());
+    println!("   File: {}", config.file.display().to_string().bright_cyan());
+    if !config.enabled {
+        println!(
+            "   {}",
+            "Recording is disabled; set [metrics] enabled = true or CLAUDE_USAGE_METRICS=true"
+                .bright_black()
+        );
+    }
+
+    let (Some(first), Some(last)) = (metrics.first(), metrics.last()) else {
+        println!("\nNo metrics recorded yet.");
+        return Ok(());
+    };
+    println!(
+        "   {} runs from {} to {}",
+        metrics.len().to_string().bright_white().bold(),
+        first.timestamp,
+        last.timestamp
+    );
+
+    println!("\n{} Commands:", "⏱️".bright_yellow());
+    for command in &summary {
+        println!(
+            "   {}: {} runs, {} failed — avg {} ms, median {} ms, max {} ms, ~{} sessions",
+            command.command.bright_white().bold(),
+            command.runs,
+            command.failures,
+            command.avg_duration_ms.to_string().bright_green(),
+            command.median_duration_ms,
+            command.max_duration_ms,
+            command.avg_sessions
+        );
+    }
+
+    if !features.is_empty() {
+        println!("\n{} Features used:", "🔧".bright_blue());
+        for (feature, count) in &features {
+            println!("   {}: {} runs", feature.bright_cyan(), count);
+        }
+    }
+
+    Ok(())
+}
@@ -4,4 +4,19 @@
 //! claude-usage tool. Each command is implemented as a separate module with
 //! its own logic and configuration.
 
-pub mod live;
\ No newline at end of file
+pub mod archive;
+pub mod blocks;
+pub mod check;
+pub mod doctor;
+pub mod error;
+pub mod export;
+pub mod hook;
+pub mod inspect;
+pub mod live;
+pub mod metrics;
+pub mod notes;
+pub mod pricing;
+pub mod reprocess;
+pub mod status;
+pub mod verify_export;
+pub mod watch;
\ No newline at end of file
@@ -0,0 +1,21 @@
+//! Note command implementation
+//!
+//! Records notes against a day so later reviews of the daily report can see
+//! why usage spiked.
+
+use anyhow::Result;
+use chrono::NaiveDate;
+
+use crate::config::get_config;
+use crate::notes::add_note;
+
+/// Add a note to `date` (YYYY-MM-DD)
+pub fn run_note_add(date: &str, text: &str) -> Result<()> {
+    let parsed = NaiveDate::parse_from_str(date, "%Y-%m-%d")
+        .map_err(|_| anyhow::anyhow!("Invalid note date format: {}. Use YYYY-MM-DD", date))?;
+
+    let file = &get_config().notes.file;
+    add_note(file, parsed, text)?;
+    println!("📝 Added note for {}", parsed.format("%Y-%m-%d"));
+    Ok(())
+}
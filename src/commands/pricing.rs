@@ -0,0 +1,134 @@
+//! Pricing command implementation
+//!
+//! `pricing refresh` downloads the LiteLLM pricing snapshot that costs are
+//! computed from offline, and `pricing show` prints the rates one model is
+//! priced at and where they came from, including `[pricing.overrides]`.
+
+use anyhow::Result;
+use chrono::Utc;
+use colored::Colorize;
+
+use crate::config::get_config;
+use crate::pricing::{model_rates, RatesSource};
+use crate::pricing_snapshot;
+
+/// Download the pricing table and save it as the snapshot
+#[cfg(feature = "pricing")]
+pub async fn run_pricing_refresh(json: bool) -> Result<()> {
+    let config = &get_config().pricing;
+    let snapshot = pricing_snapshot::PricingSnapshot::download().await?;
+    snapshot.save(&config.snapshot_file)?;
+
+    if json {
+        let output = serde_json::json!({
+            "file": config.snapshot_file,
+            "fetchedAt": snapshot.fetched_at,
+            "models": snapshot.models.len(),
+        });
+        println!("{}", serde_json::to_string_pretty(&output)?);
+        return Ok(());
+    }
+
+    println!(
+        "💾 Saved pricing for {} Claude models to {}",
+        snapshot.models.len().to_string().bright_white().bold(),
+        config.snapshot_file.display().to_string().bright_cyan()
+    );
+    Ok(())
+}
+
+/// Without the `pricing` feature there is nothing to download with
+#[cfg(not(feature = "pricing"))]
+pub async fn run_pricing_refresh(_json: bool) -> Result<()> {
+    anyhow::bail!(
+        "claude-usage was built without the `pricing` feature; rebuild with it to download pricing"
+    )
+}
+
+/// Show the per-million-token rates `model` is priced at
+pub fn run_pricing_show(model: &str, json: bool) -> Result<()> {
+    let config = &get_config().pricing;
+    let (rates, source) = model_rates(model);
+    let snapshot = pricing_snapshot::global();
+    let now = Utc::now();
+    let per_million = |rate: f64| rate * 1_000_000.0;
+
+    if json {
+        let output = serde_json::json!({
+            "model": model,
+            "source": match source {
+                RatesSource::Snapshot(_) => "snapshot",
+                RatesSource::Override(_) => "override",
+                RatesSource::BuiltIn => "builtIn",
+            },
+            "snapshotModel": match source {
+                RatesSource::Snapshot(name) => Some(name),
+                RatesSource::Override(_) | RatesSource::BuiltIn => None,
+            },
+            "overrideModel": match source {
+                RatesSource::Override(name) => Some(name),
+                RatesSource::Snapshot(_) | RatesSource::BuiltIn => None,
+            },
+            "ratesPerMillion": {
+                "input": per_million(rates.input),
+                "output": per_million(rates.output),
+                "cacheCreation": per_million(rates.cache_creation),
+                "cacheRead": per_million(rates.cache_read),
+            },
+            "snapshot": snapshot.map(|snapshot| serde_json::json!({
+                "file": config.snapshot_file,
+                "fetchedAt": snapshot.fetched_at,
+                "ageDays": snapshot.age_days(now),
+                "stale": snapshot.is_stale(now, config.max_age_days),
+            })),
+        });
+        println!("{}", serde_json::to_string_pretty(&output)?);
+        return Ok(());
+    }
+
+    println!("\n{}", format!("Pricing: {}", model).bright_white().bold());
+    match source {
+        RatesSource::Snapshot(name) => {
+            println!("   Source: pricing snapshot ({})", name.bright_cyan())
+        }
+        RatesSource::Override(name) => {
+            println!("   Source: pricing override ({})", name.bright_cyan())
+        }
+        RatesSource::BuiltIn => println!("   Source: {}", "built-in rates".bright_cyan()),
+    }
+    println!("   Per million tokens:");
+    println!("     Input:        ${:.4}", per_million(rates.input));
+    println!("     Output:       ${:.4}", per_million(rates.output));
+    println!(
+        "     Cache write:  ${:.4}",
+        per_million(rates.cache_creation)
+    );
+    println!("     Cache read:   ${:.4}", per_million(rates.cache_read));
+
+    match snapshot {
+        Some(snapshot) => {
+            println!(
+                "\n   Snapshot: {} (downloaded {}, {} days ago)",
+                config.snapshot_file.display(),
+                snapshot.fetched_at.format("%Y-%m-%d"),
+                snapshot.age_days(now)
+            );
+            if snapshot.is_stale(now, config.max_age_days) {
+                println!(
+                    "   {}",
+                    format!(
+                        "⚠️  Older than {} days; run `claude-usage pricing refresh`",
+                        config.max_age_days
+                    )
+                    .bright_yellow()
+                );
+            }
+        }
+        None => println!(
+            "\n   {}",
+            "No pricing snapshot; run `claude-usage pricing refresh` to download one"
+                .bright_black()
+        ),
+    }
+    Ok(())
+}
@@ -0,0 +1,152 @@
+//! Reprocess command implementation
+//!
+//! Totals the entries of a date range under the dedup settings reports use
+//! and under each `--settings` given (see [`crate::reprocess`]), and prints
+//! them side by side.
+
+use anyhow::Result;
+use colored::Colorize;
+
+use crate::analyzer::ClaudeUsageAnalyzer;
+use crate::dedup::ProcessOptions;
+use crate::models::MicroDollars;
+use crate::reports::{print_partial, print_skipped};
+use crate::reprocess::{reprocess, DedupSettings, ReprocessTotals};
+
+/// Width of the row label column
+const LABEL_WIDTH: usize = 20;
+
+/// Compare the totals of the entries `options` select under `settings`
+pub fn run_reprocess(
+    analyzer: &ClaudeUsageAnalyzer,
+    options: &ProcessOptions,
+    settings: &[DedupSettings],
+) -> Result<()> {
+    let (entries, skipped) = analyzer.raw_entries(options)?;
+    let partial = options.is_cancelled();
+    let configurations: Vec<ReprocessTotals> = std::iter::once(DedupSettings::default())
+        .chain(settings.iter().copied())
+        .map(|settings| reprocess(&entries, settings, options.cost_mode))
+        .collect();
+
+    if options.json_output {
+        let mut output = serde_json::json!({
+            "entriesRead": entries.len(),
+            "configurations": configurations,
+        });
+        if let Some(since) = options.since_date {
+            output["since"] = serde_json::json!(since.format("%Y-%m-%d").to_string());
+        }
+        if let Some(until) = options.until_date {
+            output["until"] = serde_json::json!(until.format("%Y-%m-%d").to_string());
+        }
+        if !skipped.is_empty() {
+            output["metadata"]["skipped"] = serde_json::json!(skipped);
+        }
+        if partial {
+            output["metadata"]["partial"] = serde_json::Value::Bool(true);
+        }
+        println!("{}", serde_json::to_string_pretty(&output)?);
+        return Ok(());
+    }
+
+    if partial {
+        print_partial();
+    }
+    println!("\n{}", "=".repeat(80).bright_cyan());
+    println!(
+        "{}",
+        "Claude Code Usage - Dedup Settings Compared"
+            .bright_white()
+            .bold()
+    );
+    println!("{}", "=".repeat(80).bright_cyan());
+    let range = match (options.since_date, options.until_date) {
+        (None, None) => "all dates".to_string(),
+        (since, until) => format!(
+            "{} to {}",
+            since.map_or("start".to_string(), |d| d.format("%Y-%m-%d").to_string()),
+            until.map_or("today".to_string(), |d| d.format("%Y-%m-%d").to_string())
+        ),
+    };
+    println!(
+        "\n{} {} entries read, {}\n",
+        "🔁".bright_yellow(),
+        entries.len().to_string().bright_white().bold(),
+        range
+    );
+
+    let current = configurations[0].total_cost;
+    let deltas: Vec<String> = configurations
+        .iter()
+        .map(|totals| cost_delta(totals.total_cost, current))
+        .collect();
+    let widths: Vec<usize> = configurations
+        .iter()
+        .zip(&deltas)
+        .map(|(totals, delta)| totals.label.len().max(delta.len()).max(12))
+        .collect();
+    let row = |name: &str, cells: Vec<String>| {
+        let cells: Vec<String> = cells
+            .iter()
+            .zip(&widths)
+            .map(|(cell, width)| format!("{:>width$}", cell, width = width))
+            .collect();
+        format!(
+            "{:<width$}  {}",
+            name,
+            cells.join("  "),
+            width = LABEL_WIDTH
+        )
+    };
+    let column = |value: fn(&ReprocessTotals) -> u64| -> Vec<String> {
+        configurations
+            .iter()
+            .map(|totals| value(totals).to_string())
+            .collect()
+    };
+
+    let labels = configurations.iter().map(|t| t.label.clone()).collect();
+    println!("{}", row("", labels).bright_white().bold());
+    println!("{}", row("Entries kept", column(|t| t.entries)));
+    println!("{}", row("Duplicates dropped", column(|t| t.duplicates)));
+    println!("{}", row("Input tokens", column(|t| t.input_tokens)));
+    println!("{}", row("Output tokens", column(|t| t.output_tokens)));
+    println!(
+        "{}",
+        row("Cache write tokens", column(|t| t.cache_creation_tokens))
+    );
+    println!(
+        "{}",
+        row("Cache read tokens", column(|t| t.cache_read_tokens))
+    );
+    let costs = configurations
+        .iter()
+        .map(|totals| format!("${:.2}", totals.total_cost))
+        .collect();
+    println!("{}", row("Cost", costs).bright_green());
+    println!("{}", row("Cost vs current", deltas));
+
+    if options.verbose {
+        print_skipped(&skipped);
+    }
+    Ok(())
+}
+
+/// `cost - current` with its percentage of `current`
+fn cost_delta(cost: MicroDollars, current: MicroDollars) -> String {
+    let delta = cost - current;
+    if delta == MicroDollars::ZERO {
+        return "-".to_string();
+    }
+    let sign = if delta > MicroDollars::ZERO { "+" } else { "-" };
+    let amount = format!("{}${:.2}", sign, delta.to_dollars().abs());
+    if current == MicroDollars::ZERO {
+        return amount;
+    }
+    format!(
+        "{} ({:+.1}%)",
+        amount,
+        delta.to_dollars() / current.to_dollars() * 100.0
+    )
+}
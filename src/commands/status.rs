@@ -0,0 +1,26 @@
+//! Status command implementation
+//!
+//! Prints the status bar totals built by [`crate::status::status`] once, as
+//! a single line or compact JSON. `--listen` is handled by
+//! [`crate::web::run_status_server`] instead.
+
+use anyhow::Result;
+
+use crate::analyzer::ClaudeUsageAnalyzer;
+use crate::parser::FileParser;
+use crate::status::{status, summary_line};
+
+/// Print today's and this month's totals from the JSONL logs
+pub fn run_status(json: bool, exclude_vms: bool, monthly_budget: Option<f64>) -> Result<()> {
+    let claude_paths = FileParser::new().discover_claude_paths(exclude_vms)?;
+    let sessions = ClaudeUsageAnalyzer::sessions_from_logs(&claude_paths)?;
+    let today = chrono::Local::now().date_naive();
+    let current = status(&sessions, today, chrono::Utc::now(), monthly_budget);
+
+    if json {
+        println!("{}", serde_json::to_string(&current)?);
+    } else {
+        println!("{}", summary_line(&current));
+    }
+    Ok(())
+}
@@ -0,0 +1,67 @@
+//! Verify-export command implementation
+//!
+//! Checks the artifacts listed in an export manifest against their recorded
+//! SHA-256 hashes and prints which ones changed or went missing.
+
+use anyhow::Result;
+use colored::Colorize;
+use std::path::Path;
+
+use crate::config::get_config;
+use crate::export_manifest::{verify, ArtifactStatus};
+
+/// Verify the manifest at `path`, returning whether every artifact matched
+pub fn run_verify_export(path: &Path, json: bool) -> Result<bool> {
+    let report = verify(path, get_config())?;
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+        return Ok(report.passed());
+    }
+
+    println!("\n{}", "Export Verification".bright_white().bold());
+    println!(
+        "   Manifest: {}",
+        report.manifest.display().to_string().bright_cyan()
+    );
+    println!(
+        "   Generated {} by claude-usage {}",
+        report.generated_at, report.tool_version
+    );
+    if report.config_matches {
+        println!("   Configuration: {}", "unchanged".bright_green());
+    } else {
+        println!(
+            "   Configuration: {}",
+            "differs from the one used for the export".bright_yellow()
+        );
+    }
+
+    println!();
+    for check in &report.artifacts {
+        let status = match check.status {
+            ArtifactStatus::Ok => "✓ ok".bright_green(),
+            ArtifactStatus::Modified => "✗ modified".bright_red(),
+            ArtifactStatus::Missing => "✗ missing".bright_red(),
+        };
+        println!("   {} {}", status, check.path.display());
+        if let Some(actual) = &check.actual_sha256 {
+            println!("     now {}", actual.bright_black());
+        }
+    }
+
+    if report.passed() {
+        println!(
+            "\n{} All {} artifacts match",
+            "✅".bright_green(),
+            report.artifacts.len()
+        );
+    } else {
+        println!(
+            "\n{} Some artifacts do not match the manifest",
+            "❌".bright_red()
+        );
+    }
+
+    Ok(report.passed())
+}
@@ -0,0 +1,44 @@
+//! Watch mode for the daily report
+//!
+//! `daily --watch [SECS]` reruns the report every SECS seconds and redraws it
+//! in place, like `watch claude-usage daily`. The analyzer keeps a
+//! [`crate::parquet::reader::ParquetCache`], so each cycle only reads the
+//! backup files claude-keeper rewrote since the previous one.
+
+use anyhow::Result;
+use colored::Colorize;
+use std::io::Write;
+use std::time::Duration;
+
+use crate::analyzer::ClaudeUsageAnalyzer;
+use crate::dedup::ProcessOptions;
+
+/// Redraw the daily report every `interval` until the process is stopped
+pub async fn run_daily_watch(
+    analyzer: &mut ClaudeUsageAnalyzer,
+    options: ProcessOptions,
+    interval: Duration,
+) -> Result<()> {
+    analyzer.enable_parquet_cache();
+
+    loop {
+        // Clear the screen and move the cursor home before redrawing
+        print!("\x1B[2J\x1B[H");
+        // A failed cycle is shown and retried on the next one
+        if let Err(e) = analyzer.run_command("daily", options.clone()).await {
+            eprintln!("Error: {:#}", e);
+        }
+        println!(
+            "\n{}",
+            format!(
+                "Updated {} · refreshing every {}s · Ctrl+C to stop",
+                chrono::Local::now().format("%H:%M:%S"),
+                interval.as_secs()
+            )
+            .bright_black()
+        );
+        let _ = std::io::stdout().flush();
+
+        tokio::time::sleep(interval).await;
+    }
+}
@@ -0,0 +1,195 @@
+//! Claude usage trailers on git commits
+//!
+//! `claude-usage hook install` writes a `prepare-commit-msg` hook into the
+//! current repository. On each commit the hook runs `claude-usage hook
+//! trailer`, which totals the usage of the repository's project since the
+//! previous commit (see
+//! [`usage_since`](crate::analyzer::ClaudeUsageAnalyzer::usage_since)) and
+//! prints a trailer such as
+//!
+//! ```text
+//! Claude-Usage: $0.42 (12.3K tokens, 2 sessions)
+//! ```
+//!
+//! which the hook adds with `git interpret-trailers`. The project is the one
+//! Claude Code names after the repository root, so sessions started in a
+//! subdirectory are not counted. Nothing is added when there was no usage,
+//! or for merges, squashes and amended commits.
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::models::DayTotals;
+
+/// Key of the trailer the hook adds
+pub const TRAILER_KEY: &str = "Claude-Usage";
+
+/// Name of the git hook
+const HOOK_NAME: &str = "prepare-commit-msg";
+
+/// Line marking a hook as written by `hook install`
+const HOOK_MARKER: &str = "# Installed by claude-usage hook install";
+
+/// The trailer line for `totals`, or `None` when there was no usage
+pub fn format_trailer(totals: &DayTotals) -> Option<String> {
+    if totals.entries == 0 {
+        return None;
+    }
+    let tokens = totals.input_tokens
+        + totals.output_tokens
+        + totals.cache_creation_tokens
+        + totals.cache_read_tokens;
+    Some(format!(
+        "{}: ${:.2} ({} tokens, {} session{})",
+        TRAILER_KEY,
+        totals.total_cost,
+        format_tokens(tokens),
+        totals.sessions,
+        if totals.sessions == 1 { "" } else { "s" }
+    ))
+}
+
+/// Token count with K or M units
+fn format_tokens(tokens: u64) -> String {
+    if tokens >= 1_000_000 {
+        format!("{:.1}M", tokens as f64 / 1_000_000.0)
+    } else if tokens >= 1_000 {
+        format!("{:.1}K", tokens as f64 / 1_000.0)
+    } else {
+        tokens.to_string()
+    }
+}
+
+/// The hook script, running `program` for the trailer
+pub fn hook_script(program: &Path) -> String {
+    let program = program.to_string_lossy().replace('\'', r"'\''");
+    format!(
+        r#"#!/bin/sh
+{marker}
+# Adds a {key} trailer with the Claude cost of this repository since the
+# last commit. Remove this file to stop.
+
+case "$2" in
+    merge|squash|commit) exit 0 ;;
+esac
+
+trailer=$('{program}' hook trailer 2>/dev/null) || exit 0
+[ -n "$trailer" ] || exit 0
+git interpret-trailers --in-place --if-exists replace --trailer "$trailer" "$1"
+"#,
+        marker = HOOK_MARKER,
+        key = TRAILER_KEY,
+        program = program
+    )
+}
+
+/// Write the hook into `hooks_dir`, returning its path
+///
+/// A hook already there is only replaced when `hook install` wrote it, or
+/// with `force`.
+pub fn install_hook(hooks_dir: &Path, program: &Path, force: bool) -> Result<PathBuf> {
+    let path = hooks_dir.join(HOOK_NAME);
+    if let Ok(existing) = fs::read_to_string(&path) {
+        if !force && !existing.contains(HOOK_MARKER) {
+            anyhow::bail!(
+                "A {} hook already exists at {}; add `{} hook trailer` to it yourself, or pass --force to replace it",
+                HOOK_NAME,
+                path.display(),
+                program.display()
+            );
+        }
+    }
+
+    fs::create_dir_all(hooks_dir)
+        .with_context(|| format!("Failed to create {}", hooks_dir.display()))?;
+    fs::write(&path, hook_script(program))
+        .with_context(|| format!("Failed to write hook: {}", path.display()))?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o755))
+            .with_context(|| format!("Failed to make hook executable: {}", path.display()))?;
+    }
+    Ok(path)
+}
+
+/// Root of the repository containing `dir`
+pub fn repo_root(dir: &Path) -> Result<PathBuf> {
+    git(dir, &["rev-parse", "--show-toplevel"]).map(PathBuf::from)
+}
+
+/// Hooks directory of the repository containing `dir`, honouring `core.hooksPath`
+pub fn hooks_dir(dir: &Path) -> Result<PathBuf> {
+    let hooks = PathBuf::from(git(dir, &["rev-parse", "--git-path", "hooks"])?);
+    Ok(if hooks.is_absolute() {
+        hooks
+    } else {
+        dir.join(hooks)
+    })
+}
+
+/// Commit time of `HEAD`, or `None` before the first commit
+pub fn last_commit_time(dir: &Path) -> Result<Option<DateTime<Utc>>> {
+    let Ok(time) = git(dir, &["log", "-1", "--format=%cI"]) else {
+        return Ok(None);
+    };
+    let time = DateTime::parse_from_rfc3339(&time)
+        .with_context(|| format!("Unexpected commit time from git: {}", time))?;
+    Ok(Some(time.with_timezone(&Utc)))
+}
+
+/// Run git in `dir`, returning its trimmed output
+fn git(dir: &Path, args: &[&str]) -> Result<String> {
+    let output = Command::new("git")
+        .args(args)
+        .current_dir(dir)
+        .output()
+        .context("Failed to run git; is it installed?")?;
+    if !output.status.success() {
+        anyhow::bail!(
+            "git {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::MicroDollars;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_trailer_and_hook_install() {
+        let mut totals = DayTotals::default();
+        assert_eq!(format_trailer(&totals), None);
+        totals.total_cost = MicroDollars::from_dollars(0.4213);
+        totals.input_tokens = 2_000;
+        totals.output_tokens = 10_300;
+        totals.entries = 3;
+        totals.sessions = 1;
+        assert_eq!(
+            format_trailer(&totals).as_deref(),
+            Some("Claude-Usage: $0.42 (12.3K tokens, 1 session)")
+        );
+
+        let temp = TempDir::new().unwrap();
+        let hooks = temp.path().join("hooks");
+        let program = Path::new("/opt/bin/claude-usage");
+        let path = install_hook(&hooks, program, false).unwrap();
+        let script = fs::read_to_string(&path).unwrap();
+        assert!(script.contains("'/opt/bin/claude-usage' hook trailer"));
+
+        // Reinstalling replaces our own hook, but not someone else's
+        install_hook(&hooks, program, false).unwrap();
+        fs::write(&path, "#!/bin/sh\nexit 0\n").unwrap();
+        assert!(install_hook(&hooks, program, false).is_err());
+        install_hook(&hooks, program, true).unwrap();
+        assert!(fs::read_to_string(&path).unwrap().contains(HOOK_MARKER));
+    }
+}
@@ -8,6 +8,7 @@
 
 use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::env;
 use std::fs;
 use std::path::{Path, PathBuf};
@@ -40,6 +41,71 @@ pub struct Config {
 
     /// Live mode configuration
     pub live: LiveConfig,
+
+    /// Local self-metrics configuration
+    #[serde(default)]
+    pub metrics: MetricsConfig,
+
+    /// Day notes store
+    #[serde(default)]
+    pub notes: NotesConfig,
+
+    /// Cost-center allocations for `--group-by cost_center`
+    #[serde(default)]
+    pub allocations: AllocationsConfig,
+
+    /// Session branches recorded by the shell hook, for `--group-by branch`
+    #[serde(default)]
+    pub branches: BranchesConfig,
+
+    /// Compacted archives of old session logs
+    #[serde(default)]
+    pub archive: ArchiveConfig,
+
+    /// Cache of parsed session logs
+    #[serde(default)]
+    pub parse_cache: ParseCacheConfig,
+
+    /// Pricing snapshot written by `pricing refresh`
+    #[serde(default)]
+    pub pricing: PricingConfig,
+
+    /// Daily budget thresholds highlighted in live mode, and the monthly
+    /// budget `forecast` checks
+    #[serde(default)]
+    pub budget: BudgetConfig,
+
+    /// Shell commands run on activity events
+    #[serde(default)]
+    pub hooks: HooksConfig,
+
+    /// Webhooks notified when spend crosses a threshold
+    #[serde(default)]
+    pub notify: NotifyConfig,
+
+    /// Report state kept for `--diff-since-last-run`
+    #[serde(default)]
+    pub last_run: LastRunConfig,
+
+    /// How session durations are measured
+    #[serde(default)]
+    pub sessions: SessionsConfig,
+
+    /// Project names hidden from report output
+    #[serde(default)]
+    pub redaction: RedactionConfig,
+
+    /// What happens to entries with corrupted token counts
+    #[serde(default)]
+    pub quality: QualityConfig,
+
+    /// Plan ceilings the daily report measures 5-hour blocks against
+    #[serde(default)]
+    pub limits: LimitsConfig,
+
+    /// Container mode, writing only under one output directory
+    #[serde(default)]
+    pub container: ContainerConfig,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -55,6 +121,27 @@ pub struct ProcessingConfig {
     pub parallel_chunks: usize,
     pub max_retries: usize,
     pub progress_interval_mb: usize,
+    #[serde(default)]
+    pub estimate_missing_usage: bool,
+    #[serde(default = "default_chars_per_token")]
+    pub chars_per_token: f64,
+    /// Maximum files open for reading at once (0 = unlimited)
+    #[serde(default)]
+    pub max_open_files: usize,
+    /// Read throughput budget in MB/s (0 = unlimited)
+    #[serde(default)]
+    pub max_read_mb_per_sec: f64,
+    /// Lines longer than this (in KB) are skipped when reading JSONL files
+    #[serde(default = "default_max_line_length_kb")]
+    pub max_line_length_kb: usize,
+}
+
+fn default_chars_per_token() -> f64 {
+    4.0
+}
+
+fn default_max_line_length_kb() -> usize {
+    10 * 1024
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -69,6 +156,47 @@ pub struct DedupConfig {
     pub window_hours: i64,
     pub cleanup_threshold: usize,
     pub enabled: bool,
+    /// How seen entries are remembered while deduplicating
+    #[serde(default)]
+    pub keys: DedupKeys,
+    /// Keep the keys live mode has seen in `state_file` between runs
+    #[serde(default)]
+    pub persist: bool,
+    #[serde(default = "default_dedup_state_file")]
+    pub state_file: PathBuf,
+}
+
+fn default_dedup_state_file() -> PathBuf {
+    dirs::cache_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("claude-usage")
+        .join("dedup.db")
+}
+
+/// How dedup sets store the `messageId:requestId` keys they have seen
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DedupKeys {
+    /// A 128-bit hash of each key, 16 bytes per entry
+    #[default]
+    Hashed,
+    /// The full key strings, with no chance of a collision
+    Exact,
+}
+
+impl std::str::FromStr for DedupKeys {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "hashed" => Ok(DedupKeys::Hashed),
+            "exact" => Ok(DedupKeys::Exact),
+            _ => Err(anyhow::anyhow!(
+                "Unknown dedup key mode '{}', expected hashed or exact",
+                s
+            )),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -83,6 +211,32 @@ pub struct PathsConfig {
     pub claude_home: PathBuf,
     pub vms_directory: PathBuf,
     pub log_directory: PathBuf,
+    /// Directories holding `session_blocks_*.json` files besides each
+    /// instance's `usage_tracking`
+    #[serde(default)]
+    pub session_blocks_dirs: Vec<PathBuf>,
+    /// Further `~/.claude` trees to read, such as other developers' homes
+    /// collected onto one machine
+    #[serde(default)]
+    pub extra_roots: Vec<PathBuf>,
+    /// Claude config directories read besides the home when they exist:
+    /// `CLAUDE_CONFIG_DIR`, or `~/.config/claude` unless `CLAUDE_HOME` is set
+    #[serde(default = "default_config_dirs")]
+    pub config_dirs: Vec<PathBuf>,
+}
+
+/// Where newer Claude Code versions keep their data: `$XDG_CONFIG_HOME/claude`,
+/// `~/.config/claude` by default on every platform
+fn default_config_dirs() -> Vec<PathBuf> {
+    let config_home = env::var_os("XDG_CONFIG_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .unwrap_or_else(|| {
+            dirs::home_dir()
+                .unwrap_or_else(|| PathBuf::from("."))
+                .join(".config")
+        });
+    vec![config_home.join("claude")]
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -91,6 +245,600 @@ pub struct LiveConfig {
     pub max_restart_attempts: u32,
     pub update_channel_buffer: usize,
     pub claude_keeper_path: String,
+    /// Stop the local keeper after this many seconds without updates, until
+    /// the session logs change again (0 = never)
+    #[serde(default)]
+    pub idle_timeout_secs: u64,
+    /// Keeper watch processes merged by live mode; empty watches the local keeper
+    #[serde(default)]
+    pub instances: Vec<KeeperInstance>,
+    /// Initial order of the recent-activity list, cycled with `s`
+    #[serde(default)]
+    pub activity_sort: ActivitySort,
+    /// Merge consecutive activity of one session into a single row; `g` toggles it
+    #[serde(default)]
+    pub collapse_activity: bool,
+    /// Most recent activity entries kept
+    #[serde(default = "default_activity_limit")]
+    pub activity_limit: usize,
+    /// Where `S` saves snapshots of the dashboard
+    #[serde(default = "default_snapshot_dir")]
+    pub snapshot_dir: PathBuf,
+    /// Forget a session's live state after this many minutes without
+    /// updates, keeping it in the totals (0 = never)
+    #[serde(default = "default_session_ttl_minutes")]
+    pub session_ttl_minutes: u64,
+}
+
+impl LiveConfig {
+    /// How long an idle session's state is kept; `None` keeps it for the whole run
+    pub fn session_ttl(&self) -> Option<std::time::Duration> {
+        (self.session_ttl_minutes > 0)
+            .then(|| std::time::Duration::from_secs(self.session_ttl_minutes.saturating_mul(60)))
+    }
+}
+
+fn default_activity_limit() -> usize {
+    100
+}
+
+fn default_session_ttl_minutes() -> u64 {
+    360
+}
+
+fn default_snapshot_dir() -> PathBuf {
+    dirs::data_local_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("claude-usage")
+        .join("snapshots")
+}
+
+/// Order of the live recent-activity list
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ActivitySort {
+    /// Newest first
+    #[default]
+    Time,
+    /// Most expensive first
+    Cost,
+    /// Most tokens first
+    Tokens,
+}
+
+impl ActivitySort {
+    /// The mode after this one, wrapping around
+    #[allow(dead_code)]
+    pub fn next(self) -> Self {
+        match self {
+            ActivitySort::Time => ActivitySort::Cost,
+            ActivitySort::Cost => ActivitySort::Tokens,
+            ActivitySort::Tokens => ActivitySort::Time,
+        }
+    }
+}
+
+impl std::str::FromStr for ActivitySort {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "time" => Ok(ActivitySort::Time),
+            "cost" => Ok(ActivitySort::Cost),
+            "tokens" => Ok(ActivitySort::Tokens),
+            _ => Err(anyhow::anyhow!(
+                "Unknown activity sort '{}', expected time, cost or tokens",
+                s
+            )),
+        }
+    }
+}
+
+/// A claude-keeper watch process feeding live mode, e.g. one per VM
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KeeperInstance {
+    /// Label shown next to the instance's activity and health
+    pub name: String,
+    /// Command streaming `claude-keeper watch --json` output, such as
+    /// `["ssh", "vm1", "claude-keeper", "watch", "--json"]`; empty runs the
+    /// local `claude_keeper_path`
+    #[serde(default)]
+    pub command: Vec<String>,
+}
+
+/// Opt-in recording of command timings and feature usage to a local file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MetricsConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default = "default_metrics_file")]
+    pub file: PathBuf,
+}
+
+fn default_metrics_file() -> PathBuf {
+    dirs::data_local_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("claude-usage")
+        .join("metrics.jsonl")
+}
+
+impl Default for MetricsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            file: default_metrics_file(),
+        }
+    }
+}
+
+/// Where notes added with `note add` are kept
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NotesConfig {
+    #[serde(default = "default_notes_file")]
+    pub file: PathBuf,
+}
+
+fn default_notes_file() -> PathBuf {
+    dirs::data_local_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("claude-usage")
+        .join("notes.json")
+}
+
+impl Default for NotesConfig {
+    fn default() -> Self {
+        Self {
+            file: default_notes_file(),
+        }
+    }
+}
+
+/// Where `--diff-since-last-run` keeps the state of each report's last run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LastRunConfig {
+    #[serde(default = "default_last_run_dir")]
+    pub dir: PathBuf,
+}
+
+fn default_last_run_dir() -> PathBuf {
+    dirs::cache_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("claude-usage")
+        .join("last-run")
+}
+
+impl Default for LastRunConfig {
+    fn default() -> Self {
+        Self {
+            dir: default_last_run_dir(),
+        }
+    }
+}
+
+/// How long a session was active
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionsConfig {
+    /// Gaps between entries longer than this many minutes count as idle time
+    #[serde(default = "default_idle_gap_minutes")]
+    pub idle_gap_minutes: u64,
+}
+
+fn default_idle_gap_minutes() -> u64 {
+    15
+}
+
+impl SessionsConfig {
+    /// The idle threshold as a duration
+    pub fn idle_gap(&self) -> chrono::Duration {
+        i64::try_from(self.idle_gap_minutes)
+            .ok()
+            .and_then(chrono::Duration::try_minutes)
+            .unwrap_or(chrono::Duration::MAX)
+    }
+}
+
+impl Default for SessionsConfig {
+    fn default() -> Self {
+        Self {
+            idle_gap_minutes: default_idle_gap_minutes(),
+        }
+    }
+}
+
+/// Rules for usage entries with corrupted token counts (see [`crate::sanitize`])
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QualityConfig {
+    /// Entries with a negative token count
+    #[serde(default = "default_negative_tokens")]
+    pub negative_tokens: SanitizeAction,
+    /// Token counts above this in any field of one entry are treated as corrupt
+    #[serde(default = "default_max_tokens_per_entry")]
+    pub max_tokens_per_entry: u64,
+    /// Entries with a token count over `max_tokens_per_entry`
+    #[serde(default = "default_excessive_tokens")]
+    pub excessive_tokens: SanitizeAction,
+}
+
+fn default_negative_tokens() -> SanitizeAction {
+    SanitizeAction::Clamp
+}
+
+fn default_max_tokens_per_entry() -> u64 {
+    10_000_000
+}
+
+fn default_excessive_tokens() -> SanitizeAction {
+    SanitizeAction::Skip
+}
+
+impl Default for QualityConfig {
+    fn default() -> Self {
+        Self {
+            negative_tokens: default_negative_tokens(),
+            max_tokens_per_entry: default_max_tokens_per_entry(),
+            excessive_tokens: default_excessive_tokens(),
+        }
+    }
+}
+
+/// What to do with an entry breaking a `[quality]` rule
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SanitizeAction {
+    /// Keep the entry, bringing its token counts into range
+    Clamp,
+    /// Leave the entry out of the totals
+    Skip,
+}
+
+impl std::str::FromStr for SanitizeAction {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "clamp" => Ok(SanitizeAction::Clamp),
+            "skip" => Ok(SanitizeAction::Skip),
+            _ => Err(anyhow::anyhow!(
+                "Unknown sanitize action '{}', expected clamp or skip",
+                s
+            )),
+        }
+    }
+}
+
+/// Plan ceilings per 5-hour block (see [`crate::limits`]); nothing is
+/// reported against them while all are unset
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LimitsConfig {
+    /// Subscription plan whose ceilings apply
+    #[serde(default)]
+    pub plan: Option<Plan>,
+    /// Input and output tokens allowed per block, overriding the plan's
+    #[serde(default)]
+    pub token_ceiling: Option<u64>,
+    /// Dollars of usage allowed per block, overriding the plan's
+    #[serde(default)]
+    pub budget_usd: Option<f64>,
+}
+
+/// Container mode (see [`crate::container`]); off while `output_dir` is unset
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ContainerConfig {
+    /// The only directory claude-usage writes to; its state files move to
+    /// `state/` there
+    #[serde(default)]
+    pub output_dir: Option<PathBuf>,
+}
+
+/// Claude subscription plan, for `limits.plan`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Plan {
+    Pro,
+    Max5,
+    Max20,
+    /// No preset: `token_ceiling` and `budget_usd` give the ceilings
+    Custom,
+}
+
+impl std::str::FromStr for Plan {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "pro" => Ok(Plan::Pro),
+            "max5" => Ok(Plan::Max5),
+            "max20" => Ok(Plan::Max20),
+            "custom" => Ok(Plan::Custom),
+            _ => Err(anyhow::anyhow!(
+                "Unknown plan '{}', expected pro, max5, max20 or custom",
+                s
+            )),
+        }
+    }
+}
+
+/// Replacing project names in reports, as `--redact-projects` does
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RedactionConfig {
+    /// Redact project names in every report, without the flag
+    #[serde(default)]
+    pub projects: bool,
+    /// JSON file mapping project names, paths or globs to aliases
+    #[serde(default)]
+    pub aliases_file: Option<PathBuf>,
+}
+
+/// Mapping file that splits session costs across cost centers
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AllocationsConfig {
+    #[serde(default)]
+    pub file: Option<PathBuf>,
+}
+
+/// Sidecar file the branch hook appends session branches to
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BranchesConfig {
+    #[serde(default = "default_branches_file")]
+    pub file: PathBuf,
+}
+
+fn default_branches_file() -> PathBuf {
+    dirs::data_local_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("claude-usage")
+        .join("branches.jsonl")
+}
+
+impl Default for BranchesConfig {
+    fn default() -> Self {
+        Self {
+            file: default_branches_file(),
+        }
+    }
+}
+
+/// Where `archive compact` writes per-month parquet archives
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchiveConfig {
+    #[serde(default = "default_archive_dir")]
+    pub dir: PathBuf,
+}
+
+fn default_archive_dir() -> PathBuf {
+    dirs::data_local_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("claude-usage")
+        .join("archive")
+}
+
+impl Default for ArchiveConfig {
+    fn default() -> Self {
+        Self {
+            dir: default_archive_dir(),
+        }
+    }
+}
+
+/// Cache of parsed session logs, so unchanged logs are not parsed again
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ParseCacheConfig {
+    #[serde(default = "default_parse_cache_enabled")]
+    pub enabled: bool,
+    #[serde(default = "default_parse_cache_file")]
+    pub file: PathBuf,
+}
+
+fn default_parse_cache_enabled() -> bool {
+    true
+}
+
+fn default_parse_cache_file() -> PathBuf {
+    dirs::cache_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("claude-usage")
+        .join("index.json")
+}
+
+impl Default for ParseCacheConfig {
+    fn default() -> Self {
+        Self {
+            enabled: default_parse_cache_enabled(),
+            file: default_parse_cache_file(),
+        }
+    }
+}
+
+/// Where token rates come from: the LiteLLM snapshot kept by `pricing
+/// refresh`, and per-model overrides applied over it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PricingConfig {
+    #[serde(default = "default_pricing_snapshot_file")]
+    pub snapshot_file: PathBuf,
+    /// Snapshots older than this many days are used with a warning
+    #[serde(default = "default_pricing_max_age_days")]
+    pub max_age_days: u32,
+    /// File of per-model overrides, applied over `overrides`
+    #[serde(default)]
+    pub overrides_file: Option<PathBuf>,
+    /// Negotiated rates by model, applied over the snapshot and built-in rates
+    #[serde(default)]
+    pub overrides: BTreeMap<String, PriceOverride>,
+}
+
+/// Rates of one model in dollars per million tokens; unset rates keep the
+/// snapshot or built-in rate
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PriceOverride {
+    #[serde(default)]
+    pub input: Option<f64>,
+    #[serde(default)]
+    pub output: Option<f64>,
+    #[serde(default)]
+    pub cache_creation: Option<f64>,
+    #[serde(default)]
+    pub cache_read: Option<f64>,
+}
+
+fn default_pricing_snapshot_file() -> PathBuf {
+    dirs::cache_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("claude-usage")
+        .join("pricing.json")
+}
+
+fn default_pricing_max_age_days() -> u32 {
+    30
+}
+
+impl Default for PricingConfig {
+    fn default() -> Self {
+        Self {
+            snapshot_file: default_pricing_snapshot_file(),
+            max_age_days: default_pricing_max_age_days(),
+            overrides_file: None,
+            overrides: BTreeMap::new(),
+        }
+    }
+}
+
+/// Daily spending limit and the percentages at which live mode alerts
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BudgetConfig {
+    /// Daily budget in dollars; unset disables budget alerts
+    #[serde(default)]
+    pub daily_usd: Option<f64>,
+    /// Monthly budget in dollars that `forecast` estimates the exhaustion of
+    /// and `status` shows the share used of
+    #[serde(default)]
+    pub monthly_usd: Option<f64>,
+    /// Percentage of the daily budget shown as a warning
+    #[serde(default = "default_budget_warning_pct")]
+    pub warning_pct: f64,
+    /// Percentage of the daily budget shown as critical
+    #[serde(default = "default_budget_critical_pct")]
+    pub critical_pct: f64,
+    /// Ring the terminal bell when a threshold is crossed
+    #[serde(default)]
+    pub bell: bool,
+    /// Show a desktop notification when a threshold is crossed
+    #[serde(default)]
+    pub notify: bool,
+}
+
+fn default_budget_warning_pct() -> f64 {
+    80.0
+}
+
+fn default_budget_critical_pct() -> f64 {
+    100.0
+}
+
+impl Default for BudgetConfig {
+    fn default() -> Self {
+        Self {
+            daily_usd: None,
+            monthly_usd: None,
+            warning_pct: default_budget_warning_pct(),
+            critical_pct: default_budget_critical_pct(),
+            bell: false,
+            notify: false,
+        }
+    }
+}
+
+/// Webhooks posted to when daily or session spend crosses a threshold (see
+/// [`crate::notify`]); nothing is sent without webhooks
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NotifyConfig {
+    #[serde(default)]
+    pub webhooks: Vec<Webhook>,
+    /// Thresholds of the local day's spend, in dollars
+    #[serde(default)]
+    pub daily_usd: Vec<f64>,
+    /// Thresholds of a single session's spend, in dollars
+    #[serde(default)]
+    pub session_usd: Vec<f64>,
+    /// Alerts `check` has sent, so each crossing is sent once
+    #[serde(default = "default_notify_state_file")]
+    pub state_file: PathBuf,
+}
+
+fn default_notify_state_file() -> PathBuf {
+    dirs::cache_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("claude-usage")
+        .join("notify.json")
+}
+
+impl Default for NotifyConfig {
+    fn default() -> Self {
+        Self {
+            webhooks: Vec::new(),
+            daily_usd: Vec::new(),
+            session_usd: Vec::new(),
+            state_file: default_notify_state_file(),
+        }
+    }
+}
+
+/// A webhook URL and the message format it expects
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Webhook {
+    pub url: String,
+    /// Message format; unset follows the URL
+    #[serde(default)]
+    pub format: Option<WebhookFormat>,
+}
+
+/// Message format of a webhook
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum WebhookFormat {
+    /// `{"text": ...}`, for Slack incoming webhooks
+    Slack,
+    /// `{"content": ...}`, for Discord webhooks
+    Discord,
+    /// The alert's fields as a JSON object, with a `text` summary
+    Generic,
+}
+
+/// Shell commands run with a JSON payload on stdin when an event happens;
+/// unset events run nothing
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HooksConfig {
+    /// Live mode saw the local day change
+    #[serde(default)]
+    pub day_rollover: Option<String>,
+    /// Live mode saw the first entry of a session
+    #[serde(default)]
+    pub new_session: Option<String>,
+    /// Live mode crossed a daily budget threshold
+    #[serde(default)]
+    pub cost_threshold: Option<String>,
+    /// A report command finished
+    #[serde(default)]
+    pub run_completed: Option<String>,
+    /// Seconds a hook may run before it is killed
+    #[serde(default = "default_hook_timeout_secs")]
+    pub timeout_secs: u64,
+}
+
+fn default_hook_timeout_secs() -> u64 {
+    30
+}
+
+impl Default for HooksConfig {
+    fn default() -> Self {
+        Self {
+            day_rollover: None,
+            new_session: None,
+            cost_threshold: None,
+            run_completed: None,
+            timeout_secs: default_hook_timeout_secs(),
+        }
+    }
 }
 
 impl Default for Config {
@@ -106,6 +854,11 @@ impl Default for Config {
                 parallel_chunks: 4,
                 max_retries: 3,
                 progress_interval_mb: 10,
+                estimate_missing_usage: false,
+                chars_per_token: default_chars_per_token(),
+                max_open_files: 0,
+                max_read_mb_per_sec: 0.0,
+                max_line_length_kb: default_max_line_length_kb(),
             },
             memory: MemoryConfig {
                 max_memory_mb: 512,
@@ -116,6 +869,9 @@ impl Default for Config {
                 window_hours: 24,
                 cleanup_threshold: 10000,
                 enabled: true,
+                keys: DedupKeys::default(),
+                persist: false,
+                state_file: default_dedup_state_file(),
             },
             output: OutputConfig {
                 json_pretty: false,
@@ -131,41 +887,106 @@ impl Default for Config {
                     .join(".claude")
                     .join("vms"),
                 log_directory: PathBuf::from("logs"),
+                session_blocks_dirs: Vec::new(),
+                extra_roots: Vec::new(),
+                config_dirs: default_config_dirs(),
             },
             live: LiveConfig {
                 startup_timeout_secs: 30,
                 max_restart_attempts: 3,
                 update_channel_buffer: 100,
                 claude_keeper_path: "claude-keeper".to_string(),
+                idle_timeout_secs: 0,
+                instances: Vec::new(),
+                activity_sort: ActivitySort::default(),
+                collapse_activity: false,
+                activity_limit: default_activity_limit(),
+                snapshot_dir: default_snapshot_dir(),
+                session_ttl_minutes: default_session_ttl_minutes(),
             },
+            metrics: MetricsConfig::default(),
+            notes: NotesConfig::default(),
+            allocations: AllocationsConfig::default(),
+            branches: BranchesConfig::default(),
+            archive: ArchiveConfig::default(),
+            parse_cache: ParseCacheConfig::default(),
+            pricing: PricingConfig::default(),
+            budget: BudgetConfig::default(),
+            hooks: HooksConfig::default(),
+            notify: NotifyConfig::default(),
+            last_run: LastRunConfig::default(),
+            sessions: SessionsConfig::default(),
+            redaction: RedactionConfig::default(),
+            quality: QualityConfig::default(),
+            limits: LimitsConfig::default(),
+            container: ContainerConfig::default(),
         }
     }
 }
 
+/// Workspace config file names, looked for in the current directory and each parent
+///
+/// Earlier names win within one directory: the visible name over the hidden
+/// one, then TOML over YAML over JSON.
+pub const WORKSPACE_CONFIG_NAMES: [&str; 8] = [
+    "claude-usage.toml",
+    "claude-usage.yaml",
+    "claude-usage.yml",
+    "claude-usage.json",
+    ".claude-usage.toml",
+    ".claude-usage.yaml",
+    ".claude-usage.yml",
+    ".claude-usage.json",
+];
+
+/// Global config file names in the claude-usage config directory, in order of preference
+pub const GLOBAL_CONFIG_NAMES: [&str; 4] =
+    ["config.toml", "config.yaml", "config.yml", "config.json"];
+
+/// Location of the global (per-user) config file
+///
+/// This is the first of [`GLOBAL_CONFIG_NAMES`] that exists, or `config.toml`.
+pub fn global_config_path() -> Option<PathBuf> {
+    let dir = dirs::config_dir()?.join("claude-usage");
+    GLOBAL_CONFIG_NAMES
+        .iter()
+        .map(|name| dir.join(name))
+        .find(|path| path.is_file())
+        .or_else(|| Some(dir.join(GLOBAL_CONFIG_NAMES[0])))
+}
+
+/// Nearest workspace config file, walking up from `start` like `.gitignore` lookup
+///
+/// Within one directory the first of [`WORKSPACE_CONFIG_NAMES`] wins.
+pub fn find_workspace_config(start: &Path) -> Option<PathBuf> {
+    start.ancestors().find_map(|dir| {
+        WORKSPACE_CONFIG_NAMES
+            .iter()
+            .map(|name| dir.join(name))
+            .find(|path| path.is_file())
+    })
+}
+
 impl Config {
-    /// Load configuration from environment, file, and defaults
+    /// Load configuration from environment, files, and defaults
+    ///
+    /// The global config is applied over the defaults, the nearest workspace
+    /// config over the global one, and environment variables last. Files only
+    /// need the settings they change.
     pub fn load() -> Result<Self> {
-        let mut config = Config::default();
+        let global = global_config_path().filter(|path| path.is_file());
+        let workspace = env::current_dir()
+            .ok()
+            .and_then(|dir| find_workspace_config(&dir))
+            .filter(|path| Some(path) != global.as_ref());
+        let files: Vec<PathBuf> = global.into_iter().chain(workspace).collect();
 
-        // Try to load from config file if it exists
-        let config_paths = [
-            PathBuf::from("claude-usage.toml"),
-            PathBuf::from(".claude-usage.toml"),
-            dirs::config_dir()
-                .map(|d| d.join("claude-usage").join("config.toml"))
-                .unwrap_or_default(),
-        ];
-
-        for path in &config_paths {
-            if path.exists() {
-                info!(config_file = %path.display(), "Loading configuration from file");
-                config = Self::load_from_file(path)?;
-                break;
-            }
-        }
+        let mut config = Self::load_layered(&files)?;
 
         // Override with environment variables
         config.apply_env_overrides()?;
+        config.load_pricing_overrides()?;
+        config.confine_to_output_dir();
 
         // Validate configuration
         config.validate()?;
@@ -187,27 +1008,71 @@ impl Config {
         PathBuf::from(path_str)
     }
 
-    /// Load configuration from TOML file
-    #[cfg(feature = "basic")]
+    /// Load configuration from a TOML, YAML (`.yaml`, `.yml`) or JSON (`.json`) file
+    #[allow(dead_code)]
     pub fn load_from_file(path: &Path) -> Result<Self> {
-        let content = fs::read_to_string(path)
-            .with_context(|| format!("Failed to read config file: {}", path.display()))?;
+        Self::load_layered(&[path.to_path_buf()])
+    }
+
+    /// Load configuration from files applied over the defaults in order, so a
+    /// later file overrides the settings it shares with earlier ones
+    ///
+    /// Each file's format follows its extension, as in [`Config::load_from_file`];
+    /// all formats share the same settings and layering.
+    #[cfg(feature = "basic")]
+    pub fn load_layered(paths: &[PathBuf]) -> Result<Self> {
+        let mut merged =
+            toml::Value::try_from(Self::default()).context("Failed to serialize defaults")?;
+
+        for path in paths {
+            info!(config_file = %path.display(), "Loading configuration from file");
+            let content = fs::read_to_string(path)
+                .with_context(|| format!("Failed to read config file: {}", path.display()))?;
+            let layer = parse_layer(path, &content)
+                .with_context(|| format!("Failed to parse config file: {}", path.display()))?;
+            merge_toml(&mut merged, layer);
+        }
+
+        let mut config: Config = merged.try_into().with_context(|| {
+            let files: Vec<_> = paths.iter().map(|p| p.display().to_string()).collect();
+            format!("Invalid configuration in {}", files.join(", "))
+        })?;
 
-        let mut config: Config = toml::from_str(&content)
-            .with_context(|| format!("Failed to parse config file: {}", path.display()))?;
-        
         // Expand ~ in path strings
         config.expand_paths();
 
         Ok(config)
     }
-    
+
     #[cfg(not(feature = "basic"))]
-    pub fn load_from_file(_path: &Path) -> Result<Self> {
+    pub fn load_layered(_paths: &[PathBuf]) -> Result<Self> {
         // Return default config when TOML support is not compiled in
         Ok(Self::default())
     }
 
+    /// Apply the rates in `pricing.overrides_file` over `pricing.overrides`
+    ///
+    /// The file maps model names to rates like the `[pricing.overrides]`
+    /// section, in TOML, YAML or JSON by extension.
+    #[cfg(feature = "basic")]
+    pub fn load_pricing_overrides(&mut self) -> Result<()> {
+        let Some(path) = &self.pricing.overrides_file else {
+            return Ok(());
+        };
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read pricing overrides: {}", path.display()))?;
+        let overrides: BTreeMap<String, PriceOverride> = parse_layer(path, &content)
+            .and_then(|layer| Ok(layer.try_into()?))
+            .with_context(|| format!("Invalid pricing overrides: {}", path.display()))?;
+        self.pricing.overrides.extend(overrides);
+        Ok(())
+    }
+
+    #[cfg(not(feature = "basic"))]
+    pub fn load_pricing_overrides(&mut self) -> Result<()> {
+        Ok(())
+    }
+
     /// Expand ~ in all path fields
     fn expand_paths(&mut self) {
         // Convert paths to strings, expand, then back to PathBuf
@@ -220,6 +1085,60 @@ impl Config {
         if let Some(log_dir_str) = self.paths.log_directory.to_str() {
             self.paths.log_directory = Self::expand_path(log_dir_str);
         }
+        for dir in &mut self.paths.session_blocks_dirs {
+            if let Some(dir_str) = dir.to_str() {
+                *dir = Self::expand_path(dir_str);
+            }
+        }
+        for root in &mut self.paths.extra_roots {
+            if let Some(root_str) = root.to_str() {
+                *root = Self::expand_path(root_str);
+            }
+        }
+        for dir in &mut self.paths.config_dirs {
+            if let Some(dir_str) = dir.to_str() {
+                *dir = Self::expand_path(dir_str);
+            }
+        }
+        if let Some(metrics_file_str) = self.metrics.file.to_str() {
+            self.metrics.file = Self::expand_path(metrics_file_str);
+        }
+        if let Some(notes_file_str) = self.notes.file.to_str() {
+            self.notes.file = Self::expand_path(notes_file_str);
+        }
+        if let Some(allocations_file) = &self.allocations.file {
+            if let Some(allocations_file_str) = allocations_file.to_str() {
+                self.allocations.file = Some(Self::expand_path(allocations_file_str));
+            }
+        }
+        if let Some(aliases_file) = &self.redaction.aliases_file {
+            if let Some(aliases_file_str) = aliases_file.to_str() {
+                self.redaction.aliases_file = Some(Self::expand_path(aliases_file_str));
+            }
+        }
+        if let Some(branches_file_str) = self.branches.file.to_str() {
+            self.branches.file = Self::expand_path(branches_file_str);
+        }
+        if let Some(archive_dir_str) = self.archive.dir.to_str() {
+            self.archive.dir = Self::expand_path(archive_dir_str);
+        }
+        if let Some(dedup_state_file_str) = self.dedup.state_file.to_str() {
+            self.dedup.state_file = Self::expand_path(dedup_state_file_str);
+        }
+        if let Some(last_run_dir_str) = self.last_run.dir.to_str() {
+            self.last_run.dir = Self::expand_path(last_run_dir_str);
+        }
+        if let Some(parse_cache_file_str) = self.parse_cache.file.to_str() {
+            self.parse_cache.file = Self::expand_path(parse_cache_file_str);
+        }
+        if let Some(snapshot_file_str) = self.pricing.snapshot_file.to_str() {
+            self.pricing.snapshot_file = Self::expand_path(snapshot_file_str);
+        }
+        if let Some(overrides_file) = &self.pricing.overrides_file {
+            if let Some(overrides_file_str) = overrides_file.to_str() {
+                self.pricing.overrides_file = Some(Self::expand_path(overrides_file_str));
+            }
+        }
     }
 
     /// Apply environment variable overrides
@@ -244,6 +1163,31 @@ impl Config {
                 .parse()
                 .context("Invalid CLAUDE_USAGE_PARALLEL_CHUNKS")?;
         }
+        if let Ok(val) = env::var("CLAUDE_USAGE_ESTIMATE_MISSING_USAGE") {
+            self.processing.estimate_missing_usage = val
+                .parse()
+                .context("Invalid CLAUDE_USAGE_ESTIMATE_MISSING_USAGE")?;
+        }
+        if let Ok(val) = env::var("CLAUDE_USAGE_CHARS_PER_TOKEN") {
+            self.processing.chars_per_token = val
+                .parse()
+                .context("Invalid CLAUDE_USAGE_CHARS_PER_TOKEN")?;
+        }
+        if let Ok(val) = env::var("CLAUDE_USAGE_MAX_OPEN_FILES") {
+            self.processing.max_open_files = val
+                .parse()
+                .context("Invalid CLAUDE_USAGE_MAX_OPEN_FILES")?;
+        }
+        if let Ok(val) = env::var("CLAUDE_USAGE_MAX_READ_MB_PER_SEC") {
+            self.processing.max_read_mb_per_sec = val
+                .parse()
+                .context("Invalid CLAUDE_USAGE_MAX_READ_MB_PER_SEC")?;
+        }
+        if let Ok(val) = env::var("CLAUDE_USAGE_MAX_LINE_LENGTH_KB") {
+            self.processing.max_line_length_kb = val
+                .parse()
+                .context("Invalid CLAUDE_USAGE_MAX_LINE_LENGTH_KB")?;
+        }
 
         // Memory overrides
         if let Ok(val) = env::var("CLAUDE_USAGE_MAX_MEMORY_MB") {
@@ -264,10 +1208,26 @@ impl Config {
         if let Ok(val) = env::var("CLAUDE_USAGE_DEDUP_ENABLED") {
             self.dedup.enabled = val.parse().context("Invalid CLAUDE_USAGE_DEDUP_ENABLED")?;
         }
+        if let Ok(val) = env::var("CLAUDE_USAGE_DEDUP_KEYS") {
+            self.dedup.keys = val.parse()?;
+        }
+        if let Ok(val) = env::var("CLAUDE_USAGE_DEDUP_PERSIST") {
+            self.dedup.persist = val.parse().context("Invalid CLAUDE_USAGE_DEDUP_PERSIST")?;
+        }
 
         // Path overrides (with ~ expansion)
         if let Ok(val) = env::var("CLAUDE_HOME") {
             self.paths.claude_home = Self::expand_path(&val);
+            // An explicit home is the only default location read
+            self.paths.config_dirs.clear();
+        }
+        if let Ok(val) = env::var("CLAUDE_CONFIG_DIR") {
+            self.paths.config_dirs = val
+                .split(',')
+                .map(str::trim)
+                .filter(|dir| !dir.is_empty())
+                .map(Self::expand_path)
+                .collect();
         }
         if let Ok(val) = env::var("CLAUDE_VMS_DIR") {
             self.paths.vms_directory = Self::expand_path(&val);
@@ -275,6 +1235,18 @@ impl Config {
         if let Ok(val) = env::var("CLAUDE_LOG_DIR") {
             self.paths.log_directory = Self::expand_path(&val);
         }
+        if let Ok(val) = env::var("CLAUDE_USAGE_SESSION_BLOCKS_DIRS") {
+            self.paths.session_blocks_dirs = env::split_paths(&val)
+                .filter(|dir| !dir.as_os_str().is_empty())
+                .map(|dir| Self::expand_path(&dir.to_string_lossy()))
+                .collect();
+        }
+        if let Ok(val) = env::var("CLAUDE_USAGE_EXTRA_ROOTS") {
+            self.paths.extra_roots = env::split_paths(&val)
+                .filter(|root| !root.as_os_str().is_empty())
+                .map(|root| Self::expand_path(&root.to_string_lossy()))
+                .collect();
+        }
 
         // Live mode overrides
         if let Ok(val) = env::var("CLAUDE_KEEPER_PATH") {
@@ -295,10 +1267,194 @@ impl Config {
                 .parse()
                 .context("Invalid CLAUDE_USAGE_LIVE_BUFFER_SIZE")?;
         }
+        if let Ok(val) = env::var("CLAUDE_USAGE_LIVE_IDLE_TIMEOUT") {
+            self.live.idle_timeout_secs = val
+                .parse()
+                .context("Invalid CLAUDE_USAGE_LIVE_IDLE_TIMEOUT")?;
+        }
+        if let Ok(val) = env::var("CLAUDE_USAGE_LIVE_ACTIVITY_SORT") {
+            self.live.activity_sort = val.parse()?;
+        }
+        if let Ok(val) = env::var("CLAUDE_USAGE_LIVE_COLLAPSE_ACTIVITY") {
+            self.live.collapse_activity = val
+                .parse()
+                .context("Invalid CLAUDE_USAGE_LIVE_COLLAPSE_ACTIVITY")?;
+        }
+        if let Ok(val) = env::var("CLAUDE_USAGE_LIVE_ACTIVITY_LIMIT") {
+            self.live.activity_limit = val
+                .parse()
+                .context("Invalid CLAUDE_USAGE_LIVE_ACTIVITY_LIMIT")?;
+        }
+        if let Ok(val) = env::var("CLAUDE_USAGE_LIVE_SNAPSHOT_DIR") {
+            self.live.snapshot_dir = Self::expand_path(&val);
+        }
+        if let Ok(val) = env::var("CLAUDE_USAGE_LIVE_SESSION_TTL") {
+            self.live.session_ttl_minutes = val
+                .parse()
+                .context("Invalid CLAUDE_USAGE_LIVE_SESSION_TTL")?;
+        }
+
+        // Self-metrics overrides
+        if let Ok(val) = env::var("CLAUDE_USAGE_METRICS") {
+            self.metrics.enabled = val.parse().context("Invalid CLAUDE_USAGE_METRICS")?;
+        }
+        if let Ok(val) = env::var("CLAUDE_USAGE_METRICS_FILE") {
+            self.metrics.file = Self::expand_path(&val);
+        }
+
+        // Notes store override
+        if let Ok(val) = env::var("CLAUDE_USAGE_NOTES_FILE") {
+            self.notes.file = Self::expand_path(&val);
+        }
+
+        // Report state override
+        if let Ok(val) = env::var("CLAUDE_USAGE_LAST_RUN_DIR") {
+            self.last_run.dir = Self::expand_path(&val);
+        }
+
+        // Session duration override
+        if let Ok(val) = env::var("CLAUDE_USAGE_SESSION_IDLE_GAP") {
+            self.sessions.idle_gap_minutes = val
+                .parse()
+                .context("Invalid CLAUDE_USAGE_SESSION_IDLE_GAP")?;
+        }
+
+        // Token count sanitizing overrides
+        if let Ok(val) = env::var("CLAUDE_USAGE_NEGATIVE_TOKENS") {
+            self.quality.negative_tokens = val.parse()?;
+        }
+        if let Ok(val) = env::var("CLAUDE_USAGE_MAX_TOKENS_PER_ENTRY") {
+            self.quality.max_tokens_per_entry = val
+                .parse()
+                .context("Invalid CLAUDE_USAGE_MAX_TOKENS_PER_ENTRY")?;
+        }
+        if let Ok(val) = env::var("CLAUDE_USAGE_EXCESSIVE_TOKENS") {
+            self.quality.excessive_tokens = val.parse()?;
+        }
+
+        // Project redaction overrides
+        if let Ok(val) = env::var("CLAUDE_USAGE_REDACT_PROJECTS") {
+            self.redaction.projects = val
+                .parse()
+                .context("Invalid CLAUDE_USAGE_REDACT_PROJECTS")?;
+        }
+        if let Ok(val) = env::var("CLAUDE_USAGE_REDACT_ALIASES_FILE") {
+            self.redaction.aliases_file = Some(Self::expand_path(&val));
+        }
+
+        // Cost-center allocations override
+        if let Ok(val) = env::var("CLAUDE_USAGE_ALLOCATIONS_FILE") {
+            self.allocations.file = Some(Self::expand_path(&val));
+        }
+
+        // Session branches override
+        if let Ok(val) = env::var("CLAUDE_USAGE_BRANCHES_FILE") {
+            self.branches.file = Self::expand_path(&val);
+        }
+
+        // Archive directory override
+        if let Ok(val) = env::var("CLAUDE_USAGE_ARCHIVE_DIR") {
+            self.archive.dir = Self::expand_path(&val);
+        }
+
+        // Parse cache overrides
+        if let Ok(val) = env::var("CLAUDE_USAGE_PARSE_CACHE") {
+            self.parse_cache.enabled = val.parse().context("Invalid CLAUDE_USAGE_PARSE_CACHE")?;
+        }
+        if let Ok(val) = env::var("CLAUDE_USAGE_PARSE_CACHE_FILE") {
+            self.parse_cache.file = Self::expand_path(&val);
+        }
+
+        // Pricing overrides
+        if let Ok(val) = env::var("CLAUDE_USAGE_PRICING_SNAPSHOT") {
+            self.pricing.snapshot_file = Self::expand_path(&val);
+        }
+        if let Ok(val) = env::var("CLAUDE_USAGE_PRICING_FILE") {
+            self.pricing.overrides_file = Some(Self::expand_path(&val));
+        }
+
+        // Daily budget override
+        if let Ok(val) = env::var("CLAUDE_USAGE_BUDGET_DAILY") {
+            self.budget.daily_usd =
+                Some(val.parse().context("Invalid CLAUDE_USAGE_BUDGET_DAILY")?);
+        }
+        if let Ok(val) = env::var("CLAUDE_USAGE_BUDGET_MONTHLY") {
+            self.budget.monthly_usd =
+                Some(val.parse().context("Invalid CLAUDE_USAGE_BUDGET_MONTHLY")?);
+        }
+
+        // Notification overrides
+        if let Ok(val) = env::var("CLAUDE_USAGE_NOTIFY_WEBHOOKS") {
+            self.notify.webhooks = val
+                .split([',', ' '])
+                .filter(|url| !url.is_empty())
+                .map(|url| Webhook {
+                    url: url.to_string(),
+                    format: None,
+                })
+                .collect();
+        }
+        if let Ok(val) = env::var("CLAUDE_USAGE_NOTIFY_DAILY_USD") {
+            self.notify.daily_usd =
+                parse_amounts(&val).context("Invalid CLAUDE_USAGE_NOTIFY_DAILY_USD")?;
+        }
+        if let Ok(val) = env::var("CLAUDE_USAGE_NOTIFY_SESSION_USD") {
+            self.notify.session_usd =
+                parse_amounts(&val).context("Invalid CLAUDE_USAGE_NOTIFY_SESSION_USD")?;
+        }
+        if let Ok(val) = env::var("CLAUDE_USAGE_NOTIFY_STATE_FILE") {
+            self.notify.state_file = Self::expand_path(&val);
+        }
+
+        // Plan ceiling overrides
+        if let Ok(val) = env::var("CLAUDE_USAGE_LIMITS_PLAN") {
+            self.limits.plan = Some(val.parse()?);
+        }
+        if let Ok(val) = env::var("CLAUDE_USAGE_LIMITS_TOKEN_CEILING") {
+            self.limits.token_ceiling = Some(
+                val.parse()
+                    .context("Invalid CLAUDE_USAGE_LIMITS_TOKEN_CEILING")?,
+            );
+        }
+        if let Ok(val) = env::var("CLAUDE_USAGE_LIMITS_BUDGET_USD") {
+            self.limits.budget_usd = Some(
+                val.parse()
+                    .context("Invalid CLAUDE_USAGE_LIMITS_BUDGET_USD")?,
+            );
+        }
+
+        // Container mode overrides
+        if let Ok(val) = env::var("CLAUDE_USAGE_CONTAINER_OUTPUT") {
+            self.container.output_dir = Some(Self::expand_path(&val));
+        }
 
         Ok(())
     }
 
+    /// In container mode, move every file claude-usage writes, and the state
+    /// it reads back, to `state/` in the output directory
+    ///
+    /// The Claude directory and the home directory can then be mounted
+    /// read-only. Notes, branches, archives and the pricing snapshot are read
+    /// from there too, so they persist with the output volume.
+    pub fn confine_to_output_dir(&mut self) {
+        let Some(dir) = &self.container.output_dir else {
+            return;
+        };
+        let state = dir.join("state");
+        self.paths.log_directory = state.join("logs");
+        self.dedup.state_file = state.join("dedup.db");
+        self.live.snapshot_dir = state.join("snapshots");
+        self.metrics.file = state.join("metrics.jsonl");
+        self.notes.file = state.join("notes.json");
+        self.last_run.dir = state.join("last-run");
+        self.branches.file = state.join("branches.jsonl");
+        self.archive.dir = state.join("archive");
+        self.parse_cache.file = state.join("index.json");
+        self.pricing.snapshot_file = state.join("pricing.json");
+        self.notify.state_file = state.join("notify.json");
+    }
+
     /// Validate configuration values
     pub fn validate(&self) -> Result<()> {
         // Validate memory settings
@@ -325,11 +1481,150 @@ impl Config {
             return Err(anyhow::anyhow!("Parallel chunks must be greater than 0"));
         }
 
+        if self.processing.chars_per_token <= 0.0 {
+            return Err(anyhow::anyhow!(
+                "Chars per token must be greater than 0, got {}",
+                self.processing.chars_per_token
+            ));
+        }
+
+        if self.processing.max_read_mb_per_sec < 0.0 {
+            return Err(anyhow::anyhow!(
+                "Max read MB per second cannot be negative, got {}",
+                self.processing.max_read_mb_per_sec
+            ));
+        }
+
+        if self.processing.max_line_length_kb == 0 {
+            return Err(anyhow::anyhow!("Max line length must be greater than 0"));
+        }
+
+        if self.quality.max_tokens_per_entry == 0 {
+            return Err(anyhow::anyhow!(
+                "Max tokens per entry must be greater than 0"
+            ));
+        }
+
+        for (model, rates) in &self.pricing.overrides {
+            let all = [
+                rates.input,
+                rates.output,
+                rates.cache_creation,
+                rates.cache_read,
+            ];
+            if all
+                .iter()
+                .flatten()
+                .any(|rate| *rate < 0.0 || !rate.is_finite())
+            {
+                return Err(anyhow::anyhow!(
+                    "Pricing override for '{}' has a negative or invalid rate",
+                    model
+                ));
+            }
+        }
+
+        if self.live.activity_limit == 0 {
+            return Err(anyhow::anyhow!(
+                "Live activity limit must be greater than 0"
+            ));
+        }
+
+        // Validate live instances
+        let mut instance_names = std::collections::HashSet::new();
+        for instance in &self.live.instances {
+            if instance.name.trim().is_empty() {
+                return Err(anyhow::anyhow!("Live instances need a name"));
+            }
+            if !instance_names.insert(instance.name.as_str()) {
+                return Err(anyhow::anyhow!(
+                    "Live instance name '{}' is used more than once",
+                    instance.name
+                ));
+            }
+        }
+
+        // Validate budget settings
+        if let Some(daily_usd) = self.budget.daily_usd {
+            if daily_usd <= 0.0 {
+                return Err(anyhow::anyhow!(
+                    "Daily budget must be greater than 0, got {}",
+                    daily_usd
+                ));
+            }
+        }
+        if let Some(monthly_usd) = self.budget.monthly_usd {
+            if monthly_usd <= 0.0 {
+                return Err(anyhow::anyhow!(
+                    "Monthly budget must be greater than 0, got {}",
+                    monthly_usd
+                ));
+            }
+        }
+
+        if self.budget.warning_pct <= 0.0 || self.budget.warning_pct > self.budget.critical_pct {
+            return Err(anyhow::anyhow!(
+                "Budget warning percentage must be greater than 0 and at most the critical percentage, got {} and {}",
+                self.budget.warning_pct,
+                self.budget.critical_pct
+            ));
+        }
+
+        // Validate notifications
+        if let Some(threshold) = self
+            .notify
+            .daily_usd
+            .iter()
+            .chain(&self.notify.session_usd)
+            .find(|threshold| !(**threshold > 0.0 && threshold.is_finite()))
+        {
+            return Err(anyhow::anyhow!(
+                "Notification thresholds must be greater than 0, got {}",
+                threshold
+            ));
+        }
+        if let Some(webhook) = self.notify.webhooks.iter().find(|webhook| {
+            !webhook.url.starts_with("https://") && !webhook.url.starts_with("http://")
+        }) {
+            return Err(anyhow::anyhow!(
+                "Webhook URL must start with https:// or http://, got {}",
+                webhook.url
+            ));
+        }
+
+        // Validate plan ceilings
+        if self.limits.token_ceiling == Some(0) {
+            return Err(anyhow::anyhow!("Token ceiling must be greater than 0"));
+        }
+        if let Some(budget_usd) = self.limits.budget_usd {
+            if budget_usd <= 0.0 || !budget_usd.is_finite() {
+                return Err(anyhow::anyhow!(
+                    "Block budget must be greater than 0, got {}",
+                    budget_usd
+                ));
+            }
+        }
+        if self.limits.plan == Some(Plan::Custom)
+            && self.limits.token_ceiling.is_none()
+            && self.limits.budget_usd.is_none()
+        {
+            return Err(anyhow::anyhow!(
+                "The custom plan needs limits.token_ceiling or limits.budget_usd"
+            ));
+        }
+
+        if self.hooks.timeout_secs == 0 {
+            return Err(anyhow::anyhow!("Hook timeout must be greater than 0"));
+        }
+
         // Validate dedup settings
         if self.dedup.window_hours < 0 {
             return Err(anyhow::anyhow!("Dedup window hours cannot be negative"));
         }
 
+        // In container mode, check the mounts before writing anything
+        crate::container::check_mounts(self)?;
+
         // Validate paths exist (create if needed)
         if !self.paths.log_directory.exists() {
             fs::create_dir_all(&self.paths.log_directory)
@@ -360,6 +1655,55 @@ impl Config {
     }
 }
 
+/// Parse a config file by its extension; anything but `.yaml`, `.yml` and
+/// `.json` is read as TOML
+#[cfg(feature = "basic")]
+fn parse_layer(path: &Path, content: &str) -> Result<toml::Value> {
+    let extension = path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .map(str::to_ascii_lowercase);
+    let layer = match extension.as_deref() {
+        // An empty YAML document is null rather than an empty mapping
+        Some("yaml" | "yml") => serde_yaml::from_str::<Option<toml::Value>>(content)?
+            .unwrap_or_else(|| toml::Value::Table(toml::Table::new())),
+        Some("json") => serde_json::from_str(content)?,
+        _ => toml::from_str(content)?,
+    };
+    Ok(layer)
+}
+
+/// Merge `layer` into `base`: tables merge key by key, other values replace
+#[cfg(feature = "basic")]
+fn merge_toml(base: &mut toml::Value, layer: toml::Value) {
+    match (base, layer) {
+        (toml::Value::Table(base), toml::Value::Table(layer)) => {
+            for (key, value) in layer {
+                match base.get_mut(&key) {
+                    Some(existing) => merge_toml(existing, value),
+                    None => {
+                        base.insert(key, value);
+                    }
+                }
+            }
+        }
+        (base, layer) => *base = layer,
+    }
+}
+
+/// Parse a comma-separated list of dollar amounts
+fn parse_amounts(list: &str) -> Result<Vec<f64>> {
+    list.split(',')
+        .map(str::trim)
+        .filter(|amount| !amount.is_empty())
+        .map(|amount| {
+            amount
+                .parse()
+                .with_context(|| format!("Invalid amount '{}'", amount))
+        })
+        .collect()
+}
+
 /// Global configuration instance
 #[cfg(not(test))]
 static CONFIG: OnceLock<Config> = OnceLock::new();
@@ -368,6 +1712,23 @@ static CONFIG: OnceLock<Config> = OnceLock::new();
 #[cfg(test)]
 static CONFIG: Mutex<Option<&'static Config>> = Mutex::new(None);
 
+/// Load the global configuration, returning the error for an invalid one
+/// instead of panicking like [`get_config`]
+#[cfg(not(test))]
+pub fn init_config() -> Result<&'static Config> {
+    if let Some(config) = CONFIG.get() {
+        return Ok(config);
+    }
+    let config = Config::load()?;
+    Ok(CONFIG.get_or_init(|| config))
+}
+
+/// Load the global configuration for tests
+#[cfg(test)]
+pub fn init_config() -> Result<&'static Config> {
+    Ok(get_config())
+}
+
 /// Get the global configuration instance
 #[cfg(not(test))]
 pub fn get_config() -> &'static Config {
@@ -426,6 +1787,57 @@ mod tests {
         assert!(config.validate().is_err());
     }
 
+    #[test]
+    fn test_budget_validation() {
+        let mut config = Config::default();
+        config.budget.daily_usd = Some(10.0);
+        assert!(config.validate().is_ok());
+
+        config.budget.daily_usd = Some(0.0);
+        assert!(config.validate().is_err());
+
+        config.budget.daily_usd = Some(10.0);
+        config.budget.monthly_usd = Some(-5.0);
+        assert!(config.validate().is_err());
+
+        config.budget.monthly_usd = Some(300.0);
+        config.budget.warning_pct = 120.0;
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_limits_validation() {
+        let mut config = Config::default();
+        config.limits.plan = Some("max5".parse().unwrap());
+        assert!(config.validate().is_ok());
+
+        config.limits.plan = Some(Plan::Custom);
+        assert!(config.validate().is_err());
+
+        config.limits.token_ceiling = Some(500_000);
+        assert!(config.validate().is_ok());
+
+        config.limits.budget_usd = Some(0.0);
+        assert!(config.validate().is_err());
+        assert!("team".parse::<Plan>().is_err());
+    }
+
+    #[test]
+    fn test_container_mode_confines_state() {
+        let mut config = Config::default();
+        config.confine_to_output_dir();
+        assert_eq!(config.paths.log_directory, PathBuf::from("logs"));
+
+        config.container.output_dir = Some(PathBuf::from("/out"));
+        config.confine_to_output_dir();
+        assert_eq!(
+            config.parse_cache.file,
+            PathBuf::from("/out/state/index.json")
+        );
+        assert_eq!(config.last_run.dir, PathBuf::from("/out/state/last-run"));
+        assert!(config.paths.log_directory.starts_with("/out"));
+    }
+
     #[test]
     fn test_config_reset_functionality() {
         // Test that reset_config_for_test works correctly
@@ -0,0 +1,138 @@
+//! Container Mode
+//!
+//! `--container DIR` (or `CLAUDE_USAGE_CONTAINER_OUTPUT`) is for scheduled
+//! report jobs in a container on a shared server. DIR is then the only
+//! directory claude-usage writes to:
+//!
+//! - state files (parse cache, last-run state, logs, self-metrics and the
+//!   pricing snapshot) move to `DIR/state/`, see
+//!   [`Config::confine_to_output_dir`];
+//! - `--output` and `export --out` paths are taken relative to DIR, and paths
+//!   leading outside it are refused;
+//! - reports read the claude-keeper backups as they are instead of running
+//!   `claude-keeper backup` when they look stale;
+//! - commands that write elsewhere (`live`, `hook install`, `archive compact
+//!   --delete-originals`) are refused.
+//!
+//! The mounts are checked before the command runs: the Claude directory
+//! (`--claude-dir`), any `--claude-root` and the backups must be readable, and
+//! DIR writable. Mount everything but DIR read-only.
+
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+use crate::config::{get_config, Config};
+
+/// Name of the file written and removed again to check the output directory
+const WRITE_CHECK_FILE: &str = ".claude-usage-write-check";
+
+/// Check that the directories read can be read and the output directory
+/// written, when container mode is on
+pub fn check_mounts(config: &Config) -> Result<()> {
+    let Some(output_dir) = &config.container.output_dir else {
+        return Ok(());
+    };
+    for dir in std::iter::once(&config.paths.claude_home).chain(&config.paths.extra_roots) {
+        fs::read_dir(dir).with_context(|| {
+            format!(
+                "Cannot read the Claude directory {}; mount it read-only and pass --claude-dir",
+                dir.display()
+            )
+        })?;
+    }
+    let backups = backup_dir();
+    if backups.exists() {
+        fs::read_dir(&backups).with_context(|| {
+            format!(
+                "Cannot read the claude-keeper backups in {}",
+                backups.display()
+            )
+        })?;
+    }
+    check_writable(output_dir)
+}
+
+fn check_writable(dir: &Path) -> Result<()> {
+    if !dir.is_dir() {
+        anyhow::bail!(
+            "Container output directory {} does not exist; mount it read-write",
+            dir.display()
+        );
+    }
+    let probe = dir.join(WRITE_CHECK_FILE);
+    fs::write(&probe, b"").with_context(|| {
+        format!(
+            "Cannot write to the container output directory {}; mount it read-write",
+            dir.display()
+        )
+    })?;
+    fs::remove_file(&probe).with_context(|| format!("Failed to remove {}", probe.display()))
+}
+
+/// claude-keeper's backup directory, which the reports read
+fn backup_dir() -> PathBuf {
+    dirs::home_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join(".claude-backup")
+}
+
+/// Where to write a file named on the command line
+///
+/// Outside container mode the path is used as given.
+pub fn output_path(path: PathBuf) -> Result<PathBuf> {
+    match &get_config().container.output_dir {
+        Some(dir) => confine(dir, path),
+        None => Ok(path),
+    }
+}
+
+/// `path` taken relative to `dir`, refused when it leads outside it
+fn confine(dir: &Path, path: PathBuf) -> Result<PathBuf> {
+    let path = if path.is_relative() {
+        dir.join(path)
+    } else {
+        path
+    };
+    if !path.starts_with(dir) || path.components().any(|c| c == Component::ParentDir) {
+        anyhow::bail!(
+            "{} is outside the container output directory {}",
+            path.display(),
+            dir.display()
+        );
+    }
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mounts_checked_and_outputs_confined() {
+        let claude = tempfile::tempdir().unwrap();
+        let out = tempfile::tempdir().unwrap();
+        let mut config = Config::default();
+        config.paths.claude_home = claude.path().to_path_buf();
+        assert!(check_mounts(&config).is_ok());
+
+        config.container.output_dir = Some(out.path().to_path_buf());
+        check_mounts(&config).unwrap();
+        assert!(!out.path().join(WRITE_CHECK_FILE).exists());
+
+        config.paths.claude_home = claude.path().join("missing");
+        assert!(check_mounts(&config).is_err());
+        config.paths.claude_home = claude.path().to_path_buf();
+        config.container.output_dir = Some(out.path().join("missing"));
+        assert!(check_mounts(&config).is_err());
+
+        let dir = Path::new("/out");
+        assert_eq!(
+            confine(dir, PathBuf::from("reports/daily.json")).unwrap(),
+            PathBuf::from("/out/reports/daily.json")
+        );
+        assert!(confine(dir, PathBuf::from("/out/daily.csv")).is_ok());
+        assert!(confine(dir, PathBuf::from("/tmp/daily.csv")).is_err());
+        assert!(confine(dir, PathBuf::from("../daily.csv")).is_err());
+    }
+}
@@ -0,0 +1,356 @@
+//! Cost Audit
+//!
+//! Compares the `costUSD` recorded in each log entry with the cost computed
+//! from its tokens at current pricing. Large differences point at upstream
+//! logging bugs or stale pricing tables, so entries outside the tolerance are
+//! counted per day and model for `--audit-costs`.
+//!
+//! A model whose recorded costs differ from token pricing in the same
+//! direction for nearly every entry, such as when an upstream tool already
+//! applied a discount, makes `auto` mode inconsistent: its entries without a
+//! `costUSD` are priced at list rates next to discounted ones. The consistency
+//! check ([`advise_cost_mode`]) recommends `display` when such entries are
+//! rare and `calculate` otherwise; `--check-mode` switches the run to it.
+
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+use crate::dedup::CostMode;
+use crate::parser::ProcessedEntry;
+
+/// Default allowed difference between recorded and computed cost, in percent
+pub const DEFAULT_TOLERANCE_PCT: f64 = 5.0;
+
+/// Differences below this many dollars are rounding, whatever the percentage
+const MIN_DIFFERENCE: f64 = 0.0001;
+
+/// Entries with a recorded cost a model needs before its costs are judged
+const MIN_CHECKED_ENTRIES: usize = 10;
+
+/// Share of a model's entries that must differ from token pricing in the
+/// same direction for the difference to be systematic
+const SYSTEMATIC_SHARE: f64 = 0.8;
+
+/// Largest share of a model's entries without a recorded cost for which
+/// `display` is recommended over `calculate`
+const DISPLAY_MAX_UNRECORDED_SHARE: f64 = 0.05;
+
+/// Entries of one day and model whose recorded cost is outside the tolerance
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CostAuditGroup {
+    /// Day of the entries (`YYYY-MM-DD`)
+    pub date: String,
+    pub model: String,
+    /// Entries of this day and model that record a cost
+    pub entries: usize,
+    /// Entries whose recorded cost is outside the tolerance
+    pub mismatched: usize,
+    /// Recorded cost of the mismatched entries
+    pub recorded_cost: f64,
+    /// Token-computed cost of the mismatched entries
+    pub computed_cost: f64,
+    /// Largest difference of a single entry, relative to its computed cost;
+    /// infinite (`null` in JSON) when a recorded cost has no priced tokens
+    pub max_difference_pct: f64,
+}
+
+/// Outcome of comparing recorded and computed costs
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CostAudit {
+    pub tolerance_pct: f64,
+    /// Entries that record a `costUSD` and were compared
+    pub checked: usize,
+    /// Entries without a recorded cost, which are priced from tokens anyway
+    pub without_recorded_cost: usize,
+    pub mismatched: usize,
+    /// Day and model groups with at least one mismatch, oldest day first
+    pub groups: Vec<CostAuditGroup>,
+}
+
+impl CostAudit {
+    /// Keep only the groups whose day or month is one of `periods`
+    pub fn in_periods(&self, periods: &[&str]) -> CostAudit {
+        let groups: Vec<CostAuditGroup> = self
+            .groups
+            .iter()
+            .filter(|group| {
+                [10, 7]
+                    .iter()
+                    .filter_map(|len| group.date.get(..*len))
+                    .any(|period| periods.contains(&period))
+            })
+            .cloned()
+            .collect();
+
+        CostAudit {
+            mismatched: groups.iter().map(|group| group.mismatched).sum(),
+            groups,
+            ..self.clone()
+        }
+    }
+}
+
+/// A model whose recorded costs systematically differ from token pricing
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ModelDivergence {
+    pub model: String,
+    /// Entries that record a cost
+    pub recorded_entries: usize,
+    /// Entries without a recorded cost, which `auto` prices from tokens
+    pub unrecorded_entries: usize,
+    /// Recorded cost of the recorded entries over their token-computed cost,
+    /// e.g. 0.8 for a 20% discount
+    pub recorded_ratio: f64,
+}
+
+/// Outcome of the cost mode consistency check
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CostModeAdvice {
+    /// Models whose recorded costs systematically differ from token pricing
+    /// while some of their entries have none, so `auto` mixes the two
+    pub divergent_models: Vec<ModelDivergence>,
+    /// Mode that prices every entry on one basis; `None` when `auto` does
+    pub recommended: Option<CostMode>,
+    /// Whether the run switched to the recommended mode
+    pub switched: bool,
+}
+
+/// Check, per model, whether `auto` mode would mix recorded costs that
+/// systematically differ from token pricing with token-priced entries
+pub fn advise_cost_mode(entries: &[ProcessedEntry], tolerance_pct: f64) -> CostModeAdvice {
+    #[derive(Default)]
+    struct ModelCosts {
+        recorded_entries: usize,
+        unrecorded_entries: usize,
+        above: usize,
+        below: usize,
+        recorded: f64,
+        computed: f64,
+    }
+
+    let mut models: BTreeMap<&str, ModelCosts> = BTreeMap::new();
+    for entry in entries {
+        let costs = models.entry(&entry.entry.message.model).or_default();
+        let Some(recorded) = entry.entry.cost_usd else {
+            costs.unrecorded_entries += 1;
+            continue;
+        };
+        let computed = entry.computed_cost();
+        costs.recorded_entries += 1;
+        costs.recorded += recorded;
+        costs.computed += computed;
+        let tolerance = (computed * tolerance_pct / 100.0).max(MIN_DIFFERENCE);
+        if recorded > computed + tolerance {
+            costs.above += 1;
+        } else if recorded < computed - tolerance {
+            costs.below += 1;
+        }
+    }
+
+    let divergent_models: Vec<ModelDivergence> = models
+        .into_iter()
+        .filter(|(_, costs)| {
+            costs.recorded_entries >= MIN_CHECKED_ENTRIES
+                && costs.unrecorded_entries > 0
+                && costs.computed > 0.0
+                && costs.above.max(costs.below) as f64
+                    >= costs.recorded_entries as f64 * SYSTEMATIC_SHARE
+        })
+        .map(|(model, costs)| ModelDivergence {
+            model: model.to_string(),
+            recorded_entries: costs.recorded_entries,
+            unrecorded_entries: costs.unrecorded_entries,
+            recorded_ratio: (costs.recorded / costs.computed * 1000.0).round() / 1000.0,
+        })
+        .collect();
+
+    let recorded: usize = divergent_models.iter().map(|m| m.recorded_entries).sum();
+    let unrecorded: usize = divergent_models.iter().map(|m| m.unrecorded_entries).sum();
+    let recommended = (!divergent_models.is_empty()).then(|| {
+        if unrecorded as f64 <= (recorded + unrecorded) as f64 * DISPLAY_MAX_UNRECORDED_SHARE {
+            CostMode::Display
+        } else {
+            CostMode::Calculate
+        }
+    });
+    CostModeAdvice {
+        divergent_models,
+        recommended,
+        switched: false,
+    }
+}
+
+/// Compare each entry's recorded cost with its token-computed cost
+pub fn audit_costs(entries: &[ProcessedEntry], tolerance_pct: f64) -> CostAudit {
+    let mut audit = CostAudit {
+        tolerance_pct,
+        ..CostAudit::default()
+    };
+    let mut groups: BTreeMap<(String, String), CostAuditGroup> = BTreeMap::new();
+
+    for entry in entries {
+        let Some(recorded) = entry.entry.cost_usd else {
+            audit.without_recorded_cost += 1;
+            continue;
+        };
+        audit.checked += 1;
+
+        let computed = entry.computed_cost();
+        let group = groups
+            .entry((entry.date.clone(), entry.entry.message.model.clone()))
+            .or_insert_with(|| CostAuditGroup {
+                date: entry.date.clone(),
+                model: entry.entry.message.model.clone(),
+                entries: 0,
+                mismatched: 0,
+                recorded_cost: 0.0,
+                computed_cost: 0.0,
+                max_difference_pct: 0.0,
+            });
+        group.entries += 1;
+
+        let difference = (recorded - computed).abs();
+        let difference_pct = if computed > 0.0 {
+            difference / computed * 100.0
+        } else if difference > 0.0 {
+            f64::INFINITY
+        } else {
+            0.0
+        };
+        if difference < MIN_DIFFERENCE || difference_pct <= tolerance_pct {
+            continue;
+        }
+
+        group.mismatched += 1;
+        group.recorded_cost += recorded;
+        group.computed_cost += computed;
+        group.max_difference_pct = group.max_difference_pct.max(difference_pct);
+        audit.mismatched += 1;
+    }
+
+    audit.groups = groups
+        .into_values()
+        .filter(|group| group.mismatched > 0)
+        .collect();
+    audit
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{MessageData, UsageData, UsageEntry};
+    use crate::parser::FileParser;
+
+    fn entry(
+        timestamp: &str,
+        model: &str,
+        output_tokens: u32,
+        cost: Option<f64>,
+    ) -> ProcessedEntry {
+        let entry = UsageEntry {
+            timestamp: timestamp.to_string(),
+            message: MessageData {
+                id: "msg".to_string(),
+                model: model.to_string(),
+                usage: Some(UsageData {
+                    input_tokens: 0,
+                    output_tokens,
+                    cache_creation_input_tokens: 0,
+                    cache_read_input_tokens: 0,
+                }),
+                secondary_usage: None,
+            },
+            cost_usd: cost,
+            request_id: "req".to_string(),
+        };
+        ProcessedEntry::new(entry, &FileParser::new(), 1).unwrap()
+    }
+
+    #[test]
+    fn test_audit_groups_mismatches_by_day_and_model() {
+        // 1000 Sonnet output tokens cost $0.015
+        let entries = [
+            entry(
+                "2025-01-14T09:00:00Z",
+                "claude-sonnet-4-20250514",
+                1000,
+                Some(0.015),
+            ),
+            entry(
+                "2025-01-14T10:00:00Z",
+                "claude-sonnet-4-20250514",
+                1000,
+                Some(0.03),
+            ),
+            entry(
+                "2025-01-14T11:00:00Z",
+                "claude-opus-4-20250514",
+                1000,
+                Some(0.075),
+            ),
+            entry(
+                "2025-01-15T09:00:00Z",
+                "claude-sonnet-4-20250514",
+                1000,
+                None,
+            ),
+            entry(
+                "2025-01-15T10:00:00Z",
+                "claude-sonnet-4-20250514",
+                1000,
+                Some(0.0155),
+            ),
+        ];
+
+        let audit = audit_costs(&entries, DEFAULT_TOLERANCE_PCT);
+        assert_eq!(audit.checked, 4);
+        assert_eq!(audit.without_recorded_cost, 1);
+        assert_eq!(audit.mismatched, 1);
+        assert_eq!(audit.groups.len(), 1);
+
+        let group = &audit.groups[0];
+        assert_eq!(group.date, "2025-01-14");
+        assert_eq!(group.model, "claude-sonnet-4-20250514");
+        assert_eq!((group.entries, group.mismatched), (2, 1));
+        assert!((group.max_difference_pct - 100.0).abs() < 1e-6);
+
+        assert_eq!(audit.in_periods(&["2025-01"]).mismatched, 1);
+        assert_eq!(audit.in_periods(&["2025-01-15"]).groups.len(), 0);
+    }
+
+    #[test]
+    fn test_systematic_divergence_recommends_cost_mode() {
+        let sonnet = "claude-sonnet-4-20250514";
+        let at = "2025-01-14T09:00:00Z";
+        // Sonnet costs recorded at a 20% discount, Opus at list price
+        let mut entries: Vec<ProcessedEntry> = (0..12)
+            .map(|_| entry(at, sonnet, 1000, Some(0.012)))
+            .chain((0..12).map(|_| entry(at, "claude-opus-4-20250514", 1000, Some(0.075))))
+            .collect();
+        let consistent = advise_cost_mode(&entries, DEFAULT_TOLERANCE_PCT);
+        assert!(consistent.divergent_models.is_empty());
+        assert_eq!(consistent.recommended, None);
+
+        entries.push(entry(at, sonnet, 1000, None));
+        let advice = advise_cost_mode(&entries, DEFAULT_TOLERANCE_PCT);
+        assert_eq!(
+            advice.divergent_models,
+            [ModelDivergence {
+                model: sonnet.to_string(),
+                recorded_entries: 12,
+                unrecorded_entries: 1,
+                recorded_ratio: 0.8,
+            }]
+        );
+        assert_eq!(advice.recommended, Some(CostMode::Calculate));
+
+        // With entries lacking a recorded cost rare, the recorded costs win
+        entries.extend((0..10).map(|_| entry(at, sonnet, 1000, Some(0.012))));
+        let advice = advise_cost_mode(&entries, DEFAULT_TOLERANCE_PCT);
+        assert_eq!(advice.recommended, Some(CostMode::Display));
+    }
+}
@@ -1,13 +1,88 @@
 //! Processing Options
 //!
 //! This module contains the ProcessOptions struct used to configure
-//! analysis operations.
+//! analysis operations. New fields are added in minor releases, so outside
+//! this crate options are built with [`ProcessOptions::builder`], which also
+//! checks that they make sense together, rather than a struct literal:
+//!
+//! ```rust
+//! use claude_usage::dedup::{Command, GroupBy, ProcessOptions};
+//!
+//! let options = ProcessOptions::builder()
+//!     .command(Command::Monthly)
+//!     .json_output(true)
+//!     .limit(6)
+//!     .group_by(GroupBy::InstanceType)
+//!     .build()?;
+//! assert_eq!(options.command, "monthly");
+//! # anyhow::Ok(())
+//! ```
+//!
+//! [`ProcessOptions::new`] and its `with_*` methods build options the same
+//! way without validation.
+//!
+//! A long scan can be stopped from another task by passing a
+//! [`CancellationToken`] with [`ProcessOptionsBuilder::cancellation`]: the
+//! analyzer checks it before each log or backup file and returns what it read
+//! so far once it is cancelled.
 
+use crate::forecast::DEFAULT_FORECAST_DAYS;
+use crate::output_version;
+use crate::pricing::attribute_entry_cost;
+use crate::ranges::{self, DateRange};
+use anyhow::{bail, Result};
 use chrono::{DateTime, Utc};
+use std::fmt;
+use std::path::PathBuf;
+
+pub use tokio_util::sync::CancellationToken;
+
+/// Commands that take [`ProcessOptions`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Command {
+    #[default]
+    Daily,
+    Monthly,
+    Session,
+    Blocks,
+    Batch,
+    Web,
+    Top,
+    Forecast,
+    Export,
+    Reprocess,
+}
+
+impl Command {
+    /// Name of the command, as stored in [`ProcessOptions::command`]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Command::Daily => "daily",
+            Command::Monthly => "monthly",
+            Command::Session => "session",
+            Command::Blocks => "blocks",
+            Command::Batch => "batch",
+            Command::Web => "web",
+            Command::Top => "top",
+            Command::Forecast => "forecast",
+            Command::Export => "export",
+            Command::Reprocess => "reprocess",
+        }
+    }
+}
+
+impl fmt::Display for Command {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
 
 #[derive(Debug, Clone)]
+#[non_exhaustive]
 pub struct ProcessOptions {
     pub json_output: bool,
+    /// Print report rows as CSV instead of the terminal report
+    pub csv_output: bool,
     pub limit: Option<usize>,
     pub since_date: Option<DateTime<Utc>>,
     pub until_date: Option<DateTime<Utc>>,
@@ -17,4 +92,629 @@ pub struct ProcessOptions {
     pub command: String,
     #[allow(dead_code)]
     pub exclude_vms: bool,
-}
\ No newline at end of file
+    pub estimate_missing_usage: bool,
+    pub provenance: bool,
+    pub group_by: GroupBy,
+    /// Fail the run when more than this percentage of log lines or backup rows fail to parse
+    pub max_error_rate: Option<f64>,
+    /// Audit recorded costs against token-computed costs, allowing this percentage difference
+    pub audit_costs: Option<f64>,
+    /// In auto mode, switch to the cost mode the consistency check recommends
+    pub check_mode: bool,
+    /// Show token columns in the daily terminal report
+    pub show_tokens: bool,
+    /// Show when work started and ended on each daily terminal report row
+    pub show_details: bool,
+    /// Order of report periods; `None` keeps each report's default
+    pub order: Option<SortOrder>,
+    /// Add per-model breakdown rows to each period
+    pub breakdown: bool,
+    /// Write the JSON or CSV report to this file, with an integrity manifest
+    pub output: Option<PathBuf>,
+    /// Report only the approximate N heaviest projects, with bounded memory
+    pub approx_top: Option<usize>,
+    /// Print only the changes since the report's last run
+    pub diff_since_last_run: bool,
+    /// Report each of these date ranges, with a comparison of their totals
+    pub ranges: Vec<DateRange>,
+    /// Only include projects matching this name, path or glob
+    pub project: Option<String>,
+    /// Show project names as aliases or hashes (see [`crate::redaction`])
+    pub redact_projects: bool,
+    /// Layout of JSON reports (see [`crate::output_version`])
+    pub output_version: u32,
+    /// Whether entry costs come from the recorded `costUSD` or from tokens
+    pub cost_mode: CostMode,
+    /// List what the date, VM and project filters left out below the report
+    pub verbose: bool,
+    /// Stop reading further files once cancelled, keeping the data read so far
+    pub cancel: Option<CancellationToken>,
+    /// What `top` ranks projects by
+    pub project_sort: ProjectSort,
+    /// Complete days `forecast` averages the daily cost over
+    pub forecast_days: u32,
+}
+
+impl Default for ProcessOptions {
+    /// Options of a plain `daily` report
+    fn default() -> Self {
+        Self::new("daily")
+    }
+}
+
+// The binary fills in the fields directly; the builder is for library callers
+#[allow(dead_code)]
+impl ProcessOptions {
+    /// Start building validated options for a `daily` report
+    pub fn builder() -> ProcessOptionsBuilder {
+        ProcessOptionsBuilder::default()
+    }
+
+    /// Options for `command` with every report option at its default
+    pub fn new(command: impl Into<String>) -> Self {
+        Self {
+            json_output: false,
+            csv_output: false,
+            limit: None,
+            since_date: None,
+            until_date: None,
+            snapshot: false,
+            command: command.into(),
+            exclude_vms: false,
+            estimate_missing_usage: false,
+            provenance: false,
+            group_by: GroupBy::default(),
+            max_error_rate: None,
+            audit_costs: None,
+            check_mode: false,
+            show_tokens: false,
+            show_details: false,
+            order: None,
+            breakdown: false,
+            output: None,
+            approx_top: None,
+            diff_since_last_run: false,
+            ranges: Vec::new(),
+            project: None,
+            redact_projects: false,
+            output_version: output_version::LATEST,
+            cost_mode: CostMode::default(),
+            verbose: false,
+            cancel: None,
+            project_sort: ProjectSort::default(),
+            forecast_days: DEFAULT_FORECAST_DAYS,
+        }
+    }
+
+    /// Whether the scan was cancelled, so the results are partial
+    pub fn is_cancelled(&self) -> bool {
+        self.cancel
+            .as_ref()
+            .is_some_and(CancellationToken::is_cancelled)
+    }
+
+    pub fn with_json_output(mut self, json_output: bool) -> Self {
+        self.json_output = json_output;
+        self
+    }
+
+    pub fn with_csv_output(mut self, csv_output: bool) -> Self {
+        self.csv_output = csv_output;
+        self
+    }
+
+    /// Show at most `limit` report periods
+    pub fn with_limit(mut self, limit: usize) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// Leave out usage before `since`
+    pub fn with_since(mut self, since: DateTime<Utc>) -> Self {
+        self.since_date = Some(since);
+        self
+    }
+
+    /// Leave out usage after `until`
+    pub fn with_until(mut self, until: DateTime<Utc>) -> Self {
+        self.until_date = Some(until);
+        self
+    }
+
+    pub fn with_exclude_vms(mut self, exclude_vms: bool) -> Self {
+        self.exclude_vms = exclude_vms;
+        self
+    }
+
+    pub fn with_estimate_missing_usage(mut self, estimate_missing_usage: bool) -> Self {
+        self.estimate_missing_usage = estimate_missing_usage;
+        self
+    }
+
+    pub fn with_provenance(mut self, provenance: bool) -> Self {
+        self.provenance = provenance;
+        self
+    }
+
+    pub fn with_group_by(mut self, group_by: GroupBy) -> Self {
+        self.group_by = group_by;
+        self
+    }
+
+    /// Fail when more than `percent` of JSONL lines fail to parse
+    pub fn with_max_error_rate(mut self, percent: f64) -> Self {
+        self.max_error_rate = Some(percent);
+        self
+    }
+
+    /// Audit recorded costs, allowing a difference of `tolerance_pct` percent
+    pub fn with_audit_costs(mut self, tolerance_pct: f64) -> Self {
+        self.audit_costs = Some(tolerance_pct);
+        self
+    }
+
+    /// Switch from auto mode to the cost mode the consistency check recommends
+    pub fn with_check_mode(mut self, check_mode: bool) -> Self {
+        self.check_mode = check_mode;
+        self
+    }
+
+    pub fn with_show_tokens(mut self, show_tokens: bool) -> Self {
+        self.show_tokens = show_tokens;
+        self
+    }
+
+    pub fn with_show_details(mut self, show_details: bool) -> Self {
+        self.show_details = show_details;
+        self
+    }
+
+    pub fn with_order(mut self, order: SortOrder) -> Self {
+        self.order = Some(order);
+        self
+    }
+
+    pub fn with_breakdown(mut self, breakdown: bool) -> Self {
+        self.breakdown = breakdown;
+        self
+    }
+
+    /// Write the report to `path` instead of printing it
+    pub fn with_output(mut self, path: impl Into<PathBuf>) -> Self {
+        self.output = Some(path.into());
+        self
+    }
+
+    /// Report only the approximate `n` heaviest projects
+    pub fn with_approx_top(mut self, n: usize) -> Self {
+        self.approx_top = Some(n);
+        self
+    }
+
+    /// Print only the changes since the report's last run
+    pub fn with_diff_since_last_run(mut self, diff_since_last_run: bool) -> Self {
+        self.diff_since_last_run = diff_since_last_run;
+        self
+    }
+
+    /// Report `range` as its own section; repeat to compare several
+    pub fn with_range(mut self, range: DateRange) -> Self {
+        self.ranges.push(range);
+        self
+    }
+
+    /// Only include projects matching `project`, a name, path or glob
+    pub fn with_project(mut self, project: impl Into<String>) -> Self {
+        self.project = Some(project.into());
+        self
+    }
+
+    pub fn with_cost_mode(mut self, cost_mode: CostMode) -> Self {
+        self.cost_mode = cost_mode;
+        self
+    }
+
+    /// List what the filters left out below the report
+    pub fn with_verbose(mut self, verbose: bool) -> Self {
+        self.verbose = verbose;
+        self
+    }
+
+    /// Stop the scan when `cancel` is cancelled
+    pub fn with_cancellation(mut self, cancel: CancellationToken) -> Self {
+        self.cancel = Some(cancel);
+        self
+    }
+
+    pub fn with_project_sort(mut self, project_sort: ProjectSort) -> Self {
+        self.project_sort = project_sort;
+        self
+    }
+
+    /// Average the last `days` complete days in `forecast`
+    pub fn with_forecast_days(mut self, days: u32) -> Self {
+        self.forecast_days = days;
+        self
+    }
+}
+
+/// Builds [`ProcessOptions`], checking them in [`build`](Self::build)
+#[derive(Debug, Clone, Default)]
+pub struct ProcessOptionsBuilder {
+    options: ProcessOptions,
+}
+
+impl From<ProcessOptions> for ProcessOptionsBuilder {
+    fn from(options: ProcessOptions) -> Self {
+        Self { options }
+    }
+}
+
+// The binary only uses the setters for options its commands share
+#[allow(dead_code)]
+impl ProcessOptionsBuilder {
+    pub fn command(mut self, command: Command) -> Self {
+        self.options.command = command.as_str().to_string();
+        self
+    }
+
+    pub fn json_output(mut self, json_output: bool) -> Self {
+        self.options.json_output = json_output;
+        self
+    }
+
+    pub fn csv_output(mut self, csv_output: bool) -> Self {
+        self.options.csv_output = csv_output;
+        self
+    }
+
+    /// Show at most `limit` report periods
+    pub fn limit(mut self, limit: usize) -> Self {
+        self.options.limit = Some(limit);
+        self
+    }
+
+    /// Leave out usage before `since`
+    pub fn since(mut self, since: DateTime<Utc>) -> Self {
+        self.options.since_date = Some(since);
+        self
+    }
+
+    /// Leave out usage after `until`
+    pub fn until(mut self, until: DateTime<Utc>) -> Self {
+        self.options.until_date = Some(until);
+        self
+    }
+
+    pub fn exclude_vms(mut self, exclude_vms: bool) -> Self {
+        self.options.exclude_vms = exclude_vms;
+        self
+    }
+
+    pub fn estimate_missing_usage(mut self, estimate_missing_usage: bool) -> Self {
+        self.options.estimate_missing_usage = estimate_missing_usage;
+        self
+    }
+
+    pub fn provenance(mut self, provenance: bool) -> Self {
+        self.options.provenance = provenance;
+        self
+    }
+
+    pub fn group_by(mut self, group_by: GroupBy) -> Self {
+        self.options.group_by = group_by;
+        self
+    }
+
+    /// Fail when more than `percent` of JSONL lines fail to parse
+    pub fn max_error_rate(mut self, percent: f64) -> Self {
+        self.options.max_error_rate = Some(percent);
+        self
+    }
+
+    /// Audit recorded costs, allowing a difference of `tolerance_pct` percent
+    pub fn audit_costs(mut self, tolerance_pct: f64) -> Self {
+        self.options.audit_costs = Some(tolerance_pct);
+        self
+    }
+
+    /// Switch from auto mode to the cost mode the consistency check recommends
+    pub fn check_mode(mut self, check_mode: bool) -> Self {
+        self.options.check_mode = check_mode;
+        self
+    }
+
+    pub fn show_tokens(mut self, show_tokens: bool) -> Self {
+        self.options.show_tokens = show_tokens;
+        self
+    }
+
+    pub fn show_details(mut self, show_details: bool) -> Self {
+        self.options.show_details = show_details;
+        self
+    }
+
+    pub fn order(mut self, order: SortOrder) -> Self {
+        self.options.order = Some(order);
+        self
+    }
+
+    pub fn breakdown(mut self, breakdown: bool) -> Self {
+        self.options.breakdown = breakdown;
+        self
+    }
+
+    /// Write the report to `path` instead of printing it
+    pub fn output(mut self, path: impl Into<PathBuf>) -> Self {
+        self.options.output = Some(path.into());
+        self
+    }
+
+    /// Report only the approximate `n` heaviest projects
+    pub fn approx_top(mut self, n: usize) -> Self {
+        self.options.approx_top = Some(n);
+        self
+    }
+
+    /// Print only the changes since the report's last run
+    pub fn diff_since_last_run(mut self, diff_since_last_run: bool) -> Self {
+        self.options.diff_since_last_run = diff_since_last_run;
+        self
+    }
+
+    /// Report `range` as its own section; repeat to compare several
+    ///
+    /// The scan covers the span of all ranges, so ranges replace `since`
+    /// and `until`.
+    pub fn range(mut self, range: DateRange) -> Self {
+        self.options.ranges.push(range);
+        self
+    }
+
+    /// Only include projects matching `project`, a name, path or glob
+    pub fn project(mut self, project: impl Into<String>) -> Self {
+        self.options.project = Some(project.into());
+        self
+    }
+
+    /// Show project names as aliases or hashes
+    pub fn redact_projects(mut self, redact_projects: bool) -> Self {
+        self.options.redact_projects = redact_projects;
+        self
+    }
+
+    /// Render JSON reports in the layout of `version`
+    pub fn output_version(mut self, version: u32) -> Self {
+        self.options.output_version = version;
+        self
+    }
+
+    pub fn cost_mode(mut self, cost_mode: CostMode) -> Self {
+        self.options.cost_mode = cost_mode;
+        self
+    }
+
+    /// List what the filters left out below the report
+    pub fn verbose(mut self, verbose: bool) -> Self {
+        self.options.verbose = verbose;
+        self
+    }
+
+    /// Stop the scan when `cancel` is cancelled
+    pub fn cancellation(mut self, cancel: CancellationToken) -> Self {
+        self.options.cancel = Some(cancel);
+        self
+    }
+
+    pub fn project_sort(mut self, project_sort: ProjectSort) -> Self {
+        self.options.project_sort = project_sort;
+        self
+    }
+
+    /// Average the last `days` complete days in `forecast`
+    pub fn forecast_days(mut self, days: u32) -> Self {
+        self.options.forecast_days = days;
+        self
+    }
+
+    /// Check the options and return them
+    ///
+    /// Fails when `since` is after `until`, a percentage is outside 0-100,
+    /// `approx_top` or `forecast_days` is zero, JSON and CSV output are both
+    /// requested, or ranges are combined with `since`, `until` or CSV.
+    pub fn build(self) -> Result<ProcessOptions> {
+        let mut options = self.options;
+        if let Some((since, until)) = ranges::span(&options.ranges) {
+            if options.since_date.is_some() || options.until_date.is_some() {
+                bail!("Date ranges cannot be combined with since or until");
+            }
+            if options.csv_output {
+                bail!("Date ranges cannot be printed as CSV");
+            }
+            options.since_date = Some(since);
+            options.until_date = Some(until);
+        }
+        if let (Some(since), Some(until)) = (options.since_date, options.until_date) {
+            if since > until {
+                bail!(
+                    "Start date {} is after end date {}",
+                    since.format("%Y-%m-%d"),
+                    until.format("%Y-%m-%d")
+                );
+            }
+        }
+        for (name, percent) in [
+            ("Maximum error rate", options.max_error_rate),
+            ("Audit tolerance", options.audit_costs),
+        ] {
+            if percent.is_some_and(|percent| !(0.0..=100.0).contains(&percent)) {
+                bail!("{} must be between 0 and 100", name);
+            }
+        }
+        if options.approx_top == Some(0) {
+            bail!("The number of top projects must be at least 1");
+        }
+        if options.forecast_days == 0 {
+            bail!("The number of forecast days must be at least 1");
+        }
+        if options.json_output && options.csv_output {
+            bail!("JSON and CSV output cannot be combined");
+        }
+        if options.check_mode && options.cost_mode != CostMode::Auto {
+            bail!("Checking the cost mode only applies to auto mode");
+        }
+        output_version::check(options.output_version)?;
+        Ok(options)
+    }
+}
+
+/// Date order of report periods, as in ccusage's `--order`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum SortOrder {
+    /// Oldest first
+    Asc,
+    /// Newest first
+    Desc,
+}
+
+/// What `top` ranks projects by, highest first
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum ProjectSort {
+    #[default]
+    Cost,
+    /// All input, output and cache tokens
+    Tokens,
+    /// Sessions with usage in the range
+    Sessions,
+}
+
+/// Where entry costs come from, as in ccusage's `--mode`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum, serde::Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CostMode {
+    /// Recorded `costUSD` when present, otherwise computed from tokens
+    #[default]
+    Auto,
+    /// Always computed from tokens at current pricing
+    Calculate,
+    /// Recorded `costUSD` only; entries without one cost nothing
+    Display,
+}
+
+impl CostMode {
+    /// Name of the mode as given to `--mode`
+    pub fn as_str(self) -> &'static str {
+        match self {
+            CostMode::Auto => "auto",
+            CostMode::Calculate => "calculate",
+            CostMode::Display => "display",
+        }
+    }
+}
+
+/// Split an entry's cost between its primary and secondary model under `mode`
+///
+/// `primary_cost` and `secondary_cost` are priced from tokens. Whenever the
+/// recorded `costUSD` is used, the secondary model's share is carved out of it
+/// as in [`attribute_entry_cost`].
+pub fn calculate_entry_cost(
+    mode: CostMode,
+    recorded_cost: Option<f64>,
+    primary_cost: f64,
+    secondary_cost: f64,
+) -> (f64, f64) {
+    match mode {
+        CostMode::Auto => attribute_entry_cost(recorded_cost, primary_cost, secondary_cost),
+        CostMode::Calculate => (primary_cost, secondary_cost),
+        CostMode::Display => attribute_entry_cost(
+            Some(recorded_cost.unwrap_or(0.0)),
+            primary_cost,
+            secondary_cost,
+        ),
+    }
+}
+
+/// How report rows are broken down
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum GroupBy {
+    #[default]
+    Project,
+    #[value(name = "instance_type")]
+    InstanceType,
+    /// Split each session across the categories of the allocations file
+    #[value(name = "cost_center")]
+    CostCenter,
+    /// Git branch each session started on, as recorded by the branch hook
+    Branch,
+    /// Claude root each session was read from: the home or one of `paths.extra_roots`
+    Root,
+    /// VM each session ran in (`vms/<name>`); other sessions by instance type
+    Vm,
+    /// Model, splitting each day of a session by the models' share of its cost
+    Model,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(day: &str) -> DateTime<Utc> {
+        format!("{}T00:00:00Z", day).parse().unwrap()
+    }
+
+    #[test]
+    fn test_builder_validates_options() {
+        let options = ProcessOptions::builder()
+            .command(Command::Session)
+            .since(date("2025-03-01"))
+            .until(date("2025-03-01"))
+            .build()
+            .unwrap();
+        assert_eq!(options.command, "session");
+        assert_eq!(options.since_date, options.until_date);
+
+        let reversed = ProcessOptions::builder()
+            .since(date("2025-03-10"))
+            .until(date("2025-03-01"))
+            .build();
+        assert!(reversed.is_err());
+        assert!(ProcessOptions::builder()
+            .max_error_rate(101.0)
+            .build()
+            .is_err());
+        assert!(ProcessOptions::builder().approx_top(0).build().is_err());
+        assert!(ProcessOptions::builder().forecast_days(0).build().is_err());
+        assert!(ProcessOptions::builder()
+            .json_output(true)
+            .csv_output(true)
+            .build()
+            .is_err());
+        assert!(ProcessOptions::builder()
+            .check_mode(true)
+            .cost_mode(CostMode::Calculate)
+            .build()
+            .is_err());
+
+        let ranges = ProcessOptions::builder()
+            .range("2025-03-10..2025-03-16".parse().unwrap())
+            .range("2025-03-01..2025-03-07".parse().unwrap())
+            .build()
+            .unwrap();
+        assert_eq!(ranges.since_date, Some(date("2025-03-01")));
+        assert!(ProcessOptions::builder()
+            .range("2025-03-01..2025-03-07".parse().unwrap())
+            .since(date("2025-03-01"))
+            .build()
+            .is_err());
+    }
+
+    #[test]
+    fn test_calculate_entry_cost_modes() {
+        let cost = |mode, recorded| calculate_entry_cost(mode, recorded, 3.0, 0.5);
+        assert_eq!(cost(CostMode::Auto, Some(2.0)), (1.5, 0.5));
+        assert_eq!(cost(CostMode::Auto, None), (3.0, 0.5));
+        assert_eq!(cost(CostMode::Calculate, Some(2.0)), (3.0, 0.5));
+        assert_eq!(cost(CostMode::Display, Some(2.0)), (1.5, 0.5));
+        assert_eq!(cost(CostMode::Display, None), (0.0, 0.0));
+    }
+}
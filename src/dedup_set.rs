@@ -0,0 +1,176 @@
+//! Sets of seen dedup keys
+//!
+//! Entries are deduplicated by their `messageId:requestId` key (see
+//! [`crate::session_utils::SessionUtils::create_unique_hash`]). With tens of
+//! millions of entries, keeping every key string costs hundreds of MB, so a
+//! [`DedupSet`] stores a 128-bit hash of each key instead, unless
+//! `dedup.keys = "exact"` asks for the strings. The hash function can be
+//! swapped with [`DedupSet::with_hasher`].
+//!
+//! Two distinct keys sharing a hash would drop one entry as a duplicate. The
+//! odds for a set of n keys are given by [`collision_probability`] and logged
+//! at debug level with the set's size by [`DedupSet::log_usage`].
+
+use dashmap::DashSet;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::mem::size_of;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use tracing::debug;
+
+use crate::config::{get_config, DedupKeys};
+
+/// Function turning a dedup key into the 128-bit hash that is stored
+pub type KeyHasher = fn(&str) -> u128;
+
+/// Keys seen so far, as hashes or as strings
+#[derive(Debug)]
+pub struct DedupSet {
+    keys: Keys,
+    hasher: KeyHasher,
+    /// Bytes of the key strings held in [`Keys::Exact`]
+    key_bytes: AtomicUsize,
+}
+
+#[derive(Debug)]
+enum Keys {
+    Hashed(DashSet<u128>),
+    Exact(DashSet<String>),
+}
+
+impl Default for DedupSet {
+    fn default() -> Self {
+        Self::new(DedupKeys::default())
+    }
+}
+
+impl DedupSet {
+    /// An empty set storing keys as `mode` says, hashed with [`sip128`]
+    pub fn new(mode: DedupKeys) -> Self {
+        let keys = match mode {
+            DedupKeys::Hashed => Keys::Hashed(DashSet::new()),
+            DedupKeys::Exact => Keys::Exact(DashSet::new()),
+        };
+        Self {
+            keys,
+            hasher: sip128,
+            key_bytes: AtomicUsize::new(0),
+        }
+    }
+
+    /// An empty set storing keys as `dedup.keys` says
+    pub fn from_config() -> Self {
+        Self::new(get_config().dedup.keys)
+    }
+
+    /// Hash keys with `hasher` instead of [`sip128`]; ignored for exact keys
+    #[allow(dead_code)]
+    pub fn with_hasher(mut self, hasher: KeyHasher) -> Self {
+        self.hasher = hasher;
+        self
+    }
+
+    /// Record `key`, returning whether it was not seen before
+    pub fn insert(&self, key: &str) -> bool {
+        match &self.keys {
+            Keys::Hashed(set) => set.insert((self.hasher)(key)),
+            Keys::Exact(set) => {
+                let inserted = set.insert(key.to_string());
+                if inserted {
+                    self.key_bytes.fetch_add(key.len(), Ordering::Relaxed);
+                }
+                inserted
+            }
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        match &self.keys {
+            Keys::Hashed(set) => set.len(),
+            Keys::Exact(set) => set.len(),
+        }
+    }
+
+    #[allow(dead_code)]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Approximate heap memory held by the set, in bytes
+    ///
+    /// Counts each allocated slot (its value plus one control byte) and, for
+    /// exact keys, the key strings themselves.
+    pub fn approx_bytes(&self) -> usize {
+        match &self.keys {
+            Keys::Hashed(set) => set.capacity() * (size_of::<u128>() + 1),
+            Keys::Exact(set) => {
+                set.capacity() * (size_of::<String>() + 1) + self.key_bytes.load(Ordering::Relaxed)
+            }
+        }
+    }
+
+    /// Log the set's size, memory use and collision odds at debug level
+    pub fn log_usage(&self, set: &str) {
+        let hashed = matches!(self.keys, Keys::Hashed(_));
+        debug!(
+            set,
+            keys = self.len(),
+            approx_kb = self.approx_bytes() / 1024,
+            hashed,
+            collision_probability = if hashed {
+                collision_probability(self.len() as u64)
+            } else {
+                0.0
+            },
+            "Dedup set usage"
+        );
+    }
+}
+
+/// 128-bit hash of `key` from two SipHash passes with different prefixes
+///
+/// Only stable within one process, which is all a dedup set needs.
+pub fn sip128(key: &str) -> u128 {
+    let half = |prefix: u8| {
+        let mut hasher = DefaultHasher::new();
+        prefix.hash(&mut hasher);
+        key.hash(&mut hasher);
+        hasher.finish()
+    };
+    (u128::from(half(0)) << 64) | u128::from(half(1))
+}
+
+/// Probability that any two of `keys` distinct keys share a 128-bit hash
+///
+/// The birthday bound n(n-1)/2 / 2^128, accurate while it is small.
+pub fn collision_probability(keys: u64) -> f64 {
+    let pairs = keys as f64 * keys.saturating_sub(1) as f64 / 2.0;
+    pairs / 2f64.powi(128)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dedup_set_modes_and_hasher() {
+        for mode in [DedupKeys::Hashed, DedupKeys::Exact] {
+            let set = DedupSet::new(mode);
+            assert!(set.insert("msg_1:req_1"));
+            assert!(set.insert("msg_1:req_2"));
+            assert!(!set.insert("msg_1:req_1"));
+            assert_eq!(set.len(), 2);
+            assert!(set.approx_bytes() >= 2 * size_of::<u128>());
+        }
+
+        // A hasher that maps every key to one value sees only one key
+        let set = DedupSet::new(DedupKeys::Hashed).with_hasher(|_| 7);
+        assert!(set.insert("a"));
+        assert!(!set.insert("b"));
+
+        assert_ne!(sip128("msg_1:req_1"), sip128("msg_1:req_2"));
+        assert_eq!(collision_probability(1), 0.0);
+        let odds = collision_probability(100_000_000);
+        assert!(odds > 1e-24 && odds < 1e-22, "{}", odds);
+    }
+}
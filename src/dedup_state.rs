@@ -0,0 +1,181 @@
+//! Dedup keys remembered between runs
+//!
+//! A [`crate::dedup_set::DedupSet`] lives for one run, which is enough for
+//! reports since every run reads all the logs again. Live mode only sees
+//! entries as they are streamed, so after a restart it cannot tell a
+//! re-streamed entry from a new one. With `dedup.persist` set, live mode
+//! keeps the `messageId:requestId` keys it has counted, with the time each
+//! was first seen, in a [`DedupState`] saved to `dedup.state_file`
+//! (`~/.cache/claude-usage/dedup.db` by default) and loaded on the next start.
+//!
+//! Keys seen more than `dedup.window_hours` ago are dropped when the state is
+//! loaded or saved, so the file stays small. Keys are stored as strings: the
+//! 128-bit hashes of a `DedupSet` are only stable within one process. A state
+//! file that cannot be read is ignored and rewritten.
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tracing::{debug, warn};
+
+use crate::config::get_config;
+
+/// Layout version of the state file
+const FORMAT_VERSION: u32 = 1;
+
+/// On-disk layout of the state
+#[derive(Serialize, Deserialize)]
+struct StateFile {
+    version: u32,
+    /// Key → when it was first seen
+    keys: HashMap<String, DateTime<Utc>>,
+}
+
+/// Dedup keys and when they were first seen, saved between runs
+#[derive(Debug)]
+pub struct DedupState {
+    path: PathBuf,
+    window: Duration,
+    keys: HashMap<String, DateTime<Utc>>,
+    changed: bool,
+}
+
+impl DedupState {
+    /// Load the state at `path`, dropping keys seen more than `window_hours`
+    /// before `now`; a missing or unreadable state is empty
+    pub fn load(path: &Path, window_hours: i64, now: DateTime<Utc>) -> Self {
+        let mut state = Self {
+            path: path.to_path_buf(),
+            window: Duration::hours(window_hours),
+            keys: HashMap::new(),
+            changed: false,
+        };
+        let content = match fs::read(path) {
+            Ok(content) => content,
+            Err(_) => return state,
+        };
+        match serde_json::from_slice::<StateFile>(&content) {
+            Ok(file) if file.version == FORMAT_VERSION => state.keys = file.keys,
+            Ok(file) => {
+                debug!(
+                    file = %path.display(),
+                    version = file.version,
+                    "Discarding dedup state of another format"
+                );
+                state.changed = true;
+            }
+            Err(e) => {
+                warn!(file = %path.display(), error = %e, "Ignoring unreadable dedup state");
+                state.changed = true;
+            }
+        }
+
+        state.prune(now);
+        debug!(file = %path.display(), keys = state.keys.len(), "Loaded dedup state");
+        state
+    }
+
+    /// The state at `dedup.state_file`, or `None` unless `dedup.persist` is
+    /// set and dedup is enabled
+    pub fn from_config() -> Option<Self> {
+        let config = &get_config().dedup;
+        (config.enabled && config.persist)
+            .then(|| Self::load(&config.state_file, config.window_hours, Utc::now()))
+    }
+
+    /// Record `key` as seen at `now`, returning whether it was not seen
+    /// within the window before
+    pub fn insert(&mut self, key: &str, now: DateTime<Utc>) -> bool {
+        if let Some(seen) = self.keys.get(key) {
+            if now - *seen <= self.window {
+                return false;
+            }
+        }
+        self.keys.insert(key.to_string(), now);
+        self.changed = true;
+        true
+    }
+
+    #[allow(dead_code)]
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    #[allow(dead_code)]
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    /// Write the state if it changed since it was loaded or last saved,
+    /// dropping keys that fell out of the window by `now`
+    pub fn save(&mut self, now: DateTime<Utc>) -> Result<()> {
+        self.prune(now);
+        if !self.changed {
+            return Ok(());
+        }
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create {}", parent.display()))?;
+        }
+
+        let content = serde_json::to_vec(&serde_json::json!({
+            "version": FORMAT_VERSION,
+            "keys": self.keys,
+        }))?;
+        let temp_path = self.path.with_extension("db.tmp");
+        fs::write(&temp_path, content)
+            .with_context(|| format!("Failed to write dedup state: {}", temp_path.display()))?;
+        fs::rename(&temp_path, &self.path)
+            .with_context(|| format!("Failed to save dedup state: {}", self.path.display()))?;
+        self.changed = false;
+        debug!(file = %self.path.display(), keys = self.keys.len(), "Saved dedup state");
+        Ok(())
+    }
+
+    /// Drop keys seen more than the window before `now`
+    fn prune(&mut self, now: DateTime<Utc>) {
+        let before = self.keys.len();
+        let window = self.window;
+        self.keys.retain(|_, seen| now - *seen <= window);
+        self.changed |= self.keys.len() != before;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_state_survives_reload_within_window() {
+        let temp = TempDir::new().unwrap();
+        let path = temp.path().join("cache").join("dedup.db");
+        let start = Utc.with_ymd_and_hms(2025, 3, 1, 10, 0, 0).unwrap();
+
+        let mut state = DedupState::load(&path, 24, start);
+        assert!(state.is_empty());
+        assert!(state.insert("msg_1:req_1", start));
+        assert!(!state.insert("msg_1:req_1", start));
+        assert!(state.insert("msg_2:req_2", start + Duration::hours(12)));
+        state.save(start + Duration::hours(12)).unwrap();
+
+        // A restart an hour later still knows both keys
+        let later = start + Duration::hours(13);
+        let mut state = DedupState::load(&path, 24, later);
+        assert_eq!(state.len(), 2);
+        assert!(!state.insert("msg_1:req_1", later));
+
+        // The first key falls out of the window on the next load
+        let next_day = start + Duration::hours(30);
+        let mut state = DedupState::load(&path, 24, next_day);
+        assert_eq!(state.len(), 1);
+        assert!(state.insert("msg_1:req_1", next_day));
+
+        fs::write(&path, "not json").unwrap();
+        assert!(DedupState::load(&path, 24, next_day).is_empty());
+    }
+}
@@ -10,6 +10,7 @@
 //! - [`LiveDisplayManager`] - Terminal UI manager using ratatui with crossterm backend
 //! - [`RunningTotals`] - Running totals for cost, tokens, and sessions
 //! - [`SessionActivity`] - Recent activity tracking with timestamps
+//! - [`ProjectActivity`] - Per-project live totals for the drill-down view
 //!
 //! ## TUI Layout
 //!
@@ -18,24 +19,41 @@
 //! ```text
 //! ┌─ Claude Usage Live ─────────────────────────┐
 //! │ Total: $45.23 | Tokens: 1.2M | Sessions: 15 │
-//! ├──────────────────────────────────────────────┤
-//! │ Current Session                              │
-//! │ ├─ Cost: $2.10                              │
-//! │ ├─ Duration: 5m 23s                         │
-//! │ └─ Tokens: In 10K / Out 15K                 │
+//! ├─ ● vm1 connected  ✗ vm2 failed ─────────────┤
+//! │ Current Session         │ Burn Rate $/min    │
+//! │ ├─ Cost: $2.10          │      ⡀    ⢀⠔⠉⠢⡀    │
+//! │ ├─ Duration: 5m 23s     │ ⣀⣀⠤⠊⠈⠢⣀⠔⠁     ⠈⠒⠤ │
+//! │ └─ Tokens: In 10K / Out │                    │
 //! ├──────────────────────────────────────────────┤
 //! │ Recent Activity (↑/↓ to scroll)             │
-//! │ [12:05:23] Project A: +500 tokens ($0.05)   │
-//! │ [12:04:15] Project B: +1200 tokens ($0.12)  │
-//! │ [12:03:45] Project A: +300 tokens ($0.03)   │
+//! │ [12:05:23] [vm1] Project A: +500 tokens     │
+//! │ [12:04:15] [vm2] Project B: +1200 tokens    │
+//! │ [12:03:45] [vm1] Project A: +300 tokens     │
 //! └─────────────────────── Ctrl+C to exit ──────┘
 //! ```
 //!
 //! ## Features
 //!
 //! - **Real-time Updates**: Processes live updates via async channels from orchestrator
-//! - **Ring Buffer**: Maintains exactly 100 recent entries with FIFO behavior
-//! - **Keyboard Navigation**: ↑/↓ arrows for scrolling, Ctrl+C to exit
+//! - **Ring Buffer**: Keeps the latest `[live] activity_limit` entries (100 by default)
+//! - **Activity Order**: `s` cycles the list between newest, most expensive
+//!   and most tokens first, and `g` collapses consecutive updates of one
+//!   session into a single row with a counter
+//! - **Burn Rate**: Braille chart of cost per minute over the last hour, kept
+//!   as a ring buffer of minute buckets; `t` switches it to tokens per minute
+//! - **Multiple Instances**: Labels activity by keeper instance and shows each
+//!   instance's health in the header when several are configured
+//! - **Idle State**: Shows when the local keeper is paused for lack of updates
+//! - **Daily Budget**: Shows today's spend against `[budget] daily_usd` in the
+//!   header, highlighted once the warning or critical percentage is crossed
+//! - **Keyboard Navigation**: ↑/↓ arrows move the selected activity row,
+//!   Enter opens a drill-down of its project (tokens by type, cost today,
+//!   recent messages) and Esc goes back, `s`/`g` sort and group activity,
+//!   `t` charts tokens or cost, `p` pauses and resumes updates, `r` resets
+//!   the since-launch counters, `S` (Shift+S, as `s` sorts) saves a JSON
+//!   snapshot, Ctrl+C exits
+//! - **Mouse**: Clicking a pane focuses it, clicking the Time, Tokens or Cost
+//!   header sorts the activity list by that column, and the wheel scrolls it
 //! - **Responsive Design**: Handles terminal resize gracefully
 //! - **Memory Efficient**: No unbounded growth, fixed-size buffers
 //!
@@ -46,17 +64,19 @@
 //! ```rust
 //! use claude_usage::display::run_display;
 //! use claude_usage::live::{BaselineSummary, LiveUpdate};
+//! use claude_usage::live::budget::BudgetBoard;
+//! use claude_usage::live::instances::HealthBoard;
 //!
 //! let baseline = BaselineSummary::default();
 //! let (tx, rx) = tokio::sync::mpsc::channel(100);
 //!
 //! // Run display in async context
-//! run_display(baseline, rx).await?;
+//! run_display(baseline, rx, HealthBoard::default(), BudgetBoard::default()).await?;
 //! ```
 
 #[cfg(feature = "live")]
 pub mod tui;
-pub mod state;
+mod state;
 #[cfg(feature = "live")]
 pub mod widgets;
 
@@ -65,10 +85,14 @@ pub use tui::*;
 #[cfg(feature = "live")]
 pub use state::*;
 
-use crate::live::{BaselineSummary, LiveUpdate};
+use crate::live::budget::BudgetBoard;
+use crate::live::instances::HealthBoard;
+use crate::live::{BaselineSummary, LiveExitSummary, LiveUpdate};
 use anyhow::Result;
 use tokio::sync::mpsc;
 #[cfg(feature = "live")]
+use std::collections::VecDeque;
+#[cfg(feature = "live")]
 use std::time::{Duration, SystemTime};
 
 /// Main entry point for running the live display
@@ -80,25 +104,32 @@ use std::time::{Duration, SystemTime};
 ///
 /// * `baseline` - Summary of existing usage data from parquet files
 /// * `update_receiver` - Channel for receiving real-time updates
+/// * `health` - Health of the claude-keeper instances feeding the updates
+/// * `budget` - Today's spend against the daily budget, if one is configured
 ///
 /// # Returns
 ///
-/// Returns `Ok(())` when the display exits normally, or an error if
-/// terminal setup or update processing fails.
+/// Returns the exit summary (totals and burn-rate history) when the display
+/// exits normally, or an error if terminal setup or update processing fails.
 #[cfg(feature = "live")]
 pub async fn run_display(
     baseline: BaselineSummary,
-    update_receiver: mpsc::Receiver<LiveUpdate>
-) -> Result<()> {
-    let mut display_manager = LiveDisplayManager::new(baseline, update_receiver).await?;
+    update_receiver: mpsc::Receiver<LiveUpdate>,
+    health: HealthBoard,
+    budget: BudgetBoard,
+) -> Result<LiveExitSummary> {
+    let mut display_manager =
+        LiveDisplayManager::new(baseline, update_receiver, health, budget).await?;
     display_manager.run().await
 }
 
 #[cfg(not(feature = "live"))]
 pub async fn run_display(
     _baseline: BaselineSummary,
-    _update_receiver: mpsc::Receiver<LiveUpdate>
-) -> Result<()> {
+    _update_receiver: mpsc::Receiver<LiveUpdate>,
+    _health: HealthBoard,
+    _budget: BudgetBoard,
+) -> Result<LiveExitSummary> {
     anyhow::bail!("Live display not available. Rebuild with --features live")
 }
 
@@ -112,6 +143,12 @@ pub struct RunningTotals {
     pub total_tokens: u64,
     /// Total number of sessions
     pub total_sessions: u32,
+    /// Cost of live updates since launch or the last reset
+    pub since_launch_cost: f64,
+    /// Tokens of live updates since launch or the last reset
+    pub since_launch_tokens: u64,
+    /// Whether the since-launch counters have been reset by the user
+    pub since_launch_reset: bool,
 }
 
 #[cfg(feature = "live")]
@@ -122,6 +159,9 @@ impl RunningTotals {
             total_cost: baseline.total_cost,
             total_tokens: baseline.total_tokens,
             total_sessions: baseline.sessions_today,
+            since_launch_cost: 0.0,
+            since_launch_tokens: 0,
+            since_launch_reset: false,
         }
     }
 
@@ -129,13 +169,23 @@ impl RunningTotals {
     pub fn update(&mut self, update: &LiveUpdate) {
         if let Some(cost) = update.entry.cost_usd {
             self.total_cost += cost;
+            self.since_launch_cost += cost;
         }
 
         if let Some(ref usage) = update.entry.message.usage {
-            self.total_tokens += (usage.input_tokens + usage.output_tokens +
+            let tokens = (usage.input_tokens + usage.output_tokens +
                 usage.cache_creation_input_tokens + usage.cache_read_input_tokens) as u64;
+            self.total_tokens += tokens;
+            self.since_launch_tokens += tokens;
         }
     }
+
+    /// Zero the since-launch counters, keeping baseline and overall totals
+    pub fn reset_since_launch(&mut self) {
+        self.since_launch_cost = 0.0;
+        self.since_launch_tokens = 0;
+        self.since_launch_reset = true;
+    }
 }
 
 #[cfg(feature = "live")]
@@ -154,6 +204,10 @@ pub struct SessionActivity {
     pub cost: f64,
     /// Session ID this activity belongs to
     pub session_id: String,
+    /// Keeper instance the activity came from, when several are watched
+    pub instance: Option<String>,
+    /// Updates merged into this row when consecutive activity is collapsed
+    pub count: u32,
 }
 
 #[cfg(feature = "live")]
@@ -195,7 +249,76 @@ impl SessionActivity {
             tokens,
             cost,
             session_id: update.session_stats.session_id.clone(),
+            instance: update.instance.clone(),
+            count: 1,
         }
     }
 }
 
+#[cfg(feature = "live")]
+/// Latest messages kept per project for its drill-down
+const PROJECT_RECENT_MESSAGES: usize = 10;
+
+#[cfg(feature = "live")]
+/// A message in a project's drill-down
+#[derive(Debug, Clone)]
+pub struct ProjectMessage {
+    /// Human-readable time string (e.g., "12:05:23")
+    pub time_str: String,
+    pub model: String,
+    pub tokens: u32,
+    pub cost: f64,
+}
+
+#[cfg(feature = "live")]
+/// Live activity of one project since launch, shown in its drill-down
+#[derive(Debug, Clone, Default)]
+pub struct ProjectActivity {
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub cache_creation_tokens: u64,
+    pub cache_read_tokens: u64,
+    /// Local day `cost_today` covers
+    pub day: String,
+    /// Cost of the project's updates on `day`
+    pub cost_today: f64,
+    /// Live updates received for the project
+    pub updates: u32,
+    /// When the latest update arrived
+    pub last_seen: Option<SystemTime>,
+    /// Latest messages, newest first
+    pub recent: VecDeque<ProjectMessage>,
+}
+
+#[cfg(feature = "live")]
+impl ProjectActivity {
+    /// Add an update of the project, logged on local `day`
+    pub fn record(&mut self, update: &LiveUpdate, activity: &SessionActivity, day: &str) {
+        if let Some(ref usage) = update.entry.message.usage {
+            self.input_tokens += u64::from(usage.input_tokens);
+            self.output_tokens += u64::from(usage.output_tokens);
+            self.cache_creation_tokens += u64::from(usage.cache_creation_input_tokens);
+            self.cache_read_tokens += u64::from(usage.cache_read_input_tokens);
+        }
+
+        // A late entry from an earlier day leaves today's cost alone
+        if day > self.day.as_str() {
+            self.day = day.to_string();
+            self.cost_today = 0.0;
+        }
+        if day == self.day {
+            self.cost_today += activity.cost;
+        }
+
+        self.updates += 1;
+        self.last_seen = Some(update.timestamp);
+        self.recent.push_front(ProjectMessage {
+            time_str: activity.time_str.clone(),
+            model: update.entry.message.model.clone(),
+            tokens: activity.tokens,
+            cost: activity.cost,
+        });
+        self.recent.truncate(PROJECT_RECENT_MESSAGES);
+    }
+}
+
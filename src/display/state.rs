@@ -4,20 +4,148 @@
 //! for recent activities, current session tracking, and running totals.
 
 #[cfg(feature = "live")]
-use crate::live::{BaselineSummary, LiveUpdate};
+use crate::config::ActivitySort;
 #[cfg(feature = "live")]
-use crate::models::SessionData;
+use crate::live::budget::BudgetStatus;
 #[cfg(feature = "live")]
-use super::{RunningTotals, SessionActivity};
+use crate::live::instances::InstanceHealth;
 #[cfg(feature = "live")]
-use std::collections::{HashMap, VecDeque};
+use crate::live::snapshot::{LiveSnapshot, SnapshotActivity};
+#[cfg(feature = "live")]
+use crate::live::{BaselineSummary, BurnRatePoint, LiveDayTotals, LiveExitSummary, LiveUpdate};
+#[cfg(feature = "live")]
+use crate::models::{SessionData, SessionDuration};
+#[cfg(feature = "live")]
+use super::{ProjectActivity, RunningTotals, SessionActivity};
+#[cfg(feature = "live")]
+use std::collections::{BTreeMap, VecDeque};
 #[cfg(feature = "live")]
 use std::time::{Duration, SystemTime};
 
 #[cfg(feature = "live")]
-/// Maximum number of recent entries to keep in the ring buffer
+/// Recent entries kept in the ring buffer unless `[live] activity_limit` says otherwise
 const MAX_RECENT_ENTRIES: usize = 100;
 
+#[cfg(feature = "live")]
+/// Minutes of per-minute history kept for the burn-rate chart
+pub const BURN_RATE_WINDOW_MINUTES: u64 = 60;
+
+#[cfg(feature = "live")]
+/// What the burn-rate chart plots per minute; `t` switches between them
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum BurnRateMetric {
+    #[default]
+    Cost,
+    Tokens,
+}
+
+#[cfg(feature = "live")]
+/// Usage received during one minute
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct MinuteBucket {
+    /// Minutes since the Unix epoch
+    minute: u64,
+    cost: f64,
+    tokens: u64,
+}
+
+#[cfg(feature = "live")]
+/// Cost and tokens received per minute over the last [`BURN_RATE_WINDOW_MINUTES`]
+#[derive(Debug, Clone, Default)]
+pub struct BurnRateHistory {
+    /// Ring buffer of the minutes with usage, oldest first
+    buckets: VecDeque<MinuteBucket>,
+}
+
+#[cfg(feature = "live")]
+impl BurnRateHistory {
+    /// Add `cost` and `tokens` to the minute containing `at`, dropping
+    /// minutes outside the window
+    pub fn record(&mut self, at: SystemTime, cost: f64, tokens: u64) {
+        let minute = epoch_minute(at);
+        // Updates arrive in order, so the minute is almost always the newest
+        let position = self
+            .buckets
+            .iter()
+            .rposition(|bucket| bucket.minute <= minute);
+        match position {
+            Some(i) if self.buckets[i].minute == minute => {
+                self.buckets[i].cost += cost;
+                self.buckets[i].tokens += tokens;
+            }
+            _ => {
+                let bucket = MinuteBucket {
+                    minute,
+                    cost,
+                    tokens,
+                };
+                self.buckets.insert(position.map_or(0, |i| i + 1), bucket);
+            }
+        }
+
+        let newest = self.buckets.back().map_or(minute, |bucket| bucket.minute);
+        let oldest = newest.saturating_sub(BURN_RATE_WINDOW_MINUTES - 1);
+        while self
+            .buckets
+            .front()
+            .is_some_and(|bucket| bucket.minute < oldest)
+        {
+            self.buckets.pop_front();
+        }
+    }
+
+    /// Cost per minute for the window ending at `now`, oldest first, with
+    /// minutes without usage as zero
+    pub fn series(&self, now: SystemTime) -> Vec<(u64, f64)> {
+        self.series_of(now, BurnRateMetric::Cost)
+    }
+
+    /// `metric` per minute for the window ending at `now`, oldest first
+    pub fn series_of(&self, now: SystemTime, metric: BurnRateMetric) -> Vec<(u64, f64)> {
+        let last = epoch_minute(now);
+        let first = last.saturating_sub(BURN_RATE_WINDOW_MINUTES - 1);
+        (first..=last)
+            .map(|minute| {
+                let bucket = self.buckets.iter().find(|bucket| bucket.minute == minute);
+                let value = bucket.map_or(0.0, |bucket| match metric {
+                    BurnRateMetric::Cost => bucket.cost,
+                    BurnRateMetric::Tokens => bucket.tokens as f64,
+                });
+                (minute, value)
+            })
+            .collect()
+    }
+}
+
+#[cfg(feature = "live")]
+/// Local day of an update's entry, or of its arrival if the entry has no
+/// readable timestamp
+fn local_date(update: &LiveUpdate) -> String {
+    let at = chrono::DateTime::parse_from_rfc3339(&update.entry.timestamp)
+        .map(|at| at.with_timezone(&chrono::Local))
+        .unwrap_or_else(|_| chrono::DateTime::<chrono::Local>::from(update.timestamp));
+    at.format("%Y-%m-%d").to_string()
+}
+
+#[cfg(feature = "live")]
+fn epoch_minute(at: SystemTime) -> u64 {
+    at.duration_since(SystemTime::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+        / 60
+}
+
+#[cfg(feature = "live")]
+/// A bordered area of the dashboard, which can be focused by clicking it
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Pane {
+    Header,
+    Session,
+    BurnRate,
+    #[default]
+    Activity,
+}
+
 #[cfg(feature = "live")]
 /// Core display state for the live monitoring TUI
 #[derive(Debug)]
@@ -25,18 +153,40 @@ pub struct LiveDisplay {
     /// Baseline summary from parquet files
     #[allow(dead_code)]
     pub baseline: BaselineSummary,
-    /// Ring buffer of recent activities (max 100, FIFO)
+    /// Ring buffer of recent activities, newest first
     pub recent_entries: VecDeque<SessionActivity>,
+    /// Entries kept in `recent_entries`
+    pub activity_limit: usize,
+    /// Order the activity list is shown in
+    pub activity_sort: ActivitySort,
+    /// Whether consecutive activity of one session is shown as one row
+    pub collapse_activity: bool,
+    /// Pane drawn with a highlighted border, the last one clicked
+    pub focused_pane: Pane,
     /// Current active session, if any
     pub current_session: Option<SessionData>,
     /// Running totals including baseline and live updates
     pub running_totals: RunningTotals,
     /// Current scroll position for recent activities
     pub scroll_position: usize,
-    /// Track sessions and their start times for duration calculation
-    session_start_times: HashMap<String, SystemTime>,
-    /// Last update timestamp for calculating session duration
-    last_update_time: SystemTime,
+    /// Selected row of the activity list, once ↑/↓ moved the selection
+    pub selected_row: Option<usize>,
+    /// Project whose drill-down is shown in place of the activity list
+    pub drill_down: Option<String>,
+    /// Live activity per project, for the drill-down
+    pub projects: BTreeMap<String, ProjectActivity>,
+    /// Cost and tokens per minute over the last hour, for the burn-rate chart
+    pub burn_rate: BurnRateHistory,
+    /// Whether the burn-rate chart plots cost or tokens
+    pub burn_rate_metric: BurnRateMetric,
+    /// Live updates of the current local day, for `--reconcile-on-exit`
+    pub today: LiveDayTotals,
+    /// Latest health of each claude-keeper instance, by name
+    pub instance_health: BTreeMap<String, InstanceHealth>,
+    /// Today's spend against the daily budget, if one is configured
+    pub budget: Option<BudgetStatus>,
+    /// Whether updates are left waiting in the channel instead of applied
+    pub paused: bool,
 }
 
 #[cfg(feature = "live")]
@@ -48,56 +198,96 @@ impl LiveDisplay {
         Self {
             baseline,
             recent_entries: VecDeque::with_capacity(MAX_RECENT_ENTRIES),
+            activity_limit: MAX_RECENT_ENTRIES,
+            activity_sort: ActivitySort::default(),
+            collapse_activity: false,
+            focused_pane: Pane::default(),
             current_session: None,
             running_totals,
             scroll_position: 0,
-            session_start_times: HashMap::new(),
-            last_update_time: SystemTime::now(),
+            selected_row: None,
+            drill_down: None,
+            projects: BTreeMap::new(),
+            burn_rate: BurnRateHistory::default(),
+            burn_rate_metric: BurnRateMetric::default(),
+            today: LiveDayTotals::default(),
+            instance_health: BTreeMap::new(),
+            budget: None,
+            paused: false,
         }
     }
 
+    /// Show activity in `sort` order, collapsed if `collapse`, keeping `limit` entries
+    pub fn with_activity_options(
+        mut self,
+        sort: ActivitySort,
+        collapse: bool,
+        limit: usize,
+    ) -> Self {
+        self.activity_sort = sort;
+        self.collapse_activity = collapse;
+        self.activity_limit = limit.max(1);
+        self
+    }
+
     /// Update display state with a new live update
     pub fn update(&mut self, update: LiveUpdate) {
-        self.last_update_time = update.timestamp;
-
         // Update running totals
         self.running_totals.update(&update);
-
-        // Track session start time
-        let session_id = update.session_stats.session_id.clone();
-        self.session_start_times
-            .entry(session_id.clone())
-            .or_insert(update.timestamp);
+        let tokens = update.entry.message.usage.as_ref().map_or(0, |usage| {
+            (usage.input_tokens
+                + usage.output_tokens
+                + usage.cache_creation_input_tokens
+                + usage.cache_read_input_tokens) as u64
+        });
+        self.burn_rate.record(
+            update.timestamp,
+            update.entry.cost_usd.unwrap_or(0.0),
+            tokens,
+        );
+        let day = local_date(&update);
+        self.today.record(&day, update.entry.cost_usd, tokens);
 
         // Update current session
         self.current_session = Some(update.session_stats.clone());
 
         // Add to recent activities
         let activity = SessionActivity::from_update(&update);
+        self.projects
+            .entry(activity.project.clone())
+            .or_default()
+            .record(&update, &activity, &day);
         self.add_recent_activity(activity);
     }
 
     /// Add a new activity to the ring buffer
     fn add_recent_activity(&mut self, activity: SessionActivity) {
+        // A collapsed list merges the activity into the top row if it is the same session's
+        let merged = self.collapse_activity
+            && self.recent_entries.front().is_some_and(|top| {
+                top.session_id == activity.session_id && top.instance == activity.instance
+            });
         self.recent_entries.push_front(activity);
         
         // Maintain ring buffer size
-        if self.recent_entries.len() > MAX_RECENT_ENTRIES {
-            self.recent_entries.pop_back();
-        }
-
-        // Reset scroll position to show newest entries
-        self.scroll_position = 0;
-    }
+        self.recent_entries.truncate(self.activity_limit);
 
-    /// Get current session duration if there's an active session
-    pub fn get_current_session_duration(&self) -> Option<Duration> {
-        if let Some(ref session) = self.current_session {
-            if let Some(&start_time) = self.session_start_times.get(&session.session_id) {
-                return self.last_update_time.duration_since(start_time).ok();
+        match self.selected_row.as_mut() {
+            // Keep the selected row in place under a new newest row
+            Some(row) => {
+                if self.activity_sort == ActivitySort::Time && !merged {
+                    *row += 1;
+                    self.scroll_position += 1;
+                }
             }
+            // Reset scroll position to show newest entries
+            None => self.scroll_position = 0,
         }
-        None
+    }
+
+    /// Duration of the current session, from the times of its entries
+    pub fn get_current_session_duration(&self) -> Option<SessionDuration> {
+        self.current_session.as_ref()?.duration()
     }
 
     /// Scroll up in the recent activities list
@@ -107,23 +297,150 @@ impl LiveDisplay {
         }
     }
 
+    /// Show the activity list in the next sort order
+    pub fn cycle_activity_sort(&mut self) {
+        self.set_activity_sort(self.activity_sort.next());
+    }
+
+    /// Show the activity list in `sort` order, from the top
+    pub fn set_activity_sort(&mut self, sort: ActivitySort) {
+        self.activity_sort = sort;
+        self.scroll_position = 0;
+        self.selected_row = None;
+    }
+
+    /// Switch between one row per update and one row per run of a session's updates
+    pub fn toggle_collapse_activity(&mut self) {
+        self.collapse_activity = !self.collapse_activity;
+        self.scroll_position = 0;
+        self.selected_row = None;
+    }
+
+    /// Select the next activity row, or the top visible one if none is
+    /// selected, scrolling to keep it among the `visible_lines`
+    pub fn select_next(&mut self, visible_lines: usize) {
+        let rows = self.activity_rows().len();
+        if rows == 0 {
+            return;
+        }
+        let row = self
+            .selected_row
+            .map_or(self.scroll_position, |row| row + 1)
+            .min(rows - 1);
+        self.select_row(row, visible_lines);
+    }
+
+    /// Select the previous activity row, or the top visible one if none is
+    /// selected
+    pub fn select_previous(&mut self, visible_lines: usize) {
+        let rows = self.activity_rows().len();
+        if rows == 0 {
+            return;
+        }
+        let row = self
+            .selected_row
+            .map_or(self.scroll_position, |row| row.min(rows).saturating_sub(1))
+            .min(rows - 1);
+        self.select_row(row, visible_lines);
+    }
+
+    fn select_row(&mut self, row: usize, visible_lines: usize) {
+        self.selected_row = Some(row);
+        if row < self.scroll_position {
+            self.scroll_position = row;
+        } else if row >= self.scroll_position + visible_lines {
+            self.scroll_position = row + 1 - visible_lines.max(1);
+        }
+    }
+
+    /// Show the drill-down of the selected row's project
+    pub fn open_drill_down(&mut self) {
+        let rows = self.activity_rows();
+        if let Some(row) = self.selected_row.and_then(|row| rows.get(row)) {
+            self.drill_down = Some(row.project.clone());
+        }
+    }
+
+    /// Leave the drill-down, or clear the selection when none is shown
+    pub fn go_back(&mut self) {
+        if self.drill_down.take().is_none() {
+            self.selected_row = None;
+        }
+    }
+
+    /// Forget the live activity of projects without updates for longer
+    /// than `ttl`, except the project whose drill-down is open
+    pub fn prune_idle_projects(&mut self, now: SystemTime, ttl: Duration) {
+        let open = self.drill_down.as_deref();
+        self.projects.retain(|project, activity| {
+            Some(project.as_str()) == open
+                || activity
+                    .last_seen
+                    .is_some_and(|seen| now.duration_since(seen).unwrap_or_default() <= ttl)
+        });
+    }
+
+    /// The project whose drill-down is shown, with its live activity
+    pub fn drill_down_project(&self) -> Option<(&str, &ProjectActivity)> {
+        let project = self.drill_down.as_deref()?;
+        Some((project, self.projects.get(project)?))
+    }
+
+    /// Rows of the activity list: consecutive updates of one session merged
+    /// when collapsing, then ordered by the sort mode
+    pub fn activity_rows(&self) -> Vec<SessionActivity> {
+        let mut rows: Vec<SessionActivity> = Vec::with_capacity(self.recent_entries.len());
+        for activity in &self.recent_entries {
+            match rows.last_mut() {
+                Some(row)
+                    if self.collapse_activity
+                        && row.session_id == activity.session_id
+                        && row.instance == activity.instance =>
+                {
+                    // Rows are newest first, so the row keeps the latest time
+                    row.tokens = row.tokens.saturating_add(activity.tokens);
+                    row.cost += activity.cost;
+                    row.count += activity.count;
+                }
+                _ => rows.push(activity.clone()),
+            }
+        }
+
+        match self.activity_sort {
+            ActivitySort::Time => {}
+            ActivitySort::Cost => rows.sort_by(|a, b| b.cost.total_cmp(&a.cost)),
+            ActivitySort::Tokens => rows.sort_by_key(|row| std::cmp::Reverse(row.tokens)),
+        }
+        rows
+    }
+
+    /// How the activity list is ordered and grouped, for its title
+    pub fn format_activity_view(&self) -> String {
+        let order = match self.activity_sort {
+            ActivitySort::Time => "newest",
+            ActivitySort::Cost => "by cost",
+            ActivitySort::Tokens => "by tokens",
+        };
+        if self.collapse_activity {
+            format!("{}, by session", order)
+        } else {
+            order.to_string()
+        }
+    }
+
     /// Scroll down in the recent activities list
     pub fn scroll_down(&mut self, visible_lines: usize) {
-        let max_scroll = if self.recent_entries.len() > visible_lines {
-            self.recent_entries.len() - visible_lines
-        } else {
-            0
-        };
-        
+        let max_scroll = self.activity_rows().len().saturating_sub(visible_lines);
+
         if self.scroll_position < max_scroll {
             self.scroll_position += 1;
         }
     }
 
     /// Get visible recent activities based on scroll position and available space
-    pub fn get_visible_activities(&self, visible_lines: usize) -> Vec<&SessionActivity> {
-        self.recent_entries
-            .iter()
+    pub fn get_visible_activities(&self, visible_lines: usize) -> Vec<SessionActivity> {
+        self.activity_rows()
+            .into_iter()
             .skip(self.scroll_position)
             .take(visible_lines)
             .collect()
@@ -133,7 +450,7 @@ impl LiveDisplay {
     pub fn format_current_session(&self) -> Option<String> {
         if let Some(ref session) = self.current_session {
             let duration = self.get_current_session_duration()
-                .map(|d| format!("{}m {}s", d.as_secs() / 60, d.as_secs() % 60))
+                .map(|d| format!("{} active", SessionDuration::format_secs(d.active_secs)))
                 .unwrap_or_else(|| "0s".to_string());
 
             let project_name = session.project_path
@@ -156,20 +473,164 @@ impl LiveDisplay {
 
     /// Format running totals for display
     pub fn format_totals(&self) -> String {
+        let since_label = if self.running_totals.since_launch_reset {
+            "Since reset"
+        } else {
+            "Since launch"
+        };
+
         format!(
-            "Total: ${:.2} | Tokens: {:.1}M | Sessions: {}",
+            "Total: ${:.2} | Tokens: {:.1}M | Sessions: {} | {}: ${:.2} / {:.1}K tokens",
             self.running_totals.total_cost,
             self.running_totals.total_tokens as f64 / 1_000_000.0,
-            self.running_totals.total_sessions
+            self.running_totals.total_sessions,
+            since_label,
+            self.running_totals.since_launch_cost,
+            self.running_totals.since_launch_tokens as f64 / 1_000.0
         )
     }
 
+    /// Replace the instance health shown in the header
+    pub fn set_instance_health(&mut self, health: BTreeMap<String, InstanceHealth>) {
+        self.instance_health = health;
+    }
+
+    /// Format per-instance health, or `None` unless several instances are
+    /// watched or one is paused while idle
+    pub fn format_instances(&self) -> Option<String> {
+        let idle = self
+            .instance_health
+            .values()
+            .any(|health| matches!(health, InstanceHealth::Idle { .. }));
+        if self.instance_health.len() < 2 && !idle {
+            return None;
+        }
+
+        let parts: Vec<String> = self
+            .instance_health
+            .iter()
+            .map(|(name, health)| {
+                let (symbol, label) = health.indicator();
+                format!("{} {} {}", symbol, name, label)
+            })
+            .collect();
+        Some(parts.join("  "))
+    }
+
+    /// Replace the budget status shown in the header
+    pub fn set_budget(&mut self, budget: Option<BudgetStatus>) {
+        self.budget = budget;
+    }
+
+    /// Format today's spend against the daily budget, if one is configured
+    pub fn format_budget(&self) -> Option<String> {
+        self.budget.as_ref().map(|budget| {
+            format!(
+                "Today: ${:.2} / ${:.2} ({:.0}%)",
+                budget.spent,
+                budget.limit,
+                budget.percent()
+            )
+        })
+    }
+
+    /// Switch the burn-rate chart between cost and tokens per minute
+    pub fn toggle_burn_rate_metric(&mut self) {
+        self.burn_rate_metric = match self.burn_rate_metric {
+            BurnRateMetric::Cost => BurnRateMetric::Tokens,
+            BurnRateMetric::Tokens => BurnRateMetric::Cost,
+        };
+    }
+
+    /// Per-minute points of the burn-rate chart, in the chosen metric
+    pub fn burn_rate_series(&self, now: SystemTime) -> Vec<(u64, f64)> {
+        self.burn_rate.series_of(now, self.burn_rate_metric)
+    }
+
+    /// Format the burn-rate chart title with the latest and peak value per minute
+    pub fn format_burn_rate(&self, now: SystemTime) -> String {
+        let series = self.burn_rate_series(now);
+        let latest = series.last().map_or(0.0, |&(_, value)| value);
+        let peak = series.iter().map(|&(_, value)| value).fold(0.0, f64::max);
+        match self.burn_rate_metric {
+            BurnRateMetric::Cost => {
+                format!("Burn Rate ${:.2}/min (peak ${:.2}, 1h)", latest, peak)
+            }
+            BurnRateMetric::Tokens => format!(
+                "Burn Rate {:.1}K tokens/min (peak {:.1}K, 1h)",
+                latest / 1_000.0,
+                peak / 1_000.0
+            ),
+        }
+    }
+
+    /// Totals and burn-rate history for the exit summary
+    pub fn exit_summary(&self, now: SystemTime) -> LiveExitSummary {
+        let burn_rate = self
+            .burn_rate
+            .series(now)
+            .into_iter()
+            .map(|(minute, cost)| BurnRatePoint {
+                minute: chrono::DateTime::from_timestamp(minute as i64 * 60, 0)
+                    .map(|start| start.to_rfc3339())
+                    .unwrap_or_default(),
+                cost,
+            })
+            .collect();
+
+        LiveExitSummary {
+            total_cost: self.running_totals.total_cost,
+            total_tokens: self.running_totals.total_tokens,
+            since_launch_cost: self.running_totals.since_launch_cost,
+            since_launch_tokens: self.running_totals.since_launch_tokens,
+            burn_rate,
+            today: self.today.clone(),
+            reconciliation: None,
+        }
+    }
+
+    /// The totals and recent activity rows as shown, for `S`
+    pub fn snapshot(&self, now: SystemTime) -> LiveSnapshot {
+        let rfc3339 = |at: SystemTime| chrono::DateTime::<chrono::Utc>::from(at).to_rfc3339();
+        let recent_activity = self
+            .activity_rows()
+            .into_iter()
+            .map(|activity| SnapshotActivity {
+                time: rfc3339(activity.timestamp),
+                project: activity.project,
+                session_id: activity.session_id,
+                instance: activity.instance,
+                tokens: activity.tokens,
+                cost: activity.cost,
+                count: activity.count,
+            })
+            .collect();
+
+        LiveSnapshot {
+            saved_at: rfc3339(now),
+            paused: self.paused,
+            total_cost: self.running_totals.total_cost,
+            total_tokens: self.running_totals.total_tokens,
+            total_sessions: self.running_totals.total_sessions,
+            since_launch_cost: self.running_totals.since_launch_cost,
+            since_launch_tokens: self.running_totals.since_launch_tokens,
+            today: self.today.clone(),
+            recent_activity,
+        }
+    }
+
+    /// Reset the since-launch counters to measure a new piece of work
+    pub fn reset_session_counters(&mut self) {
+        self.running_totals.reset_since_launch();
+    }
+
     /// Get scroll indicator text
     pub fn get_scroll_indicator(&self, visible_lines: usize) -> String {
-        if self.recent_entries.len() <= visible_lines {
+        let rows = self.activity_rows().len();
+        if rows <= visible_lines {
             "".to_string()
         } else {
-            let total_pages = (self.recent_entries.len() + visible_lines - 1) / visible_lines;
+            let total_pages = (rows + visible_lines - 1) / visible_lines;
             let current_page = (self.scroll_position / visible_lines) + 1;
             format!(" ({}/{})", current_page, total_pages)
         }
@@ -177,24 +638,15 @@ impl LiveDisplay {
 
     /// Check if there are activities to scroll through
     pub fn can_scroll(&self, visible_lines: usize) -> bool {
-        self.recent_entries.len() > visible_lines
-    }
-
-    /// Clean up old session start times to prevent memory growth
-    pub fn cleanup_old_sessions(&mut self) {
-        let cutoff_time = SystemTime::now() - Duration::from_secs(3600); // 1 hour ago
-        
-        self.session_start_times.retain(|_, &mut start_time| {
-            start_time > cutoff_time
-        });
+        self.activity_rows().len() > visible_lines
     }
 }
 
 #[cfg(all(test, feature = "live"))]
 mod tests {
     use super::*;
-    use crate::models::{MessageData, UsageData, UsageEntry};
-    use std::time::SystemTime;
+    use crate::models::{MessageData, MicroDollars, UsageData, UsageEntry};
+    use std::time::{Duration, SystemTime};
 
     fn create_test_update(session_id: &str, project: &str, tokens: u32, cost: f64) -> LiveUpdate {
         LiveUpdate {
@@ -209,6 +661,7 @@ mod tests {
                         cache_creation_input_tokens: 0,
                         cache_read_input_tokens: 0,
                     }),
+                    secondary_usage: None,
                 },
                 cost_usd: Some(cost),
                 request_id: "req1".to_string(),
@@ -216,9 +669,10 @@ mod tests {
             session_stats: {
                 let mut data = SessionData::new(session_id.to_string(), project.to_string());
                 data.input_tokens = tokens;
-                data.total_cost = cost;
+                data.total_cost = MicroDollars::from_dollars(cost);
                 data
             },
+            instance: None,
             timestamp: SystemTime::now(),
         }
     }
@@ -268,6 +722,108 @@ mod tests {
         assert_eq!(display.scroll_position, 0);
     }
 
+    #[test]
+    fn test_activity_sort_collapse_and_limit() {
+        let mut display = LiveDisplay::new(BaselineSummary::default()).with_activity_options(
+            ActivitySort::Cost,
+            false,
+            4,
+        );
+        for (session, tokens, cost) in [
+            ("s1", 100, 0.10),
+            ("s1", 300, 0.05),
+            ("s2", 200, 0.40),
+            ("s1", 50, 0.20),
+            ("s1", 150, 0.30),
+        ] {
+            display.update(create_test_update(session, "project", tokens, cost));
+        }
+
+        // The oldest update fell out of the buffer
+        assert_eq!(display.recent_entries.len(), 4);
+        let costs: Vec<f64> = display.activity_rows().iter().map(|a| a.cost).collect();
+        assert_eq!(costs, vec![0.40, 0.30, 0.20, 0.05]);
+
+        display.cycle_activity_sort();
+        assert_eq!(display.activity_sort, ActivitySort::Tokens);
+        assert_eq!(display.activity_rows()[0].tokens, 300);
+
+        // Collapsed, newest first: s1 (two updates), s2, s1
+        display.cycle_activity_sort();
+        display.toggle_collapse_activity();
+        assert_eq!(display.format_activity_view(), "newest, by session");
+        let rows = display.activity_rows();
+        assert_eq!(rows.len(), 3);
+        assert_eq!((rows[0].session_id.as_str(), rows[0].count), ("s1", 2));
+        assert_eq!(rows[0].tokens, 200);
+        assert!((rows[0].cost - 0.50).abs() < 1e-9);
+        assert_eq!((rows[1].session_id.as_str(), rows[1].count), ("s2", 1));
+        assert_eq!(rows[2].count, 1);
+    }
+
+    #[test]
+    fn test_selection_and_project_drill_down() {
+        let mut display = LiveDisplay::new(BaselineSummary::default());
+        display.update(create_test_update("s1", "/src/app", 100, 0.10));
+        display.update(create_test_update("s2", "/src/api", 200, 0.20));
+        display.update(create_test_update("s3", "/src/app", 300, 0.30));
+
+        // Nothing is selected until ↑/↓ is pressed
+        display.open_drill_down();
+        assert_eq!(display.drill_down, None);
+
+        display.select_next(2);
+        assert_eq!(display.selected_row, Some(0));
+        display.select_next(2);
+        display.select_next(2);
+        display.select_next(2);
+        assert_eq!(display.selected_row, Some(2));
+        assert_eq!(display.scroll_position, 1);
+
+        // A new update keeps the selected row in place
+        display.update(create_test_update("s4", "/src/api", 400, 0.40));
+        assert_eq!(display.selected_row, Some(3));
+        assert_eq!(display.scroll_position, 2);
+        display.select_previous(2);
+        display.select_previous(2);
+        assert_eq!(display.selected_row, Some(1));
+        assert_eq!(display.scroll_position, 1);
+
+        display.open_drill_down();
+        let (project, activity) = display.drill_down_project().unwrap();
+        assert_eq!(project, "app");
+        assert_eq!(activity.updates, 2);
+        assert_eq!(activity.input_tokens, 400);
+        assert!((activity.cost_today - 0.40).abs() < 1e-9);
+        assert_eq!(activity.recent[0].tokens, 300);
+        assert_eq!(activity.recent[0].model, "claude-3-5-sonnet-20241022");
+
+        // Esc closes the drill-down, then clears the selection
+        display.go_back();
+        assert_eq!(display.drill_down, None);
+        assert_eq!(display.selected_row, Some(1));
+        display.go_back();
+        assert_eq!(display.selected_row, None);
+    }
+
+    #[test]
+    fn test_idle_projects_pruned() {
+        let mut display = LiveDisplay::new(BaselineSummary::default());
+        display.update(create_test_update("s1", "/src/app", 100, 0.10));
+        display.update(create_test_update("s2", "/src/api", 200, 0.20));
+        let ttl = Duration::from_secs(600);
+
+        display.prune_idle_projects(SystemTime::now(), ttl);
+        assert_eq!(display.projects.len(), 2);
+
+        // The open drill-down is kept however long it has been idle
+        display.drill_down = Some("api".to_string());
+        display.prune_idle_projects(SystemTime::now() + Duration::from_secs(900), ttl);
+        assert_eq!(display.projects.keys().collect::<Vec<_>>(), ["api"]);
+        // Running totals are not affected
+        assert!((display.running_totals.since_launch_cost - 0.30).abs() < 1e-9);
+    }
+
     #[test]
     fn test_running_totals_update() {
         let baseline = BaselineSummary {
@@ -285,4 +841,152 @@ mod tests {
         assert_eq!(display.running_totals.total_cost, 10.5);
         assert_eq!(display.running_totals.total_tokens, 6000);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_reset_session_counters_keeps_totals() {
+        let baseline = BaselineSummary {
+            total_cost: 10.0,
+            total_tokens: 5000,
+            sessions_today: 2,
+            last_backup: SystemTime::UNIX_EPOCH,
+        };
+
+        let mut display = LiveDisplay::new(baseline);
+        display.update(create_test_update("session1", "project", 1000, 0.5));
+        assert_eq!(display.running_totals.since_launch_cost, 0.5);
+        assert_eq!(display.running_totals.since_launch_tokens, 1000);
+
+        display.reset_session_counters();
+        assert_eq!(display.running_totals.since_launch_cost, 0.0);
+        assert_eq!(display.running_totals.since_launch_tokens, 0);
+        assert_eq!(display.running_totals.total_cost, 10.5);
+        assert!(display.format_totals().contains("Since reset"));
+
+        display.update(create_test_update("session1", "project", 200, 0.25));
+        assert_eq!(display.running_totals.since_launch_cost, 0.25);
+        assert_eq!(display.running_totals.total_cost, 10.75);
+    }
+
+    #[test]
+    fn test_instance_labels_and_health() {
+        let mut display = LiveDisplay::new(BaselineSummary::default());
+        let mut update = create_test_update("session1", "project", 100, 0.01);
+        update.instance = Some("vm1".to_string());
+        display.update(update);
+        assert_eq!(display.recent_entries[0].instance.as_deref(), Some("vm1"));
+
+        let mut health = BTreeMap::new();
+        health.insert("vm1".to_string(), InstanceHealth::Connecting);
+        display.set_instance_health(health.clone());
+        assert_eq!(display.format_instances(), None);
+
+        // A lone instance is shown while it is paused
+        let since = SystemTime::now();
+        let mut idle = BTreeMap::new();
+        idle.insert("local".to_string(), InstanceHealth::Idle { since });
+        display.set_instance_health(idle);
+        let label = chrono::DateTime::<chrono::Local>::from(since).format("%H:%M");
+        assert_eq!(
+            display.format_instances(),
+            Some(format!("☾ local idle since {}", label))
+        );
+
+        health.insert("vm2".to_string(), InstanceHealth::Failed("gone".to_string()));
+        display.set_instance_health(health);
+        assert_eq!(
+            display.format_instances().as_deref(),
+            Some("◌ vm1 connecting  ✗ vm2 failed")
+        );
+    }
+
+    #[test]
+    fn test_budget_status_in_header() {
+        use crate::live::budget::BudgetLevel;
+
+        let mut display = LiveDisplay::new(BaselineSummary::default());
+        assert_eq!(display.format_budget(), None);
+
+        display.set_budget(Some(BudgetStatus {
+            day: chrono::NaiveDate::from_ymd_opt(2025, 3, 1).unwrap(),
+            spent: 8.5,
+            limit: 10.0,
+            level: BudgetLevel::Warning,
+        }));
+        assert_eq!(
+            display.format_budget().as_deref(),
+            Some("Today: $8.50 / $10.00 (85%)")
+        );
+    }
+
+    #[test]
+    fn test_burn_rate_history() {
+        let start = SystemTime::UNIX_EPOCH + Duration::from_secs(1_000 * 60);
+        let mut history = BurnRateHistory::default();
+        history.record(start, 0.25, 1_000);
+        history.record(start + Duration::from_secs(120), 1.0, 4_000);
+        // A late update still lands in its own minute
+        history.record(start + Duration::from_secs(30), 0.25, 500);
+
+        let series = history.series(start + Duration::from_secs(120));
+        assert_eq!(series.len(), BURN_RATE_WINDOW_MINUTES as usize);
+        assert_eq!(series[57], (1_000, 0.5));
+        assert_eq!(series[58], (1_001, 0.0));
+        assert_eq!(series[59], (1_002, 1.0));
+        let tokens = history.series_of(start + Duration::from_secs(120), BurnRateMetric::Tokens);
+        assert_eq!(tokens[57], (1_000, 1_500.0));
+        assert_eq!(tokens[59], (1_002, 4_000.0));
+
+        // Minutes older than the window are dropped
+        history.record(start + Duration::from_secs(90 * 60), 0.1, 100);
+        assert_eq!(history.buckets.len(), 1);
+    }
+
+    #[test]
+    fn test_exit_summary_includes_burn_rate_series() {
+        let mut display = LiveDisplay::new(BaselineSummary::default());
+        let mut update = create_test_update("session1", "project", 100, 0.5);
+        update.timestamp = SystemTime::UNIX_EPOCH + Duration::from_secs(60);
+        display.update(update);
+
+        let summary = display.exit_summary(SystemTime::UNIX_EPOCH + Duration::from_secs(90));
+        assert_eq!(summary.since_launch_cost, 0.5);
+        assert_eq!(summary.burn_rate.len(), 2);
+        assert_eq!(summary.burn_rate[1].minute, "1970-01-01T00:01:00+00:00");
+        assert_eq!(summary.burn_rate[1].cost, 0.5);
+        assert_eq!(summary.today.cost, 0.5);
+        assert_eq!(summary.today.entries, 1);
+
+        // Resetting the since-launch counters keeps the day's totals
+        display.reset_session_counters();
+        let summary = display.exit_summary(SystemTime::UNIX_EPOCH + Duration::from_secs(90));
+        assert_eq!(summary.since_launch_cost, 0.0);
+        assert_eq!(summary.today.cost, 0.5);
+        assert!(display
+            .format_burn_rate(SystemTime::UNIX_EPOCH + Duration::from_secs(90))
+            .contains("$0.50/min"));
+        display.toggle_burn_rate_metric();
+        assert!(display
+            .format_burn_rate(SystemTime::UNIX_EPOCH + Duration::from_secs(90))
+            .contains("tokens/min"));
+    }
+
+    #[test]
+    fn test_snapshot_lists_rows_as_shown() {
+        let mut display = LiveDisplay::new(BaselineSummary::default());
+        display.set_activity_sort(ActivitySort::Cost);
+        display.update(create_test_update("session1", "cheap", 100, 0.1));
+        display.update(create_test_update("session2", "pricey", 200, 0.9));
+        display.paused = true;
+
+        let snapshot = display.snapshot(SystemTime::UNIX_EPOCH + Duration::from_secs(60));
+        assert_eq!(snapshot.saved_at, "1970-01-01T00:01:00+00:00");
+        assert!(snapshot.paused);
+        assert_eq!(snapshot.since_launch_tokens, 300);
+        let projects: Vec<&str> = snapshot
+            .recent_activity
+            .iter()
+            .map(|row| row.project.as_str())
+            .collect();
+        assert_eq!(projects, ["pricey", "cheap"]);
+    }
+}
@@ -3,11 +3,17 @@
 //! This module provides the main TUI implementation using ratatui with crossterm backend.
 //! It handles terminal setup, event processing, and the main display loop.
 
-use super::{LiveDisplay, widgets::{render_live_display, AppTheme}};
-use crate::live::{BaselineSummary, LiveUpdate};
+use super::widgets::{activity_sort_at, pane_areas, render_live_display, AppTheme, PaneAreas};
+use super::{LiveDisplay, Pane};
+use crate::live::budget::BudgetBoard;
+use crate::live::instances::HealthBoard;
+use crate::live::{BaselineSummary, LiveExitSummary, LiveUpdate};
 use anyhow::{Context, Result};
 use crossterm::{
-    event::{self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyEventKind},
+    event::{
+        self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyEventKind, MouseButton,
+        MouseEvent, MouseEventKind,
+    },
     execute,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
@@ -16,7 +22,7 @@ use ratatui::{
     Terminal,
 };
 use std::io::{self, Stdout};
-use std::time::{Duration, Instant};
+use std::time::{Duration, Instant, SystemTime};
 use tokio::sync::mpsc;
 
 /// Update interval for the display (milliseconds)
@@ -33,12 +39,20 @@ pub struct LiveDisplayManager {
     display_state: LiveDisplay,
     /// Channel for receiving live updates
     update_receiver: mpsc::Receiver<LiveUpdate>,
+    /// Health of the claude-keeper instances
+    health: HealthBoard,
+    /// Today's spend against the daily budget
+    budget: BudgetBoard,
     /// Theme for styling the UI
     theme: AppTheme,
     /// Last error message to display
     error_message: Option<String>,
-    /// Last cleanup time for memory management
-    last_cleanup: Instant,
+    /// Outcome of the last key press, shown on the status line until the next one
+    notice: Option<String>,
+    /// Where the panes were last drawn, for mouse hit-testing
+    panes: Option<PaneAreas>,
+    /// Set once the user asked to exit and the terminal was restored
+    exited: bool,
 }
 
 impl LiveDisplayManager {
@@ -46,23 +60,34 @@ impl LiveDisplayManager {
     pub async fn new(
         baseline: BaselineSummary,
         update_receiver: mpsc::Receiver<LiveUpdate>,
+        health: HealthBoard,
+        budget: BudgetBoard,
     ) -> Result<Self> {
         let terminal = setup_terminal()?;
-        let display_state = LiveDisplay::new(baseline);
+        let live = &crate::config::get_config().live;
+        let display_state = LiveDisplay::new(baseline).with_activity_options(
+            live.activity_sort,
+            live.collapse_activity,
+            live.activity_limit,
+        );
         let theme = AppTheme::default();
 
         Ok(Self {
             terminal,
             display_state,
             update_receiver,
+            health,
+            budget,
             theme,
             error_message: None,
-            last_cleanup: Instant::now(),
+            notice: None,
+            panes: None,
+            exited: false,
         })
     }
 
-    /// Run the display loop
-    pub async fn run(&mut self) -> Result<()> {
+    /// Run the display loop until the user exits, returning the exit summary
+    pub async fn run(&mut self) -> Result<LiveExitSummary> {
         let mut last_update = Instant::now();
 
         loop {
@@ -70,6 +95,9 @@ impl LiveDisplayManager {
             if let Err(e) = self.handle_events().await {
                 self.error_message = Some(format!("Event handling error: {}", e));
             }
+            if self.exited {
+                return Ok(self.display_state.exit_summary(SystemTime::now()));
+            }
 
             // Process live updates (non-blocking)
             if let Err(e) = self.process_updates().await {
@@ -81,12 +109,6 @@ impl LiveDisplayManager {
                 self.error_message = Some(format!("Rendering error: {}", e));
             }
 
-            // Periodic cleanup to prevent memory growth
-            if self.last_cleanup.elapsed() > Duration::from_secs(300) { // 5 minutes
-                self.display_state.cleanup_old_sessions();
-                self.last_cleanup = Instant::now();
-            }
-
             // Control update rate
             let elapsed = last_update.elapsed();
             if elapsed < Duration::from_millis(UPDATE_INTERVAL_MS) {
@@ -104,34 +126,64 @@ impl LiveDisplayManager {
             match event::read()? {
                 Event::Key(key) => {
                     if key.kind == KeyEventKind::Press {
+                        self.notice = None;
                         match key.code {
                             KeyCode::Char('c') if key.modifiers.contains(event::KeyModifiers::CONTROL) => {
                                 return self.exit().await;
                             },
                             KeyCode::Up => {
-                                self.display_state.scroll_up();
+                                self.display_state.select_previous(self.activity_lines());
                                 // Clear any error message when user interacts
                                 self.error_message = None;
                             },
                             KeyCode::Down => {
-                                // Use the last known size or default
-                                let activity_height = 10; // Default scroll amount
-                                self.display_state.scroll_down(activity_height);
+                                self.display_state.select_next(self.activity_lines());
                                 // Clear any error message when user interacts
                                 self.error_message = None;
                             },
+                            KeyCode::Enter => {
+                                self.display_state.open_drill_down();
+                                self.error_message = None;
+                            },
+                            KeyCode::Esc => {
+                                self.display_state.go_back();
+                                self.error_message = None;
+                            },
                             KeyCode::Char('q') => {
                                 return self.exit().await;
                             },
+                            KeyCode::Char('s') => {
+                                self.display_state.cycle_activity_sort();
+                                self.error_message = None;
+                            },
+                            KeyCode::Char('g') => {
+                                self.display_state.toggle_collapse_activity();
+                                self.error_message = None;
+                            },
+                            KeyCode::Char('t') => {
+                                self.display_state.toggle_burn_rate_metric();
+                                self.error_message = None;
+                            },
+                            // The only pause and resume key; `r` resets counters
+                            KeyCode::Char('p') => {
+                                self.display_state.paused = !self.display_state.paused;
+                                self.error_message = None;
+                            },
+                            KeyCode::Char('S') => {
+                                self.save_snapshot();
+                            },
                             KeyCode::Char('r') => {
-                                // Reset scroll position
+                                // Reset since-launch counters, scroll position and selection
+                                self.display_state.reset_session_counters();
                                 self.display_state.scroll_position = 0;
+                                self.display_state.selected_row = None;
                                 self.error_message = None;
                             },
                             _ => {}
                         }
                     }
                 },
+                Event::Mouse(mouse) => self.handle_mouse(mouse),
                 Event::Resize(_, _) => {
                     // Terminal was resized, ratatui will handle this automatically
                 },
@@ -141,16 +193,74 @@ impl LiveDisplayManager {
         Ok(())
     }
 
+    /// Focus the clicked pane, sort by a clicked activity column and scroll
+    /// the activity list with the wheel
+    fn handle_mouse(&mut self, mouse: MouseEvent) {
+        let Some(panes) = self.panes else {
+            return;
+        };
+        let pane = panes.pane_at(mouse.column, mouse.row);
+        match mouse.kind {
+            MouseEventKind::Down(MouseButton::Left) => {
+                if let Some(pane) = pane {
+                    self.display_state.focused_pane = pane;
+                }
+                // The drill-down has no sortable columns
+                let sort = activity_sort_at(panes.activity, mouse.column, mouse.row)
+                    .filter(|_| self.display_state.drill_down.is_none());
+                if let Some(sort) = sort {
+                    self.display_state.set_activity_sort(sort);
+                }
+                self.error_message = None;
+            }
+            MouseEventKind::ScrollUp if pane == Some(Pane::Activity) => {
+                self.display_state.scroll_up();
+            }
+            MouseEventKind::ScrollDown if pane == Some(Pane::Activity) => {
+                self.display_state.scroll_down(panes.activity_lines());
+            }
+            _ => {}
+        }
+    }
+
+    /// Activity rows on screen, as of the last render
+    fn activity_lines(&self) -> usize {
+        // Before the first render, scroll as if the list showed ten rows
+        self.panes.map_or(10, |panes| panes.activity_lines())
+    }
+
+    /// Save the dashboard to a timestamped file in `[live] snapshot_dir`
+    fn save_snapshot(&mut self) {
+        let now = SystemTime::now();
+        let dir = &crate::config::get_config().live.snapshot_dir;
+        match self.display_state.snapshot(now).save(dir, now.into()) {
+            Ok(path) => {
+                self.notice = Some(format!("Saved snapshot to {}", path.display()));
+                self.error_message = None;
+            }
+            Err(e) => self.error_message = Some(format!("Snapshot failed: {:#}", e)),
+        }
+    }
+
     /// Process pending live updates from the channel
     async fn process_updates(&mut self) -> Result<()> {
-        // Process all available updates without blocking
-        while let Ok(update) = self.update_receiver.try_recv() {
+        // While paused, updates wait in the channel (and the keeper once it is full)
+        while !self.display_state.paused {
+            let Ok(update) = self.update_receiver.try_recv() else {
+                break;
+            };
             self.display_state.update(update);
             // Clear error message on successful update
             if self.error_message.is_some() {
                 self.error_message = None;
             }
         }
+        if let Some(ttl) = crate::config::get_config().live.session_ttl() {
+            self.display_state
+                .prune_idle_projects(SystemTime::now(), ttl);
+        }
+        self.display_state.set_instance_health(self.health.snapshot());
+        self.display_state.set_budget(self.budget.snapshot());
         Ok(())
     }
 
@@ -158,12 +268,14 @@ impl LiveDisplayManager {
     fn render(&mut self) -> Result<()> {
         self.terminal.draw(|frame| {
             let area = frame.area();
+            self.panes = Some(pane_areas(area));
             render_live_display(
                 frame,
                 &self.display_state,
                 area,
                 &self.theme,
                 self.error_message.as_deref(),
+                self.notice.as_deref(),
             );
         })?;
         Ok(())
@@ -171,14 +283,16 @@ impl LiveDisplayManager {
 
     /// Exit the display and cleanup terminal
     async fn exit(&mut self) -> Result<()> {
-        cleanup_terminal(&mut self.terminal)?;
-        std::process::exit(0);
+        self.exited = true;
+        cleanup_terminal(&mut self.terminal)
     }
 }
 
 impl Drop for LiveDisplayManager {
     fn drop(&mut self) {
-        let _ = cleanup_terminal(&mut self.terminal);
+        if !self.exited {
+            let _ = cleanup_terminal(&mut self.terminal);
+        }
     }
 }
 
@@ -228,7 +342,13 @@ mod tests {
         
         // This test requires a terminal, so we'll just test the creation logic
         // In a real environment, this would work
-        let result = LiveDisplayManager::new(baseline, rx).await;
+        let result = LiveDisplayManager::new(
+            baseline,
+            rx,
+            HealthBoard::default(),
+            BudgetBoard::default(),
+        )
+        .await;
         
         // In test environment without a terminal, this might fail
         // That's expected and acceptable for unit tests
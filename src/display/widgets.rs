@@ -6,11 +6,24 @@
 use ratatui::{
     layout::{Alignment, Constraint, Direction, Layout, Rect},
     style::{Color, Modifier, Style},
+    symbols::Marker,
     text::{Line, Span, Text},
-    widgets::{Block, Borders, Clear, List, ListItem, Paragraph, Wrap},
+    widgets::{
+        Axis, Block, Borders, Chart, Clear, Dataset, GraphType, List, ListItem, Paragraph, Wrap,
+    },
     Frame,
 };
-use super::{LiveDisplay, SessionActivity};
+use std::time::SystemTime;
+use super::{LiveDisplay, Pane, ProjectActivity, SessionActivity};
+use crate::config::ActivitySort;
+use crate::live::budget::BudgetLevel;
+
+/// Width of the activity list's time column, spacing included
+const TIME_COLUMN_WIDTH: u16 = 10;
+/// Width of the activity list's tokens column
+const TOKENS_COLUMN_WIDTH: u16 = 16;
+/// Width of the activity list's cost column
+const COST_COLUMN_WIDTH: u16 = 10;
 
 /// Style constants for consistent theming
 pub struct AppTheme {
@@ -18,7 +31,6 @@ pub struct AppTheme {
     pub secondary: Style,
     pub accent: Style,
     pub success: Style,
-    #[allow(dead_code)]
     pub warning: Style,
     pub error: Style,
     pub muted: Style,
@@ -38,26 +50,94 @@ impl Default for AppTheme {
     }
 }
 
+impl AppTheme {
+    /// Border of a pane, highlighted when it has focus
+    fn border(&self, focused: bool) -> Style {
+        if focused {
+            self.accent.add_modifier(Modifier::BOLD)
+        } else {
+            self.secondary
+        }
+    }
+}
+
 /// Custom widget for displaying the main header with totals
 pub struct HeaderWidget<'a> {
     totals_text: &'a str,
+    instances_text: Option<&'a str>,
+    instances_healthy: bool,
+    budget_text: Option<&'a str>,
+    budget_level: BudgetLevel,
+    focused: bool,
     theme: &'a AppTheme,
 }
 
 impl<'a> HeaderWidget<'a> {
     pub fn new(totals_text: &'a str, theme: &'a AppTheme) -> Self {
-        Self { totals_text, theme }
+        Self {
+            totals_text,
+            instances_text: None,
+            instances_healthy: true,
+            budget_text: None,
+            budget_level: BudgetLevel::Normal,
+            focused: false,
+            theme,
+        }
+    }
+
+    /// Highlight the border as the focused pane
+    pub fn focused(mut self, focused: bool) -> Self {
+        self.focused = focused;
+        self
+    }
+
+    /// Show keeper instance health along the bottom border
+    pub fn with_instances(mut self, instances_text: Option<&'a str>, healthy: bool) -> Self {
+        self.instances_text = instances_text;
+        self.instances_healthy = healthy;
+        self
+    }
+
+    /// Show today's spend against the daily budget, highlighting the header
+    /// once a threshold is crossed
+    pub fn with_budget(mut self, budget_text: Option<&'a str>, level: BudgetLevel) -> Self {
+        self.budget_text = budget_text;
+        self.budget_level = level;
+        self
     }
 
     pub fn render(&self, frame: &mut Frame, area: Rect) {
-        let header_block = Block::default()
+        let (border_style, text_style) = match self.budget_level {
+            BudgetLevel::Normal => (self.theme.border(self.focused), self.theme.success),
+            BudgetLevel::Warning => (self.theme.warning, self.theme.warning),
+            BudgetLevel::Critical => (
+                self.theme.error,
+                self.theme.error.add_modifier(Modifier::BOLD),
+            ),
+        };
+
+        let mut header_block = Block::default()
             .title("Claude Usage Live")
             .title_style(self.theme.primary)
             .borders(Borders::ALL)
-            .border_style(self.theme.secondary);
+            .border_style(border_style);
+
+        if let Some(budget) = self.budget_text {
+            header_block = header_block
+                .title(Line::styled(format!(" {} ", budget), text_style).right_aligned());
+        }
+
+        if let Some(instances) = self.instances_text {
+            let style = if self.instances_healthy {
+                self.theme.success
+            } else {
+                self.theme.warning
+            };
+            header_block = header_block.title_bottom(Line::styled(format!(" {} ", instances), style));
+        }
 
         let header_text = Paragraph::new(self.totals_text)
-            .style(self.theme.success)
+            .style(text_style)
             .alignment(Alignment::Center)
             .block(header_block);
 
@@ -68,12 +148,23 @@ impl<'a> HeaderWidget<'a> {
 /// Custom widget for displaying current session information
 pub struct SessionWidget<'a> {
     session_info: Option<&'a str>,
+    focused: bool,
     theme: &'a AppTheme,
 }
 
 impl<'a> SessionWidget<'a> {
     pub fn new(session_info: Option<&'a str>, theme: &'a AppTheme) -> Self {
-        Self { session_info, theme }
+        Self {
+            session_info,
+            focused: false,
+            theme,
+        }
+    }
+
+    /// Highlight the border as the focused pane
+    pub fn focused(mut self, focused: bool) -> Self {
+        self.focused = focused;
+        self
     }
 
     pub fn render(&self, frame: &mut Frame, area: Rect) {
@@ -81,7 +172,7 @@ impl<'a> SessionWidget<'a> {
             .title("Current Session")
             .title_style(self.theme.primary)
             .borders(Borders::ALL)
-            .border_style(self.theme.secondary);
+            .border_style(self.theme.border(self.focused));
 
         let session_text = if let Some(info) = self.session_info {
             Text::from(vec![
@@ -108,40 +199,73 @@ impl<'a> SessionWidget<'a> {
 }
 
 /// Custom widget for displaying recent activity with scrolling
+///
+/// The first line holds column headers; clicking Time, Tokens or Cost sorts
+/// the list by that column (see [`activity_sort_at`]).
 pub struct ActivityWidget<'a> {
-    activities: Vec<&'a SessionActivity>,
+    activities: Vec<SessionActivity>,
+    view: &'a str,
     scroll_indicator: &'a str,
     theme: &'a AppTheme,
     can_scroll: bool,
+    sort: ActivitySort,
+    selected: Option<usize>,
+    focused: bool,
 }
 
 impl<'a> ActivityWidget<'a> {
     pub fn new(
-        activities: Vec<&'a SessionActivity>,
+        activities: Vec<SessionActivity>,
+        view: &'a str,
         scroll_indicator: &'a str,
         theme: &'a AppTheme,
         can_scroll: bool,
     ) -> Self {
         Self {
             activities,
+            view,
             scroll_indicator,
             theme,
             can_scroll,
+            sort: ActivitySort::default(),
+            selected: None,
+            focused: false,
         }
     }
 
+    /// Mark the column the list is sorted by
+    pub fn sorted_by(mut self, sort: ActivitySort) -> Self {
+        self.sort = sort;
+        self
+    }
+
+    /// Highlight the row at `selected` among the activities shown
+    pub fn selected(mut self, selected: Option<usize>) -> Self {
+        self.selected = selected;
+        self
+    }
+
+    /// Highlight the border as the focused pane
+    pub fn focused(mut self, focused: bool) -> Self {
+        self.focused = focused;
+        self
+    }
+
     pub fn render(&self, frame: &mut Frame, area: Rect) {
         let title = if self.can_scroll {
-            format!("Recent Activity (↑/↓ to scroll){}", self.scroll_indicator)
+            format!(
+                "Recent Activity, {} (↑/↓ or wheel to scroll){}",
+                self.view, self.scroll_indicator
+            )
         } else {
-            "Recent Activity".to_string()
+            format!("Recent Activity, {}", self.view)
         };
 
         let activity_block = Block::default()
             .title(title)
             .title_style(self.theme.primary)
             .borders(Borders::ALL)
-            .border_style(self.theme.secondary);
+            .border_style(self.theme.border(self.focused));
 
         if self.activities.is_empty() {
             let empty_text = Paragraph::new("No recent activity")
@@ -153,30 +277,71 @@ impl<'a> ActivityWidget<'a> {
             return;
         }
 
-        let items: Vec<ListItem> = self.activities
-            .iter()
-            .map(|activity| {
-                let line = Line::from(vec![
-                    Span::styled(
-                        format!("[{}] ", activity.time_str),
-                        self.theme.muted,
-                    ),
-                    Span::styled(
-                        format!("{}: ", activity.project),
-                        self.theme.secondary,
-                    ),
-                    Span::styled(
-                        format!("+{} tokens ", activity.tokens),
-                        self.theme.accent,
+        let width = area.width.saturating_sub(2);
+        let project_width = project_column_width(width);
+        let heading = |label: &str, sort: Option<ActivitySort>| {
+            let marker = if sort == Some(self.sort) { "▼" } else { "" };
+            format!("{}{}", label, marker)
+        };
+        let header = Line::from(vec![Span::styled(
+            format!(
+                "{:<time$}{:<project$}{:>tokens$}{:>cost$}",
+                heading("Time", Some(ActivitySort::Time)),
+                heading("Project", None),
+                heading("Tokens", Some(ActivitySort::Tokens)),
+                heading("Cost", Some(ActivitySort::Cost)),
+                time = TIME_COLUMN_WIDTH as usize,
+                project = project_width,
+                tokens = TOKENS_COLUMN_WIDTH as usize,
+                cost = COST_COLUMN_WIDTH as usize,
+            ),
+            self.theme.primary.add_modifier(Modifier::UNDERLINED),
+        )]);
+
+        let mut items = vec![ListItem::new(header)];
+        items.extend(self.activities.iter().enumerate().map(|(i, activity)| {
+            let mut project = String::new();
+            if let Some(instance) = &activity.instance {
+                project.push_str(&format!("[{}] ", instance));
+            }
+            project.push_str(&activity.project);
+            if activity.count > 1 {
+                project.push_str(&format!(" ×{}", activity.count));
+            }
+            let project: String = project.chars().take(project_width.saturating_sub(1)).collect();
+
+            let item = ListItem::new(Line::from(vec![
+                Span::styled(
+                    format!("{:<width$}", activity.time_str, width = TIME_COLUMN_WIDTH as usize),
+                    self.theme.muted,
+                ),
+                Span::styled(
+                    format!("{:<width$}", project, width = project_width),
+                    self.theme.secondary,
+                ),
+                Span::styled(
+                    format!(
+                        "{:>width$}",
+                        format!("+{}", activity.tokens),
+                        width = TOKENS_COLUMN_WIDTH as usize
                     ),
-                    Span::styled(
-                        format!("(${:.3})", activity.cost),
-                        self.theme.success,
+                    self.theme.accent,
+                ),
+                Span::styled(
+                    format!(
+                        "{:>width$}",
+                        format!("${:.3}", activity.cost),
+                        width = COST_COLUMN_WIDTH as usize
                     ),
-                ]);
-                ListItem::new(line)
-            })
-            .collect();
+                    self.theme.success,
+                ),
+            ]));
+            if self.selected == Some(i) {
+                item.style(Style::default().add_modifier(Modifier::REVERSED))
+            } else {
+                item
+            }
+        }));
 
         let activity_list = List::new(items)
             .block(activity_block)
@@ -186,22 +351,241 @@ impl<'a> ActivityWidget<'a> {
     }
 }
 
+/// Drill-down of one project, shown in place of the activity list: its
+/// tokens by type, cost today and latest messages
+pub struct ProjectWidget<'a> {
+    project: &'a str,
+    activity: &'a ProjectActivity,
+    focused: bool,
+    theme: &'a AppTheme,
+}
+
+impl<'a> ProjectWidget<'a> {
+    pub fn new(project: &'a str, activity: &'a ProjectActivity, theme: &'a AppTheme) -> Self {
+        Self {
+            project,
+            activity,
+            focused: false,
+            theme,
+        }
+    }
+
+    /// Highlight the border as the focused pane
+    pub fn focused(mut self, focused: bool) -> Self {
+        self.focused = focused;
+        self
+    }
+
+    pub fn render(&self, frame: &mut Frame, area: Rect) {
+        let project_block = Block::default()
+            .title(format!("Project {} (Esc to go back)", self.project))
+            .title_style(self.theme.primary)
+            .borders(Borders::ALL)
+            .border_style(self.theme.border(self.focused));
+
+        let activity = self.activity;
+        let width = area.width.saturating_sub(2);
+        let model_width = project_column_width(width);
+        let mut lines = vec![
+            Line::from(vec![
+                Span::styled("Cost today: ", self.theme.muted),
+                Span::styled(format!("${:.2}", activity.cost_today), self.theme.success),
+                Span::styled("  Updates: ", self.theme.muted),
+                Span::styled(activity.updates.to_string(), self.theme.accent),
+            ]),
+            Line::from(vec![
+                Span::styled("Tokens: ", self.theme.muted),
+                Span::styled(
+                    format!(
+                        "In {}  Out {}  Cache write {}  Cache read {}",
+                        activity.input_tokens,
+                        activity.output_tokens,
+                        activity.cache_creation_tokens,
+                        activity.cache_read_tokens
+                    ),
+                    self.theme.accent,
+                ),
+            ]),
+            Line::from(""),
+            Line::styled(
+                format!(
+                    "{:<time$}{:<model$}{:>tokens$}{:>cost$}",
+                    "Time",
+                    "Model",
+                    "Tokens",
+                    "Cost",
+                    time = TIME_COLUMN_WIDTH as usize,
+                    model = model_width,
+                    tokens = TOKENS_COLUMN_WIDTH as usize,
+                    cost = COST_COLUMN_WIDTH as usize,
+                ),
+                self.theme.primary.add_modifier(Modifier::UNDERLINED),
+            ),
+        ];
+        lines.extend(activity.recent.iter().map(|message| {
+            let model: String = message
+                .model
+                .chars()
+                .take(model_width.saturating_sub(1))
+                .collect();
+            Line::from(vec![
+                Span::styled(
+                    format!("{:<width$}", message.time_str, width = TIME_COLUMN_WIDTH as usize),
+                    self.theme.muted,
+                ),
+                Span::styled(
+                    format!("{:<width$}", model, width = model_width),
+                    self.theme.secondary,
+                ),
+                Span::styled(
+                    format!(
+                        "{:>width$}",
+                        format!("+{}", message.tokens),
+                        width = TOKENS_COLUMN_WIDTH as usize
+                    ),
+                    self.theme.accent,
+                ),
+                Span::styled(
+                    format!(
+                        "{:>width$}",
+                        format!("${:.3}", message.cost),
+                        width = COST_COLUMN_WIDTH as usize
+                    ),
+                    self.theme.success,
+                ),
+            ])
+        }));
+
+        frame.render_widget(Paragraph::new(lines).block(project_block), area);
+    }
+}
+
+/// Width left for the project column in an activity list `width` columns wide
+fn project_column_width(width: u16) -> usize {
+    width.saturating_sub(TIME_COLUMN_WIDTH + TOKENS_COLUMN_WIDTH + COST_COLUMN_WIDTH) as usize
+}
+
+/// Sort order of the activity column header at `column`, `row`, if the
+/// click landed on one
+pub fn activity_sort_at(activity: Rect, column: u16, row: u16) -> Option<ActivitySort> {
+    let inner = activity.inner(ratatui::layout::Margin::new(1, 1));
+    if row != inner.y || column < inner.x || column >= inner.x + inner.width {
+        return None;
+    }
+    let x = column - inner.x;
+    let cost_start = inner.width.saturating_sub(COST_COLUMN_WIDTH);
+    let tokens_start = cost_start.saturating_sub(TOKENS_COLUMN_WIDTH);
+    if x < TIME_COLUMN_WIDTH {
+        Some(ActivitySort::Time)
+    } else if x >= cost_start {
+        Some(ActivitySort::Cost)
+    } else if x >= tokens_start {
+        Some(ActivitySort::Tokens)
+    } else {
+        None
+    }
+}
+
+/// Braille line chart of cost or tokens per minute over the last hour
+pub struct BurnRateWidget<'a> {
+    title: &'a str,
+    series: &'a [(u64, f64)],
+    focused: bool,
+    theme: &'a AppTheme,
+}
+
+impl<'a> BurnRateWidget<'a> {
+    pub fn new(title: &'a str, series: &'a [(u64, f64)], theme: &'a AppTheme) -> Self {
+        Self {
+            title,
+            series,
+            focused: false,
+            theme,
+        }
+    }
+
+    /// Highlight the border as the focused pane
+    pub fn focused(mut self, focused: bool) -> Self {
+        self.focused = focused;
+        self
+    }
+
+    pub fn render(&self, frame: &mut Frame, area: Rect) {
+        // Minutes relative to now, so the newest point sits on the right edge
+        let points: Vec<(f64, f64)> = self
+            .series
+            .iter()
+            .enumerate()
+            .map(|(i, &(_, value))| (i as f64 - (self.series.len() as f64 - 1.0), value))
+            .collect();
+        let peak = points.iter().map(|&(_, value)| value).fold(0.0, f64::max);
+        let oldest = points.first().map_or(0.0, |&(minute, _)| minute);
+
+        let dataset = Dataset::default()
+            .marker(Marker::Braille)
+            .graph_type(GraphType::Line)
+            .style(self.theme.accent)
+            .data(&points);
+
+        let chart = Chart::new(vec![dataset])
+            .block(
+                Block::default()
+                    .title(self.title)
+                    .title_style(self.theme.primary)
+                    .borders(Borders::ALL)
+                    .border_style(self.theme.border(self.focused)),
+            )
+            .x_axis(Axis::default().bounds([oldest, 0.0]))
+            // Keep a flat line at the bottom when nothing has been spent
+            .y_axis(Axis::default().bounds([0.0, if peak > 0.0 { peak } else { 1.0 }]));
+
+        frame.render_widget(chart, area);
+    }
+}
+
 /// Custom widget for displaying help/status information
 pub struct StatusWidget<'a> {
     theme: &'a AppTheme,
+    paused: bool,
+    notice: Option<&'a str>,
 }
 
 impl<'a> StatusWidget<'a> {
     pub fn new(theme: &'a AppTheme) -> Self {
-        Self { theme }
+        Self {
+            theme,
+            paused: false,
+            notice: None,
+        }
+    }
+
+    /// Say that updates are paused, and how to resume them
+    pub fn paused(mut self, paused: bool) -> Self {
+        self.paused = paused;
+        self
+    }
+
+    /// Show `notice` in place of the key help
+    pub fn with_notice(mut self, notice: Option<&'a str>) -> Self {
+        self.notice = notice;
+        self
     }
 
     pub fn render(&self, frame: &mut Frame, area: Rect) {
-        let help_text = Line::from(vec![
-            Span::styled("Press ", self.theme.muted),
-            Span::styled("Ctrl+C", self.theme.accent),
-            Span::styled(" to exit", self.theme.muted),
-        ]);
+        let help_text = if let Some(notice) = self.notice {
+            Line::from(Span::styled(notice, self.theme.success))
+        } else if self.paused {
+            Line::from(vec![
+                Span::styled("⏸ Paused", self.theme.warning),
+                Span::styled(", updates are waiting. Press ", self.theme.muted),
+                Span::styled("p", self.theme.accent),
+                Span::styled(" to resume, ", self.theme.muted),
+                Span::styled("Shift+S", self.theme.accent),
+                Span::styled(" to save a snapshot", self.theme.muted),
+            ])
+        } else {
+            self.help_line()
+        };
 
         let help_paragraph = Paragraph::new(help_text)
             .alignment(Alignment::Center)
@@ -209,6 +593,30 @@ impl<'a> StatusWidget<'a> {
 
         frame.render_widget(help_paragraph, area);
     }
+
+    fn help_line(&self) -> Line<'a> {
+        Line::from(vec![
+            Span::styled("Press ", self.theme.muted),
+            Span::styled("p", self.theme.accent),
+            Span::styled(" to pause, ", self.theme.muted),
+            Span::styled("Shift+S", self.theme.accent),
+            Span::styled(" to save a snapshot, ", self.theme.muted),
+            Span::styled("r", self.theme.accent),
+            Span::styled(" to reset session counters, ", self.theme.muted),
+            Span::styled("s", self.theme.accent),
+            Span::styled(" to sort, ", self.theme.muted),
+            Span::styled("g", self.theme.accent),
+            Span::styled(" to group activity, ", self.theme.muted),
+            Span::styled("t", self.theme.accent),
+            Span::styled(" to chart tokens or cost, ", self.theme.muted),
+            Span::styled("↑/↓ Enter", self.theme.accent),
+            Span::styled(" to open a project, ", self.theme.muted),
+            Span::styled("click", self.theme.accent),
+            Span::styled(" a column to sort by it, ", self.theme.muted),
+            Span::styled("Ctrl+C", self.theme.accent),
+            Span::styled(" to exit", self.theme.muted),
+        ])
+    }
 }
 
 /// Error overlay widget for displaying connection issues
@@ -273,6 +681,56 @@ pub fn create_main_layout(area: Rect) -> Vec<Rect> {
         .to_vec()
 }
 
+/// Where each pane of the dashboard is drawn
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PaneAreas {
+    pub header: Rect,
+    pub session: Rect,
+    pub burn_rate: Rect,
+    pub activity: Rect,
+    pub status: Rect,
+}
+
+impl PaneAreas {
+    /// The pane containing the cell at `column`, `row`
+    pub fn pane_at(&self, column: u16, row: u16) -> Option<Pane> {
+        let position = ratatui::layout::Position::new(column, row);
+        [
+            (self.header, Pane::Header),
+            (self.session, Pane::Session),
+            (self.burn_rate, Pane::BurnRate),
+            (self.activity, Pane::Activity),
+        ]
+        .into_iter()
+        .find(|(area, _)| area.contains(position))
+        .map(|(_, pane)| pane)
+    }
+
+    /// Activity rows that fit in the list, below its column headers
+    pub fn activity_lines(&self) -> usize {
+        self.activity.height.saturating_sub(3) as usize
+    }
+}
+
+/// Split the terminal `area` into the dashboard's panes
+pub fn pane_areas(area: Rect) -> PaneAreas {
+    let chunks = create_main_layout(area);
+
+    // Current session info, with the burn-rate chart alongside
+    let session_chunks = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(60), Constraint::Percentage(40)])
+        .split(chunks[1]);
+
+    PaneAreas {
+        header: chunks[0],
+        session: session_chunks[0],
+        burn_rate: session_chunks[1],
+        activity: chunks[2],
+        status: chunks[3],
+    }
+}
+
 /// Helper function to create a centered rectangle
 fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
     // Calculate exact pixel dimensions for more predictable centering
@@ -297,43 +755,87 @@ pub fn render_live_display(
     area: Rect,
     theme: &AppTheme,
     error_message: Option<&str>,
+    notice: Option<&str>,
 ) {
-    let chunks = create_main_layout(area);
+    let areas = pane_areas(area);
+    let focused = display.focused_pane;
 
     // Header with totals
     let totals_text = display.format_totals();
-    let header = HeaderWidget::new(&totals_text, theme);
-    header.render(frame, chunks[0]);
-
-    // Current session info
+    let instances_text = display.format_instances();
+    let instances_healthy = display
+        .instance_health
+        .values()
+        .all(|health| health.is_healthy());
+    let budget_text = display.format_budget();
+    let budget_level = display
+        .budget
+        .as_ref()
+        .map_or(BudgetLevel::Normal, |budget| budget.level);
+    let header = HeaderWidget::new(&totals_text, theme)
+        .with_instances(instances_text.as_deref(), instances_healthy)
+        .with_budget(budget_text.as_deref(), budget_level)
+        .focused(focused == Pane::Header);
+    header.render(frame, areas.header);
+
+    // Current session info, with the burn-rate chart alongside
     let session_info = display.format_current_session();
-    let session = SessionWidget::new(session_info.as_deref(), theme);
-    session.render(frame, chunks[1]);
+    let session = SessionWidget::new(session_info.as_deref(), theme)
+        .focused(focused == Pane::Session);
+    session.render(frame, areas.session);
+
+    let now = SystemTime::now();
+    let burn_rate_title = display.format_burn_rate(now);
+    let burn_rate_series = display.burn_rate_series(now);
+    let burn_rate = BurnRateWidget::new(&burn_rate_title, &burn_rate_series, theme)
+        .focused(focused == Pane::BurnRate);
+    burn_rate.render(frame, areas.burn_rate);
+
+    // Drill-down of the chosen project, or the recent activity list
+    if let Some((project, project_activity)) = display.drill_down_project() {
+        ProjectWidget::new(project, project_activity, theme)
+            .focused(focused == Pane::Activity)
+            .render(frame, areas.activity);
+    } else {
+        render_activity(frame, display, areas, theme);
+    }
+
+    // Status line
+    let status = StatusWidget::new(theme)
+        .paused(display.paused)
+        .with_notice(notice);
+    status.render(frame, areas.status);
 
-    // Recent activity list
-    let activity_area = chunks[2];
-    let available_lines = activity_area.height.saturating_sub(2) as usize; // Account for borders
+    // Error overlay if there's an error
+    if let Some(error) = error_message {
+        let error_overlay = ErrorOverlayWidget::new(error, theme);
+        error_overlay.render(frame, area);
+    }
+}
+
+/// Render the recent activity list, highlighting the selected row
+fn render_activity(frame: &mut Frame, display: &LiveDisplay, areas: PaneAreas, theme: &AppTheme) {
+    let available_lines = areas.activity_lines();
     let visible_activities = display.get_visible_activities(available_lines);
     let scroll_indicator = display.get_scroll_indicator(available_lines);
     let can_scroll = display.can_scroll(available_lines);
+    let activity_view = display.format_activity_view();
 
     let activity = ActivityWidget::new(
         visible_activities,
+        &activity_view,
         &scroll_indicator,
         theme,
         can_scroll,
-    );
-    activity.render(frame, activity_area);
-
-    // Status line
-    let status = StatusWidget::new(theme);
-    status.render(frame, chunks[3]);
-
-    // Error overlay if there's an error
-    if let Some(error) = error_message {
-        let error_overlay = ErrorOverlayWidget::new(error, theme);
-        error_overlay.render(frame, area);
-    }
+    )
+    .sorted_by(display.activity_sort)
+    .selected(
+        display
+            .selected_row
+            .and_then(|row| row.checked_sub(display.scroll_position)),
+    )
+    .focused(display.focused_pane == Pane::Activity);
+    activity.render(frame, areas.activity);
 }
 
 #[cfg(test)]
@@ -363,4 +865,25 @@ mod tests {
         // Activity area should take remaining space
         assert!(layout[2].height >= 8);
     }
+
+    #[test]
+    fn test_clicks_map_to_panes_and_sort_columns() {
+        let areas = pane_areas(Rect::new(0, 0, 80, 24));
+        assert_eq!(areas.pane_at(1, 1), Some(Pane::Header));
+        assert_eq!(areas.pane_at(2, 4), Some(Pane::Session));
+        assert_eq!(areas.pane_at(70, 4), Some(Pane::BurnRate));
+        assert_eq!(areas.pane_at(10, 12), Some(Pane::Activity));
+        assert_eq!(areas.pane_at(10, 23), None);
+        assert_eq!(areas.activity_lines(), areas.activity.height as usize - 3);
+
+        // Column headers sit on the first line inside the activity border
+        let header_row = areas.activity.y + 1;
+        let sort_at = |column| activity_sort_at(areas.activity, column, header_row);
+        assert_eq!(sort_at(2), Some(ActivitySort::Time));
+        assert_eq!(sort_at(30), None);
+        assert_eq!(sort_at(60), Some(ActivitySort::Tokens));
+        assert_eq!(sort_at(75), Some(ActivitySort::Cost));
+        assert_eq!(sort_at(79), None);
+        assert_eq!(activity_sort_at(areas.activity, 2, header_row + 1), None);
+    }
 }
\ No newline at end of file
@@ -0,0 +1,147 @@
+//! Library errors
+//!
+//! Library functions return [`anyhow::Result`], so every failure carries the
+//! context chain that the CLI prints. Callers that need to react to a
+//! particular failure convert the error into an [`Error`] and match on it:
+//!
+//! ```rust
+//! use claude_usage::prelude::*;
+//!
+//! fn exit_code(result: anyhow::Result<()>) -> i32 {
+//!     match result.map_err(Error::from) {
+//!         Ok(()) => 0,
+//!         Err(Error::ErrorBudgetExceeded(_)) => 3,
+//!         Err(_) => 1,
+//!     }
+//! }
+//! # assert_eq!(exit_code(Ok(())), 0);
+//! ```
+
+use std::fmt;
+use std::io;
+
+// The binary only needs ErrorBudgetExceeded; the rest are for library callers
+#[allow(unused_imports)]
+pub use crate::line_reader::{ErrorBudgetExceeded, FileFailures, ParseQuality};
+pub use crate::memory::MemoryLimitExceeded;
+pub use crate::status::{LimitBreach, SpendLimitExceeded};
+
+/// Failures a caller can tell apart
+///
+/// Variants may be added in minor releases; match with a wildcard arm.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum Error {
+    /// More log lines or backup rows failed to parse than `max_error_rate` allows
+    ErrorBudgetExceeded(ErrorBudgetExceeded),
+    /// There is no usage data to report on
+    NoDataFound(NoDataFound),
+    /// The claude-keeper executable is not installed
+    KeeperNotFound(KeeperNotFound),
+    /// Memory use went over `memory.max_memory_mb`
+    MemoryLimitExceeded(MemoryLimitExceeded),
+    /// Spend went over a limit given to `check`
+    SpendLimitExceeded(SpendLimitExceeded),
+    /// Any other failure, with its context chain
+    Other(anyhow::Error),
+}
+
+impl From<anyhow::Error> for Error {
+    fn from(error: anyhow::Error) -> Self {
+        let error = match error.downcast::<ErrorBudgetExceeded>() {
+            Ok(exceeded) => return Error::ErrorBudgetExceeded(exceeded),
+            Err(error) => error,
+        };
+        let error = match error.downcast::<NoDataFound>() {
+            Ok(missing) => return Error::NoDataFound(missing),
+            Err(error) => error,
+        };
+        let error = match error.downcast::<KeeperNotFound>() {
+            Ok(missing) => return Error::KeeperNotFound(missing),
+            Err(error) => error,
+        };
+        let error = match error.downcast::<MemoryLimitExceeded>() {
+            Ok(exceeded) => return Error::MemoryLimitExceeded(exceeded),
+            Err(error) => error,
+        };
+        match error.downcast::<SpendLimitExceeded>() {
+            Ok(exceeded) => Error::SpendLimitExceeded(exceeded),
+            Err(error) => Error::Other(error),
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::ErrorBudgetExceeded(exceeded) => exceeded.fmt(f),
+            Error::NoDataFound(missing) => missing.fmt(f),
+            Error::KeeperNotFound(missing) => missing.fmt(f),
+            Error::MemoryLimitExceeded(exceeded) => exceeded.fmt(f),
+            Error::SpendLimitExceeded(exceeded) => exceeded.fmt(f),
+            Error::Other(error) => write!(f, "{:#}", error),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::ErrorBudgetExceeded(_)
+            | Error::NoDataFound(_)
+            | Error::MemoryLimitExceeded(_)
+            | Error::SpendLimitExceeded(_) => None,
+            Error::KeeperNotFound(missing) => Some(&missing.source),
+            Error::Other(error) => error.source(),
+        }
+    }
+}
+
+/// No usage data to report on, such as when claude-keeper has never written
+/// a backup
+#[derive(Debug)]
+pub struct NoDataFound {
+    pub message: String,
+}
+
+impl fmt::Display for NoDataFound {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for NoDataFound {}
+
+/// A claude-keeper executable could not be started because it does not exist
+#[derive(Debug)]
+pub struct KeeperNotFound {
+    pub program: String,
+    source: io::Error,
+}
+
+impl KeeperNotFound {
+    /// Turn a failure to run `program` into [`KeeperNotFound`] when the
+    /// executable is missing, or into `context` otherwise
+    pub(crate) fn check(program: &str, error: io::Error, context: String) -> anyhow::Error {
+        if error.kind() == io::ErrorKind::NotFound {
+            anyhow::Error::new(KeeperNotFound {
+                program: program.to_string(),
+                source: error,
+            })
+        } else {
+            anyhow::Error::new(error).context(context)
+        }
+    }
+}
+
+impl fmt::Display for KeeperNotFound {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "claude-keeper not found: {}", self.program)
+    }
+}
+
+impl std::error::Error for KeeperNotFound {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.source)
+    }
+}
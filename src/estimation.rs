@@ -0,0 +1,130 @@
+//! Token estimation for entries without usage data
+//!
+//! Some log entries carry message content but no `usage` block, so they would
+//! otherwise be skipped and undercount a session. When estimation is enabled,
+//! token counts for those entries are approximated from the length of the
+//! message content using a configurable characters-per-token ratio, and the
+//! resulting rows are flagged as estimated in report output.
+
+use crate::config::get_config;
+use crate::models::UsageData;
+use serde_json::Value;
+
+/// Approximates token counts from message content length
+#[derive(Debug, Clone, Copy)]
+pub struct TokenEstimator {
+    chars_per_token: f64,
+}
+
+impl TokenEstimator {
+    pub fn new(chars_per_token: f64) -> Self {
+        Self { chars_per_token }
+    }
+
+    /// Create an estimator using the configured characters-per-token ratio
+    pub fn from_config() -> Self {
+        Self::new(get_config().processing.chars_per_token)
+    }
+
+    /// Estimate the number of tokens needed to encode `char_count` characters
+    pub fn estimate_tokens(&self, char_count: usize) -> u32 {
+        if char_count == 0 || self.chars_per_token <= 0.0 {
+            return 0;
+        }
+        (char_count as f64 / self.chars_per_token).ceil() as u32
+    }
+
+    /// Estimate usage for a message object (`{"role": ..., "content": ...}`)
+    ///
+    /// Assistant content is counted as output tokens, everything else as input
+    /// tokens. Returns None when the message has no countable content.
+    pub fn estimate_usage(&self, message: &Value) -> Option<UsageData> {
+        let char_count = message.get("content").map(content_char_count).unwrap_or(0);
+        let tokens = self.estimate_tokens(char_count);
+        if tokens == 0 {
+            return None;
+        }
+
+        let is_assistant = message.get("role").and_then(|v| v.as_str()) == Some("assistant");
+
+        Some(UsageData {
+            input_tokens: if is_assistant { 0 } else { tokens },
+            output_tokens: if is_assistant { tokens } else { 0 },
+            cache_creation_input_tokens: 0,
+            cache_read_input_tokens: 0,
+        })
+    }
+}
+
+/// Count characters in message content, which is either a plain string or an
+/// array of content blocks (text, thinking, tool input and tool results)
+fn content_char_count(content: &Value) -> usize {
+    match content {
+        Value::String(text) => text.chars().count(),
+        Value::Array(blocks) => blocks.iter().map(block_char_count).sum(),
+        _ => 0,
+    }
+}
+
+fn block_char_count(block: &Value) -> usize {
+    if let Some(text) = block.get("text").and_then(|v| v.as_str()) {
+        return text.chars().count();
+    }
+    if let Some(thinking) = block.get("thinking").and_then(|v| v.as_str()) {
+        return thinking.chars().count();
+    }
+    if let Some(input) = block.get("input") {
+        return input.to_string().chars().count();
+    }
+    if let Some(content) = block.get("content") {
+        return content_char_count(content);
+    }
+    0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_estimate_tokens_rounds_up() {
+        let estimator = TokenEstimator::new(4.0);
+        assert_eq!(estimator.estimate_tokens(0), 0);
+        assert_eq!(estimator.estimate_tokens(1), 1);
+        assert_eq!(estimator.estimate_tokens(8), 2);
+        assert_eq!(estimator.estimate_tokens(9), 3);
+    }
+
+    #[test]
+    fn test_estimate_assistant_message_as_output() {
+        let estimator = TokenEstimator::new(4.0);
+        let message = json!({
+            "role": "assistant",
+            "content": [{"type": "text", "text": "abcdefgh"}]
+        });
+
+        let usage = estimator.estimate_usage(&message).unwrap();
+        assert_eq!(usage.input_tokens, 0);
+        assert_eq!(usage.output_tokens, 2);
+    }
+
+    #[test]
+    fn test_estimate_user_message_as_input() {
+        let estimator = TokenEstimator::new(4.0);
+        let message = json!({"role": "user", "content": "abcdefghijkl"});
+
+        let usage = estimator.estimate_usage(&message).unwrap();
+        assert_eq!(usage.input_tokens, 3);
+        assert_eq!(usage.output_tokens, 0);
+    }
+
+    #[test]
+    fn test_estimate_without_content() {
+        let estimator = TokenEstimator::new(4.0);
+        assert!(estimator.estimate_usage(&json!({"role": "assistant"})).is_none());
+        assert!(estimator
+            .estimate_usage(&json!({"role": "assistant", "content": []}))
+            .is_none());
+    }
+}
@@ -0,0 +1,443 @@
+//! Entry-level export for downstream analytics
+//!
+//! `claude-usage export` writes one record per deduplicated usage entry, with
+//! the session and project it belongs to, so tools like DuckDB or pandas can
+//! query the data directly instead of re-parsing the JSONL logs. The same
+//! filters as the reports apply (`--since/--until`, `--project`,
+//! `--exclude-vms`), and costs follow `--mode`.
+//!
+//! Parquet output needs the `parquet-export` feature; JSON Lines is always
+//! available. Both use the same snake_case column names:
+//!
+//! | column                  | parquet type          |
+//! |-------------------------|-----------------------|
+//! | `timestamp`             | timestamp (µs, UTC)   |
+//! | `session`               | string                |
+//! | `project`               | string                |
+//! | `model`                 | string                |
+//! | `input_tokens`          | int64                 |
+//! | `output_tokens`         | int64                 |
+//! | `cache_creation_tokens` | int64                 |
+//! | `cache_read_tokens`     | int64                 |
+//! | `cost_usd`              | double                |
+//!
+//! SQLite output (the `sqlite-export` feature) is a local warehouse that is
+//! synced rather than replaced: entries are upserted into an `entries` table
+//! keyed by their dedup hash (`messageId:requestId`, or the log file and line
+//! for entries without one), so rerunning the export with a later `--since`
+//! adds new usage without duplicating old. The `sessions` and `daily` tables
+//! are then rebuilt from all entries in the database.
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+use crate::dedup::CostMode;
+use crate::parser::ProcessedEntry;
+use crate::project_names::{self, Expected};
+
+/// File format written by `claude-usage export`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum ExportFormat {
+    /// Apache Parquet (requires the `parquet-export` feature)
+    #[default]
+    Parquet,
+    /// One JSON object per line
+    Jsonl,
+    /// SQLite database, updated in place (requires the `sqlite-export` feature)
+    Sqlite,
+}
+
+impl ExportFormat {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ExportFormat::Parquet => "parquet",
+            ExportFormat::Jsonl => "jsonl",
+            ExportFormat::Sqlite => "sqlite",
+        }
+    }
+}
+
+/// One deduplicated usage entry
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ExportRecord {
+    /// Unique key of the entry: its dedup hash, or the log file and line
+    /// for entries without one
+    #[serde(skip)]
+    pub key: String,
+    pub timestamp: DateTime<Utc>,
+    /// Session id, the name of the session's log file
+    pub session: String,
+    /// Project name, as the reports show it
+    pub project: String,
+    pub model: String,
+    pub input_tokens: u32,
+    pub output_tokens: u32,
+    pub cache_creation_tokens: u32,
+    pub cache_read_tokens: u32,
+    pub cost_usd: f64,
+}
+
+impl ExportRecord {
+    /// The record of `entry`, costed as `mode` says
+    ///
+    /// Session and project come from the path of the log the entry was read
+    /// from, as in the session reports.
+    pub fn from_entry(entry: &ProcessedEntry, mode: CostMode) -> Self {
+        let export = entry.to_export_with(mode);
+        let provenance = entry.provenance.as_ref();
+        let key = provenance
+            .and_then(|provenance| provenance.dedup_hash.clone())
+            .or_else(|| {
+                provenance.map(|provenance| {
+                    format!("{}:{}", provenance.source_file, provenance.line_number)
+                })
+            })
+            .unwrap_or_else(|| format!("{}:{}", export.message_id, export.request_id));
+        let source = provenance.map(|provenance| Path::new(&provenance.source_file));
+        let session = source
+            .and_then(Path::file_stem)
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or_else(|| "unknown".to_string());
+        let project_dir = source
+            .and_then(Path::parent)
+            .and_then(Path::file_name)
+            .map(|dir| dir.to_string_lossy().into_owned())
+            .unwrap_or_else(|| "unknown".to_string());
+
+        Self {
+            key,
+            timestamp: entry.timestamp,
+            session,
+            project: project_names::resolve_project_name(&project_dir, Expected::EncodedDir, None),
+            model: export.model,
+            input_tokens: export.input_tokens,
+            output_tokens: export.output_tokens,
+            cache_creation_tokens: export.cache_creation_tokens,
+            cache_read_tokens: export.cache_read_tokens,
+            cost_usd: export.cost,
+        }
+    }
+}
+
+/// Write `records` to `path` in `format`
+///
+/// Parquet and JSON Lines files are replaced; a SQLite database is updated.
+pub fn write_records(path: &Path, format: ExportFormat, records: &[ExportRecord]) -> Result<()> {
+    match format {
+        ExportFormat::Parquet => write_parquet(path, records),
+        ExportFormat::Jsonl => write_jsonl(path, records),
+        ExportFormat::Sqlite => sync_sqlite(path, records),
+    }
+}
+
+fn write_jsonl(path: &Path, records: &[ExportRecord]) -> Result<()> {
+    let file =
+        File::create(path).with_context(|| format!("Failed to create {}", path.display()))?;
+    let mut writer = BufWriter::new(file);
+    for record in records {
+        serde_json::to_writer(&mut writer, record)?;
+        writer.write_all(b"\n")?;
+    }
+    writer
+        .flush()
+        .with_context(|| format!("Failed to write {}", path.display()))
+}
+
+#[cfg(feature = "parquet-export")]
+fn write_parquet(path: &Path, records: &[ExportRecord]) -> Result<()> {
+    use ::parquet::arrow::ArrowWriter;
+    use ::parquet::basic::Compression;
+    use ::parquet::file::properties::WriterProperties;
+    use arrow_array::{
+        ArrayRef, Float64Array, Int64Array, RecordBatch, StringArray, TimestampMicrosecondArray,
+    };
+    use arrow_schema::{DataType, Field, Schema, TimeUnit};
+    use std::sync::Arc;
+
+    let schema = Arc::new(Schema::new(vec![
+        Field::new(
+            "timestamp",
+            DataType::Timestamp(TimeUnit::Microsecond, Some("UTC".into())),
+            false,
+        ),
+        Field::new("session", DataType::Utf8, false),
+        Field::new("project", DataType::Utf8, false),
+        Field::new("model", DataType::Utf8, false),
+        Field::new("input_tokens", DataType::Int64, false),
+        Field::new("output_tokens", DataType::Int64, false),
+        Field::new("cache_creation_tokens", DataType::Int64, false),
+        Field::new("cache_read_tokens", DataType::Int64, false),
+        Field::new("cost_usd", DataType::Float64, false),
+    ]));
+
+    let strings = |field: fn(&ExportRecord) -> &str| -> ArrayRef {
+        Arc::new(StringArray::from_iter_values(records.iter().map(field)))
+    };
+    let tokens = |field: fn(&ExportRecord) -> u32| -> ArrayRef {
+        Arc::new(Int64Array::from_iter_values(
+            records.iter().map(|r| i64::from(field(r))),
+        ))
+    };
+    let columns: Vec<ArrayRef> = vec![
+        Arc::new(
+            TimestampMicrosecondArray::from_iter_values(
+                records.iter().map(|r| r.timestamp.timestamp_micros()),
+            )
+            .with_timezone("UTC"),
+        ),
+        strings(|r| &r.session),
+        strings(|r| &r.project),
+        strings(|r| &r.model),
+        tokens(|r| r.input_tokens),
+        tokens(|r| r.output_tokens),
+        tokens(|r| r.cache_creation_tokens),
+        tokens(|r| r.cache_read_tokens),
+        Arc::new(Float64Array::from_iter_values(
+            records.iter().map(|r| r.cost_usd),
+        )),
+    ];
+    let batch = RecordBatch::try_new(schema.clone(), columns)?;
+
+    let file =
+        File::create(path).with_context(|| format!("Failed to create {}", path.display()))?;
+    let properties = WriterProperties::builder()
+        .set_compression(Compression::SNAPPY)
+        .build();
+    let mut writer = ArrowWriter::try_new(file, schema, Some(properties))?;
+    writer.write(&batch)?;
+    writer
+        .close()
+        .with_context(|| format!("Failed to write {}", path.display()))?;
+    Ok(())
+}
+
+#[cfg(not(feature = "parquet-export"))]
+fn write_parquet(_path: &Path, _records: &[ExportRecord]) -> Result<()> {
+    anyhow::bail!(
+        "Parquet export not available. Rebuild with --features parquet-export, or use --format jsonl"
+    )
+}
+
+#[cfg(feature = "sqlite-export")]
+/// Tables of the SQLite export; `sessions` and `daily` are derived from
+/// `entries` and rebuilt on every sync
+const SQLITE_SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS entries (
+    key TEXT PRIMARY KEY,
+    timestamp TEXT NOT NULL,
+    session TEXT NOT NULL,
+    project TEXT NOT NULL,
+    model TEXT NOT NULL,
+    input_tokens INTEGER NOT NULL,
+    output_tokens INTEGER NOT NULL,
+    cache_creation_tokens INTEGER NOT NULL,
+    cache_read_tokens INTEGER NOT NULL,
+    cost_usd REAL NOT NULL
+);
+CREATE INDEX IF NOT EXISTS entries_timestamp ON entries (timestamp);
+CREATE TABLE IF NOT EXISTS sessions (
+    session TEXT PRIMARY KEY,
+    project TEXT NOT NULL,
+    first_activity TEXT NOT NULL,
+    last_activity TEXT NOT NULL,
+    entries INTEGER NOT NULL,
+    total_tokens INTEGER NOT NULL,
+    cost_usd REAL NOT NULL
+);
+CREATE TABLE IF NOT EXISTS daily (
+    date TEXT NOT NULL,
+    project TEXT NOT NULL,
+    sessions INTEGER NOT NULL,
+    entries INTEGER NOT NULL,
+    input_tokens INTEGER NOT NULL,
+    output_tokens INTEGER NOT NULL,
+    cache_creation_tokens INTEGER NOT NULL,
+    cache_read_tokens INTEGER NOT NULL,
+    cost_usd REAL NOT NULL,
+    PRIMARY KEY (date, project)
+);
+";
+
+#[cfg(feature = "sqlite-export")]
+/// Rebuild the derived tables from `entries`; days are UTC, like the
+/// timestamps
+const SQLITE_REBUILD: &str = "
+DELETE FROM sessions;
+INSERT INTO sessions
+SELECT session, max(project), min(timestamp), max(timestamp), count(*),
+       sum(input_tokens + output_tokens + cache_creation_tokens + cache_read_tokens),
+       sum(cost_usd)
+FROM entries GROUP BY session;
+DELETE FROM daily;
+INSERT INTO daily
+SELECT substr(timestamp, 1, 10), project, count(DISTINCT session), count(*),
+       sum(input_tokens), sum(output_tokens), sum(cache_creation_tokens),
+       sum(cache_read_tokens), sum(cost_usd)
+FROM entries GROUP BY substr(timestamp, 1, 10), project;
+";
+
+#[cfg(feature = "sqlite-export")]
+fn sync_sqlite(path: &Path, records: &[ExportRecord]) -> Result<()> {
+    use chrono::SecondsFormat;
+    use rusqlite::{params, Connection};
+    use tracing::info;
+
+    let mut conn =
+        Connection::open(path).with_context(|| format!("Failed to open {}", path.display()))?;
+    conn.execute_batch(SQLITE_SCHEMA)?;
+
+    let tx = conn.transaction()?;
+    let before: u64 = tx.query_row("SELECT count(*) FROM entries", [], |row| row.get(0))?;
+    {
+        let mut upsert = tx.prepare(
+            "INSERT INTO entries VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)
+             ON CONFLICT (key) DO UPDATE SET
+                 timestamp = excluded.timestamp, session = excluded.session,
+                 project = excluded.project, model = excluded.model,
+                 input_tokens = excluded.input_tokens, output_tokens = excluded.output_tokens,
+                 cache_creation_tokens = excluded.cache_creation_tokens,
+                 cache_read_tokens = excluded.cache_read_tokens, cost_usd = excluded.cost_usd",
+        )?;
+        for record in records {
+            upsert.execute(params![
+                record.key,
+                record
+                    .timestamp
+                    .to_rfc3339_opts(SecondsFormat::Millis, true),
+                record.session,
+                record.project,
+                record.model,
+                record.input_tokens,
+                record.output_tokens,
+                record.cache_creation_tokens,
+                record.cache_read_tokens,
+                record.cost_usd,
+            ])?;
+        }
+    }
+    tx.execute_batch(SQLITE_REBUILD)?;
+    let after: u64 = tx.query_row("SELECT count(*) FROM entries", [], |row| row.get(0))?;
+    tx.commit()
+        .with_context(|| format!("Failed to write {}", path.display()))?;
+
+    info!(
+        synced = records.len(),
+        new = after - before,
+        total = after,
+        "Synced entries into the SQLite export"
+    );
+    Ok(())
+}
+
+#[cfg(not(feature = "sqlite-export"))]
+fn sync_sqlite(_path: &Path, _records: &[ExportRecord]) -> Result<()> {
+    anyhow::bail!(
+        "SQLite export not available. Rebuild with --features sqlite-export, or use --format jsonl"
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{InstanceType, Provenance};
+    use crate::parser::FileParser;
+    use tempfile::TempDir;
+
+    const LINE: &str = r#"{"timestamp":"2025-03-01T10:00:00Z","requestId":"req_1","costUSD":0.25,"message":{"id":"msg_1","model":"claude-sonnet-4","usage":{"input_tokens":100,"output_tokens":50,"cache_creation_input_tokens":10,"cache_read_input_tokens":5}}}"#;
+
+    fn entry() -> ProcessedEntry {
+        let parser = FileParser::new();
+        let usage_entry = serde_json::from_str(LINE).unwrap();
+        let mut entry = ProcessedEntry::new(usage_entry, &parser, 1).unwrap();
+        entry.provenance = Some(Provenance {
+            source_file: "/h/.claude/projects/-h-app/abc-123.jsonl".to_string(),
+            line_number: 1,
+            instance: "host".to_string(),
+            instance_type: InstanceType::Host,
+            dedup_hash: Some("msg_1:req_1".to_string()),
+        });
+        entry
+    }
+
+    #[test]
+    fn test_export_records_from_entries() {
+        let record = ExportRecord::from_entry(&entry(), CostMode::Display);
+        assert_eq!(record.key, "msg_1:req_1");
+        assert_eq!(record.session, "abc-123");
+        assert_eq!(record.project, "-h-app");
+        assert_eq!(record.model, "claude-sonnet-4");
+        assert_eq!(record.input_tokens, 100);
+        assert_eq!(record.cache_read_tokens, 5);
+        assert_eq!(record.cost_usd, 0.25);
+
+        let temp = TempDir::new().unwrap();
+        let path = temp.path().join("usage.jsonl");
+        let records = [record.clone(), record];
+        write_records(&path, ExportFormat::Jsonl, &records).unwrap();
+        let written = std::fs::read_to_string(&path).unwrap();
+        let first: serde_json::Value =
+            serde_json::from_str(written.lines().next().unwrap()).unwrap();
+        assert_eq!(written.lines().count(), 2);
+        assert_eq!(first["timestamp"], "2025-03-01T10:00:00Z");
+        assert_eq!(first["cost_usd"], 0.25);
+
+        #[cfg(feature = "parquet-export")]
+        {
+            use ::parquet::file::reader::{FileReader, SerializedFileReader};
+
+            let path = temp.path().join("usage.parquet");
+            write_records(&path, ExportFormat::Parquet, &records).unwrap();
+            let reader = SerializedFileReader::new(File::open(&path).unwrap()).unwrap();
+            let metadata = reader.metadata().file_metadata();
+            assert_eq!(metadata.num_rows(), 2);
+            let schema = metadata.schema_descr();
+            assert_eq!(schema.num_columns(), 9);
+            assert_eq!(schema.column(8).name(), "cost_usd");
+        }
+    }
+
+    #[cfg(feature = "sqlite-export")]
+    #[test]
+    fn test_sqlite_export_upserts_by_key() {
+        let first = ExportRecord::from_entry(&entry(), CostMode::Display);
+        let next_day = ExportRecord {
+            key: "msg_2:req_2".to_string(),
+            timestamp: "2025-03-02T09:00:00Z".parse().unwrap(),
+            ..first.clone()
+        };
+        let repriced = ExportRecord {
+            cost_usd: 0.5,
+            ..first.clone()
+        };
+
+        let temp = TempDir::new().unwrap();
+        let path = temp.path().join("usage.db");
+        write_records(&path, ExportFormat::Sqlite, &[first]).unwrap();
+        // A later run sees the first entry again, repriced, and a new one
+        write_records(&path, ExportFormat::Sqlite, &[repriced, next_day]).unwrap();
+
+        let conn = rusqlite::Connection::open(&path).unwrap();
+        let count = |table: &str| -> u64 {
+            conn.query_row(&format!("SELECT count(*) FROM {}", table), [], |row| {
+                row.get(0)
+            })
+            .unwrap()
+        };
+        assert_eq!(count("entries"), 2);
+        assert_eq!(count("sessions"), 1);
+        assert_eq!(count("daily"), 2);
+        let (entries, cost): (u64, f64) = conn
+            .query_row(
+                "SELECT entries, cost_usd FROM sessions WHERE session = 'abc-123'",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .unwrap();
+        assert_eq!(entries, 2);
+        assert_eq!(cost, 0.75);
+    }
+}
@@ -0,0 +1,252 @@
+//! Integrity manifests for exported reports
+//!
+//! Reports written with `--output` and parts written by `archive compact` get
+//! a manifest recording the SHA-256 of each artifact, when and by which
+//! version they were generated, and a fingerprint of the configuration in
+//! effect. `claude-usage verify-export <manifest>` recomputes the hashes later,
+//! so an auditor can tell whether an export changed after it was produced.
+
+use crate::config::Config;
+use anyhow::{Context, Result};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs::{self, File};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+/// Suffix appended to an exported file's name for its manifest
+pub const MANIFEST_SUFFIX: &str = ".manifest.json";
+
+/// Manifest file inside each archive part directory
+pub const PART_MANIFEST_FILE: &str = "manifest.json";
+
+/// Hashes and provenance of a set of exported files
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportManifest {
+    /// When the artifacts were written (RFC 3339)
+    pub generated_at: String,
+    /// claude-usage version that wrote them
+    pub tool_version: String,
+    /// SHA-256 of the configuration in effect
+    pub config_fingerprint: String,
+    pub artifacts: Vec<Artifact>,
+}
+
+/// One exported file
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Artifact {
+    /// Path relative to the manifest's directory
+    pub path: PathBuf,
+    pub sha256: String,
+    pub bytes: u64,
+}
+
+/// Verification outcome of one artifact
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ArtifactStatus {
+    /// Hash and size match the manifest
+    Ok,
+    /// The file exists but its contents changed
+    Modified,
+    /// The file is gone or unreadable
+    Missing,
+}
+
+/// Verification of one artifact listed in a manifest
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ArtifactCheck {
+    pub path: PathBuf,
+    pub status: ArtifactStatus,
+    /// Hash found on disk when it differs from the manifest
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub actual_sha256: Option<String>,
+}
+
+/// Result of `verify-export`
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VerifyReport {
+    pub manifest: PathBuf,
+    pub generated_at: String,
+    pub tool_version: String,
+    /// Whether the current configuration has the recorded fingerprint
+    pub config_matches: bool,
+    pub artifacts: Vec<ArtifactCheck>,
+}
+
+impl VerifyReport {
+    /// Whether every artifact matches the manifest
+    pub fn passed(&self) -> bool {
+        self.artifacts
+            .iter()
+            .all(|check| check.status == ArtifactStatus::Ok)
+    }
+}
+
+impl ExportManifest {
+    /// Hash `artifacts`, recording their paths relative to `manifest_dir`
+    pub fn new(manifest_dir: &Path, artifacts: &[PathBuf], config: &Config) -> Result<Self> {
+        let artifacts = artifacts
+            .iter()
+            .map(|path| {
+                let (sha256, bytes) = sha256_file(path)?;
+                let relative = path.strip_prefix(manifest_dir).unwrap_or(path);
+                Ok(Artifact {
+                    path: relative.to_path_buf(),
+                    sha256,
+                    bytes,
+                })
+            })
+            .collect::<Result<_>>()?;
+
+        Ok(Self {
+            generated_at: Utc::now().to_rfc3339(),
+            tool_version: env!("CARGO_PKG_VERSION").to_string(),
+            config_fingerprint: config_fingerprint(config)?,
+            artifacts,
+        })
+    }
+
+    /// Load a manifest written by [`Self::save`]
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read manifest: {}", path.display()))?;
+        serde_json::from_str(&content)
+            .with_context(|| format!("Invalid manifest: {}", path.display()))
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        fs::write(path, serde_json::to_string_pretty(self)? + "\n")
+            .with_context(|| format!("Failed to write manifest: {}", path.display()))
+    }
+
+    /// Recompute the hash of every artifact, resolving paths against `manifest_dir`
+    pub fn check(&self, manifest_dir: &Path) -> Vec<ArtifactCheck> {
+        self.artifacts
+            .iter()
+            .map(|artifact| {
+                let (status, actual_sha256) = match sha256_file(&manifest_dir.join(&artifact.path))
+                {
+                    Ok((sha256, bytes)) if sha256 == artifact.sha256 && bytes == artifact.bytes => {
+                        (ArtifactStatus::Ok, None)
+                    }
+                    Ok((sha256, _)) => (ArtifactStatus::Modified, Some(sha256)),
+                    Err(_) => (ArtifactStatus::Missing, None),
+                };
+                ArtifactCheck {
+                    path: artifact.path.clone(),
+                    status,
+                    actual_sha256,
+                }
+            })
+            .collect()
+    }
+}
+
+/// Manifest path for an exported file: `daily.csv` -> `daily.csv.manifest.json`
+pub fn manifest_path(artifact: &Path) -> PathBuf {
+    let mut name = artifact.as_os_str().to_owned();
+    name.push(MANIFEST_SUFFIX);
+    PathBuf::from(name)
+}
+
+/// Write `content` to `path` along with its manifest, returning the manifest path
+pub fn write_export(path: &Path, content: &str, config: &Config) -> Result<PathBuf> {
+    fs::write(path, content).with_context(|| format!("Failed to write {}", path.display()))?;
+    write_manifest(path, config)
+}
+
+/// Write the manifest of the already written `path`, returning its path
+pub fn write_manifest(path: &Path, config: &Config) -> Result<PathBuf> {
+    let dir = path.parent().unwrap_or_else(|| Path::new(""));
+    let manifest = ExportManifest::new(dir, &[path.to_path_buf()], config)?;
+    let manifest_file = manifest_path(path);
+    manifest.save(&manifest_file)?;
+    Ok(manifest_file)
+}
+
+/// Check the artifacts listed in the manifest at `path`
+pub fn verify(path: &Path, config: &Config) -> Result<VerifyReport> {
+    let manifest = ExportManifest::load(path)?;
+    let dir = path.parent().unwrap_or_else(|| Path::new(""));
+
+    Ok(VerifyReport {
+        manifest: path.to_path_buf(),
+        config_matches: config_fingerprint(config)? == manifest.config_fingerprint,
+        artifacts: manifest.check(dir),
+        generated_at: manifest.generated_at,
+        tool_version: manifest.tool_version,
+    })
+}
+
+/// SHA-256 of the serialized configuration
+pub fn config_fingerprint(config: &Config) -> Result<String> {
+    let serialized = serde_json::to_vec(config).context("Failed to serialize configuration")?;
+    Ok(hex(&Sha256::digest(&serialized)))
+}
+
+/// Hex SHA-256 and size of a file
+fn sha256_file(path: &Path) -> Result<(String, u64)> {
+    let mut file =
+        File::open(path).with_context(|| format!("Failed to open {}", path.display()))?;
+    let mut hasher = Sha256::new();
+    let mut buffer = [0u8; 64 * 1024];
+    let mut bytes = 0u64;
+    loop {
+        let read = file
+            .read(&mut buffer)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+        bytes += read as u64;
+    }
+    Ok((hex(&hasher.finalize()), bytes))
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_export_manifest_detects_changes() {
+        let temp = TempDir::new().unwrap();
+        let config = Config::default();
+        let report = temp.path().join("daily.csv");
+
+        let manifest_file = write_export(&report, "date,cost_usd\n", &config).unwrap();
+        assert_eq!(manifest_file, temp.path().join("daily.csv.manifest.json"));
+
+        let manifest = ExportManifest::load(&manifest_file).unwrap();
+        assert_eq!(manifest.artifacts[0].path, PathBuf::from("daily.csv"));
+        assert_eq!(manifest.artifacts[0].bytes, 14);
+        assert_eq!(manifest.tool_version, env!("CARGO_PKG_VERSION"));
+
+        let report_check = verify(&manifest_file, &config).unwrap();
+        assert!(report_check.passed());
+        assert!(report_check.config_matches);
+
+        fs::write(&report, "date,cost_usd\n2025-01-01,0.00\n").unwrap();
+        let report_check = verify(&manifest_file, &config).unwrap();
+        assert_eq!(report_check.artifacts[0].status, ArtifactStatus::Modified);
+        assert!(!report_check.passed());
+
+        fs::remove_file(&report).unwrap();
+        let mut other_config = Config::default();
+        other_config.processing.batch_size = 99;
+        let report_check = verify(&manifest_file, &other_config).unwrap();
+        assert_eq!(report_check.artifacts[0].status, ArtifactStatus::Missing);
+        assert!(!report_check.config_matches);
+    }
+}
@@ -1,15 +1,86 @@
 use crate::config::get_config;
+use crate::io_throttle;
 use crate::keeper_integration::KeeperIntegration;
-use anyhow::Result;
+use crate::line_reader::{BoundedLines, Line};
+use crate::models::InstanceType;
+use anyhow::{Context, Result};
 use chrono::{DateTime, Utc};
-use glob::glob;
-use std::fs::{metadata, File};
-use std::io::{BufRead, BufReader};
+use glob::{glob, Pattern};
+use std::fs::metadata;
+use std::collections::HashSet;
+use std::io::BufReader;
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use tracing::{debug, info, warn};
+
+/// Marker file in an instance root that overrides path-based classification
+const INSTANCE_TYPE_MARKER: &str = ".instance-type";
+
+/// Which projects `--project` selects, by project directory name
+///
+/// Claude names a project's directory after its path with `/` and `.`
+/// replaced by `-` (`/home/me/src/my.app` -> `-home-me-src-my-app`). A glob
+/// (containing `*`, `?` or `[`) must match the whole directory name. A plain
+/// name or path is encoded the same way and matches directory names equal to
+/// it or ending in `-` followed by it, so `my-app` and `/home/me/src/my.app`
+/// both select `-home-me-src-my-app`.
+#[derive(Debug, Clone)]
+pub enum ProjectDirFilter {
+    Name(String),
+    Glob(Pattern),
+}
+
+impl ProjectDirFilter {
+    pub fn new(filter: &str) -> Result<Self> {
+        let filter = filter.trim();
+        if filter.is_empty() {
+            anyhow::bail!("--project needs a project name or glob");
+        }
+        if filter.contains(['*', '?', '[']) {
+            let pattern = Pattern::new(filter)
+                .with_context(|| format!("Invalid --project glob: {}", filter))?;
+            return Ok(Self::Glob(pattern));
+        }
+        Ok(Self::Name(encode_project(filter.trim_end_matches('/'))))
+    }
+
+    /// Whether a project directory name (or a project name read from the
+    /// backups, with or without its leading `-`) is selected
+    pub fn matches(&self, project: &str) -> bool {
+        let encoded = encode_project(project);
+        match self {
+            Self::Glob(pattern) => {
+                pattern.matches(project)
+                    || pattern.matches(&encoded)
+                    || encoded
+                        .strip_prefix('-')
+                        .is_some_and(|name| pattern.matches(name))
+            }
+            Self::Name(name) => {
+                let name = name.trim_start_matches('-');
+                encoded.trim_start_matches('-') == name || encoded.ends_with(&format!("-{}", name))
+            }
+        }
+    }
+
+    /// Whether the logs in `session_dir`, a directory under `projects/`, are selected
+    pub fn selects_session_dir(&self, session_dir: &Path) -> bool {
+        session_dir
+            .file_name()
+            .is_some_and(|name| self.matches(&name.to_string_lossy()))
+    }
+}
+
+/// Encode a project path the way Claude names its project directory
+fn encode_project(path: &str) -> String {
+    path.replace(['/', '.'], "-")
+}
 
 /// Handles file system traversal and discovery of Claude usage data files
 pub struct FileDiscovery {
     keeper_integration: KeeperIntegration,
+    /// Paths skipped so far because they reach a log already found
+    duplicate_paths: AtomicU64,
 }
 
 impl Default for FileDiscovery {
@@ -22,45 +93,242 @@ impl FileDiscovery {
     pub fn new() -> Self {
         Self {
             keeper_integration: KeeperIntegration::new(),
+            duplicate_paths: AtomicU64::new(0),
         }
     }
 
-    /// Discover all Claude installation paths (main + VMs)
+    /// Paths skipped by the JSONL searches so far because they reach a log
+    /// that was already found through another path
+    pub fn duplicate_paths(&self) -> u64 {
+        self.duplicate_paths.load(Ordering::Relaxed)
+    }
+
+    /// Discover all Claude installation paths (main + VMs, the config
+    /// directories, then the extra roots)
     pub fn discover_claude_paths(&self, exclude_vms: bool) -> Result<Vec<PathBuf>> {
-        let mut paths = Vec::new();
         let config = get_config();
+        let mut paths = Self::discover_home_roots(exclude_vms);
+        let extra =
+            Self::discover_extra_instance_roots(&config.paths.extra_roots, &paths, exclude_vms);
+        paths.extend(extra);
+        Ok(paths)
+    }
+
+    /// Discover the instance roots claude-keeper does not back up, which the
+    /// reports read from their logs: the Claude config directories and the
+    /// extra roots (`paths.extra_roots`), leaving out the home and its VMs
+    pub fn discover_log_roots(&self, exclude_vms: bool) -> Result<Vec<PathBuf>> {
+        let paths = &get_config().paths;
+        let home = Self::discover_main_roots(false);
+        let mut roots = Self::discover_config_dir_roots(&paths.config_dirs, &home);
+        if !paths.extra_roots.is_empty() {
+            let known: Vec<PathBuf> = home.iter().chain(&roots).cloned().collect();
+            let extra =
+                Self::discover_extra_instance_roots(&paths.extra_roots, &known, exclude_vms);
+            roots.extend(extra);
+        }
+        Ok(roots)
+    }
+
+    /// Instance roots of the home, its VMs and the Claude config directories
+    fn discover_home_roots(exclude_vms: bool) -> Vec<PathBuf> {
+        let mut roots = Self::discover_main_roots(exclude_vms);
+        let config_roots = Self::discover_config_dir_roots(&get_config().paths.config_dirs, &roots);
+        roots.extend(config_roots);
+        roots
+    }
+
+    /// Instance roots of the home and its VMs
+    fn discover_main_roots(exclude_vms: bool) -> Vec<PathBuf> {
+        let paths = &get_config().paths;
 
         // Get Claude home directory from config (respects CLAUDE_HOME env var)
-        let claude_home = &config.paths.claude_home;
-        
+        let claude_home = &paths.claude_home;
+
+        // VMs live under the home by default, but CLAUDE_VMS_DIR may point elsewhere
+        let vms_dirs = [claude_home.join("vms"), paths.vms_directory.clone()];
+
+        Self::discover_instance_roots(claude_home, &vms_dirs, exclude_vms)
+    }
+
+    /// The Claude config directories (`CLAUDE_CONFIG_DIR`, `~/.config/claude`)
+    /// holding a `projects/` folder, skipping those already in `known`
+    ///
+    /// Both locations are probed rather than configured, so one that does not
+    /// exist, or is a symlink to the home, is skipped without a warning.
+    fn discover_config_dir_roots(config_dirs: &[PathBuf], known: &[PathBuf]) -> Vec<PathBuf> {
+        let mut seen_roots: HashSet<PathBuf> = known.iter().map(|p| canonical_path(p)).collect();
+        let mut paths = Vec::new();
+        for dir in config_dirs {
+            if !dir.join("projects").is_dir() {
+                continue;
+            }
+            if seen_roots.insert(canonical_path(dir)) {
+                paths.push(dir.clone());
+            } else {
+                debug!(path = %dir.display(), "Claude config directory was already discovered; counting it once");
+            }
+        }
+        paths
+    }
+
+    /// Collect the instance roots of each extra root, searched like the home
+    /// (the root itself and the VMs under its `vms/`), skipping instances in
+    /// `known` or found through an earlier root
+    fn discover_extra_instance_roots(
+        extra_roots: &[PathBuf],
+        known: &[PathBuf],
+        exclude_vms: bool,
+    ) -> Vec<PathBuf> {
+        let mut seen_roots: HashSet<PathBuf> = known.iter().map(|p| canonical_path(p)).collect();
+        let mut paths = Vec::new();
+        for root in extra_roots {
+            if !root.is_dir() {
+                warn!(root = %root.display(), "Skipping extra Claude root that is not a directory");
+                continue;
+            }
+            for path in Self::discover_instance_roots(root, &[root.join("vms")], exclude_vms) {
+                if seen_roots.insert(canonical_path(&path)) {
+                    paths.push(path);
+                } else {
+                    warn!(
+                        path = %path.display(),
+                        root = %root.display(),
+                        "Claude instance of an extra root was already discovered; counting it once"
+                    );
+                }
+            }
+        }
+        paths
+    }
+
+    /// Collect instance roots from a home directory and VM directories
+    ///
+    /// Roots are de-duplicated by canonical path so that an instance reachable
+    /// both as the home (e.g. CLAUDE_HOME pointing inside a VMs folder) and as
+    /// a VM is only counted once.
+    fn discover_instance_roots(
+        claude_home: &Path,
+        vms_dirs: &[PathBuf],
+        exclude_vms: bool,
+    ) -> Vec<PathBuf> {
+        let mut seen_roots = HashSet::new();
+        let mut paths = Vec::new();
+
         // Main Claude path
-        let main_path = claude_home.clone();
-        if main_path.join("projects").exists() {
-            paths.push(main_path.clone());
+        if claude_home.join("projects").exists() {
+            seen_roots.insert(canonical_path(claude_home));
+            paths.push(claude_home.to_path_buf());
         }
 
         // VM paths (only if not excluded)
-        if !exclude_vms {
-            let vms_dir = main_path.join("vms");
-            if vms_dir.exists() {
-                if let Ok(entries) = std::fs::read_dir(&vms_dir) {
-                    for entry in entries.flatten() {
-                        let vm_path = entry.path();
-                        if vm_path.is_dir() && vm_path.join("projects").exists() {
-                            paths.push(vm_path);
-                        }
-                    }
+        if exclude_vms {
+            return paths;
+        }
+
+        // The default VMs directory and CLAUDE_VMS_DIR are often the same folder
+        let mut seen_vms_dirs = HashSet::new();
+        for vms_dir in vms_dirs {
+            if !vms_dir.exists() || !seen_vms_dirs.insert(canonical_path(vms_dir)) {
+                continue;
+            }
+
+            let Ok(entries) = std::fs::read_dir(vms_dir) else {
+                continue;
+            };
+            for entry in entries.flatten() {
+                let vm_path = entry.path();
+                if !vm_path.is_dir() || !vm_path.join("projects").exists() {
+                    continue;
+                }
+
+                if seen_roots.insert(canonical_path(&vm_path)) {
+                    paths.push(vm_path);
+                } else {
+                    warn!(
+                        path = %vm_path.display(),
+                        claude_home = %claude_home.display(),
+                        "Claude instance was already discovered at another path (CLAUDE_HOME and CLAUDE_VMS_DIR overlap); counting it once"
+                    );
                 }
             }
         }
 
-        Ok(paths)
+        paths
+    }
+
+    /// Name of the Claude instance a path belongs to: "main" for the home
+    /// installation, or the VM directory name for paths under `vms/`
+    pub fn instance_name(&self, claude_path: &Path) -> String {
+        let under_vms = claude_path
+            .parent()
+            .and_then(|p| p.file_name())
+            .map(|name| name == "vms")
+            .unwrap_or(false);
+
+        match claude_path.file_name() {
+            Some(name) if under_vms => name.to_string_lossy().to_string(),
+            _ => "main".to_string(),
+        }
+    }
+
+    /// The extra Claude root (`paths.extra_roots`) an instance root was
+    /// found under, or `None` for the home and its VMs
+    pub fn extra_root_of(&self, claude_path: &Path) -> Option<PathBuf> {
+        let paths = &get_config().paths;
+        Self::find_extra_root(claude_path, &paths.claude_home, &paths.extra_roots)
+            .map(Path::to_path_buf)
+    }
+
+    /// The longest of `extra_roots` containing `claude_path`, unless it is
+    /// under `claude_home`
+    fn find_extra_root<'a>(
+        claude_path: &Path,
+        claude_home: &Path,
+        extra_roots: &'a [PathBuf],
+    ) -> Option<&'a Path> {
+        if claude_path.starts_with(claude_home) {
+            return None;
+        }
+        extra_roots
+            .iter()
+            .filter(|root| claude_path.starts_with(root))
+            .max_by_key(|root| root.components().count())
+            .map(PathBuf::as_path)
+    }
+
+    /// Classify an instance root as host, VM or devcontainer
+    ///
+    /// A `.instance-type` marker file in the root (containing `host`, `vm` or
+    /// `devcontainer`) takes precedence over path conventions.
+    pub fn instance_type(&self, claude_path: &Path) -> InstanceType {
+        std::fs::read_to_string(claude_path.join(INSTANCE_TYPE_MARKER))
+            .ok()
+            .and_then(|marker| InstanceType::parse(&marker))
+            .unwrap_or_else(|| InstanceType::from_path(claude_path))
     }
 
     /// Find all JSONL files in the given Claude paths
     pub fn find_jsonl_files(&self, claude_paths: &[PathBuf]) -> Result<Vec<(PathBuf, PathBuf)>> {
+        self.find_project_jsonl_files(claude_paths, None)
+    }
+
+    /// Find the JSONL files of the projects selected by `project`, or of
+    /// all projects without a filter
+    ///
+    /// A log reachable through several paths (a symlinked session directory,
+    /// a hard link, or different letter case on a case-insensitive
+    /// filesystem) is listed once, under the first path found; the others
+    /// are counted in [`Self::duplicate_paths`].
+    pub fn find_project_jsonl_files(
+        &self,
+        claude_paths: &[PathBuf],
+        project: Option<&ProjectDirFilter>,
+    ) -> Result<Vec<(PathBuf, PathBuf)>> {
         let mut file_tuples = Vec::new();
-        let mut seen_files = std::collections::HashSet::new();
+        let mut seen_files = HashSet::new();
+        let mut seen_identities = HashSet::new();
+        let mut duplicates = 0u64;
 
         for claude_path in claude_paths {
             let projects_dir = claude_path.join("projects");
@@ -77,18 +345,50 @@ impl FileDiscovery {
 
             for pattern in patterns {
                 if let Ok(paths) = glob(&pattern.to_string_lossy()) {
-                    for entry in paths.flatten() {
-                        // Deduplicate files that match multiple patterns
-                        if seen_files.insert(entry.clone()) {
-                            if let Some(session_dir) = entry.parent() {
-                                file_tuples.push((entry.clone(), session_dir.to_path_buf()));
+                    for entry in paths {
+                        let entry = match entry {
+                            Ok(entry) => entry,
+                            Err(e) if io_throttle::is_fd_exhausted(e.error()) => {
+                                warn!(
+                                    path = %e.path().display(),
+                                    "Too many open files while listing session logs, so some were not found. Raise the limit with `ulimit -n {}`",
+                                    io_throttle::suggested_ulimit()
+                                );
+                                continue;
                             }
+                            Err(e) => {
+                                debug!(path = %e.path().display(), error = %e.error(), "Skipping unreadable path");
+                                continue;
+                            }
+                        };
+                        // Deduplicate files that match multiple patterns
+                        if !seen_files.insert(entry.clone()) {
+                            continue;
+                        }
+                        if !seen_identities.insert(FileIdentity::of(&entry)) {
+                            debug!(path = %entry.display(), "Skipping another path to a log already found");
+                            duplicates += 1;
+                            continue;
+                        }
+                        let Some(session_dir) = entry.parent() else {
+                            continue;
+                        };
+                        if project.map_or(true, |filter| filter.selects_session_dir(session_dir)) {
+                            file_tuples.push((entry.clone(), session_dir.to_path_buf()));
                         }
                     }
                 }
             }
         }
 
+        if duplicates > 0 {
+            info!(
+                duplicates,
+                "Skipped duplicate paths to the same session logs"
+            );
+            self.duplicate_paths
+                .fetch_add(duplicates, Ordering::Relaxed);
+        }
         Ok(file_tuples)
     }
 
@@ -178,7 +478,7 @@ impl FileDiscovery {
         &self,
         file_path: &Path,
     ) -> Result<(Option<DateTime<Utc>>, Option<DateTime<Utc>>)> {
-        let file = File::open(file_path)?;
+        let file = io_throttle::global().open(file_path)?;
         let reader = BufReader::new(file);
 
         let mut earliest_timestamp: Option<DateTime<Utc>> = None;
@@ -188,8 +488,10 @@ impl FileDiscovery {
         let mut first_line = None;
         let mut last_line = None;
 
-        for line in reader.lines() {
-            let line = line?;
+        for line in BoundedLines::from_config(reader) {
+            let Line::Text(line) = line?.1 else {
+                continue;
+            };
             let line = line.trim();
             if line.is_empty() {
                 continue;
@@ -227,11 +529,13 @@ impl FileDiscovery {
 
     /// Get the earliest timestamp from a file
     pub fn get_earliest_timestamp(&self, file_path: &Path) -> Result<Option<DateTime<Utc>>> {
-        let file = File::open(file_path)?;
+        let file = io_throttle::global().open(file_path)?;
         let reader = BufReader::new(file);
 
-        for line in reader.lines() {
-            let line = line?;
+        for line in BoundedLines::from_config(reader) {
+            let Line::Text(line) = line?.1 else {
+                continue;
+            };
             let line = line.trim();
             if line.is_empty() {
                 continue;
@@ -250,6 +554,7 @@ impl FileDiscovery {
     }
 
     /// Sort files by timestamp (modification time + content timestamp for smaller datasets)
+    #[allow(dead_code)]
     pub fn sort_files_by_timestamp(
         &self,
         mut file_tuples: Vec<(PathBuf, PathBuf)>,
@@ -288,22 +593,41 @@ impl FileDiscovery {
         file_tuples
     }
 
-    /// Find session blocks files
-    #[allow(dead_code)]
-    pub fn find_session_blocks_files(&self, claude_paths: &[PathBuf]) -> Result<Vec<PathBuf>> {
-        let mut block_files = Vec::new();
-
+    /// Find session blocks files, newest first
+    ///
+    /// Looks in `usage_tracking` under each instance root and under each VM
+    /// of a root's `vms` directory (a VM without `projects/` is not an
+    /// instance root, but may still track blocks), and directly in each of
+    /// `extra_dirs`. A file reachable through more than one path is listed once.
+    pub fn find_session_blocks_files(
+        &self,
+        claude_paths: &[PathBuf],
+        extra_dirs: &[PathBuf],
+    ) -> Result<Vec<PathBuf>> {
+        let mut search_dirs = Vec::new();
         for claude_path in claude_paths {
-            let usage_dir = claude_path.join("usage_tracking");
-            if !usage_dir.exists() {
+            search_dirs.push(claude_path.join("usage_tracking"));
+            let vm_pattern = claude_path.join("vms").join("*").join("usage_tracking");
+            if let Ok(paths) = glob(&vm_pattern.to_string_lossy()) {
+                search_dirs.extend(paths.flatten());
+            }
+        }
+        search_dirs.extend(extra_dirs.iter().cloned());
+
+        let mut block_files = Vec::new();
+        let mut seen_identities = HashSet::new();
+        for dir in search_dirs {
+            if !dir.is_dir() {
                 continue;
             }
 
             // Find session block files
-            let pattern = usage_dir.join("session_blocks_*.json");
+            let pattern = dir.join("session_blocks_*.json");
             if let Ok(paths) = glob(&pattern.to_string_lossy()) {
                 for entry in paths.flatten() {
-                    block_files.push(entry);
+                    if seen_identities.insert(FileIdentity::of(&entry)) {
+                        block_files.push(entry);
+                    }
                 }
             }
         }
@@ -322,3 +646,270 @@ impl FileDiscovery {
         Ok(block_files)
     }
 }
+
+/// Resolve symlinks and relative components, falling back to the path as given
+fn canonical_path(path: &Path) -> PathBuf {
+    path.canonicalize().unwrap_or_else(|_| path.to_path_buf())
+}
+
+/// What makes two paths the same file
+///
+/// The canonical path resolves symlinks; on Unix the device and inode also
+/// catch hard links and letter-case variants on case-insensitive
+/// filesystems, which canonicalization does not always normalize.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum FileIdentity {
+    #[cfg(unix)]
+    Inode {
+        dev: u64,
+        ino: u64,
+    },
+    Path(PathBuf),
+}
+
+impl FileIdentity {
+    fn of(path: &Path) -> Self {
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::MetadataExt;
+            if let Ok(meta) = metadata(path) {
+                return Self::Inode {
+                    dev: meta.dev(),
+                    ino: meta.ino(),
+                };
+            }
+        }
+        Self::Path(canonical_path(path))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn make_instance(path: &Path) -> PathBuf {
+        std::fs::create_dir_all(path.join("projects")).unwrap();
+        path.to_path_buf()
+    }
+
+    #[test]
+    fn test_instance_name() {
+        let discovery = FileDiscovery::new();
+        assert_eq!(discovery.instance_name(Path::new("/home/user/.claude")), "main");
+        assert_eq!(
+            discovery.instance_name(Path::new("/home/user/.claude/vms/build-box")),
+            "build-box"
+        );
+    }
+
+    #[test]
+    fn test_discovers_home_and_vms() {
+        let temp = TempDir::new().unwrap();
+        let home = make_instance(&temp.path().join(".claude"));
+        let vm = make_instance(&home.join("vms").join("vm1"));
+
+        let roots = FileDiscovery::discover_instance_roots(&home, &[home.join("vms")], false);
+        assert_eq!(roots, vec![home.clone(), vm]);
+
+        let roots = FileDiscovery::discover_instance_roots(&home, &[home.join("vms")], true);
+        assert_eq!(roots, vec![home]);
+    }
+
+    #[test]
+    fn test_extra_roots_searched_like_the_home() {
+        let temp = TempDir::new().unwrap();
+        let home = make_instance(&temp.path().join("me").join(".claude"));
+        let alice = temp.path().join("alice").join(".claude");
+        make_instance(&alice);
+        let alice_vm = make_instance(&alice.join("vms").join("vm1"));
+        let bob = make_instance(&temp.path().join("bob").join(".claude"));
+        let missing = temp.path().join("carol").join(".claude");
+
+        let known = FileDiscovery::discover_instance_roots(&home, &[home.join("vms")], false);
+        let extra_roots = [alice.clone(), bob.clone(), missing, home, bob.clone()];
+        let roots = FileDiscovery::discover_extra_instance_roots(&extra_roots, &known, false);
+        assert_eq!(roots, vec![alice.clone(), alice_vm.clone(), bob.clone()]);
+        let roots = FileDiscovery::discover_extra_instance_roots(&extra_roots, &known, true);
+        assert_eq!(roots, vec![alice.clone(), bob]);
+
+        let home = &known[0];
+        let find = |path: &Path| FileDiscovery::find_extra_root(path, home, &extra_roots);
+        assert_eq!(find(&alice_vm), Some(alice.as_path()));
+        assert_eq!(find(home), None);
+        assert_eq!(find(&temp.path().join("dave")), None);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_config_dirs_probed_and_counted_once() {
+        let temp = TempDir::new().unwrap();
+        let home = make_instance(&temp.path().join(".claude"));
+        let xdg = make_instance(&temp.path().join(".config").join("claude"));
+        let missing = temp.path().join("custom");
+        let link = temp.path().join("claude-link");
+        std::os::unix::fs::symlink(&home, &link).unwrap();
+
+        let known = FileDiscovery::discover_instance_roots(&home, &[home.join("vms")], false);
+        let config_dirs = [missing, link, xdg.clone(), xdg.clone()];
+        let roots = FileDiscovery::discover_config_dir_roots(&config_dirs, &known);
+        assert_eq!(roots, vec![xdg.clone()]);
+
+        // Without a home, the config directory is the only instance
+        let roots = FileDiscovery::discover_config_dir_roots(std::slice::from_ref(&xdg), &[]);
+        assert_eq!(roots, vec![xdg]);
+    }
+
+    #[test]
+    fn test_home_inside_vms_dir_counted_once() {
+        let temp = TempDir::new().unwrap();
+        let vms_dir = temp.path().join(".claude").join("vms");
+        let home = make_instance(&vms_dir.join("vm1"));
+        let other_vm = make_instance(&vms_dir.join("vm2"));
+
+        let roots = FileDiscovery::discover_instance_roots(
+            &home,
+            &[home.join("vms"), vms_dir.clone()],
+            false,
+        );
+        assert_eq!(roots, vec![home, other_vm]);
+    }
+
+    #[test]
+    fn test_same_vms_dir_listed_twice() {
+        let temp = TempDir::new().unwrap();
+        let home = make_instance(&temp.path().join(".claude"));
+        let vm = make_instance(&home.join("vms").join("vm1"));
+
+        // Same folder spelled two ways, e.g. default and CLAUDE_VMS_DIR
+        let vms_dirs = [home.join("vms"), home.join("vms").join("..").join("vms")];
+        let roots = FileDiscovery::discover_instance_roots(&home, &vms_dirs, false);
+        assert_eq!(roots, vec![home, vm]);
+    }
+
+    #[test]
+    fn test_instance_type_from_conventions_and_marker() {
+        let temp = TempDir::new().unwrap();
+        let discovery = FileDiscovery::new();
+        let home = make_instance(&temp.path().join(".claude"));
+        let vm = make_instance(&home.join("vms").join("vm1"));
+        let container = make_instance(&temp.path().join(".devcontainer").join(".claude"));
+
+        assert_eq!(discovery.instance_type(&home), InstanceType::Host);
+        assert_eq!(discovery.instance_type(&vm), InstanceType::Vm);
+        assert_eq!(discovery.instance_type(&container), InstanceType::Devcontainer);
+
+        std::fs::write(home.join(INSTANCE_TYPE_MARKER), "devcontainer\n").unwrap();
+        assert_eq!(discovery.instance_type(&home), InstanceType::Devcontainer);
+
+        assert_eq!(InstanceType::vm_name(&vm).as_deref(), Some("vm1"));
+        assert_eq!(
+            InstanceType::vm_name(&vm.join("projects").join("app")).as_deref(),
+            Some("vm1")
+        );
+        assert_eq!(InstanceType::vm_name(&home), None);
+    }
+
+    #[test]
+    fn test_project_filter_matches_names_paths_and_globs() {
+        let app = "-home-me-src-my-app";
+        let by_name = ProjectDirFilter::new("my-app").unwrap();
+        assert!(by_name.matches(app));
+        assert!(by_name.matches("home-me-src-my-app"));
+        assert!(!by_name.matches("-home-me-src-my-app-server"));
+        let by_path = ProjectDirFilter::new("/home/me/src/my.app/").unwrap();
+        assert!(by_path.matches(app));
+        assert!(ProjectDirFilter::new("*src-my-*").unwrap().matches(app));
+        assert!(!ProjectDirFilter::new("src-*").unwrap().matches(app));
+        assert!(ProjectDirFilter::new(" ").is_err());
+
+        let temp = TempDir::new().unwrap();
+        let home = make_instance(&temp.path().join(".claude"));
+        for project in [app, "-home-me-src-other"] {
+            let dir = home.join("projects").join(project);
+            std::fs::create_dir_all(&dir).unwrap();
+            std::fs::write(dir.join("s.jsonl"), "").unwrap();
+        }
+        let files = FileDiscovery::new()
+            .find_project_jsonl_files(&[home], Some(&by_name))
+            .unwrap();
+        assert_eq!(files.len(), 1);
+        assert!(files[0].1.ends_with(app));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_symlinked_home_counted_once() {
+        let temp = TempDir::new().unwrap();
+        let vms_dir = temp.path().join("vms");
+        let vm = make_instance(&vms_dir.join("vm1"));
+        let link = temp.path().join("home-link");
+        std::os::unix::fs::symlink(&vm, &link).unwrap();
+
+        let roots = FileDiscovery::discover_instance_roots(&link, &[vms_dir], false);
+        assert_eq!(roots, vec![link]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_symlinked_and_case_variant_logs_listed_once() {
+        let temp = TempDir::new().unwrap();
+        let home = make_instance(&temp.path().join(".claude"));
+        let app = home.join("projects").join("-home-me-app");
+        std::fs::create_dir_all(&app).unwrap();
+        let log = app.join("Session.jsonl");
+        std::fs::write(&log, "{}\n").unwrap();
+
+        // A project directory linking to the first, as left by a moved checkout
+        std::os::unix::fs::symlink(&app, home.join("projects").join("-home-me-app-link")).unwrap();
+        // The log in another letter case: the same file on a case-insensitive
+        // filesystem, and a hard link standing in for it elsewhere
+        let variant = app.join("session.jsonl");
+        let case_sensitive = !variant.exists();
+        if case_sensitive {
+            std::fs::hard_link(&log, &variant).unwrap();
+        }
+
+        let discovery = FileDiscovery::new();
+        let files = discovery.find_jsonl_files(&[home]).unwrap();
+        assert_eq!(files, vec![(log, app)]);
+        // Every other path to the log: through the link, and in the other case
+        let expected = if case_sensitive { 3 } else { 1 };
+        assert_eq!(discovery.duplicate_paths(), expected);
+    }
+
+    #[test]
+    fn test_session_blocks_found_in_instances_vms_and_extra_dirs() {
+        let temp = TempDir::new().unwrap();
+        let discovery = FileDiscovery::new();
+        let home = make_instance(&temp.path().join(".claude"));
+        let vm = make_instance(&home.join("vms").join("vm1"));
+        // Tracks blocks without having any session logs of its own
+        let bare_vm = home.join("vms").join("vm2");
+        let extra = temp.path().join("exported");
+        for dir in [
+            home.join("usage_tracking"),
+            vm.join("usage_tracking"),
+            bare_vm.join("usage_tracking"),
+            extra.clone(),
+        ] {
+            std::fs::create_dir_all(&dir).unwrap();
+            std::fs::write(dir.join("session_blocks_2025-03-01.json"), "[]").unwrap();
+        }
+        std::fs::write(extra.join("unrelated.json"), "[]").unwrap();
+
+        // The VM is both a root and under the home's `vms`, but listed once
+        let mut files = discovery
+            .find_session_blocks_files(&[home.clone(), vm.clone()], std::slice::from_ref(&extra))
+            .unwrap();
+        files.sort();
+        let mut expected = vec![
+            home.join("usage_tracking/session_blocks_2025-03-01.json"),
+            vm.join("usage_tracking/session_blocks_2025-03-01.json"),
+            bare_vm.join("usage_tracking/session_blocks_2025-03-01.json"),
+            extra.join("session_blocks_2025-03-01.json"),
+        ];
+        expected.sort();
+        assert_eq!(files, expected);
+    }
+}
@@ -0,0 +1,208 @@
+//! Burn rate and month-end forecast
+//!
+//! `claude-usage forecast` averages the daily cost of the last N complete
+//! days (today is still in progress, so it is left out of the average) and
+//! projects the month-end spend as the month-to-date cost plus that average
+//! for every day left after today. With `[budget] monthly_usd` set, it also
+//! estimates the day the monthly budget runs out at the current burn rate.
+//!
+//! The projection is linear on purpose: it answers "where does this month
+//! end up if the last N days are typical", not a trend fit.
+
+use chrono::{Datelike, Duration, Months, NaiveDate};
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+use crate::models::{MicroDollars, SessionOutput};
+
+/// Days averaged when no `--days` is given
+pub const DEFAULT_FORECAST_DAYS: u32 = 7;
+
+/// Burn rate over the last days and the projected month-end spend
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Forecast {
+    /// Month projected (YYYY-MM)
+    pub month: String,
+    /// First and last day averaged (YYYY-MM-DD)
+    pub window_start: String,
+    pub window_end: String,
+    pub window_days: u32,
+    pub window_cost: MicroDollars,
+    /// `window_cost` spread over `window_days`, days without usage included
+    pub average_daily_cost: MicroDollars,
+    /// Cost from the first of the month through today
+    pub month_to_date_cost: MicroDollars,
+    pub days_elapsed: u32,
+    pub days_in_month: u32,
+    /// `month_to_date_cost` plus the average for each day after today
+    pub projected_month_cost: MicroDollars,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub budget: Option<BudgetForecast>,
+}
+
+/// How the month compares with `[budget] monthly_usd`
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BudgetForecast {
+    pub monthly_budget: MicroDollars,
+    /// Budget left after the month-to-date cost; negative once exceeded
+    pub remaining: MicroDollars,
+    /// `projected_month_cost` as a percentage of the budget
+    pub projected_percent: f64,
+    /// The month-to-date cost already reached the budget
+    pub exhausted: bool,
+    /// Day the budget runs out at the average burn rate, when that is
+    /// before the month ends
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub exhausted_on: Option<String>,
+}
+
+/// Forecast the month of `today` from the sessions' daily usage
+///
+/// `days` complete days before `today` are averaged; `monthly_budget` is in
+/// dollars.
+pub fn forecast(
+    sessions: &[SessionOutput],
+    today: NaiveDate,
+    days: u32,
+    monthly_budget: Option<f64>,
+) -> Forecast {
+    let days = days.max(1);
+    let daily = daily_costs(sessions);
+    let window_start = today - Duration::days(i64::from(days));
+    let window_end = today - Duration::days(1);
+    let month_start = today.with_day(1).unwrap_or(today);
+
+    let window_cost: MicroDollars = daily
+        .range(window_start..=window_end)
+        .map(|(_, cost)| *cost)
+        .sum();
+    let month_to_date_cost: MicroDollars = daily
+        .range(month_start..=today)
+        .map(|(_, cost)| *cost)
+        .sum();
+    let average_daily_cost = MicroDollars(window_cost.0 / i64::from(days));
+
+    let days_in_month = days_in_month(month_start);
+    let days_left = days_in_month - today.day();
+    let projected_month_cost =
+        month_to_date_cost + MicroDollars(average_daily_cost.0 * i64::from(days_left));
+
+    let budget = monthly_budget.map(|budget| {
+        let monthly_budget = MicroDollars::from_dollars(budget);
+        let remaining = monthly_budget - month_to_date_cost;
+        let exhausted = remaining <= MicroDollars::ZERO;
+        // The budget runs out on the first day whose spend covers what remains
+        let exhausted_on = (!exhausted && average_daily_cost > MicroDollars::ZERO)
+            .then(|| {
+                let days_until = (remaining.0 + average_daily_cost.0 - 1) / average_daily_cost.0;
+                today + Duration::days(days_until)
+            })
+            .filter(|day| day.month() == today.month())
+            .map(|day| day.format("%Y-%m-%d").to_string());
+        BudgetForecast {
+            monthly_budget,
+            remaining,
+            projected_percent: projected_month_cost.to_dollars() / budget * 100.0,
+            exhausted,
+            exhausted_on,
+        }
+    });
+
+    Forecast {
+        month: month_start.format("%Y-%m").to_string(),
+        window_start: window_start.format("%Y-%m-%d").to_string(),
+        window_end: window_end.format("%Y-%m-%d").to_string(),
+        window_days: days,
+        window_cost,
+        average_daily_cost,
+        month_to_date_cost,
+        days_elapsed: today.day(),
+        days_in_month,
+        projected_month_cost,
+        budget,
+    }
+}
+
+/// First day whose usage a forecast for `today` over `days` days needs
+pub fn first_day_needed(today: NaiveDate, days: u32) -> NaiveDate {
+    let month_start = today.with_day(1).unwrap_or(today);
+    month_start.min(today - Duration::days(i64::from(days.max(1))))
+}
+
+/// Cost of every day with usage, across all sessions
+fn daily_costs(sessions: &[SessionOutput]) -> BTreeMap<NaiveDate, MicroDollars> {
+    let mut daily = BTreeMap::new();
+    for session in sessions {
+        for (date, usage) in &session.daily_usage {
+            let Ok(date) = NaiveDate::parse_from_str(date, "%Y-%m-%d") else {
+                continue;
+            };
+            *daily.entry(date).or_insert(MicroDollars::ZERO) += usage.cost;
+        }
+    }
+    daily
+}
+
+fn days_in_month(month_start: NaiveDate) -> u32 {
+    let next_month = month_start
+        .checked_add_months(Months::new(1))
+        .unwrap_or(month_start);
+    (next_month - month_start).num_days() as u32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::test_support::session;
+
+    #[test]
+    fn test_forecast_projects_month_and_budget() {
+        let today = NaiveDate::from_ymd_opt(2025, 4, 10).unwrap();
+        let sessions = vec![
+            session("s1")
+                .days(&[
+                    ("2025-03-31", 4.0),
+                    ("2025-04-01", 2.0),
+                    ("2025-04-05", 6.0),
+                ])
+                .build(),
+            session("s1")
+                .days(&[("2025-04-09", 2.0), ("2025-04-10", 1.0)])
+                .build(),
+        ];
+
+        let result = forecast(&sessions, today, 5, Some(40.0));
+        assert_eq!(result.month, "2025-04");
+        assert_eq!(result.window_start, "2025-04-05");
+        assert_eq!(result.window_end, "2025-04-09");
+        assert_eq!(result.average_daily_cost, MicroDollars::from_dollars(1.6));
+        assert_eq!(result.month_to_date_cost, MicroDollars::from_dollars(11.0));
+        assert_eq!(result.days_in_month, 30);
+        // 11 so far plus 1.6 for each of the 20 days after the 10th
+        assert_eq!(
+            result.projected_month_cost,
+            MicroDollars::from_dollars(43.0)
+        );
+
+        let budget = result.budget.unwrap();
+        assert_eq!(budget.remaining, MicroDollars::from_dollars(29.0));
+        assert!(!budget.exhausted);
+        // 29 / 1.6 = 18.1, so the 19th day after today
+        assert_eq!(budget.exhausted_on.as_deref(), Some("2025-04-29"));
+
+        let spent = forecast(&sessions, today, 5, Some(10.0)).budget.unwrap();
+        assert!(spent.exhausted);
+        assert_eq!(spent.exhausted_on, None);
+
+        assert_eq!(
+            first_day_needed(today, 30),
+            NaiveDate::from_ymd_opt(2025, 3, 11).unwrap()
+        );
+        assert_eq!(
+            first_day_needed(today, 5),
+            NaiveDate::from_ymd_opt(2025, 4, 1).unwrap()
+        );
+    }
+}
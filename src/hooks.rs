@@ -0,0 +1,198 @@
+//! Activity-triggered hooks
+//!
+//! Each event under `[hooks]` maps to a shell command, run with `sh -c`
+//! (`cmd /C` on Windows) when the event happens. The command gets a JSON
+//! object on stdin with the `event` name, a `timestamp` and the event's
+//! fields, and the event name in `CLAUDE_USAGE_HOOK_EVENT`:
+//!
+//! - `day_rollover`: live mode saw the local day change (`previousDay`, `day`)
+//! - `new_session`: live mode saw the first entry of a session (`sessionId`,
+//!   `project`, `instance`, `model`)
+//! - `cost_threshold`: live mode crossed a daily budget threshold (`level`,
+//!   `day`, `spent`, `limit`, `percent`)
+//! - `run_completed`: a report command finished (the metric `metrics.file`
+//!   records, see [`crate::self_metrics`])
+//!
+//! Hooks are best-effort: their output is discarded, one that fails or runs
+//! longer than `hooks.timeout_secs` is logged and killed, and none of this
+//! fails the command that fired it. Live mode runs hooks in the background;
+//! `run_completed` is waited for, so it has finished when the command exits.
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::io::Write;
+use std::process::{Child, Command, ExitStatus, Stdio};
+use std::time::{Duration, Instant};
+use tracing::{debug, warn};
+
+use crate::config::{get_config, HooksConfig};
+
+/// How often a running hook is checked for exit
+const POLL_INTERVAL: Duration = Duration::from_millis(20);
+
+/// Something a hook can be run for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HookEvent {
+    DayRollover,
+    NewSession,
+    CostThreshold,
+    RunCompleted,
+}
+
+impl HookEvent {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            HookEvent::DayRollover => "day_rollover",
+            HookEvent::NewSession => "new_session",
+            HookEvent::CostThreshold => "cost_threshold",
+            HookEvent::RunCompleted => "run_completed",
+        }
+    }
+
+    /// The command configured for this event, if any
+    fn command(self, config: &HooksConfig) -> Option<&str> {
+        let command = match self {
+            HookEvent::DayRollover => &config.day_rollover,
+            HookEvent::NewSession => &config.new_session,
+            HookEvent::CostThreshold => &config.cost_threshold,
+            HookEvent::RunCompleted => &config.run_completed,
+        };
+        command
+            .as_deref()
+            .filter(|command| !command.trim().is_empty())
+    }
+}
+
+/// Run the hook for `event` in the background, if one is configured
+///
+/// `data` must serialize to a JSON object; its fields are added to the
+/// payload.
+pub fn fire(event: HookEvent, data: impl Serialize) {
+    let config = &get_config().hooks;
+    let Some(command) = event.command(config) else {
+        return;
+    };
+    let Some(child) = start(command, event, &data) else {
+        return;
+    };
+    let timeout = Duration::from_secs(config.timeout_secs);
+    std::thread::spawn(move || finish(event, child, timeout));
+}
+
+/// Run the hook for `event` and wait for it, if one is configured
+pub fn fire_and_wait(event: HookEvent, data: impl Serialize) {
+    let config = &get_config().hooks;
+    let Some(command) = event.command(config) else {
+        return;
+    };
+    if let Some(child) = start(command, event, &data) {
+        finish(event, child, Duration::from_secs(config.timeout_secs));
+    }
+}
+
+/// Start `command` with the payload on stdin, logging a failure to do so
+fn start(command: &str, event: HookEvent, data: &impl Serialize) -> Option<Child> {
+    match spawn_hook(command, event, data) {
+        Ok(child) => Some(child),
+        Err(e) => {
+            warn!(event = event.as_str(), error = %e, "Failed to run hook");
+            None
+        }
+    }
+}
+
+/// Wait for a hook, logging it if it fails or times out
+fn finish(event: HookEvent, child: Child, timeout: Duration) {
+    match wait_hook(child, timeout) {
+        Ok(status) if status.success() => debug!(event = event.as_str(), "Hook finished"),
+        Ok(status) => warn!(event = event.as_str(), %status, "Hook failed"),
+        Err(e) => warn!(event = event.as_str(), error = %e, "Hook failed"),
+    }
+}
+
+fn spawn_hook(command: &str, event: HookEvent, data: &impl Serialize) -> Result<Child> {
+    let mut payload = serde_json::to_value(data)?;
+    let fields = payload
+        .as_object_mut()
+        .context("Hook payload is not a JSON object")?;
+    fields.insert("event".to_string(), event.as_str().into());
+    // A run's metric already has the time it finished
+    fields
+        .entry("timestamp")
+        .or_insert_with(|| chrono::Utc::now().to_rfc3339().into());
+    let payload = serde_json::to_vec(&payload)?;
+
+    let mut child = shell_command(command)
+        .env("CLAUDE_USAGE_HOOK_EVENT", event.as_str())
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .with_context(|| format!("Failed to start hook '{}'", command))?;
+
+    // A hook that does not read its payload may exit before it is written
+    if let Some(mut stdin) = child.stdin.take() {
+        if let Err(e) = stdin.write_all(&payload) {
+            debug!(event = event.as_str(), error = %e, "Hook did not read its payload");
+        }
+    }
+    Ok(child)
+}
+
+/// Wait up to `timeout` for a hook to exit, killing it after that
+fn wait_hook(mut child: Child, timeout: Duration) -> Result<ExitStatus> {
+    let started = Instant::now();
+    loop {
+        if let Some(status) = child.try_wait()? {
+            return Ok(status);
+        }
+        if started.elapsed() >= timeout {
+            let _ = child.kill();
+            let _ = child.wait();
+            anyhow::bail!("Hook timed out after {}s and was killed", timeout.as_secs());
+        }
+        std::thread::sleep(POLL_INTERVAL);
+    }
+}
+
+/// The platform shell running `command`
+fn shell_command(command: &str) -> Command {
+    if cfg!(windows) {
+        let mut shell = Command::new("cmd");
+        shell.arg("/C").arg(command);
+        shell
+    } else {
+        let mut shell = Command::new("sh");
+        shell.arg("-c").arg(command);
+        shell
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_hook_gets_payload_on_stdin() {
+        let temp = TempDir::new().unwrap();
+        let out = temp.path().join("payload.json");
+        let command = format!(
+            "cat > '{}' && test \"$CLAUDE_USAGE_HOOK_EVENT\" = new_session",
+            out.display()
+        );
+
+        let data = serde_json::json!({"sessionId": "abc", "project": "demo"});
+        let child = spawn_hook(&command, HookEvent::NewSession, &data).unwrap();
+        assert!(wait_hook(child, Duration::from_secs(10)).unwrap().success());
+
+        let payload: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(&out).unwrap()).unwrap();
+        assert_eq!(payload["event"], "new_session");
+        assert_eq!(payload["sessionId"], "abc");
+        assert!(payload["timestamp"].is_string());
+
+        let child = spawn_hook("sleep 5", HookEvent::DayRollover, &data).unwrap();
+        assert!(wait_hook(child, Duration::from_millis(100)).is_err());
+    }
+}
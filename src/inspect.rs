@@ -0,0 +1,200 @@
+//! Single-file inspection
+//!
+//! `claude-usage inspect <file.jsonl>` parses one session log on its own and
+//! reports what the aggregation pipeline would make of it: how many lines
+//! became usage entries, which lines failed to parse and why, token and cost
+//! totals, models, the timestamp range and how many entries carry a dedup
+//! key. It exists to answer "why is this conversation missing from my
+//! reports?" without rerunning a full analysis.
+
+use crate::io_throttle;
+use crate::line_reader::{BoundedLines, Line};
+use crate::models::MicroDollars;
+use crate::parser::{FileParser, ProcessedEntry};
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashSet};
+use std::io::BufReader;
+use std::path::{Path, PathBuf};
+
+/// A line that could not be turned into an entry
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct LineError {
+    pub line: usize,
+    pub reason: String,
+}
+
+/// Breakdown of one JSONL file
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FileInspection {
+    pub file: PathBuf,
+    pub lines: usize,
+    pub blank_lines: usize,
+    /// Entries with token usage, i.e. what reports count
+    pub entries: usize,
+    /// Valid JSON lines that carry no usage (user turns, summaries, ...)
+    pub non_usage_lines: usize,
+    pub errors: Vec<LineError>,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub cache_creation_tokens: u64,
+    pub cache_read_tokens: u64,
+    pub total_cost: MicroDollars,
+    /// Usage entries per model
+    pub models: BTreeMap<String, usize>,
+    pub first_timestamp: Option<String>,
+    pub last_timestamp: Option<String>,
+    /// Usage entries with a `messageId:requestId` dedup key
+    pub with_dedup_key: usize,
+    /// Usage entries whose dedup key already appeared earlier in the file
+    pub duplicate_keys: usize,
+}
+
+impl FileInspection {
+    /// Share of usage entries that can be deduplicated, as a percentage
+    pub fn dedup_coverage(&self) -> f64 {
+        if self.entries == 0 {
+            0.0
+        } else {
+            self.with_dedup_key as f64 / self.entries as f64 * 100.0
+        }
+    }
+
+    fn error(&mut self, line: usize, reason: String) {
+        self.errors.push(LineError { line, reason });
+    }
+
+    fn add_entry(&mut self, entry: &ProcessedEntry) {
+        let export = entry.to_export();
+        self.entries += 1;
+        self.input_tokens += export.input_tokens as u64;
+        self.output_tokens += export.output_tokens as u64;
+        self.cache_creation_tokens += export.cache_creation_tokens as u64;
+        self.cache_read_tokens += export.cache_read_tokens as u64;
+        self.total_cost += MicroDollars::from_dollars(export.cost);
+        *self.models.entry(export.model).or_default() += 1;
+
+        let timestamp = entry.timestamp.to_rfc3339();
+        if self.first_timestamp.as_ref().map_or(true, |first| timestamp < *first) {
+            self.first_timestamp = Some(timestamp.clone());
+        }
+        if self.last_timestamp.as_ref().map_or(true, |last| timestamp > *last) {
+            self.last_timestamp = Some(timestamp);
+        }
+    }
+}
+
+/// Parse `path` line by line and collect its breakdown
+pub fn inspect_file(path: &Path) -> Result<FileInspection> {
+    let parser = FileParser::new();
+    let file = io_throttle::global()
+        .open(path)
+        .with_context(|| format!("Failed to open {}", path.display()))?;
+    let mut inspection = FileInspection {
+        file: path.to_path_buf(),
+        ..Default::default()
+    };
+    let mut seen_keys = HashSet::new();
+
+    for line in BoundedLines::from_config(BufReader::new(file)) {
+        let (line_number, line) = line?;
+        inspection.lines += 1;
+
+        let text = match line {
+            Line::Text(text) => text,
+            Line::Oversized(len) => {
+                inspection.error(
+                    line_number,
+                    format!("{} bytes, over processing.max_line_length_kb", len),
+                );
+                continue;
+            }
+            Line::Invalid => {
+                inspection.error(line_number, "invalid UTF-8".to_string());
+                continue;
+            }
+        };
+
+        if text.trim().is_empty() {
+            inspection.blank_lines += 1;
+            continue;
+        }
+        if let Err(e) = serde_json::from_str::<serde_json::Value>(&text) {
+            inspection.error(line_number, format!("invalid JSON: {}", e));
+            continue;
+        }
+        let Some(entry) = parser.parse_line(&text) else {
+            inspection.non_usage_lines += 1;
+            continue;
+        };
+        let entry = match ProcessedEntry::new(entry, &parser, line_number) {
+            Ok(entry) => entry,
+            Err(e) => {
+                inspection.error(line_number, format!("invalid timestamp: {}", e));
+                continue;
+            }
+        };
+        if !entry.has_usage() {
+            inspection.non_usage_lines += 1;
+            continue;
+        }
+
+        if let Some(key) = parser.create_unique_hash(&entry.entry) {
+            inspection.with_dedup_key += 1;
+            if !seen_keys.insert(key) {
+                inspection.duplicate_keys += 1;
+            }
+        }
+        inspection.add_entry(&entry);
+    }
+
+    Ok(inspection)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn line(id: &str, timestamp: &str, cost: f64) -> String {
+        format!(
+            r#"{{"timestamp":"{}","message":{{"id":"{}","model":"claude-3-5-sonnet-20241022","usage":{{"input_tokens":10,"output_tokens":5}}}},"requestId":"req_{}","costUSD":{}}}"#,
+            timestamp, id, id, cost
+        )
+    }
+
+    #[test]
+    fn test_inspect_file_breakdown() {
+        let temp = TempDir::new().unwrap();
+        let path = temp.path().join("session.jsonl");
+        let lines = [
+            line("a", "2025-01-10T10:00:00Z", 1.0),
+            String::new(),
+            "{not json".to_string(),
+            line("b", "2025-01-11T10:00:00Z", 2.0),
+            line("a", "2025-01-10T10:00:00Z", 1.0),
+        ];
+        std::fs::write(&path, lines.join("\n")).unwrap();
+
+        let inspection = inspect_file(&path).unwrap();
+        assert_eq!(inspection.lines, 5);
+        assert_eq!(inspection.blank_lines, 1);
+        assert_eq!(inspection.entries, 3);
+        assert_eq!(inspection.errors.len(), 1);
+        assert_eq!(inspection.errors[0].line, 3);
+        assert!(inspection.errors[0].reason.starts_with("invalid JSON"));
+        assert_eq!(inspection.input_tokens, 30);
+        assert_eq!(inspection.total_cost.to_dollars(), 4.0);
+        assert_eq!(inspection.models.get("claude-3-5-sonnet-20241022"), Some(&3));
+        assert_eq!(inspection.with_dedup_key, 3);
+        assert_eq!(inspection.duplicate_keys, 1);
+        assert!(inspection.first_timestamp.unwrap().starts_with("2025-01-10"));
+        assert!(inspection.last_timestamp.unwrap().starts_with("2025-01-11"));
+    }
+
+    #[test]
+    fn test_inspect_missing_file_is_an_error() {
+        assert!(inspect_file(Path::new("/nonexistent/session.jsonl")).is_err());
+    }
+}
@@ -0,0 +1,437 @@
+//! Disk IO throttling
+//!
+//! Full scans over a large Claude history can saturate the disk on shared
+//! build servers. [`IoThrottle`] caps how many files are open for reading at
+//! once and optionally limits read throughput, so cron-driven analyses can
+//! run alongside other workloads. Both limits are configured under
+//! `[processing]` (`max_open_files`, `max_read_mb_per_sec`); 0 disables a limit.
+//!
+//! Readers that stream a file should open it through [`IoThrottle::open`],
+//! which charges every read against the byte budget. Callers that hand a path
+//! to a library which opens the file itself use [`IoThrottle::acquire_file`]
+//! instead, which charges the whole file size up front. Permits are
+//! re-entrant per thread: a thread that already holds one shares its slot
+//! instead of waiting, so opening a file while holding a permit cannot
+//! deadlock however low the cap is.
+//!
+//! When an open fails because the process ran out of file descriptors (a low
+//! `ulimit -n`), [`IoThrottle::open`] lowers the open-file cap to the files
+//! it already has open, warns with a suggested `ulimit -n`, and retries once
+//! one of them closes, so parallel parsing slows down instead of failing.
+//! The configured cap is tried again [`LIMIT_RECOVERY`] later, as the
+//! descriptors may have been held by something short-lived.
+
+use crate::config::get_config;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{self, Read};
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Condvar, Mutex, MutexGuard, OnceLock};
+use std::thread::{self, ThreadId};
+use std::time::{Duration, Instant};
+use tracing::{debug, warn};
+
+/// How long an open-file cap lowered for lack of file descriptors holds
+/// before the configured cap is tried again
+pub const LIMIT_RECOVERY: Duration = Duration::from_secs(30);
+
+static IO_THROTTLE: OnceLock<IoThrottle> = OnceLock::new();
+
+/// Get the process-wide throttle, initialized from configuration on first use
+pub fn global() -> &'static IoThrottle {
+    IO_THROTTLE.get_or_init(IoThrottle::from_config)
+}
+
+/// Limits concurrent open files and read throughput
+pub struct IoThrottle {
+    /// The `max_open_files` setting; 0 = unlimited
+    configured_max_open_files: usize,
+    /// Lowered when the process runs out of file descriptors; 0 = unlimited
+    max_open_files: AtomicUsize,
+    limit_recovery: Duration,
+    bytes_per_sec: f64,
+    open_files: Mutex<OpenFiles>,
+    file_closed: Condvar,
+    budget: Mutex<ByteBudget>,
+}
+
+/// File slots in use, and the permits each thread holding one has taken
+#[derive(Default)]
+struct OpenFiles {
+    count: usize,
+    holders: HashMap<ThreadId, usize>,
+    /// When `max_open_files` was last lowered
+    lowered_at: Option<Instant>,
+}
+
+struct ByteBudget {
+    available: f64,
+    last_refill: Instant,
+}
+
+impl IoThrottle {
+    pub fn new(max_open_files: usize, max_read_mb_per_sec: f64) -> Self {
+        let bytes_per_sec = max_read_mb_per_sec.max(0.0) * 1_000_000.0;
+        Self {
+            configured_max_open_files: max_open_files,
+            max_open_files: AtomicUsize::new(max_open_files),
+            limit_recovery: LIMIT_RECOVERY,
+            bytes_per_sec,
+            open_files: Mutex::new(OpenFiles::default()),
+            file_closed: Condvar::new(),
+            budget: Mutex::new(ByteBudget {
+                available: bytes_per_sec,
+                last_refill: Instant::now(),
+            }),
+        }
+    }
+
+    /// Create a throttle using the `[processing]` limits
+    pub fn from_config() -> Self {
+        let config = get_config();
+        Self::new(
+            config.processing.max_open_files,
+            config.processing.max_read_mb_per_sec,
+        )
+    }
+
+    /// Block until a file slot is free and reserve it
+    ///
+    /// A thread that already holds a permit gets another for the same slot
+    /// without waiting; the slot is freed when its last permit is dropped.
+    pub fn acquire(&self) -> FilePermit<'_> {
+        let thread = thread::current().id();
+        let mut open_files = self.lock_open_files();
+        if let Some(permits) = open_files.holders.get_mut(&thread) {
+            *permits += 1;
+            return FilePermit {
+                throttle: self,
+                thread,
+                shared: true,
+            };
+        }
+        loop {
+            self.recover_limit(&mut open_files);
+            let max_open_files = self.max_open_files.load(Ordering::Relaxed);
+            if max_open_files == 0 || open_files.count < max_open_files {
+                break;
+            }
+            // Time out now and then, so a lowered cap recovers even when no
+            // file closes
+            open_files = self
+                .file_closed
+                .wait_timeout(open_files, self.limit_recovery)
+                .unwrap_or_else(|e| e.into_inner())
+                .0;
+        }
+        open_files.count += 1;
+        open_files.holders.insert(thread, 1);
+        FilePermit {
+            throttle: self,
+            thread,
+            shared: false,
+        }
+    }
+
+    /// Reserve a file slot and charge the whole file size against the budget
+    pub fn acquire_file(&self, path: &Path) -> FilePermit<'_> {
+        let permit = self.acquire();
+        if let Ok(metadata) = std::fs::metadata(path) {
+            self.consume(metadata.len());
+        }
+        permit
+    }
+
+    /// Open a file for reading, with every read charged against the budget
+    ///
+    /// Out of file descriptors, the open waits for another file opened
+    /// through the throttle to close and is retried.
+    pub fn open(&self, path: &Path) -> io::Result<ThrottledFile<'_>> {
+        loop {
+            let permit = self.acquire();
+            match File::open(path) {
+                Ok(file) => return Ok(ThrottledFile { file, permit }),
+                Err(e) if is_fd_exhausted(&e) && self.lower_limit() => {
+                    // A shared slot stays reserved, so acquiring again would
+                    // not wait for the other files to close
+                    let shared = permit.shared;
+                    drop(permit);
+                    if shared {
+                        self.wait_for_close();
+                    }
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    /// Cap open files at those open besides the caller's, after an open
+    /// failed for lack of file descriptors
+    ///
+    /// Returns false when no other file is open, so waiting cannot help.
+    fn lower_limit(&self) -> bool {
+        let mut open_files = self.lock_open_files();
+        let others = open_files.count.saturating_sub(1);
+        if others == 0 {
+            return false;
+        }
+        let limit = self.max_open_files.load(Ordering::Relaxed);
+        if limit == 0 || others < limit {
+            self.max_open_files.store(others, Ordering::Relaxed);
+            open_files.lowered_at = Some(Instant::now());
+            warn!(
+                max_open_files = others,
+                "Too many open files: now reading at most {} files at once. Raise the limit with `ulimit -n {}` to read faster",
+                others,
+                suggested_ulimit()
+            );
+        }
+        true
+    }
+
+    /// Restore the configured cap once `limit_recovery` has passed since it
+    /// was lowered
+    fn recover_limit(&self, open_files: &mut OpenFiles) {
+        if open_files
+            .lowered_at
+            .is_some_and(|lowered_at| lowered_at.elapsed() >= self.limit_recovery)
+        {
+            open_files.lowered_at = None;
+            self.max_open_files
+                .store(self.configured_max_open_files, Ordering::Relaxed);
+            debug!(
+                max_open_files = self.configured_max_open_files,
+                "Trying the configured open-file limit again"
+            );
+        }
+    }
+
+    /// Wait a little for another file opened through the throttle to close
+    fn wait_for_close(&self) {
+        let open_files = self.lock_open_files();
+        let _ = self
+            .file_closed
+            .wait_timeout(open_files, Duration::from_millis(100));
+    }
+
+    /// Account for `bytes` read, sleeping if the throughput budget is exhausted
+    ///
+    /// The budget refills continuously and allows bursts of up to one second's
+    /// worth of reads.
+    pub fn consume(&self, bytes: u64) {
+        if self.bytes_per_sec <= 0.0 || bytes == 0 {
+            return;
+        }
+
+        let wait = {
+            let mut budget = self.budget.lock().unwrap_or_else(|e| e.into_inner());
+            let now = Instant::now();
+            let elapsed = now.duration_since(budget.last_refill).as_secs_f64();
+            budget.available =
+                (budget.available + elapsed * self.bytes_per_sec).min(self.bytes_per_sec);
+            budget.last_refill = now;
+            budget.available -= bytes as f64;
+
+            if budget.available < 0.0 {
+                Duration::from_secs_f64(-budget.available / self.bytes_per_sec)
+            } else {
+                Duration::ZERO
+            }
+        };
+
+        if !wait.is_zero() {
+            std::thread::sleep(wait);
+        }
+    }
+
+    /// Number of files currently open through this throttle
+    #[allow(dead_code)]
+    pub fn open_files(&self) -> usize {
+        self.lock_open_files().count
+    }
+
+    fn lock_open_files(&self) -> MutexGuard<'_, OpenFiles> {
+        self.open_files.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Drop one of `thread`'s permits, freeing its slot with the last one
+    fn release(&self, thread: ThreadId) {
+        let mut open_files = self.lock_open_files();
+        match open_files.holders.get_mut(&thread) {
+            Some(permits) if *permits > 1 => *permits -= 1,
+            _ => {
+                open_files.holders.remove(&thread);
+                open_files.count = open_files.count.saturating_sub(1);
+                // Shared permits wait for a close alongside acquirers
+                self.file_closed.notify_all();
+            }
+        }
+    }
+}
+
+/// Whether `e` means the process or system is out of file descriptors
+/// (`EMFILE`/`ENFILE`, or `ERROR_TOO_MANY_OPEN_FILES` on Windows)
+pub(crate) fn is_fd_exhausted(e: &io::Error) -> bool {
+    let codes: &[i32] = if cfg!(windows) { &[4] } else { &[23, 24] };
+    e.raw_os_error().is_some_and(|code| codes.contains(&code))
+}
+
+/// A `ulimit -n` worth suggesting: twice the current soft limit where it can
+/// be read, and at least 4096
+pub(crate) fn suggested_ulimit() -> u64 {
+    let current = std::fs::read_to_string("/proc/self/limits")
+        .ok()
+        .and_then(|limits| {
+            let line = limits
+                .lines()
+                .find(|line| line.starts_with("Max open files"))?;
+            line.split_whitespace().nth(3)?.parse::<u64>().ok()
+        });
+    current.map_or(4096, |current| (current * 2).max(4096))
+}
+
+/// A reserved file slot, released when dropped
+pub struct FilePermit<'a> {
+    throttle: &'a IoThrottle,
+    /// Thread the permit was acquired on, which holds the slot
+    thread: ThreadId,
+    /// Whether the thread already held the slot when the permit was acquired
+    shared: bool,
+}
+
+impl Drop for FilePermit<'_> {
+    fn drop(&mut self) {
+        self.throttle.release(self.thread);
+    }
+}
+
+/// A file whose reads count against the throttle's byte budget
+pub struct ThrottledFile<'a> {
+    file: File,
+    permit: FilePermit<'a>,
+}
+
+impl Read for ThrottledFile<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.file.read(buf)?;
+        self.permit.throttle.consume(n as u64);
+        Ok(n)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    /// Acquire a permit on a thread of its own, so it takes its own slot
+    fn acquire_elsewhere(throttle: &IoThrottle) -> FilePermit<'_> {
+        thread::scope(|scope| scope.spawn(|| throttle.acquire()).join().unwrap())
+    }
+
+    #[test]
+    fn test_permits_are_released_on_drop() {
+        let throttle = IoThrottle::new(2, 0.0);
+        let first = throttle.acquire();
+        let second = acquire_elsewhere(&throttle);
+        assert_eq!(throttle.open_files(), 2);
+
+        drop(first);
+        assert_eq!(throttle.open_files(), 1);
+        drop(second);
+        assert_eq!(throttle.open_files(), 0);
+    }
+
+    #[test]
+    fn test_nested_permits_share_the_thread_slot() {
+        let throttle = IoThrottle::new(1, 0.0);
+        let file = tempfile::NamedTempFile::new().unwrap();
+        let outer = throttle.acquire_file(file.path());
+
+        // Would wait for the outer permit forever if it took a slot of its own
+        let mut inner = throttle.open(file.path()).unwrap();
+        inner.read_to_end(&mut Vec::new()).unwrap();
+        assert_eq!(throttle.open_files(), 1);
+
+        drop(outer);
+        assert_eq!(throttle.open_files(), 1);
+        drop(inner);
+        assert_eq!(throttle.open_files(), 0);
+    }
+
+    #[test]
+    fn test_acquire_blocks_at_limit() {
+        let throttle = Arc::new(IoThrottle::new(1, 0.0));
+        let permit = throttle.acquire();
+
+        let waiter = {
+            let throttle = Arc::clone(&throttle);
+            std::thread::spawn(move || {
+                let _permit = throttle.acquire();
+            })
+        };
+
+        std::thread::sleep(Duration::from_millis(50));
+        assert!(!waiter.is_finished());
+
+        drop(permit);
+        waiter.join().unwrap();
+        assert_eq!(throttle.open_files(), 0);
+    }
+
+    #[test]
+    fn test_limit_lowers_to_open_files_when_descriptors_run_out() {
+        let throttle = IoThrottle::new(0, 0.0);
+        let held: Vec<_> = (0..3).map(|_| acquire_elsewhere(&throttle)).collect();
+        let failed = throttle.acquire();
+        assert!(throttle.lower_limit());
+        assert_eq!(throttle.max_open_files.load(Ordering::Relaxed), 3);
+        drop(failed);
+        drop(held);
+
+        // With nothing else open there is nothing to wait for
+        let only = throttle.acquire();
+        assert!(!throttle.lower_limit());
+        drop(only);
+
+        if cfg!(unix) {
+            assert!(is_fd_exhausted(&io::Error::from_raw_os_error(24)));
+            assert!(!is_fd_exhausted(&io::Error::from_raw_os_error(2)));
+        }
+    }
+
+    #[test]
+    fn test_lowered_limit_recovers() {
+        let mut throttle = IoThrottle::new(2, 0.0);
+        throttle.limit_recovery = Duration::from_millis(50);
+        throttle.max_open_files.store(1, Ordering::Relaxed);
+        throttle.lock_open_files().lowered_at = Some(Instant::now());
+        let held = acquire_elsewhere(&throttle);
+
+        // No file closes, but the wait times out and restores the cap of 2
+        let start = Instant::now();
+        let permit = throttle.acquire();
+        assert!(start.elapsed() >= Duration::from_millis(40));
+        assert_eq!(throttle.max_open_files.load(Ordering::Relaxed), 2);
+        assert_eq!(throttle.open_files(), 2);
+        drop((held, permit));
+    }
+
+    #[test]
+    fn test_consume_sleeps_when_budget_exhausted() {
+        // 1 MB/s with a 1 MB burst: reading 1.1 MB must wait ~100ms
+        let throttle = IoThrottle::new(0, 1.0);
+        let start = Instant::now();
+        throttle.consume(1_100_000);
+        assert!(start.elapsed() >= Duration::from_millis(90));
+    }
+
+    #[test]
+    fn test_unlimited_throttle_does_not_sleep() {
+        let throttle = IoThrottle::new(0, 0.0);
+        let start = Instant::now();
+        throttle.consume(u64::MAX);
+        assert!(start.elapsed() < Duration::from_millis(50));
+    }
+}
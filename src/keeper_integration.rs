@@ -3,7 +3,9 @@
 //! This module provides the bridge between claude-usage's existing
 //! data models and claude-keeper's FlexObject/SchemaAdapter system.
 
-use crate::models::{MessageData, SessionBlock, UsageData, UsageEntry};
+use crate::io_throttle::{self, IoThrottle};
+use crate::models::{MessageData, SecondaryUsage, SessionBlock, UsageData, UsageEntry};
+use crate::sanitize::{self, SanitizedUsage};
 use anyhow::Result;
 use claude_keeper::claude::{create_claude_adapter, ClaudeMessage};
 use claude_keeper::core::{FlexObject, JsonlParser, SchemaAdapter};
@@ -71,12 +73,24 @@ impl KeeperIntegration {
 
     /// Parse JSONL file using claude-keeper streaming parser
     pub fn parse_jsonl_file(&self, file_path: &Path) -> Result<Vec<UsageEntry>> {
+        self.parse_jsonl_file_throttled(file_path, io_throttle::global())
+    }
+
+    /// Parse a JSONL file, holding a file slot of `throttle` while it is read
+    fn parse_jsonl_file_throttled(
+        &self,
+        file_path: &Path,
+        throttle: &IoThrottle,
+    ) -> Result<Vec<UsageEntry>> {
         debug!(
             file = %file_path.display(),
             "Parsing JSONL file with claude-keeper streaming parser"
         );
 
-        // Use claude-keeper's streaming parser - this handles memory efficiently
+        // Use claude-keeper's streaming parser - this handles memory efficiently.
+        // The parser opens the file itself, so charge the whole file up front.
+        // The permit is re-entrant, so callers may already hold one.
+        let _permit = throttle.acquire_file(file_path);
         let parse_result = self.parser.parse_file(file_path)?;
         
         let mut entries = Vec::new();
@@ -304,27 +318,16 @@ impl KeeperIntegration {
             .unwrap_or("claude-3-5-sonnet-20241022")
             .to_string();
 
-        // Extract usage data if present
-        let usage = message
+        // Extract usage data if present, applying the [quality] rules
+        let usage = match message
             .message_usage(&self.adapter)
-            .map(|usage_val| UsageData {
-                input_tokens: usage_val
-                    .get("input_tokens")
-                    .and_then(|v| v.as_u64())
-                    .unwrap_or(0) as u32,
-                output_tokens: usage_val
-                    .get("output_tokens")
-                    .and_then(|v| v.as_u64())
-                    .unwrap_or(0) as u32,
-                cache_creation_input_tokens: usage_val
-                    .get("cache_creation_input_tokens")
-                    .and_then(|v| v.as_u64())
-                    .unwrap_or(0) as u32,
-                cache_read_input_tokens: usage_val
-                    .get("cache_read_input_tokens")
-                    .and_then(|v| v.as_u64())
-                    .unwrap_or(0) as u32,
-            });
+            .map(|usage_val| sanitize::sanitize_usage(&usage_val))
+        {
+            Some(SanitizedUsage::Usage(usage)) => Some(usage),
+            Some(SanitizedUsage::Skip) => return None,
+            Some(SanitizedUsage::Empty) | None => None,
+        };
+        let secondary_usage = Self::secondary_usage(&message_content);
 
         // Extract cost if present using schema adapter
         let cost_usd = self
@@ -338,11 +341,38 @@ impl KeeperIntegration {
                 id: message_id,
                 model,
                 usage,
+                secondary_usage,
             },
             cost_usd,
             request_id,
         })
     }
+
+    /// Extract usage reported for a secondary (draft/speculative) model
+    /// Accepts a `secondary_usage` or `draft_usage` block carrying its own `model`
+    pub fn secondary_usage(message_content: &serde_json::Value) -> Option<SecondaryUsage> {
+        let block = message_content
+            .get("secondary_usage")
+            .or_else(|| message_content.get("draft_usage"))?;
+        let model = block.get("model").and_then(|v| v.as_str())?.to_string();
+
+        Some(SecondaryUsage {
+            model,
+            usage: usage_from_value(block),
+        })
+    }
+}
+
+/// Read token counts from a usage block, treating missing fields as zero
+fn usage_from_value(usage_val: &serde_json::Value) -> UsageData {
+    let tokens = |field: &str| usage_val.get(field).and_then(|v| v.as_u64()).unwrap_or(0) as u32;
+
+    UsageData {
+        input_tokens: tokens("input_tokens"),
+        output_tokens: tokens("output_tokens"),
+        cache_creation_input_tokens: tokens("cache_creation_input_tokens"),
+        cache_read_input_tokens: tokens("cache_read_input_tokens"),
+    }
 }
 
 #[cfg(test)]
@@ -389,6 +419,31 @@ mod tests {
         assert_eq!(entries.len(), 2); // Should parse valid lines despite errors
     }
 
+    #[test]
+    fn test_parse_while_holding_the_only_file_slot() {
+        let mut temp_file = NamedTempFile::new().unwrap();
+        writeln!(temp_file, r#"{{"timestamp":"2025-01-15T10:30:00Z","message":{{"id":"msg_1","model":"claude-3-5-sonnet-20241022"}},"requestId":"req_1"}}"#).unwrap();
+        temp_file.flush().unwrap();
+        let path = temp_file.path().to_path_buf();
+
+        // A deadlock would hang this thread, so wait for it with a timeout
+        let (done, finished) = std::sync::mpsc::channel();
+        std::thread::spawn(move || {
+            let throttle = IoThrottle::new(1, 0.0);
+            let _permit = throttle.acquire();
+            let entries = KeeperIntegration::new()
+                .parse_jsonl_file_throttled(&path, &throttle)
+                .map(|entries| entries.len());
+            let _ = done.send((entries.ok(), throttle.open_files()));
+        });
+
+        let (entries, open_files) = finished
+            .recv_timeout(std::time::Duration::from_secs(10))
+            .expect("parsing while holding a permit deadlocked");
+        assert_eq!(entries, Some(1));
+        assert_eq!(open_files, 1);
+    }
+
     #[test]  
     fn debug_claude_keeper_parsing() {
         // Set up debug logging for this test
@@ -452,6 +507,20 @@ mod tests {
         assert!(result.is_none());
     }
 
+    #[test]
+    fn test_parse_secondary_usage() {
+        let integration = KeeperIntegration::new();
+
+        let line = r#"{"timestamp":"2024-01-15T10:30:00Z","message":{"id":"test","model":"claude-opus-4-20250514","usage":{"input_tokens":100,"output_tokens":50},"draft_usage":{"model":"claude-3-5-haiku-20241022","input_tokens":20,"output_tokens":80}},"requestId":"req_123"}"#;
+        let entry = integration.parse_single_line(line).unwrap();
+
+        let secondary = entry.message.secondary_usage.unwrap();
+        assert_eq!(secondary.model, "claude-3-5-haiku-20241022");
+        assert_eq!(secondary.usage.input_tokens, 20);
+        assert_eq!(secondary.usage.output_tokens, 80);
+        assert_eq!(secondary.usage.cache_read_input_tokens, 0);
+    }
+
 
     #[test]
     fn test_parse_session_blocks() {
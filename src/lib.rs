@@ -22,9 +22,10 @@
 //! - [`models`] - Core data structures for usage entries, sessions, and aggregated reports
 //! - [`parser`] - File discovery and JSONL parsing with streaming support
 //! - [`analyzer`] - Main analysis engine that orchestrates parsing and aggregation
-//! - [`dedup`] - Deduplication engine for handling overlapping usage data
+//! - [`dedup`] - Report options ([`dedup::ProcessOptions`]) and grouping
 //! - [`display`] - Terminal UI and live display components for real-time monitoring
 //! - [`reports`] - Output formatting for various report types
+//! - [`request`] - Typed report requests for [`ClaudeUsageAnalyzer::query`]
 //! - [`pricing`] - Cost calculation and pricing data management
 //! - [`config`] - Configuration management with environment variable support
 //! - [`logging`] - Structured logging with JSON and pretty-print formats
@@ -36,21 +37,16 @@
 //! API for all analysis operations:
 //!
 //! ```rust
-//! use claude_usage::{ClaudeUsageAnalyzer, dedup::ProcessOptions};
+//! use claude_usage::prelude::*;
 //!
 //! # async fn example() -> anyhow::Result<()> {
 //! let analyzer = ClaudeUsageAnalyzer::new();
-//! let options = ProcessOptions {
-//!     command: "daily".to_string(),
-//!     json_output: false,
-//!     limit: Some(30),
-//!     since_date: None,
-//!     until_date: None,
-//!     snapshot: false,
-//!     exclude_vms: false,
-//! };
-//!
-//! let sessions = analyzer.aggregate_data("daily", options).await?;
+//! let request = AnalyzerRequest::new(ReportKind::Daily).limit(30);
+//!
+//! let sessions = analyzer.sessions(request.clone()).await?;
+//! if let Report::Daily(days) = analyzer.query(request).await? {
+//!     println!("{} sessions over {} days", sessions.len(), days.len());
+//! }
 //! # Ok(())
 //! # }
 //! ```
@@ -61,21 +57,87 @@
 //! - [`SessionData`] - Aggregated session information
 //! - [`SessionOutput`] - Serializable session data for reports
 //! - [`dedup::ProcessOptions`] - Configuration for analysis operations
+//! - [`request::AnalyzerRequest`] - A typed report request, answered as a
+//!   [`request::Report`]
+//! - [`Error`] - Failures callers can match on
+//!
+//! [`prelude`] re-exports these along with the report types, so
+//! `use claude_usage::prelude::*;` is enough for most tools.
+//!
+//! ## Stability
+//!
+//! From 1.0 the crate follows semantic versioning for its documented API:
+//! every public item that appears in the generated documentation. A minor
+//! release may add modules, functions, enum variants marked
+//! `#[non_exhaustive]` and fields of `#[non_exhaustive]` structs such as
+//! [`dedup::ProcessOptions`], which is why options are built with
+//! [`dedup::ProcessOptions::builder`] (or [`dedup::ProcessOptions::new`] and
+//! its `with_*` methods). Anything else needs a major release.
+//!
+//! Not covered, and free to change in any release:
+//!
+//! - the [`commands`], [`web`] and [`self_metrics`] modules, which hold the
+//!   command-line interface and are public only for the `claude-usage`
+//!   binary;
+//! - the exact text of terminal reports (JSON and CSV output are covered);
+//! - the on-disk layout of caches kept by the tool;
+//! - the minimum supported Rust version, which a minor release may raise.
 
+pub mod allocations;
 pub mod analyzer;
+pub mod archive;
+pub mod batch;
+pub mod blocks;
+pub mod branches;
+pub mod commit_trailer;
 pub mod config;
+pub mod container;
+pub mod cost_audit;
 pub mod dedup;
+pub mod dedup_set;
+pub mod dedup_state;
 pub mod display;
-pub mod file_discovery;
+pub mod error;
+pub mod estimation;
+pub mod export;
+pub mod export_manifest;
+pub(crate) mod file_discovery;
+pub mod forecast;
+pub mod hooks;
+pub mod inspect;
+pub(crate) mod io_throttle;
+pub mod limits;
+pub(crate) mod line_reader;
 pub mod logging;
 pub mod memory;
 pub mod models;
+pub mod notes;
+pub mod notify;
+pub mod output_version;
+pub(crate) mod parse_cache;
 pub mod parser;
 pub mod parser_wrapper;
+pub(crate) mod pipeline;
 pub mod pricing;
+pub mod pricing_snapshot;
+pub mod project_names;
+pub mod projects;
+pub mod ranges;
+pub mod redaction;
+pub mod report_diff;
 pub mod reports;
-pub mod session_utils;
+pub mod reprocess;
+pub mod request;
+pub mod sanitize;
+#[doc(hidden)]
+pub mod self_metrics;
+pub(crate) mod session_utils;
+pub mod skipped;
+pub mod status;
 pub mod timestamp_parser;
+pub mod top_k;
+#[doc(hidden)]
+pub mod web;
 
 // Live mode modules
 pub mod live;
@@ -83,10 +145,31 @@ pub mod litellm_pricing;
 pub mod parquet;
 
 // Command modules
+#[doc(hidden)]
 pub mod commands;
 
 pub use analyzer::ClaudeUsageAnalyzer;
+pub use error::Error;
 pub use models::*;
+pub use pipeline::FailedFile;
+
+/// The types most tools need, for a single glob import
+pub mod prelude {
+    pub use crate::analyzer::ClaudeUsageAnalyzer;
+    pub use crate::dedup::{
+        CancellationToken, Command, CostMode, GroupBy, ProcessOptions, ProcessOptionsBuilder,
+        SortOrder,
+    };
+    pub use crate::error::Error;
+    pub use crate::models::{
+        DailyData, DailyUsage, DayTotals, EntryExport, InstanceType, MicroDollars, MonthlyData,
+        SessionBlock, SessionOutput, UsageEntry,
+    };
+    pub use crate::parser::JsonlProcessor;
+    pub use crate::reports::ReportDisplayManager;
+    pub use crate::request::{AnalyzerRequest, Report, ReportKind};
+    pub use crate::top_k::ApproxTopReport;
+}
 
 // Keeper integration module for schema-resilient parsing
 pub mod keeper_integration;
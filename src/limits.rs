@@ -0,0 +1,228 @@
+//! Plan Ceilings
+//!
+//! Claude subscriptions cap usage per 5-hour block (see [`crate::blocks`]).
+//! `[limits]` names the plan, or sets the ceilings directly, and `daily
+//! --json` then adds a `limits` section showing the share of the token
+//! ceiling and of the block budget each block on the report's days used,
+//! with the peak share of each day.
+//!
+//! Anthropic does not publish exact limits. The plan presets are the
+//! estimates usage monitors commonly work with, so `token_ceiling` and
+//! `budget_usd` override them when your own observations differ. Only input
+//! and output tokens count against the ceiling: cache reads and writes are
+//! far larger and weigh much less on the limits.
+
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+use crate::config::{LimitsConfig, Plan};
+use crate::models::SessionBlock;
+
+impl Plan {
+    /// Estimated token ceiling and budget per block, for the named plans
+    pub fn preset(self) -> Option<(u64, f64)> {
+        match self {
+            Plan::Pro => Some((19_000, 18.0)),
+            Plan::Max5 => Some((88_000, 35.0)),
+            Plan::Max20 => Some((220_000, 140.0)),
+            Plan::Custom => None,
+        }
+    }
+}
+
+/// The ceilings in effect, from the plan preset and explicit overrides
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PlanLimits {
+    pub plan: Plan,
+    pub token_ceiling: Option<u64>,
+    pub budget_usd: Option<f64>,
+}
+
+impl PlanLimits {
+    /// Resolve `[limits]`; `None` when nothing is configured
+    ///
+    /// Ceilings given without a plan are a custom plan.
+    pub fn from_config(config: &LimitsConfig) -> Option<Self> {
+        if config.plan.is_none() && config.token_ceiling.is_none() && config.budget_usd.is_none() {
+            return None;
+        }
+        let plan = config.plan.unwrap_or(Plan::Custom);
+        let preset = plan.preset();
+        Some(Self {
+            plan,
+            token_ceiling: config.token_ceiling.or(preset.map(|(tokens, _)| tokens)),
+            budget_usd: config.budget_usd.or(preset.map(|(_, usd)| usd)),
+        })
+    }
+
+    fn token_percent(&self, tokens: u64) -> Option<f64> {
+        self.token_ceiling
+            .map(|ceiling| percent(tokens as f64, ceiling as f64))
+    }
+
+    fn budget_percent(&self, cost_usd: f64) -> Option<f64> {
+        self.budget_usd.map(|budget| percent(cost_usd, budget))
+    }
+}
+
+/// The `limits` section of the daily report
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LimitsReport {
+    pub plan: Plan,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub token_ceiling: Option<u64>,
+    #[serde(rename = "budgetUSD", skip_serializing_if = "Option::is_none")]
+    pub budget_usd: Option<f64>,
+    pub blocks: Vec<BlockLimits>,
+    pub days: Vec<DayLimits>,
+}
+
+/// Usage of one block against the ceilings
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BlockLimits {
+    pub start_time: String,
+    pub end_time: String,
+    pub is_active: bool,
+    /// Input and output tokens
+    pub tokens: u64,
+    #[serde(rename = "costUSD")]
+    pub cost_usd: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub token_percent: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub budget_percent: Option<f64>,
+}
+
+/// The blocks started on one day, and the most any of them used
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DayLimits {
+    pub date: String,
+    pub blocks: u32,
+    pub tokens: u64,
+    #[serde(rename = "costUSD")]
+    pub cost_usd: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub peak_token_percent: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub peak_budget_percent: Option<f64>,
+}
+
+/// Measure the blocks started on `dates` (UTC, like the daily rows)
+/// against `limits`
+pub fn limits_report(limits: &PlanLimits, blocks: &[SessionBlock], dates: &[&str]) -> LimitsReport {
+    let mut report_blocks = Vec::new();
+    let mut days: BTreeMap<&str, DayLimits> = BTreeMap::new();
+    for block in blocks {
+        let Some(date) = block
+            .start_time
+            .get(..10)
+            .filter(|date| dates.contains(date))
+        else {
+            continue;
+        };
+        let tokens = u64::from(block.token_counts.input_tokens)
+            + u64::from(block.token_counts.output_tokens);
+        let block_limits = BlockLimits {
+            start_time: block.start_time.clone(),
+            end_time: block.end_time.clone(),
+            is_active: block.is_active,
+            tokens,
+            cost_usd: block.cost_usd,
+            token_percent: limits.token_percent(tokens),
+            budget_percent: limits.budget_percent(block.cost_usd),
+        };
+
+        let day = days.entry(date).or_insert_with(|| DayLimits {
+            date: date.to_string(),
+            blocks: 0,
+            tokens: 0,
+            cost_usd: 0.0,
+            peak_token_percent: None,
+            peak_budget_percent: None,
+        });
+        day.blocks += 1;
+        day.tokens += tokens;
+        day.cost_usd += block.cost_usd;
+        day.peak_token_percent = max(day.peak_token_percent, block_limits.token_percent);
+        day.peak_budget_percent = max(day.peak_budget_percent, block_limits.budget_percent);
+        report_blocks.push(block_limits);
+    }
+
+    LimitsReport {
+        plan: limits.plan,
+        token_ceiling: limits.token_ceiling,
+        budget_usd: limits.budget_usd,
+        blocks: report_blocks,
+        days: days.into_values().rev().collect(),
+    }
+}
+
+/// `part` as a percentage of `whole`, to one decimal
+fn percent(part: f64, whole: f64) -> f64 {
+    (part / whole * 1000.0).round() / 10.0
+}
+
+fn max(a: Option<f64>, b: Option<f64>) -> Option<f64> {
+    match (a, b) {
+        (Some(a), Some(b)) => Some(a.max(b)),
+        (a, b) => a.or(b),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::TokenCounts;
+
+    fn block(start_time: &str, output_tokens: u32, cost_usd: f64) -> SessionBlock {
+        SessionBlock {
+            start_time: start_time.to_string(),
+            end_time: String::new(),
+            actual_end_time: None,
+            is_active: false,
+            entries: 1,
+            models: Vec::new(),
+            token_counts: TokenCounts {
+                input_tokens: 1_000,
+                output_tokens,
+                cache_creation_input_tokens: 0,
+                cache_read_input_tokens: 50_000,
+            },
+            cost_usd,
+        }
+    }
+
+    #[test]
+    fn test_blocks_measured_against_plan_ceilings() {
+        let config = LimitsConfig {
+            plan: Some(Plan::Max5),
+            token_ceiling: Some(100_000),
+            budget_usd: None,
+        };
+        let limits = PlanLimits::from_config(&config).unwrap();
+        assert_eq!(limits.token_ceiling, Some(100_000));
+        assert_eq!(limits.budget_usd, Some(35.0));
+        assert_eq!(PlanLimits::from_config(&LimitsConfig::default()), None);
+
+        let blocks = [
+            block("2025-01-09T20:00:00Z", 9_000, 7.0),
+            block("2025-01-10T01:00:00Z", 24_000, 3.5),
+            block("2025-01-10T09:00:00Z", 49_000, 14.0),
+        ];
+        let report = limits_report(&limits, &blocks, &["2025-01-10"]);
+
+        assert_eq!(report.blocks.len(), 2);
+        assert_eq!(report.blocks[0].tokens, 25_000);
+        assert_eq!(report.blocks[0].token_percent, Some(25.0));
+        assert_eq!(report.blocks[0].budget_percent, Some(10.0));
+
+        assert_eq!(report.days.len(), 1);
+        let day = &report.days[0];
+        assert_eq!((day.blocks, day.tokens, day.cost_usd), (2, 75_000, 17.5));
+        assert_eq!(day.peak_token_percent, Some(50.0));
+        assert_eq!(day.peak_budget_percent, Some(40.0));
+    }
+}
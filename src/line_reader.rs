@@ -0,0 +1,380 @@
+//! Bounded line reading for JSONL files
+//!
+//! A corrupted log with megabyte-long lines or embedded binary data should not
+//! force unbounded allocations. [`BoundedLines`] reads lines up to a
+//! configurable maximum length (`processing.max_line_length_kb`); longer lines
+//! are consumed without being buffered and reported as [`Line::Oversized`], and
+//! lines that are not valid UTF-8 are reported as [`Line::Invalid`].
+//!
+//! Readers on the main parsing path opt in with
+//! [`BoundedLines::with_quality_tracking`], so their lines are counted in a
+//! process-wide [`ParseQuality`] report available from [`quality_report`].
+//! Pre-scans (such as reading a file's date range) are left untracked so that
+//! a skipped line is not reported twice.
+//!
+//! The parser adds lines that were read but are not valid JSON
+//! ([`record_parse_error`]) and per-file failure counts
+//! ([`record_file_failures`]), which back the `--max-error-rate` budget
+//! checked by [`check_error_budget`]. The backup reader adds the log lines
+//! claude-keeper backed up, one row each, counting rows with usage but no
+//! readable timestamp as failed ([`record_backup_rows`]), so the budget also
+//! covers the installations reports read from backups.
+
+use crate::config::get_config;
+use serde::Serialize;
+use std::fmt;
+use std::io::{self, BufRead};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+
+/// Global parse quality counters
+static LINES_READ: AtomicU64 = AtomicU64::new(0);
+static OVERSIZED_LINES: AtomicU64 = AtomicU64::new(0);
+static INVALID_LINES: AtomicU64 = AtomicU64::new(0);
+static PARSE_ERRORS: AtomicU64 = AtomicU64::new(0);
+static UNDATED_LINES: AtomicU64 = AtomicU64::new(0);
+
+/// Files with at least one failed line
+static FILE_FAILURES: Mutex<Vec<FileFailures>> = Mutex::new(Vec::new());
+
+/// Files listed when the error budget is exceeded
+const WORST_FILES_LISTED: usize = 5;
+
+/// Counts of lines read and skipped across all JSONL parsing
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+pub struct ParseQuality {
+    #[serde(rename = "linesRead")]
+    pub lines_read: u64,
+    #[serde(rename = "oversizedLines")]
+    pub oversized_lines: u64,
+    #[serde(rename = "invalidLines")]
+    pub invalid_lines: u64,
+    #[serde(rename = "parseErrors")]
+    pub parse_errors: u64,
+    /// Backed-up lines with usage but no readable timestamp
+    #[serde(rename = "undatedLines")]
+    pub undated_lines: u64,
+}
+
+impl ParseQuality {
+    /// Number of lines skipped before JSON parsing was attempted
+    pub fn skipped_lines(&self) -> u64 {
+        self.oversized_lines + self.invalid_lines
+    }
+
+    /// Number of lines that could not be used: skipped, not valid JSON or
+    /// without a readable timestamp
+    pub fn failed_lines(&self) -> u64 {
+        self.skipped_lines() + self.parse_errors + self.undated_lines
+    }
+
+    /// Failed lines as a percentage of lines read
+    pub fn error_rate(&self) -> f64 {
+        if self.lines_read == 0 {
+            0.0
+        } else {
+            self.failed_lines() as f64 / self.lines_read as f64 * 100.0
+        }
+    }
+}
+
+/// Snapshot of the parse quality counters
+pub fn quality_report() -> ParseQuality {
+    ParseQuality {
+        lines_read: LINES_READ.load(Ordering::Relaxed),
+        oversized_lines: OVERSIZED_LINES.load(Ordering::Relaxed),
+        invalid_lines: INVALID_LINES.load(Ordering::Relaxed),
+        parse_errors: PARSE_ERRORS.load(Ordering::Relaxed),
+        undated_lines: UNDATED_LINES.load(Ordering::Relaxed),
+    }
+}
+
+/// Count a tracked line that was read but is not valid JSON
+pub fn record_parse_error() {
+    PARSE_ERRORS.fetch_add(1, Ordering::Relaxed);
+}
+
+/// Count the `rows` lines read from a claude-keeper backup file, `undated`
+/// of which have usage but no readable timestamp
+pub fn record_backup_rows(path: &Path, rows: u64, undated: u64) {
+    LINES_READ.fetch_add(rows, Ordering::Relaxed);
+    UNDATED_LINES.fetch_add(undated, Ordering::Relaxed);
+    record_file_failures(path, rows, undated);
+}
+
+/// Failed line counts for one file
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FileFailures {
+    pub path: PathBuf,
+    pub lines: u64,
+    pub failed: u64,
+}
+
+/// Remember how many of a parsed file's lines failed, if any
+pub fn record_file_failures(path: &Path, lines: u64, failed: u64) {
+    if failed == 0 {
+        return;
+    }
+    FILE_FAILURES
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .push(FileFailures {
+            path: path.to_path_buf(),
+            lines,
+            failed,
+        });
+}
+
+/// Files with the most failed lines, worst first
+pub fn worst_files(limit: usize) -> Vec<FileFailures> {
+    let mut files = FILE_FAILURES
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .clone();
+    files.sort_by(|a, b| b.failed.cmp(&a.failed).then_with(|| a.path.cmp(&b.path)));
+    files.truncate(limit);
+    files
+}
+
+/// The run's parse error rate went over `--max-error-rate`
+#[derive(Debug)]
+pub struct ErrorBudgetExceeded {
+    pub quality: ParseQuality,
+    pub max_error_rate: f64,
+    pub worst_files: Vec<FileFailures>,
+}
+
+impl fmt::Display for ErrorBudgetExceeded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Parse error rate {:.2}% exceeds the budget of {}% ({} of {} lines failed)",
+            self.quality.error_rate(),
+            self.max_error_rate,
+            self.quality.failed_lines(),
+            self.quality.lines_read
+        )?;
+        for file in &self.worst_files {
+            write!(
+                f,
+                "\n  {}: {} of {} lines failed",
+                file.path.display(),
+                file.failed,
+                file.lines
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ErrorBudgetExceeded {}
+
+/// Fail when more than `max_error_rate` percent of tracked lines failed
+pub fn check_error_budget(max_error_rate: f64) -> Result<(), ErrorBudgetExceeded> {
+    let quality = quality_report();
+    if quality.error_rate() <= max_error_rate {
+        return Ok(());
+    }
+    Err(ErrorBudgetExceeded {
+        quality,
+        max_error_rate,
+        worst_files: worst_files(WORST_FILES_LISTED),
+    })
+}
+
+/// A single line read from a JSONL file
+#[derive(Debug, PartialEq)]
+pub enum Line {
+    /// Line content without the trailing newline
+    Text(String),
+    /// Line longer than the limit; carries its length in bytes
+    Oversized(usize),
+    /// Line that is not valid UTF-8
+    Invalid,
+}
+
+/// Iterator over `(line_number, Line)` pairs with a maximum line length
+///
+/// Line numbers are 1-based.
+pub struct BoundedLines<R> {
+    reader: R,
+    max_len: usize,
+    buf: Vec<u8>,
+    line_number: usize,
+    track_quality: bool,
+}
+
+impl<R: BufRead> BoundedLines<R> {
+    pub fn new(reader: R, max_len: usize) -> Self {
+        Self {
+            reader,
+            max_len,
+            buf: Vec::new(),
+            line_number: 0,
+            track_quality: false,
+        }
+    }
+
+    /// Create a reader using the configured maximum line length
+    pub fn from_config(reader: R) -> Self {
+        Self::new(reader, get_config().processing.max_line_length_kb * 1024)
+    }
+
+    /// Count lines read by this reader in the global quality report
+    pub fn with_quality_tracking(mut self) -> Self {
+        self.track_quality = true;
+        self
+    }
+
+    fn record(&self, counter: &AtomicU64) {
+        if self.track_quality {
+            counter.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    /// Read one line into `buf`, returning its full length or None at EOF
+    fn read_line(&mut self) -> io::Result<Option<usize>> {
+        self.buf.clear();
+        let mut total = 0;
+        let mut read_any = false;
+
+        loop {
+            let available = match self.reader.fill_buf() {
+                Ok(available) => available,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e),
+            };
+            if available.is_empty() {
+                return Ok(read_any.then_some(total));
+            }
+            read_any = true;
+
+            let newline = available.iter().position(|&b| b == b'\n');
+            let content_len = newline.unwrap_or(available.len());
+
+            // Stop buffering once the line is known to be too long
+            if total + content_len <= self.max_len {
+                self.buf.extend_from_slice(&available[..content_len]);
+            }
+            total += content_len;
+
+            let consumed = newline.map_or(content_len, |pos| pos + 1);
+            self.reader.consume(consumed);
+
+            if newline.is_some() {
+                return Ok(Some(total));
+            }
+        }
+    }
+}
+
+impl<R: BufRead> Iterator for BoundedLines<R> {
+    type Item = io::Result<(usize, Line)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let total = match self.read_line() {
+            Ok(Some(total)) => total,
+            Ok(None) => return None,
+            Err(e) => return Some(Err(e)),
+        };
+
+        self.line_number += 1;
+        self.record(&LINES_READ);
+
+        if total > self.max_len {
+            self.record(&OVERSIZED_LINES);
+            return Some(Ok((self.line_number, Line::Oversized(total))));
+        }
+
+        if self.buf.last() == Some(&b'\r') {
+            self.buf.pop();
+        }
+
+        let line = match String::from_utf8(std::mem::take(&mut self.buf)) {
+            Ok(text) => Line::Text(text),
+            Err(_) => {
+                self.record(&INVALID_LINES);
+                Line::Invalid
+            }
+        };
+        Some(Ok((self.line_number, line)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{BufReader, Cursor};
+
+    fn read_all(input: &[u8], max_len: usize) -> Vec<(usize, Line)> {
+        // Small buffer so long lines span several fill_buf calls
+        let reader = BufReader::with_capacity(4, Cursor::new(input.to_vec()));
+        BoundedLines::new(reader, max_len)
+            .map(|line| line.unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_reads_lines_with_numbers() {
+        let lines = read_all(b"one\r\ntwo\n\nthree", 16);
+        assert_eq!(
+            lines,
+            vec![
+                (1, Line::Text("one".to_string())),
+                (2, Line::Text("two".to_string())),
+                (3, Line::Text(String::new())),
+                (4, Line::Text("three".to_string())),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_skips_oversized_lines_without_losing_sync() {
+        let long = "x".repeat(100);
+        let input = format!("short\n{}\nafter\n", long);
+        let lines = read_all(input.as_bytes(), 10);
+        assert_eq!(
+            lines,
+            vec![
+                (1, Line::Text("short".to_string())),
+                (2, Line::Oversized(100)),
+                (3, Line::Text("after".to_string())),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_reports_binary_junk_as_invalid() {
+        let lines = read_all(b"ok\n\xff\xfe\x00junk\nok\n", 64);
+        assert_eq!(lines[1], (2, Line::Invalid));
+        assert_eq!(lines[2], (3, Line::Text("ok".to_string())));
+    }
+
+    #[test]
+    fn test_error_rate_and_budget_message() {
+        let quality = ParseQuality {
+            lines_read: 200,
+            oversized_lines: 1,
+            invalid_lines: 1,
+            parse_errors: 6,
+            undated_lines: 2,
+        };
+        assert_eq!(quality.failed_lines(), 10);
+        assert_eq!(quality.error_rate(), 5.0);
+        assert_eq!(ParseQuality::default().error_rate(), 0.0);
+
+        let exceeded = ErrorBudgetExceeded {
+            quality,
+            max_error_rate: 1.0,
+            worst_files: vec![FileFailures {
+                path: PathBuf::from("/logs/a.jsonl"),
+                lines: 50,
+                failed: 8,
+            }],
+        };
+        let message = exceeded.to_string();
+        assert!(message.starts_with("Parse error rate 5.00% exceeds the budget of 1%"));
+        assert!(message.contains("/logs/a.jsonl: 8 of 50 lines failed"));
+    }
+}
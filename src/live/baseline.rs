@@ -3,12 +3,14 @@
 //! This module handles loading summary information from existing parquet backup
 //! files created by claude-keeper. This provides the initial state for live mode.
 
-use anyhow::{Context, Result};
+use anyhow::Result;
 use std::time::{Duration, SystemTime};
 use tracing::{debug, info, warn};
 
 use crate::config::get_config;
+use crate::error::KeeperNotFound;
 use crate::live::BaselineSummary;
+use crate::models::MicroDollars;
 use crate::parquet::reader::ParquetSummaryReader;
 
 /// Load baseline summary from parquet backup files
@@ -47,6 +49,28 @@ pub fn load_baseline_summary() -> Result<BaselineSummary> {
     Ok(summary)
 }
 
+/// Cost recorded for `day` in the parquet backups, for the daily budget
+pub fn load_day_cost(day: chrono::NaiveDate) -> Result<f64> {
+    let backup_dir = dirs::home_dir()
+        .unwrap_or_else(|| std::path::PathBuf::from("."))
+        .join(".claude-backup");
+
+    if !backup_dir.exists() {
+        return Ok(0.0);
+    }
+
+    let date = day.format("%Y-%m-%d").to_string();
+    let sessions = ParquetSummaryReader::new(backup_dir)?.read_detailed_sessions()?;
+    let cost = sessions
+        .iter()
+        .filter_map(|session| session.daily_usage.get(&date))
+        .map(|usage| usage.cost)
+        .sum::<MicroDollars>();
+
+    debug!(%date, cost = cost.to_dollars(), "Loaded today's cost for the daily budget");
+    Ok(cost.to_dollars())
+}
+
 /// Trigger a backup via claude-keeper subprocess and reload baseline
 pub async fn refresh_baseline() -> Result<BaselineSummary> {
     info!("Refreshing baseline data via claude-keeper backup");
@@ -67,7 +91,13 @@ pub async fn refresh_baseline() -> Result<BaselineSummary> {
         .args(&["backup", claude_dir.to_str().unwrap(), "--out", backup_dir.to_str().unwrap(), "--quiet"])
         .output()
         .await
-        .context("Failed to execute claude-keeper backup")?;
+        .map_err(|e| {
+            KeeperNotFound::check(
+                "claude-keeper",
+                e,
+                "Failed to execute claude-keeper backup".to_string(),
+            )
+        })?;
     
     if !output.status.success() {
         let stderr = String::from_utf8_lossy(&output.stderr);
@@ -174,7 +204,13 @@ pub async fn get_sql_analytics() -> Result<serde_json::Value> {
             .current_dir(&backup_dir)
             .output()
             .await
-            .context(format!("Failed to execute SQL query: {}", query_name))?;
+            .map_err(|e| {
+                KeeperNotFound::check(
+                    "claude-keeper",
+                    e,
+                    format!("Failed to execute SQL query: {}", query_name),
+                )
+            })?;
         
         if output.status.success() {
             let stdout = String::from_utf8_lossy(&output.stdout);
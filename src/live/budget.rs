@@ -0,0 +1,215 @@
+//! Daily budget alerts for live mode
+//!
+//! With `[budget] daily_usd` (or `CLAUDE_USAGE_BUDGET_DAILY`) set, the
+//! orchestrator keeps a [`BudgetTracker`] of today's cost and publishes its
+//! [`BudgetStatus`] on a [`BudgetBoard`], so the display can highlight the
+//! totals once the warning or critical percentage is crossed. Crossing a
+//! threshold can also ring the terminal bell or show a desktop notification.
+
+use chrono::NaiveDate;
+use std::io::Write;
+use std::process::{Command, Stdio};
+use std::sync::{Arc, Mutex};
+use tracing::debug;
+
+use crate::config::BudgetConfig;
+
+/// How much of the daily budget has been spent
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum BudgetLevel {
+    /// Below the warning percentage
+    Normal,
+    /// At or above the warning percentage
+    Warning,
+    /// At or above the critical percentage
+    Critical,
+}
+
+impl BudgetLevel {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            BudgetLevel::Normal => "normal",
+            BudgetLevel::Warning => "warning",
+            BudgetLevel::Critical => "critical",
+        }
+    }
+}
+
+/// Today's spend against the daily budget
+#[derive(Debug, Clone, PartialEq)]
+pub struct BudgetStatus {
+    /// Local day the spend belongs to
+    pub day: NaiveDate,
+    /// Cost spent on `day`, in dollars
+    pub spent: f64,
+    /// Daily budget, in dollars
+    pub limit: f64,
+    pub level: BudgetLevel,
+}
+
+impl BudgetStatus {
+    /// Share of the budget spent, in percent
+    pub fn percent(&self) -> f64 {
+        self.spent / self.limit * 100.0
+    }
+}
+
+/// Running total of today's cost checked against the configured thresholds
+#[derive(Debug, Clone)]
+pub struct BudgetTracker {
+    limit: f64,
+    warning_pct: f64,
+    critical_pct: f64,
+    day: NaiveDate,
+    spent: f64,
+    level: BudgetLevel,
+}
+
+impl BudgetTracker {
+    /// Start tracking `day` with `spent` already used, or `None` without a daily budget
+    pub fn new(config: &BudgetConfig, day: NaiveDate, spent: f64) -> Option<Self> {
+        let mut tracker = Self {
+            limit: config.daily_usd?,
+            warning_pct: config.warning_pct,
+            critical_pct: config.critical_pct,
+            day,
+            spent,
+            level: BudgetLevel::Normal,
+        };
+        tracker.level = tracker.level_for(spent);
+        Some(tracker)
+    }
+
+    /// Add `cost` spent on `day`, starting over when the day changes
+    ///
+    /// Returns the new level when a threshold was crossed upwards.
+    pub fn record(&mut self, day: NaiveDate, cost: f64) -> Option<BudgetLevel> {
+        if day != self.day {
+            self.day = day;
+            self.spent = 0.0;
+            self.level = BudgetLevel::Normal;
+        }
+        self.spent += cost;
+
+        let level = self.level_for(self.spent);
+        let crossed = level > self.level;
+        self.level = level;
+        crossed.then_some(level)
+    }
+
+    /// Current spend and level
+    pub fn status(&self) -> BudgetStatus {
+        BudgetStatus {
+            day: self.day,
+            spent: self.spent,
+            limit: self.limit,
+            level: self.level,
+        }
+    }
+
+    fn level_for(&self, spent: f64) -> BudgetLevel {
+        let percent = spent / self.limit * 100.0;
+        if percent >= self.critical_pct {
+            BudgetLevel::Critical
+        } else if percent >= self.warning_pct {
+            BudgetLevel::Warning
+        } else {
+            BudgetLevel::Normal
+        }
+    }
+}
+
+/// Latest budget status, shared between the orchestrator and the display
+#[derive(Debug, Clone, Default)]
+pub struct BudgetBoard {
+    status: Arc<Mutex<Option<BudgetStatus>>>,
+}
+
+impl BudgetBoard {
+    /// Publish the current status
+    pub fn set(&self, status: BudgetStatus) {
+        *self.status.lock().unwrap_or_else(|e| e.into_inner()) = Some(status);
+    }
+
+    /// Current status, or `None` without a daily budget
+    #[allow(dead_code)]
+    pub fn snapshot(&self) -> Option<BudgetStatus> {
+        self.status
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+}
+
+/// Ring the bell and/or show a desktop notification for a crossed threshold
+pub fn alert(config: &BudgetConfig, status: &BudgetStatus) {
+    let message = format!(
+        "Spent ${:.2} of the ${:.2} daily budget ({:.0}%)",
+        status.spent,
+        status.limit,
+        status.percent()
+    );
+
+    if config.bell {
+        let mut stdout = std::io::stdout();
+        let _ = stdout.write_all(b"\x07").and_then(|_| stdout.flush());
+    }
+
+    if config.notify {
+        let title = match status.level {
+            BudgetLevel::Critical => "Claude usage budget exceeded",
+            _ => "Claude usage budget warning",
+        };
+        if let Err(e) = notification_command(title, &message)
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+        {
+            debug!(error = %e, "Failed to show budget notification");
+        }
+    }
+}
+
+/// Desktop notification command for the current platform
+fn notification_command(title: &str, message: &str) -> Command {
+    if cfg!(target_os = "macos") {
+        let mut command = Command::new("osascript");
+        command.arg("-e").arg(format!(
+            "display notification {:?} with title {:?}",
+            message, title
+        ));
+        command
+    } else {
+        let mut command = Command::new("notify-send");
+        command.arg(title).arg(message);
+        command
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tracker_reports_each_crossed_threshold_once() {
+        let config = BudgetConfig {
+            daily_usd: Some(10.0),
+            ..BudgetConfig::default()
+        };
+        let day = NaiveDate::from_ymd_opt(2025, 3, 1).unwrap();
+        assert!(BudgetTracker::new(&BudgetConfig::default(), day, 0.0).is_none());
+
+        let mut tracker = BudgetTracker::new(&config, day, 7.0).unwrap();
+        assert_eq!(tracker.record(day, 0.5), None);
+        assert_eq!(tracker.record(day, 0.5), Some(BudgetLevel::Warning));
+        assert_eq!(tracker.record(day, 1.0), None);
+        assert_eq!(tracker.record(day, 1.0), Some(BudgetLevel::Critical));
+        assert_eq!(tracker.status().percent(), 100.0);
+
+        // A new day starts from zero
+        let next = day.succ_opt().unwrap();
+        assert_eq!(tracker.record(next, 1.0), None);
+        assert_eq!(tracker.status().level, BudgetLevel::Normal);
+        assert_eq!(tracker.status().spent, 1.0);
+    }
+}
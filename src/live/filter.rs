@@ -0,0 +1,80 @@
+//! Project filtering for live mode
+//!
+//! `live --project <glob>` restricts the feed to matching projects. A pattern
+//! is matched against both the full project path reported by claude-keeper
+//! and its final component, so `--project api` and `--project '*/work/*'` both
+//! work. Updates without a known project never match a non-empty filter.
+
+use anyhow::{Context, Result};
+use glob::Pattern;
+
+/// Set of project globs; an empty filter matches everything
+#[derive(Debug, Clone, Default)]
+pub struct ProjectFilter {
+    patterns: Vec<Pattern>,
+}
+
+impl ProjectFilter {
+    /// Compile the given glob patterns
+    pub fn new(patterns: &[String]) -> Result<Self> {
+        let patterns = patterns
+            .iter()
+            .map(|p| Pattern::new(p).with_context(|| format!("Invalid project pattern: {}", p)))
+            .collect::<Result<_>>()?;
+        Ok(Self { patterns })
+    }
+
+    /// Whether no patterns were given
+    pub fn is_empty(&self) -> bool {
+        self.patterns.is_empty()
+    }
+
+    /// Whether an update for `project` should reach the display
+    pub fn matches(&self, project: Option<&str>) -> bool {
+        if self.is_empty() {
+            return true;
+        }
+        let Some(project) = project else {
+            return false;
+        };
+        let name = project
+            .trim_end_matches('/')
+            .rsplit('/')
+            .next()
+            .unwrap_or(project);
+
+        self.patterns
+            .iter()
+            .any(|pattern| pattern.matches(project) || pattern.matches(name))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn filter(patterns: &[&str]) -> ProjectFilter {
+        ProjectFilter::new(&patterns.iter().map(|p| p.to_string()).collect::<Vec<_>>()).unwrap()
+    }
+
+    #[test]
+    fn test_empty_filter_matches_everything() {
+        let filter = filter(&[]);
+        assert!(filter.matches(Some("/home/me/api")));
+        assert!(filter.matches(None));
+    }
+
+    #[test]
+    fn test_matches_name_or_full_path() {
+        let filter = filter(&["api*", "/home/me/work/*"]);
+        assert!(filter.matches(Some("/home/me/src/api-server")));
+        assert!(filter.matches(Some("/home/me/work/site/")));
+        assert!(!filter.matches(Some("/home/me/src/frontend")));
+        assert!(!filter.matches(None));
+    }
+
+    #[test]
+    fn test_invalid_pattern_is_an_error() {
+        assert!(ProjectFilter::new(&["[".to_string()]).is_err());
+    }
+}
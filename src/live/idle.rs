@@ -0,0 +1,81 @@
+//! Idle detection for live mode
+//!
+//! With `live.idle_timeout_secs` set, an instance running the local
+//! claude-keeper is paused once no update arrived for that long. While paused,
+//! an [`ActivityProbe`] polls the modification times of the session logs under
+//! the Claude home, and the keeper is started again as soon as one changes.
+//! Instances with their own command (e.g. over ssh) are never paused, since
+//! their logs are not visible locally.
+
+use glob::glob;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+/// How often the session logs are checked while paused
+pub const IDLE_POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Detects changes to the session logs since the probe was created
+#[derive(Debug, Clone)]
+pub struct ActivityProbe {
+    projects_dir: PathBuf,
+    last_modified: Option<SystemTime>,
+}
+
+impl ActivityProbe {
+    /// Probe the session logs in `claude_home`, taking their current state as quiet
+    pub fn new(claude_home: &Path) -> Self {
+        let projects_dir = claude_home.join("projects");
+        let last_modified = latest_modification(&projects_dir);
+        Self {
+            projects_dir,
+            last_modified,
+        }
+    }
+
+    /// Whether a session log was written or created since the probe was created
+    pub fn has_activity(&self) -> bool {
+        latest_modification(&self.projects_dir) > self.last_modified
+    }
+}
+
+/// Latest modification time of the session logs in `projects_dir`
+fn latest_modification(projects_dir: &Path) -> Option<SystemTime> {
+    let pattern = projects_dir.join("*").join("*.jsonl");
+    glob(&pattern.to_string_lossy())
+        .ok()?
+        .flatten()
+        .filter_map(|path| path.metadata().and_then(|m| m.modified()).ok())
+        .max()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::io::Write;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_probe_detects_new_and_appended_logs() {
+        let home = TempDir::new().unwrap();
+        let project = home.path().join("projects").join("-work-app");
+        fs::create_dir_all(&project).unwrap();
+
+        let probe = ActivityProbe::new(home.path());
+        assert!(!probe.has_activity());
+
+        fs::write(project.join("session.jsonl"), "{}\n").unwrap();
+        assert!(probe.has_activity());
+
+        let probe = ActivityProbe::new(home.path());
+        assert!(!probe.has_activity());
+        // Stay clear of coarse filesystem timestamps
+        std::thread::sleep(Duration::from_millis(50));
+        let mut file = fs::File::options()
+            .append(true)
+            .open(project.join("session.jsonl"))
+            .unwrap();
+        writeln!(file, "{{}}").unwrap();
+        assert!(probe.has_activity());
+    }
+}
@@ -0,0 +1,293 @@
+//! Multiple claude-keeper instances
+//!
+//! Live mode can watch several claude-keeper processes at once, typically one
+//! inside each VM (`[[live.instances]]` in the config). Every instance runs its
+//! own [`KeeperWatcher`] on a task that restarts it on errors; entries from all
+//! of them are merged into one stream labelled with the instance name, and the
+//! state of each watcher is published on a [`HealthBoard`] for the display.
+//! The local keeper is paused while idle, see [`crate::live::idle`]. Without
+//! claude-keeper installed, the local instance tails the session logs with a
+//! [`JsonlTailer`] instead.
+
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime};
+use tokio::sync::mpsc;
+use tracing::{info, warn};
+
+use crate::config::{self, KeeperInstance};
+use crate::error::KeeperNotFound;
+use crate::live::idle::{ActivityProbe, IDLE_POLL_INTERVAL};
+use crate::live::watcher::{JsonlTailer, KeeperEntry, KeeperWatcher, TAIL_POLL_INTERVAL};
+use crate::live::LiveConfig;
+
+/// Name of the implicit instance when none are configured
+pub const LOCAL_INSTANCE: &str = "local";
+
+/// Connection state of one keeper instance
+#[derive(Debug, Clone, PartialEq)]
+pub enum InstanceHealth {
+    /// Started, no entry received yet
+    Connecting,
+    /// Streaming; `last_entry` is when the latest entry arrived
+    Connected { last_entry: SystemTime },
+    /// Lost the process and restarting it
+    Reconnecting { attempt: u32, max_attempts: u32 },
+    /// Paused after no entries arrived for the idle timeout; resumes on new activity
+    Idle { since: SystemTime },
+    /// The process ended normally
+    Finished,
+    /// The process could not be (re)started
+    Failed(String),
+}
+
+impl InstanceHealth {
+    /// Symbol and short label for the display
+    #[allow(dead_code)]
+    pub fn indicator(&self) -> (&'static str, String) {
+        match self {
+            Self::Connecting => ("◌", "connecting".to_string()),
+            Self::Connected { .. } => ("●", "connected".to_string()),
+            Self::Reconnecting {
+                attempt,
+                max_attempts,
+            } => ("◌", format!("reconnecting ({}/{})", attempt, max_attempts)),
+            Self::Idle { since } => {
+                let since = chrono::DateTime::<chrono::Local>::from(*since);
+                ("☾", format!("idle since {}", since.format("%H:%M")))
+            }
+            Self::Finished => ("○", "finished".to_string()),
+            Self::Failed(_) => ("✗", "failed".to_string()),
+        }
+    }
+
+    /// Whether the instance is connected or expected to be soon
+    #[allow(dead_code)]
+    pub fn is_healthy(&self) -> bool {
+        matches!(
+            self,
+            Self::Connecting | Self::Connected { .. } | Self::Idle { .. }
+        )
+    }
+}
+
+/// Latest health of every instance, shared between watcher tasks and the display
+#[derive(Debug, Clone, Default)]
+pub struct HealthBoard {
+    instances: Arc<Mutex<BTreeMap<String, InstanceHealth>>>,
+}
+
+impl HealthBoard {
+    /// Record the health of `instance`
+    pub fn set(&self, instance: &str, health: InstanceHealth) {
+        self.instances
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(instance.to_string(), health);
+    }
+
+    /// Current health of every instance, by name
+    pub fn snapshot(&self) -> BTreeMap<String, InstanceHealth> {
+        self.instances
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+}
+
+/// Instances to watch: the configured ones, or the local keeper
+pub fn keeper_instances(config: &config::LiveConfig) -> Vec<KeeperInstance> {
+    if config.instances.is_empty() {
+        vec![KeeperInstance {
+            name: LOCAL_INSTANCE.to_string(),
+            command: Vec::new(),
+        }]
+    } else {
+        config.instances.clone()
+    }
+}
+
+/// Watch one instance until it finishes or runs out of restarts
+///
+/// Entries are sent as `(instance name, entry)`; the task ends early when the
+/// receiver is dropped. With an idle timeout, the local keeper is paused when
+/// no entry arrives for that long and resumed once the session logs change.
+/// When claude-keeper is missing, the local instance tails the session logs.
+pub async fn watch_instance(
+    config: LiveConfig,
+    instance: KeeperInstance,
+    tx: mpsc::Sender<(String, KeeperEntry)>,
+    health: HealthBoard,
+) {
+    let name = instance.name.clone();
+    health.set(&name, InstanceHealth::Connecting);
+
+    let mut watcher = match KeeperWatcher::new(&config, &instance) {
+        Ok(watcher) => watcher,
+        Err(e) if instance.command.is_empty() && e.is::<KeeperNotFound>() => {
+            info!(
+                instance = %name,
+                "claude-keeper not found, tailing the session logs instead"
+            );
+            tail_session_logs(&name, tx, health).await;
+            return;
+        }
+        Err(e) => {
+            warn!(instance = %name, error = %e, "Failed to start claude-keeper watcher");
+            health.set(&name, InstanceHealth::Failed(format!("{:#}", e)));
+            return;
+        }
+    };
+
+    // Only the local keeper's activity is visible in the local session logs
+    let idle_timeout = (config.idle_timeout_secs > 0 && instance.command.is_empty())
+        .then(|| Duration::from_secs(config.idle_timeout_secs));
+
+    loop {
+        let next = match idle_timeout {
+            Some(timeout) => match tokio::time::timeout(timeout, watcher.next_entry()).await {
+                Ok(next) => next,
+                Err(_) => {
+                    info!(
+                        instance = %name,
+                        idle_secs = timeout.as_secs(),
+                        "No usage updates, pausing claude-keeper"
+                    );
+                    health.set(
+                        &name,
+                        InstanceHealth::Idle {
+                            since: SystemTime::now(),
+                        },
+                    );
+                    watcher.pause().await;
+
+                    let probe = ActivityProbe::new(&config::get_config().paths.claude_home);
+                    while !probe.has_activity() {
+                        if tx.is_closed() {
+                            return;
+                        }
+                        tokio::time::sleep(IDLE_POLL_INTERVAL).await;
+                    }
+
+                    health.set(&name, InstanceHealth::Connecting);
+                    if let Err(e) = watcher.resume() {
+                        warn!(
+                            instance = %name,
+                            error = %e,
+                            "Failed to resume claude-keeper watcher"
+                        );
+                        health.set(&name, InstanceHealth::Failed(format!("{:#}", e)));
+                        return;
+                    }
+                    continue;
+                }
+            },
+            None => watcher.next_entry().await,
+        };
+
+        match next {
+            Ok(Some(entry)) => {
+                health.set(
+                    &name,
+                    InstanceHealth::Connected {
+                        last_entry: SystemTime::now(),
+                    },
+                );
+                if tx.send((name.clone(), entry)).await.is_err() {
+                    return;
+                }
+            }
+            Ok(None) => {
+                info!(instance = %name, "Claude-keeper watcher finished");
+                health.set(&name, InstanceHealth::Finished);
+                return;
+            }
+            Err(e) => {
+                warn!(instance = %name, error = %e, "Error from claude-keeper watcher");
+                if !watcher.should_restart() {
+                    health.set(&name, InstanceHealth::Failed(format!("{:#}", e)));
+                    return;
+                }
+                health.set(
+                    &name,
+                    InstanceHealth::Reconnecting {
+                        attempt: watcher.restart_count() + 1,
+                        max_attempts: config.max_restart_attempts,
+                    },
+                );
+                if let Err(e) = watcher.restart().await {
+                    health.set(&name, InstanceHealth::Failed(format!("{:#}", e)));
+                    return;
+                }
+            }
+        }
+    }
+}
+
+/// Send the entries appended to the local session logs until the receiver
+/// is dropped
+async fn tail_session_logs(
+    name: &str,
+    tx: mpsc::Sender<(String, KeeperEntry)>,
+    health: HealthBoard,
+) {
+    let mut tailer = JsonlTailer::new(&config::get_config().paths.claude_home);
+    while !tx.is_closed() {
+        for entry in tailer.poll() {
+            health.set(
+                name,
+                InstanceHealth::Connected {
+                    last_entry: SystemTime::now(),
+                },
+            );
+            if tx.send((name.to_string(), entry)).await.is_err() {
+                return;
+            }
+        }
+        tokio::time::sleep(TAIL_POLL_INTERVAL).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_local_instance_when_none_configured() {
+        let mut config = crate::config::Config::default().live;
+        assert_eq!(keeper_instances(&config)[0].name, LOCAL_INSTANCE);
+
+        config.instances = vec![
+            KeeperInstance {
+                name: "vm1".to_string(),
+                command: vec!["ssh".to_string(), "vm1".to_string()],
+            },
+            KeeperInstance {
+                name: "vm2".to_string(),
+                command: Vec::new(),
+            },
+        ];
+        let names: Vec<String> = keeper_instances(&config)
+            .into_iter()
+            .map(|instance| instance.name)
+            .collect();
+        assert_eq!(names, ["vm1", "vm2"]);
+    }
+
+    #[tokio::test]
+    async fn test_unstartable_instance_is_marked_failed() {
+        let health = HealthBoard::default();
+        let (tx, mut rx) = mpsc::channel(1);
+        let instance = KeeperInstance {
+            name: "vm1".to_string(),
+            command: vec!["/nonexistent/claude-keeper".to_string()],
+        };
+
+        watch_instance(LiveConfig::default(), instance, tx, health.clone()).await;
+
+        assert!(rx.recv().await.is_none());
+        let snapshot = health.snapshot();
+        assert!(matches!(snapshot["vm1"], InstanceHealth::Failed(_)));
+        assert!(!snapshot["vm1"].is_healthy());
+    }
+}
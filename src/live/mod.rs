@@ -10,6 +10,14 @@ use crate::models::{UsageEntry, SessionData};
 
 pub mod orchestrator;
 pub mod baseline;
+pub mod budget;
+pub mod filter;
+pub mod idle;
+pub mod instances;
+pub mod reconcile;
+pub mod sessions;
+#[cfg(feature = "live")]
+pub mod snapshot;
 pub mod watcher;
 
 /// Live mode configuration
@@ -23,6 +31,8 @@ pub struct LiveConfig {
     pub update_channel_buffer: usize,
     /// Path to claude-keeper executable
     pub claude_keeper_path: String,
+    /// Seconds without updates before the local keeper is paused (0 = never)
+    pub idle_timeout_secs: u64,
 }
 
 impl Default for LiveConfig {
@@ -32,6 +42,7 @@ impl Default for LiveConfig {
             max_restart_attempts: 3,
             update_channel_buffer: 100,
             claude_keeper_path: "claude-keeper".to_string(),
+            idle_timeout_secs: 0,
         }
     }
 }
@@ -70,8 +81,76 @@ pub struct LiveUpdate {
     /// Current session statistics
     #[allow(dead_code)]
     pub session_stats: SessionData,
+    /// Keeper instance the entry came from, when several are watched
+    #[allow(dead_code)]
+    pub instance: Option<String>,
     /// Timestamp when this update was received
     #[allow(dead_code)]
     pub timestamp: SystemTime,
 }
 
+
+/// Summary of a live session, printed as JSON on exit with `live --json`
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LiveExitSummary {
+    /// Total cost including baseline
+    pub total_cost: f64,
+    /// Total tokens including baseline
+    pub total_tokens: u64,
+    /// Cost of live updates since launch or the last reset
+    pub since_launch_cost: f64,
+    /// Tokens of live updates since launch or the last reset
+    pub since_launch_tokens: u64,
+    /// Cost per minute over the last hour, oldest minute first
+    pub burn_rate: Vec<BurnRatePoint>,
+    /// Live updates of the current local day
+    pub today: LiveDayTotals,
+    /// Comparison with the daily report, with `live --reconcile-on-exit`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reconciliation: Option<reconcile::Reconciliation>,
+}
+
+/// Live updates dated one local day, kept apart from the since-launch
+/// counters so resetting those does not affect them
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LiveDayTotals {
+    /// Local day (YYYY-MM-DD), empty before the first update
+    pub date: String,
+    /// Recorded `costUSD` of the day's updates, in dollars
+    pub cost: f64,
+    pub tokens: u64,
+    pub entries: u64,
+    /// Updates without a recorded `costUSD`, counted as free
+    pub entries_without_cost: u64,
+}
+
+impl LiveDayTotals {
+    /// Add an update dated `date`, starting over when the day changes
+    #[allow(dead_code)]
+    pub fn record(&mut self, date: &str, cost: Option<f64>, tokens: u64) {
+        if self.date != date {
+            *self = Self {
+                date: date.to_string(),
+                ..Self::default()
+            };
+        }
+        self.cost += cost.unwrap_or(0.0);
+        self.tokens += tokens;
+        self.entries += 1;
+        if cost.is_none() {
+            self.entries_without_cost += 1;
+        }
+    }
+}
+
+/// Cost of live updates received during one minute
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BurnRatePoint {
+    /// Start of the minute (RFC 3339, UTC)
+    pub minute: String,
+    /// Cost received during the minute, in dollars
+    pub cost: f64,
+}
@@ -2,20 +2,46 @@
 //!
 //! The orchestrator coordinates all live mode operations including:
 //! - Loading baseline data from parquet files
-//! - Managing claude-keeper subprocess
+//! - Managing claude-keeper subprocesses, one per configured instance
+//! - Filtering incoming usage updates to the watched projects
 //! - Processing incoming usage updates
-//! - Maintaining session state
+//! - Maintaining session state, evicting sessions idle past
+//!   `[live] session_ttl_minutes` (see [`crate::live::sessions`])
+//! - Tracking today's cost against the daily budget
+//! - Sending webhook notifications when today's or a session's spend crosses
+//!   a `[notify]` threshold (see [`crate::notify`])
+//! - Running the day rollover, new session and cost threshold hooks
+//! - Skipping entries already counted, in this or an earlier run, when
+//!   `dedup.persist` is set (see [`crate::dedup_state`])
 
-use anyhow::{Context, Result};
-use std::collections::HashMap;
-use std::time::SystemTime;
+use anyhow::Result;
+use chrono::NaiveDate;
+use serde_json::json;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime};
 use tokio::sync::mpsc;
 use tracing::{debug, error, info, warn};
 
 use crate::live::{BaselineSummary, LiveConfig, LiveUpdate};
-use crate::live::baseline::{load_baseline_summary, refresh_baseline, should_refresh_baseline};
-use crate::live::watcher::KeeperWatcher;
-use crate::models::{SessionData, UsageEntry};
+use crate::live::baseline::{
+    load_baseline_summary, load_day_cost, refresh_baseline, should_refresh_baseline,
+};
+use crate::live::budget::{self, BudgetBoard, BudgetTracker};
+use crate::live::filter::ProjectFilter;
+use crate::config::get_config;
+use crate::dedup_state::DedupState;
+use crate::hooks::{self, HookEvent};
+use crate::live::instances::{keeper_instances, watch_instance, HealthBoard, InstanceHealth};
+use crate::live::sessions::LiveSessions;
+use crate::live::watcher::KeeperEntry;
+use crate::models::{MicroDollars, SessionData, UsageEntry};
+use crate::notify::{self, SpendWatch};
+use crate::session_utils::SessionUtils;
+use crate::timestamp_parser::TimestampParser;
+
+/// How often live mode checks whether the local day changed and saves its
+/// dedup state
+const DAY_CHECK_INTERVAL: Duration = Duration::from_secs(60);
 
 /// Format token count with appropriate units (K, M)
 fn format_tokens(tokens: u64) -> String {
@@ -32,17 +58,33 @@ fn format_tokens(tokens: u64) -> String {
 pub struct LiveOrchestrator {
     config: LiveConfig,
     baseline: BaselineSummary,
-    sessions: HashMap<String, SessionData>,
+    sessions: LiveSessions,
     no_baseline: bool,
+    project_filter: ProjectFilter,
+    health: HealthBoard,
+    budget: Option<BudgetTracker>,
+    budget_board: BudgetBoard,
+    spend_watch: Option<SpendWatch>,
+    /// Local day the latest entry or check fell on
+    day: NaiveDate,
+    /// Keys counted in this and earlier runs, with `dedup.persist`
+    dedup_state: Option<Arc<Mutex<DedupState>>>,
 }
 
 impl LiveOrchestrator {
     /// Create a new live orchestrator
-    pub async fn new(no_baseline: bool) -> Result<Self> {
-        let config = LiveConfig::default(); // Use default for now
+    ///
+    /// Baseline totals cover every project, so no baseline is loaded while a
+    /// project filter is active.
+    pub async fn new(no_baseline: bool, project_filter: ProjectFilter) -> Result<Self> {
+        let config = LiveConfig {
+            idle_timeout_secs: get_config().live.idle_timeout_secs,
+            ..LiveConfig::default() // Use defaults for the rest for now
+        };
+        let no_baseline = no_baseline || !project_filter.is_empty();
         
         let baseline = if no_baseline {
-            info!("Skipping baseline loading (--no-baseline specified)");
+            info!("Skipping baseline loading (--no-baseline or --project specified)");
             BaselineSummary::default()
         } else {
             // Check if we need to refresh baseline
@@ -80,11 +122,39 @@ impl LiveOrchestrator {
             }
         };
 
+        // Today's spend so far counts towards the daily budget and thresholds
+        let budget_config = &get_config().budget;
+        let notify_config = &get_config().notify;
+        let today = chrono::Local::now().date_naive();
+        let spent = if no_baseline
+            || (budget_config.daily_usd.is_none() && !notify::enabled(notify_config))
+        {
+            0.0
+        } else {
+            load_day_cost(today).unwrap_or_else(|e| {
+                warn!(error = %e, "Failed to load today's cost, budget starts from zero");
+                0.0
+            })
+        };
+        let budget = BudgetTracker::new(budget_config, today, spent);
+        let spend_watch = SpendWatch::new(notify_config, today, spent);
+        let budget_board = BudgetBoard::default();
+        if let Some(tracker) = &budget {
+            budget_board.set(tracker.status());
+        }
+
         Ok(Self {
             config,
             baseline,
-            sessions: HashMap::new(),
+            sessions: LiveSessions::new(get_config().live.session_ttl()),
             no_baseline,
+            project_filter,
+            health: HealthBoard::default(),
+            budget,
+            budget_board,
+            spend_watch,
+            day: today,
+            dedup_state: DedupState::from_config().map(|state| Arc::new(Mutex::new(state))),
         })
     }
 
@@ -109,62 +179,149 @@ impl LiveOrchestrator {
             "Starting live mode orchestrator"
         );
 
-        // Start claude-keeper watcher
-        println!("🔗 Connecting to claude-keeper for live updates...");
-        let mut watcher = KeeperWatcher::new(&self.config)?;
-        
+        // Start one claude-keeper watcher per instance, merged into one stream
+        let instances = keeper_instances(&get_config().live);
+        let labelled = instances.len() > 1;
+        if labelled {
+            let names: Vec<&str> = instances.iter().map(|i| i.name.as_str()).collect();
+            println!("🔗 Connecting to claude-keeper instances: {}", names.join(", "));
+        } else {
+            println!("🔗 Connecting to claude-keeper for live updates...");
+        }
+
+        let (entry_tx, mut entry_rx) = mpsc::channel(self.config.update_channel_buffer);
+        for instance in instances {
+            tokio::spawn(watch_instance(
+                self.config.clone(),
+                instance,
+                entry_tx.clone(),
+                self.health.clone(),
+            ));
+        }
+        // The channel closes once every watcher task has ended
+        drop(entry_tx);
+
         // Flag to track first successful connection
         let mut first_connection = true;
-        
-        // Main processing loop
+
+        // Main processing loop; the day is also checked while no entries arrive
+        let mut day_check = tokio::time::interval(DAY_CHECK_INTERVAL);
         loop {
-            // Get next usage entry from claude-keeper
-            match watcher.next_entry().await {
-                Ok(Some(entry)) => {
-                    // Show success message on first entry
-                    if first_connection {
-                        println!("✅ Connected! Now monitoring live Claude usage...");
-                        println!("💡 Use new Claude conversations to see real-time updates");
-                        println!();
-                        first_connection = false;
-                    }
-                    
-                    if let Err(e) = self.process_entry(entry, &tx).await {
-                        error!(error = %e, "Failed to process usage entry");
-                        // Continue processing other entries
+            let (instance, entry) = tokio::select! {
+                received = entry_rx.recv() => match received {
+                    Some(received) => received,
+                    None => break,
+                },
+                _ = day_check.tick() => {
+                    self.check_day_rollover();
+                    let evicted = self.sessions.prune(Instant::now());
+                    if evicted > 0 {
+                        debug!(evicted, tracked = self.sessions.tracked(), "Evicted idle live sessions");
                     }
-                }
-                Ok(None) => {
-                    // No more entries, keeper process finished
-                    info!("Claude-keeper watcher finished");
-                    break;
-                }
-                Err(e) => {
-                    error!(error = %e, "Error from claude-keeper watcher");
-                    
-                    // Try to restart watcher
-                    if watcher.should_restart() {
-                        println!("⚠️  Connection lost, attempting to reconnect...");
-                        warn!("Attempting to restart claude-keeper watcher");
-                        watcher = KeeperWatcher::new(&self.config)?;
-                        continue;
-                    } else {
-                        println!("❌ Connection failed permanently after multiple attempts");
-                        return Err(e).context("Claude-keeper watcher failed and cannot restart");
+                    if let Some(state) = &self.dedup_state {
+                        save_dedup_state(state);
                     }
+                    continue;
                 }
+            };
+            self.check_day_rollover();
+
+            // Show success message on first entry
+            if first_connection {
+                println!("✅ Connected! Now monitoring live Claude usage...");
+                println!("💡 Use new Claude conversations to see real-time updates");
+                println!();
+                first_connection = false;
+            }
+
+            if !self.project_filter.matches(entry.project.as_deref()) {
+                debug!(
+                    instance = %instance,
+                    request_id = %entry.entry.request_id,
+                    project = ?entry.project,
+                    "Skipping usage entry outside watched projects"
+                );
+                continue;
+            }
+
+            if !self.remember(&entry.entry) {
+                debug!(
+                    instance = %instance,
+                    request_id = %entry.entry.request_id,
+                    "Skipping usage entry already counted"
+                );
+                continue;
+            }
+
+            let instance = labelled.then_some(instance);
+            if let Err(e) = self.process_entry(instance, entry, &tx).await {
+                error!(error = %e, "Failed to process usage entry");
+                // Continue processing other entries
             }
         }
 
+        let health = self.health.snapshot();
+        if !health.is_empty()
+            && health
+                .values()
+                .all(|state| matches!(state, InstanceHealth::Failed(_)))
+        {
+            println!("❌ Connection failed permanently after multiple attempts");
+            return Err(anyhow::anyhow!(
+                "Every claude-keeper watcher failed and cannot restart"
+            ));
+        }
+
+        if let Some(state) = &self.dedup_state {
+            save_dedup_state(state);
+        }
+        info!("Claude-keeper watchers finished");
         Ok(())
     }
 
+    /// Record the entry's dedup key, returning whether it should be counted
+    ///
+    /// Without `dedup.persist`, or without a key, every entry is counted.
+    fn remember(&self, entry: &UsageEntry) -> bool {
+        let (Some(state), Some(key)) = (&self.dedup_state, SessionUtils::create_unique_hash(entry))
+        else {
+            return true;
+        };
+        state
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(&key, chrono::Utc::now())
+    }
+
+    /// Run the `day_rollover` hook when the local day has changed
+    fn check_day_rollover(&mut self) {
+        let today = chrono::Local::now().date_naive();
+        if today == self.day {
+            return;
+        }
+        info!(previous = %self.day, day = %today, "Local day changed");
+        hooks::fire(
+            HookEvent::DayRollover,
+            json!({
+                "previousDay": self.day.format("%Y-%m-%d").to_string(),
+                "day": today.format("%Y-%m-%d").to_string(),
+            }),
+        );
+        self.day = today;
+    }
+
     /// Process a single usage entry
+    ///
+    /// Sessions are tracked per instance, since different VMs are separate
+    /// sources of usage.
     async fn process_entry(
         &mut self,
-        entry: UsageEntry,
+        instance: Option<String>,
+        entry: KeeperEntry,
         tx: &mpsc::Sender<LiveUpdate>,
     ) -> Result<()> {
+        let KeeperEntry { entry, project } = entry;
+
         debug!(
             request_id = %entry.request_id,
             model = %entry.message.model,
@@ -173,14 +330,28 @@ impl LiveOrchestrator {
 
         // Extract session information from the entry
         let session_id = entry.message.id.clone();
-        
-        // For now, use a simple project path extraction
-        // In the future, this could be enhanced to use real project detection
-        let project_path = "unknown".to_string();
+        let session_key = match &instance {
+            Some(instance) => format!("{}:{}", instance, session_id),
+            None => session_id.clone(),
+        };
+
+        let project_path = project.unwrap_or_else(|| "unknown".to_string());
 
         // Update or create session data
-        let session_data = self.sessions.entry(session_id.clone())
-            .or_insert_with(|| SessionData::new(session_id.clone(), project_path));
+        if !self.sessions.contains(&session_key) {
+            hooks::fire(
+                HookEvent::NewSession,
+                json!({
+                    "sessionId": session_id,
+                    "project": project_path,
+                    "instance": instance,
+                    "model": entry.message.model,
+                }),
+            );
+        }
+        let session_data = self.sessions.touch(session_key, Instant::now(), || {
+            SessionData::new(session_id.clone(), project_path)
+        });
 
         // Update session with new usage data
         if let Some(usage) = &entry.message.usage {
@@ -190,17 +361,67 @@ impl LiveOrchestrator {
             session_data.cache_read_tokens += usage.cache_read_input_tokens;
             
             if let Some(cost) = entry.cost_usd {
-                session_data.total_cost += cost;
+                session_data.total_cost += MicroDollars::from_dollars(cost);
+                if let Some(tracker) = &mut self.budget {
+                    let crossed = tracker.record(chrono::Local::now().date_naive(), cost);
+                    let status = tracker.status();
+                    if crossed.is_some() {
+                        info!(
+                            spent = status.spent,
+                            limit = status.limit,
+                            level = ?status.level,
+                            "Daily budget threshold crossed"
+                        );
+                        budget::alert(&get_config().budget, &status);
+                        hooks::fire(
+                            HookEvent::CostThreshold,
+                            json!({
+                                "level": status.level.as_str(),
+                                "day": status.day.format("%Y-%m-%d").to_string(),
+                                "spent": status.spent,
+                                "limit": status.limit,
+                                "percent": status.percent(),
+                            }),
+                        );
+                    }
+                    self.budget_board.set(status);
+                }
+                if let Some(watch) = &mut self.spend_watch {
+                    let alerts = watch.record(
+                        chrono::Local::now().date_naive(),
+                        cost,
+                        &session_id,
+                        &session_data.project_path,
+                        session_data.total_cost.to_dollars(),
+                    );
+                    for alert in alerts {
+                        info!(scope = ?alert.scope, threshold = alert.threshold, "Spend threshold crossed");
+                        notify::spawn_send(alert);
+                    }
+                }
             }
             
             session_data.models_used.insert(entry.message.model.clone());
             session_data.last_activity = Some(entry.timestamp.clone());
+            if let Ok(at) = TimestampParser::parse(&entry.timestamp) {
+                session_data.record_entry_time(at);
+            }
+        }
+
+        // Draft/speculative model tokens are part of the same request
+        if let Some(secondary) = &entry.message.secondary_usage {
+            session_data.input_tokens += secondary.usage.input_tokens;
+            session_data.output_tokens += secondary.usage.output_tokens;
+            session_data.cache_creation_tokens += secondary.usage.cache_creation_input_tokens;
+            session_data.cache_read_tokens += secondary.usage.cache_read_input_tokens;
+            session_data.models_used.insert(secondary.model.clone());
         }
 
         // Create live update
         let update = LiveUpdate {
             entry,
             session_stats: session_data.clone(),
+            instance,
             timestamp: SystemTime::now(),
         };
 
@@ -217,15 +438,36 @@ impl LiveOrchestrator {
         self.baseline.clone()
     }
 
-    /// Get current session summary
+    /// Get the health board updated by the keeper watchers
+    pub fn health(&self) -> HealthBoard {
+        self.health.clone()
+    }
+
+    /// Get the dedup state, so it can be saved when live mode exits
+    pub fn dedup_state(&self) -> Option<Arc<Mutex<DedupState>>> {
+        self.dedup_state.clone()
+    }
+
+    /// Get the board with today's spend against the daily budget
+    pub fn budget(&self) -> BudgetBoard {
+        self.budget_board.clone()
+    }
+
+    /// Get current session summary, counting evicted sessions
     #[allow(dead_code)]
     pub fn get_session_summary(&self) -> (usize, f64, u64) {
-        let total_sessions = self.sessions.len();
-        let total_cost = self.baseline.total_cost + 
-            self.sessions.values().map(|s| s.total_cost).sum::<f64>();
-        let total_tokens = self.baseline.total_tokens +
-            self.sessions.values().map(|s| s.total_tokens() as u64).sum::<u64>();
-        
-        (total_sessions, total_cost, total_tokens)
+        let (sessions, cost, tokens) = self.sessions.totals();
+        let total_cost = self.baseline.total_cost + cost.to_dollars();
+        let total_tokens = self.baseline.total_tokens + tokens;
+
+        (sessions, total_cost, total_tokens)
     }
-}
\ No newline at end of file
+}
+
+/// Save the dedup state, logging a failure
+pub fn save_dedup_state(state: &Mutex<DedupState>) {
+    let mut state = state.lock().unwrap_or_else(|e| e.into_inner());
+    if let Err(e) = state.save(chrono::Utc::now()) {
+        warn!(error = %e, "Failed to save dedup state");
+    }
+}
@@ -0,0 +1,187 @@
+//! Reconciling live totals with the daily report
+//!
+//! With `live --reconcile-on-exit`, quitting live mode refreshes the
+//! backups, runs the daily aggregation for today and compares its cost with
+//! what live mode counted for the day: the backups' cost for today when live
+//! mode started plus the live updates received since. Any difference above a
+//! cent is reported with its likely causes, worked out from what live mode
+//! saw (updates without `costUSD`, a `--project` filter, a failed backup).
+
+use anyhow::Result;
+use chrono::NaiveDate;
+use serde::Serialize;
+
+use crate::analyzer::ClaudeUsageAnalyzer;
+use crate::dedup::{Command, ProcessOptions};
+use crate::live::LiveDayTotals;
+use crate::request::{AnalyzerRequest, ReportKind};
+
+/// Differences up to this many dollars count as a match
+pub const TOLERANCE_USD: f64 = 0.01;
+
+/// Live and daily cost of one day, and why they may differ
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Reconciliation {
+    /// Local day compared (YYYY-MM-DD)
+    pub date: String,
+    /// Today's cost in the backups when live mode started
+    pub baseline_cost: f64,
+    /// Cost of the day's live updates
+    pub live_updates_cost: f64,
+    /// `baseline_cost` plus `live_updates_cost`
+    pub live_cost: f64,
+    /// The day's cost in the daily report
+    pub daily_cost: f64,
+    /// `live_cost` minus `daily_cost`
+    pub difference: f64,
+    pub matches: bool,
+    /// Probable reasons for the difference, most likely first
+    pub likely_causes: Vec<String>,
+}
+
+/// What live mode knew about the day besides its totals
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ReconcileContext {
+    /// Live updates were limited with `--project`
+    pub project_filter: bool,
+    /// The backups were refreshed before the daily aggregation
+    pub backup_refreshed: bool,
+}
+
+/// Compare the live totals of `date` with the daily report's `daily_cost`
+pub fn reconcile(
+    date: NaiveDate,
+    baseline_cost: f64,
+    today: &LiveDayTotals,
+    daily_cost: f64,
+    context: ReconcileContext,
+) -> Reconciliation {
+    let date = date.format("%Y-%m-%d").to_string();
+    // Updates of another day (live mode ran past midnight) are not today's
+    let live_updates_cost = if today.date == date { today.cost } else { 0.0 };
+    let live_cost = baseline_cost + live_updates_cost;
+    let difference = live_cost - daily_cost;
+    let matches = difference.abs() <= TOLERANCE_USD;
+
+    let mut likely_causes = Vec::new();
+    if !matches && difference < 0.0 {
+        if today.date == date && today.entries_without_cost > 0 {
+            likely_causes.push(format!(
+                "{} live update(s) had no costUSD and counted as $0; the daily report prices them from their tokens",
+                today.entries_without_cost
+            ));
+        }
+        if context.project_filter {
+            likely_causes.push(
+                "--project limited the live updates to matching projects; the daily report covers all of them"
+                    .to_string(),
+            );
+        }
+        likely_causes.push(
+            "Usage from instances live mode did not watch, such as VMs without a [[live.instances]] entry"
+                .to_string(),
+        );
+    } else if !matches {
+        if !context.backup_refreshed {
+            likely_causes.push(
+                "The backups could not be refreshed, so the daily report is missing usage since the last backup"
+                    .to_string(),
+            );
+        }
+        likely_causes.push(
+            "The backups do not include the newest usage yet; rerun `claude-usage daily` after the next claude-keeper backup"
+                .to_string(),
+        );
+        likely_causes.push(
+            "Live mode counted repeated updates of one message that the daily report deduplicates"
+                .to_string(),
+        );
+    }
+
+    Reconciliation {
+        date,
+        baseline_cost,
+        live_updates_cost,
+        live_cost,
+        daily_cost,
+        difference,
+        matches,
+        likely_causes,
+    }
+}
+
+/// Cost of `day` in the daily report
+pub async fn daily_cost(day: NaiveDate) -> Result<f64> {
+    let start = day.and_hms_opt(0, 0, 0).map(|start| start.and_utc());
+    let end = day.and_hms_opt(23, 59, 59).map(|end| end.and_utc());
+    let mut options = ProcessOptions::new(Command::Daily.as_str());
+    options.since_date = start;
+    options.until_date = end;
+    options.json_output = true;
+
+    let date = day.format("%Y-%m-%d").to_string();
+    let sessions = ClaudeUsageAnalyzer::new()
+        .sessions(AnalyzerRequest::with_options(ReportKind::Daily, options))
+        .await?;
+    Ok(sessions
+        .iter()
+        .filter_map(|session| session.daily_usage.get(&date))
+        .map(|usage| usage.cost.to_dollars())
+        .sum())
+}
+
+/// Today's cost in the backups, captured when live mode starts
+pub fn baseline_cost(day: NaiveDate) -> f64 {
+    crate::live::baseline::load_day_cost(day).unwrap_or_else(|e| {
+        tracing::warn!(error = %e, "Failed to load today's cost for reconciliation");
+        0.0
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn day_totals(date: &str, cost: f64, without_cost: u64) -> LiveDayTotals {
+        LiveDayTotals {
+            date: date.to_string(),
+            cost,
+            tokens: 1000,
+            entries: 4,
+            entries_without_cost: without_cost,
+        }
+    }
+
+    #[test]
+    fn test_reconcile_matches_and_explains_differences() {
+        let date = NaiveDate::from_ymd_opt(2025, 3, 1).unwrap();
+        let context = ReconcileContext {
+            project_filter: true,
+            backup_refreshed: true,
+        };
+
+        let matched = reconcile(date, 2.0, &day_totals("2025-03-01", 0.5, 0), 2.505, context);
+        assert!(matched.matches);
+        assert_eq!(matched.live_cost, 2.5);
+        assert!(matched.likely_causes.is_empty());
+
+        let low = reconcile(date, 2.0, &day_totals("2025-03-01", 0.5, 2), 3.5, context);
+        assert!(!low.matches);
+        assert_eq!(low.difference, -1.0);
+        assert!(low.likely_causes[0].starts_with("2 live update(s) had no costUSD"));
+        assert!(low.likely_causes[1].starts_with("--project"));
+
+        // Updates from another day do not count towards `date`
+        let high = reconcile(
+            date,
+            2.0,
+            &day_totals("2025-02-28", 0.5, 0),
+            1.0,
+            ReconcileContext::default(),
+        );
+        assert_eq!(high.live_updates_cost, 0.0);
+        assert_eq!(high.difference, 1.0);
+        assert!(high.likely_causes[0].starts_with("The backups could not be refreshed"));
+    }
+}
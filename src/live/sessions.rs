@@ -0,0 +1,147 @@
+//! Live session table
+//!
+//! The orchestrator keeps the state of every session it has seen, so each
+//! live update carries the session's totals so far. Over a run of several
+//! days that table would only grow, so sessions without updates for longer
+//! than `[live] session_ttl_minutes` are evicted. Their cost and tokens are
+//! kept as evicted totals, so the session summary still counts them. A
+//! session that resumes after being evicted starts over from its next entry
+//! and fires the `new_session` hook again.
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use crate::models::{MicroDollars, SessionData};
+
+/// A session's state and when its latest entry arrived
+#[derive(Debug)]
+struct TrackedSession {
+    data: SessionData,
+    last_seen: Instant,
+}
+
+/// Totals of the sessions evicted so far
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct EvictedTotals {
+    pub sessions: usize,
+    pub cost: MicroDollars,
+    pub tokens: u64,
+}
+
+/// Sessions seen in this run, keyed by instance and session ID
+#[derive(Debug, Default)]
+pub struct LiveSessions {
+    sessions: HashMap<String, TrackedSession>,
+    /// Idle time after which a session is evicted; `None` never evicts
+    ttl: Option<Duration>,
+    evicted: EvictedTotals,
+}
+
+impl LiveSessions {
+    pub fn new(ttl: Option<Duration>) -> Self {
+        Self {
+            ttl,
+            ..Self::default()
+        }
+    }
+
+    /// Whether the session is being tracked
+    pub fn contains(&self, key: &str) -> bool {
+        self.sessions.contains_key(key)
+    }
+
+    /// The session's state, created with `new` if it is not tracked, marked
+    /// as seen at `now`
+    pub fn touch(
+        &mut self,
+        key: String,
+        now: Instant,
+        new: impl FnOnce() -> SessionData,
+    ) -> &mut SessionData {
+        let session = self.sessions.entry(key).or_insert_with(|| TrackedSession {
+            data: new(),
+            last_seen: now,
+        });
+        session.last_seen = now;
+        &mut session.data
+    }
+
+    /// Evict the sessions idle for longer than the TTL at `now`, returning
+    /// how many were evicted
+    pub fn prune(&mut self, now: Instant) -> usize {
+        let Some(ttl) = self.ttl else {
+            return 0;
+        };
+        let before = self.sessions.len();
+        let evicted = &mut self.evicted;
+        self.sessions.retain(|_, session| {
+            let idle = now.saturating_duration_since(session.last_seen) > ttl;
+            if idle {
+                evicted.sessions += 1;
+                evicted.cost += session.data.total_cost;
+                evicted.tokens += u64::from(session.data.total_tokens());
+            }
+            !idle
+        });
+        before - self.sessions.len()
+    }
+
+    /// Number of sessions currently tracked
+    pub fn tracked(&self) -> usize {
+        self.sessions.len()
+    }
+
+    /// Sessions, cost and tokens of the run, evicted sessions included
+    pub fn totals(&self) -> (usize, MicroDollars, u64) {
+        let cost = self
+            .sessions
+            .values()
+            .map(|session| session.data.total_cost)
+            .sum::<MicroDollars>();
+        let tokens = self
+            .sessions
+            .values()
+            .map(|session| u64::from(session.data.total_tokens()))
+            .sum::<u64>();
+        (
+            self.sessions.len() + self.evicted.sessions,
+            cost + self.evicted.cost,
+            tokens + self.evicted.tokens,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn session(cost: f64, tokens: u32) -> SessionData {
+        let mut data = SessionData::new("session".to_string(), "project".to_string());
+        data.total_cost = MicroDollars::from_dollars(cost);
+        data.input_tokens = tokens;
+        data
+    }
+
+    #[test]
+    fn test_idle_sessions_evicted_into_totals() {
+        let start = Instant::now();
+        let mut sessions = LiveSessions::new(Some(Duration::from_secs(600)));
+        sessions.touch("old".to_string(), start, || session(1.0, 100));
+        sessions.touch("busy".to_string(), start, || session(2.0, 200));
+        sessions.touch("busy".to_string(), start + Duration::from_secs(500), || {
+            unreachable!("the session is tracked")
+        });
+
+        assert_eq!(sessions.prune(start + Duration::from_secs(700)), 1);
+        assert!(!sessions.contains("old"));
+        assert!(sessions.contains("busy"));
+        assert_eq!(sessions.tracked(), 1);
+        assert_eq!(sessions.totals(), (2, MicroDollars::from_dollars(3.0), 300));
+
+        // Without a TTL nothing is evicted
+        let mut kept = LiveSessions::new(None);
+        kept.touch("old".to_string(), start, || session(1.0, 100));
+        assert_eq!(kept.prune(start + Duration::from_secs(86_400)), 0);
+        assert_eq!(kept.tracked(), 1);
+    }
+}
@@ -0,0 +1,105 @@
+//! Live dashboard snapshots
+//!
+//! `S` in the live dashboard saves what it shows, the running totals, today's
+//! live totals and the recent activity rows in their current order, as a
+//! timestamped JSON file in `[live] snapshot_dir`. A snapshot taken while
+//! updates are paused (`p`) records the state as of the pause.
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tracing::debug;
+
+use super::LiveDayTotals;
+
+/// The live dashboard at one moment
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LiveSnapshot {
+    /// When the snapshot was taken (RFC 3339, UTC)
+    pub saved_at: String,
+    /// Whether updates were paused, so newer ones were still waiting
+    pub paused: bool,
+    /// Total cost including baseline
+    pub total_cost: f64,
+    /// Total tokens including baseline
+    pub total_tokens: u64,
+    pub total_sessions: u32,
+    /// Cost of live updates since launch or the last reset
+    pub since_launch_cost: f64,
+    /// Tokens of live updates since launch or the last reset
+    pub since_launch_tokens: u64,
+    /// Live updates of the current local day
+    pub today: LiveDayTotals,
+    /// Recent activity rows, in the order the dashboard showed them
+    pub recent_activity: Vec<SnapshotActivity>,
+}
+
+/// A row of the recent activity list
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SnapshotActivity {
+    /// When the update was received (RFC 3339, UTC)
+    pub time: String,
+    pub project: String,
+    pub session_id: String,
+    /// Keeper instance the update came from, when several are watched
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub instance: Option<String>,
+    pub tokens: u32,
+    pub cost: f64,
+    /// Updates merged into the row when consecutive activity is collapsed
+    pub count: u32,
+}
+
+impl LiveSnapshot {
+    /// Write the snapshot to a new file in `dir`, named after its time
+    pub fn save(&self, dir: &Path, at: DateTime<Utc>) -> Result<PathBuf> {
+        fs::create_dir_all(dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+        let path = dir.join(format!("live-{}.json", at.format("%Y%m%dT%H%M%SZ")));
+        fs::write(&path, serde_json::to_string_pretty(self)? + "\n")
+            .with_context(|| format!("Failed to write snapshot: {}", path.display()))?;
+        debug!(file = %path.display(), rows = self.recent_activity.len(), "Saved live snapshot");
+        Ok(path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_snapshot_saved_as_timestamped_json() {
+        let dir = tempfile::tempdir().unwrap();
+        let at = "2025-06-01T12:30:05Z".parse().unwrap();
+        let snapshot = LiveSnapshot {
+            saved_at: "2025-06-01T12:30:05+00:00".to_string(),
+            paused: true,
+            total_cost: 12.5,
+            total_tokens: 40_000,
+            total_sessions: 3,
+            since_launch_cost: 0.5,
+            since_launch_tokens: 1_000,
+            today: LiveDayTotals::default(),
+            recent_activity: vec![SnapshotActivity {
+                time: "2025-06-01T12:29:59+00:00".to_string(),
+                project: "app".to_string(),
+                session_id: "session1".to_string(),
+                instance: None,
+                tokens: 1_000,
+                cost: 0.5,
+                count: 1,
+            }],
+        };
+
+        let path = snapshot.save(&dir.path().join("snapshots"), at).unwrap();
+        assert!(path.ends_with("snapshots/live-20250601T123005Z.json"));
+        let saved: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(saved["paused"], true);
+        assert_eq!(saved["recentActivity"][0]["sessionId"], "session1");
+        assert!(saved["recentActivity"][0].get("instance").is_none());
+    }
+}
@@ -1,17 +1,164 @@
 //! Claude-keeper subprocess integration
 //!
-//! This module manages the claude-keeper subprocess in watch mode and handles
-//! the JSON streaming of usage updates.
+//! This module manages a claude-keeper subprocess in watch mode and handles
+//! the JSON streaming of usage updates. Each [`KeeperInstance`] gets its own
+//! watcher; see [`crate::live::instances`] for how several are merged.
+//!
+//! When claude-keeper is not installed, the local instance falls back to a
+//! [`JsonlTailer`], which reads the lines appended to the session logs under
+//! `~/.claude/projects` itself.
 
 use anyhow::{Context, Result};
+use glob::glob;
 use serde_json;
+use std::collections::{HashMap, HashSet};
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
 use std::process::Stdio;
+use std::time::Duration;
 use tokio::io::{AsyncBufReadExt, BufReader};
 use tokio::process::{Child, Command};
 use tracing::{debug, error, info, warn};
 
+use crate::config::KeeperInstance;
+use crate::error::KeeperNotFound;
+use crate::keeper_integration::KeeperIntegration;
 use crate::live::LiveConfig;
 use crate::models::UsageEntry;
+use crate::project_names::{resolve_project_name, Expected};
+use crate::session_utils::SessionUtils;
+
+/// How often [`JsonlTailer`] checks the session logs for new lines
+pub const TAIL_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Keys claude-keeper may use for the project an entry belongs to
+const PROJECT_KEYS: [&str; 4] = ["cwd", "projectPath", "project_name", "projectName"];
+
+/// A usage entry streamed by claude-keeper, with its project when reported
+#[derive(Debug, Clone)]
+pub struct KeeperEntry {
+    pub entry: UsageEntry,
+    pub project: Option<String>,
+}
+
+impl KeeperEntry {
+    /// Parse one JSON line from claude-keeper's watch output
+    pub fn parse(line: &str) -> serde_json::Result<Self> {
+        let value: serde_json::Value = serde_json::from_str(line)?;
+        let project = project_of(&value);
+        let entry = serde_json::from_value(value)?;
+        Ok(Self { entry, project })
+    }
+}
+
+/// Project an entry belongs to, when one of [`PROJECT_KEYS`] names it
+fn project_of(value: &serde_json::Value) -> Option<String> {
+    PROJECT_KEYS
+        .iter()
+        .find_map(|key| value.get(*key).and_then(|v| v.as_str()))
+        .filter(|project| !project.is_empty())
+        .map(|project| resolve_project_name(project, Expected::NameOrPath, None))
+}
+
+/// Follows the session logs directly, for when claude-keeper is not installed
+///
+/// Every [`poll`](Self::poll) reads the complete lines appended to each log
+/// since the previous one. Logs present when the tailer is created are
+/// followed from their current end, since the baseline already covers them;
+/// logs created later are read from the start. Repeated lines of one
+/// request are reported once.
+pub struct JsonlTailer {
+    projects_dir: PathBuf,
+    offsets: HashMap<PathBuf, u64>,
+    seen: HashSet<String>,
+    parser: KeeperIntegration,
+}
+
+impl JsonlTailer {
+    /// Tail the session logs in `claude_home`
+    pub fn new(claude_home: &Path) -> Self {
+        let projects_dir = claude_home.join("projects");
+        let offsets = session_logs(&projects_dir)
+            .into_iter()
+            .filter_map(|path| {
+                let len = path.metadata().ok()?.len();
+                Some((path, len))
+            })
+            .collect();
+        Self {
+            projects_dir,
+            offsets,
+            seen: HashSet::new(),
+            parser: KeeperIntegration::new(),
+        }
+    }
+
+    /// Usage entries appended to the logs since the last poll
+    ///
+    /// Logs that cannot be read are skipped until the next poll.
+    pub fn poll(&mut self) -> Vec<KeeperEntry> {
+        let mut entries = Vec::new();
+        for path in session_logs(&self.projects_dir) {
+            let offset = self.offsets.get(&path).copied().unwrap_or(0);
+            match read_new_lines(&path, offset) {
+                Ok((text, offset)) => {
+                    self.offsets.insert(path, offset);
+                    entries.extend(text.lines().filter_map(|line| self.parse(line)));
+                }
+                Err(e) => {
+                    debug!(file = %path.display(), error = %e, "Failed to read session log");
+                }
+            }
+        }
+        entries
+    }
+
+    /// Parse a log line, skipping lines without usage and repeats
+    fn parse(&mut self, line: &str) -> Option<KeeperEntry> {
+        let entry = self.parser.parse_single_line(line)?;
+        if let Some(hash) = SessionUtils::create_unique_hash(&entry) {
+            if !self.seen.insert(hash) {
+                return None;
+            }
+        }
+        let project = serde_json::from_str(line)
+            .ok()
+            .and_then(|value| project_of(&value));
+        Some(KeeperEntry { entry, project })
+    }
+}
+
+/// Session logs in `projects_dir`, including those of subagents
+fn session_logs(projects_dir: &Path) -> Vec<PathBuf> {
+    let pattern = projects_dir.join("**").join("*.jsonl");
+    glob(&pattern.to_string_lossy())
+        .map(|paths| paths.flatten().collect())
+        .unwrap_or_default()
+}
+
+/// Complete lines of `path` after byte `offset`, and the offset after them
+///
+/// A log shorter than `offset` was replaced and is read from the start. A
+/// trailing line still being written is left for the next read.
+fn read_new_lines(path: &Path, offset: u64) -> std::io::Result<(String, u64)> {
+    let mut file = File::open(path)?;
+    let len = file.metadata()?.len();
+    let offset = if len < offset { 0 } else { offset };
+    if len == offset {
+        return Ok((String::new(), offset));
+    }
+
+    file.seek(SeekFrom::Start(offset))?;
+    let mut bytes = Vec::new();
+    file.take(len - offset).read_to_end(&mut bytes)?;
+    let complete = bytes.iter().rposition(|&b| b == b'\n').map_or(0, |i| i + 1);
+    bytes.truncate(complete);
+    Ok((
+        String::from_utf8_lossy(&bytes).into_owned(),
+        offset + complete as u64,
+    ))
+}
 
 /// Manages claude-keeper subprocess for live usage monitoring
 pub struct KeeperWatcher {
@@ -19,37 +166,60 @@ pub struct KeeperWatcher {
     restart_count: u32,
     max_restarts: u32,
     config: LiveConfig,
+    instance: KeeperInstance,
 }
 
 impl KeeperWatcher {
-    /// Create a new keeper watcher and start the subprocess
-    pub fn new(config: &LiveConfig) -> Result<Self> {
+    /// Create a new keeper watcher for `instance` and start the subprocess
+    pub fn new(config: &LiveConfig, instance: &KeeperInstance) -> Result<Self> {
         let mut watcher = Self {
             process: None,
             restart_count: 0,
             max_restarts: config.max_restart_attempts,
             config: config.clone(),
+            instance: instance.clone(),
         };
 
         watcher.start_process()?;
         Ok(watcher)
     }
 
+    /// Restarts used so far
+    pub fn restart_count(&self) -> u32 {
+        self.restart_count
+    }
+
     /// Start the claude-keeper watch process
     fn start_process(&mut self) -> Result<()> {
+        let (program, args) = match self.instance.command.split_first() {
+            Some((program, args)) => (program.clone(), args.to_vec()),
+            None => (
+                self.config.claude_keeper_path.clone(),
+                vec!["watch".to_string(), "--json".to_string()],
+            ),
+        };
         info!(
-            executable = %self.config.claude_keeper_path,
+            instance = %self.instance.name,
+            executable = %program,
             "Starting claude-keeper watch process"
         );
 
-        let mut cmd = Command::new(&self.config.claude_keeper_path);
-        cmd.args(&["watch", "--json"])
+        let mut cmd = Command::new(&program);
+        cmd.args(&args)
             .stdout(Stdio::piped())
             .stderr(Stdio::piped())
             .stdin(Stdio::null());
 
-        let child = cmd.spawn()
-            .with_context(|| format!("Failed to start claude-keeper process: {}", self.config.claude_keeper_path))?;
+        let child = cmd.spawn().map_err(|e| {
+            KeeperNotFound::check(
+                &program,
+                e,
+                format!(
+                    "Failed to start claude-keeper process for {}: {}",
+                    self.instance.name, program
+                ),
+            )
+        })?;
 
         self.process = Some(child);
         
@@ -58,7 +228,7 @@ impl KeeperWatcher {
     }
 
     /// Get the next usage entry from claude-keeper
-    pub async fn next_entry(&mut self) -> Result<Option<UsageEntry>> {
+    pub async fn next_entry(&mut self) -> Result<Option<KeeperEntry>> {
         let process = self.process.as_mut()
             .context("No claude-keeper process running")?;
 
@@ -86,7 +256,7 @@ impl KeeperWatcher {
                     debug!(line = %trimmed, "Received line from claude-keeper");
 
                     // Try to parse as JSON
-                    match serde_json::from_str::<UsageEntry>(trimmed) {
+                    match KeeperEntry::parse(trimmed) {
                         Ok(entry) => {
                             line.clear();
                             return Ok(Some(entry));
@@ -111,13 +281,26 @@ impl KeeperWatcher {
         }
     }
 
+    /// Stop the subprocess while no usage is expected; [`Self::resume`] starts it again
+    pub async fn pause(&mut self) {
+        if let Some(mut process) = self.process.take() {
+            info!(instance = %self.instance.name, "Pausing idle claude-keeper process");
+            let _ = process.kill().await;
+        }
+    }
+
+    /// Start the subprocess again after [`Self::pause`], without counting a restart
+    pub fn resume(&mut self) -> Result<()> {
+        info!(instance = %self.instance.name, "Resuming claude-keeper process");
+        self.start_process()
+    }
+
     /// Check if the watcher should attempt to restart
     pub fn should_restart(&self) -> bool {
         self.restart_count < self.max_restarts
     }
 
     /// Restart the claude-keeper process
-    #[allow(dead_code)]
     pub async fn restart(&mut self) -> Result<()> {
         if !self.should_restart() {
             return Err(anyhow::anyhow!(
@@ -127,6 +310,7 @@ impl KeeperWatcher {
         }
 
         warn!(
+            instance = %self.instance.name,
             attempt = self.restart_count + 1,
             max_attempts = self.max_restarts,
             "Restarting claude-keeper process"
@@ -163,4 +347,56 @@ impl Drop for KeeperWatcher {
             let _ = process.start_kill();
         }
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::io::Write;
+    use tempfile::TempDir;
+
+    fn usage_line(id: &str) -> String {
+        format!(
+            r#"{{"timestamp":"2025-03-01T12:00:00Z","cwd":"/work/app","message":{{"id":"{}","model":"claude-3-5-sonnet-20241022","usage":{{"input_tokens":100,"output_tokens":50}}}},"requestId":"req_{}","costUSD":0.01}}"#,
+            id, id
+        )
+    }
+
+    #[test]
+    fn test_tailer_reads_only_new_complete_lines() {
+        let home = TempDir::new().unwrap();
+        let project = home.path().join("projects").join("-work-app");
+        fs::create_dir_all(&project).unwrap();
+        let log = project.join("session.jsonl");
+        fs::write(&log, format!("{}\n", usage_line("old"))).unwrap();
+
+        let mut tailer = JsonlTailer::new(home.path());
+        assert!(tailer.poll().is_empty());
+
+        let mut file = fs::File::options().append(true).open(&log).unwrap();
+        let new = usage_line("new");
+        let late = usage_line("late");
+        let (head, tail) = late.split_at(40);
+        write!(file, "{}\n{}\n{}", new, new, head).unwrap();
+        let entries = tailer.poll();
+        assert_eq!(entries.len(), 1, "repeated lines are reported once");
+        assert_eq!(entries[0].entry.message.id, "new");
+        assert_eq!(entries[0].project.as_deref(), Some("/work/app"));
+
+        // A line finished after the last poll is read whole, as are new logs
+        writeln!(file, "{}", tail).unwrap();
+        fs::write(
+            project.join("agent.jsonl"),
+            format!("{}\n", usage_line("agent")),
+        )
+        .unwrap();
+        let mut ids: Vec<String> = tailer
+            .poll()
+            .into_iter()
+            .map(|entry| entry.entry.message.id)
+            .collect();
+        ids.sort();
+        assert_eq!(ids, ["agent", "late"]);
+    }
+}
@@ -2,24 +2,78 @@ use anyhow::{Context, Result};
 use clap::{Parser, Subcommand};
 use tracing::error;
 
+mod allocations;
 mod analyzer;
+mod archive;
+mod batch;
+mod blocks;
+mod branches;
 mod ccusage_compat;
 mod commands;
+mod commit_trailer;
 mod config;
+mod container;
+mod cost_audit;
 mod dedup;
+mod dedup_set;
+mod dedup_state;
 mod display;
+mod error;
+mod estimation;
+mod export;
+mod export_manifest;
+mod file_discovery;
+mod forecast;
+mod hooks;
+mod inspect;
+mod io_throttle;
 mod keeper_integration;
+mod limits;
+mod line_reader;
 mod live;
 mod logging;
+mod memory;
 mod models;
+mod notes;
+mod notify;
+mod output_version;
 mod parquet;
+mod parse_cache;
+mod parser;
+mod pipeline;
 mod pricing;
+mod pricing_snapshot;
+mod project_names;
+mod projects;
+mod ranges;
+mod redaction;
+mod report_diff;
 mod reports;
+mod reprocess;
+mod request;
+mod sanitize;
+mod self_metrics;
+mod session_utils;
+mod skipped;
+mod status;
 mod timestamp_parser;
+mod top_k;
+mod web;
 
 use analyzer::ClaudeUsageAnalyzer;
+use commands::error::CliError;
 use config::get_config;
-use dedup::ProcessOptions;
+use cost_audit::DEFAULT_TOLERANCE_PCT;
+use dedup::{
+    CancellationToken, Command, CostMode, GroupBy, ProcessOptions, ProjectSort, SortOrder,
+};
+use export::ExportFormat;
+use forecast::DEFAULT_FORECAST_DAYS;
+use hooks::HookEvent;
+use ranges::DateRange;
+use reprocess::DedupSettings;
+use request::{AnalyzerRequest, ReportKind};
+use self_metrics::MetricsRecorder;
 
 #[derive(Parser)]
 #[command(name = "claude-usage")]
@@ -28,6 +82,15 @@ use dedup::ProcessOptions;
 struct Cli {
     #[command(subcommand)]
     command: Option<Commands>,
+    /// Also read the ~/.claude tree at PATH, e.g. another developer's; repeat for more (overrides paths.extra_roots)
+    #[arg(long = "claude-root", value_name = "PATH", global = true)]
+    claude_roots: Vec<std::path::PathBuf>,
+    /// Read the ~/.claude tree at PATH instead of ~/.claude (overrides CLAUDE_HOME)
+    #[arg(long, value_name = "PATH", global = true)]
+    claude_dir: Option<std::path::PathBuf>,
+    /// Container mode: write nothing outside DIR and check the mounts first (see CONFIGURATION.md)
+    #[arg(long, value_name = "DIR", global = true)]
+    container: Option<std::path::PathBuf>,
 }
 
 #[derive(Subcommand)]
@@ -37,7 +100,13 @@ enum Commands {
         /// Output in JSON format
         #[arg(long)]
         json: bool,
-        /// Show last N entries
+        /// Output format; --json is shorthand for --format json
+        #[arg(long, value_enum, default_value_t = OutputFormat::Pretty, conflicts_with = "json")]
+        format: OutputFormat,
+        /// Render --json in the layout of an earlier JSON version (default: latest)
+        #[arg(long, value_name = "N")]
+        output_version: Option<u32>,
+        /// Show the last N days, including days without usage (default: 30)
         #[arg(long)]
         limit: Option<usize>,
         /// Start date filter (YYYY-MM-DD)
@@ -49,13 +118,207 @@ enum Commands {
         /// Exclude VMs directory from analysis
         #[arg(long)]
         exclude_vms: bool,
+        /// Cost source: costUSD with token pricing as fallback (auto), token pricing only (calculate) or costUSD only (display)
+        #[arg(long, value_enum, default_value_t = CostMode::Auto)]
+        mode: CostMode,
+        /// Only include projects matching NAME: a project directory name, a path such as $PWD, or a glob like '*api*'
+        #[arg(long, value_name = "NAME")]
+        project: Option<String>,
+        /// Show project names as aliases from the aliases file or as stable hashes
+        #[arg(long)]
+        redact_projects: bool,
+        /// Estimate tokens from message content for entries without usage data
+        #[arg(long)]
+        estimate_missing: bool,
+        /// Include per-entry records with source file, line, instance and dedup hash (JSON only)
+        #[arg(long)]
+        provenance: bool,
+        /// Break down usage by project, instance type (host, vm, devcontainer), allocated cost center, git branch, Claude root, VM or model
+        #[arg(long, value_enum, default_value_t = GroupBy::Project)]
+        group_by: GroupBy,
+        /// Exit with code 3 if more than this percentage of log lines or backup rows fail to parse
+        #[arg(long, value_name = "PCT", value_parser = parse_percentage)]
+        max_error_rate: Option<f64>,
+        /// Compare each entry's recorded costUSD with its token-computed cost and report differences per model and day
+        #[arg(long)]
+        audit_costs: bool,
+        /// Allowed difference between recorded and computed cost for --audit-costs, in percent
+        #[arg(long, value_name = "PCT", value_parser = parse_percentage, default_value_t = DEFAULT_TOLERANCE_PCT)]
+        audit_tolerance: f64,
+        /// In auto mode, switch to calculate or display when a model's recorded costUSD systematically differs from token pricing
+        #[arg(long)]
+        check_mode: bool,
+        /// Show input, output, cache read and cache write token columns per row
+        #[arg(long)]
+        tokens: bool,
+        /// Show when work started and ended (first and last entry) on each row
+        #[arg(long)]
+        details: bool,
+        /// Date order of rows (default: desc)
+        #[arg(long, value_enum)]
+        order: Option<SortOrder>,
+        /// Add per-model token and cost rows to each day
+        #[arg(long)]
+        breakdown: bool,
+        /// Write the JSON or CSV report to FILE, with a FILE.manifest.json of SHA-256 hashes
+        #[arg(long, value_name = "FILE")]
+        output: Option<std::path::PathBuf>,
+        /// List only the N most expensive projects, estimated with bounded memory
+        #[arg(long, value_name = "N", value_parser = clap::value_parser!(u64).range(1..))]
+        approx_top: Option<u64>,
+        /// Rerun the report every SECS seconds (default: 10) and redraw it in place
+        #[arg(
+            long,
+            value_name = "SECS",
+            num_args = 0..=1,
+            default_missing_value = "10",
+            value_parser = clap::value_parser!(u64).range(1..)
+        )]
+        watch: Option<u64>,
+        /// Print only what changed since the last run of this report: new days, changed totals and new projects
+        #[arg(long, conflicts_with = "watch")]
+        diff_since_last_run: bool,
+        /// Report the days from START to END (inclusive) as their own section; repeat to compare several periods in one scan
+        #[arg(
+            long,
+            value_name = "START..END",
+            conflicts_with_all = ["since", "until", "limit", "watch", "diff_since_last_run", "approx_top"]
+        )]
+        range: Vec<DateRange>,
+        /// List the files, sessions and entries the date, VM and project filters left out
+        #[arg(short, long)]
+        verbose: bool,
     },
     /// Show monthly usage aggregation
     Monthly {
         /// Output in JSON format
         #[arg(long)]
         json: bool,
-        /// Show last N entries
+        /// Output format; --json is shorthand for --format json
+        #[arg(long, value_enum, default_value_t = OutputFormat::Pretty, conflicts_with = "json")]
+        format: OutputFormat,
+        /// Render --json in the layout of an earlier JSON version (default: latest)
+        #[arg(long, value_name = "N")]
+        output_version: Option<u32>,
+        /// Show the last N months with usage (default: 10)
+        #[arg(long)]
+        limit: Option<usize>,
+        /// Start date filter (YYYY-MM-DD)
+        #[arg(long)]
+        since: Option<String>,
+        /// End date filter (YYYY-MM-DD)
+        #[arg(long)]
+        until: Option<String>,
+        /// Exclude VMs directory from analysis
+        #[arg(long)]
+        exclude_vms: bool,
+        /// Cost source: costUSD with token pricing as fallback (auto), token pricing only (calculate) or costUSD only (display)
+        #[arg(long, value_enum, default_value_t = CostMode::Auto)]
+        mode: CostMode,
+        /// Only include projects matching NAME: a project directory name, a path such as $PWD, or a glob like '*api*'
+        #[arg(long, value_name = "NAME")]
+        project: Option<String>,
+        /// Show project names as aliases from the aliases file or as stable hashes
+        #[arg(long)]
+        redact_projects: bool,
+        /// Estimate tokens from message content for entries without usage data
+        #[arg(long)]
+        estimate_missing: bool,
+        /// Include per-entry records with source file, line, instance and dedup hash (JSON only)
+        #[arg(long)]
+        provenance: bool,
+        /// Break down usage by project, instance type (host, vm, devcontainer), allocated cost center, git branch, Claude root, VM or model
+        #[arg(long, value_enum, default_value_t = GroupBy::Project)]
+        group_by: GroupBy,
+        /// Exit with code 3 if more than this percentage of log lines or backup rows fail to parse
+        #[arg(long, value_name = "PCT", value_parser = parse_percentage)]
+        max_error_rate: Option<f64>,
+        /// Compare each entry's recorded costUSD with its token-computed cost and report differences per model and day
+        #[arg(long)]
+        audit_costs: bool,
+        /// Allowed difference between recorded and computed cost for --audit-costs, in percent
+        #[arg(long, value_name = "PCT", value_parser = parse_percentage, default_value_t = DEFAULT_TOLERANCE_PCT)]
+        audit_tolerance: f64,
+        /// In auto mode, switch to calculate or display when a model's recorded costUSD systematically differs from token pricing
+        #[arg(long)]
+        check_mode: bool,
+        /// Date order of rows (default: asc)
+        #[arg(long, value_enum)]
+        order: Option<SortOrder>,
+        /// Add per-model token and cost rows to each month
+        #[arg(long)]
+        breakdown: bool,
+        /// Write the JSON or CSV report to FILE, with a FILE.manifest.json of SHA-256 hashes
+        #[arg(long, value_name = "FILE")]
+        output: Option<std::path::PathBuf>,
+        /// List only the N most expensive projects, estimated with bounded memory
+        #[arg(long, value_name = "N", value_parser = clap::value_parser!(u64).range(1..))]
+        approx_top: Option<u64>,
+        /// Print only what changed since the last run of this report: new months, changed totals and new groups
+        #[arg(long)]
+        diff_since_last_run: bool,
+        /// Report the months of the days from START to END (inclusive) as their own section; repeat to compare several periods in one scan
+        #[arg(
+            long,
+            value_name = "START..END",
+            conflicts_with_all = ["since", "until", "limit", "diff_since_last_run", "approx_top"]
+        )]
+        range: Vec<DateRange>,
+        /// List the files, sessions and entries the date, VM and project filters left out
+        #[arg(short, long)]
+        verbose: bool,
+    },
+    /// Show per-session breakdowns with cost, tokens, models and last activity
+    Session {
+        /// Output in JSON format
+        #[arg(long)]
+        json: bool,
+        /// Output format; --json is shorthand for --format json
+        #[arg(long, value_enum, default_value_t = OutputFormat::Pretty, conflicts_with = "json")]
+        format: OutputFormat,
+        /// Render --json in the layout of an earlier JSON version (default: latest)
+        #[arg(long, value_name = "N")]
+        output_version: Option<u32>,
+        /// Show the N most recently active sessions (default: all)
+        #[arg(long)]
+        limit: Option<usize>,
+        /// Start date filter (YYYY-MM-DD)
+        #[arg(long)]
+        since: Option<String>,
+        /// End date filter (YYYY-MM-DD)
+        #[arg(long)]
+        until: Option<String>,
+        /// Exclude VMs directory from analysis
+        #[arg(long)]
+        exclude_vms: bool,
+        /// Cost source: costUSD with token pricing as fallback (auto), token pricing only (calculate) or costUSD only (display)
+        #[arg(long, value_enum, default_value_t = CostMode::Auto)]
+        mode: CostMode,
+        /// Only include projects matching NAME: a project directory name, a path such as $PWD, or a glob like '*api*'
+        #[arg(long, value_name = "NAME")]
+        project: Option<String>,
+        /// Show project names as aliases from the aliases file or as stable hashes
+        #[arg(long)]
+        redact_projects: bool,
+        /// Add per-model token and cost rows to each session
+        #[arg(long)]
+        breakdown: bool,
+        /// Write the JSON or CSV report to FILE, with a FILE.manifest.json of SHA-256 hashes
+        #[arg(long, value_name = "FILE")]
+        output: Option<std::path::PathBuf>,
+        /// List the files, sessions and entries the date, VM and project filters left out
+        #[arg(short, long)]
+        verbose: bool,
+    },
+    /// Rank projects by cost, tokens or sessions within a date range
+    Top {
+        /// Output in JSON format
+        #[arg(long)]
+        json: bool,
+        /// Output format; --json is shorthand for --format json
+        #[arg(long, value_enum, default_value_t = OutputFormat::Pretty, conflicts_with = "json")]
+        format: OutputFormat,
+        /// Show the N highest-ranked projects (default: 10)
         #[arg(long)]
         limit: Option<usize>,
         /// Start date filter (YYYY-MM-DD)
@@ -64,6 +327,207 @@ enum Commands {
         /// End date filter (YYYY-MM-DD)
         #[arg(long)]
         until: Option<String>,
+        /// Rank projects by this total
+        #[arg(long, value_enum, default_value_t = ProjectSort::Cost)]
+        sort: ProjectSort,
+        /// Exclude VMs directory from analysis
+        #[arg(long)]
+        exclude_vms: bool,
+        /// Cost source: costUSD with token pricing as fallback (auto), token pricing only (calculate) or costUSD only (display)
+        #[arg(long, value_enum, default_value_t = CostMode::Auto)]
+        mode: CostMode,
+        /// Only include projects matching NAME: a project directory name, a path such as $PWD, or a glob like '*api*'
+        #[arg(long, value_name = "NAME")]
+        project: Option<String>,
+        /// Show project names as aliases from the aliases file or as stable hashes
+        #[arg(long)]
+        redact_projects: bool,
+        /// Write the JSON or CSV report to FILE, with a FILE.manifest.json of SHA-256 hashes
+        #[arg(long, value_name = "FILE")]
+        output: Option<std::path::PathBuf>,
+        /// List the files, sessions and entries the date, VM and project filters left out
+        #[arg(short, long)]
+        verbose: bool,
+    },
+    /// Show the recent burn rate, the projected month-end spend and when the monthly budget runs out
+    Forecast {
+        /// Output in JSON format
+        #[arg(long)]
+        json: bool,
+        /// Average the daily cost over the last N complete days
+        #[arg(long, value_name = "N", default_value_t = DEFAULT_FORECAST_DAYS, value_parser = clap::value_parser!(u32).range(1..))]
+        days: u32,
+        /// Exclude VMs directory from analysis
+        #[arg(long)]
+        exclude_vms: bool,
+        /// Cost source: costUSD with token pricing as fallback (auto), token pricing only (calculate) or costUSD only (display)
+        #[arg(long, value_enum, default_value_t = CostMode::Auto)]
+        mode: CostMode,
+        /// Only include projects matching NAME: a project directory name, a path such as $PWD, or a glob like '*api*'
+        #[arg(long, value_name = "NAME")]
+        project: Option<String>,
+    },
+    /// Show usage grouped into 5-hour billing blocks, flagging the active one
+    Blocks {
+        /// Output in JSON format
+        #[arg(long)]
+        json: bool,
+        /// Show only the N most recent blocks
+        #[arg(long)]
+        limit: Option<usize>,
+        /// Start date filter (YYYY-MM-DD)
+        #[arg(long)]
+        since: Option<String>,
+        /// End date filter (YYYY-MM-DD)
+        #[arg(long)]
+        until: Option<String>,
+        /// Exclude VMs directory from analysis
+        #[arg(long)]
+        exclude_vms: bool,
+        /// Cost source: costUSD with token pricing as fallback (auto), token pricing only (calculate) or costUSD only (display)
+        #[arg(long, value_enum, default_value_t = CostMode::Auto)]
+        mode: CostMode,
+        /// List the files, sessions and entries the date, VM and project filters left out
+        #[arg(short, long)]
+        verbose: bool,
+    },
+    /// Write the deduplicated usage entries to a file for DuckDB, pandas and others
+    Export {
+        /// Output in JSON format
+        #[arg(long)]
+        json: bool,
+        /// File format; parquet and sqlite require the `parquet-export` and `sqlite-export` features
+        #[arg(long, value_enum, default_value_t = ExportFormat::Parquet)]
+        format: ExportFormat,
+        /// File to write, or SQLite database to update
+        #[arg(long, value_name = "FILE")]
+        out: std::path::PathBuf,
+        /// Start date filter (YYYY-MM-DD)
+        #[arg(long)]
+        since: Option<String>,
+        /// End date filter (YYYY-MM-DD)
+        #[arg(long)]
+        until: Option<String>,
+        /// Exclude VMs directory from analysis
+        #[arg(long)]
+        exclude_vms: bool,
+        /// Cost source: costUSD with token pricing as fallback (auto), token pricing only (calculate) or costUSD only (display)
+        #[arg(long, value_enum, default_value_t = CostMode::Auto)]
+        mode: CostMode,
+        /// Only include projects matching NAME: a project directory name, a path such as $PWD, or a glob like '*api*'
+        #[arg(long, value_name = "NAME")]
+        project: Option<String>,
+        /// Show project names as aliases from the aliases file or as stable hashes
+        #[arg(long)]
+        redact_projects: bool,
+        /// List the files, sessions and entries the date, VM and project filters left out
+        #[arg(short, long)]
+        verbose: bool,
+    },
+    /// Compare the totals of a date range under different dedup settings
+    Reprocess {
+        /// Output in JSON format
+        #[arg(long)]
+        json: bool,
+        /// Dedup settings to compare with the current ones, as [window=HOURS|all][,scope=global|instance|session][,key=message-request|message|request|none] (repeatable)
+        #[arg(long = "settings", value_name = "SETTINGS", required = true)]
+        settings: Vec<String>,
+        /// Start date filter (YYYY-MM-DD)
+        #[arg(long)]
+        since: Option<String>,
+        /// End date filter (YYYY-MM-DD)
+        #[arg(long)]
+        until: Option<String>,
+        /// Exclude VMs directory from analysis
+        #[arg(long)]
+        exclude_vms: bool,
+        /// Cost source: costUSD with token pricing as fallback (auto), token pricing only (calculate) or costUSD only (display)
+        #[arg(long, value_enum, default_value_t = CostMode::Auto)]
+        mode: CostMode,
+        /// Only include projects matching NAME: a project directory name, a path such as $PWD, or a glob like '*api*'
+        #[arg(long, value_name = "NAME")]
+        project: Option<String>,
+        /// List the files, sessions and entries the date, VM and project filters left out
+        #[arg(short, long)]
+        verbose: bool,
+    },
+    /// Answer several report queries from a single scan, as JSON keyed by query name
+    Batch {
+        /// JSON file with an array of queries: {"name", "report", "since", "until", "limit", "groupBy"}
+        #[arg(long, value_name = "FILE")]
+        file: Option<std::path::PathBuf>,
+        /// Query as NAME=REPORT[,since=DATE][,until=DATE][,limit=N][,group_by=G] (repeatable)
+        #[arg(long = "query", value_name = "QUERY")]
+        queries: Vec<String>,
+        /// Exclude VMs directory from analysis
+        #[arg(long)]
+        exclude_vms: bool,
+        /// Estimate tokens from message content for entries without usage data
+        #[arg(long)]
+        estimate_missing: bool,
+    },
+    /// Serve a local web dashboard with JSON endpoints (requires the `web` feature)
+    Web {
+        /// Port to listen on at 127.0.0.1
+        #[arg(long, default_value_t = 8787)]
+        port: u16,
+        /// Reload report data when it is older than this many seconds
+        #[arg(long, value_name = "SECS", default_value_t = 60)]
+        refresh: u64,
+        /// Exclude VMs directory from analysis
+        #[arg(long)]
+        exclude_vms: bool,
+    },
+    /// Export usage gauges over HTTP for Grafana and others (requires the `web` feature)
+    Serve {
+        /// Serve Prometheus gauges at /metrics
+        #[arg(long, required = true)]
+        prometheus: bool,
+        /// Address to listen on
+        #[arg(long, default_value = "127.0.0.1")]
+        bind: String,
+        /// Port to listen on
+        #[arg(long, default_value_t = 9464)]
+        port: u16,
+        /// Reload usage data every this many seconds
+        #[arg(long, value_name = "SECS", default_value_t = 60)]
+        refresh: u64,
+        /// Exclude VMs directory from analysis
+        #[arg(long)]
+        exclude_vms: bool,
+    },
+    /// Print today's and this month's totals for status bars, or serve them over HTTP with --listen
+    Status {
+        /// Output compact JSON
+        #[arg(long)]
+        json: bool,
+        /// Serve /today, /month and /status as JSON on this port instead (requires the `web` feature)
+        #[arg(long, value_name = "PORT")]
+        listen: Option<u16>,
+        /// Address to listen on with --listen
+        #[arg(long, default_value = "127.0.0.1", requires = "listen")]
+        bind: String,
+        /// Reload usage data every this many seconds with --listen
+        #[arg(long, value_name = "SECS", default_value_t = 30, requires = "listen")]
+        refresh: u64,
+        /// Exclude VMs directory from analysis
+        #[arg(long)]
+        exclude_vms: bool,
+    },
+    /// Check spend, totalled as the daily report does, against limits and send webhook notifications for thresholds crossed (for cron and CI)
+    Check {
+        /// Output JSON
+        #[arg(long)]
+        json: bool,
+        /// Exit with code 10 when today's cost is over this many dollars
+        #[arg(long, value_name = "USD", value_parser = parse_dollars)]
+        max_daily: Option<f64>,
+        /// Exit with code 10 when this month's cost is over this many dollars
+        #[arg(long, value_name = "USD", value_parser = parse_dollars)]
+        max_monthly: Option<f64>,
+        /// List the notifications without sending them
+        #[arg(long)]
+        dry_run: bool,
         /// Exclude VMs directory from analysis
         #[arg(long)]
         exclude_vms: bool,
@@ -73,6 +537,15 @@ enum Commands {
         /// Skip loading baseline data from parquet backups
         #[arg(long)]
         no_baseline: bool,
+        /// Only show usage from projects matching this glob (repeatable)
+        #[arg(long = "project", value_name = "GLOB")]
+        projects: Vec<String>,
+        /// Print totals and the last hour's burn rate as JSON on exit
+        #[arg(long)]
+        json: bool,
+        /// On exit, compare today's live totals with the daily report and explain any difference
+        #[arg(long)]
+        reconcile_on_exit: bool,
     },
     /// Test ccusage compatibility mode for exact parity
     TestCompat {
@@ -82,116 +555,723 @@ enum Commands {
         /// End date filter (YYYY-MM-DD)
         #[arg(long)]
         until: Option<String>,
+        /// Cost source: costUSD with token pricing as fallback (auto), token pricing only (calculate) or costUSD only (display)
+        #[arg(long, value_enum, default_value_t = CostMode::Auto)]
+        mode: CostMode,
+    },
+    /// Inspect locally recorded self-metrics (opt-in, never uploaded)
+    Metrics {
+        #[command(subcommand)]
+        action: MetricsAction,
+    },
+    /// Parse a single JSONL file and show what reports would see in it
+    Inspect {
+        /// Session log to inspect
+        file: std::path::PathBuf,
+        /// Output in JSON format
+        #[arg(long)]
+        json: bool,
+    },
+    /// Add the Claude cost since the previous commit to git commit messages
+    Hook {
+        #[command(subcommand)]
+        action: HookAction,
+    },
+    /// Annotate days with notes shown in daily reports
+    Note {
+        #[command(subcommand)]
+        action: NoteAction,
+    },
+    /// Manage compacted archives of old session logs
+    Archive {
+        #[command(subcommand)]
+        action: ArchiveAction,
+    },
+    /// Download and inspect the offline pricing snapshot
+    Pricing {
+        #[command(subcommand)]
+        action: PricingAction,
+    },
+    /// Check the Claude data, claude-keeper, backups and pricing, and suggest fixes
+    Doctor {
+        /// Output in JSON format
+        #[arg(long)]
+        json: bool,
+    },
+    /// Check exported files against the SHA-256 hashes in their manifest
+    VerifyExport {
+        /// Manifest written next to an export or inside an archive part
+        manifest: std::path::PathBuf,
+        /// Output in JSON format
+        #[arg(long)]
+        json: bool,
+    },
+}
+
+impl Commands {
+    /// Whether the command prints JSON, and so reports failures as JSON too
+    fn json_output(&self) -> bool {
+        match self {
+            Commands::Daily { json, format, .. }
+            | Commands::Monthly { json, format, .. }
+            | Commands::Session { json, format, .. }
+            | Commands::Top { json, format, .. } => *json || *format == OutputFormat::Json,
+            Commands::Blocks { json, .. }
+            | Commands::Export { json, .. }
+            | Commands::Reprocess { json, .. }
+            | Commands::Forecast { json, .. }
+            | Commands::Status { json, .. }
+            | Commands::Check { json, .. }
+            | Commands::Live { json, .. }
+            | Commands::Inspect { json, .. }
+            | Commands::Doctor { json }
+            | Commands::VerifyExport { json, .. }
+            | Commands::Metrics {
+                action: MetricsAction::Show { json },
+            }
+            | Commands::Archive {
+                action: ArchiveAction::Compact { json, .. },
+            }
+            | Commands::Pricing {
+                action: PricingAction::Refresh { json } | PricingAction::Show { json, .. },
+            } => *json,
+            Commands::Batch { .. } => true,
+            Commands::Web { .. }
+            | Commands::Serve { .. }
+            | Commands::TestCompat { .. }
+            | Commands::Hook { .. }
+            | Commands::Note { .. } => false,
+        }
+    }
+}
+
+/// How report commands print their results
+#[derive(Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+enum OutputFormat {
+    /// Terminal report
+    Pretty,
+    Json,
+    /// One row per day, month or session with token and cost columns
+    Csv,
+}
+
+#[derive(Subcommand)]
+enum ArchiveAction {
+    /// Rewrite old JSONL logs into deduplicated per-month parquet archives
+    Compact {
+        /// Compact logs last modified before the start of the month this many months ago
+        #[arg(long, value_name = "MONTHS", default_value_t = 3, value_parser = clap::value_parser!(u32).range(1..))]
+        older_than: u32,
+        /// Delete original logs once their archived months are verified
+        #[arg(long)]
+        delete_originals: bool,
+        /// Show what would be compacted without writing anything
+        #[arg(long)]
+        dry_run: bool,
+        /// Output in JSON format
+        #[arg(long)]
+        json: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum PricingAction {
+    /// Download the LiteLLM pricing table for offline cost calculation
+    Refresh {
+        /// Output in JSON format
+        #[arg(long)]
+        json: bool,
+    },
+    /// Show the rates a model is priced at and where they come from
+    Show {
+        /// Model name, e.g. claude-sonnet-4-20250514
+        model: String,
+        /// Output in JSON format
+        #[arg(long)]
+        json: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum HookAction {
+    /// Install a prepare-commit-msg hook adding a Claude-Usage trailer to each commit
+    Install {
+        /// Replace a prepare-commit-msg hook that claude-usage did not install
+        #[arg(long)]
+        force: bool,
+    },
+    /// Print the Claude-Usage trailer for this repository's project since its last commit
+    Trailer,
+}
+
+#[derive(Subcommand)]
+enum NoteAction {
+    /// Add a note to a day
+    Add {
+        /// Day the note belongs to (YYYY-MM-DD)
+        date: String,
+        /// Note text, e.g. "big refactor with opus agent"
+        text: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum MetricsAction {
+    /// Summarize command timings, dataset sizes and feature usage
+    Show {
+        /// Output in JSON format
+        #[arg(long)]
+        json: bool,
     },
 }
 
 #[tokio::main]
-async fn main() -> Result<()> {
-    // Load configuration first (this also validates it)
-    get_config();
+async fn main() {
+    let cli = Cli::parse();
+    let json = cli.command.as_ref().is_some_and(Commands::json_output);
+
+    // Handed to the configuration as CLAUDE_USAGE_EXTRA_ROOTS, CLAUDE_HOME and
+    // CLAUDE_USAGE_CONTAINER_OUTPUT
+    if !cli.claude_roots.is_empty() {
+        match std::env::join_paths(&cli.claude_roots) {
+            Ok(roots) => std::env::set_var("CLAUDE_USAGE_EXTRA_ROOTS", roots),
+            Err(e) => handle_error(
+                CliError::config(anyhow::Error::new(e).context("Invalid --claude-root")),
+                json,
+            ),
+        }
+    }
+    if let Some(dir) = &cli.claude_dir {
+        std::env::set_var("CLAUDE_HOME", dir);
+    }
+    if let Some(dir) = &cli.container {
+        std::env::set_var("CLAUDE_USAGE_CONTAINER_OUTPUT", dir);
+    }
+
+    // Load configuration first (this also validates it, and checks the mounts
+    // in container mode)
+    if let Err(e) = config::init_config() {
+        handle_error(CliError::config(e), json);
+    }
+    if let Err(e) = check_container_command(cli.command.as_ref()) {
+        handle_error(CliError::config(e), json);
+    }
 
     // Initialize logging with config
     logging::init_logging();
 
     // Initialize memory monitoring with config
-    // memory::init_memory_limit(); // Removed to eliminate unused module warnings
+    memory::init_memory_limit();
 
-    let cli = Cli::parse();
+    if let Err(e) = run(cli.command).await {
+        handle_error(e, json);
+    }
+}
 
+async fn run(command: Option<Commands>) -> Result<()> {
     // Handle command with its specific options
-    match cli.command.unwrap_or(Commands::Daily {
+    match command.unwrap_or(Commands::Daily {
         json: false,
+        format: OutputFormat::Pretty,
+        output_version: None,
         limit: None,
         since: None,
         until: None,
         exclude_vms: false,
+        mode: CostMode::Auto,
+        project: None,
+        redact_projects: false,
+        estimate_missing: false,
+        provenance: false,
+        group_by: GroupBy::Project,
+        max_error_rate: None,
+        audit_costs: false,
+        audit_tolerance: DEFAULT_TOLERANCE_PCT,
+        check_mode: false,
+        tokens: false,
+        details: false,
+        order: None,
+        breakdown: false,
+        output: None,
+        approx_top: None,
+        watch: None,
+        diff_since_last_run: false,
+        range: Vec::new(),
+        verbose: false,
     }) {
         Commands::Daily {
             json,
+            format,
+            output_version,
             limit,
             since,
             until,
             exclude_vms,
+            mode,
+            project,
+            redact_projects,
+            estimate_missing,
+            provenance,
+            group_by,
+            max_error_rate,
+            audit_costs,
+            audit_tolerance,
+            check_mode,
+            tokens,
+            details,
+            order,
+            breakdown,
+            output,
+            approx_top,
+            watch,
+            diff_since_last_run,
+            range,
+            verbose,
         } => {
-            let (_since_date, _until_date, mut analyzer, options) =
-                parse_common_args(json, limit, since, until, "daily", exclude_vms)?;
+            let json = json || format == OutputFormat::Json;
+            let (_since_date, _until_date, mut analyzer, mut options) = parse_common_args(
+                json,
+                limit,
+                since,
+                until,
+                Command::Daily,
+                exclude_vms,
+                estimate_missing,
+            )?;
+            options.csv_output = format == OutputFormat::Csv;
+            options.output = check_output(output, format, json)?;
+            options.output_version = check_output_version(output_version, json)?;
+            options.project = project;
+            options.redact_projects = redact_projects;
+            options.cost_mode = mode;
+            options.provenance = provenance;
+            options.group_by = group_by;
+            options.max_error_rate = max_error_rate;
+            options.audit_costs = audit_costs.then_some(audit_tolerance);
+            options.check_mode = check_cost_mode(check_mode, mode)?;
+            options.show_tokens = tokens;
+            options.show_details = details;
+            options.order = order;
+            options.breakdown = breakdown;
+            options.approx_top = approx_top.map(|n| n as usize);
+            options.diff_since_last_run = diff_since_last_run;
+            options.verbose = verbose;
+            check_approx_top(&options)?;
+            check_diff_since_last_run(&options)?;
+            set_ranges(&mut options, range)?;
+
+            if let Some(secs) = watch {
+                if json || options.csv_output || options.output.is_some() {
+                    anyhow::bail!("--watch only works with the terminal report");
+                }
+                let interval = std::time::Duration::from_secs(secs);
+                return commands::watch::run_daily_watch(&mut analyzer, options, interval).await;
+            }
+            options.cancel = Some(cancel_on_ctrl_c());
+
+            let metrics = command_metrics(&options);
+            let result = analyzer.run_command("daily", options).await;
+            finish_metrics(metrics, &analyzer, result.is_ok());
 
-            match analyzer.run_command("daily", options).await {
+            match result {
                 Ok(_) => Ok(()),
                 Err(e) => handle_error(e, json),
             }
         }
         Commands::Monthly {
             json,
+            format,
+            output_version,
             limit,
             since,
             until,
             exclude_vms,
+            mode,
+            project,
+            redact_projects,
+            estimate_missing,
+            provenance,
+            group_by,
+            max_error_rate,
+            audit_costs,
+            audit_tolerance,
+            check_mode,
+            order,
+            breakdown,
+            output,
+            approx_top,
+            diff_since_last_run,
+            range,
+            verbose,
+        } => {
+            let json = json || format == OutputFormat::Json;
+            let (_since_date, _until_date, mut analyzer, mut options) = parse_common_args(
+                json,
+                limit,
+                since,
+                until,
+                Command::Monthly,
+                exclude_vms,
+                estimate_missing,
+            )?;
+            options.csv_output = format == OutputFormat::Csv;
+            options.output = check_output(output, format, json)?;
+            options.output_version = check_output_version(output_version, json)?;
+            options.project = project;
+            options.redact_projects = redact_projects;
+            options.cost_mode = mode;
+            options.provenance = provenance;
+            options.group_by = group_by;
+            options.max_error_rate = max_error_rate;
+            options.audit_costs = audit_costs.then_some(audit_tolerance);
+            options.check_mode = check_cost_mode(check_mode, mode)?;
+            options.order = order;
+            options.breakdown = breakdown;
+            options.approx_top = approx_top.map(|n| n as usize);
+            options.diff_since_last_run = diff_since_last_run;
+            options.verbose = verbose;
+            options.cancel = Some(cancel_on_ctrl_c());
+            check_approx_top(&options)?;
+            check_diff_since_last_run(&options)?;
+            set_ranges(&mut options, range)?;
+
+            let metrics = command_metrics(&options);
+            let result = analyzer.run_command("monthly", options).await;
+            finish_metrics(metrics, &analyzer, result.is_ok());
+
+            match result {
+                Ok(_) => Ok(()),
+                Err(e) => handle_error(e, json),
+            }
+        }
+        Commands::Session {
+            json,
+            format,
+            output_version,
+            limit,
+            since,
+            until,
+            exclude_vms,
+            mode,
+            project,
+            redact_projects,
+            breakdown,
+            output,
+            verbose,
+        } => {
+            let json = json || format == OutputFormat::Json;
+            let (_since_date, _until_date, mut analyzer, mut options) = parse_common_args(
+                json,
+                limit,
+                since,
+                until,
+                Command::Session,
+                exclude_vms,
+                false,
+            )?;
+            options.csv_output = format == OutputFormat::Csv;
+            options.output = check_output(output, format, json)?;
+            options.output_version = check_output_version(output_version, json)?;
+            options.project = project;
+            options.redact_projects = redact_projects;
+            options.cost_mode = mode;
+            options.breakdown = breakdown;
+            options.verbose = verbose;
+            options.cancel = Some(cancel_on_ctrl_c());
+
+            let metrics = command_metrics(&options);
+            let result = analyzer.run_command("session", options).await;
+            finish_metrics(metrics, &analyzer, result.is_ok());
+
+            match result {
+                Ok(_) => Ok(()),
+                Err(e) => handle_error(e, json),
+            }
+        }
+        Commands::Top {
+            json,
+            format,
+            limit,
+            since,
+            until,
+            sort,
+            exclude_vms,
+            mode,
+            project,
+            redact_projects,
+            output,
+            verbose,
+        } => {
+            let json = json || format == OutputFormat::Json;
+            let (_since_date, _until_date, mut analyzer, mut options) =
+                parse_common_args(json, limit, since, until, Command::Top, exclude_vms, false)?;
+            options.csv_output = format == OutputFormat::Csv;
+            options.output = check_output(output, format, json)?;
+            options.project = project;
+            options.redact_projects = redact_projects;
+            options.cost_mode = mode;
+            options.verbose = verbose;
+            options.project_sort = sort;
+            options.cancel = Some(cancel_on_ctrl_c());
+
+            let metrics = command_metrics(&options);
+            let result = analyzer.run_command("top", options).await;
+            finish_metrics(metrics, &analyzer, result.is_ok());
+
+            match result {
+                Ok(_) => Ok(()),
+                Err(e) => handle_error(e, json),
+            }
+        }
+        Commands::Blocks {
+            json,
+            limit,
+            since,
+            until,
+            exclude_vms,
+            mode,
+            verbose,
+        } => {
+            let (_since_date, _until_date, analyzer, mut options) = parse_common_args(
+                json,
+                limit,
+                since,
+                until,
+                Command::Blocks,
+                exclude_vms,
+                false,
+            )?;
+            options.cost_mode = mode;
+            options.verbose = verbose;
+            options.cancel = Some(cancel_on_ctrl_c());
+
+            let metrics = command_metrics(&options);
+            let result = commands::blocks::run_blocks(&analyzer, &options);
+            finish_metrics(metrics, &analyzer, result.is_ok());
+
+            match result {
+                Ok(_) => Ok(()),
+                Err(e) => handle_error(e, json),
+            }
+        }
+        Commands::Export {
+            json,
+            format,
+            out,
+            since,
+            until,
+            exclude_vms,
+            mode,
+            project,
+            redact_projects,
+            verbose,
+        } => {
+            let (_since_date, _until_date, analyzer, mut options) = parse_common_args(
+                json,
+                None,
+                since,
+                until,
+                Command::Export,
+                exclude_vms,
+                false,
+            )?;
+            options.project = project;
+            options.redact_projects = redact_projects;
+            options.cost_mode = mode;
+            options.verbose = verbose;
+            options.cancel = Some(cancel_on_ctrl_c());
+
+            let metrics = command_metrics(&options);
+            let out = container::output_path(out)?;
+            let result = commands::export::run_export(&analyzer, &options, format, &out);
+            finish_metrics(metrics, &analyzer, result.is_ok());
+
+            match result {
+                Ok(_) => Ok(()),
+                Err(e) => handle_error(e, json),
+            }
+        }
+        Commands::Reprocess {
+            json,
+            settings,
+            since,
+            until,
+            exclude_vms,
+            mode,
+            project,
+            verbose,
+        } => {
+            let settings = match settings
+                .iter()
+                .map(|spec| spec.parse::<DedupSettings>())
+                .collect::<Result<Vec<_>>>()
+            {
+                Ok(settings) => settings,
+                Err(e) => handle_error(e, json),
+            };
+            let (_since_date, _until_date, analyzer, mut options) = parse_common_args(
+                json,
+                None,
+                since,
+                until,
+                Command::Reprocess,
+                exclude_vms,
+                false,
+            )?;
+            options.project = project;
+            options.cost_mode = mode;
+            options.verbose = verbose;
+            options.cancel = Some(cancel_on_ctrl_c());
+
+            let metrics = command_metrics(&options);
+            let result = commands::reprocess::run_reprocess(&analyzer, &options, &settings);
+            finish_metrics(metrics, &analyzer, result.is_ok());
+
+            match result {
+                Ok(_) => Ok(()),
+                Err(e) => handle_error(e, json),
+            }
+        }
+        Commands::Forecast {
+            json,
+            days,
+            exclude_vms,
+            mode,
+            project,
+        } => {
+            // The average needs the last `days` days, the month-to-date cost the whole month
+            let today = chrono::Local::now().date_naive();
+            let since = forecast::first_day_needed(today, days)
+                .format("%Y-%m-%d")
+                .to_string();
+            let (_since_date, _until_date, mut analyzer, mut options) = parse_common_args(
+                json,
+                None,
+                Some(since),
+                None,
+                Command::Forecast,
+                exclude_vms,
+                false,
+            )?;
+            options.project = project;
+            options.cost_mode = mode;
+            options.forecast_days = days;
+            options.cancel = Some(cancel_on_ctrl_c());
+
+            let metrics = command_metrics(&options);
+            let result = analyzer.run_command("forecast", options).await;
+            finish_metrics(metrics, &analyzer, result.is_ok());
+
+            match result {
+                Ok(_) => Ok(()),
+                Err(e) => handle_error(e, json),
+            }
+        }
+        Commands::Batch {
+            file,
+            queries,
+            exclude_vms,
+            estimate_missing,
         } => {
-            let (_since_date, _until_date, mut analyzer, options) =
-                parse_common_args(json, limit, since, until, "monthly", exclude_vms)?;
+            let queries = match batch::collect_queries(file.as_deref(), &queries) {
+                Ok(queries) => queries,
+                Err(e) => handle_error(e, true),
+            };
+            let (_since_date, _until_date, mut analyzer, options) = parse_common_args(
+                true,
+                None,
+                None,
+                None,
+                Command::Batch,
+                exclude_vms,
+                estimate_missing,
+            )?;
 
-            match analyzer.run_command("monthly", options).await {
+            let metrics = command_metrics(&options);
+            let result = analyzer.run_batch(&queries, options).await;
+            finish_metrics(metrics, &analyzer, result.is_ok());
+
+            match result {
+                Ok(output) => {
+                    println!("{}", serde_json::to_string_pretty(&output)?);
+                    Ok(())
+                }
+                Err(e) => handle_error(e, true),
+            }
+        }
+        Commands::Web {
+            port,
+            refresh,
+            exclude_vms,
+        } => {
+            let (_since_date, _until_date, _analyzer, options) =
+                parse_common_args(true, None, None, None, Command::Web, exclude_vms, false)?;
+            web::run_web_server(port, std::time::Duration::from_secs(refresh), options).await
+        }
+        Commands::Serve {
+            prometheus: _,
+            bind,
+            port,
+            refresh,
+            exclude_vms,
+        } => {
+            let refresh = std::time::Duration::from_secs(refresh);
+            web::run_prometheus_server(&bind, port, refresh, exclude_vms).await
+        }
+        Commands::Status {
+            json,
+            listen,
+            bind,
+            refresh,
+            exclude_vms,
+        } => {
+            let monthly_budget = get_config().budget.monthly_usd;
+            if let Some(port) = listen {
+                let refresh = std::time::Duration::from_secs(refresh);
+                return web::run_status_server(&bind, port, refresh, exclude_vms, monthly_budget)
+                    .await;
+            }
+
+            let result = commands::status::run_status(json, exclude_vms, monthly_budget);
+            match result {
                 Ok(_) => Ok(()),
                 Err(e) => handle_error(e, json),
             }
         }
-        Commands::Live { no_baseline } => {
-            match commands::live::run_live_mode(no_baseline).await {
+        Commands::Check {
+            json,
+            max_daily,
+            max_monthly,
+            dry_run,
+            exclude_vms,
+        } => {
+            let limits = commands::check::SpendLimits {
+                max_daily,
+                max_monthly,
+            };
+            match commands::check::run_check(json, exclude_vms, dry_run, limits).await {
+                Ok(_) => Ok(()),
+                Err(e) => handle_error(e, json),
+            }
+        }
+        Commands::Live {
+            no_baseline,
+            projects,
+            json,
+            reconcile_on_exit,
+        } => {
+            match commands::live::run_live_mode(no_baseline, &projects, json, reconcile_on_exit)
+                .await
+            {
                 Ok(_) => Ok(()),
                 Err(e) => {
                     error!(error = %e, "Live mode failed");
-                    
-                    // Provide user-friendly error messages with actionable guidance
-                    let error_msg = e.to_string();
-                    
-                    if error_msg.contains("claude-keeper not found") {
-                        eprintln!("❌ Claude Keeper Not Found");
-                        eprintln!();
-                        eprintln!("Claude Usage Live requires claude-keeper to be installed.");
-                        eprintln!("Please install it first:");
-                        eprintln!();
-                        eprintln!("📦 Installation options:");
-                        eprintln!("   • Visit: https://github.com/mufeedvh/claude-keeper");
-                        eprintln!("   • Or use: cargo install claude-keeper");
-                        eprintln!();
-                        eprintln!("After installation, try running 'claude-usage live' again.");
-                    } else if error_msg.contains("permission") {
-                        eprintln!("❌ Permission Error");
-                        eprintln!();
-                        eprintln!("Claude-keeper cannot be executed due to permission issues.");
-                        eprintln!();
-                        eprintln!("🔧 Try these solutions:");
-                        eprintln!("   • Make sure claude-keeper is executable: chmod +x $(which claude-keeper)");
-                        eprintln!("   • Check if claude-keeper is in your PATH");
-                        eprintln!("   • Run with appropriate permissions");
-                    } else if error_msg.contains("backup directory") || error_msg.contains("No such file") {
-                        eprintln!("❌ Configuration Issue");
-                        eprintln!();
-                        eprintln!("Unable to access Claude conversation data.");
-                        eprintln!();
-                        eprintln!("💡 Possible solutions:");
-                        eprintln!("   • Make sure Claude Desktop is installed and has been used");
-                        eprintln!("   • Check that ~/.claude/backups directory exists");
-                        eprintln!("   • Try running: claude-keeper backup");
-                        eprintln!("   • Or use: claude-usage live --no-baseline");
-                    } else {
-                        eprintln!("❌ Live Mode Failed");
-                        eprintln!();
-                        eprintln!("Error: {}", e);
-                        eprintln!();
-                        eprintln!("💡 Troubleshooting tips:");
-                        eprintln!("   • Check that claude-keeper is installed and working");
-                        eprintln!("   • Verify Claude Desktop is properly configured");
-                        eprintln!("   • Try running with --no-baseline flag");
-                        eprintln!("   • Check the logs for more details");
-                    }
-                    
+                    commands::doctor::print_live_failure(&e);
                     Err(e)
                 }
             }
         }
-        Commands::TestCompat { since, until } => {
+        Commands::TestCompat { since, until, mode } => {
             println!("🧪 Testing CCUsage Compatibility Mode");
             println!("=====================================");
             
@@ -203,20 +1283,23 @@ async fn main() -> Result<()> {
             match ccusage_compat::get_ccusage_compatible_cost(
                 since_yyyymmdd.as_deref(),
                 until_yyyymmdd.as_deref(),
+                mode,
             ).await {
                 Ok(cost) => {
                     println!("\n✅ CCUsage-compatible cost: ${:.2}", cost);
                     println!("\nThis should match ccusage's output exactly.");
                     
                     // Also run normal mode for comparison
-                    let (_since_date, _until_date, mut analyzer, options) =
-                        parse_common_args(false, None, since.clone(), until.clone(), "daily", false)?;
+                    let (_since_date, _until_date, analyzer, mut options) =
+                        parse_common_args(false, None, since.clone(), until.clone(), Command::Daily, false, false)?;
+                    options.cost_mode = mode;
                     
-                    match analyzer.aggregate_data("daily", options).await {
+                    match analyzer.sessions(AnalyzerRequest::with_options(ReportKind::Daily, options)).await {
                         Ok(sessions) => {
                             let normal_cost: f64 = sessions.iter()
                                 .map(|s| s.total_cost)
-                                .sum();
+                                .sum::<models::MicroDollars>()
+                                .to_dollars();
                             println!("\n📊 Normal mode cost: ${:.2}", normal_cost);
                             
                             let diff = (cost - normal_cost).abs();
@@ -239,6 +1322,76 @@ async fn main() -> Result<()> {
                 }
             }
         }
+        Commands::Metrics { action } => match action {
+            MetricsAction::Show { json } => match commands::metrics::run_metrics_show(json) {
+                Ok(_) => Ok(()),
+                Err(e) => handle_error(e, json),
+            },
+        },
+        Commands::Inspect { file, json } => match commands::inspect::run_inspect(&file, json) {
+            Ok(_) => Ok(()),
+            Err(e) => handle_error(e, json),
+        },
+        Commands::Hook { action } => {
+            let result = match action {
+                HookAction::Install { force } => commands::hook::run_hook_install(force),
+                HookAction::Trailer => commands::hook::run_hook_trailer(),
+            };
+            match result {
+                Ok(_) => Ok(()),
+                Err(e) => handle_error(e, false),
+            }
+        }
+        Commands::Note { action } => match action {
+            NoteAction::Add { date, text } => match commands::notes::run_note_add(&date, &text) {
+                Ok(_) => Ok(()),
+                Err(e) => handle_error(e, false),
+            },
+        },
+        Commands::Archive { action } => match action {
+            ArchiveAction::Compact {
+                older_than,
+                delete_originals,
+                dry_run,
+                json,
+            } => {
+                let options = archive::CompactOptions {
+                    older_than_months: older_than,
+                    delete_originals,
+                    dry_run,
+                };
+                match commands::archive::run_archive_compact(&options, json) {
+                    Ok(_) => Ok(()),
+                    Err(e) => handle_error(e, json),
+                }
+            }
+        },
+        Commands::Pricing { action } => match action {
+            PricingAction::Refresh { json } => {
+                match commands::pricing::run_pricing_refresh(json).await {
+                    Ok(_) => Ok(()),
+                    Err(e) => handle_error(e, json),
+                }
+            }
+            PricingAction::Show { model, json } => {
+                match commands::pricing::run_pricing_show(&model, json) {
+                    Ok(_) => Ok(()),
+                    Err(e) => handle_error(e, json),
+                }
+            }
+        },
+        Commands::Doctor { json } => match commands::doctor::run_doctor(json) {
+            Ok(true) => Ok(()),
+            Ok(false) => std::process::exit(EXIT_DOCTOR_FAILED),
+            Err(e) => handle_error(e, json),
+        },
+        Commands::VerifyExport { manifest, json } => {
+            match commands::verify_export::run_verify_export(&manifest, json) {
+                Ok(true) => Ok(()),
+                Ok(false) => std::process::exit(EXIT_VERIFY_FAILED),
+                Err(e) => handle_error(e, json),
+            }
+        }
     }
 }
 
@@ -247,8 +1400,9 @@ fn parse_common_args(
     limit: Option<usize>,
     since: Option<String>,
     until: Option<String>,
-    command: &str,
+    command: Command,
     exclude_vms: bool,
+    estimate_missing: bool,
 ) -> Result<(
     Option<chrono::DateTime<chrono::Utc>>,
     Option<chrono::DateTime<chrono::Utc>>,
@@ -296,26 +1450,218 @@ fn parse_common_args(
     let analyzer = ClaudeUsageAnalyzer::new();
 
     // Build options
-    let options = ProcessOptions {
-        command: command.to_string(),
-        json_output: json,
-        limit,
-        since_date,
-        until_date,
-        snapshot: false,
-        exclude_vms,
-    };
+    let mut builder = ProcessOptions::builder()
+        .command(command)
+        .json_output(json)
+        .exclude_vms(exclude_vms)
+        .estimate_missing_usage(estimate_missing || get_config().processing.estimate_missing_usage);
+    if let Some(limit) = limit {
+        builder = builder.limit(limit);
+    }
+    if let Some(since) = since_date {
+        builder = builder.since(since);
+    }
+    if let Some(until) = until_date {
+        builder = builder.until(until);
+    }
+    let options = builder.build()?;
 
     Ok((since_date, until_date, analyzer, options))
 }
 
-fn handle_error(e: anyhow::Error, json: bool) -> Result<(), anyhow::Error> {
+/// Start measuring a report command, noting which options it uses
+fn command_metrics(options: &ProcessOptions) -> MetricsRecorder {
+    let mut metrics = MetricsRecorder::start(&options.command);
+    metrics.feature("json", options.json_output);
+    metrics.feature("csv", options.csv_output);
+    metrics.feature("limit", options.limit.is_some());
+    metrics.feature("since", options.since_date.is_some());
+    metrics.feature("until", options.until_date.is_some());
+    metrics.feature("exclude_vms", options.exclude_vms);
+    metrics.feature("estimate_missing", options.estimate_missing_usage);
+    metrics.feature("provenance", options.provenance);
+    metrics.feature("cost_mode", options.cost_mode != CostMode::Auto);
+    metrics.feature("group_by_instance_type", options.group_by == GroupBy::InstanceType);
+    metrics.feature("group_by_cost_center", options.group_by == GroupBy::CostCenter);
+    metrics.feature("group_by_branch", options.group_by == GroupBy::Branch);
+    metrics.feature("group_by_root", options.group_by == GroupBy::Root);
+    metrics.feature("group_by_vm", options.group_by == GroupBy::Vm);
+    metrics.feature("group_by_model", options.group_by == GroupBy::Model);
+    metrics.feature("tokens", options.show_tokens);
+    metrics.feature("details", options.show_details);
+    metrics.feature("audit_costs", options.audit_costs.is_some());
+    metrics.feature("check_mode", options.check_mode);
+    metrics.feature("order", options.order.is_some());
+    metrics.feature("breakdown", options.breakdown);
+    metrics.feature(
+        "output_version",
+        options.output_version != output_version::LATEST,
+    );
+    metrics.feature("output", options.output.is_some());
+    metrics.feature("approx_top", options.approx_top.is_some());
+    metrics.feature("diff_since_last_run", options.diff_since_last_run);
+    metrics.feature("project", options.project.is_some());
+    metrics.feature("verbose", options.verbose);
+    metrics.feature("project_sort", options.project_sort != ProjectSort::Cost);
+    metrics
+}
+
+/// Record a finished report command and run the `run_completed` hook
+fn finish_metrics(mut metrics: MetricsRecorder, analyzer: &ClaudeUsageAnalyzer, success: bool) {
+    metrics.set_sessions(analyzer.sessions_processed());
+    let metric = metrics.finish(success);
+    hooks::fire_and_wait(HookEvent::RunCompleted, &metric);
+}
+
+/// Cancel the returned token on the first Ctrl+C, so the report is printed
+/// from the data read so far; a second Ctrl+C exits right away
+fn cancel_on_ctrl_c() -> CancellationToken {
+    let cancel = CancellationToken::new();
+    let token = cancel.clone();
+    tokio::spawn(async move {
+        while tokio::signal::ctrl_c().await.is_ok() {
+            if token.is_cancelled() {
+                std::process::exit(EXIT_INTERRUPTED);
+            }
+            eprintln!("\nInterrupted, finishing with the data read so far (Ctrl+C again to quit)");
+            token.cancel();
+        }
+    });
+    cancel
+}
+
+/// Exit code of a second Ctrl+C, as for a shell job killed by SIGINT
+const EXIT_INTERRUPTED: i32 = 130;
+
+/// Exit code when `verify-export` finds a modified or missing artifact
+const EXIT_VERIFY_FAILED: i32 = 4;
+
+/// Exit code when `doctor` finds a problem reports cannot work around
+const EXIT_DOCTOR_FAILED: i32 = 9;
+
+/// Accept `--output` only for the JSON and CSV formats
+fn check_output(
+    output: Option<std::path::PathBuf>,
+    format: OutputFormat,
+    json: bool,
+) -> Result<Option<std::path::PathBuf>> {
+    if output.is_some() && !json && format != OutputFormat::Csv {
+        anyhow::bail!("--output needs --json or --format csv");
+    }
+    output.map(container::output_path).transpose()
+}
+
+/// `--check-mode` chooses a mode in place of auto mode, so it needs auto mode
+fn check_cost_mode(check_mode: bool, mode: CostMode) -> Result<bool> {
+    if check_mode && mode != CostMode::Auto {
+        anyhow::bail!("--check-mode only applies to --mode auto");
+    }
+    Ok(check_mode)
+}
+
+/// Refuse the commands that write outside the output directory in container mode
+fn check_container_command(command: Option<&Commands>) -> Result<()> {
+    if get_config().container.output_dir.is_none() {
+        return Ok(());
+    }
+    let refused = match command {
+        Some(Commands::Live { .. }) => "live",
+        Some(Commands::Hook {
+            action: HookAction::Install { .. },
+        }) => "hook install",
+        Some(Commands::Archive {
+            action:
+                ArchiveAction::Compact {
+                    delete_originals: true,
+                    ..
+                },
+        }) => "archive compact --delete-originals",
+        _ => return Ok(()),
+    };
+    anyhow::bail!("{} writes outside the container output directory", refused)
+}
+
+/// `--output-version` only shapes JSON, and an older version gets a deprecation warning
+fn check_output_version(version: Option<u32>, json: bool) -> Result<u32> {
+    let Some(version) = version else {
+        return Ok(output_version::LATEST);
+    };
+    if !json {
+        anyhow::bail!("--output-version needs --json");
+    }
+    output_version::check(version)?;
+    if let Some(deprecation) = output_version::deprecation(version) {
+        eprintln!("⚠️  {}", deprecation);
+    }
+    Ok(version)
+}
+
+/// `--approx-top` replaces the report with its own table or JSON document
+fn check_approx_top(options: &ProcessOptions) -> Result<()> {
+    if options.approx_top.is_some() && (options.csv_output || options.output.is_some()) {
+        anyhow::bail!("--approx-top cannot be combined with --format csv or --output");
+    }
+    Ok(())
+}
+
+/// Report `ranges`, scanning once over the span covering all of them
+fn set_ranges(options: &mut ProcessOptions, ranges: Vec<DateRange>) -> Result<()> {
+    let Some((since, until)) = ranges::span(&ranges) else {
+        return Ok(());
+    };
+    if options.csv_output || options.output.is_some() {
+        anyhow::bail!("--range cannot be combined with --format csv or --output");
+    }
+    options.since_date = Some(since);
+    options.until_date = Some(until);
+    options.ranges = ranges;
+    Ok(())
+}
+
+fn check_diff_since_last_run(options: &ProcessOptions) -> Result<()> {
+    if options.diff_since_last_run
+        && (options.csv_output || options.output.is_some() || options.approx_top.is_some())
+    {
+        anyhow::bail!(
+            "--diff-since-last-run cannot be combined with --format csv, --output or --approx-top"
+        );
+    }
+    Ok(())
+}
+
+/// Parse a `--max-error-rate` percentage between 0 and 100
+fn parse_percentage(value: &str) -> Result<f64, String> {
+    let percent: f64 = value
+        .parse()
+        .map_err(|_| format!("{} is not a number", value))?;
+    if (0.0..=100.0).contains(&percent) {
+        Ok(percent)
+    } else {
+        Err(format!("{} is not between 0 and 100", value))
+    }
+}
+
+/// Parse a dollar amount of at least zero
+fn parse_dollars(value: &str) -> Result<f64, String> {
+    let dollars: f64 = value
+        .parse()
+        .map_err(|_| format!("{} is not a number", value))?;
+    if dollars >= 0.0 && dollars.is_finite() {
+        Ok(dollars)
+    } else {
+        Err(format!("{} is not a dollar amount", value))
+    }
+}
+
+/// Report a failed command and exit with the code for its kind of failure,
+/// so pipelines can tell failures apart
+fn handle_error(e: impl Into<CliError>, json: bool) -> ! {
+    let e = e.into();
+    error!(error = %e.error, code = e.code.as_str(), "Command failed");
     if json {
-        error!(error = %e, "Command failed");
-        println!("{{\"error\": \"{}\"}}", e);
+        println!("{}", e.to_json());
     } else {
-        error!(error = %e, "Command failed");
-        eprintln!("Error: {}", e);
+        eprintln!("Error: {}", e.error);
     }
-    Err(e)
+    std::process::exit(e.code.exit_code())
 }
@@ -2,9 +2,16 @@
 //!
 //! This module provides enhanced memory tracking and pressure management
 //! with atomic-based tracking and adaptive sizing capabilities.
+//!
+//! Usage is the process's resident set where the platform reports it (Linux)
+//! and the allocations recorded with [`track_allocation`] elsewhere. As usage
+//! approaches `memory.max_memory_mb`, parsing runs on fewer workers with
+//! smaller read buffers; once it exceeds the limit, parsing stops with
+//! [`MemoryLimitExceeded`] instead of running until the system kills it.
 
 use crate::config::get_config;
 use anyhow::Result;
+use std::fmt;
 use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::OnceLock;
 use tracing::{debug, error, warn};
@@ -13,6 +20,7 @@ use tracing::{debug, error, warn};
 static MEMORY_LIMIT: AtomicUsize = AtomicUsize::new(0);
 static CURRENT_USAGE: AtomicUsize = AtomicUsize::new(0);
 static MEMORY_INITIALIZED: OnceLock<()> = OnceLock::new();
+#[allow(dead_code)]
 static LAST_WARNING_TIME: AtomicUsize = AtomicUsize::new(0);
 
 /// Memory pressure levels for adaptive behavior
@@ -26,12 +34,33 @@ pub enum MemoryPressureLevel {
 
 /// Memory statistics for monitoring
 #[derive(Debug, Clone)]
+#[allow(dead_code)]
 pub struct MemoryStats {
     pub current_usage: usize,
     pub memory_limit: usize,
     pub usage_percentage: f64,
 }
 
+/// Usage went over `memory.max_memory_mb`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MemoryLimitExceeded {
+    pub used_mb: usize,
+    pub limit_mb: usize,
+}
+
+impl fmt::Display for MemoryLimitExceeded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Memory limit exceeded: using {} MB of {} MB. Raise [memory] max_memory_mb \
+             (or CLAUDE_USAGE_MAX_MEMORY_MB), or narrow the report with --since or --project",
+            self.used_mb, self.limit_mb
+        )
+    }
+}
+
+impl std::error::Error for MemoryLimitExceeded {}
+
 /// Initialize the global memory limit with configuration
 pub fn init_memory_limit() {
     let config = get_config();
@@ -56,6 +85,7 @@ fn ensure_initialized() {
 
 /// Check if we're approaching memory limit (backward compatibility)
 /// Maps MemoryPressureLevel to boolean for existing code
+#[allow(dead_code)]
 pub fn check_memory_pressure() -> bool {
     ensure_initialized();
     let pressure = get_pressure_level();
@@ -87,6 +117,7 @@ pub fn check_memory_pressure() -> bool {
 }
 
 /// Track approximate memory allocation (backward compatibility)
+#[allow(dead_code)]
 pub fn track_allocation(bytes: usize) {
     ensure_initialized();
     let limit = MEMORY_LIMIT.load(Ordering::Relaxed);
@@ -103,6 +134,7 @@ pub fn track_allocation(bytes: usize) {
 }
 
 /// Track approximate memory deallocation (backward compatibility)
+#[allow(dead_code)]
 pub fn track_deallocation(bytes: usize) {
     ensure_initialized();
     // Use saturating_sub to prevent underflow
@@ -111,6 +143,49 @@ pub fn track_deallocation(bytes: usize) {
     CURRENT_USAGE.store(new_usage, Ordering::Relaxed);
 }
 
+/// Memory in use: the resident set where the platform reports it, otherwise
+/// the tracked allocations
+fn current_usage() -> usize {
+    resident_set_bytes().unwrap_or_else(|| CURRENT_USAGE.load(Ordering::Relaxed))
+}
+
+/// Resident set size of this process, from `/proc/self/status`
+fn resident_set_bytes() -> Option<usize> {
+    let status = std::fs::read_to_string("/proc/self/status").ok()?;
+    let line = status.lines().find(|line| line.starts_with("VmRSS:"))?;
+    let kb: usize = line.split_whitespace().nth(1)?.parse().ok()?;
+    Some(kb * 1024)
+}
+
+/// Fail once usage is over the limit
+///
+/// Called between files, so a run stops with a clear error before it grows
+/// far enough past `memory.max_memory_mb` to be killed by the system.
+pub fn check_memory_limit() -> Result<(), MemoryLimitExceeded> {
+    ensure_initialized();
+    within_limit(current_usage(), MEMORY_LIMIT.load(Ordering::Relaxed))
+}
+
+fn within_limit(current: usize, limit: usize) -> Result<(), MemoryLimitExceeded> {
+    if limit > 0 && current > limit {
+        return Err(MemoryLimitExceeded {
+            used_mb: current / 1_000_000,
+            limit_mb: limit / 1_000_000,
+        });
+    }
+    Ok(())
+}
+
+/// Number of workers to keep busy out of `workers` under the current pressure
+pub fn get_adaptive_workers(workers: usize) -> usize {
+    ensure_initialized();
+    match get_pressure_level() {
+        MemoryPressureLevel::Low | MemoryPressureLevel::Normal => workers,
+        MemoryPressureLevel::High => (workers / 2).max(1),
+        MemoryPressureLevel::Critical => 1,
+    }
+}
+
 /// Get current memory usage estimate in MB (backward compatibility)
 #[allow(dead_code)]
 pub fn get_memory_usage_mb() -> usize {
@@ -135,9 +210,10 @@ pub fn get_adaptive_batch_size(default_size: usize) -> usize {
 }
 
 /// Get detailed memory statistics
+#[allow(dead_code)]
 pub fn get_memory_stats() -> MemoryStats {
     ensure_initialized();
-    let current = CURRENT_USAGE.load(Ordering::Relaxed);
+    let current = current_usage();
     let limit = MEMORY_LIMIT.load(Ordering::Relaxed);
     let percentage = if limit > 0 {
         (current as f64 / limit as f64) * 100.0
@@ -153,6 +229,7 @@ pub fn get_memory_stats() -> MemoryStats {
 }
 
 /// Check if we should spill to disk due to critical memory pressure
+#[allow(dead_code)]
 pub fn should_spill_to_disk() -> bool {
     ensure_initialized();
     matches!(get_pressure_level(), MemoryPressureLevel::Critical)
@@ -161,7 +238,7 @@ pub fn should_spill_to_disk() -> bool {
 /// Get current memory pressure level
 pub fn get_pressure_level() -> MemoryPressureLevel {
     ensure_initialized();
-    let current = CURRENT_USAGE.load(Ordering::Relaxed);
+    let current = current_usage();
     let limit = MEMORY_LIMIT.load(Ordering::Relaxed);
 
     if limit == 0 {
@@ -179,6 +256,7 @@ pub fn get_pressure_level() -> MemoryPressureLevel {
 }
 
 /// Attempt to trigger garbage collection if memory pressure is high
+#[allow(dead_code)]
 pub fn try_gc_if_needed() -> Result<()> {
     ensure_initialized();
     match get_pressure_level() {
@@ -251,6 +329,17 @@ mod tests {
         // Boolean value is always true or false, assertion is redundant
     }
 
+    #[test]
+    fn test_usage_over_limit_is_an_error() {
+        assert!(within_limit(400_000_000, 512_000_000).is_ok());
+        assert!(within_limit(usize::MAX, 0).is_ok());
+
+        let exceeded = within_limit(600_000_000, 512_000_000).unwrap_err();
+        assert_eq!(exceeded.used_mb, 600);
+        assert_eq!(exceeded.limit_mb, 512);
+        assert!(exceeded.to_string().contains("max_memory_mb"));
+    }
+
     #[test]
     fn test_pressure_level() {
         init_memory_limit();
@@ -19,23 +19,35 @@
 //! - [`UsageEntry`] - Top-level wrapper for a single usage record
 //! - [`MessageData`] - Information about the specific Claude message/interaction
 //! - [`UsageData`] - Token consumption details (input, output, cache operations)
+//! - [`SecondaryUsage`] - Usage attributed to a draft/speculative model in the same entry
 //!
 //! ### Session Management
 //! - [`SessionData`] - Internal session tracking with daily breakdowns
 //! - [`SessionOutput`] - External-facing session summary for reports
 //! - [`DailyUsage`] - Per-day usage summary within a session
+//! - [`ModelSwitches`] - How often a session moved between model families
+//! - [`SessionDuration`] - When a session started and ended, and how long it was active
+//! - [`InstanceType`] - Whether usage came from the host, a VM or a devcontainer
 //!
 //! ### Report Generation
 //! - [`DailyData`] - Daily usage report with project breakdown
 //! - [`DailyProject`] - Project-specific usage within a day
 //! - [`MonthlyData`] - Monthly usage summary
+//! - [`MonthToDateComparison`] - Current month so far vs the same days last month
+//! - [`DayTotals`] - Totals for a single day, for quick lookups
 //!
 //! ### Session Blocks
 //! - [`SessionBlock`] - Time-bounded session information from Claude's session tracking
-//! - [`TokenCounts`] - Structured token usage counts for session blocks
+//! - [`TokenCounts`] - Structured token usage counts for session blocks and daily report rows
+//! - [`TokenCosts`] - Cost split by token type for daily and session report rows
 //!
 //! ### Pricing Data
 //! - [`PricingData`] - Cost per token for different token types and models
+//! - [`MicroDollars`] - Fixed-point cost used for all accumulated totals
+//!
+//! ### Entry Exports
+//! - [`EntryExport`] - Per-entry record for JSON exports
+//! - [`Provenance`] - Source file, line, instance and dedup hash of an entry
 //!
 //! ## Features
 //!
@@ -44,8 +56,93 @@
 //! - **Token Calculation**: Automatic total token computation
 //! - **Type Safety**: Strong typing prevents common data manipulation errors
 
-use serde::{Deserialize, Serialize};
-use std::collections::{HashMap, HashSet};
+use crate::pipeline::FailedFile;
+use crate::session_utils::SessionUtils;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::iter::Sum;
+use std::ops::{Add, AddAssign, Sub};
+
+/// A cost in fixed-point micro-dollars (1 USD = 1,000,000)
+///
+/// Per-entry costs are priced as `f64` and rounded to the micro-dollar once;
+/// every total is then accumulated as an integer, so summing millions of
+/// entries does not drift. Values convert back to dollars only for display,
+/// and serialize as a dollar amount so JSON output keeps its shape.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct MicroDollars(pub i64);
+
+impl MicroDollars {
+    pub const ZERO: MicroDollars = MicroDollars(0);
+    const PER_DOLLAR: f64 = 1_000_000.0;
+
+    /// Round a dollar amount to the nearest micro-dollar
+    pub fn from_dollars(dollars: f64) -> Self {
+        if dollars.is_finite() {
+            Self((dollars * Self::PER_DOLLAR).round() as i64)
+        } else {
+            Self::ZERO
+        }
+    }
+
+    pub fn to_dollars(self) -> f64 {
+        self.0 as f64 / Self::PER_DOLLAR
+    }
+}
+
+impl Add for MicroDollars {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self(self.0 + other.0)
+    }
+}
+
+impl AddAssign for MicroDollars {
+    fn add_assign(&mut self, other: Self) {
+        self.0 += other.0;
+    }
+}
+
+impl Sub for MicroDollars {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        Self(self.0 - other.0)
+    }
+}
+
+impl Sum for MicroDollars {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        Self(iter.map(|cost| cost.0).sum())
+    }
+}
+
+impl<'a> Sum<&'a MicroDollars> for MicroDollars {
+    fn sum<I: Iterator<Item = &'a Self>>(iter: I) -> Self {
+        Self(iter.map(|cost| cost.0).sum())
+    }
+}
+
+/// Formats as dollars, honoring precision (`format!("${:.2}", cost)`)
+impl std::fmt::Display for MicroDollars {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        std::fmt::Display::fmt(&self.to_dollars(), f)
+    }
+}
+
+impl Serialize for MicroDollars {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_f64(self.to_dollars())
+    }
+}
+
+impl<'de> Deserialize<'de> for MicroDollars {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        f64::deserialize(deserializer).map(Self::from_dollars)
+    }
+}
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UsageEntry {
@@ -62,9 +159,18 @@ pub struct MessageData {
     pub id: String,
     pub model: String,
     pub usage: Option<UsageData>, // Make usage optional to match Python behavior
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub secondary_usage: Option<SecondaryUsage>,
 }
 
+/// Usage reported for a secondary (draft/speculative) model alongside the primary one
 #[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SecondaryUsage {
+    pub model: String,
+    pub usage: UsageData,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct UsageData {
     #[serde(rename = "input_tokens")]
     pub input_tokens: u32,
@@ -76,13 +182,116 @@ pub struct UsageData {
     pub cache_read_input_tokens: u32,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct DailyUsage {
     pub input_tokens: u32,
     pub output_tokens: u32,
     pub cache_creation_tokens: u32,
     pub cache_read_tokens: u32,
-    pub cost: f64,
+    pub cost: MicroDollars,
+    pub estimated_entries: u32, // Entries whose tokens were estimated from content
+    pub model_costs: HashMap<String, MicroDollars>, // Cost attributed to each model
+    pub model_tokens: HashMap<String, TokenCounts>, // Tokens attributed to each model
+    /// Cost contributed by each token type
+    pub costs: TokenCosts,
+    /// Times of the day's first and last entry
+    pub work_started: Option<DateTime<Utc>>,
+    pub work_ended: Option<DateTime<Utc>>,
+}
+
+impl DailyUsage {
+    /// Widen the day's working hours to cover an entry at `at`
+    pub fn record_entry_time(&mut self, at: DateTime<Utc>) {
+        self.work_started = Some(self.work_started.map_or(at, |started| started.min(at)));
+        self.work_ended = Some(self.work_ended.map_or(at, |ended| ended.max(at)));
+    }
+
+    /// Attribute an entry's tokens and cost for `model` to this day
+    pub fn add_model_usage(&mut self, model: &str, tokens: TokenCounts, cost: MicroDollars) {
+        self.costs += crate::pricing::split_cost(model, &tokens, cost);
+        *self.model_costs.entry(model.to_string()).or_default() += cost;
+        *self.model_tokens.entry(model.to_string()).or_default() += tokens;
+    }
+}
+
+/// Kind of machine a Claude instance runs on
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum InstanceType {
+    #[default]
+    Host,
+    Vm,
+    Devcontainer,
+}
+
+impl InstanceType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            InstanceType::Host => "host",
+            InstanceType::Vm => "vm",
+            InstanceType::Devcontainer => "devcontainer",
+        }
+    }
+
+    /// Classify a path by convention: roots inside a devcontainer workspace
+    /// (`/workspaces/...` or a `.devcontainer`/`devcontainers` folder) are
+    /// devcontainers, roots under a `vms` directory are VMs, everything else
+    /// is the host
+    pub fn from_path(path: &std::path::Path) -> Self {
+        let components: Vec<String> = path
+            .components()
+            .filter_map(|c| match c {
+                std::path::Component::Normal(name) => Some(name.to_string_lossy().to_string()),
+                _ => None,
+            })
+            .collect();
+
+        let in_devcontainer = path.starts_with("/workspaces")
+            || components
+                .iter()
+                .any(|c| c == ".devcontainer" || c == "devcontainers");
+        if in_devcontainer {
+            return InstanceType::Devcontainer;
+        }
+
+        // A VM root is a directory *inside* `vms`, not the `vms` folder itself
+        let under_vms = components
+            .iter()
+            .rev()
+            .skip(1)
+            .any(|c| c == "vms");
+        if under_vms {
+            return InstanceType::Vm;
+        }
+
+        InstanceType::Host
+    }
+
+    /// Name of the VM a path belongs to: the directory right under its last
+    /// `vms` component (`~/.claude/vms/<name>/...`)
+    pub fn vm_name(path: &std::path::Path) -> Option<String> {
+        let components: Vec<_> = path.components().collect();
+        let vms = components.iter().rposition(|c| c.as_os_str() == "vms")?;
+        components
+            .get(vms + 1)
+            .map(|name| name.as_os_str().to_string_lossy().to_string())
+    }
+
+    /// Parse a type name as written in marker files or backup rows
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "host" => Some(InstanceType::Host),
+            "vm" => Some(InstanceType::Vm),
+            "devcontainer" | "container" => Some(InstanceType::Devcontainer),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for InstanceType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -93,16 +302,132 @@ pub struct SessionData {
     pub output_tokens: u32,
     pub cache_creation_tokens: u32,
     pub cache_read_tokens: u32,
-    pub total_cost: f64,
+    pub total_cost: MicroDollars,
     pub last_activity: Option<String>,
     pub models_used: HashSet<String>,
     pub daily_usage: HashMap<String, DailyUsage>, // Track usage per day
+    pub estimated_entries: u32,
+    pub model_costs: HashMap<String, MicroDollars>,
+    pub instance_type: InstanceType,
+    /// Extra Claude root the session was read from; `None` for the home
+    pub root: Option<String>,
+    /// VM the session ran in (see [`InstanceType::vm_name`])
+    pub vm: Option<String>,
+    /// Timestamp, primary model and cost of each entry, for [`ModelSwitches`]
+    pub model_timeline: Vec<(String, String, MicroDollars)>,
+    /// Time of each entry, for [`SessionDuration`]
+    pub entry_times: Vec<DateTime<Utc>>,
 }
 
-#[derive(Debug, Clone, Serialize)]
+/// When a session ran, and how much of that time it was active
+///
+/// Gaps between consecutive entries longer than the idle threshold
+/// (`sessions.idle_gap_minutes`) count as idle time, so a session left open
+/// overnight is not reported as active for the whole night.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionDuration {
+    pub started: DateTime<Utc>,
+    pub ended: DateTime<Utc>,
+    /// Time between entries, leaving out idle gaps
+    pub active_secs: i64,
+    /// Gaps between entries longer than the idle threshold
+    pub idle_gaps: u32,
+}
+
+impl SessionDuration {
+    /// Duration of a session with entries at `times`, in any order
+    pub fn from_entry_times(times: &[DateTime<Utc>], idle_gap: chrono::Duration) -> Option<Self> {
+        let mut ordered = times.to_vec();
+        ordered.sort();
+        let mut duration = Self {
+            started: *ordered.first()?,
+            ended: *ordered.last()?,
+            active_secs: 0,
+            idle_gaps: 0,
+        };
+        for pair in ordered.windows(2) {
+            let gap = pair[1] - pair[0];
+            if gap > idle_gap {
+                duration.idle_gaps += 1;
+            } else {
+                duration.active_secs += gap.num_seconds();
+            }
+        }
+        Some(duration)
+    }
+
+    /// Time from the first entry to the last, idle gaps included
+    pub fn span_secs(&self) -> i64 {
+        (self.ended - self.started).num_seconds()
+    }
+
+    /// `secs` as `1h 05m`, `12m 30s` or `45s`
+    pub fn format_secs(secs: i64) -> String {
+        let (hours, minutes, seconds) = (secs / 3600, secs % 3600 / 60, secs % 60);
+        if hours > 0 {
+            format!("{}h {:02}m", hours, minutes)
+        } else if minutes > 0 {
+            format!("{}m {:02}s", minutes, seconds)
+        } else {
+            format!("{}s", seconds)
+        }
+    }
+}
+
+/// Moves between model families (opus, sonnet, haiku) within a session, such
+/// as a fallback to a cheaper model and back
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ModelSwitches {
+    pub count: u32,
+    /// Families in the order they were used, repeats collapsed
+    pub sequence: Vec<String>,
+    /// Cost of entries made on a family other than the session's first
+    pub switched_cost: MicroDollars,
+}
+
+impl ModelSwitches {
+    /// Summarize a session's entries, ordered by timestamp
+    pub fn from_timeline(timeline: &[(String, String, MicroDollars)]) -> Self {
+        let mut ordered: Vec<&(String, String, MicroDollars)> = timeline.iter().collect();
+        ordered.sort_by(|a, b| a.0.cmp(&b.0));
+
+        let mut switches = Self::default();
+        for (_, model, cost) in ordered {
+            let family = model_family(model);
+            if switches.sequence.last() != Some(&family) {
+                switches.sequence.push(family.clone());
+            }
+            if switches.sequence.first() != Some(&family) {
+                switches.switched_cost += *cost;
+            }
+        }
+        switches.count = switches.sequence.len().saturating_sub(1) as u32;
+        switches
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+}
+
+/// Family of a Claude model (`opus`, `sonnet` or `haiku`), or the model name
+/// itself when it belongs to none of them
+pub fn model_family(model: &str) -> String {
+    ["opus", "sonnet", "haiku"]
+        .into_iter()
+        .find(|family| model.contains(family))
+        .map_or_else(|| model.to_string(), str::to_string)
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
 pub struct SessionOutput {
     #[serde(rename = "sessionId")]
     pub session_id: String,
+    /// Readable, stable identifier (see [`SessionUtils::display_id`])
+    #[serde(rename = "displayId")]
+    pub display_id: String,
     #[serde(rename = "projectPath")]
     pub project_path: String,
     #[serde(rename = "inputTokens")]
@@ -114,11 +439,31 @@ pub struct SessionOutput {
     #[serde(rename = "cacheReadTokens")]
     pub cache_read_tokens: u32,
     #[serde(rename = "totalCost")]
-    pub total_cost: f64,
+    pub total_cost: MicroDollars,
+    /// What each token type contributed to `total_cost`
+    #[serde(rename = "costBreakdown")]
+    pub cost_breakdown: TokenCosts,
     #[serde(rename = "lastActivity")]
     pub last_activity: String,
     #[serde(rename = "modelsUsed")]
     pub models_used: Vec<String>,
+    #[serde(rename = "estimatedEntries", skip_serializing_if = "is_zero")]
+    pub estimated_entries: u32,
+    #[serde(rename = "modelCosts", skip_serializing_if = "HashMap::is_empty")]
+    pub model_costs: HashMap<String, MicroDollars>,
+    #[serde(rename = "instanceType")]
+    pub instance_type: InstanceType,
+    /// Extra Claude root (`paths.extra_roots`) the session was read from;
+    /// `None` for the home and its VMs
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub root: Option<String>,
+    /// VM the session ran in; `None` outside VMs
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub vm: Option<String>,
+    #[serde(rename = "modelSwitches", skip_serializing_if = "ModelSwitches::is_empty")]
+    pub model_switches: ModelSwitches,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub duration: Option<SessionDuration>,
     #[serde(skip)]
     pub daily_usage: HashMap<String, DailyUsage>, // Daily breakdown for internal use
 }
@@ -128,9 +473,23 @@ pub struct DailyProject {
     pub project: String,
     pub sessions: u32,
     #[serde(rename = "totalCost")]
-    pub total_cost: f64,
+    pub total_cost: MicroDollars,
     #[serde(rename = "totalTokens")]
     pub total_tokens: u32,
+    /// Breakdown of the row's tokens, shown by `daily --tokens`
+    #[serde(skip)]
+    pub tokens: TokenCounts,
+    /// What each token type contributed to `total_cost`
+    #[serde(rename = "costBreakdown")]
+    pub cost_breakdown: TokenCosts,
+    /// Time of the row's first entry of the day, shown by `daily --details`
+    #[serde(rename = "workStarted", skip_serializing_if = "Option::is_none")]
+    pub work_started: Option<DateTime<Utc>>,
+    /// Time of the row's last entry of the day
+    #[serde(rename = "workEnded", skip_serializing_if = "Option::is_none")]
+    pub work_ended: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub estimated: bool,
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -138,18 +497,101 @@ pub struct DailyData {
     pub date: String,
     pub projects: Vec<DailyProject>,
     #[serde(rename = "totalCost")]
-    pub total_cost: f64,
+    pub total_cost: MicroDollars,
     #[serde(rename = "totalSessions")]
     pub total_sessions: u32,
+    #[serde(rename = "inputTokens")]
+    pub input_tokens: u32,
+    #[serde(rename = "outputTokens")]
+    pub output_tokens: u32,
+    #[serde(rename = "cacheCreationTokens")]
+    pub cache_creation_tokens: u32,
+    #[serde(rename = "cacheReadTokens")]
+    pub cache_read_tokens: u32,
+    /// What each token type contributed to `total_cost`
+    #[serde(rename = "costBreakdown")]
+    pub cost_breakdown: TokenCosts,
+    /// Notes recorded for this day with `note add`
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub notes: Vec<String>,
+    /// Per-model rows, filled by `--breakdown`
+    #[serde(rename = "modelBreakdowns", skip_serializing_if = "Vec::is_empty")]
+    pub model_breakdowns: Vec<ModelBreakdown>,
 }
 
 #[derive(Debug, Clone, Serialize)]
 pub struct MonthlyData {
     pub month: String,
     #[serde(rename = "totalCost")]
-    pub total_cost: f64,
+    pub total_cost: MicroDollars,
     #[serde(rename = "totalSessions")]
     pub total_sessions: u32,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub estimated: bool,
+    #[serde(rename = "modelCosts", skip_serializing_if = "BTreeMap::is_empty")]
+    pub model_costs: BTreeMap<String, MicroDollars>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub groups: BTreeMap<String, MicroDollars>, // Cost per group unless grouping by project
+    /// Per-model rows, filled by `--breakdown`
+    #[serde(rename = "modelBreakdowns", skip_serializing_if = "Vec::is_empty")]
+    pub model_breakdowns: Vec<ModelBreakdown>,
+}
+
+/// Tokens and cost of one model within a report period, shaped like
+/// ccusage's `modelBreakdowns` rows
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ModelBreakdown {
+    pub model_name: String,
+    pub input_tokens: u32,
+    pub output_tokens: u32,
+    pub cache_creation_tokens: u32,
+    pub cache_read_tokens: u32,
+    pub cost: MicroDollars,
+}
+
+/// Month-to-date usage against the same leading days of the previous month
+#[derive(Debug, Clone, Serialize)]
+pub struct MonthToDateComparison {
+    #[serde(rename = "currentMonth")]
+    pub current_month: String,
+    #[serde(rename = "previousMonth")]
+    pub previous_month: String,
+    pub days: u32,
+    #[serde(rename = "currentCost")]
+    pub current_cost: MicroDollars,
+    #[serde(rename = "previousCost")]
+    pub previous_cost: MicroDollars,
+    #[serde(rename = "currentSessions")]
+    pub current_sessions: u32,
+    #[serde(rename = "previousSessions")]
+    pub previous_sessions: u32,
+    #[serde(rename = "changePercent")]
+    pub change_percent: Option<f64>, // None when the previous period had no cost
+}
+
+/// Usage totals for one calendar day (UTC)
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct DayTotals {
+    /// The day, or the day of the start of a [`usage_since`] window
+    ///
+    /// [`usage_since`]: crate::analyzer::ClaudeUsageAnalyzer::usage_since
+    pub date: String,
+    #[serde(rename = "totalCost")]
+    pub total_cost: MicroDollars,
+    #[serde(rename = "inputTokens")]
+    pub input_tokens: u64,
+    #[serde(rename = "outputTokens")]
+    pub output_tokens: u64,
+    #[serde(rename = "cacheCreationTokens")]
+    pub cache_creation_tokens: u64,
+    #[serde(rename = "cacheReadTokens")]
+    pub cache_read_tokens: u64,
+    pub entries: u32,
+    pub sessions: u32,
+    /// Logs that could not be read, left out of the totals
+    #[serde(rename = "failedFiles", skip_serializing_if = "Vec::is_empty")]
+    pub failed_files: Vec<FailedFile>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -158,13 +600,22 @@ pub struct SessionBlock {
     pub start_time: String,
     #[serde(rename = "endTime")]
     pub end_time: String,
+    /// Timestamp of the block's last entry
+    #[serde(rename = "actualEndTime", default, skip_serializing_if = "Option::is_none")]
+    pub actual_end_time: Option<String>,
+    #[serde(rename = "isActive", default)]
+    pub is_active: bool,
+    #[serde(default)]
+    pub entries: u32,
+    #[serde(default)]
+    pub models: Vec<String>,
     #[serde(rename = "tokenCounts")]
     pub token_counts: TokenCounts,
     #[serde(rename = "costUSD")]
     pub cost_usd: f64,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
 pub struct TokenCounts {
     #[serde(rename = "inputTokens")]
     pub input_tokens: u32,
@@ -176,6 +627,54 @@ pub struct TokenCounts {
     pub cache_read_input_tokens: u32,
 }
 
+/// Cost split by the token type that incurred it; the parts add up to the
+/// cost they were split from
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TokenCosts {
+    pub input: MicroDollars,
+    pub output: MicroDollars,
+    pub cache_creation: MicroDollars,
+    pub cache_read: MicroDollars,
+}
+
+/// Where a usage entry was read from, for auditing exported data
+#[derive(Debug, Clone, Serialize)]
+pub struct Provenance {
+    #[serde(rename = "sourceFile")]
+    pub source_file: String,
+    #[serde(rename = "lineNumber")]
+    pub line_number: usize,
+    pub instance: String,
+    #[serde(rename = "instanceType")]
+    pub instance_type: InstanceType,
+    #[serde(rename = "dedupHash")]
+    pub dedup_hash: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct EntryExport {
+    pub timestamp: String,
+    #[serde(rename = "requestId")]
+    pub request_id: String,
+    #[serde(rename = "messageId")]
+    pub message_id: String,
+    pub model: String,
+    #[serde(rename = "inputTokens")]
+    pub input_tokens: u32,
+    #[serde(rename = "outputTokens")]
+    pub output_tokens: u32,
+    #[serde(rename = "cacheCreationTokens")]
+    pub cache_creation_tokens: u32,
+    #[serde(rename = "cacheReadTokens")]
+    pub cache_read_tokens: u32,
+    pub cost: f64,
+    #[serde(rename = "secondaryUsage", skip_serializing_if = "Option::is_none")]
+    pub secondary_usage: Option<SecondaryUsage>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub provenance: Option<Provenance>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PricingData {
     pub input_cost_per_token: Option<f64>,
@@ -193,21 +692,55 @@ impl SessionData {
             output_tokens: 0,
             cache_creation_tokens: 0,
             cache_read_tokens: 0,
-            total_cost: 0.0,
+            total_cost: MicroDollars::ZERO,
             last_activity: None,
             models_used: HashSet::new(),
             daily_usage: HashMap::new(),
+            estimated_entries: 0,
+            model_costs: HashMap::new(),
+            instance_type: InstanceType::Host,
+            root: None,
+            vm: None,
+            model_timeline: Vec::new(),
+            entry_times: Vec::new(),
         }
     }
 
+    /// Record the time of an entry for the session's duration
+    pub fn record_entry_time(&mut self, at: DateTime<Utc>) {
+        self.entry_times.push(at);
+    }
+
+    /// When the session ran, with gaps over `sessions.idle_gap_minutes` as idle
+    pub fn duration(&self) -> Option<SessionDuration> {
+        let idle_gap = crate::config::get_config().sessions.idle_gap();
+        SessionDuration::from_entry_times(&self.entry_times, idle_gap)
+    }
+
+    /// Record the primary model and cost of an entry for switch tracking
+    pub fn record_model(&mut self, timestamp: &str, model: &str, cost: MicroDollars) {
+        self.model_timeline
+            .push((timestamp.to_string(), model.to_string(), cost));
+    }
+
     pub fn total_tokens(&self) -> u32 {
         self.input_tokens + self.output_tokens + self.cache_creation_tokens + self.cache_read_tokens
     }
 }
 
+impl SessionOutput {
+    /// Display ID for a session, dated by the first day it was active
+    pub fn display_id_for(data: &SessionData) -> String {
+        let start_date = data.daily_usage.keys().min().map(String::as_str);
+        SessionUtils::display_id(&data.session_id, &data.project_path, start_date)
+    }
+}
+
 impl From<SessionData> for SessionOutput {
     fn from(data: SessionData) -> Self {
+        let duration = data.duration();
         Self {
+            display_id: Self::display_id_for(&data),
             session_id: data.session_id,
             project_path: data.project_path,
             input_tokens: data.input_tokens,
@@ -215,6 +748,7 @@ impl From<SessionData> for SessionOutput {
             cache_creation_tokens: data.cache_creation_tokens,
             cache_read_tokens: data.cache_read_tokens,
             total_cost: data.total_cost,
+            cost_breakdown: data.daily_usage.values().map(|day| day.costs).sum(),
             last_activity: data
                 .last_activity
                 .unwrap_or_else(|| "1970-01-01".to_string()),
@@ -223,13 +757,23 @@ impl From<SessionData> for SessionOutput {
                 models.sort();
                 models
             },
+            estimated_entries: data.estimated_entries,
+            model_costs: data.model_costs,
+            instance_type: data.instance_type,
+            root: data.root,
+            vm: data.vm,
+            model_switches: ModelSwitches::from_timeline(&data.model_timeline),
+            duration,
             daily_usage: data.daily_usage,
         }
     }
 }
 
+fn is_zero(value: &u32) -> bool {
+    *value == 0
+}
+
 impl TokenCounts {
-    #[allow(dead_code)]
     pub fn total(&self) -> u32 {
         self.input_tokens
             + self.output_tokens
@@ -237,3 +781,245 @@ impl TokenCounts {
             + self.cache_read_input_tokens
     }
 }
+
+impl From<&UsageData> for TokenCounts {
+    fn from(usage: &UsageData) -> Self {
+        Self {
+            input_tokens: usage.input_tokens,
+            output_tokens: usage.output_tokens,
+            cache_creation_input_tokens: usage.cache_creation_input_tokens,
+            cache_read_input_tokens: usage.cache_read_input_tokens,
+        }
+    }
+}
+
+impl AddAssign for TokenCounts {
+    fn add_assign(&mut self, rhs: Self) {
+        self.input_tokens += rhs.input_tokens;
+        self.output_tokens += rhs.output_tokens;
+        self.cache_creation_input_tokens += rhs.cache_creation_input_tokens;
+        self.cache_read_input_tokens += rhs.cache_read_input_tokens;
+    }
+}
+
+impl Sum for TokenCounts {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Self::default(), |mut total, counts| {
+            total += counts;
+            total
+        })
+    }
+}
+
+impl TokenCosts {
+    pub fn total(&self) -> MicroDollars {
+        self.input + self.output + self.cache_creation + self.cache_read
+    }
+
+    /// The largest part, which absorbs rounding when a cost is split
+    pub fn largest_mut(&mut self) -> &mut MicroDollars {
+        [
+            &mut self.input,
+            &mut self.output,
+            &mut self.cache_creation,
+            &mut self.cache_read,
+        ]
+        .into_iter()
+        .max_by_key(|part| **part)
+        .expect("four parts")
+    }
+}
+
+impl AddAssign for TokenCosts {
+    fn add_assign(&mut self, rhs: Self) {
+        self.input += rhs.input;
+        self.output += rhs.output;
+        self.cache_creation += rhs.cache_creation;
+        self.cache_read += rhs.cache_read;
+    }
+}
+
+impl Sum for TokenCosts {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Self::default(), |mut total, costs| {
+            total += costs;
+            total
+        })
+    }
+}
+
+/// Sessions for unit tests, built up a day at a time
+#[cfg(test)]
+pub(crate) mod test_support {
+    use super::{DailyUsage, MicroDollars, SessionOutput};
+
+    /// Start building a session with `id` in the `project` project
+    pub(crate) fn session(id: &str) -> SessionBuilder {
+        SessionBuilder {
+            session: SessionOutput {
+                session_id: id.to_string(),
+                display_id: id.to_string(),
+                project_path: "project".to_string(),
+                ..Default::default()
+            },
+            total_cost: None,
+        }
+    }
+
+    pub(crate) struct SessionBuilder {
+        session: SessionOutput,
+        total_cost: Option<MicroDollars>,
+    }
+
+    impl SessionBuilder {
+        pub(crate) fn project(mut self, project: &str) -> Self {
+            self.session.project_path = project.to_string();
+            self
+        }
+
+        pub(crate) fn last_activity(mut self, last_activity: &str) -> Self {
+            self.session.last_activity = last_activity.to_string();
+            self
+        }
+
+        /// Add a day costing `cost`, with 100 input tokens
+        pub(crate) fn day(self, date: &str, cost: f64) -> Self {
+            self.day_usage(
+                date,
+                DailyUsage {
+                    input_tokens: 100,
+                    cost: MicroDollars::from_dollars(cost),
+                    ..Default::default()
+                },
+            )
+        }
+
+        /// Add a [`day`](Self::day) for each date and cost
+        pub(crate) fn days(self, days: &[(&str, f64)]) -> Self {
+            days.iter()
+                .fold(self, |builder, (date, cost)| builder.day(date, *cost))
+        }
+
+        pub(crate) fn day_usage(mut self, date: &str, usage: DailyUsage) -> Self {
+            self.session.daily_usage.insert(date.to_string(), usage);
+            self
+        }
+
+        /// Set the session's total cost instead of summing its days
+        pub(crate) fn total_cost(mut self, total: f64) -> Self {
+            self.total_cost = Some(MicroDollars::from_dollars(total));
+            self
+        }
+
+        /// The session, with its token and cost totals summed from its days
+        pub(crate) fn build(self) -> SessionOutput {
+            let mut session = self.session;
+            for usage in session.daily_usage.values() {
+                session.input_tokens += usage.input_tokens;
+                session.output_tokens += usage.output_tokens;
+                session.cache_creation_tokens += usage.cache_creation_tokens;
+                session.cache_read_tokens += usage.cache_read_tokens;
+            }
+            session.total_cost = self
+                .total_cost
+                .unwrap_or_else(|| session.daily_usage.values().map(|usage| usage.cost).sum());
+            session
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_micro_dollars_sum_does_not_drift() {
+        let entry = 0.000123;
+        let count = 1_000_000;
+
+        let float_total: f64 = (0..count).map(|_| entry).sum();
+        let total: MicroDollars = (0..count).map(|_| MicroDollars::from_dollars(entry)).sum();
+
+        assert_ne!(float_total, 123.0);
+        assert_eq!(total, MicroDollars(123_000_000));
+        assert_eq!(total.to_dollars(), 123.0);
+    }
+
+    #[test]
+    fn test_micro_dollars_totals_match_regardless_of_grouping() {
+        // Synthetic per-entry costs with awkward binary representations
+        let costs: Vec<MicroDollars> = (0..200_000u32)
+            .map(|i| MicroDollars::from_dollars(0.1 + f64::from(i % 97) * 0.0000731))
+            .collect();
+
+        let flat: MicroDollars = costs.iter().sum();
+        let by_chunk: MicroDollars = costs
+            .chunks(31)
+            .map(|chunk| chunk.iter().sum::<MicroDollars>())
+            .sum();
+        let reversed: MicroDollars = costs.iter().rev().sum();
+
+        assert_eq!(flat, by_chunk);
+        assert_eq!(flat, reversed);
+    }
+
+    #[test]
+    fn test_micro_dollars_serde_and_display() {
+        let cost = MicroDollars::from_dollars(0.0022500000000000003);
+        assert_eq!(cost, MicroDollars(2_250));
+        assert_eq!(serde_json::to_string(&cost).unwrap(), "0.00225");
+        assert_eq!(serde_json::from_str::<MicroDollars>("0.00225").unwrap(), cost);
+        assert_eq!(format!("${:.2}", MicroDollars::from_dollars(12.345)), "$12.35");
+        assert_eq!(MicroDollars::from_dollars(f64::NAN), MicroDollars::ZERO);
+    }
+
+    #[test]
+    fn test_model_switches_follow_timestamp_order() {
+        let entry = |timestamp: &str, model: &str, cost: f64| {
+            (
+                timestamp.to_string(),
+                model.to_string(),
+                MicroDollars::from_dollars(cost),
+            )
+        };
+        let timeline = [
+            entry("2025-01-10T10:05:00Z", "claude-3-5-haiku-20241022", 0.25),
+            entry("2025-01-10T10:00:00Z", "claude-opus-4-20250514", 1.0),
+            entry("2025-01-10T10:06:00Z", "claude-3-5-haiku-20241022", 0.25),
+            entry("2025-01-10T10:10:00Z", "claude-opus-4-1-20250805", 2.0),
+        ];
+
+        let switches = ModelSwitches::from_timeline(&timeline);
+        assert_eq!(switches.count, 2);
+        assert_eq!(switches.sequence, ["opus", "haiku", "opus"]);
+        assert_eq!(switches.switched_cost.to_dollars(), 0.5);
+
+        let single = ModelSwitches::from_timeline(&timeline[1..2]);
+        assert!(single.is_empty());
+        assert_eq!(model_family("<synthetic>"), "<synthetic>");
+    }
+
+    #[test]
+    fn test_session_duration_leaves_out_idle_gaps() {
+        let at = |time: &str| format!("2025-01-10T{}Z", time).parse().unwrap();
+        let times = [
+            at("10:05:00"),
+            at("10:00:00"),
+            at("10:10:30"),
+            // Lunch break
+            at("13:00:00"),
+            at("13:02:00"),
+        ];
+
+        let idle_gap = chrono::Duration::minutes(15);
+        let duration = SessionDuration::from_entry_times(&times, idle_gap).unwrap();
+        assert_eq!(duration.started, at("10:00:00"));
+        assert_eq!(duration.ended, at("13:02:00"));
+        assert_eq!(duration.active_secs, 12 * 60 + 30);
+        assert_eq!(duration.idle_gaps, 1);
+        let format = SessionDuration::format_secs;
+        assert_eq!(format(duration.active_secs), "12m 30s");
+        assert_eq!(format(duration.span_secs()), "3h 02m");
+        assert!(SessionDuration::from_entry_times(&[], idle_gap).is_none());
+    }
+}
@@ -0,0 +1,83 @@
+//! Day notes
+//!
+//! `claude-usage note add <date> "text"` records why a day looked the way it
+//! did ("big refactor with opus agent"). Notes live in a small JSON file
+//! (`notes.file`) mapping `YYYY-MM-DD` dates to the notes added for that day,
+//! oldest first, and are shown alongside the matching rows of daily reports.
+
+use anyhow::{Context, Result};
+use chrono::NaiveDate;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+/// Notes keyed by `YYYY-MM-DD` date
+pub type DayNotes = BTreeMap<String, Vec<String>>;
+
+/// Load all notes; a missing file yields no notes
+pub fn load_notes(path: &Path) -> Result<DayNotes> {
+    if !path.exists() {
+        return Ok(DayNotes::new());
+    }
+    let content = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read notes file: {}", path.display()))?;
+    serde_json::from_str(&content)
+        .with_context(|| format!("Invalid notes file: {}", path.display()))
+}
+
+/// Append a note to `date` and save the store
+pub fn add_note(path: &Path, date: NaiveDate, text: &str) -> Result<()> {
+    let text = text.trim();
+    if text.is_empty() {
+        return Err(anyhow::anyhow!("Note text cannot be empty"));
+    }
+
+    let mut notes = load_notes(path)?;
+    notes
+        .entry(date.format("%Y-%m-%d").to_string())
+        .or_default()
+        .push(text.to_string());
+    save_notes(path, &notes)
+}
+
+/// Write the store through a temporary file so a failed write cannot truncate it
+fn save_notes(path: &Path, notes: &DayNotes) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create {}", parent.display()))?;
+    }
+    let temp_path = path.with_extension("json.tmp");
+    fs::write(&temp_path, serde_json::to_string_pretty(notes)? + "\n")
+        .with_context(|| format!("Failed to write notes file: {}", temp_path.display()))?;
+    fs::rename(&temp_path, path)
+        .with_context(|| format!("Failed to save notes file: {}", path.display()))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_add_and_load_notes() {
+        let temp = TempDir::new().unwrap();
+        let path = temp.path().join("nested").join("notes.json");
+        let date = NaiveDate::from_ymd_opt(2025, 1, 15).unwrap();
+
+        assert!(load_notes(&path).unwrap().is_empty());
+
+        add_note(&path, date, "big refactor with opus agent").unwrap();
+        add_note(&path, date, "  reran the test suite  ").unwrap();
+        assert!(add_note(&path, date, "   ").is_err());
+
+        let notes = load_notes(&path).unwrap();
+        assert_eq!(
+            notes.get("2025-01-15"),
+            Some(&vec![
+                "big refactor with opus agent".to_string(),
+                "reran the test suite".to_string()
+            ])
+        );
+    }
+}
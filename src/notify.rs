@@ -0,0 +1,466 @@
+//! Spend notifications
+//!
+//! With webhooks and thresholds under `[notify]`, a message is posted to each
+//! webhook when today's spend crosses one of `daily_usd`, or a session's spend
+//! crosses one of `session_usd`. Slack and Discord webhooks get a message in
+//! their format, recognised from the URL or set with `format`; other URLs get
+//! the alert as JSON.
+//!
+//! - Live mode checks each entry as it arrives (see [`SpendWatch`]) and sends
+//!   in the background, so a slow webhook does not hold up the dashboard.
+//! - `claude-usage check` aggregates usage once as the `daily` report does,
+//!   which suits cron. It sends the highest threshold crossed today and by
+//!   each session active today, and records what it sent in
+//!   `[notify] state_file`, so each crossing is sent once however often it
+//!   runs.
+//!
+//! Webhooks are posted to with the HTTP client of the `pricing` feature.
+
+use anyhow::{Context, Result};
+use chrono::{Duration, NaiveDate};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use tracing::{debug, warn};
+
+use crate::config::{get_config, NotifyConfig, Webhook, WebhookFormat};
+use crate::models::{MicroDollars, SessionOutput};
+
+/// Days sent alerts are remembered for
+const SENT_RETENTION_DAYS: i64 = 30;
+
+/// What spend crossed a threshold
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AlertScope {
+    /// The local day's spend, over `daily_usd`
+    Daily,
+    /// A session's spend, over `session_usd`
+    Session,
+}
+
+/// A spend threshold crossed
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SpendAlert {
+    pub scope: AlertScope,
+    /// Local day (YYYY-MM-DD) the threshold was crossed on
+    pub day: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub session_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub project: Option<String>,
+    /// Threshold crossed, in dollars
+    pub threshold: f64,
+    /// Spend when it was crossed, in dollars
+    pub spent: f64,
+}
+
+impl SpendAlert {
+    /// One-line message for chat webhooks
+    pub fn message(&self) -> String {
+        match self.scope {
+            AlertScope::Daily => format!(
+                "claude-usage: spend on {} reached ${:.2}, over the ${:.2} daily threshold",
+                self.day, self.spent, self.threshold
+            ),
+            AlertScope::Session => format!(
+                "claude-usage: session {} ({}) reached ${:.2}, over the ${:.2} session threshold",
+                self.session_id.as_deref().unwrap_or("unknown"),
+                self.project.as_deref().unwrap_or("unknown"),
+                self.spent,
+                self.threshold
+            ),
+        }
+    }
+
+    /// Identifies the crossing, so `check` sends it once
+    fn key(&self) -> String {
+        match self.scope {
+            AlertScope::Daily => format!("daily:{}:{}", self.day, self.threshold),
+            AlertScope::Session => format!(
+                "session:{}:{}",
+                self.session_id.as_deref().unwrap_or_default(),
+                self.threshold
+            ),
+        }
+    }
+}
+
+/// Whether notifications are configured: a webhook and at least one threshold
+pub fn enabled(config: &NotifyConfig) -> bool {
+    let thresholds = config.daily_usd.len() + config.session_usd.len();
+    !config.webhooks.is_empty() && thresholds > 0
+}
+
+/// The highest threshold reached going from `before` to `after`, if any
+fn crossed(thresholds: &[f64], before: f64, after: f64) -> Option<f64> {
+    thresholds
+        .iter()
+        .copied()
+        .filter(|threshold| before < *threshold && *threshold <= after)
+        .max_by(f64::total_cmp)
+}
+
+/// Today's and each session's spend, checked as live mode's entries arrive
+#[derive(Debug, Clone)]
+pub struct SpendWatch {
+    daily_usd: Vec<f64>,
+    session_usd: Vec<f64>,
+    day: NaiveDate,
+    spent: f64,
+}
+
+impl SpendWatch {
+    /// Start watching `day` with `spent` already used, or `None` when
+    /// notifications are not configured
+    pub fn new(config: &NotifyConfig, day: NaiveDate, spent: f64) -> Option<Self> {
+        enabled(config).then(|| Self {
+            daily_usd: config.daily_usd.clone(),
+            session_usd: config.session_usd.clone(),
+            day,
+            spent,
+        })
+    }
+
+    /// Add an entry costing `cost` on `day` to a session whose spend is now
+    /// `session_spent`, returning the thresholds it crossed
+    ///
+    /// A new day starts again from zero.
+    pub fn record(
+        &mut self,
+        day: NaiveDate,
+        cost: f64,
+        session_id: &str,
+        project: &str,
+        session_spent: f64,
+    ) -> Vec<SpendAlert> {
+        if day != self.day {
+            self.day = day;
+            self.spent = 0.0;
+        }
+        let before = self.spent;
+        self.spent += cost;
+        let day = day.format("%Y-%m-%d").to_string();
+
+        let mut alerts = Vec::new();
+        if let Some(threshold) = crossed(&self.daily_usd, before, self.spent) {
+            alerts.push(SpendAlert {
+                scope: AlertScope::Daily,
+                day: day.clone(),
+                session_id: None,
+                project: None,
+                threshold,
+                spent: self.spent,
+            });
+        }
+        if let Some(threshold) = crossed(&self.session_usd, session_spent - cost, session_spent) {
+            alerts.push(SpendAlert {
+                scope: AlertScope::Session,
+                day,
+                session_id: Some(session_id.to_string()),
+                project: Some(project.to_string()),
+                threshold,
+                spent: session_spent,
+            });
+        }
+        alerts
+    }
+}
+
+/// The thresholds crossed by `today`'s spend and by the sessions active
+/// `today`, the highest of each
+pub fn current_alerts(
+    config: &NotifyConfig,
+    sessions: &[SessionOutput],
+    today: NaiveDate,
+) -> Vec<SpendAlert> {
+    let day = today.format("%Y-%m-%d").to_string();
+    let mut alerts = Vec::new();
+
+    let spent = sessions
+        .iter()
+        .filter_map(|session| session.daily_usage.get(&day))
+        .map(|usage| usage.cost)
+        .sum::<MicroDollars>()
+        .to_dollars();
+    if let Some(threshold) = crossed(&config.daily_usd, 0.0, spent) {
+        alerts.push(SpendAlert {
+            scope: AlertScope::Daily,
+            day: day.clone(),
+            session_id: None,
+            project: None,
+            threshold,
+            spent,
+        });
+    }
+
+    for session in sessions
+        .iter()
+        .filter(|session| session.daily_usage.contains_key(&day))
+    {
+        let spent = session.total_cost.to_dollars();
+        if let Some(threshold) = crossed(&config.session_usd, 0.0, spent) {
+            alerts.push(SpendAlert {
+                scope: AlertScope::Session,
+                day: day.clone(),
+                session_id: Some(session.session_id.clone()),
+                project: Some(session.project_path.clone()),
+                threshold,
+                spent,
+            });
+        }
+    }
+    alerts
+}
+
+/// Alerts `check` has sent, keyed by crossing, with the day each was sent
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct SentAlerts {
+    sent: BTreeMap<String, String>,
+}
+
+impl SentAlerts {
+    /// Load the sent alerts, none when the file does not exist yet
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        serde_json::from_str(&content)
+            .with_context(|| format!("Failed to parse {}", path.display()))
+    }
+
+    /// Whether the alert's crossing was sent already
+    pub fn contains(&self, alert: &SpendAlert) -> bool {
+        self.sent.contains_key(&alert.key())
+    }
+
+    /// Record the alert as sent
+    pub fn insert(&mut self, alert: &SpendAlert) {
+        self.sent.insert(alert.key(), alert.day.clone());
+    }
+
+    /// Save the sent alerts, forgetting those sent over
+    /// [`SENT_RETENTION_DAYS`] before `today`
+    pub fn save(&mut self, path: &Path, today: NaiveDate) -> Result<()> {
+        let cutoff = (today - Duration::days(SENT_RETENTION_DAYS))
+            .format("%Y-%m-%d")
+            .to_string();
+        self.sent.retain(|_, day| *day >= cutoff);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create {}", parent.display()))?;
+        }
+        fs::write(path, serde_json::to_string_pretty(self)? + "\n")
+            .with_context(|| format!("Failed to write {}", path.display()))
+    }
+}
+
+/// Message format of a webhook, from its URL unless set
+fn format_of(webhook: &Webhook) -> WebhookFormat {
+    if let Some(format) = webhook.format {
+        return format;
+    }
+    let host = host_of(&webhook.url);
+    if host == "hooks.slack.com" {
+        WebhookFormat::Slack
+    } else if matches!(host, "discord.com" | "discordapp.com")
+        && webhook.url.contains("/api/webhooks/")
+    {
+        WebhookFormat::Discord
+    } else {
+        WebhookFormat::Generic
+    }
+}
+
+/// The URL's host, which can be logged where the URL, a secret, cannot
+fn host_of(url: &str) -> &str {
+    let rest = url.split_once("://").map_or(url, |(_, rest)| rest);
+    rest.split(['/', '?', '#']).next().unwrap_or_default()
+}
+
+/// Request body posted to the webhook for the alert
+fn payload(webhook: &Webhook, alert: &SpendAlert) -> serde_json::Value {
+    match format_of(webhook) {
+        WebhookFormat::Slack => serde_json::json!({ "text": alert.message() }),
+        WebhookFormat::Discord => serde_json::json!({ "content": alert.message() }),
+        WebhookFormat::Generic => {
+            let mut body = serde_json::to_value(alert).unwrap_or_default();
+            body["text"] = alert.message().into();
+            body
+        }
+    }
+}
+
+/// Post the alert to every webhook, failing if any of them failed
+#[cfg(feature = "pricing")]
+pub async fn send(webhooks: &[Webhook], alert: &SpendAlert) -> Result<()> {
+    let client = reqwest::Client::builder()
+        .timeout(std::time::Duration::from_secs(10))
+        .connect_timeout(std::time::Duration::from_secs(5))
+        .build()?;
+
+    let mut failed = 0;
+    for webhook in webhooks {
+        let result = client
+            .post(&webhook.url)
+            .header(
+                "User-Agent",
+                format!("claude-usage/{}", env!("CARGO_PKG_VERSION")),
+            )
+            .json(&payload(webhook, alert))
+            .send()
+            .await
+            .and_then(|response| response.error_for_status());
+        match result {
+            Ok(_) => debug!(host = host_of(&webhook.url), "Sent spend notification"),
+            Err(e) => {
+                warn!(host = host_of(&webhook.url), error = %e.without_url(), "Failed to send spend notification");
+                failed += 1;
+            }
+        }
+    }
+    if failed > 0 {
+        anyhow::bail!("{} of {} webhooks failed", failed, webhooks.len());
+    }
+    Ok(())
+}
+
+#[cfg(not(feature = "pricing"))]
+pub async fn send(_webhooks: &[Webhook], _alert: &SpendAlert) -> Result<()> {
+    anyhow::bail!(
+        "claude-usage was built without the `pricing` feature; rebuild with it to send notifications"
+    )
+}
+
+/// Send the alert to the configured webhooks in the background, logging a
+/// failure
+pub fn spawn_send(alert: SpendAlert) {
+    tokio::spawn(async move {
+        if let Err(e) = send(&get_config().notify.webhooks, &alert).await {
+            warn!(error = %e, scope = ?alert.scope, "Spend notification not delivered");
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::test_support::session;
+
+    fn config() -> NotifyConfig {
+        NotifyConfig {
+            webhooks: vec![Webhook {
+                url: "https://example.com/hook".to_string(),
+                format: None,
+            }],
+            daily_usd: vec![10.0, 20.0],
+            session_usd: vec![5.0],
+            ..NotifyConfig::default()
+        }
+    }
+
+    #[test]
+    fn test_spend_watch_alerts_once_per_threshold() {
+        let day = NaiveDate::from_ymd_opt(2025, 6, 1).unwrap();
+        assert!(SpendWatch::new(&NotifyConfig::default(), day, 0.0).is_none());
+
+        let mut watch = SpendWatch::new(&config(), day, 8.0).unwrap();
+        assert!(watch.record(day, 1.0, "s1", "app", 1.0).is_empty());
+        // Jumping past both daily thresholds reports the higher one
+        let alerts = watch.record(day, 12.0, "s1", "app", 13.0);
+        assert_eq!(alerts.len(), 2);
+        assert_eq!(
+            (alerts[0].scope, alerts[0].threshold),
+            (AlertScope::Daily, 20.0)
+        );
+        assert_eq!(
+            (alerts[1].scope, alerts[1].threshold),
+            (AlertScope::Session, 5.0)
+        );
+        assert!(watch.record(day, 1.0, "s1", "app", 14.0).is_empty());
+
+        // A new day starts from zero
+        let next = day.succ_opt().unwrap();
+        let alerts = watch.record(next, 10.0, "s2", "app", 1.0);
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(alerts[0].day, "2025-06-02");
+    }
+
+    #[test]
+    fn test_current_alerts_recorded_as_sent() {
+        let today = NaiveDate::from_ymd_opt(2025, 6, 1).unwrap();
+        let sessions = [
+            session("big")
+                .total_cost(30.0)
+                .days(&[("2025-05-31", 20.0), ("2025-06-01", 8.0)])
+                .build(),
+            session("small")
+                .total_cost(4.0)
+                .days(&[("2025-06-01", 4.0)])
+                .build(),
+            // Not active today, so not reported again
+            session("old")
+                .total_cost(50.0)
+                .days(&[("2025-05-30", 50.0)])
+                .build(),
+        ];
+        let alerts = current_alerts(&config(), &sessions, today);
+        assert_eq!(alerts.len(), 2);
+        assert_eq!(
+            (alerts[0].scope, alerts[0].threshold),
+            (AlertScope::Daily, 10.0)
+        );
+        assert_eq!(alerts[1].session_id.as_deref(), Some("big"));
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("state").join("notify.json");
+        let mut sent = SentAlerts::load(&path).unwrap();
+        sent.insert(&alerts[0]);
+        sent.save(&path, today).unwrap();
+        let sent = SentAlerts::load(&path).unwrap();
+        assert!(sent.contains(&alerts[0]));
+        assert!(!sent.contains(&alerts[1]));
+
+        // Forgotten once past the retention
+        let mut sent = sent;
+        sent.save(&path, today + Duration::days(31)).unwrap();
+        assert!(!SentAlerts::load(&path).unwrap().contains(&alerts[0]));
+    }
+
+    #[test]
+    fn test_payload_follows_webhook_format() {
+        let alert = SpendAlert {
+            scope: AlertScope::Daily,
+            day: "2025-06-01".to_string(),
+            session_id: None,
+            project: None,
+            threshold: 10.0,
+            spent: 12.5,
+        };
+        let hook = |url: &str| Webhook {
+            url: url.to_string(),
+            format: None,
+        };
+
+        let slack = payload(&hook("https://hooks.slack.com/services/T0/B0/x"), &alert);
+        assert!(slack["text"].as_str().unwrap().contains("$12.50"));
+        let discord = payload(&hook("https://discord.com/api/webhooks/1/x"), &alert);
+        assert!(discord["content"].is_string());
+        let generic = payload(&hook("https://example.com/hook?token=x"), &alert);
+        assert_eq!(generic["scope"], "daily");
+        assert_eq!(generic["threshold"], 10.0);
+        assert!(generic["text"].is_string());
+        assert!(generic.get("sessionId").is_none());
+
+        let forced = Webhook {
+            format: Some(WebhookFormat::Slack),
+            ..hook("https://chat.example.com/hook")
+        };
+        assert!(payload(&forced, &alert)["text"].is_string());
+        assert_eq!(host_of("https://example.com/hook?token=x"), "example.com");
+    }
+}
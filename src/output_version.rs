@@ -0,0 +1,229 @@
+//! Versions of the report JSON
+//!
+//! The JSON of `daily`, `monthly` and `session` gains fields as the reports
+//! grow. `--output-version N` renders the layout of an earlier version from
+//! the current report, so consumers written against it keep working while
+//! they move to the latest one:
+//!
+//! - **1**: the original layout. Days have their date, projects, total cost
+//!   and sessions; projects their sessions, cost and tokens; months their
+//!   total cost and sessions; sessions their tokens, cost, models and last
+//!   activity, and the session report has no `totals`.
+//! - **2** (latest): adds the token counts and `costBreakdown` of days,
+//!   projects and sessions, day `notes`, project `workStarted` and
+//!   `workEnded`, month `modelCosts`, session `displayId`, `modelCosts`,
+//!   `instanceType`, `modelSwitches`, `duration`, `root`, `vm` and
+//!   `totalTokens`, the daily report's `limits`, the monthly report's
+//!   `monthToDate` and the session report's `totals`.
+//!
+//! An older version only drops the fields added since. Fields that appear
+//! only when asked for, such as the `modelBreakdowns` of `--breakdown`, and
+//! the `metadata` section are kept. Deprecated versions still render, with a
+//! warning naming the release that removes them.
+
+use anyhow::Result;
+use serde_json::Value;
+
+use crate::dedup::Command;
+
+/// Oldest version still rendered
+pub const OLDEST: u32 = 1;
+
+/// Version printed by default
+pub const LATEST: u32 = 2;
+
+/// Versions that will be removed, and the release removing them
+const DEPRECATED: &[(u32, &str)] = &[(1, "2.0.0")];
+
+/// Fields a version added to the objects at `path` of a report
+struct Added {
+    version: u32,
+    report: Command,
+    /// Keys leading to the objects, `*` standing for every array element
+    path: &'static [&'static str],
+    fields: &'static [&'static str],
+}
+
+const ADDED: &[Added] = &[
+    Added {
+        version: 2,
+        report: Command::Daily,
+        path: &["daily", "*"],
+        fields: &[
+            "inputTokens",
+            "outputTokens",
+            "cacheCreationTokens",
+            "cacheReadTokens",
+            "costBreakdown",
+            "notes",
+        ],
+    },
+    Added {
+        version: 2,
+        report: Command::Daily,
+        path: &["daily", "*", "projects", "*"],
+        fields: &["costBreakdown", "workStarted", "workEnded"],
+    },
+    Added {
+        version: 2,
+        report: Command::Daily,
+        path: &[],
+        fields: &["limits"],
+    },
+    Added {
+        version: 2,
+        report: Command::Monthly,
+        path: &["monthly", "*"],
+        fields: &["modelCosts"],
+    },
+    Added {
+        version: 2,
+        report: Command::Monthly,
+        path: &[],
+        fields: &["monthToDate"],
+    },
+    Added {
+        version: 2,
+        report: Command::Session,
+        path: &["sessions", "*"],
+        fields: &[
+            "displayId",
+            "costBreakdown",
+            "modelCosts",
+            "instanceType",
+            "modelSwitches",
+            "duration",
+            "root",
+            "vm",
+            "totalTokens",
+        ],
+    },
+    Added {
+        version: 2,
+        report: Command::Session,
+        path: &[],
+        fields: &["totals"],
+    },
+];
+
+/// Check that `version` can be rendered
+pub fn check(version: u32) -> Result<()> {
+    if !(OLDEST..=LATEST).contains(&version) {
+        anyhow::bail!(
+            "Output version {} is not supported; use {} to {}",
+            version,
+            OLDEST,
+            LATEST
+        );
+    }
+    Ok(())
+}
+
+/// The warning to show for a deprecated `version`
+pub fn deprecation(version: u32) -> Option<String> {
+    DEPRECATED
+        .iter()
+        .find(|(deprecated, _)| *deprecated == version)
+        .map(|(_, removed_in)| {
+            format!(
+                "Output version {} is deprecated and will be removed in {}; version {} is the latest",
+                version, removed_in, LATEST
+            )
+        })
+}
+
+/// Rewrite the latest JSON of `report` in the layout of `version`
+pub fn render(report: Command, document: &mut Value, version: u32) {
+    for added in ADDED
+        .iter()
+        .filter(|added| added.report == report && added.version > version)
+    {
+        remove_fields(document, added.path, added.fields);
+    }
+}
+
+fn remove_fields(value: &mut Value, path: &[&str], fields: &[&str]) {
+    match path.split_first() {
+        None => {
+            if let Value::Object(object) = value {
+                for field in fields {
+                    object.remove(*field);
+                }
+            }
+        }
+        Some((&"*", rest)) => {
+            if let Value::Array(items) = value {
+                for item in items {
+                    remove_fields(item, rest, fields);
+                }
+            }
+        }
+        Some((key, rest)) => {
+            if let Some(child) = value.get_mut(*key) {
+                remove_fields(child, rest, fields);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_older_versions_drop_later_fields() {
+        let latest = json!({
+            "sessions": [{
+                "sessionId": "a",
+                "displayId": "app-2025-03-01-1a2b3c",
+                "totalCost": 1.5,
+                "totalTokens": 1000,
+                "modelBreakdowns": [{"modelName": "claude-sonnet-4"}],
+            }],
+            "totals": {"totalCost": 1.5},
+            "metadata": {"partial": true},
+        });
+
+        let mut document = latest.clone();
+        render(Command::Session, &mut document, LATEST);
+        assert_eq!(document, latest);
+
+        render(Command::Session, &mut document, 1);
+        assert_eq!(
+            document,
+            json!({
+                "sessions": [{
+                    "sessionId": "a",
+                    "totalCost": 1.5,
+                    "modelBreakdowns": [{"modelName": "claude-sonnet-4"}],
+                }],
+                "metadata": {"partial": true},
+            })
+        );
+
+        let mut monthly = json!({
+            "monthly": [{
+                "month": "2025-03",
+                "totalCost": 1.5,
+                "modelCosts": {"claude-sonnet-4": 1.5},
+            }],
+            "monthToDate": null,
+        });
+        render(Command::Monthly, &mut monthly, 1);
+        assert_eq!(
+            monthly,
+            json!({"monthly": [{"month": "2025-03", "totalCost": 1.5}]})
+        );
+
+        // Fields are only dropped from the report that added them
+        let mut monthly = json!({"monthly": [], "totals": {}});
+        render(Command::Monthly, &mut monthly, 1);
+        assert!(monthly.get("totals").is_some());
+
+        assert!(check(OLDEST).is_ok() && check(LATEST).is_ok());
+        assert!(check(0).is_err() && check(LATEST + 1).is_err());
+        assert!(deprecation(1).is_some_and(|note| note.contains("deprecated")));
+        assert_eq!(deprecation(LATEST), None);
+    }
+}
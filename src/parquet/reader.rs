@@ -6,21 +6,36 @@
 use anyhow::{Context, Result};
 use chrono;
 use serde_json::Value;
+use std::collections::HashMap;
 use std::fs;
 use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
 use std::time::{SystemTime, UNIX_EPOCH, Duration};
 use tracing::{debug, info, warn};
 
 
+use crate::archive::ArchiveIndex;
+use crate::dedup::{calculate_entry_cost, CancellationToken, CostMode};
+use crate::dedup_set::DedupSet;
+use crate::error::NoDataFound;
+use crate::estimation::TokenEstimator;
+use crate::file_discovery::ProjectDirFilter;
+use crate::keeper_integration::KeeperIntegration;
+use crate::line_reader;
 use crate::live::BaselineSummary;
+use crate::pricing::calculate_cost_simple;
+use crate::project_names::{self, Expected};
+use crate::sanitize::{self, SanitizedUsage};
+use crate::skipped::{SkipReason, SkippedData};
 
 /// Read a parquet file using claude-keeper library and return JSON values directly
-fn read_parquet_with_library(parquet_file: &PathBuf) -> Result<Vec<serde_json::Value>> {
+pub(crate) fn read_parquet_with_library(parquet_file: &PathBuf) -> Result<Vec<serde_json::Value>> {
     debug!("Attempting to read parquet file: {}", parquet_file.display());
     
     // Use claude-keeper library to read and convert parquet to JSONL
     // Note: The cfg check is not needed since claude-keeper is a direct dependency
     use claude_keeper::parquet_reader::{ConversationParquetReader, QueryFilter};
+    let _permit = crate::io_throttle::global().acquire_file(parquet_file);
     match ConversationParquetReader::new(parquet_file) {
             Ok(reader) => {
                 info!("Successfully created parquet reader for: {}", parquet_file.display());
@@ -108,22 +123,116 @@ fn read_parquet_with_library(parquet_file: &PathBuf) -> Result<Vec<serde_json::V
         }
 }
 
+/// Messages of parquet files read earlier, reused while a file keeps its
+/// size and modification time
+///
+/// `daily --watch` keeps one across refreshes so only the files claude-keeper
+/// rewrote since the last cycle are read again.
+#[derive(Debug, Clone, Default)]
+pub struct ParquetCache {
+    files: Arc<Mutex<HashMap<PathBuf, CachedFile>>>,
+}
+
+#[derive(Debug)]
+struct CachedFile {
+    modified: SystemTime,
+    len: u64,
+    messages: Arc<Vec<Value>>,
+}
+
+impl ParquetCache {
+    /// Messages of `parquet_file`, from the cache when the file is unchanged
+    pub fn read(&self, parquet_file: &PathBuf) -> Result<Arc<Vec<Value>>> {
+        let stamp = fs::metadata(parquet_file)
+            .ok()
+            .and_then(|metadata| Some((metadata.modified().ok()?, metadata.len())));
+        let mut files = self.files.lock().unwrap_or_else(|e| e.into_inner());
+
+        if let (Some((modified, len)), Some(cached)) = (stamp, files.get(parquet_file)) {
+            if cached.modified == modified && cached.len == len {
+                debug!(file = %parquet_file.display(), "Reusing cached parquet messages");
+                return Ok(cached.messages.clone());
+            }
+        }
+
+        let messages = Arc::new(read_parquet_with_library(parquet_file)?);
+        match stamp {
+            Some((modified, len)) => {
+                let cached = CachedFile {
+                    modified,
+                    len,
+                    messages: messages.clone(),
+                };
+                files.insert(parquet_file.clone(), cached);
+            }
+            None => {
+                files.remove(parquet_file);
+            }
+        }
+        Ok(messages)
+    }
+}
+
 /// Reads summary information from parquet backup files
 pub struct ParquetSummaryReader {
     backup_dir: PathBuf,
+    estimator: Option<TokenEstimator>,
+    cache: Option<ParquetCache>,
+    project: Option<ProjectDirFilter>,
+    cost_mode: CostMode,
+    cancel: Option<CancellationToken>,
 }
 
 impl ParquetSummaryReader {
     /// Create a new parquet summary reader
     pub fn new(backup_dir: PathBuf) -> Result<Self> {
         if !backup_dir.exists() {
-            return Err(anyhow::anyhow!(
-                "Backup directory does not exist: {}",
-                backup_dir.display()
-            ));
+            return Err(NoDataFound {
+                message: format!("Backup directory does not exist: {}", backup_dir.display()),
+            }
+            .into());
         }
 
-        Ok(Self { backup_dir })
+        Ok(Self {
+            backup_dir,
+            estimator: None,
+            cache: None,
+            project: None,
+            cost_mode: CostMode::default(),
+            cancel: None,
+        })
+    }
+
+    /// Estimate tokens from message content for entries without usage data
+    /// instead of skipping them
+    pub fn with_estimator(mut self, estimator: Option<TokenEstimator>) -> Self {
+        self.estimator = estimator;
+        self
+    }
+
+    /// Reuse the messages of unchanged files across readers
+    pub fn with_cache(mut self, cache: Option<ParquetCache>) -> Self {
+        self.cache = cache;
+        self
+    }
+
+    /// Only read the messages of projects selected by `project`
+    pub fn with_project_filter(mut self, project: Option<ProjectDirFilter>) -> Self {
+        self.project = project;
+        self
+    }
+
+    /// Take message costs as `cost_mode` says instead of preferring `costUSD`
+    pub fn with_cost_mode(mut self, cost_mode: CostMode) -> Self {
+        self.cost_mode = cost_mode;
+        self
+    }
+
+    /// Stop reading backup files once `cancel` is cancelled, keeping the
+    /// sessions read so far
+    pub fn with_cancellation(mut self, cancel: Option<CancellationToken>) -> Self {
+        self.cancel = cancel;
+        self
     }
 
     /// Read summary data from parquet files
@@ -238,12 +347,26 @@ impl ParquetSummaryReader {
     }
 
     /// Find all parquet files in the backup directory (recursively)
+    /// followed by the parts listed in the archive index
     fn find_parquet_files(&self) -> Result<Vec<PathBuf>> {
         let mut parquet_files = Vec::new();
         self.find_parquet_files_recursive(&self.backup_dir, &mut parquet_files)?;
         
         // Sort files by name for consistent ordering
         parquet_files.sort();
+
+        // Compacted archives; the archive may live inside the backup directory
+        let archive_dir = &crate::config::get_config().archive.dir;
+        match ArchiveIndex::load(archive_dir) {
+            Ok(index) => {
+                for file in index.parquet_files(archive_dir) {
+                    if !parquet_files.contains(&file) {
+                        parquet_files.push(file);
+                    }
+                }
+            }
+            Err(e) => warn!(error = %e, "Ignoring unreadable archive index"),
+        }
         
         Ok(parquet_files)
     }
@@ -272,7 +395,6 @@ impl ParquetSummaryReader {
     }
 
     /// Get statistics about the backup files
-    #[allow(dead_code)]
     pub fn get_backup_stats(&self) -> Result<BackupStats> {
         let parquet_files = self.find_parquet_files()?;
         
@@ -299,10 +421,14 @@ impl ParquetSummaryReader {
 
     /// Read detailed session data for daily/monthly analysis
     pub fn read_detailed_sessions(&self) -> Result<Vec<crate::models::SessionOutput>> {
-        use crate::models::{SessionData, SessionOutput, DailyUsage};
-        use crate::timestamp_parser::TimestampParser;
-        use std::collections::{HashMap, HashSet};
-        
+        Ok(self.read_detailed_sessions_with_skipped()?.0)
+    }
+
+    /// [`read_detailed_sessions`](Self::read_detailed_sessions), along with
+    /// the messages the project filter left out
+    pub fn read_detailed_sessions_with_skipped(
+        &self,
+    ) -> Result<(Vec<crate::models::SessionOutput>, SkippedData)> {
         info!(
             backup_dir = %self.backup_dir.display(),
             "Reading detailed session data from parquet backups"
@@ -314,18 +440,53 @@ impl ParquetSummaryReader {
         
         if parquet_files.is_empty() {
             warn!("No parquet files found in backup directory");
-            return Ok(Vec::new());
+            return Ok((Vec::new(), SkippedData::default()));
         }
 
-        let total_files = parquet_files.len();
-        info!(file_count = total_files, "Processing parquet files for detailed sessions");
+        info!(file_count = parquet_files.len(), "Processing parquet files for detailed sessions");
+
+        // Each file is read only when its turn comes, after the cancellation check
+        let files = parquet_files.into_iter().map(|parquet_file| {
+            info!("About to read parquet file: {}", parquet_file.display());
+            let read = match &self.cache {
+                Some(cache) => cache.read(&parquet_file),
+                None => read_parquet_with_library(&parquet_file).map(Arc::new),
+            };
+            (parquet_file, read)
+        });
+        self.sessions_from_messages(files)
+    }
+
+    /// Aggregate the messages read from each backup file into sessions, as
+    /// [`read_detailed_sessions_with_skipped`](Self::read_detailed_sessions_with_skipped)
+    /// does for the files of the backup directory
+    ///
+    /// Files that could not be read are skipped.
+    pub fn sessions_from_messages<I>(
+        &self,
+        files: I,
+    ) -> Result<(Vec<crate::models::SessionOutput>, SkippedData)>
+    where
+        I: IntoIterator<Item = (PathBuf, Result<Arc<Vec<Value>>>)>,
+        I::IntoIter: ExactSizeIterator,
+    {
+        use crate::models::{
+            InstanceType, MicroDollars, ModelSwitches, SessionData, SessionOutput, TokenCounts,
+        };
+        use crate::timestamp_parser::TimestampParser;
+        use std::collections::HashMap;
+
+        let mut files = files.into_iter();
+        let total_files = files.len();
 
         // Map to aggregate sessions across all files
         let mut sessions_map: HashMap<String, SessionData> = HashMap::new();
         
         // Set for deduplication using messageId:requestId (like ccusage)
-        let mut seen_messages: HashSet<String> = HashSet::new();
+        let seen_messages = DedupSet::from_config();
         
+        let mut skipped = SkippedData::default();
+
         // Debug counters
         let mut total_messages_seen = 0;
         let mut deduplicated_count = 0;
@@ -334,13 +495,27 @@ impl ParquetSummaryReader {
         let mut aug20_messages = 0;
 
         // Process each parquet file
-        for (file_idx, parquet_file) in parquet_files.iter().enumerate() {
+        for file_idx in 0..total_files {
+            if self
+                .cancel
+                .as_ref()
+                .is_some_and(CancellationToken::is_cancelled)
+            {
+                warn!(
+                    files_read = file_idx,
+                    file_count = total_files,
+                    "Cancelled, keeping the sessions read so far"
+                );
+                break;
+            }
+            crate::memory::check_memory_limit()?;
+            let Some((parquet_file, read)) = files.next() else {
+                break;
+            };
             debug!(file = %parquet_file.display(), "Reading messages from parquet file {}/{}", 
-                   file_idx + 1, parquet_files.len());
+                   file_idx + 1, total_files);
             
-            // Use claude-keeper library directly to read parquet data
-            info!("About to read parquet file: {}", parquet_file.display());
-            let messages: Vec<Value> = match read_parquet_with_library(parquet_file) {
+            let messages: Arc<Vec<Value>> = match read {
                 Ok(data) => {
                     info!(file = %parquet_file.display(), "Successfully read {} messages from parquet", data.len());
                     data
@@ -382,12 +557,11 @@ impl ParquetSummaryReader {
             let mut file_aug20 = 0;
             let mut file_aug20_skipped_no_usage = 0;
             let mut file_aug20_skipped_dedup = 0;
-            let mut file_total_processed = 0;
+            let mut file_undated = 0;
 
             // Process each message
-            for msg in messages {
+            for msg in messages.iter() {
                 total_messages_seen += 1;
-                file_total_processed += 1;
                 
                 // Extract message ID and request ID for deduplication
                 let message_id = msg.get("message")
@@ -408,7 +582,7 @@ impl ParquetSummaryReader {
                 // Try to deduplicate when both IDs available, but don't require them
                 if let (Some(mid), Some(rid)) = (message_id, request_id) {
                     let dedup_key = format!("{}:{}", mid, rid);
-                    if seen_messages.contains(&dedup_key) {
+                    if !seen_messages.insert(&dedup_key) {
                         // Skip duplicate message
                         deduplicated_count += 1;
                         if is_aug20 {
@@ -417,7 +591,6 @@ impl ParquetSummaryReader {
                         }
                         continue;
                     }
-                    seen_messages.insert(dedup_key);
                 } else {
                     // Count messages without dedup keys but still process them
                     no_dedup_key_count += 1;
@@ -433,16 +606,62 @@ impl ParquetSummaryReader {
                 let project_name = msg.get("project_name")
                     .or_else(|| msg.get("projectName"))
                     .and_then(|v| v.as_str())
-                    .unwrap_or("default")
-                    .to_string();
+                    .unwrap_or("default");
+                let project_name = project_names::resolve_project_name(
+                    project_name,
+                    Expected::EncodedDir,
+                    project_fallback(msg).as_deref(),
+                );
+                if self
+                    .project
+                    .as_ref()
+                    .is_some_and(|filter| !filter.matches(&project_name))
+                {
+                    skipped.add_entries(SkipReason::ProjectExclusion, 1);
+                    continue;
+                }
                 
+                // Explicit instance type from the backup, else classify the source path
+                let source_path = ["source_file", "sourceFile", "file_path", "cwd"]
+                    .iter()
+                    .find_map(|field| msg.get(*field).and_then(|v| v.as_str()))
+                    .map(Path::new);
+                let instance_type = msg
+                    .get("instance_type")
+                    .or_else(|| msg.get("instanceType"))
+                    .and_then(|v| v.as_str())
+                    .and_then(InstanceType::parse)
+                    .or_else(|| source_path.map(InstanceType::from_path))
+                    .unwrap_or_default();
+                let vm = if instance_type == InstanceType::Vm {
+                    source_path.and_then(InstanceType::vm_name)
+                } else {
+                    None
+                };
+
                 // Get usage data - check message field first (where it actually is)
                 let usage = msg.get("message")
                     .and_then(|m| m.get("usage"))
                     .or_else(|| msg.get("usage"));
+
+                // Apply the [quality] rules; a block without token counts is missing usage
+                let usage = match usage.map(sanitize::sanitize_usage) {
+                    Some(SanitizedUsage::Usage(usage)) => Some(usage),
+                    Some(SanitizedUsage::Skip) => continue,
+                    Some(SanitizedUsage::Empty) | None => None,
+                };
                 
+                // Without usage data, estimate from content when enabled
+                let estimated_usage = match (&usage, &self.estimator) {
+                    (None, Some(estimator)) => msg
+                        .get("message")
+                        .and_then(|m| estimator.estimate_usage(m)),
+                    _ => None,
+                };
+                let is_estimated = estimated_usage.is_some();
+
                 // Skip if no usage data (like ccusage does)
-                if usage.is_none() {
+                if usage.is_none() && !is_estimated {
                     if is_aug20 {
                         file_aug20_skipped_no_usage += 1;
                     }
@@ -462,15 +681,8 @@ impl ParquetSummaryReader {
                     }
                 }
 
-                let input_tokens = usage
-                    .and_then(|u| u.get("input_tokens"))
-                    .and_then(|v| v.as_u64())
-                    .unwrap_or(0) as u32;
-
-                let output_tokens = usage
-                    .and_then(|u| u.get("output_tokens"))
-                    .and_then(|v| v.as_u64())
-                    .unwrap_or(0) as u32;
+                let input_tokens = usage.as_ref().map_or(0, |u| u.input_tokens);
+                let output_tokens = usage.as_ref().map_or(0, |u| u.output_tokens);
                 
                 // ccusage doesn't filter messages based on token counts
                 // It processes ALL messages that have valid structure and usage data
@@ -478,15 +690,21 @@ impl ParquetSummaryReader {
                 
                 messages_with_usage += 1;
 
-                let cache_creation_tokens = usage
-                    .and_then(|u| u.get("cache_creation_input_tokens"))
-                    .and_then(|v| v.as_u64())
-                    .unwrap_or(0) as u32;
-
-                let cache_read_tokens = usage
-                    .and_then(|u| u.get("cache_read_input_tokens"))
-                    .and_then(|v| v.as_u64())
-                    .unwrap_or(0) as u32;
+                let cache_creation_tokens =
+                    usage.as_ref().map_or(0, |u| u.cache_creation_input_tokens);
+                let cache_read_tokens = usage.as_ref().map_or(0, |u| u.cache_read_input_tokens);
+
+                // Estimated entries take their counts from the estimate instead
+                let (input_tokens, output_tokens, cache_creation_tokens, cache_read_tokens) =
+                    match &estimated_usage {
+                        Some(estimate) => (
+                            estimate.input_tokens,
+                            estimate.output_tokens,
+                            estimate.cache_creation_input_tokens,
+                            estimate.cache_read_input_tokens,
+                        ),
+                        None => (input_tokens, output_tokens, cache_creation_tokens, cache_read_tokens),
+                    };
                 
                 // Debug: Log Aug 20 token extraction
                 if is_aug20 && aug20_messages <= 5 {
@@ -500,26 +718,67 @@ impl ParquetSummaryReader {
                     .and_then(|v| v.as_str())
                     .unwrap_or("claude-3-sonnet");
 
-                // Calculate cost - prefer costUSD field but fallback to LiteLLM pricing
-                let cost = if let Some(cost_val) = msg.get("costUSD")
-                    .or_else(|| msg.get("cost_usd")) {
-                    cost_val.as_f64().unwrap_or(0.0)
-                } else {
-                    // Use hardcoded pricing as fallback since LiteLLM pricing is async
-                    // In the future, we could pre-fetch pricing data to avoid this
-                    crate::pricing::calculate_cost_simple(
-                        model,
-                        input_tokens,
-                        output_tokens,
-                        cache_creation_tokens,
-                        cache_read_tokens
+                // Draft/speculative model usage reported alongside the primary model
+                let secondary = msg.get("message")
+                    .and_then(KeeperIntegration::secondary_usage);
+
+                // Calculate cost - by default prefer costUSD, falling back to pricing
+                let recorded_cost = msg.get("costUSD")
+                    .or_else(|| msg.get("cost_usd"))
+                    .map(|cost_val| cost_val.as_f64().unwrap_or(0.0));
+                // Use hardcoded pricing as fallback since LiteLLM pricing is async
+                // In the future, we could pre-fetch pricing data to avoid this
+                let primary_cost = calculate_cost_simple(
+                    model,
+                    input_tokens,
+                    output_tokens,
+                    cache_creation_tokens,
+                    cache_read_tokens
+                );
+                let secondary_cost = secondary.as_ref().map_or(0.0, |s| {
+                    calculate_cost_simple(
+                        &s.model,
+                        s.usage.input_tokens,
+                        s.usage.output_tokens,
+                        s.usage.cache_creation_input_tokens,
+                        s.usage.cache_read_input_tokens
                     )
+                });
+                let (primary_cost, secondary_cost) = calculate_entry_cost(
+                    self.cost_mode,
+                    recorded_cost,
+                    primary_cost,
+                    secondary_cost,
+                );
+                let primary_cost = MicroDollars::from_dollars(primary_cost);
+                let secondary_cost = MicroDollars::from_dollars(secondary_cost);
+                let cost = primary_cost + secondary_cost;
+
+                let primary_tokens = TokenCounts {
+                    input_tokens,
+                    output_tokens,
+                    cache_creation_input_tokens: cache_creation_tokens,
+                    cache_read_input_tokens: cache_read_tokens,
                 };
 
+                // Secondary tokens count towards the entry's totals
+                let (input_tokens, output_tokens, cache_creation_tokens, cache_read_tokens) =
+                    match &secondary {
+                        Some(s) => (
+                            input_tokens + s.usage.input_tokens,
+                            output_tokens + s.usage.output_tokens,
+                            cache_creation_tokens + s.usage.cache_creation_input_tokens,
+                            cache_read_tokens + s.usage.cache_read_input_tokens,
+                        ),
+                        None => (input_tokens, output_tokens, cache_creation_tokens, cache_read_tokens),
+                    };
+
                 // Parse date for daily aggregation
-                let date_str = if let Ok(ts) = TimestampParser::parse(timestamp_str) {
+                let timestamp = TimestampParser::parse(timestamp_str).ok();
+                let date_str = if let Some(ts) = timestamp {
                     ts.format("%Y-%m-%d").to_string()
                 } else {
+                    file_undated += 1;
                     // Log when we can't parse timestamp
                     if timestamp_str.contains("2025-08-20") {
                         debug!("Failed to parse Aug 20 timestamp: {}", timestamp_str);
@@ -529,7 +788,12 @@ impl ParquetSummaryReader {
 
                 // Get or create session
                 let session = sessions_map.entry(session_id.clone())
-                    .or_insert_with(|| SessionData::new(session_id.clone(), project_name.clone()));
+                    .or_insert_with(|| {
+                        let mut session = SessionData::new(session_id.clone(), project_name.clone());
+                        session.instance_type = instance_type;
+                        session.vm = vm;
+                        session
+                    });
 
                 // Update session totals
                 session.input_tokens += input_tokens;
@@ -539,22 +803,38 @@ impl ParquetSummaryReader {
                 session.total_cost += cost;
                 session.last_activity = Some(timestamp_str.to_string());
                 session.models_used.insert(model.to_string());
+                session.record_model(timestamp_str, model, cost);
+                if let Some(timestamp) = timestamp {
+                    session.record_entry_time(timestamp);
+                }
+                *session.model_costs.entry(model.to_string()).or_default() += primary_cost;
+                if let Some(s) = &secondary {
+                    session.models_used.insert(s.model.clone());
+                    *session.model_costs.entry(s.model.clone()).or_default() += secondary_cost;
+                }
+                if is_estimated {
+                    session.estimated_entries += 1;
+                }
 
                 // Update daily usage
                 let daily = session.daily_usage.entry(date_str.clone())
-                    .or_insert_with(|| DailyUsage {
-                        input_tokens: 0,
-                        output_tokens: 0,
-                        cache_creation_tokens: 0,
-                        cache_read_tokens: 0,
-                        cost: 0.0,
-                    });
+                    .or_default();
                 
                 daily.input_tokens += input_tokens;
                 daily.output_tokens += output_tokens;
                 daily.cache_creation_tokens += cache_creation_tokens;
                 daily.cache_read_tokens += cache_read_tokens;
                 daily.cost += cost;
+                if let Some(ts) = timestamp {
+                    daily.record_entry_time(ts);
+                }
+                daily.add_model_usage(model, primary_tokens, primary_cost);
+                if let Some(s) = &secondary {
+                    daily.add_model_usage(&s.model, TokenCounts::from(&s.usage), secondary_cost);
+                }
+                if is_estimated {
+                    daily.estimated_entries += 1;
+                }
                 
                 // Debug: Track Aug 20 cost accumulation
                 if date_str == "2025-08-20" {
@@ -574,8 +854,11 @@ impl ParquetSummaryReader {
                       file_aug20, file_aug20_skipped_no_usage, file_aug20_skipped_dedup,
                       file_aug20 + file_aug20_skipped_no_usage + file_aug20_skipped_dedup);
             }
+            line_reader::record_backup_rows(&parquet_file, messages.len() as u64, file_undated);
         }
 
+        seen_messages.log_usage("parquet messages");
+
         // Convert to SessionOutput format
         let mut sessions: Vec<SessionOutput> = sessions_map
             .into_iter()
@@ -584,7 +867,7 @@ impl ParquetSummaryReader {
                 if session_data.daily_usage.contains_key("2025-08-20") {
                     let aug20_cost = session_data.daily_usage.get("2025-08-20")
                         .map(|d| d.cost)
-                        .unwrap_or(0.0);
+                        .unwrap_or_default();
                     info!(
                         "Session {} has Aug 20 data: ${:.2} (total session cost: ${:.2})",
                         &session_data.session_id[..20.min(session_data.session_id.len())],
@@ -593,7 +876,9 @@ impl ParquetSummaryReader {
                     );
                 }
                 
+                let duration = session_data.duration();
                 SessionOutput {
+                    display_id: SessionOutput::display_id_for(&session_data),
                     session_id: session_data.session_id,
                     project_path: session_data.project_path,
                     input_tokens: session_data.input_tokens,
@@ -601,8 +886,21 @@ impl ParquetSummaryReader {
                     cache_creation_tokens: session_data.cache_creation_tokens,
                     cache_read_tokens: session_data.cache_read_tokens,
                     total_cost: session_data.total_cost,
+                    cost_breakdown: session_data.daily_usage.values().map(|day| day.costs).sum(),
                     last_activity: session_data.last_activity.unwrap_or_else(|| "".to_string()),
-                    models_used: session_data.models_used.into_iter().collect(),
+                    models_used: {
+                        let mut models: Vec<String> =
+                            session_data.models_used.into_iter().collect();
+                        models.sort();
+                        models
+                    },
+                    estimated_entries: session_data.estimated_entries,
+                    model_costs: session_data.model_costs,
+                    instance_type: session_data.instance_type,
+                    root: None,
+                    vm: session_data.vm,
+                    model_switches: ModelSwitches::from_timeline(&session_data.model_timeline),
+                    duration,
                     daily_usage: session_data.daily_usage,
                 }
             })
@@ -621,10 +919,23 @@ impl ParquetSummaryReader {
             "Loaded detailed session data from parquet files"
         );
 
-        Ok(sessions)
+        Ok((sessions, skipped))
     }
 }
 
+/// Full path of a backed-up message's project, for when its recorded name
+/// is not trusted: the working directory, else the project directory of
+/// the session log it came from
+fn project_fallback(msg: &Value) -> Option<String> {
+    if let Some(cwd) = msg.get("cwd").and_then(|v| v.as_str()) {
+        return Some(cwd.to_string());
+    }
+    ["source_file", "sourceFile", "file_path"]
+        .iter()
+        .find_map(|field| msg.get(*field).and_then(|v| v.as_str()))
+        .and_then(|path| project_names::project_dir_of(Path::new(path)))
+}
+
 /// Statistics about backup files
 #[allow(dead_code)]
 pub struct BackupStats {
@@ -639,4 +950,59 @@ struct ParquetFileStats {
     total_cost: f64,
     total_tokens: u64,
     session_times: Vec<SystemTime>,
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_parquet_cache_rereads_changed_files_only() {
+        let temp = TempDir::new().unwrap();
+        let file = temp.path().join("part.parquet");
+        // Unreadable parquet yields no messages, which is cached like any other result
+        fs::write(&file, "not parquet").unwrap();
+
+        let cache = ParquetCache::default();
+        let first = cache.read(&file).unwrap();
+        assert!(first.is_empty());
+        assert!(Arc::ptr_eq(&first, &cache.read(&file).unwrap()));
+
+        fs::write(&file, "still not parquet").unwrap();
+        assert!(!Arc::ptr_eq(&first, &cache.read(&file).unwrap()));
+    }
+
+    #[test]
+    fn test_undated_backup_rows_count_against_the_error_budget() {
+        let temp = TempDir::new().unwrap();
+        let file = temp.path().join("undated.parquet");
+        let message = |id: &str, timestamp: &str| {
+            serde_json::json!({
+                "timestamp": timestamp,
+                "message": {
+                    "id": id,
+                    "model": "claude-3-5-sonnet-20241022",
+                    "usage": {"input_tokens": 100, "output_tokens": 10}
+                },
+                "requestId": format!("req_{id}"),
+                "session_id": "session-u",
+                "project_name": "-home-user-api"
+            })
+        };
+        let messages = vec![
+            message("msg_u1", "2025-02-01T10:00:00Z"),
+            message("msg_u2", "not a timestamp"),
+        ];
+
+        let reader = ParquetSummaryReader::new(temp.path().to_path_buf()).unwrap();
+        let (sessions, _) = reader
+            .sessions_from_messages(vec![(file.clone(), Ok(Arc::new(messages)))])
+            .unwrap();
+        assert_eq!(sessions.len(), 1);
+
+        let failures = line_reader::worst_files(usize::MAX);
+        let failure = failures.iter().find(|f| f.path == file).unwrap();
+        assert_eq!((failure.failed, failure.lines), (1, 2));
+        assert!(line_reader::quality_report().undated_lines >= 1);
+    }
+}
@@ -0,0 +1,283 @@
+//! Persistent cache of parsed session logs
+//!
+//! Parsing every JSONL log on each run dominates the runtime on long
+//! histories, even though almost all of them are finished sessions that never
+//! change again. The cache (`parse_cache.file`) keeps the usage records parsed
+//! from each log, keyed by path, modification time and size; a log whose
+//! metadata still matches is not opened again, and only new or modified logs
+//! are parsed.
+//!
+//! Records are cached rather than per-file totals because duplicates are
+//! dropped across files and costs are priced when aggregating, so pricing
+//! updates still apply to cached logs. A cache written by another version of
+//! claude-usage or under other `[quality]` rules, or one that cannot be read,
+//! is ignored and rebuilt.
+//!
+//! Each lookup is counted in a process-wide [`ParseCacheStats`], available
+//! from [`stats`]: logs served from the cache, logs parsed again because they
+//! changed, and logs seen for the first time. Reports list them under
+//! `metadata.parseCache` and in the `--verbose` footer, so it is easy to check
+//! that a run only parsed what changed.
+
+use crate::config::{get_config, QualityConfig};
+use crate::models::UsageEntry;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::{self, Metadata};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::SystemTime;
+use tracing::{debug, warn};
+
+/// Global parse cache counters
+static CACHED: AtomicU64 = AtomicU64::new(0);
+static REPARSED: AtomicU64 = AtomicU64::new(0);
+static DISCOVERED: AtomicU64 = AtomicU64::new(0);
+
+/// How the logs read in this run were served by the parse cache
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ParseCacheStats {
+    /// Logs taken unchanged from the cache
+    pub cached: u64,
+    /// Cached logs that changed since, parsed again
+    pub reparsed: u64,
+    /// Logs not in the cache yet, parsed for the first time
+    pub discovered: u64,
+}
+
+impl ParseCacheStats {
+    /// Number of logs looked up in the cache
+    pub fn total(&self) -> u64 {
+        self.cached + self.reparsed + self.discovered
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.total() == 0
+    }
+}
+
+/// Snapshot of the parse cache counters
+pub fn stats() -> ParseCacheStats {
+    ParseCacheStats {
+        cached: CACHED.load(Ordering::Relaxed),
+        reparsed: REPARSED.load(Ordering::Relaxed),
+        discovered: DISCOVERED.load(Ordering::Relaxed),
+    }
+}
+
+/// One usage record and the 1-based line it was read from
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CachedEntry {
+    pub line: usize,
+    pub entry: UsageEntry,
+}
+
+/// Usage records of one log as of the recorded modification time and size
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CachedFile {
+    pub path: PathBuf,
+    pub modified: SystemTime,
+    pub len: u64,
+    pub entries: Vec<CachedEntry>,
+}
+
+impl CachedFile {
+    /// Cache `entries` for the log at `path` with metadata `meta`
+    pub fn new(path: &Path, meta: &Metadata, entries: Vec<CachedEntry>) -> Option<Self> {
+        Some(Self {
+            path: path.to_path_buf(),
+            modified: meta.modified().ok()?,
+            len: meta.len(),
+            entries,
+        })
+    }
+
+    fn matches(&self, meta: &Metadata) -> bool {
+        self.len == meta.len()
+            && meta
+                .modified()
+                .is_ok_and(|modified| modified == self.modified)
+    }
+}
+
+/// On-disk layout of the cache
+#[derive(Serialize, Deserialize)]
+struct CacheFile {
+    version: String,
+    /// Rules the cached usage was sanitized with
+    #[serde(default)]
+    quality: Option<QualityConfig>,
+    files: Vec<CachedFile>,
+}
+
+/// Parsed logs keyed by path
+#[derive(Debug, Default)]
+pub struct ParseCache {
+    files: HashMap<PathBuf, CachedFile>,
+    changed: bool,
+}
+
+impl ParseCache {
+    /// Load the cache at `path`; a missing, unreadable or outdated cache is empty
+    pub fn load(path: &Path) -> Self {
+        let content = match fs::read(path) {
+            Ok(content) => content,
+            Err(_) => return Self::default(),
+        };
+        let cache: CacheFile = match serde_json::from_slice(&content) {
+            Ok(cache) => cache,
+            Err(e) => {
+                warn!(file = %path.display(), error = %e, "Ignoring unreadable parse cache");
+                return Self::default();
+            }
+        };
+        if cache.version != env!("CARGO_PKG_VERSION") {
+            debug!(
+                file = %path.display(),
+                version = %cache.version,
+                "Discarding parse cache from another version"
+            );
+            return Self::default();
+        }
+        if cache.quality.as_ref() != Some(&get_config().quality) {
+            debug!(
+                file = %path.display(),
+                "Discarding parse cache sanitized under other [quality] rules"
+            );
+            return Self::default();
+        }
+
+        Self {
+            files: cache
+                .files
+                .into_iter()
+                .map(|file| (file.path.clone(), file))
+                .collect(),
+            changed: false,
+        }
+    }
+
+    /// Write the cache to `path` if anything changed since it was loaded
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if !self.changed {
+            return Ok(());
+        }
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create {}", parent.display()))?;
+        }
+
+        let mut files: Vec<&CachedFile> = self.files.values().collect();
+        files.sort_by(|a, b| a.path.cmp(&b.path));
+        let content = serde_json::to_vec(&serde_json::json!({
+            "version": env!("CARGO_PKG_VERSION"),
+            "quality": get_config().quality,
+            "files": files,
+        }))?;
+        let temp_path = path.with_extension("json.tmp");
+        fs::write(&temp_path, content)
+            .with_context(|| format!("Failed to write parse cache: {}", temp_path.display()))?;
+        fs::rename(&temp_path, path)
+            .with_context(|| format!("Failed to save parse cache: {}", path.display()))
+    }
+
+    /// Records cached for `path`, if its metadata still matches `meta`,
+    /// counting the lookup in [`stats`]
+    pub fn get(&self, path: &Path, meta: &Metadata) -> Option<&[CachedEntry]> {
+        let file = self.files.get(path);
+        let cached = file.filter(|file| file.matches(meta));
+        let counter = match (file, cached) {
+            (_, Some(_)) => &CACHED,
+            (Some(_), None) => &REPARSED,
+            (None, _) => &DISCOVERED,
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+        cached.map(|file| file.entries.as_slice())
+    }
+
+    /// Cache a freshly parsed log, replacing any older version of it
+    pub fn insert(&mut self, file: CachedFile) {
+        self.files.insert(file.path.clone(), file);
+        self.changed = true;
+    }
+
+    /// Forget logs that no longer exist
+    pub fn prune_missing(&mut self) {
+        let before = self.files.len();
+        self.files.retain(|path, _| path.exists());
+        self.changed |= self.files.len() != before;
+    }
+
+    #[allow(dead_code)]
+    pub fn len(&self) -> usize {
+        self.files.len()
+    }
+
+    #[allow(dead_code)]
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::MessageData;
+    use tempfile::TempDir;
+
+    fn entry(id: &str) -> CachedEntry {
+        CachedEntry {
+            line: 1,
+            entry: UsageEntry {
+                timestamp: "2025-01-10T10:00:00Z".to_string(),
+                message: MessageData {
+                    id: id.to_string(),
+                    model: "claude-3-5-sonnet-20241022".to_string(),
+                    usage: None,
+                    secondary_usage: None,
+                },
+                cost_usd: Some(1.0),
+                request_id: format!("req_{}", id),
+            },
+        }
+    }
+
+    #[test]
+    fn test_cache_round_trip_and_invalidation() {
+        let temp = TempDir::new().unwrap();
+        let log = temp.path().join("session.jsonl");
+        let cache_path = temp.path().join("cache").join("index.json");
+        fs::write(&log, "{}\n").unwrap();
+
+        let mut cache = ParseCache::load(&cache_path);
+        assert!(cache.is_empty());
+        let meta = fs::metadata(&log).unwrap();
+        cache.insert(CachedFile::new(&log, &meta, vec![entry("a")]).unwrap());
+        cache.save(&cache_path).unwrap();
+
+        let cache = ParseCache::load(&cache_path);
+        let cached = cache.get(&log, &fs::metadata(&log).unwrap()).unwrap();
+        assert_eq!(cached[0].entry.message.id, "a");
+
+        // A log that grew is parsed again
+        fs::write(&log, "{}\n{}\n").unwrap();
+        assert!(cache.get(&log, &fs::metadata(&log).unwrap()).is_none());
+
+        // Other tests share the counters, so only check they moved
+        let before = stats();
+        cache.get(&log, &fs::metadata(&log).unwrap());
+        cache.get(&cache_path, &fs::metadata(&cache_path).unwrap());
+        let after = stats();
+        assert!(after.reparsed > before.reparsed && after.discovered > before.discovered);
+
+        let mut cache = ParseCache::load(&cache_path);
+        fs::remove_file(&log).unwrap();
+        cache.prune_missing();
+        assert!(cache.is_empty());
+
+        fs::write(&cache_path, "not json").unwrap();
+        assert!(ParseCache::load(&cache_path).is_empty());
+    }
+}
@@ -33,6 +33,7 @@
 //! - [`FilterProcessor`] - Filters entries based on a predicate function
 //! - [`StreamProcessor`] - Processes entries through a callback function
 //! - [`ProcessedEntryCollector`] - Collects enhanced ProcessedEntry objects
+//! - [`ProvenanceCollector`] - Collects ProcessedEntry objects tagged with their [`Provenance`]
 //! - [`ValidEntryProcessor`] - Processes only entries with valid usage data
 //!
 //! ## Usage Examples
@@ -71,6 +72,10 @@
 //! }
 //! ```
 //!
+//! A processor run with [`FileParser::process_jsonl_file`] sees one file as it
+//! is; [`crate::analyzer::ClaudeUsageAnalyzer::process_with`] runs it over
+//! every discovered log, filtered and deduplicated like the reports.
+//!
 //! ## Integration Points
 //!
 //! This parser integrates with:
@@ -79,18 +84,25 @@
 //! - [`SessionUtils`] for session management utilities
 //! - Main analysis pipeline through [`crate::analyzer::ClaudeUsageAnalyzer`]
 
-use crate::file_discovery::FileDiscovery;
+use crate::config::get_config;
+use crate::dedup::CostMode;
+use crate::file_discovery::{FileDiscovery, ProjectDirFilter};
+use crate::io_throttle;
 use crate::keeper_integration::KeeperIntegration;
+use crate::line_reader::{self, BoundedLines, Line};
+use crate::memory;
 use crate::models::*;
+use crate::pricing::calculate_cost_simple;
 use crate::session_utils::SessionUtils;
 use crate::timestamp_parser::TimestampParser;
 use anyhow::Result;
 use chrono::{DateTime, Utc};
+use std::io::BufReader;
 use std::path::{Path, PathBuf};
+use tracing::{debug, warn};
 
 pub struct FileParser {
     file_discovery: FileDiscovery,
-    #[allow(dead_code)]
     keeper_integration: KeeperIntegration,
 }
 
@@ -112,6 +124,7 @@ pub struct ProcessedEntry {
     pub date: String, // YYYY-MM-DD format
     pub line_number: usize,
     pub total_tokens: u32,
+    pub provenance: Option<Provenance>,
 }
 
 #[allow(dead_code)]
@@ -127,20 +140,95 @@ impl ProcessedEntry {
             date,
             line_number,
             total_tokens,
+            provenance: None,
         })
     }
 
-    fn calculate_total_tokens(entry: &UsageEntry) -> u32 {
-        if let Some(usage) = &entry.message.usage {
-            usage.input_tokens
-                + usage.output_tokens
-                + usage.cache_creation_input_tokens
-                + usage.cache_read_input_tokens
-        } else {
-            0
+    /// Attach the source location and dedup hash this entry was read from
+    pub fn with_provenance(mut self, provenance: Provenance) -> Self {
+        self.provenance = Some(provenance);
+        self
+    }
+
+    /// Cost of the entry's primary and secondary tokens at current pricing,
+    /// ignoring any recorded `costUSD`
+    pub fn computed_cost(&self) -> f64 {
+        let primary_cost = self.entry.message.usage.as_ref().map_or(0.0, |usage| {
+            calculate_cost_simple(
+                &self.entry.message.model,
+                usage.input_tokens,
+                usage.output_tokens,
+                usage.cache_creation_input_tokens,
+                usage.cache_read_input_tokens,
+            )
+        });
+        let secondary_cost = self
+            .entry
+            .message
+            .secondary_usage
+            .as_ref()
+            .map_or(0.0, |secondary| {
+                calculate_cost_simple(
+                    &secondary.model,
+                    secondary.usage.input_tokens,
+                    secondary.usage.output_tokens,
+                    secondary.usage.cache_creation_input_tokens,
+                    secondary.usage.cache_read_input_tokens,
+                )
+            });
+        primary_cost + secondary_cost
+    }
+
+    /// Convert into the per-entry record used by JSON exports
+    pub fn to_export(&self) -> EntryExport {
+        self.to_export_with(CostMode::Auto)
+    }
+
+    /// Convert into the per-entry record, taking the cost as `mode` says
+    pub fn to_export_with(&self, mode: CostMode) -> EntryExport {
+        let usage = self.entry.message.usage.as_ref();
+        let input_tokens = usage.map(|u| u.input_tokens).unwrap_or(0);
+        let output_tokens = usage.map(|u| u.output_tokens).unwrap_or(0);
+        let cache_creation_tokens = usage.map(|u| u.cache_creation_input_tokens).unwrap_or(0);
+        let cache_read_tokens = usage.map(|u| u.cache_read_input_tokens).unwrap_or(0);
+        let secondary_usage = self.entry.message.secondary_usage.clone();
+        let cost = match mode {
+            CostMode::Auto => self.entry.cost_usd.unwrap_or_else(|| self.computed_cost()),
+            CostMode::Calculate => self.computed_cost(),
+            CostMode::Display => self.entry.cost_usd.unwrap_or(0.0),
+        };
+
+        EntryExport {
+            timestamp: self.entry.timestamp.clone(),
+            request_id: self.entry.request_id.clone(),
+            message_id: self.entry.message.id.clone(),
+            model: self.entry.message.model.clone(),
+            input_tokens,
+            output_tokens,
+            cache_creation_tokens,
+            cache_read_tokens,
+            cost,
+            secondary_usage,
+            provenance: self.provenance.clone(),
         }
     }
 
+    fn calculate_total_tokens(entry: &UsageEntry) -> u32 {
+        let primary = entry.message.usage.as_ref();
+        let secondary = entry.message.secondary_usage.as_ref().map(|s| &s.usage);
+
+        primary
+            .into_iter()
+            .chain(secondary)
+            .map(|usage| {
+                usage.input_tokens
+                    + usage.output_tokens
+                    + usage.cache_creation_input_tokens
+                    + usage.cache_read_input_tokens
+            })
+            .sum()
+    }
+
     pub fn input_tokens(&self) -> u32 {
         self.entry
             .message
@@ -191,10 +279,28 @@ impl FileParser {
         self.file_discovery.discover_claude_paths(exclude_vms)
     }
 
+    pub fn discover_log_roots(&self, exclude_vms: bool) -> Result<Vec<PathBuf>> {
+        self.file_discovery.discover_log_roots(exclude_vms)
+    }
+
     pub fn find_jsonl_files(&self, claude_paths: &[PathBuf]) -> Result<Vec<(PathBuf, PathBuf)>> {
         self.file_discovery.find_jsonl_files(claude_paths)
     }
 
+    pub fn find_project_jsonl_files(
+        &self,
+        claude_paths: &[PathBuf],
+        project: Option<&ProjectDirFilter>,
+    ) -> Result<Vec<(PathBuf, PathBuf)>> {
+        self.file_discovery
+            .find_project_jsonl_files(claude_paths, project)
+    }
+
+    /// Paths the JSONL searches skipped because they reach a log already found
+    pub fn duplicate_paths(&self) -> u64 {
+        self.file_discovery.duplicate_paths()
+    }
+
     pub fn should_include_file(
         &self,
         file_path: &Path,
@@ -210,6 +316,7 @@ impl FileParser {
         self.file_discovery.get_earliest_timestamp(file_path)
     }
 
+    #[allow(dead_code)]
     pub fn sort_files_by_timestamp(
         &self,
         file_tuples: Vec<(PathBuf, PathBuf)>,
@@ -221,6 +328,7 @@ impl FileParser {
         TimestampParser::parse(timestamp_str)
     }
 
+    #[allow(dead_code)]
     pub fn extract_session_info(&self, session_dir_name: &str) -> (String, String) {
         SessionUtils::extract_session_info(session_dir_name)
     }
@@ -229,20 +337,112 @@ impl FileParser {
         SessionUtils::create_unique_hash(entry)
     }
 
+    /// Parse one JSONL line, returning `None` for lines that are not usage entries
+    pub fn parse_line(&self, line: &str) -> Option<UsageEntry> {
+        self.keeper_integration.parse_single_line(line)
+    }
+
+    pub fn instance_name(&self, claude_path: &Path) -> String {
+        self.file_discovery.instance_name(claude_path)
+    }
+
+    pub fn instance_type(&self, claude_path: &Path) -> InstanceType {
+        self.file_discovery.instance_type(claude_path)
+    }
+
+    pub fn extra_root_of(&self, claude_path: &Path) -> Option<PathBuf> {
+        self.file_discovery.extra_root_of(claude_path)
+    }
+
+    /// Stream a JSONL file line by line through a processor
+    ///
+    /// Line numbers passed to the processor are 1-based and count every line
+    /// in the file, including blank and unparseable ones. Lines longer than
+    /// `processing.max_line_length_kb`, containing invalid UTF-8 or not valid
+    /// JSON are skipped and counted in the parse quality report.
+    pub fn process_jsonl_file<P: JsonlProcessor>(
+        &self,
+        file_path: &Path,
+        mut processor: P,
+    ) -> Result<P::Output> {
+        let capacity = memory::get_adaptive_batch_size(get_config().memory.buffer_size_kb * 1024);
+        let reader = BufReader::with_capacity(capacity, io_throttle::global().open(file_path)?);
+        let mut lines = 0;
+        let mut failed = 0;
+
+        for line in BoundedLines::from_config(reader).with_quality_tracking() {
+            let (line_number, line) = line?;
+            lines += 1;
+            match line {
+                Line::Text(text) => {
+                    if let Some(entry) = self.keeper_integration.parse_single_line(&text) {
+                        processor.process_entry(entry, line_number)?;
+                    } else if !text.trim().is_empty()
+                        && serde_json::from_str::<serde::de::IgnoredAny>(&text).is_err()
+                    {
+                        // Valid JSON without usage (user turns, summaries) is expected
+                        failed += 1;
+                        line_reader::record_parse_error();
+                        debug!(
+                            file = %file_path.display(),
+                            line = line_number,
+                            "Skipping line that is not valid JSON"
+                        );
+                    }
+                }
+                Line::Oversized(len) => {
+                    failed += 1;
+                    warn!(
+                        file = %file_path.display(),
+                        line = line_number,
+                        bytes = len,
+                        "Skipping oversized line"
+                    )
+                }
+                Line::Invalid => {
+                    failed += 1;
+                    warn!(
+                        file = %file_path.display(),
+                        line = line_number,
+                        "Skipping line with invalid UTF-8"
+                    )
+                }
+            }
+        }
+
+        line_reader::record_file_failures(file_path, lines, failed);
+        processor.finalize()
+    }
+
+    /// Parse a JSONL file into entries tagged with their provenance
+    pub fn collect_entries_with_provenance(
+        &self,
+        file_path: &Path,
+        instance: &str,
+        instance_type: InstanceType,
+    ) -> Result<Vec<ProcessedEntry>> {
+        self.process_jsonl_file(
+            file_path,
+            ProvenanceCollector::new(file_path, instance, instance_type),
+        )
+    }
+
     #[allow(dead_code)]
     pub fn find_session_blocks_files(&self, claude_paths: &[PathBuf]) -> Result<Vec<PathBuf>> {
-        self.file_discovery.find_session_blocks_files(claude_paths)
+        let extra_dirs = &get_config().paths.session_blocks_dirs;
+        self.file_discovery
+            .find_session_blocks_files(claude_paths, extra_dirs)
     }
 
+    /// Session blocks of every instance, VM and `paths.session_blocks_dirs`
+    /// directory, merged and oldest first
     #[allow(dead_code)]
-    pub fn get_latest_session_blocks(&self, claude_paths: &[PathBuf]) -> Result<Vec<SessionBlock>> {
-        let block_files = self.find_session_blocks_files(claude_paths)?;
-
-        if let Some(latest_file) = block_files.first() {
-            self.parse_session_blocks_file(latest_file)
-        } else {
-            Ok(Vec::new())
+    pub fn get_session_blocks(&self, claude_paths: &[PathBuf]) -> Result<Vec<SessionBlock>> {
+        let mut blocks = Vec::new();
+        for file in self.find_session_blocks_files(claude_paths)? {
+            blocks.extend(self.parse_session_blocks_file(&file)?);
         }
+        Ok(SessionUtils::merge_session_blocks(blocks))
     }
 
     #[allow(dead_code)]
@@ -434,6 +634,49 @@ impl JsonlProcessor for ProcessedEntryCollector {
     }
 }
 
+// Processor that collects ProcessedEntry objects tagged with where they came from
+pub struct ProvenanceCollector {
+    entries: Vec<ProcessedEntry>,
+    parser: FileParser,
+    source_file: String,
+    instance: String,
+    instance_type: InstanceType,
+}
+
+impl ProvenanceCollector {
+    pub fn new(file_path: &Path, instance: &str, instance_type: InstanceType) -> Self {
+        Self {
+            entries: Vec::new(),
+            parser: FileParser::new(),
+            source_file: file_path.to_string_lossy().to_string(),
+            instance: instance.to_string(),
+            instance_type,
+        }
+    }
+}
+
+impl JsonlProcessor for ProvenanceCollector {
+    type Output = Vec<ProcessedEntry>;
+
+    fn process_entry(&mut self, entry: UsageEntry, line_number: usize) -> Result<()> {
+        let provenance = Provenance {
+            source_file: self.source_file.clone(),
+            line_number,
+            instance: self.instance.clone(),
+            instance_type: self.instance_type,
+            dedup_hash: self.parser.create_unique_hash(&entry),
+        };
+        if let Ok(processed) = ProcessedEntry::new(entry, &self.parser, line_number) {
+            self.entries.push(processed.with_provenance(provenance));
+        }
+        Ok(())
+    }
+
+    fn finalize(self) -> Result<Self::Output> {
+        Ok(self.entries)
+    }
+}
+
 // Processor that only processes valid entries (with usage data) through a callback
 #[allow(dead_code)]
 pub struct ValidEntryProcessor<F>
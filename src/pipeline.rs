@@ -0,0 +1,806 @@
+//! Session log processing pipeline
+//!
+//! Every session log of every Claude instance (the host installation and
+//! each VM or devcontainer) goes through discovery → parse → aggregate.
+//! Instances are discovered first; their logs are then parsed on up to
+//! `processing.parallel_chunks` worker threads while the calling thread
+//! deduplicates and aggregates each parsed log as it arrives, so parsing and
+//! aggregation overlap instead of one waiting for the other to finish.
+//!
+//! Parsed logs are consumed in discovery order: instances in the order they
+//! were found, and each instance's logs sorted by path. The first log in that
+//! order keeps a duplicated entry, so results do not depend on thread timing.
+//! Workers parse at most a few logs ahead of the consumer, which bounds the
+//! parsed entries held in memory; under memory pressure fewer workers parse
+//! and the window shrinks with them (see [`crate::memory`]).
+//!
+//! An instance whose logs cannot be listed is logged and left out of the
+//! results instead of failing the whole run; the failures are reported
+//! alongside the merged sessions. A session log that cannot be read is
+//! retried up to `processing.max_retries` times and then skipped, so one
+//! unreadable file (permissions, a transient NFS error) only loses its own
+//! entries; skipped files are reported as `failed_files`.
+//!
+//! Reports read the home installation from claude-keeper's backups when
+//! there are any (see [`crate::parquet`]) and everything else through the
+//! pipeline: the Claude config directories and the instances under
+//! `paths.extra_roots`, which claude-keeper does not back up, and every
+//! instance while no backup exists yet. `status` and the web server read all
+//! their instances through it.
+//!
+//! [`process_instances_cached`] reuses the records of logs unchanged since
+//! they were last parsed (see [`crate::parse_cache`]); dedup and aggregation
+//! still run over every record.
+
+use crate::config::get_config;
+use crate::dedup_set::DedupSet;
+use crate::memory;
+use crate::models::*;
+use crate::parse_cache::{CachedEntry, CachedFile, ParseCache};
+use crate::parser::{FileParser, ProcessedEntry, ProcessedEntryCollector};
+use crate::pricing::{attribute_entry_cost, calculate_cost_simple};
+use crate::project_names::{self, Expected};
+use anyhow::Result;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{mpsc, Condvar, Mutex};
+use std::time::Duration;
+use tracing::{debug, warn};
+
+/// Pause before the first retry of a failed read, growing with each attempt
+const RETRY_DELAY: Duration = Duration::from_millis(50);
+
+/// Logs each active worker may parse ahead of the consumer
+const IN_FLIGHT_PER_WORKER: usize = 2;
+
+/// How often a worker paused by memory pressure checks it again
+const PRESSURE_POLL: Duration = Duration::from_millis(50);
+
+/// Merged result of running every instance pipeline
+#[derive(Debug, Default)]
+pub struct PipelineResults {
+    /// Sessions from all instances, most recently active first
+    pub sessions: Vec<SessionOutput>,
+    /// Instance roots whose pipeline failed, with the error
+    pub failed_instances: Vec<(PathBuf, String)>,
+    /// Session logs skipped after every read attempt failed
+    pub failed_files: Vec<FailedFile>,
+}
+
+/// A file that could not be read, even after retrying
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FailedFile {
+    pub path: PathBuf,
+    /// Error of the last attempt
+    pub error: String,
+    pub attempts: usize,
+}
+
+/// What the sessions of an instance root are tagged with
+struct Instance {
+    instance_type: InstanceType,
+    /// Extra Claude root the instance was found under
+    root: Option<String>,
+    /// Name of the VM, for VM instances
+    vm: Option<String>,
+}
+
+/// A session log to parse, and the instance it belongs to
+struct SessionLog {
+    /// Index of the instance in `instances`
+    instance: usize,
+    path: PathBuf,
+    session_dir: PathBuf,
+}
+
+/// Entries read from one session log, with their dedup hashes
+struct ParsedLog {
+    entries: Vec<(Option<String>, ProcessedEntry)>,
+    /// The log's records, when it was parsed rather than taken from the
+    /// parse cache
+    parsed_file: Option<CachedFile>,
+}
+
+/// Process the session logs of every instance root and merge the sessions
+///
+/// Fails only when memory use goes over `memory.max_memory_mb`; other
+/// failures are reported in the results.
+pub fn process_instances(claude_paths: &[PathBuf]) -> Result<PipelineResults> {
+    Ok(run_pipelines(claude_paths, None)?.0)
+}
+
+/// Like [`process_instances`], but only parse logs that are not in `cache`
+/// or changed since they were cached, and cache the newly parsed ones
+pub fn process_instances_cached(
+    claude_paths: &[PathBuf],
+    cache: &mut ParseCache,
+) -> Result<PipelineResults> {
+    let (results, parsed_files) = run_pipelines(claude_paths, Some(cache))?;
+    debug!(
+        parsed = parsed_files.len(),
+        "Parsed logs missing from the parse cache"
+    );
+    for file in parsed_files {
+        cache.insert(file);
+    }
+    cache.prune_missing();
+    Ok(results)
+}
+
+/// Run the pipeline, also returning the logs that were parsed rather than
+/// taken from `cache`
+fn run_pipelines(
+    claude_paths: &[PathBuf],
+    cache: Option<&ParseCache>,
+) -> Result<(PipelineResults, Vec<CachedFile>)> {
+    let workers = get_config().processing.parallel_chunks;
+    let mut results = PipelineResults::default();
+    let mut parsed_files = Vec::new();
+
+    let discovered = run_parallel(claude_paths, workers, |root| discover_instance(root));
+    let mut instances = Vec::new();
+    let mut logs = Vec::new();
+    for (root, result) in claude_paths.iter().zip(discovered) {
+        match result {
+            Ok((found, files)) => {
+                let instance = instances.len();
+                instances.push(found);
+                logs.extend(files.into_iter().map(|(path, session_dir)| SessionLog {
+                    instance,
+                    path,
+                    session_dir,
+                }));
+            }
+            Err(e) if e.is::<memory::MemoryLimitExceeded>() => return Err(e),
+            Err(e) => {
+                warn!(
+                    instance = %root.display(),
+                    error = %e,
+                    "Skipping instance that failed to process"
+                );
+                results
+                    .failed_instances
+                    .push((root.clone(), format!("{:#}", e)));
+            }
+        }
+    }
+
+    let seen_hashes = DedupSet::from_config();
+    let mut sessions: HashMap<(usize, String), SessionData> = HashMap::new();
+    run_pipelined(
+        &logs,
+        workers,
+        // Each worker owns its parser so workers share no parsing state
+        FileParser::new,
+        |parser, log| parse_log(parser, log, cache),
+        |log, parsed| {
+            let parsed = match parsed? {
+                Ok(parsed) => parsed,
+                Err(failed) => {
+                    results.failed_files.push(failed);
+                    return Ok(());
+                }
+            };
+            parsed_files.extend(parsed.parsed_file);
+            aggregate_log(log, parsed.entries, &instances, &seen_hashes, &mut sessions);
+            Ok(())
+        },
+    )?;
+    seen_hashes.log_usage("entries");
+
+    results.sessions = sessions.into_values().map(SessionOutput::from).collect();
+    results.sessions.sort_by(|a, b| {
+        b.last_activity
+            .cmp(&a.last_activity)
+            .then_with(|| a.session_id.cmp(&b.session_id))
+    });
+    Ok((results, parsed_files))
+}
+
+/// Apply `f` to every item on up to `workers` threads, keeping input order
+fn run_parallel<T, R, F>(items: &[T], workers: usize, f: F) -> Vec<R>
+where
+    T: Sync,
+    R: Send,
+    F: Fn(&T) -> R + Sync,
+{
+    let workers = workers.clamp(1, items.len().max(1));
+    if workers == 1 {
+        return items.iter().map(f).collect();
+    }
+
+    let next = AtomicUsize::new(0);
+    let slots: Mutex<Vec<Option<R>>> = Mutex::new(items.iter().map(|_| None).collect());
+
+    std::thread::scope(|scope| {
+        for worker in 0..workers {
+            let next = &next;
+            let slots = &slots;
+            let f = &f;
+            scope.spawn(move || loop {
+                // Under memory pressure the extra workers stop taking items
+                if worker >= memory::get_adaptive_workers(workers) {
+                    break;
+                }
+                let index = next.fetch_add(1, Ordering::Relaxed);
+                let Some(item) = items.get(index) else {
+                    break;
+                };
+                let result = f(item);
+                slots.lock().unwrap_or_else(|e| e.into_inner())[index] = Some(result);
+            });
+        }
+    });
+
+    slots
+        .into_inner()
+        .unwrap_or_else(|e| e.into_inner())
+        .into_iter()
+        .flatten()
+        .collect()
+}
+
+/// Apply `produce` to every item on up to `workers` threads, each with its
+/// own state from `init`, and hand the results to `consume` on the calling
+/// thread in input order as they become ready
+///
+/// Workers produce at most [`IN_FLIGHT_PER_WORKER`] results per active
+/// worker ahead of `consume`, so a slow consumer holds them back instead of
+/// results piling up. The first error from `consume` stops the workers and
+/// is returned.
+fn run_pipelined<T, S, R>(
+    items: &[T],
+    workers: usize,
+    init: impl Fn() -> S + Sync,
+    produce: impl Fn(&mut S, &T) -> R + Sync,
+    mut consume: impl FnMut(&T, R) -> Result<()>,
+) -> Result<()>
+where
+    T: Sync,
+    R: Send,
+{
+    let workers = workers.clamp(1, items.len().max(1));
+    if workers == 1 {
+        let mut state = init();
+        for item in items {
+            consume(item, produce(&mut state, item))?;
+        }
+        return Ok(());
+    }
+
+    let window = Mutex::new(Window::default());
+    let progress = Condvar::new();
+    let (sender, receiver) = mpsc::channel();
+
+    std::thread::scope(|scope| {
+        for worker in 0..workers {
+            let window = &window;
+            let progress = &progress;
+            let init = &init;
+            let produce = &produce;
+            let sender = sender.clone();
+            scope.spawn(move || {
+                let mut state = init();
+                while let Some(index) = claim_item(window, progress, worker, workers, items.len()) {
+                    let result = produce(&mut state, &items[index]);
+                    if sender.send((index, result)).is_err() {
+                        break;
+                    }
+                }
+            });
+        }
+        drop(sender);
+
+        // Results arriving ahead of their turn wait here
+        let mut pending = BTreeMap::new();
+        let mut next = 0;
+        let mut outcome = Ok(());
+        'receive: for (index, result) in &receiver {
+            pending.insert(index, result);
+            while let Some(result) = pending.remove(&next) {
+                if let Err(e) = consume(&items[next], result) {
+                    outcome = Err(e);
+                    break 'receive;
+                }
+                next += 1;
+                window.lock().unwrap_or_else(|e| e.into_inner()).consumed = next;
+                progress.notify_all();
+            }
+        }
+
+        window.lock().unwrap_or_else(|e| e.into_inner()).stopped = true;
+        progress.notify_all();
+        outcome
+    })
+}
+
+/// Progress of [`run_pipelined`], shared by its workers and consumer
+#[derive(Default)]
+struct Window {
+    /// Index of the next item to produce
+    next: usize,
+    /// Number of results consumed so far
+    consumed: usize,
+    /// Set once the consumer is done, possibly early
+    stopped: bool,
+}
+
+/// Wait until `worker` may produce another item and return its index, or
+/// `None` once there is nothing left to produce
+fn claim_item(
+    window: &Mutex<Window>,
+    progress: &Condvar,
+    worker: usize,
+    workers: usize,
+    len: usize,
+) -> Option<usize> {
+    let mut state = window.lock().unwrap_or_else(|e| e.into_inner());
+    loop {
+        if state.stopped || state.next >= len {
+            return None;
+        }
+        // Under memory pressure the extra workers pause and the window shrinks
+        let active = memory::get_adaptive_workers(workers);
+        if worker < active && state.next < state.consumed + active * IN_FLIGHT_PER_WORKER {
+            state.next += 1;
+            return Some(state.next - 1);
+        }
+        state = progress
+            .wait_timeout(state, PRESSURE_POLL)
+            .unwrap_or_else(|e| e.into_inner())
+            .0;
+    }
+}
+
+/// Call `read` until it succeeds, retrying up to `processing.max_retries` times
+pub(crate) fn read_with_retries<T>(
+    path: &Path,
+    mut read: impl FnMut() -> Result<T>,
+) -> std::result::Result<T, FailedFile> {
+    let max_retries = get_config().processing.max_retries;
+    let mut attempts = 0;
+    loop {
+        attempts += 1;
+        match read() {
+            Ok(value) => return Ok(value),
+            Err(e) if attempts > max_retries => {
+                warn!(
+                    file = %path.display(),
+                    attempts,
+                    error = %e,
+                    "Skipping file that could not be read"
+                );
+                return Err(FailedFile {
+                    path: path.to_path_buf(),
+                    error: format!("{:#}", e),
+                    attempts,
+                });
+            }
+            Err(e) => {
+                debug!(file = %path.display(), attempts, error = %e, "Retrying file read");
+                std::thread::sleep(RETRY_DELAY * attempts as u32);
+            }
+        }
+    }
+}
+
+/// Discovery stage: list the session logs of one instance, sorted by path
+fn discover_instance(claude_path: &Path) -> Result<(Instance, Vec<(PathBuf, PathBuf)>)> {
+    let parser = FileParser::new();
+    let mut files = parser.find_jsonl_files(&[claude_path.to_path_buf()])?;
+    files.sort();
+    debug!(
+        instance = %claude_path.display(),
+        files = files.len(),
+        "Processing instance"
+    );
+    let instance_type = parser.instance_type(claude_path);
+    // VMs marked by `.instance-type` or under CLAUDE_VMS_DIR are named after their root
+    let vm = (instance_type == InstanceType::Vm)
+        .then(|| {
+            InstanceType::vm_name(claude_path).or_else(|| {
+                claude_path
+                    .file_name()
+                    .map(|name| name.to_string_lossy().to_string())
+            })
+        })
+        .flatten();
+    let instance = Instance {
+        instance_type,
+        root: parser
+            .extra_root_of(claude_path)
+            .map(|root| root.display().to_string()),
+        vm,
+    };
+    Ok((instance, files))
+}
+
+/// Parsing stage: read one session log
+///
+/// With a `cache`, a log it holds unchanged is not read again, and one that
+/// was read is returned as `parsed_file`. Fails only when memory use goes
+/// over the limit; a log that cannot be read is returned as a [`FailedFile`].
+fn parse_log(
+    parser: &FileParser,
+    log: &SessionLog,
+    cache: Option<&ParseCache>,
+) -> Result<std::result::Result<ParsedLog, FailedFile>> {
+    let (parsed, parsed_file) = match read_entries(parser, &log.path, cache)? {
+        Ok(read) => read,
+        Err(failed) => return Ok(Err(failed)),
+    };
+    let entries = parsed
+        .into_iter()
+        .map(|entry| (parser.create_unique_hash(&entry.entry), entry))
+        .collect();
+    Ok(Ok(ParsedLog {
+        entries,
+        parsed_file,
+    }))
+}
+
+/// Read one session log outside a pipeline run, as the parsing stage does
+///
+/// A log `cache` holds unchanged is not read again, and one that was read
+/// is added to it. Fails only when memory use goes over the limit; a log
+/// that cannot be read is returned as a [`FailedFile`].
+pub(crate) fn read_log(
+    parser: &FileParser,
+    file_path: &Path,
+    cache: Option<&mut ParseCache>,
+) -> Result<std::result::Result<Vec<ProcessedEntry>, FailedFile>> {
+    let (entries, parsed_file) = match read_entries(parser, file_path, cache.as_deref())? {
+        Ok(read) => read,
+        Err(failed) => return Ok(Err(failed)),
+    };
+    if let (Some(cache), Some(parsed_file)) = (cache, parsed_file) {
+        cache.insert(parsed_file);
+    }
+    Ok(Ok(entries))
+}
+
+/// Entries with usage of one log, and the log's records when it was read
+/// rather than taken from the parse cache
+type ReadEntries = (Vec<ProcessedEntry>, Option<CachedFile>);
+
+/// The entries with usage of one log, from `cache` when it holds the log
+/// unchanged
+fn read_entries(
+    parser: &FileParser,
+    file_path: &Path,
+    cache: Option<&ParseCache>,
+) -> Result<std::result::Result<ReadEntries, FailedFile>> {
+    memory::check_memory_limit()?;
+    let meta = cache.and_then(|_| std::fs::metadata(file_path).ok());
+    let cached = cache
+        .zip(meta.as_ref())
+        .and_then(|(cache, meta)| cache.get(file_path, meta));
+    let mut parsed_file = None;
+    let parsed = match cached {
+        Some(cached) => cached
+            .iter()
+            .filter_map(|c| ProcessedEntry::new(c.entry.clone(), parser, c.line).ok())
+            .collect(),
+        None => {
+            let parsed = read_with_retries(file_path, || {
+                parser.process_jsonl_file(file_path, ProcessedEntryCollector::new())
+            });
+            let parsed: Vec<ProcessedEntry> = match parsed {
+                Ok(parsed) => parsed
+                    .into_iter()
+                    .filter(|entry| entry.has_usage())
+                    .collect(),
+                Err(failed) => return Ok(Err(failed)),
+            };
+            if let Some(meta) = &meta {
+                let records = parsed
+                    .iter()
+                    .map(|entry| CachedEntry {
+                        line: entry.line_number,
+                        entry: entry.entry.clone(),
+                    })
+                    .collect();
+                parsed_file = CachedFile::new(file_path, meta, records);
+            }
+            parsed
+        }
+    };
+    Ok(Ok((parsed, parsed_file)))
+}
+
+fn file_name(path: &Path, part: impl Fn(&Path) -> Option<&std::ffi::OsStr>) -> String {
+    part(path)
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_else(|| "unknown".to_string())
+}
+
+/// Aggregation stage: add the entries of one log not seen in an earlier log
+/// to its session
+fn aggregate_log(
+    log: &SessionLog,
+    entries: Vec<(Option<String>, ProcessedEntry)>,
+    instances: &[Instance],
+    seen_hashes: &DedupSet,
+    sessions: &mut HashMap<(usize, String), SessionData>,
+) {
+    let session_id = file_name(&log.path, |p| p.file_stem());
+    let project = project_names::resolve_project_name(
+        &file_name(&log.session_dir, |p| p.file_name()),
+        Expected::EncodedDir,
+        None,
+    );
+    let mut kept = entries
+        .into_iter()
+        .filter(|(hash, _)| hash.as_deref().map_or(true, |h| seen_hashes.insert(h)))
+        .peekable();
+    if kept.peek().is_none() {
+        return;
+    }
+
+    let session = sessions
+        .entry((log.instance, session_id.clone()))
+        .or_insert_with(|| {
+            let mut session = SessionData::new(session_id, project);
+            session.instance_type = instances[log.instance].instance_type;
+            session.root = instances[log.instance].root.clone();
+            session.vm = instances[log.instance].vm.clone();
+            session
+        });
+    for (_, entry) in kept {
+        add_entry(session, &entry);
+    }
+}
+
+/// Add one entry's tokens and cost to its session and day
+fn add_entry(session: &mut SessionData, entry: &ProcessedEntry) {
+    let export = entry.to_export();
+    let primary_cost = calculate_cost_simple(
+        &export.model,
+        export.input_tokens,
+        export.output_tokens,
+        export.cache_creation_tokens,
+        export.cache_read_tokens,
+    );
+    let secondary_cost = export.secondary_usage.as_ref().map_or(0.0, |s| {
+        calculate_cost_simple(
+            &s.model,
+            s.usage.input_tokens,
+            s.usage.output_tokens,
+            s.usage.cache_creation_input_tokens,
+            s.usage.cache_read_input_tokens,
+        )
+    });
+    let (primary_cost, secondary_cost) =
+        attribute_entry_cost(entry.entry.cost_usd, primary_cost, secondary_cost);
+    let primary_cost = MicroDollars::from_dollars(primary_cost);
+    let secondary_cost = MicroDollars::from_dollars(secondary_cost);
+
+    // Secondary tokens count towards the entry's totals
+    let mut usage = UsageData {
+        input_tokens: export.input_tokens,
+        output_tokens: export.output_tokens,
+        cache_creation_input_tokens: export.cache_creation_tokens,
+        cache_read_input_tokens: export.cache_read_tokens,
+    };
+    if let Some(s) = &export.secondary_usage {
+        usage.input_tokens += s.usage.input_tokens;
+        usage.output_tokens += s.usage.output_tokens;
+        usage.cache_creation_input_tokens += s.usage.cache_creation_input_tokens;
+        usage.cache_read_input_tokens += s.usage.cache_read_input_tokens;
+    }
+
+    session.input_tokens += usage.input_tokens;
+    session.output_tokens += usage.output_tokens;
+    session.cache_creation_tokens += usage.cache_creation_input_tokens;
+    session.cache_read_tokens += usage.cache_read_input_tokens;
+    session.total_cost += primary_cost + secondary_cost;
+    if session.last_activity.as_deref() < Some(export.timestamp.as_str()) {
+        session.last_activity = Some(export.timestamp.clone());
+    }
+    session.models_used.insert(export.model.clone());
+    session.record_model(
+        &export.timestamp,
+        &export.model,
+        primary_cost + secondary_cost,
+    );
+    session.record_entry_time(entry.timestamp);
+    *session.model_costs.entry(export.model.clone()).or_default() += primary_cost;
+
+    let daily = session.daily_usage.entry(entry.date.clone()).or_default();
+    daily.input_tokens += usage.input_tokens;
+    daily.output_tokens += usage.output_tokens;
+    daily.cache_creation_tokens += usage.cache_creation_input_tokens;
+    daily.cache_read_tokens += usage.cache_read_input_tokens;
+    daily.cost += primary_cost + secondary_cost;
+    daily.record_entry_time(entry.timestamp);
+    let primary_tokens = TokenCounts {
+        input_tokens: export.input_tokens,
+        output_tokens: export.output_tokens,
+        cache_creation_input_tokens: export.cache_creation_tokens,
+        cache_read_input_tokens: export.cache_read_tokens,
+    };
+    daily.add_model_usage(&export.model, primary_tokens, primary_cost);
+
+    if let Some(s) = &export.secondary_usage {
+        session.models_used.insert(s.model.clone());
+        *session.model_costs.entry(s.model.clone()).or_default() += secondary_cost;
+        daily.add_model_usage(&s.model, TokenCounts::from(&s.usage), secondary_cost);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn write_session(root: &Path, project: &str, session: &str, lines: &[&str]) {
+        let dir = root.join("projects").join(project);
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join(format!("{}.jsonl", session)), lines.join("\n")).unwrap();
+    }
+
+    fn line(id: &str, timestamp: &str, cost: f64) -> String {
+        format!(
+            r#"{{"timestamp":"{}","message":{{"id":"{}","model":"claude-3-5-sonnet-20241022","usage":{{"input_tokens":10,"output_tokens":5}}}},"requestId":"req_{}","costUSD":{}}}"#,
+            timestamp, id, id, cost
+        )
+    }
+
+    #[test]
+    fn test_run_parallel_keeps_input_order() {
+        let items: Vec<usize> = (0..50).collect();
+        let doubled = run_parallel(&items, 4, |n| n * 2);
+        assert_eq!(doubled, items.iter().map(|n| n * 2).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_run_pipelined_consumes_in_input_order() {
+        let items: Vec<u64> = (0..100).collect();
+        let mut consumed = Vec::new();
+        run_pipelined(
+            &items,
+            4,
+            || (),
+            |_, n| {
+                // Later items often finish first
+                std::thread::sleep(Duration::from_micros((n % 5) * 200));
+                n * 2
+            },
+            |_, doubled| {
+                consumed.push(doubled);
+                Ok(())
+            },
+        )
+        .unwrap();
+        assert_eq!(consumed, items.iter().map(|n| n * 2).collect::<Vec<_>>());
+
+        let mut calls = 0;
+        let result = run_pipelined(
+            &items,
+            4,
+            || (),
+            |_, n| *n,
+            |_, n| {
+                calls += 1;
+                if n == 10 {
+                    anyhow::bail!("Consumer failed");
+                }
+                Ok(())
+            },
+        );
+        assert!(result.is_err());
+        assert_eq!(calls, 11);
+    }
+
+    #[test]
+    fn test_read_with_retries_recovers_from_transient_errors() {
+        let mut calls = 0;
+        let value = read_with_retries(Path::new("flaky.jsonl"), || {
+            calls += 1;
+            if calls == 1 {
+                anyhow::bail!("Stale file handle");
+            }
+            Ok(calls)
+        });
+        assert_eq!(value, Ok(2));
+    }
+
+    #[test]
+    fn test_merges_instances_and_drops_cross_instance_duplicates() {
+        let temp = TempDir::new().unwrap();
+        let host = temp.path().join("host");
+        let vm = temp.path().join("vm");
+        write_session(
+            &host,
+            "proj",
+            "host-session",
+            &[&line("a", "2025-01-10T10:00:00Z", 1.0)],
+        );
+        write_session(
+            &vm,
+            "proj",
+            "vm-session",
+            &[
+                &line("a", "2025-01-10T10:00:00Z", 1.0),
+                &line("b", "2025-01-11T10:00:00Z", 2.0),
+            ],
+        );
+
+        let results = process_instances(&[host, vm]).unwrap();
+        assert!(results.failed_instances.is_empty());
+        assert_eq!(results.sessions.len(), 2);
+
+        let vm_session = results
+            .sessions
+            .iter()
+            .find(|s| s.session_id == "vm-session")
+            .unwrap();
+        // The duplicate stays with the first instance
+        assert_eq!(vm_session.total_cost.to_dollars(), 2.0);
+        let total: MicroDollars = results.sessions.iter().map(|s| s.total_cost).sum();
+        assert_eq!(total.to_dollars(), 3.0);
+    }
+
+    #[test]
+    fn test_cached_run_matches_uncached_run() {
+        let temp = TempDir::new().unwrap();
+        let host = temp.path().join("host");
+        write_session(
+            &host,
+            "proj",
+            "host-session",
+            &[
+                &line("a", "2025-01-10T10:00:00Z", 1.0),
+                &line("b", "2025-01-11T10:00:00Z", 2.0),
+            ],
+        );
+        let roots = [host];
+
+        let mut cache = ParseCache::default();
+        let first = process_instances_cached(&roots, &mut cache).unwrap();
+        assert_eq!(cache.len(), 1);
+        let second = process_instances_cached(&roots, &mut cache).unwrap();
+        let uncached = process_instances(&roots).unwrap();
+
+        for results in [&first, &second] {
+            assert_eq!(results.sessions.len(), 1);
+            assert_eq!(
+                results.sessions[0].total_cost,
+                uncached.sessions[0].total_cost
+            );
+            assert_eq!(
+                results.sessions[0].daily_usage.len(),
+                uncached.sessions[0].daily_usage.len()
+            );
+        }
+    }
+
+    #[test]
+    fn test_unreadable_file_is_skipped_and_reported() {
+        let temp = TempDir::new().unwrap();
+        let host = temp.path().join("host");
+        write_session(
+            &host,
+            "proj",
+            "host-session",
+            &[&line("a", "2025-01-10T10:00:00Z", 1.0)],
+        );
+
+        // A session log that is a directory cannot be opened
+        let broken = temp.path().join("broken");
+        fs::create_dir_all(broken.join("projects").join("proj").join("bad.jsonl")).unwrap();
+
+        let results = process_instances(&[host, broken.clone()]).unwrap();
+        assert_eq!(results.sessions.len(), 1);
+        assert!(results.failed_instances.is_empty());
+        assert_eq!(results.failed_files.len(), 1);
+
+        let failed = &results.failed_files[0];
+        assert!(failed.path.starts_with(&broken));
+        assert_eq!(failed.attempts, get_config().processing.max_retries + 1);
+    }
+}
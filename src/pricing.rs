@@ -37,6 +37,19 @@
 //! This provides up-to-date pricing for all supported Claude models with detailed
 //! breakdowns for different token types.
 //!
+//! ### Offline Snapshot
+//! `claude-usage pricing refresh` saves the table to disk (see
+//! [`crate::pricing_snapshot`]). Token costs ([`calculate_cost_simple`]) are
+//! always computed offline, from the snapshot where it lists the model and
+//! from the built-in rates ([`builtin_rates`]) otherwise.
+//!
+//! ### Overrides
+//! Rates under `[pricing.overrides]`, or in the file named by
+//! `pricing.overrides_file` (`CLAUDE_USAGE_PRICING_FILE`), replace the
+//! snapshot, LiteLLM and built-in rates of the models they name, so
+//! negotiated rates are what costs are computed from. Rates an override
+//! leaves out keep their usual value.
+//!
 //! ### Fallback Pricing
 //! When external API is unavailable, the module uses hardcoded pricing for:
 //! - `claude-sonnet-4-20250514`: $3/1M input, $15/1M output tokens
@@ -88,9 +101,10 @@
 //! - [`crate::models::UsageData`] for token consumption data
 //! - External LiteLLM pricing API for current rates
 
+use crate::config::{get_config, PriceOverride};
 use crate::models::*;
 use anyhow::Result;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::sync::Mutex;
 use std::sync::OnceLock;
 
@@ -112,14 +126,17 @@ impl PricingManager {
             }
         }
 
-        // Fetch from API
-        #[cfg(feature = "pricing")]
-        let pricing = Self::fetch_pricing_data()
-            .await
-            .unwrap_or_else(|_| Self::get_fallback_pricing());
-        
-        #[cfg(not(feature = "pricing"))]
-        let pricing = Self::get_fallback_pricing();
+        // Prefer the offline snapshot, then fetch from API
+        let mut pricing = match crate::pricing_snapshot::global() {
+            Some(snapshot) => snapshot.models.clone().into_iter().collect(),
+            #[cfg(feature = "pricing")]
+            None => Self::fetch_pricing_data()
+                .await
+                .unwrap_or_else(|_| Self::get_fallback_pricing()),
+            #[cfg(not(feature = "pricing"))]
+            None => Self::get_fallback_pricing(),
+        };
+        apply_overrides(&mut pricing, &get_config().pricing.overrides);
 
         // Cache the result
         {
@@ -132,8 +149,8 @@ impl PricingManager {
     }
 
     #[cfg(feature = "pricing")]
-    async fn fetch_pricing_data() -> Result<HashMap<String, PricingData>> {
-        let url = "https://raw.githubusercontent.com/BerriAI/litellm/main/model_prices_and_context_window.json";
+    pub(crate) async fn fetch_pricing_data() -> Result<HashMap<String, PricingData>> {
+        let url = crate::pricing_snapshot::SOURCE_URL;
 
         // Create client with timeout and security settings
         let client = reqwest::Client::builder()
@@ -251,17 +268,31 @@ impl PricingManager {
     }
 }
 
-/// Simple synchronous cost calculation using hardcoded pricing
-/// Used when async pricing API is not available (e.g., in parquet reader)
-pub fn calculate_cost_simple(
-    model: &str,
-    input_tokens: u32,
-    output_tokens: u32,
-    cache_creation_tokens: u32,
-    cache_read_tokens: u32,
-) -> f64 {
+/// Per-token rates of one model, in dollars
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TokenRates {
+    pub input: f64,
+    pub output: f64,
+    pub cache_creation: f64,
+    pub cache_read: f64,
+}
+
+/// Where the rates of a model came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RatesSource<'a> {
+    /// The pricing snapshot entry of this name
+    Snapshot(&'a str),
+    /// The `[pricing.overrides]` entry of this name, over the snapshot or
+    /// built-in rates
+    Override(&'a str),
+    /// The rates built into claude-usage
+    BuiltIn,
+}
+
+/// Rates built into claude-usage, by model family
+pub fn builtin_rates(model: &str) -> TokenRates {
     // Use hardcoded pricing based on model name - updated to match LiteLLM pricing
-    let (input_cost_per_token, output_cost_per_token, cache_creation_cost, cache_read_cost) = 
+    let (input, output, cache_creation, cache_read) =
         if model.contains("opus-4") || model.contains("claude-opus-4") {
             // Claude 4 Opus pricing from LiteLLM
             (0.000015, 0.000075, 0.00001875, 0.000001875) // $15/$75/$18.75/$1.875 per 1M tokens
@@ -281,14 +312,253 @@ pub fn calculate_cost_simple(
             // Default to Sonnet pricing
             (0.000003, 0.000015, 0.00000375, 0.0000003)
         };
-    
+
+    TokenRates {
+        input,
+        output,
+        cache_creation,
+        cache_read,
+    }
+}
+
+/// Rates of `model` from the pricing snapshot, with built-in rates filling
+/// in whatever the snapshot does not list, and any override applied last
+pub fn model_rates(model: &str) -> (TokenRates, RatesSource<'static>) {
+    let builtin = builtin_rates(model);
+    let (rates, source) =
+        match crate::pricing_snapshot::global().and_then(|snapshot| snapshot.get(model)) {
+            Some((name, pricing)) => (
+                TokenRates {
+                    input: pricing.input_cost_per_token.unwrap_or(builtin.input),
+                    output: pricing.output_cost_per_token.unwrap_or(builtin.output),
+                    cache_creation: pricing
+                        .cache_creation_input_token_cost
+                        .unwrap_or(builtin.cache_creation),
+                    cache_read: pricing
+                        .cache_read_input_token_cost
+                        .unwrap_or(builtin.cache_read),
+                },
+                RatesSource::Snapshot(name),
+            ),
+            None => (builtin, RatesSource::BuiltIn),
+        };
+
+    match match_model(&get_config().pricing.overrides, model) {
+        Some((name, rates_override)) => (rates_override.apply(rates), RatesSource::Override(name)),
+        None => (rates, source),
+    }
+}
+
+/// Entry of `model` in `table`: an exact match, else the longest listed name
+/// it extends with `-…`, so dated releases find their undated entry
+pub fn match_model<'a, V>(table: &'a BTreeMap<String, V>, model: &str) -> Option<(&'a str, &'a V)> {
+    if let Some((name, value)) = table.get_key_value(model) {
+        return Some((name, value));
+    }
+    table
+        .iter()
+        .filter(|(name, _)| {
+            model
+                .strip_prefix(name.as_str())
+                .is_some_and(|rest| rest.starts_with('-'))
+        })
+        .max_by_key(|(name, _)| name.len())
+        .map(|(name, value)| (name.as_str(), value))
+}
+
+const PER_MILLION: f64 = 1_000_000.0;
+
+impl PriceOverride {
+    /// `rates` with every rate this override sets replaced
+    pub fn apply(&self, rates: TokenRates) -> TokenRates {
+        let per_token =
+            |rate: Option<f64>, fallback: f64| rate.map_or(fallback, |r| r / PER_MILLION);
+        TokenRates {
+            input: per_token(self.input, rates.input),
+            output: per_token(self.output, rates.output),
+            cache_creation: per_token(self.cache_creation, rates.cache_creation),
+            cache_read: per_token(self.cache_read, rates.cache_read),
+        }
+    }
+}
+
+/// Merge `overrides` over a pricing table: listed models matching an
+/// override take its rates, and overridden models the table lacks are added
+/// with built-in rates for whatever the override leaves out
+fn apply_overrides(
+    pricing: &mut HashMap<String, PricingData>,
+    overrides: &BTreeMap<String, PriceOverride>,
+) {
+    if overrides.is_empty() {
+        return;
+    }
+    for (model, data) in pricing.iter_mut() {
+        if let Some((_, rates_override)) = match_model(overrides, model) {
+            let per_token = |rate: Option<f64>| rate.map(|r| r / PER_MILLION);
+            data.input_cost_per_token =
+                per_token(rates_override.input).or(data.input_cost_per_token);
+            data.output_cost_per_token =
+                per_token(rates_override.output).or(data.output_cost_per_token);
+            data.cache_creation_input_token_cost =
+                per_token(rates_override.cache_creation).or(data.cache_creation_input_token_cost);
+            data.cache_read_input_token_cost =
+                per_token(rates_override.cache_read).or(data.cache_read_input_token_cost);
+        }
+    }
+    for (model, rates_override) in overrides {
+        pricing.entry(model.clone()).or_insert_with(|| {
+            let rates = rates_override.apply(builtin_rates(model));
+            PricingData {
+                input_cost_per_token: Some(rates.input),
+                output_cost_per_token: Some(rates.output),
+                cache_creation_input_token_cost: Some(rates.cache_creation),
+                cache_read_input_token_cost: Some(rates.cache_read),
+            }
+        });
+    }
+}
+
+/// Synchronous cost calculation from the offline rates of [`model_rates`]
+/// Used everywhere costs are computed from tokens (e.g., in parquet reader)
+pub fn calculate_cost_simple(
+    model: &str,
+    input_tokens: u32,
+    output_tokens: u32,
+    cache_creation_tokens: u32,
+    cache_read_tokens: u32,
+) -> f64 {
+    let (rates, _) = model_rates(model);
+
     let mut cost = 0.0;
-    cost += input_tokens as f64 * input_cost_per_token;
-    cost += output_tokens as f64 * output_cost_per_token;
-    
+    cost += input_tokens as f64 * rates.input;
+    cost += output_tokens as f64 * rates.output;
+
     // Cache tokens use specific cache pricing
-    cost += cache_creation_tokens as f64 * cache_creation_cost;
-    cost += cache_read_tokens as f64 * cache_read_cost;
-    
+    cost += cache_creation_tokens as f64 * rates.cache_creation;
+    cost += cache_read_tokens as f64 * rates.cache_read;
+
     cost
 }
+
+/// Split an entry's cost between its primary and secondary (draft) model
+///
+/// When the log records a total `costUSD` for the entry, the secondary model's
+/// priced share is carved out of it and the remainder goes to the primary
+/// model. Otherwise each model keeps its own priced cost.
+pub fn attribute_entry_cost(
+    recorded_cost: Option<f64>,
+    primary_cost: f64,
+    secondary_cost: f64,
+) -> (f64, f64) {
+    match recorded_cost {
+        Some(total) => {
+            let secondary = secondary_cost.clamp(0.0, total.max(0.0));
+            (total - secondary, secondary)
+        }
+        None => (primary_cost, secondary_cost),
+    }
+}
+
+/// Split `cost` for `tokens` of `model` into what each token type contributed
+///
+/// Each type's share follows its priced cost, so a recorded `costUSD` that
+/// differs from the priced total is spread the same way. When the tokens
+/// price to nothing the cost is split by token count. Rounding is settled on
+/// the largest part, so the parts always add up to `cost`.
+pub fn split_cost(model: &str, tokens: &TokenCounts, cost: MicroDollars) -> TokenCosts {
+    let (rates, _) = model_rates(model);
+    let mut weights = [
+        f64::from(tokens.input_tokens) * rates.input,
+        f64::from(tokens.output_tokens) * rates.output,
+        f64::from(tokens.cache_creation_input_tokens) * rates.cache_creation,
+        f64::from(tokens.cache_read_input_tokens) * rates.cache_read,
+    ];
+    if weights.iter().sum::<f64>() <= 0.0 {
+        weights = [
+            f64::from(tokens.input_tokens),
+            f64::from(tokens.output_tokens),
+            f64::from(tokens.cache_creation_input_tokens),
+            f64::from(tokens.cache_read_input_tokens),
+        ];
+    }
+    let total: f64 = weights.iter().sum();
+    if total <= 0.0 {
+        return TokenCosts {
+            input: cost,
+            ..TokenCosts::default()
+        };
+    }
+
+    let part = |weight: f64| MicroDollars((cost.0 as f64 * weight / total).round() as i64);
+    let mut costs = TokenCosts {
+        input: part(weights[0]),
+        output: part(weights[1]),
+        cache_creation: part(weights[2]),
+        cache_read: part(weights[3]),
+    };
+    let remainder = cost - costs.total();
+    *costs.largest_mut() += remainder;
+    costs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builtin_rates_by_family() {
+        assert_eq!(builtin_rates("claude-opus-4-1-20250805").output, 0.000075);
+        assert_eq!(builtin_rates("claude-3-haiku-20240307").input, 0.00000025);
+        assert_eq!(
+            builtin_rates("some-new-model"),
+            builtin_rates("claude-sonnet-4-20250514")
+        );
+    }
+
+    #[test]
+    fn test_overrides_replace_only_the_rates_they_set() {
+        let overrides = BTreeMap::from([(
+            "claude-sonnet-4".to_string(),
+            PriceOverride {
+                input: Some(2.4),
+                output: Some(12.0),
+                ..Default::default()
+            },
+        )]);
+
+        let (name, rates_override) = match_model(&overrides, "claude-sonnet-4-20250514").unwrap();
+        assert_eq!(name, "claude-sonnet-4");
+        let rates = rates_override.apply(builtin_rates("claude-sonnet-4-20250514"));
+        assert!((rates.input - 2.4e-6).abs() < 1e-15);
+        assert!((rates.output - 12e-6).abs() < 1e-15);
+        assert_eq!(rates.cache_read, 0.0000003);
+        assert!(match_model(&overrides, "claude-sonnet-40").is_none());
+
+        let mut pricing = HashMap::from([(
+            "claude-sonnet-4-20250514".to_string(),
+            PricingData {
+                input_cost_per_token: Some(3e-6),
+                output_cost_per_token: Some(15e-6),
+                cache_creation_input_token_cost: None,
+                cache_read_input_token_cost: Some(3e-7),
+            },
+        )]);
+        apply_overrides(&mut pricing, &overrides);
+        let dated = &pricing["claude-sonnet-4-20250514"];
+        assert!((dated.output_cost_per_token.unwrap() - 12e-6).abs() < 1e-15);
+        assert_eq!(dated.cache_read_input_token_cost, Some(3e-7));
+        assert!(pricing.contains_key("claude-sonnet-4"));
+    }
+
+    #[test]
+    fn test_attribute_entry_cost_without_recorded_cost() {
+        assert_eq!(attribute_entry_cost(None, 1.5, 0.25), (1.5, 0.25));
+    }
+
+    #[test]
+    fn test_attribute_entry_cost_splits_recorded_total() {
+        assert_eq!(attribute_entry_cost(Some(2.0), 9.0, 0.5), (1.5, 0.5));
+        // Secondary share never exceeds the recorded total
+        assert_eq!(attribute_entry_cost(Some(0.1), 0.0, 0.5), (0.0, 0.1));
+    }
+}
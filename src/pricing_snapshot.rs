@@ -0,0 +1,180 @@
+//! Offline pricing snapshot
+//!
+//! `claude-usage pricing refresh` downloads LiteLLM's pricing table and keeps
+//! the Claude models in the snapshot file (`pricing.snapshot_file`). Costs are computed
+//! from the snapshot without any network access: a listed model is priced at
+//! its snapshot rates, and any rate the table leaves out, or any model it does
+//! not list, falls back to the built-in rates in [`crate::pricing`].
+//!
+//! A snapshot older than `pricing.max_age_days` is still used, with a warning
+//! to refresh it; a missing or unreadable snapshot means built-in rates only.
+
+use crate::config::get_config;
+use crate::models::PricingData;
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use std::sync::OnceLock;
+use tracing::warn;
+
+/// Where the pricing table is downloaded from
+pub const SOURCE_URL: &str =
+    "https://raw.githubusercontent.com/BerriAI/litellm/main/model_prices_and_context_window.json";
+
+static SNAPSHOT: OnceLock<Option<PricingSnapshot>> = OnceLock::new();
+
+/// Per-token rates of every Claude model in the table, as of `fetched_at`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PricingSnapshot {
+    pub fetched_at: DateTime<Utc>,
+    pub source: String,
+    pub models: BTreeMap<String, PricingData>,
+}
+
+impl PricingSnapshot {
+    /// Download the current table
+    #[cfg(feature = "pricing")]
+    pub async fn download() -> Result<Self> {
+        let models = crate::pricing::PricingManager::fetch_pricing_data()
+            .await
+            .context("Failed to download pricing from LiteLLM")?;
+        if models.is_empty() {
+            anyhow::bail!("LiteLLM pricing table lists no Claude models");
+        }
+        Ok(Self {
+            fetched_at: Utc::now(),
+            source: SOURCE_URL.to_string(),
+            models: models.into_iter().collect(),
+        })
+    }
+
+    /// Load the snapshot at `path`, or `None` if there is none yet
+    pub fn load(path: &Path) -> Result<Option<Self>> {
+        let content = match fs::read(path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => {
+                return Err(e).with_context(|| {
+                    format!("Failed to read pricing snapshot: {}", path.display())
+                })
+            }
+        };
+        let snapshot = serde_json::from_slice(&content)
+            .with_context(|| format!("Invalid pricing snapshot: {}", path.display()))?;
+        Ok(Some(snapshot))
+    }
+
+    /// Write the snapshot to `path`, replacing any older one
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create {}", parent.display()))?;
+        }
+        let temp_path = path.with_extension("json.tmp");
+        fs::write(&temp_path, serde_json::to_vec_pretty(self)?).with_context(|| {
+            format!("Failed to write pricing snapshot: {}", temp_path.display())
+        })?;
+        fs::rename(&temp_path, path)
+            .with_context(|| format!("Failed to save pricing snapshot: {}", path.display()))
+    }
+
+    /// Rates of `model`, matched as by [`crate::pricing::match_model`]
+    pub fn get(&self, model: &str) -> Option<(&str, &PricingData)> {
+        crate::pricing::match_model(&self.models, model)
+    }
+
+    /// Whole days since the snapshot was downloaded
+    pub fn age_days(&self, now: DateTime<Utc>) -> i64 {
+        (now - self.fetched_at).num_days()
+    }
+
+    /// Whether the snapshot is older than `max_age_days`
+    pub fn is_stale(&self, now: DateTime<Utc>, max_age_days: u32) -> bool {
+        self.age_days(now) > i64::from(max_age_days)
+    }
+}
+
+/// The snapshot at `pricing.snapshot_file`, loaded on first use
+///
+/// Warns once when it is stale or cannot be read.
+pub fn global() -> Option<&'static PricingSnapshot> {
+    SNAPSHOT
+        .get_or_init(|| {
+            let config = &get_config().pricing;
+            match PricingSnapshot::load(&config.snapshot_file) {
+                Ok(Some(snapshot)) => {
+                    if snapshot.is_stale(Utc::now(), config.max_age_days) {
+                        warn!(
+                            age_days = snapshot.age_days(Utc::now()),
+                            file = %config.snapshot_file.display(),
+                            "Pricing snapshot is stale; run `claude-usage pricing refresh`"
+                        );
+                    }
+                    Some(snapshot)
+                }
+                Ok(None) => None,
+                Err(e) => {
+                    warn!(error = %format!("{:#}", e), "Ignoring pricing snapshot; using built-in rates");
+                    None
+                }
+            }
+        })
+        .as_ref()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+    use tempfile::TempDir;
+
+    fn pricing(input: f64) -> PricingData {
+        PricingData {
+            input_cost_per_token: Some(input),
+            output_cost_per_token: Some(input * 5.0),
+            cache_creation_input_token_cost: None,
+            cache_read_input_token_cost: None,
+        }
+    }
+
+    #[test]
+    fn test_snapshot_round_trip_lookup_and_staleness() {
+        let temp = TempDir::new().unwrap();
+        let path = temp.path().join("pricing").join("pricing.json");
+        assert!(PricingSnapshot::load(&path).unwrap().is_none());
+
+        let now = Utc::now();
+        let snapshot = PricingSnapshot {
+            fetched_at: now - Duration::days(40),
+            source: SOURCE_URL.to_string(),
+            models: BTreeMap::from([
+                ("claude-opus-4-1".to_string(), pricing(15e-6)),
+                ("claude-opus-4-1-20250805".to_string(), pricing(16e-6)),
+                ("claude-sonnet-4".to_string(), pricing(3e-6)),
+            ]),
+        };
+        snapshot.save(&path).unwrap();
+
+        let loaded = PricingSnapshot::load(&path).unwrap().unwrap();
+        assert_eq!(
+            loaded.get("claude-opus-4-1-20250805").unwrap().0,
+            "claude-opus-4-1-20250805"
+        );
+        assert_eq!(
+            loaded.get("claude-sonnet-4-20250514").unwrap().0,
+            "claude-sonnet-4"
+        );
+        assert!(loaded.get("claude-3-haiku-20240307").is_none());
+
+        assert_eq!(loaded.age_days(now), 40);
+        assert!(loaded.is_stale(now, 30));
+        assert!(!loaded.is_stale(now, 60));
+
+        fs::write(&path, "not json").unwrap();
+        assert!(PricingSnapshot::load(&path).is_err());
+    }
+}
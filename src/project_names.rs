@@ -0,0 +1,281 @@
+//! Guard rails for extracted project names
+//!
+//! Project names are worked out heuristically: from the directory a log sits
+//! in under `projects/`, from the `project_name` recorded in the backups, or
+//! from the fields of a live update. When a heuristic picks the wrong thing
+//! the result is a generic path component such as `projects` or `default`,
+//! or a raw encoded directory name (`-home-me-src-app`) where a plain name or
+//! path was recorded. [`check_project_name`] flags such extractions as low
+//! confidence and replaces them with the decoded full path of the project
+//! when one is available.
+//!
+//! Low-confidence names are recorded for the run, so reports can list them
+//! (see [`low_confidence_projects`]) and a change to the heuristics that
+//! starts producing them shows up in the tests.
+
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// Names a heuristic yields when it picked a generic path component or fell
+/// back to a default, compared case-insensitively
+const PLACEHOLDER_NAMES: [&str; 6] = ["projects", "default", "unknown", "vms", ".claude", "claude"];
+
+/// Low-confidence names of this run, keyed by the name reported instead
+static LOW_CONFIDENCE: Mutex<BTreeMap<String, LowConfidenceProject>> = Mutex::new(BTreeMap::new());
+
+/// The form a name is expected in where it was extracted
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Expected {
+    /// An encoded project directory name, as in the session logs' layout
+    EncodedDir,
+    /// A plain project name or path, as recorded by claude-keeper
+    NameOrPath,
+}
+
+/// Why an extracted name is not trusted
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum LowConfidence {
+    /// A generic component such as `projects`, or a default like `unknown`
+    Placeholder,
+    /// An encoded directory name where a plain name or path was expected
+    EncodedPath,
+}
+
+impl LowConfidence {
+    pub fn label(self) -> &'static str {
+        match self {
+            LowConfidence::Placeholder => "placeholder name",
+            LowConfidence::EncodedPath => "encoded directory name",
+        }
+    }
+}
+
+/// An extracted name after validation
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CheckedName {
+    /// The name to report: the extracted one, or its fallback
+    pub name: String,
+    pub low_confidence: Option<LowConfidence>,
+}
+
+/// A project reported under a fallback name
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LowConfidenceProject {
+    /// Name reported instead
+    pub project: String,
+    /// Name the heuristic produced
+    pub extracted: String,
+    pub reason: LowConfidence,
+}
+
+/// Validate an extracted project name
+///
+/// A low-confidence name is replaced by `fallback` (a full path, or an
+/// encoded directory name, which is decoded) unless that is a placeholder
+/// too; an encoded name without a usable fallback is decoded itself.
+pub fn check_project_name(
+    extracted: &str,
+    expected: Expected,
+    fallback: Option<&str>,
+) -> CheckedName {
+    let low_confidence = if is_placeholder(extracted) {
+        Some(LowConfidence::Placeholder)
+    } else if expected == Expected::NameOrPath && is_encoded_dir(extracted) {
+        Some(LowConfidence::EncodedPath)
+    } else {
+        None
+    };
+
+    let name = match low_confidence {
+        None => extracted.to_string(),
+        Some(reason) => match fallback.filter(|fallback| !is_placeholder(fallback)) {
+            Some(fallback) if is_encoded_dir(fallback) => decode_project_dir(fallback),
+            Some(fallback) => fallback.to_string(),
+            None if reason == LowConfidence::EncodedPath => decode_project_dir(extracted),
+            None => extracted.to_string(),
+        },
+    };
+    CheckedName {
+        name,
+        low_confidence,
+    }
+}
+
+/// [`check_project_name`], recording a low-confidence name for the run
+pub fn resolve_project_name(extracted: &str, expected: Expected, fallback: Option<&str>) -> String {
+    let checked = check_project_name(extracted, expected, fallback);
+    if let Some(reason) = checked.low_confidence {
+        LOW_CONFIDENCE
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .entry(checked.name.clone())
+            .or_insert_with(|| LowConfidenceProject {
+                project: checked.name.clone(),
+                extracted: extracted.to_string(),
+                reason,
+            });
+    }
+    checked.name
+}
+
+/// Projects reported under a fallback name so far, by name
+pub fn low_confidence_projects() -> Vec<LowConfidenceProject> {
+    LOW_CONFIDENCE
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .values()
+        .cloned()
+        .collect()
+}
+
+/// The encoded directory name of the project a session log belongs to,
+/// when `path` sits directly in a project directory under `projects/`
+pub fn project_dir_of(path: &Path) -> Option<String> {
+    let project_dir = path.parent()?;
+    if project_dir.parent()?.file_name()? != "projects" {
+        return None;
+    }
+    Some(project_dir.file_name()?.to_string_lossy().into_owned())
+}
+
+/// Decode a project directory name back into the path it encodes
+///
+/// Claude encodes `/` and `.` alike as `-`, so `-home-me-my-app` may stand
+/// for `/home/me/my-app` or `/home/me/my/app`. Where the directories exist on
+/// this machine they decide; elsewhere each `-` is taken as a separator, and
+/// `--` as the start of a dot-directory such as `.config`.
+pub fn decode_project_dir(name: &str) -> String {
+    let mut components: Vec<String> = Vec::new();
+    let mut current: Option<String> = None;
+    let mut dot_next = false;
+
+    for token in name.strip_prefix('-').unwrap_or(name).split('-') {
+        if token.is_empty() {
+            dot_next = true;
+            continue;
+        }
+        let piece = if dot_next {
+            format!(".{}", token)
+        } else {
+            token.to_string()
+        };
+        current = Some(match current.take() {
+            None => piece,
+            Some(segment) => {
+                let parent: PathBuf = std::iter::once("/".to_string())
+                    .chain(components.iter().cloned())
+                    .collect();
+                if !dot_next && continues_entry(&parent, &segment, &piece) {
+                    format!("{}-{}", segment, piece)
+                } else {
+                    components.push(segment);
+                    piece
+                }
+            }
+        });
+        dot_next = false;
+    }
+    components.extend(current);
+    format!("/{}", components.join("/"))
+}
+
+/// Whether `segment-piece` begins the name of an entry of `parent` while
+/// `segment` itself is not a directory there
+fn continues_entry(parent: &Path, segment: &str, piece: &str) -> bool {
+    if parent.join(segment).is_dir() {
+        return false;
+    }
+    let prefix = format!("{}-{}", segment, piece);
+    fs::read_dir(parent).is_ok_and(|entries| {
+        entries
+            .flatten()
+            .any(|entry| entry.file_name().to_string_lossy().starts_with(&prefix))
+    })
+}
+
+fn is_placeholder(name: &str) -> bool {
+    let name = name.trim().trim_matches('/');
+    name.trim_matches('-').is_empty()
+        || PLACEHOLDER_NAMES
+            .iter()
+            .any(|placeholder| name.eq_ignore_ascii_case(placeholder))
+}
+
+/// Whether `name` looks like Claude's encoding of an absolute path
+fn is_encoded_dir(name: &str) -> bool {
+    name.len() > 1 && name.starts_with('-') && !name.contains(['/', '\\'])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_low_confidence_extractions_are_flagged() {
+        // Extractions the heuristics produce, with the fallback available
+        let extractions = [
+            ("-home-me-src-app", Expected::EncodedDir, None),
+            ("webapp", Expected::NameOrPath, None),
+            ("/home/me/api", Expected::NameOrPath, None),
+            ("projects", Expected::EncodedDir, None),
+            ("default", Expected::EncodedDir, Some("/home/me/cli")),
+            ("unknown", Expected::EncodedDir, Some("-home-me-tool")),
+            ("-home-me-web", Expected::NameOrPath, None),
+            ("---", Expected::EncodedDir, Some("unknown")),
+        ];
+        let checked: Vec<CheckedName> = extractions
+            .iter()
+            .map(|(name, expected, fallback)| check_project_name(name, *expected, *fallback))
+            .collect();
+
+        let low: Vec<&str> = checked
+            .iter()
+            .filter(|c| c.low_confidence.is_some())
+            .map(|c| c.name.as_str())
+            .collect();
+        assert_eq!(
+            low,
+            [
+                "projects",
+                "/home/me/cli",
+                "/home/me/tool",
+                "/home/me/web",
+                "---"
+            ]
+        );
+        assert_eq!(checked[0].name, "-home-me-src-app");
+        assert_eq!(checked[6].low_confidence, Some(LowConfidence::EncodedPath));
+
+        assert_eq!(
+            resolve_project_name("Projects", Expected::EncodedDir, Some("-home-me-x")),
+            "/home/me/x"
+        );
+        assert!(low_confidence_projects()
+            .iter()
+            .any(|p| p.project == "/home/me/x" && p.extracted == "Projects"));
+    }
+
+    #[test]
+    fn test_decode_project_dir_prefers_existing_directories() {
+        let temp = TempDir::new().unwrap();
+        fs::create_dir_all(temp.path().join("src").join("my-app")).unwrap();
+        let root = temp.path().to_string_lossy().replace(['/', '.'], "-");
+
+        let decoded = decode_project_dir(&format!("{}-src-my-app", root));
+        assert_eq!(Path::new(&decoded), temp.path().join("src").join("my-app"));
+        assert_eq!(
+            decode_project_dir("-nonexistent-me--config-app"),
+            "/nonexistent/me/.config/app"
+        );
+
+        let log = Path::new("/h/.claude/projects/-h-app/s.jsonl");
+        assert_eq!(project_dir_of(log).as_deref(), Some("-h-app"));
+        assert_eq!(project_dir_of(Path::new("/h/s.jsonl")), None);
+    }
+}
@@ -0,0 +1,137 @@
+//! Projects ranked by usage
+//!
+//! `claude-usage top` totals the usage of each project (a session's
+//! `project_path`) within `--since/--until` and ranks the projects by cost,
+//! tokens or sessions. Only the days of a session inside the range count, so
+//! a session that continued past `--until` contributes just its in-range
+//! usage. Unlike `--approx-top` (see [`crate::top_k`]) the totals are exact,
+//! at the price of one row per project.
+
+use crate::dedup::ProjectSort;
+use crate::models::{MicroDollars, SessionOutput};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// Usage of one project within the report's date range
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectTotals {
+    pub project: String,
+    pub cost: MicroDollars,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub cache_creation_tokens: u64,
+    pub cache_read_tokens: u64,
+    pub total_tokens: u64,
+    /// Sessions with usage in the range
+    pub sessions: u32,
+    /// Latest day with usage in the range (YYYY-MM-DD)
+    pub last_active: String,
+}
+
+/// Total every project's usage within `since..=until`, ranked by `sort`
+///
+/// Ties are broken by cost, then by project name, so the order is stable.
+pub fn rank_projects(
+    sessions: &[SessionOutput],
+    since: Option<DateTime<Utc>>,
+    until: Option<DateTime<Utc>>,
+    sort: ProjectSort,
+) -> Vec<ProjectTotals> {
+    let since = since.map(|since| since.format("%Y-%m-%d").to_string());
+    let until = until.map(|until| until.format("%Y-%m-%d").to_string());
+    let mut projects: HashMap<&str, ProjectTotals> = HashMap::new();
+
+    for session in sessions {
+        let mut days = session
+            .daily_usage
+            .iter()
+            .filter(|(day, _)| since.as_ref().map_or(true, |since| *day >= since))
+            .filter(|(day, _)| until.as_ref().map_or(true, |until| *day <= until))
+            .peekable();
+        if days.peek().is_none() {
+            continue;
+        }
+
+        let totals = projects
+            .entry(&session.project_path)
+            .or_insert_with(|| ProjectTotals {
+                project: session.project_path.clone(),
+                ..ProjectTotals::default()
+            });
+        totals.sessions += 1;
+        for (day, usage) in days {
+            totals.cost += usage.cost;
+            totals.input_tokens += u64::from(usage.input_tokens);
+            totals.output_tokens += u64::from(usage.output_tokens);
+            totals.cache_creation_tokens += u64::from(usage.cache_creation_tokens);
+            totals.cache_read_tokens += u64::from(usage.cache_read_tokens);
+            if *day > totals.last_active {
+                totals.last_active = day.clone();
+            }
+        }
+        totals.total_tokens = totals.input_tokens
+            + totals.output_tokens
+            + totals.cache_creation_tokens
+            + totals.cache_read_tokens;
+    }
+
+    let mut ranked: Vec<ProjectTotals> = projects.into_values().collect();
+    ranked.sort_by(|a, b| {
+        let primary = match sort {
+            ProjectSort::Cost => b.cost.cmp(&a.cost),
+            ProjectSort::Tokens => b.total_tokens.cmp(&a.total_tokens),
+            ProjectSort::Sessions => b.sessions.cmp(&a.sessions),
+        };
+        primary
+            .then_with(|| b.cost.cmp(&a.cost))
+            .then_with(|| a.project.cmp(&b.project))
+    });
+    ranked
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::test_support::session;
+    use crate::models::DailyUsage;
+
+    #[test]
+    fn test_rank_projects_within_range() {
+        let sessions = vec![
+            session("api")
+                .project("api")
+                .days(&[("2025-03-01", 1.0), ("2025-03-05", 4.0)])
+                .build(),
+            session("api").project("api").day("2025-03-02", 0.5).build(),
+            session("web")
+                .project("web")
+                .day_usage(
+                    "2025-03-02",
+                    DailyUsage {
+                        input_tokens: 5000,
+                        cost: MicroDollars::from_dollars(2.0),
+                        ..Default::default()
+                    },
+                )
+                .build(),
+            session("old").project("old").day("2025-02-01", 9.0).build(),
+        ];
+        let since = "2025-03-01T00:00:00Z".parse().ok();
+        let until = "2025-03-04T23:59:59Z".parse().ok();
+
+        let by_cost = rank_projects(&sessions, since, until, ProjectSort::Cost);
+        let names: Vec<&str> = by_cost.iter().map(|p| p.project.as_str()).collect();
+        assert_eq!(names, ["web", "api"]);
+        assert_eq!(by_cost[1].cost, MicroDollars::from_dollars(1.5));
+        assert_eq!(by_cost[1].sessions, 2);
+        assert_eq!(by_cost[1].last_active, "2025-03-02");
+
+        let by_sessions = rank_projects(&sessions, since, until, ProjectSort::Sessions);
+        assert_eq!(by_sessions[0].project, "api");
+        let by_tokens = rank_projects(&sessions, None, None, ProjectSort::Tokens);
+        assert_eq!(by_tokens[0].project, "web");
+        assert_eq!(by_tokens.len(), 3);
+    }
+}
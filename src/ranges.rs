@@ -0,0 +1,228 @@
+//! Date Ranges
+//!
+//! `--range START..END` on `daily` and `monthly` reports a specific period,
+//! and can be repeated to compare several, such as the weeks before and after
+//! enabling prompt caching. The usage is scanned once for the span covering
+//! every range; each range is then rendered as its own section from the
+//! per-day usage falling inside it, followed by a comparison table of the
+//! ranges' totals.
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::Serialize;
+use std::collections::HashSet;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::models::{MicroDollars, SessionOutput};
+
+/// An inclusive range of days, written `START..END`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DateRange {
+    pub start: NaiveDate,
+    pub end: NaiveDate,
+}
+
+impl FromStr for DateRange {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let (start, end) = s
+            .split_once("..")
+            .with_context(|| format!("Invalid range '{}', expected START..END", s))?;
+        let parse = |date: &str| {
+            NaiveDate::parse_from_str(date, "%Y-%m-%d")
+                .with_context(|| format!("Invalid date format: {}. Use YYYY-MM-DD", date))
+        };
+        let range = Self {
+            start: parse(start)?,
+            end: parse(end)?,
+        };
+        if range.start > range.end {
+            anyhow::bail!("Range {} starts after it ends", range);
+        }
+        Ok(range)
+    }
+}
+
+impl fmt::Display for DateRange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}..{}",
+            self.start.format("%Y-%m-%d"),
+            self.end.format("%Y-%m-%d")
+        )
+    }
+}
+
+impl DateRange {
+    /// Number of days in the range
+    pub fn days(&self) -> usize {
+        (self.end - self.start).num_days() as usize + 1
+    }
+
+    /// Whether the `YYYY-MM-DD` date `date` falls in the range
+    pub fn contains(&self, date: &str) -> bool {
+        NaiveDate::parse_from_str(date, "%Y-%m-%d")
+            .is_ok_and(|date| self.start <= date && date <= self.end)
+    }
+
+    /// The sessions with usage in the range, keeping only the days inside it
+    ///
+    /// Session-level totals are left as they are; the reports built from the
+    /// result read the per-day usage.
+    pub fn clip(&self, sessions: &[SessionOutput]) -> Vec<SessionOutput> {
+        sessions
+            .iter()
+            .filter(|session| session.daily_usage.keys().any(|date| self.contains(date)))
+            .map(|session| {
+                let mut session = session.clone();
+                session.daily_usage.retain(|date, _| self.contains(date));
+                session
+            })
+            .collect()
+    }
+}
+
+/// Time bounds of the span covering every range, to scan once for all of them
+pub fn span(ranges: &[DateRange]) -> Option<(DateTime<Utc>, DateTime<Utc>)> {
+    let start = ranges.iter().map(|range| range.start).min()?;
+    let end = ranges.iter().map(|range| range.end).max()?;
+    Some((
+        start.and_hms_opt(0, 0, 0)?.and_utc(),
+        end.and_hms_opt(23, 59, 59)?.and_utc(),
+    ))
+}
+
+/// Totals of one range, a row of the comparison table
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RangeTotals {
+    pub range: String,
+    pub days: usize,
+    /// Days of the range with any usage
+    pub active_days: usize,
+    pub sessions: usize,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub cache_creation_tokens: u64,
+    pub cache_read_tokens: u64,
+    pub total_cost: MicroDollars,
+    /// Cost per day with usage
+    pub cost_per_active_day: MicroDollars,
+    /// Change of the cost per active day from the first range, in percent
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub change_pct: Option<f64>,
+}
+
+impl RangeTotals {
+    /// Every token kind added up
+    pub fn total_tokens(&self) -> u64 {
+        self.input_tokens + self.output_tokens + self.cache_creation_tokens + self.cache_read_tokens
+    }
+}
+
+/// Total each range's usage, comparing each with the first range
+///
+/// Ranges are compared by cost per active day, so ranges of different
+/// lengths, or with idle weekends, can be set side by side.
+pub fn compare(ranges: &[DateRange], sessions: &[SessionOutput]) -> Vec<RangeTotals> {
+    let mut rows: Vec<RangeTotals> = ranges
+        .iter()
+        .map(|range| range_totals(range, sessions))
+        .collect();
+    let baseline = rows.first().map(|first| first.cost_per_active_day);
+    for row in rows.iter_mut().skip(1) {
+        row.change_pct = baseline
+            .filter(|baseline| *baseline > MicroDollars::ZERO)
+            .map(|baseline| {
+                let change = (row.cost_per_active_day - baseline).to_dollars();
+                (change / baseline.to_dollars() * 1000.0).round() / 10.0
+            });
+    }
+    rows
+}
+
+fn range_totals(range: &DateRange, sessions: &[SessionOutput]) -> RangeTotals {
+    let mut totals = RangeTotals {
+        range: range.to_string(),
+        days: range.days(),
+        active_days: 0,
+        sessions: 0,
+        input_tokens: 0,
+        output_tokens: 0,
+        cache_creation_tokens: 0,
+        cache_read_tokens: 0,
+        total_cost: MicroDollars::ZERO,
+        cost_per_active_day: MicroDollars::ZERO,
+        change_pct: None,
+    };
+    let mut active_days = HashSet::new();
+    for session in sessions {
+        let mut active = false;
+        for (date, usage) in &session.daily_usage {
+            if !range.contains(date) {
+                continue;
+            }
+            active = true;
+            active_days.insert(date.as_str());
+            totals.input_tokens += u64::from(usage.input_tokens);
+            totals.output_tokens += u64::from(usage.output_tokens);
+            totals.cache_creation_tokens += u64::from(usage.cache_creation_tokens);
+            totals.cache_read_tokens += u64::from(usage.cache_read_tokens);
+            totals.total_cost += usage.cost;
+        }
+        totals.sessions += usize::from(active);
+    }
+    totals.active_days = active_days.len();
+    if totals.active_days > 0 {
+        totals.cost_per_active_day = MicroDollars(totals.total_cost.0 / totals.active_days as i64);
+    }
+    totals
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::test_support::session;
+
+    #[test]
+    fn test_ranges_clip_and_compare() {
+        let before: DateRange = "2025-06-01..2025-06-07".parse().unwrap();
+        let after: DateRange = "2025-06-08..2025-06-14".parse().unwrap();
+        assert_eq!(before.to_string(), "2025-06-01..2025-06-07");
+        assert_eq!(before.days(), 7);
+        assert!("2025-06-07..2025-06-01".parse::<DateRange>().is_err());
+        assert!("2025-06-01".parse::<DateRange>().is_err());
+
+        let sessions = [
+            session("a")
+                .days(&[("2025-06-02", 4.0), ("2025-06-03", 2.0)])
+                .build(),
+            // Spans both ranges
+            session("b")
+                .days(&[("2025-06-07", 3.0), ("2025-06-08", 1.0)])
+                .build(),
+            session("c").days(&[("2025-06-20", 9.0)]).build(),
+        ];
+
+        let clipped = after.clip(&sessions);
+        assert_eq!(clipped.len(), 1);
+        assert_eq!(clipped[0].session_id, "b");
+        assert_eq!(clipped[0].daily_usage.len(), 1);
+
+        let (since, until) = span(&[after, before]).unwrap();
+        assert_eq!(since.to_rfc3339(), "2025-06-01T00:00:00+00:00");
+        assert_eq!(until.to_rfc3339(), "2025-06-14T23:59:59+00:00");
+
+        let rows = compare(&[before, after], &sessions);
+        assert_eq!((rows[0].active_days, rows[0].sessions), (3, 2));
+        assert_eq!(rows[0].total_cost, MicroDollars::from_dollars(9.0));
+        assert_eq!(rows[0].cost_per_active_day, MicroDollars::from_dollars(3.0));
+        assert_eq!(rows[0].total_tokens(), 300);
+        assert_eq!(rows[0].change_pct, None);
+        assert_eq!((rows[1].active_days, rows[1].sessions), (1, 1));
+        assert_eq!(rows[1].change_pct, Some(-66.7));
+    }
+}
@@ -0,0 +1,145 @@
+//! Redacted project names
+//!
+//! With `--redact-projects` (or `redaction.projects` set), reports replace
+//! each project name with an alias before printing, so they can be shared
+//! without exposing internal project names. The alias comes from the
+//! aliases file (`redaction.aliases_file`), a JSON object mapping a project
+//! name, path or glob to the name to show, matched the way `--project`
+//! matches:
+//!
+//! ```json
+//! {
+//!   "/home/me/src/billing-api": "Client A",
+//!   "*-internal-*": "Internal tools"
+//! }
+//! ```
+//!
+//! A plain name or path takes precedence over a glob. Projects without an
+//! alias are shown as `project-` followed by the first 8 hex digits of the
+//! SHA-256 of their name, which stays the same between runs and machines.
+//!
+//! Redaction applies to the sessions a report is built from, so pretty,
+//! JSON and CSV output and `export` records all show the same names.
+
+use anyhow::{Context, Result};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use crate::config::get_config;
+use crate::export::ExportRecord;
+use crate::file_discovery::ProjectDirFilter;
+use crate::models::SessionOutput;
+use crate::session_utils::SessionUtils;
+
+/// Replaces project names with aliases or stable hashes
+#[derive(Debug, Clone, Default)]
+pub struct ProjectRedactor {
+    /// Filters and their aliases, plain names before globs
+    aliases: Vec<(ProjectDirFilter, String)>,
+}
+
+impl ProjectRedactor {
+    /// Build a redactor from aliases keyed by project name, path or glob
+    pub fn new(aliases: BTreeMap<String, String>) -> Result<Self> {
+        let mut filters = Vec::with_capacity(aliases.len());
+        for (project, alias) in aliases {
+            let filter = ProjectDirFilter::new(&project)
+                .with_context(|| format!("Invalid project '{}' in aliases", project))?;
+            filters.push((filter, alias));
+        }
+        filters.sort_by_key(|(filter, _)| matches!(filter, ProjectDirFilter::Glob(_)));
+        Ok(Self { aliases: filters })
+    }
+
+    /// Load an aliases file
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read aliases file: {}", path.display()))?;
+        let aliases = serde_json::from_str(&content)
+            .with_context(|| format!("Invalid aliases file: {}", path.display()))?;
+        Self::new(aliases).with_context(|| format!("Invalid aliases in {}", path.display()))
+    }
+
+    /// The redactor for a run, or `None` unless `redact_projects` or
+    /// `redaction.projects` asks for one
+    pub fn for_run(redact_projects: bool) -> Result<Option<Self>> {
+        let config = &get_config().redaction;
+        if !redact_projects && !config.projects {
+            return Ok(None);
+        }
+        match &config.aliases_file {
+            Some(path) => Self::load(path).map(Some),
+            None => Ok(Some(Self::default())),
+        }
+    }
+
+    /// The name to show for `project`
+    pub fn project(&self, project: &str) -> String {
+        self.aliases
+            .iter()
+            .find(|(filter, _)| filter.matches(project))
+            .map(|(_, alias)| alias.clone())
+            .unwrap_or_else(|| hashed_name(project))
+    }
+
+    /// Redact the project of each session, and the display ID derived from it
+    pub fn redact_sessions(&self, sessions: &mut [SessionOutput]) {
+        for session in sessions {
+            session.project_path = self.project(&session.project_path);
+            let start_date = session.daily_usage.keys().min().map(String::as_str);
+            session.display_id =
+                SessionUtils::display_id(&session.session_id, &session.project_path, start_date);
+        }
+    }
+
+    /// Redact the project of each export record
+    pub fn redact_records(&self, records: &mut [ExportRecord]) {
+        for record in records {
+            record.project = self.project(&record.project);
+        }
+    }
+}
+
+/// `project-` and the start of the SHA-256 of `project`
+fn hashed_name(project: &str) -> String {
+    let digest = Sha256::digest(project.as_bytes());
+    let hash: String = digest[..4].iter().map(|b| format!("{:02x}", b)).collect();
+    format!("project-{}", hash)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_aliases_before_stable_hashes() {
+        let aliases = BTreeMap::from([
+            ("*-internal-*".to_string(), "Internal tools".to_string()),
+            (
+                "/home/me/src/my-internal-api".to_string(),
+                "Client A".to_string(),
+            ),
+        ]);
+        let redactor = ProjectRedactor::new(aliases).unwrap();
+
+        // A plain path wins over a glob matching the same project
+        assert_eq!(redactor.project("home/me/src/my-internal-api"), "Client A");
+        assert_eq!(redactor.project("-home-me-src-my-internal-api"), "Client A");
+        assert_eq!(
+            redactor.project("home/me/src/an-internal-tool"),
+            "Internal tools"
+        );
+
+        let hashed = redactor.project("home/me/src/secret");
+        assert_eq!(hashed, hashed_name("home/me/src/secret"));
+        assert!(hashed.starts_with("project-"));
+        assert_eq!(hashed.len(), "project-".len() + 8);
+        assert!(!hashed.contains("secret"));
+        assert_ne!(hashed, redactor.project("home/me/src/other"));
+
+        let empty_key = BTreeMap::from([(" ".to_string(), "x".to_string())]);
+        assert!(ProjectRedactor::new(empty_key).is_err());
+    }
+}
@@ -0,0 +1,327 @@
+//! Report changes since the last run
+//!
+//! With `--diff-since-last-run`, `daily` and `monthly` print only what changed
+//! since the same report last ran: periods that are new, periods whose total
+//! changed, and projects (or other groups) new to a period. Nothing is printed
+//! when nothing changed, so a cron job can mail whatever the command prints.
+//!
+//! Each run saves the totals of its periods as a [`ReportState`] in
+//! `last_run.dir`, in a file named after the command and a fingerprint of the
+//! options that shape the report, so reports with different filters keep
+//! separate state. The first run reports every period with usage as new, and
+//! an interrupted run is not saved.
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use colored::Colorize;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tracing::{debug, warn};
+
+use crate::config::get_config;
+use crate::dedup::ProcessOptions;
+use crate::models::{DailyData, MicroDollars, MonthlyData};
+
+/// Layout version of the state files
+const FORMAT_VERSION: u32 = 1;
+
+/// Totals of one day or month of a report
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PeriodState {
+    pub total_cost: MicroDollars,
+    pub sessions: u32,
+    /// Cost of each project or other group
+    pub groups: BTreeMap<String, MicroDollars>,
+}
+
+/// The periods of one run of a report
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReportState {
+    version: u32,
+    pub saved_at: DateTime<Utc>,
+    pub periods: BTreeMap<String, PeriodState>,
+}
+
+impl ReportState {
+    /// The state of a daily report
+    pub fn from_daily(days: &[DailyData]) -> Self {
+        Self::new(days.iter().map(|day| {
+            let state = PeriodState {
+                total_cost: day.total_cost,
+                sessions: day.total_sessions,
+                groups: day
+                    .projects
+                    .iter()
+                    .map(|project| (project.project.clone(), project.total_cost))
+                    .collect(),
+            };
+            (day.date.clone(), state)
+        }))
+    }
+
+    /// The state of a monthly report, whose groups are only known when not
+    /// grouping by project
+    pub fn from_monthly(months: &[MonthlyData]) -> Self {
+        Self::new(months.iter().map(|month| {
+            let state = PeriodState {
+                total_cost: month.total_cost,
+                sessions: month.total_sessions,
+                groups: month.groups.clone(),
+            };
+            (month.month.clone(), state)
+        }))
+    }
+
+    fn new(periods: impl Iterator<Item = (String, PeriodState)>) -> Self {
+        Self {
+            version: FORMAT_VERSION,
+            saved_at: Utc::now(),
+            periods: periods.collect(),
+        }
+    }
+
+    /// The state saved at `path`, if there is a readable one
+    pub fn load(path: &Path) -> Option<Self> {
+        let content = fs::read(path).ok()?;
+        match serde_json::from_slice::<ReportState>(&content) {
+            Ok(state) if state.version == FORMAT_VERSION => Some(state),
+            Ok(state) => {
+                debug!(
+                    file = %path.display(),
+                    version = state.version,
+                    "Discarding report state of another format"
+                );
+                None
+            }
+            Err(e) => {
+                warn!(file = %path.display(), error = %e, "Ignoring unreadable report state");
+                None
+            }
+        }
+    }
+
+    /// Write the state to `path`, replacing the previous run's
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create {}", parent.display()))?;
+        }
+        let temp_path = path.with_extension("json.tmp");
+        fs::write(&temp_path, serde_json::to_vec(self)?)
+            .with_context(|| format!("Failed to write report state: {}", temp_path.display()))?;
+        fs::rename(&temp_path, path)
+            .with_context(|| format!("Failed to save report state: {}", path.display()))?;
+        debug!(file = %path.display(), periods = self.periods.len(), "Saved report state");
+        Ok(())
+    }
+}
+
+/// What changed about a period
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ChangeKind {
+    /// A period with usage that the last run did not have
+    NewPeriod,
+    /// A period whose total cost differs from the last run's
+    ChangedTotal,
+    /// A project or other group that the period did not have in the last run
+    NewGroup,
+}
+
+/// One change of a report since its last run
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReportChange {
+    pub period: String,
+    pub kind: ChangeKind,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub group: Option<String>,
+    /// The period's total in the last run, for a changed total
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub previous_cost: Option<MicroDollars>,
+    /// The period's or group's cost now
+    pub total_cost: MicroDollars,
+}
+
+/// Changes from `previous` to `current`, oldest period first
+///
+/// Periods the current report no longer covers are not changes, and empty
+/// periods are only reported once they have usage.
+pub fn diff(previous: Option<&ReportState>, current: &ReportState) -> Vec<ReportChange> {
+    let mut changes = Vec::new();
+    for (period, now) in &current.periods {
+        let before = previous.and_then(|state| state.periods.get(period));
+        let change = |kind, group, previous_cost, total_cost| ReportChange {
+            period: period.clone(),
+            kind,
+            group,
+            previous_cost,
+            total_cost,
+        };
+        match before {
+            None if now.total_cost == MicroDollars::ZERO && now.sessions == 0 => continue,
+            None => changes.push(change(ChangeKind::NewPeriod, None, None, now.total_cost)),
+            Some(before) if before.total_cost != now.total_cost => changes.push(change(
+                ChangeKind::ChangedTotal,
+                None,
+                Some(before.total_cost),
+                now.total_cost,
+            )),
+            Some(_) => {}
+        }
+        for (group, cost) in &now.groups {
+            if before.map_or(true, |before| !before.groups.contains_key(group)) {
+                changes.push(change(
+                    ChangeKind::NewGroup,
+                    Some(group.clone()),
+                    None,
+                    *cost,
+                ));
+            }
+        }
+    }
+    changes
+}
+
+/// State file of the report `options` select, in `dir`
+pub fn state_file(dir: &Path, options: &ProcessOptions) -> PathBuf {
+    let mut shape = format!(
+        "{:?}|{:?}|{:?}|{}|{:?}|{:?}|{:?}|{}",
+        options.limit,
+        options.since_date,
+        options.until_date,
+        options.exclude_vms,
+        options.project,
+        options.group_by,
+        options.cost_mode,
+        options.estimate_missing_usage,
+    );
+    // Added only when set, so earlier state files keep their names
+    if options.redact_projects || get_config().redaction.projects {
+        shape.push_str("|redacted");
+    }
+    let digest = Sha256::digest(shape.as_bytes());
+    let fingerprint: String = digest[..8].iter().map(|b| format!("{:02x}", b)).collect();
+    dir.join(format!("{}-{}.json", options.command, fingerprint))
+}
+
+/// Print `changes` since the run saved in `previous`; nothing when there are none
+pub fn print_changes(changes: &[ReportChange], previous: Option<&ReportState>) {
+    if changes.is_empty() {
+        return;
+    }
+    let since = previous.map_or("first run".to_string(), |state| {
+        format!(
+            "since {}",
+            DateTime::<chrono::Local>::from(state.saved_at).format("%Y-%m-%d %H:%M")
+        )
+    });
+    println!(
+        "\n{} {} change{} {}\n",
+        "🔔".bright_yellow(),
+        changes.len().to_string().bright_white().bold(),
+        if changes.len() == 1 { "" } else { "s" },
+        since
+    );
+    for change in changes {
+        let cost = format!("${:.2}", change.total_cost).bright_green();
+        match change.kind {
+            ChangeKind::NewPeriod => {
+                println!("   {} {} (new)", change.period.bright_white().bold(), cost)
+            }
+            ChangeKind::ChangedTotal => {
+                let previous = change.previous_cost.unwrap_or_default();
+                let delta = change.total_cost - previous;
+                let sign = if delta >= MicroDollars::ZERO {
+                    "+"
+                } else {
+                    "-"
+                };
+                println!(
+                    "   {} ${:.2} → {} ({}${:.2})",
+                    change.period.bright_white().bold(),
+                    previous,
+                    cost,
+                    sign,
+                    delta.to_dollars().abs()
+                );
+            }
+            ChangeKind::NewGroup => println!(
+                "      + {}: {} (new in {})",
+                change.group.as_deref().unwrap_or_default().bright_cyan(),
+                cost,
+                change.period
+            ),
+        }
+    }
+    println!();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    /// Period, total cost and cost of each group
+    type Period<'a> = (&'a str, f64, &'a [(&'a str, f64)]);
+
+    fn state(periods: &[Period]) -> ReportState {
+        let dollars = MicroDollars::from_dollars;
+        ReportState::new(periods.iter().map(|(period, cost, groups)| {
+            let state = PeriodState {
+                total_cost: dollars(*cost),
+                sessions: 1,
+                groups: groups
+                    .iter()
+                    .map(|(group, cost)| (group.to_string(), dollars(*cost)))
+                    .collect(),
+            };
+            (period.to_string(), state)
+        }))
+    }
+
+    #[test]
+    fn test_diff_reports_new_periods_totals_and_groups() {
+        let previous = state(&[
+            ("2025-03-01", 2.0, &[("api", 2.0)]),
+            ("2025-03-02", 1.0, &[("api", 1.0)]),
+        ]);
+        let current = state(&[
+            ("2025-03-02", 1.5, &[("api", 1.0), ("web", 0.5)]),
+            ("2025-03-03", 0.25, &[("api", 0.25)]),
+        ]);
+
+        let changes = diff(Some(&previous), &current);
+        let kinds: Vec<(&str, ChangeKind)> = changes
+            .iter()
+            .map(|change| (change.period.as_str(), change.kind))
+            .collect();
+        assert_eq!(
+            kinds,
+            [
+                ("2025-03-02", ChangeKind::ChangedTotal),
+                ("2025-03-02", ChangeKind::NewGroup),
+                ("2025-03-03", ChangeKind::NewPeriod),
+                ("2025-03-03", ChangeKind::NewGroup),
+            ]
+        );
+        assert_eq!(
+            changes[0].previous_cost,
+            Some(MicroDollars::from_dollars(1.0))
+        );
+        assert_eq!(changes[1].group.as_deref(), Some("web"));
+        assert!(diff(Some(&current), &current).is_empty());
+
+        let temp = TempDir::new().unwrap();
+        let path = temp.path().join("last-run").join("daily.json");
+        assert!(ReportState::load(&path).is_none());
+        current.save(&path).unwrap();
+        let saved = ReportState::load(&path).unwrap();
+        assert_eq!(saved.periods, current.periods);
+    }
+}
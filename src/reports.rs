@@ -29,14 +29,15 @@
 //! - Date and total cost per day
 //! - Individual project costs and percentages
 //! - Session counts per project
-//! - Configurable display limits (default: 30 days)
+//! - `--limit N` renders the last N days, including days without usage (default: 30)
 //!
 //! ### Monthly Reports
 //! Monthly reports provide higher-level summaries:
 //! - Month-by-month cost totals
 //! - Session counts per month
-//! - Configurable display limits (default: 10 months)
+//! - `--limit N` renders the last N months with usage (default: 10)
 //! - Reverse chronological ordering (most recent first)
+//! - Month-to-date comparison against the same days of the previous month
 //!
 //! ### JSON Output
 //! When `json_output` is enabled, all reports are formatted as structured JSON:
@@ -96,12 +97,60 @@
 //! - [`crate::analyzer::ClaudeUsageAnalyzer`] for receiving processed data
 //! - Terminal color libraries for enhanced visual output
 
+use crate::allocations::{split_amount, Allocations};
+use crate::branches::SessionBranches;
+use crate::cost_audit::{CostAudit, CostModeAdvice};
+use crate::dedup::{Command, GroupBy, SortOrder};
+use crate::forecast::Forecast;
+use crate::limits::{self, PlanLimits};
 use crate::models::*;
+use crate::notes::DayNotes;
+use crate::output_version;
+use crate::parse_cache::ParseCacheStats;
+use crate::pipeline::FailedFile;
+use crate::project_names::LowConfidenceProject;
+use crate::projects::ProjectTotals;
+use crate::ranges::{self, DateRange};
+use crate::sanitize::SanitizedEntries;
+use crate::skipped::SkippedData;
+use crate::top_k::ApproxTopReport;
+use chrono::{DateTime, Datelike, Local, Months, NaiveDate, Utc};
 use colored::Colorize;
-use std::collections::{HashMap, HashSet};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use tracing::{debug, info};
 
-pub struct ReportDisplayManager;
+/// Days shown by the daily report when no `--limit` is given
+pub const DEFAULT_DAILY_LIMIT: usize = 30;
+
+/// Months shown by the monthly report when no `--limit` is given
+pub const DEFAULT_MONTHLY_LIMIT: usize = 10;
+
+/// Projects shown by `top` when no `--limit` is given
+pub const DEFAULT_TOP_LIMIT: usize = 10;
+
+pub struct ReportDisplayManager {
+    group_by: GroupBy,
+    notes: DayNotes,
+    allocations: Allocations,
+    branches: SessionBranches,
+    show_tokens: bool,
+    show_details: bool,
+    cost_audit: Option<CostAudit>,
+    cost_mode_advice: Option<CostModeAdvice>,
+    limits: Option<(PlanLimits, Vec<SessionBlock>)>,
+    failed_files: Vec<FailedFile>,
+    skipped: SkippedData,
+    sanitized: SanitizedEntries,
+    parse_cache: ParseCacheStats,
+    partial: bool,
+    low_confidence_projects: Vec<LowConfidenceProject>,
+    order: Option<SortOrder>,
+    breakdown: bool,
+    output_version: u32,
+    /// `--since` and `--until` the sessions were filtered by
+    date_filter: (Option<DateTime<Utc>>, Option<DateTime<Utc>>),
+}
 
 impl Default for ReportDisplayManager {
     fn default() -> Self {
@@ -111,14 +160,220 @@ impl Default for ReportDisplayManager {
 
 impl ReportDisplayManager {
     pub fn new() -> Self {
-        Self
+        Self {
+            group_by: GroupBy::Project,
+            notes: DayNotes::new(),
+            allocations: Allocations::default(),
+            branches: SessionBranches::default(),
+            show_tokens: false,
+            show_details: false,
+            cost_audit: None,
+            cost_mode_advice: None,
+            limits: None,
+            failed_files: Vec::new(),
+            skipped: SkippedData::default(),
+            sanitized: SanitizedEntries::default(),
+            parse_cache: ParseCacheStats::default(),
+            partial: false,
+            low_confidence_projects: Vec::new(),
+            order: None,
+            breakdown: false,
+            output_version: output_version::LATEST,
+            date_filter: (None, None),
+        }
+    }
+
+    /// Choose whether daily rows and monthly breakdowns are per project or per instance type
+    pub fn set_group_by(&mut self, group_by: GroupBy) {
+        self.group_by = group_by;
+    }
+
+    /// Notes to show alongside the matching daily rows
+    pub fn set_notes(&mut self, notes: DayNotes) {
+        self.notes = notes;
+    }
+
+    /// Cost-center splits used by `GroupBy::CostCenter`
+    pub fn set_allocations(&mut self, allocations: Allocations) {
+        self.allocations = allocations;
+    }
+
+    /// Session branches used by `GroupBy::Branch`
+    pub fn set_branches(&mut self, branches: SessionBranches) {
+        self.branches = branches;
+    }
+
+    /// Show input, output and cache token columns in the daily terminal report
+    pub fn set_show_tokens(&mut self, show_tokens: bool) {
+        self.show_tokens = show_tokens;
+    }
+
+    /// Show when work started and ended on each row of the daily terminal report
+    pub fn set_show_details(&mut self, show_details: bool) {
+        self.show_details = show_details;
+    }
+
+    /// Recorded vs computed cost comparison to report for the rendered periods
+    pub fn set_cost_audit(&mut self, cost_audit: CostAudit) {
+        self.cost_audit = Some(cost_audit);
+    }
+
+    /// Outcome of the cost mode consistency check, reported below the report
+    pub fn set_cost_mode_advice(&mut self, advice: CostModeAdvice) {
+        self.cost_mode_advice = Some(advice);
+    }
+
+    /// Plan ceilings and the 5-hour blocks measured against them in daily JSON
+    pub fn set_limits(&mut self, limits: PlanLimits, blocks: Vec<SessionBlock>) {
+        self.limits = Some((limits, blocks));
+    }
+
+    /// Files skipped because they could not be read, reported with the results
+    pub fn set_failed_files(&mut self, failed_files: Vec<FailedFile>) {
+        self.failed_files = failed_files;
+    }
+
+    /// What the date, VM and project filters left out, reported in JSON
+    pub fn set_skipped(&mut self, skipped: SkippedData) {
+        self.skipped = skipped;
+    }
+
+    /// Entries the `[quality]` rules changed or left out, reported in JSON
+    pub fn set_sanitized(&mut self, sanitized: SanitizedEntries) {
+        self.sanitized = sanitized;
+    }
+
+    /// Logs served from the parse cache or parsed, reported in JSON
+    pub fn set_parse_cache(&mut self, parse_cache: ParseCacheStats) {
+        self.parse_cache = parse_cache;
+    }
+
+    /// Mark the data as cut short by a cancelled scan, reported in JSON
+    pub fn set_partial(&mut self, partial: bool) {
+        self.partial = partial;
+    }
+
+    /// Projects reported under a fallback name, listed in JSON
+    pub fn set_low_confidence_projects(&mut self, projects: Vec<LowConfidenceProject>) {
+        self.low_confidence_projects = projects;
+    }
+
+    /// Date order of report periods; `None` keeps daily newest first and monthly oldest first
+    pub fn set_order(&mut self, order: Option<SortOrder>) {
+        self.order = order;
+    }
+
+    /// Add per-model breakdown rows to each day or month
+    pub fn set_breakdown(&mut self, breakdown: bool) {
+        self.breakdown = breakdown;
+    }
+
+    /// Render JSON reports in the layout of an earlier version (see
+    /// [`output_version`])
+    pub fn set_output_version(&mut self, version: u32) {
+        self.output_version = version;
+    }
+
+    /// The `--since` and `--until` the sessions were filtered by; the
+    /// month-to-date comparison is left out when they cut its window
+    pub fn set_date_filter(&mut self, since: Option<DateTime<Utc>>, until: Option<DateTime<Utc>>) {
+        self.date_filter = (since, until);
+    }
+
+    /// Per-model rows of the days within `period` (a day or a month) when
+    /// `--breakdown` is on
+    fn period_breakdowns(&self, data: &[SessionOutput], period: &str) -> Vec<ModelBreakdown> {
+        if !self.breakdown {
+            return Vec::new();
+        }
+        model_breakdowns(
+            data.iter()
+                .flat_map(|session| &session.daily_usage)
+                .filter(|(date, _)| date.starts_with(period))
+                .map(|(_, usage)| usage),
+        )
+    }
+
+    /// Per-model rows of a session when `--breakdown` is on
+    fn session_breakdowns(&self, session: &SessionOutput) -> Vec<ModelBreakdown> {
+        if !self.breakdown {
+            return Vec::new();
+        }
+        model_breakdowns(session.daily_usage.values())
     }
 
+    /// Add the `metadata` section to a JSON report when there is anything to report
+    fn add_metadata(&self, output: &mut serde_json::Value) {
+        let mut metadata = serde_json::Map::new();
+        if !self.failed_files.is_empty() {
+            metadata.insert(
+                "failedFiles".to_string(),
+                serde_json::json!(self.failed_files),
+            );
+        }
+        if !self.skipped.is_empty() {
+            metadata.insert("skipped".to_string(), serde_json::json!(self.skipped));
+        }
+        if !self.sanitized.is_empty() {
+            metadata.insert("sanitized".to_string(), serde_json::json!(self.sanitized));
+        }
+        if !self.parse_cache.is_empty() {
+            metadata.insert(
+                "parseCache".to_string(),
+                serde_json::json!(self.parse_cache),
+            );
+        }
+        if self.partial {
+            metadata.insert("partial".to_string(), serde_json::Value::Bool(true));
+        }
+        if !self.low_confidence_projects.is_empty() {
+            metadata.insert(
+                "lowConfidenceProjects".to_string(),
+                serde_json::json!(self.low_confidence_projects),
+            );
+        }
+        if !metadata.is_empty() {
+            output["metadata"] = serde_json::Value::Object(metadata);
+        }
+    }
+
+    /// Rows a session's usage on a day contributes to under the current
+    /// grouping, with the percentage each row receives
+    fn group_shares(&self, session: &SessionOutput, day: &DailyUsage) -> Vec<(String, f64)> {
+        match self.group_by {
+            GroupBy::Project => vec![(session.project_path.clone(), 100.0)],
+            GroupBy::InstanceType => vec![(session.instance_type.to_string(), 100.0)],
+            GroupBy::CostCenter => self.allocations.shares(session),
+            GroupBy::Branch => vec![(self.branches.branch(session).to_string(), 100.0)],
+            GroupBy::Root => vec![(session.root.as_deref().unwrap_or("home").to_string(), 100.0)],
+            GroupBy::Vm => vec![(
+                session
+                    .vm
+                    .clone()
+                    .unwrap_or_else(|| session.instance_type.to_string()),
+                100.0,
+            )],
+            GroupBy::Model => model_shares(day),
+        }
+    }
+
+    #[allow(dead_code)]
     pub fn display_daily(&self, data: &[SessionOutput], limit: Option<usize>, json_output: bool) {
-        let daily_data = self.process_daily_with_projects(data, limit);
+        self.display_daily_with_entries(data, limit, json_output, None);
+    }
+
+    /// Display daily usage, adding per-entry records to JSON output when given
+    pub fn display_daily_with_entries(
+        &self,
+        data: &[SessionOutput],
+        limit: Option<usize>,
+        json_output: bool,
+        entries: Option<&[EntryExport]>,
+    ) {
+        let today = chrono::Local::now().date_naive();
 
         if json_output {
-            let output = serde_json::json!({"daily": daily_data});
+            let output = self.daily_json(data, limit, entries, today);
             match serde_json::to_string_pretty(&output) {
                 Ok(json_str) => println!("{}", json_str),
                 Err(e) => {
@@ -129,25 +384,64 @@ impl ReportDisplayManager {
             return;
         }
 
+        let daily_data = self.process_daily_with_projects(data, limit, today);
+
+        let breakdown = match self.group_by {
+            GroupBy::Project => "Project",
+            GroupBy::InstanceType => "Instance Type",
+            GroupBy::CostCenter => "Cost Center",
+            GroupBy::Branch => "Branch",
+            GroupBy::Root => "Root",
+            GroupBy::Vm => "VM",
+            GroupBy::Model => "Model",
+        };
         println!("\n{}", "=".repeat(80).bright_cyan());
         println!(
             "{}",
-            "Claude Code Usage Report - Daily with Project Breakdown (All Instances)"
-                .bright_white()
-                .bold()
+            format!(
+                "Claude Code Usage Report - Daily with {} Breakdown (All Instances)",
+                breakdown
+            )
+            .bright_white()
+            .bold()
         );
         println!("{}", "=".repeat(80).bright_cyan());
 
-        let total_cost: f64 = daily_data.iter().map(|d| d.total_cost).sum();
+        let total_cost: MicroDollars = daily_data.iter().map(|d| d.total_cost).sum();
         let total_sessions: u32 = daily_data.iter().map(|d| d.total_sessions).sum();
 
         println!(
-            "\n{} {} days • {} sessions • {} total\n",
+            "\n{} {} days • {} sessions • {} total",
             "📊".bright_yellow(),
             daily_data.len().to_string().bright_white().bold(),
             total_sessions.to_string().bright_white().bold(),
             format!("${:.2}", total_cost).bright_green().bold()
         );
+        if self.show_tokens {
+            let tokens: TokenCounts = daily_data
+                .iter()
+                .flat_map(|d| &d.projects)
+                .map(|p| p.tokens)
+                .sum();
+            println!(
+                "{} {} input • {} output • {} cache read • {} cache write",
+                "🔢".bright_yellow(),
+                tokens.input_tokens.to_string().bright_white().bold(),
+                tokens.output_tokens.to_string().bright_white().bold(),
+                tokens.cache_read_input_tokens.to_string().bright_white().bold(),
+                tokens.cache_creation_input_tokens.to_string().bright_white().bold()
+            );
+            let costs: TokenCosts = daily_data.iter().map(|d| d.cost_breakdown).sum();
+            println!(
+                "{} {} input • {} output • {} cache read • {} cache write",
+                "💵".bright_yellow(),
+                format!("${:.2}", costs.input).bright_green(),
+                format!("${:.2}", costs.output).bright_green(),
+                format!("${:.2}", costs.cache_read).bright_green(),
+                format!("${:.2}", costs.cache_creation).bright_green()
+            );
+        }
+        println!();
 
         for day in &daily_data {
             println!(
@@ -157,92 +451,871 @@ impl ReportDisplayManager {
                 format!("${:.2}", day.total_cost).bright_green().bold(),
                 format!("{}", day.total_sessions).bright_white()
             );
+            for note in &day.notes {
+                println!("   {} {}", "📝".bright_yellow(), note.italic());
+            }
+
+            if self.show_tokens {
+                if !day.projects.is_empty() {
+                    print_token_table(breakdown, day, self.show_details);
+                }
+                println!();
+                continue;
+            }
 
             // Show all projects
             for project in &day.projects {
-                let percentage = if day.total_cost > 0.0 {
-                    project.total_cost / day.total_cost * 100.0
+                let percentage = if day.total_cost > MicroDollars::ZERO {
+                    project.total_cost.to_dollars() / day.total_cost.to_dollars() * 100.0
                 } else {
                     0.0
                 };
+                let hours = working_hours(project.work_started, project.work_ended)
+                    .filter(|_| self.show_details)
+                    .map(|hours| format!(" {}", hours.bright_black()))
+                    .unwrap_or_default();
                 println!(
-                    "   {}: {} ({}%, {} sessions)",
+                    "   {}: {} ({}%, {} sessions){}{}",
                     project.project.bright_cyan(),
                     format!("${:.2}", project.total_cost).bright_green(),
                     format!("{:.0}", percentage).bright_yellow(),
-                    format!("{}", project.sessions).bright_white()
+                    format!("{}", project.sessions).bright_white(),
+                    hours,
+                    estimated_marker(project.estimated)
+                );
+            }
+            print_model_breakdowns(&day.model_breakdowns, "   ");
+
+            println!(); // Empty line
+        }
+
+        if let Some(audit) = &self.cost_audit {
+            let days: Vec<&str> = daily_data.iter().map(|d| d.date.as_str()).collect();
+            print_cost_audit(&audit.in_periods(&days));
+        }
+        if let Some(advice) = &self.cost_mode_advice {
+            print_cost_mode_advice(advice);
+        }
+        print_failed_files(&self.failed_files);
+    }
+
+    #[allow(dead_code)]
+    pub fn display_monthly(&self, data: &[SessionOutput], limit: Option<usize>, json_output: bool) {
+        self.display_monthly_with_entries(data, limit, json_output, None);
+    }
+
+    /// Display monthly usage, adding per-entry records to JSON output when given
+    pub fn display_monthly_with_entries(
+        &self,
+        data: &[SessionOutput],
+        limit: Option<usize>,
+        json_output: bool,
+        entries: Option<&[EntryExport]>,
+    ) {
+        let today = chrono::Local::now().date_naive();
+
+        if json_output {
+            let output = self.monthly_json(data, limit, entries, today);
+            match serde_json::to_string_pretty(&output) {
+                Ok(json_str) => println!("{}", json_str),
+                Err(e) => {
+                    eprintln!("Error serializing monthly data to JSON: {}", e);
+                    return;
+                }
+            }
+            return;
+        }
+
+        let monthly_data = self.process_monthly_data(data, limit);
+        let month_to_date = self.filtered_month_to_date(data, today);
+
+        println!("\n{}", "=".repeat(80).bright_cyan());
+        println!(
+            "{}",
+            "Claude Code Usage Report - Monthly (All Instances)"
+                .bright_white()
+                .bold()
+        );
+        println!("{}", "=".repeat(80).bright_cyan());
+
+        let total_cost: MicroDollars = monthly_data.iter().map(|m| m.total_cost).sum();
+        let total_sessions: u32 = monthly_data.iter().map(|m| m.total_sessions).sum();
+
+        println!("\n{} Total Usage Summary:", "📊".bright_yellow());
+        println!(
+            "   Records: {}",
+            monthly_data.len().to_string().bright_white().bold()
+        );
+        println!(
+            "   Total Cost: {}",
+            format!("${:.2}", total_cost).bright_green().bold()
+        );
+        println!(
+            "   Total Sessions: {}",
+            total_sessions.to_string().bright_white().bold()
+        );
+        println!();
+
+        println!(
+            "{} Recent monthly usage (last {}):",
+            "📅".bright_blue(),
+            monthly_data.len().to_string().bright_white().bold()
+        );
+        for month in &monthly_data {
+            println!(
+                "   {}: {} ({} sessions){}",
+                month.month.bright_white().bold(),
+                format!("${:.2}", month.total_cost).bright_green(),
+                format!("{}", month.total_sessions).bright_white(),
+                estimated_marker(month.estimated)
+            );
+
+            for (group, cost) in &month.groups {
+                println!(
+                    "      {}: {}",
+                    group.bright_cyan(),
+                    format!("${:.2}", cost).green()
+                );
+            }
+
+            if !month.model_breakdowns.is_empty() {
+                print_model_breakdowns(&month.model_breakdowns, "      ");
+            } else if month.model_costs.len() > 1 {
+                // Per-model split is only interesting when more than one model was used
+                for (model, cost) in &month.model_costs {
+                    println!(
+                        "      {}: {}",
+                        model.bright_black(),
+                        format!("${:.2}", cost).green()
+                    );
+                }
+            }
+        }
+
+        match month_to_date {
+            Some(month_to_date) => print_month_to_date(&month_to_date),
+            None => println!(
+                "\n{} Month-to-date comparison left out: --since/--until cut this month or last",
+                "📈".bright_blue()
+            ),
+        }
+
+        if let Some(audit) = &self.cost_audit {
+            let months: Vec<&str> = monthly_data.iter().map(|m| m.month.as_str()).collect();
+            print_cost_audit(&audit.in_periods(&months));
+        }
+        if let Some(advice) = &self.cost_mode_advice {
+            print_cost_mode_advice(advice);
+        }
+        print_failed_files(&self.failed_files);
+    }
+
+    /// Display per-session breakdowns, most recently active first
+    pub fn display_sessions(&self, data: &[SessionOutput], limit: Option<usize>, json_output: bool) {
+        let output = self.session_json(data, limit);
+        if json_output {
+            match serde_json::to_string_pretty(&output) {
+                Ok(json_str) => println!("{}", json_str),
+                Err(e) => eprintln!("Error serializing session data to JSON: {}", e),
+            }
+            return;
+        }
+
+        let sessions = sorted_sessions(data, limit);
+
+        println!("\n{}", "=".repeat(80).bright_cyan());
+        println!(
+            "{}",
+            "Claude Code Usage Report - Sessions (All Instances)"
+                .bright_white()
+                .bold()
+        );
+        println!("{}", "=".repeat(80).bright_cyan());
+
+        let total_cost: MicroDollars = sessions.iter().map(|s| s.total_cost).sum();
+        println!(
+            "\n{} {} of {} sessions • {} total\n",
+            "📊".bright_yellow(),
+            sessions.len().to_string().bright_white().bold(),
+            data.len().to_string().bright_white().bold(),
+            format!("${:.2}", total_cost).bright_green().bold()
+        );
+
+        for session in sessions {
+            println!(
+                "{} {} — {}{}",
+                "💬".bright_blue(),
+                session.display_id.bright_white().bold(),
+                format!("${:.2}", session.total_cost).bright_green().bold(),
+                estimated_marker(session.estimated_entries > 0)
+            );
+            println!(
+                "   Project: {} ({})",
+                session.project_path.bright_cyan(),
+                session.instance_type
+            );
+            println!(
+                "   Tokens: {} input • {} output • {} cache read • {} cache write",
+                session.input_tokens.to_string().bright_white(),
+                session.output_tokens.to_string().bright_white(),
+                session.cache_read_tokens.to_string().bright_white(),
+                session.cache_creation_tokens.to_string().bright_white()
+            );
+            println!("   Models: {}", session.models_used.join(", ").bright_black());
+            print_model_breakdowns(&self.session_breakdowns(session), "      ");
+            if !session.model_switches.is_empty() {
+                println!(
+                    "   Model switches: {} ({}), {} after switching",
+                    session.model_switches.count.to_string().bright_white(),
+                    session.model_switches.sequence.join(" → ").bright_black(),
+                    format!("${:.2}", session.model_switches.switched_cost).bright_green()
+                );
+            }
+            if let Some(duration) = &session.duration {
+                let idle = match duration.idle_gaps {
+                    0 => String::new(),
+                    1 => ", 1 idle gap".to_string(),
+                    gaps => format!(", {} idle gaps", gaps),
+                };
+                println!(
+                    "   Duration: {} active of {} ({}{})",
+                    SessionDuration::format_secs(duration.active_secs).bright_white(),
+                    SessionDuration::format_secs(duration.span_secs()),
+                    working_hours(Some(duration.started), Some(duration.ended)).unwrap_or_default(),
+                    idle
+                );
+            }
+            println!("   Last activity: {}", session.last_activity.bright_white());
+            println!();
+        }
+        print_failed_files(&self.failed_files);
+    }
+
+    /// Display the approximate heaviest projects of `--approx-top`
+    pub fn display_approx_top(&self, report: &ApproxTopReport, json_output: bool) {
+        if json_output {
+            let mut output = serde_json::json!({ "approxTopProjects": report });
+            self.add_metadata(&mut output);
+            match serde_json::to_string_pretty(&output) {
+                Ok(json_str) => println!("{}", json_str),
+                Err(e) => eprintln!("Error serializing top projects to JSON: {}", e),
+            }
+            return;
+        }
+
+        println!("\n{}", "=".repeat(80).bright_cyan());
+        println!(
+            "{}",
+            format!("Top {} Projects (approximate)", report.projects.len())
+                .bright_white()
+                .bold()
+        );
+        println!("{}", "=".repeat(80).bright_cyan());
+        println!(
+            "\n{} {} total • {} counters • each cost overstated by at most {}\n",
+            "📊".bright_yellow(),
+            format!("${:.2}", report.total_cost).bright_green().bold(),
+            report.counters,
+            format!("${:.2}", report.error_bound).bright_white()
+        );
+
+        for (rank, project) in report.projects.iter().enumerate() {
+            let error = if project.max_error > MicroDollars::ZERO {
+                format!(" (≥ ${:.2})", project.cost - project.max_error)
+                    .bright_black()
+                    .to_string()
+            } else {
+                String::new()
+            };
+            println!(
+                "{:>4}. {:<50} {}{}",
+                rank + 1,
+                project.project.bright_cyan(),
+                format!("${:.2}", project.cost).bright_green(),
+                error
+            );
+        }
+        println!(
+            "\n{}",
+            format!(
+                "Every project costing more than ${:.2} is listed; costs are never understated.",
+                report.error_bound
+            )
+            .bright_black()
+        );
+        print_failed_files(&self.failed_files);
+    }
+
+    /// Display the projects ranked by `top`, keeping the first `limit` (default 10)
+    pub fn display_top_projects(
+        &self,
+        projects: &[ProjectTotals],
+        limit: Option<usize>,
+        json_output: bool,
+    ) {
+        if json_output {
+            match serde_json::to_string_pretty(&self.top_projects_json(projects, limit)) {
+                Ok(json_str) => println!("{}", json_str),
+                Err(e) => eprintln!("Error serializing top projects to JSON: {}", e),
+            }
+            return;
+        }
+
+        let shown = top_projects(projects, limit);
+        let total_cost: MicroDollars = projects.iter().map(|p| p.cost).sum();
+        println!("\n{}", "=".repeat(80).bright_cyan());
+        println!(
+            "{}",
+            format!("Top {} Projects", shown.len())
+                .bright_white()
+                .bold()
+        );
+        println!("{}", "=".repeat(80).bright_cyan());
+        println!(
+            "\n{} {} of {} projects • {} total\n",
+            "📊".bright_yellow(),
+            shown.len().to_string().bright_white().bold(),
+            projects.len(),
+            format!("${:.2}", total_cost).bright_green().bold()
+        );
+
+        for (rank, project) in shown.iter().enumerate() {
+            println!(
+                "{:>4}. {:<50} {}",
+                rank + 1,
+                project.project.bright_cyan(),
+                format!("${:.2}", project.cost).bright_green()
+            );
+            println!(
+                "      {} tokens • {} sessions • last active {}",
+                project.total_tokens.to_string().bright_white(),
+                project.sessions.to_string().bright_white(),
+                project.last_active.bright_black()
+            );
+        }
+        print_failed_files(&self.failed_files);
+    }
+
+    /// Build the document printed by `top --json`
+    pub fn top_projects_json(
+        &self,
+        projects: &[ProjectTotals],
+        limit: Option<usize>,
+    ) -> serde_json::Value {
+        let mut output = serde_json::json!({ "projects": top_projects(projects, limit) });
+        self.add_metadata(&mut output);
+        output
+    }
+
+    /// Build the `top --format csv` document, in rank order
+    pub fn top_projects_csv(&self, projects: &[ProjectTotals], limit: Option<usize>) -> String {
+        let mut csv = csv_header(&["project", "sessions", "last_active"]);
+        for project in top_projects(projects, limit) {
+            csv.push_str(&format!(
+                "{},{},{},{},{},{},{},{},{:.6}\n",
+                csv_field(&project.project),
+                project.sessions,
+                project.last_active,
+                project.input_tokens,
+                project.output_tokens,
+                project.cache_creation_tokens,
+                project.cache_read_tokens,
+                project.total_tokens,
+                project.cost.to_dollars()
+            ));
+        }
+        csv
+    }
+
+    /// Display the burn rate and month-end projection of `forecast`
+    pub fn display_forecast(&self, forecast: &Forecast, json_output: bool) {
+        if json_output {
+            match serde_json::to_string_pretty(&self.forecast_json(forecast)) {
+                Ok(json_str) => println!("{}", json_str),
+                Err(e) => eprintln!("Error serializing forecast to JSON: {}", e),
+            }
+            return;
+        }
+
+        println!("\n{}", "=".repeat(80).bright_cyan());
+        println!(
+            "{}",
+            format!("Forecast - {}", forecast.month)
+                .bright_white()
+                .bold()
+        );
+        println!("{}", "=".repeat(80).bright_cyan());
+        println!(
+            "\n{} Burn rate: {} per day over the last {} days ({} to {}, {} total)",
+            "🔥".bright_yellow(),
+            format!("${:.2}", forecast.average_daily_cost)
+                .bright_green()
+                .bold(),
+            forecast.window_days,
+            forecast.window_start,
+            forecast.window_end,
+            format!("${:.2}", forecast.window_cost).bright_white()
+        );
+        println!(
+            "{} Month to date: {} over {} of {} days",
+            "📅".bright_yellow(),
+            format!("${:.2}", forecast.month_to_date_cost).bright_green(),
+            forecast.days_elapsed,
+            forecast.days_in_month
+        );
+        println!(
+            "{} Projected month end: {}",
+            "📈".bright_yellow(),
+            format!("${:.2}", forecast.projected_month_cost)
+                .bright_green()
+                .bold()
+        );
+
+        if let Some(budget) = &forecast.budget {
+            let status = if budget.exhausted {
+                let over = MicroDollars::ZERO - budget.remaining;
+                format!("exhausted, ${:.2} over", over).red().bold()
+            } else if let Some(day) = &budget.exhausted_on {
+                format!("runs out on {} at this rate", day).yellow().bold()
+            } else {
+                "lasts the month at this rate".green()
+            };
+            println!(
+                "{} Monthly budget: {} • {:.0}% projected • {}",
+                "💰".bright_yellow(),
+                format!("${:.2}", budget.monthly_budget).bright_white(),
+                budget.projected_percent,
+                status
+            );
+        } else {
+            println!(
+                "\n{}",
+                "Set [budget] monthly_usd to see when the monthly budget runs out.".bright_black()
+            );
+        }
+        print_failed_files(&self.failed_files);
+    }
+
+    /// Build the document printed by `forecast --json`
+    pub fn forecast_json(&self, forecast: &Forecast) -> serde_json::Value {
+        let mut output = serde_json::json!({ "forecast": forecast });
+        self.add_metadata(&mut output);
+        output
+    }
+
+    /// Build the document printed by `daily --json`, with days counted back from `today`
+    pub fn daily_json(
+        &self,
+        data: &[SessionOutput],
+        limit: Option<usize>,
+        entries: Option<&[EntryExport]>,
+        today: NaiveDate,
+    ) -> serde_json::Value {
+        let daily_data = self.process_daily_with_projects(data, limit, today);
+        let mut output = serde_json::json!({"daily": daily_data});
+        if let Some(entries) = entries {
+            let days = daily_data.iter().map(|d| d.date.as_str()).collect();
+            output["entries"] = serde_json::json!(entries_in_periods(entries, &days));
+        }
+        if let Some(audit) = &self.cost_audit {
+            let days: Vec<&str> = daily_data.iter().map(|d| d.date.as_str()).collect();
+            output["costAudit"] = serde_json::json!(audit.in_periods(&days));
+        }
+        if let Some(advice) = &self.cost_mode_advice {
+            output["costModeCheck"] = serde_json::json!(advice);
+        }
+        if let Some((plan_limits, blocks)) = &self.limits {
+            let days: Vec<&str> = daily_data.iter().map(|d| d.date.as_str()).collect();
+            output["limits"] = serde_json::json!(limits::limits_report(plan_limits, blocks, &days));
+        }
+        self.add_metadata(&mut output);
+        output_version::render(Command::Daily, &mut output, self.output_version);
+        output
+    }
+
+    /// Build the document printed by `monthly --json`, with month-to-date relative to `today`
+    pub fn monthly_json(
+        &self,
+        data: &[SessionOutput],
+        limit: Option<usize>,
+        entries: Option<&[EntryExport]>,
+        today: NaiveDate,
+    ) -> serde_json::Value {
+        let monthly_data = self.process_monthly_data(data, limit);
+        let month_to_date = self.filtered_month_to_date(data, today);
+        let mut output =
+            serde_json::json!({"monthly": monthly_data, "monthToDate": month_to_date});
+        if let Some(entries) = entries {
+            let months = monthly_data.iter().map(|m| m.month.as_str()).collect();
+            output["entries"] = serde_json::json!(entries_in_periods(entries, &months));
+        }
+        if let Some(audit) = &self.cost_audit {
+            let months: Vec<&str> = monthly_data.iter().map(|m| m.month.as_str()).collect();
+            output["costAudit"] = serde_json::json!(audit.in_periods(&months));
+        }
+        if let Some(advice) = &self.cost_mode_advice {
+            output["costModeCheck"] = serde_json::json!(advice);
+        }
+        self.add_metadata(&mut output);
+        output_version::render(Command::Monthly, &mut output, self.output_version);
+        output
+    }
+
+    /// Display a `daily` or `monthly` section for each of `ranges`, then a
+    /// comparison of their totals
+    pub fn display_ranges(
+        &self,
+        command: Command,
+        data: &[SessionOutput],
+        ranges: &[DateRange],
+        json_output: bool,
+    ) {
+        if json_output {
+            match serde_json::to_string_pretty(&self.ranges_json(command, data, ranges)) {
+                Ok(json_str) => println!("{}", json_str),
+                Err(e) => eprintln!("Error serializing date ranges to JSON: {}", e),
+            }
+            return;
+        }
+
+        for range in ranges {
+            let clipped = range.clip(data);
+            println!("\n{}", "=".repeat(80).bright_cyan());
+            println!(
+                "{}",
+                format!("Claude Code Usage Report - {}", range)
+                    .bright_white()
+                    .bold()
+            );
+            println!("{}", "=".repeat(80).bright_cyan());
+            if command == Command::Monthly {
+                for month in self.process_monthly_data(&clipped, Some(usize::MAX)) {
+                    println!(
+                        "   {}: {} ({} sessions){}",
+                        month.month.bright_white().bold(),
+                        format!("${:.2}", month.total_cost).bright_green(),
+                        format!("{}", month.total_sessions).bright_white(),
+                        estimated_marker(month.estimated)
+                    );
+                    for (group, cost) in &month.groups {
+                        println!(
+                            "      {}: {}",
+                            group.bright_cyan(),
+                            format!("${:.2}", cost).green()
+                        );
+                    }
+                }
+                continue;
+            }
+            let days = self.process_daily_with_projects(&clipped, Some(range.days()), range.end);
+            for day in days
+                .iter()
+                .filter(|day| day.total_cost > MicroDollars::ZERO)
+            {
+                println!(
+                    "{} {} — {} ({} sessions)",
+                    "📅".bright_blue(),
+                    day.date.bright_white().bold(),
+                    format!("${:.2}", day.total_cost).bright_green().bold(),
+                    format!("{}", day.total_sessions).bright_white()
                 );
+                for project in &day.projects {
+                    println!(
+                        "   {}: {} ({} sessions){}",
+                        project.project.bright_cyan(),
+                        format!("${:.2}", project.total_cost).bright_green(),
+                        format!("{}", project.sessions).bright_white(),
+                        estimated_marker(project.estimated)
+                    );
+                }
+            }
+        }
+
+        println!("\n{} Range comparison:", "📊".bright_yellow());
+        println!(
+            "   {:<22} {:>5} {:>7} {:>9} {:>14} {:>11} {:>10} {:>8}",
+            "Range", "Days", "Active", "Sessions", "Tokens", "Cost", "Per day", "Change"
+        );
+        for row in ranges::compare(ranges, data) {
+            let change = match row.change_pct {
+                Some(change) if change >= 0.0 => {
+                    format!("{:>8}", format!("+{:.1}%", change)).bright_red()
+                }
+                Some(change) => format!("{:>8}", format!("{:.1}%", change)).bright_green(),
+                None => format!("{:>8}", "—").bright_black(),
+            };
+            println!(
+                "   {:<22} {:>5} {:>7} {:>9} {:>14} {:>11} {:>10} {}",
+                row.range.bright_white(),
+                row.days,
+                row.active_days,
+                row.sessions,
+                row.total_tokens(),
+                format!("${:.2}", row.total_cost).bright_green(),
+                format!("${:.2}", row.cost_per_active_day),
+                change
+            );
+        }
+        print_failed_files(&self.failed_files);
+    }
+
+    /// Build the document printed by `daily --range` or `monthly --range`
+    /// with `--json`: a section per range, in the layout of the plain report,
+    /// and the `comparison` of their totals
+    pub fn ranges_json(
+        &self,
+        command: Command,
+        data: &[SessionOutput],
+        ranges: &[DateRange],
+    ) -> serde_json::Value {
+        let sections: Vec<serde_json::Value> = ranges
+            .iter()
+            .map(|range| {
+                let clipped = range.clip(data);
+                let mut section = serde_json::json!({
+                    "range": range.to_string(),
+                    "start": range.start.format("%Y-%m-%d").to_string(),
+                    "end": range.end.format("%Y-%m-%d").to_string(),
+                });
+                if command == Command::Monthly {
+                    section["monthly"] =
+                        serde_json::json!(self.process_monthly_data(&clipped, Some(usize::MAX)));
+                } else {
+                    section["daily"] = serde_json::json!(self.process_daily_with_projects(
+                        &clipped,
+                        Some(range.days()),
+                        range.end
+                    ));
+                }
+                output_version::render(command, &mut section, self.output_version);
+                section
+            })
+            .collect();
+        let mut output = serde_json::json!({
+            "ranges": sections,
+            "comparison": ranges::compare(ranges, data),
+        });
+        self.add_metadata(&mut output);
+        output
+    }
+
+    /// Build a per-session document, most recently active first, with the
+    /// `totalTokens`, `modelBreakdowns` and `totals` of ccusage's session report
+    #[allow(dead_code)]
+    pub fn session_json(&self, data: &[SessionOutput], limit: Option<usize>) -> serde_json::Value {
+        let mut totals = SessionTotals::default();
+        let sessions: Vec<SessionRow> = sorted_sessions(data, limit)
+            .into_iter()
+            .map(|session| {
+                let total_tokens = session_total_tokens(session);
+                totals.input_tokens += u64::from(session.input_tokens);
+                totals.output_tokens += u64::from(session.output_tokens);
+                totals.cache_creation_tokens += u64::from(session.cache_creation_tokens);
+                totals.cache_read_tokens += u64::from(session.cache_read_tokens);
+                totals.total_tokens += total_tokens;
+                totals.total_cost += session.total_cost;
+                SessionRow {
+                    session,
+                    total_tokens,
+                    model_breakdowns: self.session_breakdowns(session),
+                }
+            })
+            .collect();
+        let mut output = serde_json::json!({"sessions": sessions, "totals": totals});
+        self.add_metadata(&mut output);
+        output_version::render(Command::Session, &mut output, self.output_version);
+        output
+    }
+
+    /// Build the `daily --format csv` document: one row per day and group, newest day first
+    pub fn daily_csv(
+        &self,
+        data: &[SessionOutput],
+        limit: Option<usize>,
+        today: NaiveDate,
+    ) -> String {
+        let mut csv = csv_header(&["date", self.group_column(), "sessions"]);
+        for day in self.process_daily_with_projects(data, limit, today) {
+            for project in &day.projects {
+                push_csv_row(
+                    &mut csv,
+                    &[&day.date, &project.project, &project.sessions.to_string()],
+                    &project.tokens,
+                    project.total_cost,
+                );
+            }
+        }
+        csv
+    }
+
+    /// Build the `monthly --format csv` document: one row per month and group, oldest month first
+    pub fn monthly_csv(&self, data: &[SessionOutput], limit: Option<usize>) -> String {
+        let months: HashSet<String> = self
+            .process_monthly_data(data, limit)
+            .into_iter()
+            .map(|m| m.month)
+            .collect();
+
+        // (month, group) -> tokens, cost and the sessions contributing to the row
+        let mut rows: BTreeMap<(String, String), (TokenCounts, MicroDollars, HashSet<&str>)> =
+            BTreeMap::new();
+        for session in data {
+            for (date, daily_usage) in &session.daily_usage {
+                let Some(month) = date.get(..7).filter(|month| months.contains(*month)) else {
+                    continue;
+                };
+                let shares = self.group_shares(session, daily_usage);
+                let percentages: Vec<f64> = shares.iter().map(|(_, percent)| *percent).collect();
+                for ((group, _), (tokens, cost)) in shares
+                    .iter()
+                    .zip(split_daily_usage(daily_usage, &percentages))
+                {
+                    let row = rows
+                        .entry((month.to_string(), group.clone()))
+                        .or_insert_with(|| {
+                            (TokenCounts::default(), MicroDollars::ZERO, HashSet::new())
+                        });
+                    row.0 += tokens;
+                    row.1 += cost;
+                    row.2.insert(&session.session_id);
+                }
             }
+        }
 
-            println!(); // Empty line
+        let mut csv = csv_header(&["month", self.group_column(), "sessions"]);
+        for ((month, group), (tokens, cost, sessions)) in rows {
+            let sessions = sessions.len().to_string();
+            push_csv_row(&mut csv, &[&month, &group, &sessions], &tokens, cost);
         }
+        csv
     }
 
-    pub fn display_monthly(&self, data: &[SessionOutput], limit: Option<usize>, json_output: bool) {
-        let monthly_data = self.process_monthly_data(data, limit);
+    /// Build the `session --format csv` document, most recently active first
+    pub fn session_csv(&self, data: &[SessionOutput], limit: Option<usize>) -> String {
+        let mut csv = csv_header(&[
+            "session_id",
+            "project",
+            "instance_type",
+            "last_activity",
+            "models",
+            "model_switches",
+        ]);
+        for session in sorted_sessions(data, limit) {
+            let tokens = TokenCounts {
+                input_tokens: session.input_tokens,
+                output_tokens: session.output_tokens,
+                cache_creation_input_tokens: session.cache_creation_tokens,
+                cache_read_input_tokens: session.cache_read_tokens,
+            };
+            push_csv_row(
+                &mut csv,
+                &[
+                    &session.session_id,
+                    &session.project_path,
+                    &session.instance_type.to_string(),
+                    &session.last_activity,
+                    &session.models_used.join(" "),
+                    &session.model_switches.count.to_string(),
+                ],
+                &tokens,
+                session.total_cost,
+            );
+        }
+        csv
+    }
 
-        if json_output {
-            let output = serde_json::json!({"monthly": monthly_data});
-            match serde_json::to_string_pretty(&output) {
-                Ok(json_str) => println!("{}", json_str),
-                Err(e) => {
-                    eprintln!("Error serializing monthly data to JSON: {}", e);
-                    return;
-                }
-            }
-            return;
+    /// CSV column naming the rows of the current grouping
+    fn group_column(&self) -> &'static str {
+        match self.group_by {
+            GroupBy::Project => "project",
+            GroupBy::InstanceType => "instance_type",
+            GroupBy::CostCenter => "cost_center",
+            GroupBy::Branch => "branch",
+            GroupBy::Root => "root",
+            GroupBy::Vm => "vm",
+            GroupBy::Model => "model",
         }
+    }
 
-        println!("\n{}", "=".repeat(80).bright_cyan());
-        println!(
-            "{}",
-            "Claude Code Usage Report - Monthly (All Instances)"
-                .bright_white()
-                .bold()
-        );
-        println!("{}", "=".repeat(80).bright_cyan());
+    /// [`month_to_date_comparison`](Self::month_to_date_comparison), unless
+    /// the date filter leaves out part of the previous month or of the
+    /// current month up to `today`
+    fn filtered_month_to_date(
+        &self,
+        session_data: &[SessionOutput],
+        today: NaiveDate,
+    ) -> Option<MonthToDateComparison> {
+        let previous_start = today
+            .with_day(1)
+            .and_then(|start| start.checked_sub_months(Months::new(1)))?;
+        // Compared with days at midnight UTC, as the date filter keeps them
+        let day_start = |date: NaiveDate| date.and_hms_opt(0, 0, 0).map(|dt| dt.and_utc());
+        let (since, until) = self.date_filter;
+        let covered = since.map_or(true, |since| day_start(previous_start) >= Some(since))
+            && until.map_or(true, |until| day_start(today) <= Some(until));
+        covered.then(|| self.month_to_date_comparison(session_data, today))
+    }
 
-        let total_cost: f64 = monthly_data.iter().map(|m| m.total_cost).sum();
-        let total_sessions: u32 = monthly_data.iter().map(|m| m.total_sessions).sum();
+    /// Compare the current month up to `today` with the same number of leading
+    /// days of the previous month (clamped to that month's length)
+    pub fn month_to_date_comparison(
+        &self,
+        session_data: &[SessionOutput],
+        today: NaiveDate,
+    ) -> MonthToDateComparison {
+        let current_start = today.with_day(1).unwrap_or(today);
+        let previous_start = current_start
+            .checked_sub_months(Months::new(1))
+            .unwrap_or(current_start);
+        let previous_month_days = (current_start - previous_start).num_days() as u32;
+        let previous_end = previous_start
+            + chrono::Duration::days(today.day().min(previous_month_days) as i64 - 1);
 
-        println!("\n{} Total Usage Summary:", "📊".bright_yellow());
-        println!(
-            "   Records: {}",
-            monthly_data.len().to_string().bright_white().bold()
-        );
-        println!(
-            "   Total Cost: {}",
-            format!("${:.2}", total_cost).bright_green().bold()
-        );
-        println!(
-            "   Total Sessions: {}",
-            total_sessions.to_string().bright_white().bold()
-        );
-        println!();
+        let mut current_cost = MicroDollars::ZERO;
+        let mut previous_cost = MicroDollars::ZERO;
+        let mut current_sessions = HashSet::new();
+        let mut previous_sessions = HashSet::new();
 
-        let display_limit = limit.unwrap_or(10);
-        let recent_data: Vec<_> = monthly_data.iter().rev().take(display_limit).collect();
-        println!(
-            "{} Recent monthly usage (last {}):",
-            "📅".bright_blue(),
-            recent_data.len().to_string().bright_white().bold()
-        );
-        for month in recent_data.iter().rev() {
-            println!(
-                "   {}: {} ({} sessions)",
-                month.month.bright_white().bold(),
-                format!("${:.2}", month.total_cost).bright_green(),
-                format!("{}", month.total_sessions).bright_white()
-            );
+        for session in session_data {
+            for (date, daily_usage) in &session.daily_usage {
+                let Ok(date) = NaiveDate::parse_from_str(date, "%Y-%m-%d") else {
+                    continue;
+                };
+
+                if date >= current_start && date <= today {
+                    current_cost += daily_usage.cost;
+                    current_sessions.insert(session.session_id.as_str());
+                } else if date >= previous_start && date <= previous_end {
+                    previous_cost += daily_usage.cost;
+                    previous_sessions.insert(session.session_id.as_str());
+                }
+            }
+        }
+
+        let change_percent = (previous_cost > MicroDollars::ZERO).then(|| {
+            (current_cost.to_dollars() - previous_cost.to_dollars()) / previous_cost.to_dollars()
+                * 100.0
+        });
+
+        MonthToDateComparison {
+            current_month: current_start.format("%Y-%m").to_string(),
+            previous_month: previous_start.format("%Y-%m").to_string(),
+            days: today.day(),
+            current_cost,
+            previous_cost,
+            current_sessions: current_sessions.len() as u32,
+            previous_sessions: previous_sessions.len() as u32,
+            change_percent,
         }
     }
 
-    fn process_daily_with_projects(
+    pub(crate) fn process_daily_with_projects(
         &self,
         session_data: &[SessionOutput],
         limit: Option<usize>,
+        today: NaiveDate,
     ) -> Vec<DailyData> {
-        let display_limit = limit.unwrap_or(30);
+        let display_limit = limit.unwrap_or(DEFAULT_DAILY_LIMIT);
 
         // Create a map to store daily aggregated data
         let mut daily_aggregates: HashMap<String, HashMap<String, DailyProject>> = HashMap::new();
@@ -275,32 +1348,77 @@ impl ReportDisplayManager {
                 }
                 
                 let date_projects = daily_aggregates.entry(date.clone()).or_default();
-
-                let project = date_projects
-                    .entry(session.project_path.clone())
-                    .or_insert_with(|| DailyProject {
-                        project: session.project_path.clone(),
-                        sessions: 0,
-                        total_cost: 0.0,
-                        total_tokens: 0,
-                    });
-
-                // Add tokens and cost for this day
-                project.total_cost += daily_usage.cost;
-                project.total_tokens += daily_usage.input_tokens
+                let shares = self.group_shares(session, daily_usage);
+                let percentages: Vec<f64> = shares.iter().map(|(_, percent)| *percent).collect();
+                let tokens = daily_usage.input_tokens
                     + daily_usage.output_tokens
                     + daily_usage.cache_creation_tokens
                     + daily_usage.cache_read_tokens;
+                let costs = split_amount(daily_usage.cost.0, &percentages);
+                let token_parts = split_amount(i64::from(tokens), &percentages);
+                let split_tokens = |count: u32| split_amount(i64::from(count), &percentages);
+                let inputs = split_tokens(daily_usage.input_tokens);
+                let outputs = split_tokens(daily_usage.output_tokens);
+                let cache_creations = split_tokens(daily_usage.cache_creation_tokens);
+                let cache_reads = split_tokens(daily_usage.cache_read_tokens);
+                let split_cost = |cost: MicroDollars| split_amount(cost.0, &percentages);
+                let input_costs = split_cost(daily_usage.costs.input);
+                let output_costs = split_cost(daily_usage.costs.output);
+                let cache_creation_costs = split_cost(daily_usage.costs.cache_creation);
+                let cache_read_costs = split_cost(daily_usage.costs.cache_read);
+
+                for (i, (((group, _), cost), tokens)) in
+                    shares.iter().zip(costs).zip(token_parts).enumerate()
+                {
+                    let project = date_projects
+                        .entry(group.clone())
+                        .or_insert_with(|| DailyProject {
+                            project: group.clone(),
+                            sessions: 0,
+                            total_cost: MicroDollars::ZERO,
+                            total_tokens: 0,
+                            tokens: TokenCounts::default(),
+                            cost_breakdown: TokenCosts::default(),
+                            work_started: None,
+                            work_ended: None,
+                            estimated: false,
+                        });
+
+                    // Add this row's share of the day's tokens and cost
+                    project.total_cost += MicroDollars(cost);
+                    project.total_tokens += tokens as u32;
+                    project.tokens += TokenCounts {
+                        input_tokens: inputs[i] as u32,
+                        output_tokens: outputs[i] as u32,
+                        cache_creation_input_tokens: cache_creations[i] as u32,
+                        cache_read_input_tokens: cache_reads[i] as u32,
+                    };
+                    let mut costs = TokenCosts {
+                        input: MicroDollars(input_costs[i]),
+                        output: MicroDollars(output_costs[i]),
+                        cache_creation: MicroDollars(cache_creation_costs[i]),
+                        cache_read: MicroDollars(cache_read_costs[i]),
+                    };
+                    // Keep the parts adding up to the row's share of the cost
+                    let remainder = MicroDollars(cost) - costs.total();
+                    *costs.largest_mut() += remainder;
+                    project.cost_breakdown += costs;
+                    project.work_started = earliest(project.work_started, daily_usage.work_started);
+                    project.work_ended = project.work_ended.max(daily_usage.work_ended);
+                    project.estimated |= daily_usage.estimated_entries > 0;
+                }
             }
 
             // Count the session only once per day it was active
-            for date in session.daily_usage.keys() {
+            for (date, daily_usage) in &session.daily_usage {
                 let counted_this_day = counted_sessions_per_day.entry(date.clone()).or_default();
                 if counted_this_day.insert(session.session_id.clone()) {
                     // This session hasn't been counted for this day yet
                     if let Some(date_projects) = daily_aggregates.get_mut(date) {
-                        if let Some(project) = date_projects.get_mut(&session.project_path) {
-                            project.sessions += 1;
+                        for (group, _) in self.group_shares(session, daily_usage) {
+                            if let Some(project) = date_projects.get_mut(&group) {
+                                project.sessions += 1;
+                            }
                         }
                     }
                 }
@@ -309,7 +1427,7 @@ impl ReportDisplayManager {
 
         // Debug: Log Aug 20 final totals
         if let Some(aug20_data) = daily_aggregates.get("2025-08-20") {
-            let aug20_total: f64 = aug20_data.values().map(|p| p.total_cost).sum();
+            let aug20_total: MicroDollars = aug20_data.values().map(|p| p.total_cost).sum();
             let aug20_sessions: u32 = aug20_data.values().map(|p| p.sessions).sum();
             info!(
                 "Aug 20 final aggregation: {} sessions, total cost: ${:.2}",
@@ -321,50 +1439,66 @@ impl ReportDisplayManager {
         // Generate the last N days, even if they have no data
         let mut result = Vec::new();
 
-        // Get today's date
-        let today = chrono::Local::now().date_naive();
-
         // Generate the last display_limit days
         for i in 0..display_limit {
             let target_date = today - chrono::Duration::days(i as i64);
             let date_str = target_date.format("%Y-%m-%d").to_string();
+            let notes = self.notes.get(&date_str).cloned().unwrap_or_default();
 
             if let Some(date_projects) = daily_aggregates.get(&date_str) {
                 // Process projects for this date
                 let mut projects: Vec<DailyProject> = date_projects.values().cloned().collect();
                 projects.sort_by(|a, b| a.project.cmp(&b.project));
 
-                let day_total: f64 = projects.iter().map(|p| p.total_cost).sum();
+                let day_total: MicroDollars = projects.iter().map(|p| p.total_cost).sum();
                 let day_sessions: u32 = projects.iter().map(|p| p.sessions).sum();
+                let day_tokens: TokenCounts = projects.iter().map(|p| p.tokens).sum();
 
                 result.push(DailyData {
+                    model_breakdowns: self.period_breakdowns(session_data, &date_str),
                     date: date_str,
-                    projects,
                     total_cost: day_total,
                     total_sessions: day_sessions,
+                    input_tokens: day_tokens.input_tokens,
+                    output_tokens: day_tokens.output_tokens,
+                    cache_creation_tokens: day_tokens.cache_creation_input_tokens,
+                    cache_read_tokens: day_tokens.cache_read_input_tokens,
+                    cost_breakdown: projects.iter().map(|p| p.cost_breakdown).sum(),
+                    projects,
+                    notes,
                 });
             } else {
                 // No data for this date, create empty entry
                 result.push(DailyData {
                     date: date_str,
                     projects: Vec::new(),
-                    total_cost: 0.0,
+                    total_cost: MicroDollars::ZERO,
                     total_sessions: 0,
+                    input_tokens: 0,
+                    output_tokens: 0,
+                    cache_creation_tokens: 0,
+                    cache_read_tokens: 0,
+                    cost_breakdown: TokenCosts::default(),
+                    notes,
+                    model_breakdowns: Vec::new(),
                 });
             }
         }
 
         // Don't truncate - show exactly the number of days requested
+        if self.order == Some(SortOrder::Asc) {
+            result.reverse();
+        }
 
         result
     }
 
-    fn process_monthly_data(
+    pub(crate) fn process_monthly_data(
         &self,
         session_data: &[SessionOutput],
         limit: Option<usize>,
     ) -> Vec<MonthlyData> {
-        let mut monthly_aggregates: HashMap<String, (f64, HashSet<String>)> = HashMap::new();
+        let mut monthly_aggregates: HashMap<String, (MonthlyData, HashSet<String>)> = HashMap::new();
 
         // Process each session
         for session in session_data {
@@ -377,12 +1511,36 @@ impl ReportDisplayManager {
                     "unknown".to_string()
                 };
 
-                let (cost, sessions) = monthly_aggregates
-                    .entry(month)
-                    .or_insert_with(|| (0.0, HashSet::new()));
+                let (month_data, sessions) =
+                    monthly_aggregates.entry(month.clone()).or_insert_with(|| {
+                        (
+                            MonthlyData {
+                                month,
+                                total_cost: MicroDollars::ZERO,
+                                total_sessions: 0,
+                                estimated: false,
+                                model_costs: BTreeMap::new(),
+                                groups: BTreeMap::new(),
+                                model_breakdowns: Vec::new(),
+                            },
+                            HashSet::new(),
+                        )
+                    });
 
                 // Add cost for this day
-                *cost += daily_usage.cost;
+                month_data.total_cost += daily_usage.cost;
+                month_data.estimated |= daily_usage.estimated_entries > 0;
+                for (model, cost) in &daily_usage.model_costs {
+                    *month_data.model_costs.entry(model.clone()).or_default() += *cost;
+                }
+                if self.group_by != GroupBy::Project {
+                    let shares = self.group_shares(session, daily_usage);
+                    let percentages: Vec<f64> = shares.iter().map(|(_, p)| *p).collect();
+                    let costs = split_amount(daily_usage.cost.0, &percentages);
+                    for ((group, _), cost) in shares.into_iter().zip(costs) {
+                        *month_data.groups.entry(group).or_default() += MicroDollars(cost);
+                    }
+                }
 
                 // Track unique session for this month
                 sessions.insert(session.session_id.clone());
@@ -391,23 +1549,952 @@ impl ReportDisplayManager {
 
         // Convert to MonthlyData
         let mut result: Vec<MonthlyData> = monthly_aggregates
-            .into_iter()
-            .map(|(month, (total_cost, sessions))| MonthlyData {
-                month,
-                total_cost,
+            .into_values()
+            .map(|(month_data, sessions)| MonthlyData {
                 total_sessions: sessions.len() as u32,
+                model_breakdowns: self.period_breakdowns(session_data, &month_data.month),
+                ..month_data
             })
             .collect();
 
         result.sort_by(|a, b| a.month.cmp(&b.month));
 
         // Apply limit - show most recent months
-        let display_limit = limit.unwrap_or(10);
+        let display_limit = limit.unwrap_or(DEFAULT_MONTHLY_LIMIT);
         if result.len() > display_limit {
             let skip_count = result.len() - display_limit;
             result = result.into_iter().skip(skip_count).collect();
         }
+        if self.order == Some(SortOrder::Desc) {
+            result.reverse();
+        }
 
         result
     }
 }
+
+/// Per-model rows of a period's usage, most expensive first
+fn model_breakdowns<'a>(usages: impl Iterator<Item = &'a DailyUsage>) -> Vec<ModelBreakdown> {
+    let mut models: BTreeMap<&str, (TokenCounts, MicroDollars)> = BTreeMap::new();
+    for usage in usages {
+        for (model, cost) in &usage.model_costs {
+            models.entry(model).or_default().1 += *cost;
+        }
+        for (model, tokens) in &usage.model_tokens {
+            models.entry(model).or_default().0 += *tokens;
+        }
+    }
+
+    let mut rows: Vec<ModelBreakdown> = models
+        .into_iter()
+        .map(|(model, (tokens, cost))| ModelBreakdown {
+            model_name: model.to_string(),
+            input_tokens: tokens.input_tokens,
+            output_tokens: tokens.output_tokens,
+            cache_creation_tokens: tokens.cache_creation_input_tokens,
+            cache_read_tokens: tokens.cache_read_input_tokens,
+            cost,
+        })
+        .collect();
+    rows.sort_by_key(|row| std::cmp::Reverse(row.cost));
+    rows
+}
+
+/// Print `--breakdown` rows below a day or month
+fn print_model_breakdowns(rows: &[ModelBreakdown], indent: &str) {
+    for row in rows {
+        let tokens = u64::from(row.input_tokens)
+            + u64::from(row.output_tokens)
+            + u64::from(row.cache_creation_tokens)
+            + u64::from(row.cache_read_tokens);
+        println!(
+            "{}{}: {} ({} tokens)",
+            indent,
+            row.model_name.bright_black(),
+            format!("${:.2}", row.cost).green(),
+            tokens.to_string().bright_white()
+        );
+    }
+}
+
+/// Each model's share of a day's cost, in percent; a day without costs is
+/// split evenly across its models
+fn model_shares(day: &DailyUsage) -> Vec<(String, f64)> {
+    let models: BTreeMap<&String, MicroDollars> = day
+        .model_costs
+        .iter()
+        .map(|(model, cost)| (model, *cost))
+        .collect();
+    if models.is_empty() {
+        return vec![("unknown".to_string(), 100.0)];
+    }
+    let total: i64 = models.values().map(|cost| cost.0).sum();
+    models
+        .into_iter()
+        .map(|(model, cost)| {
+            let percent = if total > 0 {
+                cost.0 as f64 * 100.0 / total as f64
+            } else {
+                100.0 / day.model_costs.len() as f64
+            };
+            (model.clone(), percent)
+        })
+        .collect()
+}
+
+/// Split a day's tokens and cost across a session's group percentages
+fn split_daily_usage(usage: &DailyUsage, percentages: &[f64]) -> Vec<(TokenCounts, MicroDollars)> {
+    let split = |count: u32| split_amount(i64::from(count), percentages);
+    let inputs = split(usage.input_tokens);
+    let outputs = split(usage.output_tokens);
+    let cache_creations = split(usage.cache_creation_tokens);
+    let cache_reads = split(usage.cache_read_tokens);
+    let costs = split_amount(usage.cost.0, percentages);
+
+    (0..percentages.len())
+        .map(|i| {
+            let tokens = TokenCounts {
+                input_tokens: inputs[i] as u32,
+                output_tokens: outputs[i] as u32,
+                cache_creation_input_tokens: cache_creations[i] as u32,
+                cache_read_input_tokens: cache_reads[i] as u32,
+            };
+            (tokens, MicroDollars(costs[i]))
+        })
+        .collect()
+}
+
+/// Header of a CSV report: the row's own columns followed by token and cost columns
+fn csv_header(columns: &[&str]) -> String {
+    format!(
+        "{},input_tokens,output_tokens,cache_creation_tokens,cache_read_tokens,total_tokens,cost_usd\n",
+        columns.join(",")
+    )
+}
+
+/// Append one CSV row, quoting fields that contain separators or quotes
+fn push_csv_row(csv: &mut String, fields: &[&str], tokens: &TokenCounts, cost: MicroDollars) {
+    let fields: Vec<String> = fields.iter().map(|field| csv_field(field)).collect();
+    csv.push_str(&format!(
+        "{},{},{},{},{},{},{:.6}\n",
+        fields.join(","),
+        tokens.input_tokens,
+        tokens.output_tokens,
+        tokens.cache_creation_input_tokens,
+        tokens.cache_read_input_tokens,
+        tokens.total(),
+        cost.to_dollars()
+    ));
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// The first `limit` ranked projects, [`DEFAULT_TOP_LIMIT`] when unset
+fn top_projects(projects: &[ProjectTotals], limit: Option<usize>) -> &[ProjectTotals] {
+    let limit = limit.unwrap_or(DEFAULT_TOP_LIMIT);
+    &projects[..limit.min(projects.len())]
+}
+
+/// A session of `session --json`
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SessionRow<'a> {
+    #[serde(flatten)]
+    session: &'a SessionOutput,
+    total_tokens: u64,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    model_breakdowns: Vec<ModelBreakdown>,
+}
+
+/// Tokens and cost of the sessions in `session --json`
+#[derive(Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SessionTotals {
+    input_tokens: u64,
+    output_tokens: u64,
+    cache_creation_tokens: u64,
+    cache_read_tokens: u64,
+    total_tokens: u64,
+    total_cost: MicroDollars,
+}
+
+/// Input, output and cache tokens of a session together
+fn session_total_tokens(session: &SessionOutput) -> u64 {
+    u64::from(session.input_tokens)
+        + u64::from(session.output_tokens)
+        + u64::from(session.cache_creation_tokens)
+        + u64::from(session.cache_read_tokens)
+}
+
+/// Sessions ordered most recently active first, keeping the first `limit`
+pub(crate) fn sorted_sessions(data: &[SessionOutput], limit: Option<usize>) -> Vec<&SessionOutput> {
+    let mut sessions: Vec<&SessionOutput> = data.iter().collect();
+    sessions.sort_by(|a, b| {
+        b.last_activity
+            .cmp(&a.last_activity)
+            .then_with(|| a.session_id.cmp(&b.session_id))
+    });
+    if let Some(limit) = limit {
+        sessions.truncate(limit);
+    }
+    sessions
+}
+
+/// Entries whose day (`YYYY-MM-DD`) or month (`YYYY-MM`) is one of the rendered periods
+///
+/// Keeps per-entry exports in step with `--limit`, so the entries always add
+/// up to the rows they accompany.
+fn entries_in_periods<'a>(
+    entries: &'a [EntryExport],
+    periods: &HashSet<&str>,
+) -> Vec<&'a EntryExport> {
+    entries
+        .iter()
+        .filter(|entry| {
+            [10, 7]
+                .iter()
+                .filter_map(|len| entry.timestamp.get(..*len))
+                .any(|period| periods.contains(period))
+        })
+        .collect()
+}
+
+/// Print the recorded vs computed cost comparison below a report
+fn print_cost_audit(audit: &CostAudit) {
+    println!(
+        "\n{} Cost audit: {} of {} entries with a recorded costUSD differ from token pricing by more than {}%",
+        "🔍".bright_yellow(),
+        audit.mismatched.to_string().bright_white().bold(),
+        audit.checked.to_string().bright_white().bold(),
+        audit.tolerance_pct
+    );
+    if audit.groups.is_empty() {
+        return;
+    }
+
+    println!(
+        "   {}",
+        format!(
+            "{:<10} {:<32} {:>10} {:>10} {:>10} {:>9}",
+            "Date", "Model", "Entries", "Recorded", "Computed", "Max Diff"
+        )
+        .bright_black()
+    );
+    for group in &audit.groups {
+        println!(
+            "   {:<10} {:<32} {:>10} {:>10} {:>10} {:>9}",
+            group.date,
+            group.model,
+            format!("{}/{}", group.mismatched, group.entries),
+            format!("${:.4}", group.recorded_cost),
+            format!("${:.4}", group.computed_cost),
+            if group.max_difference_pct.is_finite() {
+                format!("{:.0}%", group.max_difference_pct)
+            } else {
+                "n/a".to_string()
+            }
+            .bright_red()
+        );
+    }
+}
+
+/// Explain a cost mode recommendation below a report
+fn print_cost_mode_advice(advice: &CostModeAdvice) {
+    let Some(mode) = advice.recommended else {
+        return;
+    };
+    println!(
+        "\n{} Cost mode: recorded costUSD differs systematically from token pricing for these models, so auto mode prices their entries on two bases:",
+        "💡".bright_yellow()
+    );
+    for model in &advice.divergent_models {
+        println!(
+            "   {:<32} recorded at {:.2}× token pricing over {} entries, {} without costUSD",
+            model.model, model.recorded_ratio, model.recorded_entries, model.unrecorded_entries
+        );
+    }
+    let flag = format!("--mode {}", mode.as_str()).bright_cyan();
+    if advice.switched {
+        println!("   Switched to {} for this run", flag);
+    } else {
+        println!(
+            "   Rerun with {}, or add --check-mode to switch automatically",
+            flag
+        );
+    }
+}
+
+/// Print the month-to-date section of the monthly report
+fn print_month_to_date(month_to_date: &MonthToDateComparison) {
+    println!(
+        "\n{} Month-to-date (first {} days):",
+        "📈".bright_blue(),
+        month_to_date.days.to_string().bright_white().bold()
+    );
+    println!(
+        "   {}: {} ({} sessions)",
+        month_to_date.current_month.bright_white().bold(),
+        format!("${:.2}", month_to_date.current_cost).bright_green(),
+        month_to_date.current_sessions.to_string().bright_white()
+    );
+    println!(
+        "   {}: {} ({} sessions)",
+        month_to_date.previous_month.bright_white().bold(),
+        format!("${:.2}", month_to_date.previous_cost).bright_green(),
+        month_to_date.previous_sessions.to_string().bright_white()
+    );
+    match month_to_date.change_percent {
+        Some(change) if change >= 0.0 => {
+            println!("   Change: {}", format!("+{:.1}%", change).bright_red())
+        }
+        Some(change) => println!("   Change: {}", format!("{:.1}%", change).bright_green()),
+        None => println!("   Change: {}", "n/a".bright_black()),
+    }
+}
+
+/// Warn about files left out of the report because they could not be read
+fn print_failed_files(failed_files: &[FailedFile]) {
+    if failed_files.is_empty() {
+        return;
+    }
+
+    println!(
+        "\n{}  Skipped {} unreadable file(s); totals may be incomplete:",
+        "⚠️".bright_yellow(),
+        failed_files.len().to_string().bright_white().bold()
+    );
+    for failed in failed_files {
+        println!(
+            "   {} ({} attempts): {}",
+            failed.path.display().to_string().bright_white(),
+            failed.attempts,
+            failed.error.bright_red()
+        );
+    }
+}
+
+/// Warn on stderr that the scan was interrupted and the report is incomplete
+pub fn print_partial() {
+    eprintln!(
+        "{}  {}",
+        "⚠️".bright_yellow(),
+        "Interrupted: showing partial results from the data read so far".bright_yellow()
+    );
+}
+
+/// List what each filter left out of the report, for `--verbose`
+pub fn print_skipped(skipped: &SkippedData) {
+    if skipped.is_empty() {
+        println!("\n{}", "🔎 Filters left nothing out".bright_black());
+        return;
+    }
+
+    println!("\n{}", "🔎 Left out by filters:".bright_white().bold());
+    for (reason, volume) in skipped.reasons() {
+        println!("   {}: {}", reason.label().bright_cyan(), volume.describe());
+    }
+}
+
+/// Print how the parse cache served the logs read, below a `--verbose` report
+pub fn print_parse_cache(stats: &ParseCacheStats) {
+    if stats.is_empty() {
+        println!(
+            "{}",
+            "🗂  No logs were read through the parse cache".bright_black()
+        );
+        return;
+    }
+    println!(
+        "{} {} cached, {} re-parsed, {} new",
+        "🗂  Parse cache:".bright_white().bold(),
+        stats.cached,
+        stats.reparsed,
+        stats.discovered
+    );
+}
+
+/// Print a day's rows with one column per token kind, followed by the day's totals
+fn print_token_table(label: &str, day: &DailyData, show_details: bool) {
+    println!(
+        "   {}",
+        format!(
+            "{:<32} {:>12} {:>12} {:>12} {:>12} {:>10}",
+            label, "Input", "Output", "Cache Read", "Cache Write", "Cost"
+        )
+        .bright_black()
+    );
+    let row = |name: &str, tokens: &TokenCounts, cost: MicroDollars| {
+        format!(
+            "{:<32} {:>12} {:>12} {:>12} {:>12} {:>10}",
+            name,
+            tokens.input_tokens,
+            tokens.output_tokens,
+            tokens.cache_read_input_tokens,
+            tokens.cache_creation_input_tokens,
+            format!("${:.2}", cost)
+        )
+    };
+    for project in &day.projects {
+        let hours = working_hours(project.work_started, project.work_ended)
+            .filter(|_| show_details)
+            .map(|hours| format!(" {}", hours.bright_black()))
+            .unwrap_or_default();
+        println!(
+            "   {}{}{}",
+            row(&project.project, &project.tokens, project.total_cost),
+            hours,
+            estimated_marker(project.estimated)
+        );
+    }
+    let totals: TokenCounts = day.projects.iter().map(|p| p.tokens).sum();
+    println!("   {}", row("Total", &totals, day.total_cost).bold());
+    let costs = &day.cost_breakdown;
+    let cost = |amount: MicroDollars| format!("${:.2}", amount);
+    println!(
+        "   {}",
+        format!(
+            "{:<32} {:>12} {:>12} {:>12} {:>12} {:>10}",
+            "Cost",
+            cost(costs.input),
+            cost(costs.output),
+            cost(costs.cache_read),
+            cost(costs.cache_creation),
+            cost(costs.total())
+        )
+        .bright_green()
+    );
+}
+
+/// The earlier of two times, ignoring a missing one
+fn earliest(a: Option<DateTime<Utc>>, b: Option<DateTime<Utc>>) -> Option<DateTime<Utc>> {
+    match (a, b) {
+        (Some(a), Some(b)) => Some(a.min(b)),
+        _ => a.or(b),
+    }
+}
+
+/// `HH:MM–HH:MM` in local time for a row's first and last entry
+fn working_hours(started: Option<DateTime<Utc>>, ended: Option<DateTime<Utc>>) -> Option<String> {
+    let local = |at: DateTime<Utc>| DateTime::<Local>::from(at).format("%H:%M").to_string();
+    Some(format!("{}–{}", local(started?), local(ended?)))
+}
+
+/// Suffix marking report rows that include token counts estimated from content
+fn estimated_marker(estimated: bool) -> String {
+    if estimated {
+        format!(" {}", "(estimated)".bright_black())
+    } else {
+        String::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::test_support::session;
+
+    #[test]
+    fn test_month_to_date_uses_same_leading_days() {
+        let manager = ReportDisplayManager::new();
+        let data = vec![
+            session("a")
+                .days(&[("2025-03-05", 2.0), ("2025-03-12", 1.0)])
+                .build(),
+            session("b")
+                .days(&[("2025-02-10", 1.0), ("2025-02-13", 5.0)])
+                .build(),
+        ];
+        let today = NaiveDate::from_ymd_opt(2025, 3, 12).unwrap();
+
+        let mtd = manager.month_to_date_comparison(&data, today);
+        assert_eq!(mtd.current_month, "2025-03");
+        assert_eq!(mtd.previous_month, "2025-02");
+        assert_eq!(mtd.days, 12);
+        assert_eq!(mtd.current_cost.to_dollars(), 3.0);
+        // Feb 13 falls outside the first 12 days
+        assert_eq!(mtd.previous_cost.to_dollars(), 1.0);
+        assert_eq!(mtd.current_sessions, 1);
+        assert_eq!(mtd.previous_sessions, 1);
+        assert_eq!(mtd.change_percent, Some(200.0));
+    }
+
+    #[test]
+    fn test_month_to_date_clamps_to_shorter_previous_month() {
+        let manager = ReportDisplayManager::new();
+        let data = vec![session("a")
+            .days(&[("2025-02-28", 4.0), ("2025-03-01", 1.0)])
+            .build()];
+        let today = NaiveDate::from_ymd_opt(2025, 3, 31).unwrap();
+
+        let mtd = manager.month_to_date_comparison(&data, today);
+        assert_eq!(mtd.previous_cost.to_dollars(), 4.0);
+        assert_eq!(mtd.current_cost.to_dollars(), 1.0);
+    }
+
+    #[test]
+    fn test_month_to_date_left_out_when_date_filter_cuts_it() {
+        let mut manager = ReportDisplayManager::new();
+        let data = vec![session("a")
+            .days(&[("2025-02-10", 1.0), ("2025-03-05", 2.0)])
+            .build()];
+        let today = NaiveDate::from_ymd_opt(2025, 3, 12).unwrap();
+        let day = |d: &str| {
+            NaiveDate::parse_from_str(d, "%Y-%m-%d")
+                .unwrap()
+                .and_hms_opt(0, 0, 0)
+                .unwrap()
+                .and_utc()
+        };
+
+        manager.set_date_filter(Some(day("2025-02-01")), None);
+        let output = manager.monthly_json(&data, None, None, today);
+        assert_eq!(output["monthToDate"]["previousCost"], 1.0);
+
+        // Starting mid-February would count only part of last month
+        manager.set_date_filter(Some(day("2025-02-10")), None);
+        let output = manager.monthly_json(&data, None, None, today);
+        assert!(output["monthToDate"].is_null());
+
+        manager.set_date_filter(None, Some(day("2025-03-11")));
+        let output = manager.monthly_json(&data, None, None, today);
+        assert!(output["monthToDate"].is_null());
+    }
+
+    #[test]
+    fn test_month_to_date_without_previous_usage() {
+        let manager = ReportDisplayManager::new();
+        let data = vec![session("a").days(&[("2025-01-02", 1.0)]).build()];
+        let today = NaiveDate::from_ymd_opt(2025, 1, 15).unwrap();
+
+        let mtd = manager.month_to_date_comparison(&data, today);
+        assert_eq!(mtd.previous_month, "2024-12");
+        assert_eq!(mtd.change_percent, None);
+    }
+
+    #[test]
+    fn test_monthly_groups_by_instance_type() {
+        let mut manager = ReportDisplayManager::new();
+        let mut vm_session = session("b").days(&[("2025-03-02", 2.0)]).build();
+        vm_session.instance_type = InstanceType::Vm;
+        let data = vec![
+            session("a").days(&[("2025-03-01", 1.0)]).build(),
+            vm_session,
+        ];
+
+        let monthly = manager.process_monthly_data(&data, None);
+        assert!(monthly[0].groups.is_empty());
+
+        manager.set_group_by(GroupBy::InstanceType);
+        let monthly = manager.process_monthly_data(&data, None);
+        assert_eq!(monthly[0].groups["host"].to_dollars(), 1.0);
+        assert_eq!(monthly[0].groups["vm"].to_dollars(), 2.0);
+    }
+
+    #[test]
+    fn test_daily_groups_by_vm_and_model() {
+        let mut manager = ReportDisplayManager::new();
+        let mut vm_session = session("b").days(&[("2025-03-01", 3.0)]).build();
+        vm_session.instance_type = InstanceType::Vm;
+        vm_session.vm = Some("build-box".to_string());
+        let day = vm_session.daily_usage.get_mut("2025-03-01").unwrap();
+        day.model_costs = HashMap::from([
+            ("claude-opus".to_string(), MicroDollars::from_dollars(2.0)),
+            ("claude-haiku".to_string(), MicroDollars::from_dollars(1.0)),
+        ]);
+        let data = vec![
+            session("a").days(&[("2025-03-01", 1.0)]).build(),
+            vm_session,
+        ];
+        let today = NaiveDate::from_ymd_opt(2025, 3, 1).unwrap();
+        let row_costs = |manager: &ReportDisplayManager| -> Vec<(String, f64)> {
+            manager.process_daily_with_projects(&data, Some(1), today)[0]
+                .projects
+                .iter()
+                .map(|row| (row.project.clone(), row.total_cost.to_dollars()))
+                .collect()
+        };
+
+        manager.set_group_by(GroupBy::Vm);
+        let mut rows = row_costs(&manager);
+        rows.sort_by(|a, b| a.0.cmp(&b.0));
+        assert_eq!(
+            rows,
+            vec![("build-box".to_string(), 3.0), ("host".to_string(), 1.0)]
+        );
+
+        manager.set_group_by(GroupBy::Model);
+        let mut rows = row_costs(&manager);
+        rows.sort_by(|a, b| a.0.cmp(&b.0));
+        assert_eq!(
+            rows,
+            vec![
+                ("claude-haiku".to_string(), 1.0),
+                ("claude-opus".to_string(), 2.0),
+                ("unknown".to_string(), 1.0),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_daily_rows_carry_notes() {
+        let mut manager = ReportDisplayManager::new();
+        let data = vec![session("a").days(&[("2025-01-14", 1.0)]).build()];
+        let today = NaiveDate::from_ymd_opt(2025, 1, 15).unwrap();
+        manager.set_notes(DayNotes::from([(
+            "2025-01-14".to_string(),
+            vec!["big refactor with opus agent".to_string()],
+        )]));
+
+        let json = manager.daily_json(&data, Some(2), None, today);
+        assert!(json["daily"][0].get("notes").is_none());
+        assert_eq!(json["daily"][1]["notes"][0], "big refactor with opus agent");
+    }
+
+    fn export(timestamp: &str) -> EntryExport {
+        EntryExport {
+            timestamp: timestamp.to_string(),
+            request_id: String::new(),
+            message_id: String::new(),
+            model: String::new(),
+            input_tokens: 0,
+            output_tokens: 0,
+            cache_creation_tokens: 0,
+            cache_read_tokens: 0,
+            cost: 0.0,
+            secondary_usage: None,
+            provenance: None,
+        }
+    }
+
+    #[test]
+    fn test_daily_limit_renders_last_n_days_in_json() {
+        let manager = ReportDisplayManager::new();
+        let data = vec![session("a")
+            .days(&[("2025-01-10", 1.0), ("2025-01-14", 2.0)])
+            .build()];
+        let entries = [
+            export("2025-01-10T09:00:00+00:00"),
+            export("2025-01-14T09:00:00+00:00"),
+        ];
+        let today = NaiveDate::from_ymd_opt(2025, 1, 15).unwrap();
+
+        let json = manager.daily_json(&data, Some(3), Some(&entries), today);
+        let days = json["daily"].as_array().unwrap();
+        assert_eq!(days.len(), 3);
+        assert_eq!(days[2]["date"], "2025-01-13");
+        assert_eq!(json["entries"].as_array().unwrap().len(), 1);
+        assert_eq!(json["entries"][0]["timestamp"], "2025-01-14T09:00:00+00:00");
+
+        let json = manager.daily_json(&data, None, None, today);
+        assert_eq!(json["daily"].as_array().unwrap().len(), DEFAULT_DAILY_LIMIT);
+    }
+
+    #[test]
+    fn test_monthly_limit_renders_last_n_months_in_json() {
+        let manager = ReportDisplayManager::new();
+        let data = vec![session("a")
+            .days(&[
+                ("2024-11-02", 1.0),
+                ("2024-12-02", 2.0),
+                ("2025-01-02", 4.0),
+            ])
+            .build()];
+        let entries = [
+            export("2024-11-02T09:00:00+00:00"),
+            export("2025-01-02T09:00:00+00:00"),
+        ];
+        let today = NaiveDate::from_ymd_opt(2025, 1, 15).unwrap();
+
+        let json = manager.monthly_json(&data, Some(2), Some(&entries), today);
+        let months: Vec<&str> = json["monthly"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|m| m["month"].as_str().unwrap())
+            .collect();
+        assert_eq!(months, ["2024-12", "2025-01"]);
+        assert_eq!(json["entries"].as_array().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_cost_center_splits_sessions() {
+        let mut manager = ReportDisplayManager::new();
+        manager.set_group_by(GroupBy::CostCenter);
+        manager.set_allocations(
+            Allocations::new(HashMap::from([(
+                "shared".to_string(),
+                BTreeMap::from([("PROJ-1".to_string(), 60.0), ("PROJ-2".to_string(), 40.0)]),
+            )]))
+            .unwrap(),
+        );
+        let data = vec![
+            session("shared").days(&[("2025-03-01", 1.0)]).build(),
+            session("solo").days(&[("2025-03-01", 0.5)]).build(),
+        ];
+        let today = NaiveDate::from_ymd_opt(2025, 3, 1).unwrap();
+
+        let daily = manager.process_daily_with_projects(&data, Some(1), today);
+        let rows: Vec<(&str, f64, u32)> = daily[0]
+            .projects
+            .iter()
+            .map(|p| (p.project.as_str(), p.total_cost.to_dollars(), p.sessions))
+            .collect();
+        assert_eq!(
+            rows,
+            [("PROJ-1", 0.6, 1), ("PROJ-2", 0.4, 1), ("unallocated", 0.5, 1)]
+        );
+        assert_eq!(daily[0].total_cost.to_dollars(), 1.5);
+
+        let monthly = manager.process_monthly_data(&data, None);
+        assert_eq!(monthly[0].groups["PROJ-1"].to_dollars(), 0.6);
+        assert_eq!(monthly[0].groups["unallocated"].to_dollars(), 0.5);
+        assert_eq!(monthly[0].total_cost.to_dollars(), 1.5);
+    }
+
+    #[test]
+    fn test_daily_rows_carry_token_breakdown() {
+        let mut data = vec![
+            session("a").days(&[("2025-03-01", 1.0)]).build(),
+            session("b").days(&[("2025-03-01", 1.0)]).build(),
+        ];
+        for (session, base) in data.iter_mut().zip([100, 1000]) {
+            let usage = session.daily_usage.get_mut("2025-03-01").unwrap();
+            usage.input_tokens = base;
+            usage.output_tokens = base * 2;
+            usage.cache_creation_tokens = base * 3;
+            usage.cache_read_tokens = base * 4;
+        }
+        let today = NaiveDate::from_ymd_opt(2025, 3, 1).unwrap();
+
+        let daily = ReportDisplayManager::new().process_daily_with_projects(&data, Some(1), today);
+        let project = &daily[0].projects[0];
+        assert_eq!(
+            project.tokens,
+            TokenCounts {
+                input_tokens: 1100,
+                output_tokens: 2200,
+                cache_creation_input_tokens: 3300,
+                cache_read_input_tokens: 4400,
+            }
+        );
+        assert_eq!(project.total_tokens, 11000);
+        // The breakdown stays out of the JSON output
+        assert!(serde_json::to_value(project).unwrap().get("tokens").is_none());
+    }
+
+    #[test]
+    fn test_daily_cost_breakdown_follows_token_prices() {
+        let mut data = vec![session("a").days(&[("2025-03-01", 0.0)]).build()];
+        let day = data[0].daily_usage.get_mut("2025-03-01").unwrap();
+        let tokens = TokenCounts {
+            input_tokens: 1000,
+            output_tokens: 1000,
+            cache_creation_input_tokens: 0,
+            cache_read_input_tokens: 10000,
+        };
+        // A recorded cost twice the priced one is spread in the same proportions
+        day.cost = MicroDollars::from_dollars(0.042);
+        day.input_tokens = 1000;
+        day.output_tokens = 1000;
+        day.cache_read_tokens = 10000;
+        day.add_model_usage("claude-sonnet-4", tokens, day.cost);
+        let today = NaiveDate::from_ymd_opt(2025, 3, 1).unwrap();
+
+        let manager = ReportDisplayManager::new();
+        let daily = manager.process_daily_with_projects(&data, Some(1), today);
+        let costs = daily[0].cost_breakdown;
+        assert_eq!(costs.input, MicroDollars::from_dollars(0.006));
+        assert_eq!(costs.output, MicroDollars::from_dollars(0.03));
+        assert_eq!(costs.cache_creation, MicroDollars::ZERO);
+        assert_eq!(costs.cache_read, MicroDollars::from_dollars(0.006));
+        assert_eq!(costs.total(), daily[0].total_cost);
+        assert_eq!(daily[0].projects[0].cost_breakdown, costs);
+
+        let json = manager.daily_json(&data, Some(1), None, today);
+        assert_eq!(json["daily"][0]["cacheReadTokens"], 10000);
+        assert_eq!(json["daily"][0]["costBreakdown"]["cacheRead"], 0.006);
+    }
+
+    #[test]
+    fn test_daily_rows_span_first_to_last_entry() {
+        let mut data = vec![
+            session("a").days(&[("2025-03-01", 1.0)]).build(),
+            session("b").days(&[("2025-03-01", 1.0)]).build(),
+        ];
+        let at = |time: &str| {
+            format!("2025-03-01T{}Z", time)
+                .parse::<DateTime<Utc>>()
+                .unwrap()
+        };
+        let day = data[0].daily_usage.get_mut("2025-03-01").unwrap();
+        day.record_entry_time(at("13:30:00"));
+        day.record_entry_time(at("09:15:00"));
+        let day = data[1].daily_usage.get_mut("2025-03-01").unwrap();
+        day.record_entry_time(at("17:45:00"));
+        let today = NaiveDate::from_ymd_opt(2025, 3, 1).unwrap();
+
+        let manager = ReportDisplayManager::new();
+        let daily = manager.process_daily_with_projects(&data, Some(1), today);
+        let project = &daily[0].projects[0];
+        assert_eq!(project.work_started, Some(at("09:15:00")));
+        assert_eq!(project.work_ended, Some(at("17:45:00")));
+
+        let json = manager.daily_json(&data, Some(1), None, today);
+        let row = &json["daily"][0]["projects"][0];
+        assert_eq!(row["workStarted"], "2025-03-01T09:15:00Z");
+        assert_eq!(row["workEnded"], "2025-03-01T17:45:00Z");
+    }
+
+    #[test]
+    fn test_order_and_breakdown_match_ccusage_shapes() {
+        let mut data = vec![
+            session("a")
+                .days(&[("2025-02-28", 1.0), ("2025-03-01", 1.0)])
+                .build(),
+            session("b").days(&[("2025-03-01", 1.0)]).build(),
+        ];
+        let tokens = |input| TokenCounts {
+            input_tokens: input,
+            ..TokenCounts::default()
+        };
+        let cost = MicroDollars::from_dollars;
+        let day = data[0].daily_usage.get_mut("2025-03-01").unwrap();
+        day.add_model_usage("claude-sonnet-4", tokens(10), cost(0.25));
+        let day = data[1].daily_usage.get_mut("2025-03-01").unwrap();
+        day.add_model_usage("claude-opus-4", tokens(5), cost(1.0));
+        day.add_model_usage("claude-sonnet-4", tokens(20), cost(0.5));
+        let today = NaiveDate::from_ymd_opt(2025, 3, 1).unwrap();
+
+        let mut manager = ReportDisplayManager::new();
+        let json = manager.daily_json(&data, Some(2), None, today);
+        assert_eq!(json["daily"][0]["date"], "2025-03-01");
+        assert!(json["daily"][0].get("modelBreakdowns").is_none());
+
+        manager.set_order(Some(SortOrder::Asc));
+        manager.set_breakdown(true);
+        let json = manager.daily_json(&data, Some(2), None, today);
+        assert_eq!(json["daily"][0]["date"], "2025-02-28");
+        let rows = &json["daily"][1]["modelBreakdowns"];
+        assert_eq!(rows[0]["modelName"], "claude-opus-4");
+        assert_eq!(rows[1]["modelName"], "claude-sonnet-4");
+        assert_eq!(rows[1]["inputTokens"], 30);
+        assert_eq!(rows[1]["cost"], 0.75);
+
+        manager.set_order(Some(SortOrder::Desc));
+        let monthly = manager.process_monthly_data(&data, None);
+        let months: Vec<&str> = monthly.iter().map(|m| m.month.as_str()).collect();
+        assert_eq!(months, ["2025-03", "2025-02"]);
+        assert_eq!(monthly[0].model_breakdowns.len(), 2);
+    }
+
+    #[test]
+    fn test_session_json_matches_ccusage_session_report() {
+        let mut data = vec![
+            session("a").days(&[("2025-03-01", 1.0)]).build(),
+            session("b").days(&[("2025-03-02", 0.5)]).build(),
+        ];
+        data[0].last_activity = "2025-03-02T10:00:00+00:00".to_string();
+        data[1].last_activity = "2025-03-02T09:00:00+00:00".to_string();
+        data[0].input_tokens = 100;
+        data[0].cache_read_tokens = 900;
+        data[1].output_tokens = 50;
+        let day = data[0].daily_usage.get_mut("2025-03-01").unwrap();
+        let tokens = TokenCounts {
+            input_tokens: 100,
+            ..TokenCounts::default()
+        };
+        day.add_model_usage("claude-sonnet-4", tokens, MicroDollars::from_dollars(1.0));
+
+        let mut manager = ReportDisplayManager::new();
+        let json = manager.session_json(&data, None);
+        assert_eq!(json["sessions"][0]["sessionId"], "a");
+        assert_eq!(json["sessions"][0]["totalTokens"], 1000);
+        assert_eq!(
+            json["sessions"][0]["lastActivity"],
+            "2025-03-02T10:00:00+00:00"
+        );
+        assert!(json["sessions"][0].get("modelBreakdowns").is_none());
+        assert_eq!(json["totals"]["inputTokens"], 200);
+        assert_eq!(json["totals"]["totalTokens"], 1150);
+        assert_eq!(json["totals"]["totalCost"], 1.5);
+
+        manager.set_breakdown(true);
+        let json = manager.session_json(&data, Some(1));
+        let rows = &json["sessions"][0]["modelBreakdowns"];
+        assert_eq!(rows[0]["modelName"], "claude-sonnet-4");
+        assert_eq!(rows[0]["inputTokens"], 100);
+        assert_eq!(rows[0]["cost"], 1.0);
+        assert_eq!(json["totals"]["totalCost"], 1.0);
+    }
+
+    #[test]
+    fn test_failed_files_are_reported_in_json_metadata() {
+        let mut manager = ReportDisplayManager::new();
+        let data = vec![session("a").days(&[("2025-01-14", 2.0)]).build()];
+        assert!(manager.session_json(&data, None).get("metadata").is_none());
+
+        manager.set_failed_files(vec![FailedFile {
+            path: "/logs/session.jsonl".into(),
+            error: "Permission denied".to_string(),
+            attempts: 4,
+        }]);
+        let today = NaiveDate::from_ymd_opt(2025, 1, 15).unwrap();
+        let json = manager.monthly_json(&data, None, None, today);
+        let failed = &json["metadata"]["failedFiles"][0];
+        assert_eq!(failed["path"], "/logs/session.jsonl");
+        assert_eq!(failed["attempts"], 4);
+        assert!(json["metadata"].get("skipped").is_none());
+
+        let mut skipped = SkippedData::default();
+        skipped.add_sessions(crate::skipped::SkipReason::DateFilter, 3);
+        manager.set_skipped(skipped);
+        let json = manager.session_json(&data, None);
+        assert_eq!(json["metadata"]["skipped"]["dateFilter"]["sessions"], 3);
+        assert!(json["metadata"].get("partial").is_none());
+
+        manager.set_partial(true);
+        let json = manager.session_json(&data, None);
+        assert_eq!(json["metadata"]["partial"], true);
+    }
+
+    #[test]
+    fn test_csv_reports_have_one_row_per_period_and_group() {
+        let manager = ReportDisplayManager::new();
+        let quoted = session("b")
+            .project("acme, inc")
+            .days(&[("2025-01-14", 0.5)])
+            .build();
+        let data = vec![
+            session("a")
+                .days(&[("2025-01-10", 1.0), ("2025-01-14", 2.0)])
+                .build(),
+            quoted,
+        ];
+        let today = NaiveDate::from_ymd_opt(2025, 1, 15).unwrap();
+
+        let daily = manager.daily_csv(&data, Some(7), today);
+        let lines: Vec<&str> = daily.lines().collect();
+        assert_eq!(
+            lines[0],
+            "date,project,sessions,input_tokens,output_tokens,cache_creation_tokens,cache_read_tokens,total_tokens,cost_usd"
+        );
+        assert_eq!(lines[1], "2025-01-14,\"acme, inc\",1,100,0,0,0,100,0.500000");
+        assert_eq!(lines[2], "2025-01-14,project,1,100,0,0,0,100,2.000000");
+        assert_eq!(lines[3], "2025-01-10,project,1,100,0,0,0,100,1.000000");
+        assert_eq!(lines.len(), 4);
+
+        let monthly = manager.monthly_csv(&data, None);
+        let lines: Vec<&str> = monthly.lines().collect();
+        assert!(lines[0].starts_with("month,project,sessions,"));
+        assert_eq!(lines[2], "2025-01,project,1,200,0,0,0,200,3.000000");
+
+        let sessions = manager.session_csv(&data, Some(1));
+        assert_eq!(sessions.lines().count(), 2);
+    }
+}
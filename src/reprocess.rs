@@ -0,0 +1,305 @@
+//! Totals under alternative dedup settings
+//!
+//! Reports drop an entry whose `messageId:requestId` key was already seen in
+//! any log of any instance, however long ago. `claude-usage reprocess` reads
+//! the entries of a date range once, duplicates included, and totals them
+//! again under each requested [`DedupSettings`], so the effect of a window,
+//! a narrower scope or another key is measured rather than guessed:
+//!
+//! - **window**: a key counts as a duplicate only within this many hours of
+//!   its previous occurrence
+//! - **scope**: keys are compared across all logs, within an instance, or
+//!   within a session log
+//! - **key**: `messageId:requestId`, either ID alone, or no deduplication
+//!
+//! Entries are replayed oldest first and the first occurrence is kept.
+//! Entries before `--since` are not read, so their repeats inside the range
+//! are kept under every setting.
+
+use anyhow::{bail, Context, Result};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::dedup::CostMode;
+use crate::models::MicroDollars;
+use crate::parser::ProcessedEntry;
+
+/// Which entries a key is compared with
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum DedupScope {
+    /// Every log of every instance
+    #[default]
+    Global,
+    /// The logs of the same Claude instance (host or VM)
+    Instance,
+    /// The same session log
+    Session,
+}
+
+impl DedupScope {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            DedupScope::Global => "global",
+            DedupScope::Instance => "instance",
+            DedupScope::Session => "session",
+        }
+    }
+}
+
+/// What makes two entries the same
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum DedupKeyStrategy {
+    /// `messageId:requestId`, as reports use
+    #[default]
+    MessageRequest,
+    /// `messageId` alone
+    Message,
+    /// `requestId` alone
+    Request,
+    /// Keep every entry
+    None,
+}
+
+impl DedupKeyStrategy {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            DedupKeyStrategy::MessageRequest => "message-request",
+            DedupKeyStrategy::Message => "message",
+            DedupKeyStrategy::Request => "request",
+            DedupKeyStrategy::None => "none",
+        }
+    }
+
+    /// The key of `entry`, or `None` when it has none and is always kept
+    fn key(self, entry: &ProcessedEntry) -> Option<String> {
+        let message_id = entry.entry.message.id.as_str();
+        let request_id = entry.entry.request_id.as_str();
+        match self {
+            DedupKeyStrategy::MessageRequest
+                if !message_id.is_empty() && !request_id.is_empty() =>
+            {
+                Some(format!("{}:{}", message_id, request_id))
+            }
+            DedupKeyStrategy::Message if !message_id.is_empty() => Some(message_id.to_string()),
+            DedupKeyStrategy::Request if !request_id.is_empty() => Some(request_id.to_string()),
+            _ => None,
+        }
+    }
+}
+
+/// One way of deduplicating entries; the default is what reports do
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DedupSettings {
+    /// Hours within which a repeated key is a duplicate; `None` for any
+    /// time apart
+    pub window_hours: Option<u32>,
+    pub scope: DedupScope,
+    pub key: DedupKeyStrategy,
+}
+
+impl fmt::Display for DedupSettings {
+    /// The settings that differ from the default, as accepted by `--settings`
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut parts = Vec::new();
+        if let Some(hours) = self.window_hours {
+            parts.push(format!("window={}", hours));
+        }
+        if self.scope != DedupScope::default() {
+            parts.push(format!("scope={}", self.scope.as_str()));
+        }
+        if self.key != DedupKeyStrategy::default() {
+            parts.push(format!("key={}", self.key.as_str()));
+        }
+        if parts.is_empty() {
+            return f.write_str("current");
+        }
+        f.write_str(&parts.join(","))
+    }
+}
+
+impl FromStr for DedupSettings {
+    type Err = anyhow::Error;
+
+    /// Parse `[window=HOURS|all][,scope=SCOPE][,key=KEY]`
+    fn from_str(s: &str) -> Result<Self> {
+        let mut settings = DedupSettings::default();
+        for part in s.split(',').filter(|part| !part.is_empty()) {
+            let (name, value) = part.split_once('=').with_context(|| {
+                format!(
+                    "Invalid setting '{}' in '{}', expected window, scope or key=value",
+                    part, s
+                )
+            })?;
+            match name {
+                "window" if value == "all" => settings.window_hours = None,
+                "window" => {
+                    settings.window_hours = Some(value.parse().with_context(|| {
+                        format!("Invalid window '{}', expected hours or all", value)
+                    })?)
+                }
+                "scope" => {
+                    settings.scope = match value {
+                        "global" => DedupScope::Global,
+                        "instance" => DedupScope::Instance,
+                        "session" => DedupScope::Session,
+                        _ => bail!(
+                            "Unknown dedup scope '{}', expected global, instance or session",
+                            value
+                        ),
+                    }
+                }
+                "key" => {
+                    settings.key = match value {
+                        "message-request" => DedupKeyStrategy::MessageRequest,
+                        "message" => DedupKeyStrategy::Message,
+                        "request" => DedupKeyStrategy::Request,
+                        "none" => DedupKeyStrategy::None,
+                        _ => bail!(
+                            "Unknown dedup key '{}', expected message-request, message, request or none",
+                            value
+                        ),
+                    }
+                }
+                other => bail!(
+                    "Unknown setting '{}' in '{}', expected window, scope or key",
+                    other,
+                    s
+                ),
+            }
+        }
+        Ok(settings)
+    }
+}
+
+/// Totals of the entries kept under one [`DedupSettings`]
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReprocessTotals {
+    /// The settings as given to `--settings`, or `current`
+    pub label: String,
+    pub settings: DedupSettings,
+    pub entries: u64,
+    /// Entries dropped as duplicates
+    pub duplicates: u64,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub cache_creation_tokens: u64,
+    pub cache_read_tokens: u64,
+    pub total_cost: MicroDollars,
+}
+
+/// Total `entries`, oldest first and duplicates included, deduplicated as
+/// `settings` say
+pub fn reprocess(
+    entries: &[ProcessedEntry],
+    settings: DedupSettings,
+    mode: CostMode,
+) -> ReprocessTotals {
+    let mut totals = ReprocessTotals {
+        label: settings.to_string(),
+        settings,
+        entries: 0,
+        duplicates: 0,
+        input_tokens: 0,
+        output_tokens: 0,
+        cache_creation_tokens: 0,
+        cache_read_tokens: 0,
+        total_cost: MicroDollars::ZERO,
+    };
+    // Last occurrence of each key within its scope
+    let mut seen = HashMap::new();
+
+    for entry in entries {
+        if let Some(key) = settings.key.key(entry) {
+            let scope = entry
+                .provenance
+                .as_ref()
+                .map(|provenance| match settings.scope {
+                    DedupScope::Global => "",
+                    DedupScope::Instance => provenance.instance.as_str(),
+                    DedupScope::Session => provenance.source_file.as_str(),
+                })
+                .unwrap_or_default();
+            let previous = seen.insert((scope, key), entry.timestamp);
+            let duplicate = previous.is_some_and(|previous| {
+                settings.window_hours.map_or(true, |hours| {
+                    entry.timestamp - previous <= chrono::Duration::hours(i64::from(hours))
+                })
+            });
+            if duplicate {
+                totals.duplicates += 1;
+                continue;
+            }
+        }
+
+        let export = entry.to_export_with(mode);
+        totals.entries += 1;
+        totals.input_tokens += u64::from(export.input_tokens);
+        totals.output_tokens += u64::from(export.output_tokens);
+        totals.cache_creation_tokens += u64::from(export.cache_creation_tokens);
+        totals.cache_read_tokens += u64::from(export.cache_read_tokens);
+        totals.total_cost += MicroDollars::from_dollars(export.cost);
+    }
+    totals
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{InstanceType, Provenance, UsageEntry};
+    use crate::parser::FileParser;
+
+    fn entry(timestamp: &str, message_id: &str, request_id: &str, file: &str) -> ProcessedEntry {
+        let line = format!(
+            r#"{{"timestamp":"{}","requestId":"{}","costUSD":1.0,"message":{{"id":"{}","model":"claude-sonnet-4","usage":{{"input_tokens":10,"output_tokens":5,"cache_creation_input_tokens":0,"cache_read_input_tokens":0}}}}}}"#,
+            timestamp, request_id, message_id
+        );
+        let usage: UsageEntry = serde_json::from_str(&line).unwrap();
+        ProcessedEntry::new(usage, &FileParser::new(), 1)
+            .unwrap()
+            .with_provenance(Provenance {
+                source_file: file.to_string(),
+                line_number: 1,
+                instance: "host".to_string(),
+                instance_type: InstanceType::Host,
+                dedup_hash: None,
+            })
+    }
+
+    #[test]
+    fn test_reprocess_compares_window_scope_and_key() {
+        let entries = vec![
+            entry("2025-03-01T10:00:00Z", "m1", "r1", "a.jsonl"),
+            entry("2025-03-01T11:00:00Z", "m1", "r1", "b.jsonl"),
+            entry("2025-03-03T10:00:00Z", "m1", "r1", "a.jsonl"),
+            entry("2025-03-03T10:05:00Z", "m1", "r2", "a.jsonl"),
+        ];
+        let totals = |spec: &str| {
+            let settings: DedupSettings = spec.parse().unwrap();
+            reprocess(&entries, settings, CostMode::Auto)
+        };
+
+        let current = totals("");
+        assert_eq!(current.label, "current");
+        assert_eq!((current.entries, current.duplicates), (2, 2));
+        assert_eq!(current.input_tokens, 20);
+        assert_eq!(current.total_cost, MicroDollars::from_dollars(2.0));
+
+        // The repeat two days later falls outside a 24-hour window
+        assert_eq!(totals("window=24").entries, 3);
+        assert_eq!(totals("scope=session").entries, 3);
+        assert_eq!(totals("key=message").entries, 1);
+        assert_eq!(totals("key=none").duplicates, 0);
+        assert_eq!(
+            totals("window=24,scope=session,key=message").label,
+            "window=24,scope=session,key=message"
+        );
+        assert!("scope=project".parse::<DedupSettings>().is_err());
+        assert!("window=day".parse::<DedupSettings>().is_err());
+    }
+}
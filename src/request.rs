@@ -0,0 +1,232 @@
+//! Typed report requests
+//!
+//! [`ClaudeUsageAnalyzer::query`](crate::analyzer::ClaudeUsageAnalyzer::query)
+//! takes an [`AnalyzerRequest`], which names the report with a [`ReportKind`]
+//! rather than a command string, and returns its rows as a [`Report`]:
+//!
+//! ```rust
+//! use claude_usage::prelude::*;
+//!
+//! # async fn example() -> anyhow::Result<()> {
+//! let request = AnalyzerRequest::new(ReportKind::Monthly)
+//!     .limit(6)
+//!     .exclude_vms(true);
+//!
+//! if let Report::Monthly(months) = ClaudeUsageAnalyzer::new().query(request).await? {
+//!     for month in months {
+//!         println!("{}: ${:.2}", month.month, month.total_cost.to_dollars());
+//!     }
+//! }
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! The request sets the options' [`Command`] from its kind, so the two cannot
+//! disagree. Options without a setter here are reached through
+//! [`AnalyzerRequest::options`].
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+
+use crate::dedup::{
+    CancellationToken, Command, CostMode, GroupBy, ProcessOptions, ProcessOptionsBuilder,
+};
+use crate::models::{DailyData, MonthlyData, SessionBlock, SessionOutput};
+
+/// The report an [`AnalyzerRequest`] asks for
+// The binary only requests daily sessions; the rest is for library callers
+#[allow(dead_code)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ReportKind {
+    #[default]
+    Daily,
+    Monthly,
+    Session,
+    Blocks,
+}
+
+impl ReportKind {
+    pub fn as_str(self) -> &'static str {
+        self.command().as_str()
+    }
+
+    /// The command printing this report
+    pub fn command(self) -> Command {
+        match self {
+            ReportKind::Daily => Command::Daily,
+            ReportKind::Monthly => Command::Monthly,
+            ReportKind::Session => Command::Session,
+            ReportKind::Blocks => Command::Blocks,
+        }
+    }
+}
+
+impl From<ReportKind> for Command {
+    fn from(kind: ReportKind) -> Self {
+        kind.command()
+    }
+}
+
+/// A report and the options to produce it with, checked in
+/// [`build`](Self::build)
+#[derive(Debug, Clone)]
+pub struct AnalyzerRequest {
+    kind: ReportKind,
+    options: ProcessOptionsBuilder,
+}
+
+#[allow(dead_code)]
+impl AnalyzerRequest {
+    /// A request for `kind` with default options
+    pub fn new(kind: ReportKind) -> Self {
+        Self::with_options(kind, ProcessOptions::default())
+    }
+
+    /// A request for `kind` with existing options; their command is replaced
+    /// by the kind's
+    pub fn with_options(kind: ReportKind, options: ProcessOptions) -> Self {
+        Self {
+            kind,
+            options: ProcessOptionsBuilder::from(options).command(kind.command()),
+        }
+    }
+
+    pub fn daily() -> Self {
+        Self::new(ReportKind::Daily)
+    }
+
+    pub fn monthly() -> Self {
+        Self::new(ReportKind::Monthly)
+    }
+
+    pub fn session() -> Self {
+        Self::new(ReportKind::Session)
+    }
+
+    pub fn blocks() -> Self {
+        Self::new(ReportKind::Blocks)
+    }
+
+    pub fn kind(&self) -> ReportKind {
+        self.kind
+    }
+
+    /// Keep the last `limit` days, months or blocks, or the `limit` most
+    /// recently active sessions
+    pub fn limit(self, limit: usize) -> Self {
+        self.options(|options| options.limit(limit))
+    }
+
+    pub fn since(self, since: DateTime<Utc>) -> Self {
+        self.options(|options| options.since(since))
+    }
+
+    pub fn until(self, until: DateTime<Utc>) -> Self {
+        self.options(|options| options.until(until))
+    }
+
+    pub fn exclude_vms(self, exclude_vms: bool) -> Self {
+        self.options(|options| options.exclude_vms(exclude_vms))
+    }
+
+    pub fn project(self, project: impl Into<String>) -> Self {
+        self.options(|options| options.project(project))
+    }
+
+    pub fn group_by(self, group_by: GroupBy) -> Self {
+        self.options(|options| options.group_by(group_by))
+    }
+
+    pub fn cost_mode(self, cost_mode: CostMode) -> Self {
+        self.options(|options| options.cost_mode(cost_mode))
+    }
+
+    pub fn estimate_missing_usage(self, estimate_missing_usage: bool) -> Self {
+        self.options(|options| options.estimate_missing_usage(estimate_missing_usage))
+    }
+
+    pub fn cancellation(self, cancel: CancellationToken) -> Self {
+        self.options(|options| options.cancellation(cancel))
+    }
+
+    /// Set any other option on the underlying [`ProcessOptionsBuilder`]
+    ///
+    /// A command set here is overridden by the request's kind.
+    pub fn options(
+        mut self,
+        configure: impl FnOnce(ProcessOptionsBuilder) -> ProcessOptionsBuilder,
+    ) -> Self {
+        self.options = configure(self.options);
+        self
+    }
+
+    /// Check the options and return them with the report kind
+    ///
+    /// Fails where [`ProcessOptionsBuilder::build`] does.
+    pub fn build(self) -> Result<(ReportKind, ProcessOptions)> {
+        let options = self.options.command(self.kind.command()).build()?;
+        Ok((self.kind, options))
+    }
+}
+
+/// The rows of a report, as produced by
+/// [`ClaudeUsageAnalyzer::query`](crate::analyzer::ClaudeUsageAnalyzer::query)
+#[allow(dead_code)]
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub enum Report {
+    /// Days newest first, each with its projects
+    Daily(Vec<DailyData>),
+    /// Months oldest first
+    Monthly(Vec<MonthlyData>),
+    /// Sessions most recently active first
+    Session(Vec<SessionOutput>),
+    /// 5-hour billing blocks oldest first
+    Blocks(Vec<SessionBlock>),
+}
+
+#[allow(dead_code)]
+impl Report {
+    pub fn kind(&self) -> ReportKind {
+        match self {
+            Report::Daily(_) => ReportKind::Daily,
+            Report::Monthly(_) => ReportKind::Monthly,
+            Report::Session(_) => ReportKind::Session,
+            Report::Blocks(_) => ReportKind::Blocks,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_request_command_follows_kind() {
+        let options = ProcessOptions::builder()
+            .command(Command::Session)
+            .limit(3)
+            .build()
+            .unwrap();
+        let (kind, options) = AnalyzerRequest::with_options(ReportKind::Monthly, options)
+            .options(|options| options.command(Command::Daily))
+            .exclude_vms(true)
+            .build()
+            .unwrap();
+        assert_eq!(kind, ReportKind::Monthly);
+        assert_eq!(options.command, "monthly");
+        assert_eq!(options.limit, Some(3));
+        assert!(options.exclude_vms);
+        assert_eq!(ReportKind::Blocks.as_str(), "blocks");
+
+        let since = Utc.with_ymd_and_hms(2025, 5, 2, 0, 0, 0).unwrap();
+        let until = Utc.with_ymd_and_hms(2025, 5, 1, 0, 0, 0).unwrap();
+        assert!(AnalyzerRequest::daily()
+            .since(since)
+            .until(until)
+            .build()
+            .is_err());
+    }
+}
@@ -0,0 +1,201 @@
+//! Sanitizing corrupted token counts
+//!
+//! Now and then a log line or backup row carries a usage block with negative
+//! or absurdly large token counts, and a single such entry can skew a month
+//! of totals. The `[quality]` rules decide what happens to them: entries with
+//! a negative count are kept with it clamped to zero, and entries with a
+//! count over `quality.max_tokens_per_entry` are skipped, by default. A usage
+//! block without any token count is read as missing usage, as if the entry
+//! had none.
+//!
+//! Sanitized entries are counted in a process-wide [`SanitizedEntries`]
+//! report, available from [`report`] and listed under `metadata.sanitized`
+//! in JSON reports. Like the parse quality counts, it covers the entries read
+//! in this run, so logs taken unchanged from the parse cache are not counted
+//! again; the cache is discarded when the rules change.
+
+use crate::config::{get_config, QualityConfig, SanitizeAction};
+use crate::models::UsageData;
+use serde::Serialize;
+use serde_json::Value;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Global sanitizing counters
+static NEGATIVE_TOKENS: AtomicU64 = AtomicU64::new(0);
+static EXCESSIVE_TOKENS: AtomicU64 = AtomicU64::new(0);
+static EMPTY_USAGE: AtomicU64 = AtomicU64::new(0);
+static SKIPPED: AtomicU64 = AtomicU64::new(0);
+
+/// Token count fields of a usage block
+const TOKEN_FIELDS: [&str; 4] = [
+    "input_tokens",
+    "output_tokens",
+    "cache_creation_input_tokens",
+    "cache_read_input_tokens",
+];
+
+/// Counts of entries the `[quality]` rules changed or left out
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SanitizedEntries {
+    /// Entries with a negative token count
+    #[serde(skip_serializing_if = "is_zero")]
+    pub negative_tokens: u64,
+    /// Entries with a token count over `quality.max_tokens_per_entry`
+    #[serde(skip_serializing_if = "is_zero")]
+    pub excessive_tokens: u64,
+    /// Usage blocks without any token count, read as missing usage
+    #[serde(skip_serializing_if = "is_zero")]
+    pub empty_usage: u64,
+    /// Entries of the above left out of the totals
+    #[serde(skip_serializing_if = "is_zero")]
+    pub skipped: u64,
+}
+
+impl SanitizedEntries {
+    /// Number of entries sanitized in any way
+    pub fn total(&self) -> u64 {
+        self.negative_tokens + self.excessive_tokens + self.empty_usage
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.total() == 0
+    }
+}
+
+/// Snapshot of the sanitizing counters
+pub fn report() -> SanitizedEntries {
+    SanitizedEntries {
+        negative_tokens: NEGATIVE_TOKENS.load(Ordering::Relaxed),
+        excessive_tokens: EXCESSIVE_TOKENS.load(Ordering::Relaxed),
+        empty_usage: EMPTY_USAGE.load(Ordering::Relaxed),
+        skipped: SKIPPED.load(Ordering::Relaxed),
+    }
+}
+
+/// A usage block after the `[quality]` rules
+#[derive(Debug, Clone, PartialEq)]
+pub enum SanitizedUsage {
+    /// Token counts to use, brought into range where a rule clamps them
+    Usage(UsageData),
+    /// No token counts: read the entry as having no usage
+    Empty,
+    /// Leave the entry out
+    Skip,
+}
+
+/// What was wrong with a usage block
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Issue {
+    NegativeTokens,
+    ExcessiveTokens,
+    EmptyUsage,
+}
+
+/// Apply the configured `[quality]` rules to a usage block, counting the
+/// entry when it breaks one
+pub fn sanitize_usage(usage: &Value) -> SanitizedUsage {
+    let (sanitized, issue) = apply_rules(usage, &get_config().quality);
+    let counter = match issue {
+        Some(Issue::NegativeTokens) => &NEGATIVE_TOKENS,
+        Some(Issue::ExcessiveTokens) => &EXCESSIVE_TOKENS,
+        Some(Issue::EmptyUsage) => &EMPTY_USAGE,
+        None => return sanitized,
+    };
+    counter.fetch_add(1, Ordering::Relaxed);
+    if sanitized == SanitizedUsage::Skip {
+        SKIPPED.fetch_add(1, Ordering::Relaxed);
+    }
+    sanitized
+}
+
+fn apply_rules(usage: &Value, rules: &QualityConfig) -> (SanitizedUsage, Option<Issue>) {
+    let counts = TOKEN_FIELDS.map(|field| usage.get(field).and_then(token_count));
+    if counts.iter().all(Option::is_none) {
+        return (SanitizedUsage::Empty, Some(Issue::EmptyUsage));
+    }
+
+    let max = i128::from(rules.max_tokens_per_entry.min(u64::from(u32::MAX)));
+    let (issue, action) = if counts.iter().flatten().any(|count| *count < 0) {
+        (Some(Issue::NegativeTokens), rules.negative_tokens)
+    } else if counts.iter().flatten().any(|count| *count > max) {
+        (Some(Issue::ExcessiveTokens), rules.excessive_tokens)
+    } else {
+        (None, SanitizeAction::Clamp)
+    };
+    if action == SanitizeAction::Skip {
+        return (SanitizedUsage::Skip, issue);
+    }
+
+    let [input, output, cache_creation, cache_read] =
+        counts.map(|count| count.unwrap_or(0).clamp(0, max) as u32);
+    let usage = UsageData {
+        input_tokens: input,
+        output_tokens: output,
+        cache_creation_input_tokens: cache_creation,
+        cache_read_input_tokens: cache_read,
+    };
+    (SanitizedUsage::Usage(usage), issue)
+}
+
+/// A token count as written, including negative and fractional ones
+fn token_count(value: &Value) -> Option<i128> {
+    value
+        .as_i64()
+        .map(i128::from)
+        .or_else(|| value.as_u64().map(i128::from))
+        .or_else(|| value.as_f64().map(|count| count as i128))
+}
+
+fn is_zero(value: &u64) -> bool {
+    *value == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn tokens(sanitized: &SanitizedUsage) -> [u32; 4] {
+        match sanitized {
+            SanitizedUsage::Usage(usage) => [
+                usage.input_tokens,
+                usage.output_tokens,
+                usage.cache_creation_input_tokens,
+                usage.cache_read_input_tokens,
+            ],
+            other => panic!("expected usage, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_rules_clamp_or_skip_corrupted_counts() {
+        let rules = QualityConfig::default();
+
+        let (usage, issue) = apply_rules(&json!({"input_tokens": 10, "output_tokens": 5}), &rules);
+        assert_eq!(tokens(&usage), [10, 5, 0, 0]);
+        assert_eq!(issue, None);
+
+        let negative = json!({"input_tokens": -3, "output_tokens": 5});
+        let (usage, issue) = apply_rules(&negative, &rules);
+        assert_eq!(tokens(&usage), [0, 5, 0, 0]);
+        assert_eq!(issue, Some(Issue::NegativeTokens));
+
+        let excessive = json!({"input_tokens": 1, "cache_read_input_tokens": 5_000_000_000u64});
+        let (usage, issue) = apply_rules(&excessive, &rules);
+        assert_eq!(usage, SanitizedUsage::Skip);
+        assert_eq!(issue, Some(Issue::ExcessiveTokens));
+
+        let (usage, issue) = apply_rules(&json!({"service_tier": "standard"}), &rules);
+        assert_eq!(usage, SanitizedUsage::Empty);
+        assert_eq!(issue, Some(Issue::EmptyUsage));
+
+        let swapped = QualityConfig {
+            negative_tokens: SanitizeAction::Skip,
+            max_tokens_per_entry: 100,
+            excessive_tokens: SanitizeAction::Clamp,
+        };
+        assert_eq!(apply_rules(&negative, &swapped).0, SanitizedUsage::Skip);
+        assert_eq!(tokens(&apply_rules(&excessive, &swapped).0), [1, 0, 0, 100]);
+    }
+}
@@ -0,0 +1,248 @@
+//! Opt-in local self-metrics
+//!
+//! When `metrics.enabled` is set, every report command appends one JSON line
+//! to the metrics file (`metrics.file`) recording how long it took, how much
+//! data it processed and which features were used. Nothing is ever uploaded;
+//! the file exists so users can attach real performance data when filing
+//! issues. `claude-usage metrics show` summarizes it.
+//!
+//! Recording is best-effort: a metrics file that cannot be written is logged
+//! and never fails the command being measured.
+
+use crate::config::get_config;
+use crate::line_reader;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+use std::time::Instant;
+use tracing::{debug, warn};
+
+/// One recorded command run
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CommandMetric {
+    pub timestamp: String,
+    pub command: String,
+    #[serde(rename = "durationMs")]
+    pub duration_ms: u64,
+    pub success: bool,
+    #[serde(default)]
+    pub sessions: usize,
+    #[serde(rename = "linesRead", default)]
+    pub lines_read: u64,
+    #[serde(default)]
+    pub features: Vec<String>,
+    #[serde(default)]
+    pub version: String,
+}
+
+/// Aggregated statistics for one command
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CommandSummary {
+    pub command: String,
+    pub runs: usize,
+    pub failures: usize,
+    #[serde(rename = "avgDurationMs")]
+    pub avg_duration_ms: u64,
+    #[serde(rename = "medianDurationMs")]
+    pub median_duration_ms: u64,
+    #[serde(rename = "maxDurationMs")]
+    pub max_duration_ms: u64,
+    #[serde(rename = "avgSessions")]
+    pub avg_sessions: usize,
+}
+
+/// Measures a single command run
+pub struct MetricsRecorder {
+    command: String,
+    started: Instant,
+    features: Vec<String>,
+    sessions: usize,
+}
+
+impl MetricsRecorder {
+    /// Start timing `command`
+    pub fn start(command: &str) -> Self {
+        Self {
+            command: command.to_string(),
+            started: Instant::now(),
+            features: Vec::new(),
+            sessions: 0,
+        }
+    }
+
+    /// Note that a feature (flag or option) was used in this run
+    pub fn feature(&mut self, name: &str, used: bool) {
+        if used {
+            self.features.push(name.to_string());
+        }
+    }
+
+    /// Number of sessions the command processed
+    pub fn set_sessions(&mut self, sessions: usize) {
+        self.sessions = sessions;
+    }
+
+    /// Stop timing and append the run to the metrics file, if enabled
+    ///
+    /// Returns the run whether or not it was recorded.
+    pub fn finish(self, success: bool) -> CommandMetric {
+        let metric = self.into_metric(success);
+        let config = &get_config().metrics;
+        if !config.enabled {
+            return metric;
+        }
+
+        match append_metric(&config.file, &metric) {
+            Ok(()) => debug!(file = %config.file.display(), "Recorded command metrics"),
+            Err(e) => warn!(error = %e, "Failed to record command metrics"),
+        }
+        metric
+    }
+
+    fn into_metric(self, success: bool) -> CommandMetric {
+        CommandMetric {
+            timestamp: chrono::Utc::now().to_rfc3339(),
+            command: self.command,
+            duration_ms: self.started.elapsed().as_millis() as u64,
+            success,
+            sessions: self.sessions,
+            lines_read: line_reader::quality_report().lines_read,
+            features: self.features,
+            version: env!("CARGO_PKG_VERSION").to_string(),
+        }
+    }
+}
+
+/// Append one metric as a JSON line, creating the file if needed
+pub fn append_metric(path: &Path, metric: &CommandMetric) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create {}", parent.display()))?;
+    }
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .with_context(|| format!("Failed to open metrics file: {}", path.display()))?;
+    writeln!(file, "{}", serde_json::to_string(metric)?)?;
+    Ok(())
+}
+
+/// Load every recorded metric, skipping lines that cannot be parsed
+///
+/// A missing file yields no metrics.
+pub fn load_metrics(path: &Path) -> Result<Vec<CommandMetric>> {
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let content = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read metrics file: {}", path.display()))?;
+
+    Ok(content
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect())
+}
+
+/// Summarize runs per command, sorted by command name
+pub fn summarize(metrics: &[CommandMetric]) -> Vec<CommandSummary> {
+    let mut by_command: BTreeMap<&str, Vec<&CommandMetric>> = BTreeMap::new();
+    for metric in metrics {
+        by_command.entry(&metric.command).or_default().push(metric);
+    }
+
+    by_command
+        .into_iter()
+        .map(|(command, runs)| {
+            let mut durations: Vec<u64> = runs.iter().map(|m| m.duration_ms).collect();
+            durations.sort_unstable();
+            let count = runs.len();
+
+            CommandSummary {
+                command: command.to_string(),
+                runs: count,
+                failures: runs.iter().filter(|m| !m.success).count(),
+                avg_duration_ms: durations.iter().sum::<u64>() / count as u64,
+                median_duration_ms: durations[count / 2],
+                max_duration_ms: durations[count - 1],
+                avg_sessions: runs.iter().map(|m| m.sessions).sum::<usize>() / count,
+            }
+        })
+        .collect()
+}
+
+/// Count how many runs used each feature
+pub fn feature_usage(metrics: &[CommandMetric]) -> BTreeMap<String, usize> {
+    let mut usage = BTreeMap::new();
+    for feature in metrics.iter().flat_map(|m| &m.features) {
+        *usage.entry(feature.clone()).or_default() += 1;
+    }
+    usage
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn metric(command: &str, duration_ms: u64, success: bool, features: &[&str]) -> CommandMetric {
+        CommandMetric {
+            timestamp: "2025-01-15T10:00:00+00:00".to_string(),
+            command: command.to_string(),
+            duration_ms,
+            success,
+            sessions: 10,
+            lines_read: 0,
+            features: features.iter().map(|f| f.to_string()).collect(),
+            version: "1.0.0".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_append_and_load_round_trip() {
+        let temp = TempDir::new().unwrap();
+        let path = temp.path().join("nested").join("metrics.jsonl");
+
+        assert!(load_metrics(&path).unwrap().is_empty());
+
+        let first = metric("daily", 120, true, &["json"]);
+        let second = metric("monthly", 80, false, &[]);
+        append_metric(&path, &first).unwrap();
+        append_metric(&path, &second).unwrap();
+        fs::OpenOptions::new()
+            .append(true)
+            .open(&path)
+            .unwrap()
+            .write_all(b"not json\n")
+            .unwrap();
+
+        assert_eq!(load_metrics(&path).unwrap(), vec![first, second]);
+    }
+
+    #[test]
+    fn test_summarize_per_command() {
+        let metrics = vec![
+            metric("daily", 100, true, &["json"]),
+            metric("daily", 300, false, &["json", "since"]),
+            metric("daily", 200, true, &[]),
+            metric("monthly", 50, true, &[]),
+        ];
+
+        let summary = summarize(&metrics);
+        assert_eq!(summary.len(), 2);
+        assert_eq!(summary[0].command, "daily");
+        assert_eq!(summary[0].runs, 3);
+        assert_eq!(summary[0].failures, 1);
+        assert_eq!(summary[0].avg_duration_ms, 200);
+        assert_eq!(summary[0].median_duration_ms, 200);
+        assert_eq!(summary[0].max_duration_ms, 300);
+
+        let usage = feature_usage(&metrics);
+        assert_eq!(usage.get("json"), Some(&2));
+        assert_eq!(usage.get("since"), Some(&1));
+    }
+}
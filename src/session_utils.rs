@@ -1,6 +1,8 @@
+use crate::io_throttle;
 use crate::keeper_integration::KeeperIntegration;
 use crate::models::*;
 use anyhow::Result;
+use std::collections::HashMap;
 use std::path::Path;
 
 /// Handles session-related utilities including session ID extraction and session blocks parsing
@@ -35,6 +37,27 @@ impl SessionUtils {
         Some(format!("{}:{}", message_id, request_id))
     }
 
+    /// Derive a readable, stable identifier for a session
+    ///
+    /// Session logs are usually named by UUID, so reports show
+    /// `<project>-<start date>-<short hash>` instead, e.g.
+    /// `home-me-api-2025-01-15-3f9a2c`. The hash is taken from the raw session
+    /// ID, so the identifier stays the same across runs and machines.
+    pub fn display_id(session_id: &str, project_path: &str, start_date: Option<&str>) -> String {
+        let project = project_path
+            .rsplit('/')
+            .find(|part| !part.is_empty())
+            .unwrap_or_default()
+            .trim_start_matches('-');
+        let project = if project.is_empty() { "session" } else { project };
+        let hash = format!("{:06x}", fnv1a_64(session_id.as_bytes()) & 0xff_ffff);
+
+        match start_date {
+            Some(date) => format!("{}-{}-{}", project, date, hash),
+            None => format!("{}-{}", project, hash),
+        }
+    }
+
     /// Parse a session blocks file and return the session blocks
     /// Uses claude-keeper subprocess to read and parse the file
     #[allow(dead_code)]
@@ -43,9 +66,14 @@ impl SessionUtils {
         keeper: &KeeperIntegration,
     ) -> Result<Vec<SessionBlock>> {
         // Read the file directly instead of using subprocess
-        use std::fs;
-        
-        match fs::read_to_string(file_path) {
+        use std::io::Read;
+
+        let mut content = String::new();
+        let read = io_throttle::global()
+            .open(file_path)
+            .and_then(|mut file| file.read_to_string(&mut content));
+
+        match read.map(|_| content) {
             Ok(content) => {
                 // Parse the content using keeper's session blocks parser
                 keeper.parse_session_blocks(&content)
@@ -56,6 +84,38 @@ impl SessionUtils {
             }
         }
     }
+
+    /// Merge the blocks read from several session blocks files, oldest first
+    ///
+    /// Blocks with the same start and end time are one block seen by several
+    /// instances or in several snapshots of one file; the copy with the most
+    /// entries is kept, the earliest listed one on a tie.
+    pub fn merge_session_blocks(
+        blocks: impl IntoIterator<Item = SessionBlock>,
+    ) -> Vec<SessionBlock> {
+        let mut merged: Vec<SessionBlock> = Vec::new();
+        let mut index: HashMap<(String, String), usize> = HashMap::new();
+        for block in blocks {
+            let key = (block.start_time.clone(), block.end_time.clone());
+            match index.get(&key) {
+                Some(&i) if merged[i].entries < block.entries => merged[i] = block,
+                Some(_) => {}
+                None => {
+                    index.insert(key, merged.len());
+                    merged.push(block);
+                }
+            }
+        }
+        merged.sort_by(|a, b| a.start_time.cmp(&b.start_time));
+        merged
+    }
+}
+
+/// 64-bit FNV-1a, used where a hash must not change between Rust releases
+fn fnv1a_64(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(0x0000_0100_0000_01b3)
+    })
 }
 
 #[cfg(test)]
@@ -83,6 +143,7 @@ mod tests {
                 id: "msg123".to_string(),
                 usage: None,
                 model: "claude-3".to_string(),
+                secondary_usage: None,
             },
             request_id: "req456".to_string(),
             timestamp: "2024-01-01T12:00:00Z".to_string(),
@@ -100,6 +161,7 @@ mod tests {
                 id: "".to_string(),
                 usage: None,
                 model: "claude-3".to_string(),
+                secondary_usage: None,
             },
             request_id: "req456".to_string(),
             timestamp: "2024-01-01T12:00:00Z".to_string(),
@@ -109,4 +171,67 @@ mod tests {
         let hash = SessionUtils::create_unique_hash(&entry);
         assert_eq!(hash, None);
     }
+
+    #[test]
+    fn test_display_id_is_readable_and_stable() {
+        let id = "0b7e6f2a-1c3d-4e5f-8a9b-123456789abc";
+        let display_id =
+            SessionUtils::display_id(id, "-home-me-src-api", Some("2025-01-15"));
+
+        assert!(display_id.starts_with("home-me-src-api-2025-01-15-"));
+        assert_eq!(display_id.len(), "home-me-src-api-2025-01-15-".len() + 6);
+        assert_eq!(
+            display_id,
+            SessionUtils::display_id(id, "-home-me-src-api", Some("2025-01-15"))
+        );
+        assert_ne!(
+            display_id,
+            SessionUtils::display_id("other", "-home-me-src-api", Some("2025-01-15"))
+        );
+        assert_eq!(fnv1a_64(b"a"), 0xaf63_dc4c_8601_ec8c);
+    }
+
+    #[test]
+    fn test_display_id_fallbacks() {
+        assert!(SessionUtils::display_id("abc", "", None).starts_with("session-"));
+        assert!(SessionUtils::display_id("abc", "/work/site/", None).starts_with("site-"));
+    }
+
+    #[test]
+    fn test_merge_session_blocks_across_instances() {
+        let block = |start: &str, end: &str, entries: u32| SessionBlock {
+            start_time: start.to_string(),
+            end_time: end.to_string(),
+            actual_end_time: None,
+            is_active: false,
+            entries,
+            models: Vec::new(),
+            token_counts: TokenCounts::default(),
+            cost_usd: f64::from(entries),
+        };
+        let main = vec![
+            block("2025-03-01T10:00:00Z", "2025-03-01T15:00:00Z", 4),
+            block("2025-03-01T16:00:00Z", "2025-03-01T21:00:00Z", 2),
+        ];
+        let vm = vec![
+            block("2025-03-01T06:00:00Z", "2025-03-01T11:00:00Z", 1),
+            // A later snapshot of the same block
+            block("2025-03-01T16:00:00Z", "2025-03-01T21:00:00Z", 5),
+            block("2025-03-01T10:00:00Z", "2025-03-01T15:00:00Z", 4),
+        ];
+
+        let merged = SessionUtils::merge_session_blocks(main.into_iter().chain(vm));
+        let starts_and_entries: Vec<(&str, u32)> = merged
+            .iter()
+            .map(|block| (block.start_time.as_str(), block.entries))
+            .collect();
+        assert_eq!(
+            starts_and_entries,
+            [
+                ("2025-03-01T06:00:00Z", 1),
+                ("2025-03-01T10:00:00Z", 4),
+                ("2025-03-01T16:00:00Z", 5),
+            ]
+        );
+    }
 }
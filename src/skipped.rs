@@ -0,0 +1,197 @@
+//! What the report filters left out
+//!
+//! `--since`/`--until`, `--exclude-vms` and `--project` drop data at
+//! different stages: whole logs before they are read, and entries or sessions
+//! after. [`SkippedData`] tallies what each filter removed, so reports can
+//! list it under `metadata.skipped` in JSON and below the report with
+//! `--verbose`, and users can confirm a filter did what they intended.
+//!
+//! Logs skipped unread are counted with their size on disk; entries and
+//! sessions are only counted where they were read before being dropped.
+//! Paths that reach a log already found (through a symlink, or in another
+//! letter case) are counted too, as files without a size since their data is
+//! read once under the first path.
+
+use serde::Serialize;
+use std::fs;
+use std::path::Path;
+
+/// Filter that left data out
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SkipReason {
+    /// `--since` / `--until`
+    DateFilter,
+    /// `--exclude-vms`
+    VmExclusion,
+    /// `--project`
+    ProjectExclusion,
+    /// Another path to a log already found
+    DuplicatePath,
+}
+
+impl SkipReason {
+    pub fn label(self) -> &'static str {
+        match self {
+            SkipReason::DateFilter => "date filter",
+            SkipReason::VmExclusion => "VM exclusion",
+            SkipReason::ProjectExclusion => "project filter",
+            SkipReason::DuplicatePath => "duplicate path",
+        }
+    }
+}
+
+/// Logs, sessions and entries one filter left out
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SkippedVolume {
+    #[serde(skip_serializing_if = "is_zero")]
+    pub files: u64,
+    /// Size on disk of the skipped files
+    #[serde(skip_serializing_if = "is_zero")]
+    pub bytes: u64,
+    #[serde(skip_serializing_if = "is_zero")]
+    pub sessions: u64,
+    #[serde(skip_serializing_if = "is_zero")]
+    pub entries: u64,
+}
+
+impl SkippedVolume {
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Such as `12 files (3.4 MB), 40 entries`
+    pub fn describe(&self) -> String {
+        let mut parts = Vec::new();
+        if self.files > 0 && self.bytes == 0 {
+            parts.push(count(self.files, "file", "files"));
+        } else if self.files > 0 {
+            parts.push(format!(
+                "{} ({:.1} MB)",
+                count(self.files, "file", "files"),
+                self.bytes as f64 / (1024.0 * 1024.0)
+            ));
+        }
+        if self.sessions > 0 {
+            parts.push(count(self.sessions, "session", "sessions"));
+        }
+        if self.entries > 0 {
+            parts.push(count(self.entries, "entry", "entries"));
+        }
+        parts.join(", ")
+    }
+}
+
+/// What each filter left out of one report
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SkippedData {
+    #[serde(skip_serializing_if = "SkippedVolume::is_empty")]
+    pub date_filter: SkippedVolume,
+    #[serde(skip_serializing_if = "SkippedVolume::is_empty")]
+    pub vm_exclusion: SkippedVolume,
+    #[serde(skip_serializing_if = "SkippedVolume::is_empty")]
+    pub project_exclusion: SkippedVolume,
+    #[serde(skip_serializing_if = "SkippedVolume::is_empty")]
+    pub duplicate_path: SkippedVolume,
+}
+
+impl SkippedData {
+    pub fn is_empty(&self) -> bool {
+        self.reasons().next().is_none()
+    }
+
+    fn volume_mut(&mut self, reason: SkipReason) -> &mut SkippedVolume {
+        match reason {
+            SkipReason::DateFilter => &mut self.date_filter,
+            SkipReason::VmExclusion => &mut self.vm_exclusion,
+            SkipReason::ProjectExclusion => &mut self.project_exclusion,
+            SkipReason::DuplicatePath => &mut self.duplicate_path,
+        }
+    }
+
+    /// Count the log at `path`, skipped without reading it
+    pub fn add_file(&mut self, reason: SkipReason, path: &Path) {
+        let volume = self.volume_mut(reason);
+        volume.files += 1;
+        volume.bytes += fs::metadata(path).map_or(0, |meta| meta.len());
+    }
+
+    /// Count `files` logs without their size
+    pub fn add_files(&mut self, reason: SkipReason, files: u64) {
+        self.volume_mut(reason).files += files;
+    }
+
+    pub fn add_sessions(&mut self, reason: SkipReason, sessions: u64) {
+        self.volume_mut(reason).sessions += sessions;
+    }
+
+    pub fn add_entries(&mut self, reason: SkipReason, entries: u64) {
+        self.volume_mut(reason).entries += entries;
+    }
+
+    /// Filters that left anything out, with what they left out
+    pub fn reasons(&self) -> impl Iterator<Item = (SkipReason, &SkippedVolume)> {
+        [
+            (SkipReason::DateFilter, &self.date_filter),
+            (SkipReason::VmExclusion, &self.vm_exclusion),
+            (SkipReason::ProjectExclusion, &self.project_exclusion),
+            (SkipReason::DuplicatePath, &self.duplicate_path),
+        ]
+        .into_iter()
+        .filter(|(_, volume)| !volume.is_empty())
+    }
+}
+
+fn count(n: u64, one: &str, many: &str) -> String {
+    format!("{} {}", n, if n == 1 { one } else { many })
+}
+
+fn is_zero(value: &u64) -> bool {
+    *value == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_skipped_volumes_by_reason() {
+        let temp = TempDir::new().unwrap();
+        let log = temp.path().join("session.jsonl");
+        fs::write(&log, vec![b'x'; 2048]).unwrap();
+
+        let mut skipped = SkippedData::default();
+        assert!(skipped.is_empty());
+        assert_eq!(
+            serde_json::to_value(&skipped).unwrap(),
+            serde_json::json!({})
+        );
+
+        skipped.add_file(SkipReason::DateFilter, &log);
+        skipped.add_entries(SkipReason::DateFilter, 3);
+        skipped.add_sessions(SkipReason::ProjectExclusion, 2);
+        skipped.add_files(SkipReason::DuplicatePath, 2);
+
+        let reasons: Vec<SkipReason> = skipped.reasons().map(|(reason, _)| reason).collect();
+        assert_eq!(
+            reasons,
+            [
+                SkipReason::DateFilter,
+                SkipReason::ProjectExclusion,
+                SkipReason::DuplicatePath
+            ]
+        );
+        assert_eq!(skipped.date_filter.describe(), "1 file (0.0 MB), 3 entries");
+        assert_eq!(skipped.duplicate_path.describe(), "2 files");
+        assert_eq!(
+            serde_json::to_value(&skipped).unwrap(),
+            serde_json::json!({
+                "dateFilter": {"files": 1, "bytes": 2048, "entries": 3},
+                "projectExclusion": {"sessions": 2},
+                "duplicatePath": {"files": 2},
+            })
+        );
+    }
+}
@@ -0,0 +1,262 @@
+//! Compact usage totals for status bars
+//!
+//! `claude-usage status` prints today's and this month's cost and tokens in
+//! one line, or as compact JSON with `--json`. With `--listen <port>` (and
+//! the `web` feature) it keeps running and serves the same data over HTTP
+//! instead, so widgets such as Raycast, BetterTouchTool or Waybar scripts
+//! can poll it without spawning the CLI each time:
+//!
+//! - `GET /today` - today's totals
+//! - `GET /month` - this month's totals, with the budget when one is set
+//! - `GET /status` - both, plus active sessions and refresh state
+//!
+//! Totals are read from the JSONL logs rather than the backups, so they
+//! include usage from minutes ago. "Today" and "this month" are local time,
+//! matching `daily` and `monthly`.
+//!
+//! `claude-usage check --max-daily/--max-monthly` compares the same totals
+//! with spending limits and fails with [`SpendLimitExceeded`] when one is
+//! exceeded, for cron jobs and CI.
+
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use serde::Serialize;
+use std::fmt;
+
+use crate::models::{MicroDollars, SessionOutput};
+use crate::timestamp_parser::TimestampParser;
+
+/// How recently a session must have been active to count as active
+pub const ACTIVE_WINDOW_MINUTES: i64 = 60;
+
+/// Cost and tokens of a day or month
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PeriodTotals {
+    /// Day (YYYY-MM-DD) or month (YYYY-MM)
+    pub period: String,
+    pub cost: MicroDollars,
+    /// Input, output and cache tokens together
+    pub tokens: u64,
+    /// Sessions with usage in the period
+    pub sessions: u32,
+}
+
+/// How this month's cost compares with `[budget] monthly_usd`
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BudgetStatus {
+    pub monthly_budget: MicroDollars,
+    /// This month's cost as a percentage of the budget
+    pub percent_used: f64,
+}
+
+/// Everything a status bar shows
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Status {
+    pub today: PeriodTotals,
+    pub month: PeriodTotals,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub budget: Option<BudgetStatus>,
+    /// Sessions active within the last [`ACTIVE_WINDOW_MINUTES`]
+    pub active_sessions: usize,
+    /// When the totals were computed (RFC 3339)
+    pub updated_at: String,
+}
+
+/// Totals of `today` and its month; `monthly_budget` is in dollars
+pub fn status(
+    sessions: &[SessionOutput],
+    today: NaiveDate,
+    now: DateTime<Utc>,
+    monthly_budget: Option<f64>,
+) -> Status {
+    let day = today.format("%Y-%m-%d").to_string();
+    let month = today.format("%Y-%m").to_string();
+    let mut today_totals = PeriodTotals {
+        period: day.clone(),
+        ..PeriodTotals::default()
+    };
+    let mut month_totals = PeriodTotals {
+        period: month.clone(),
+        ..PeriodTotals::default()
+    };
+
+    for session in sessions {
+        let mut in_month = false;
+        for (date, usage) in &session.daily_usage {
+            if !date.starts_with(&month) {
+                continue;
+            }
+            let tokens = u64::from(usage.input_tokens)
+                + u64::from(usage.output_tokens)
+                + u64::from(usage.cache_creation_tokens)
+                + u64::from(usage.cache_read_tokens);
+            month_totals.cost += usage.cost;
+            month_totals.tokens += tokens;
+            in_month = true;
+            if *date == day {
+                today_totals.cost += usage.cost;
+                today_totals.tokens += tokens;
+                today_totals.sessions += 1;
+            }
+        }
+        if in_month {
+            month_totals.sessions += 1;
+        }
+    }
+
+    let window = Duration::minutes(ACTIVE_WINDOW_MINUTES);
+    let active_sessions = sessions
+        .iter()
+        .filter(|session| {
+            TimestampParser::parse(&session.last_activity).is_ok_and(|last| now - last <= window)
+        })
+        .count();
+
+    let budget = monthly_budget.map(|budget| BudgetStatus {
+        monthly_budget: MicroDollars::from_dollars(budget),
+        percent_used: month_totals.cost.to_dollars() / budget * 100.0,
+    });
+
+    Status {
+        today: today_totals,
+        month: month_totals,
+        budget,
+        active_sessions,
+        updated_at: now.to_rfc3339(),
+    }
+}
+
+/// A period's cost over its limit
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LimitBreach {
+    /// `daily` or `monthly`
+    pub limit_kind: &'static str,
+    /// Day (YYYY-MM-DD) or month (YYYY-MM)
+    pub period: String,
+    pub spent: MicroDollars,
+    pub limit: MicroDollars,
+}
+
+/// Today's or this month's cost went over the limit `check` was given
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpendLimitExceeded {
+    pub breaches: Vec<LimitBreach>,
+}
+
+impl fmt::Display for SpendLimitExceeded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let breaches: Vec<String> = self
+            .breaches
+            .iter()
+            .map(|breach| {
+                format!(
+                    "{} spend for {} is ${:.2}, over the ${:.2} limit",
+                    breach.limit_kind,
+                    breach.period,
+                    breach.spent.to_dollars(),
+                    breach.limit.to_dollars()
+                )
+            })
+            .collect();
+        write!(f, "Spend limit exceeded: {}", breaches.join("; "))
+    }
+}
+
+impl std::error::Error for SpendLimitExceeded {}
+
+/// Check today's and this month's cost against the limits, in dollars
+pub fn check_limits(
+    status: &Status,
+    max_daily: Option<f64>,
+    max_monthly: Option<f64>,
+) -> Result<(), SpendLimitExceeded> {
+    let breaches: Vec<LimitBreach> = [
+        ("daily", &status.today, max_daily),
+        ("monthly", &status.month, max_monthly),
+    ]
+    .into_iter()
+    .filter_map(|(limit_kind, totals, limit)| {
+        let limit = MicroDollars::from_dollars(limit?);
+        (totals.cost > limit).then(|| LimitBreach {
+            limit_kind,
+            period: totals.period.clone(),
+            spent: totals.cost,
+            limit,
+        })
+    })
+    .collect();
+    if breaches.is_empty() {
+        Ok(())
+    } else {
+        Err(SpendLimitExceeded { breaches })
+    }
+}
+
+/// One-line summary, e.g. `Today $1.20 · Month $35.40 (35% of $100)`
+pub fn summary_line(status: &Status) -> String {
+    let mut line = format!(
+        "Today ${:.2} · Month ${:.2}",
+        status.today.cost.to_dollars(),
+        status.month.cost.to_dollars()
+    );
+    if let Some(budget) = &status.budget {
+        line.push_str(&format!(
+            " ({:.0}% of ${:.0})",
+            budget.percent_used,
+            budget.monthly_budget.to_dollars()
+        ));
+    }
+    line
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::test_support::session;
+
+    #[test]
+    fn test_status_totals_today_and_month() {
+        let today = NaiveDate::from_ymd_opt(2025, 4, 10).unwrap();
+        let now = "2025-04-10T12:00:00Z".parse().unwrap();
+        let sessions = vec![
+            session("s1")
+                .last_activity("2025-04-10T11:30:00Z")
+                .days(&[("2025-03-31", 9.0), ("2025-04-10", 1.5)])
+                .build(),
+            session("s1")
+                .last_activity("2025-04-02T08:00:00Z")
+                .days(&[("2025-04-02", 3.0)])
+                .build(),
+        ];
+
+        let result = status(&sessions, today, now, Some(50.0));
+        assert_eq!(result.today.period, "2025-04-10");
+        assert_eq!(result.today.cost, MicroDollars::from_dollars(1.5));
+        assert_eq!(result.today.tokens, 100);
+        assert_eq!(result.today.sessions, 1);
+        assert_eq!(result.month.cost, MicroDollars::from_dollars(4.5));
+        assert_eq!(result.month.sessions, 2);
+        assert_eq!(result.active_sessions, 1);
+        assert_eq!(
+            summary_line(&result),
+            "Today $1.50 · Month $4.50 (9% of $50)"
+        );
+
+        let json = serde_json::to_value(status(&sessions, today, now, None)).unwrap();
+        assert_eq!(json["month"]["cost"], 4.5);
+        assert!(json.get("budget").is_none());
+
+        assert!(check_limits(&result, Some(1.5), Some(10.0)).is_ok());
+        let exceeded = check_limits(&result, Some(1.0), Some(4.0)).unwrap_err();
+        assert_eq!(exceeded.breaches.len(), 2);
+        assert_eq!(exceeded.breaches[1].period, "2025-04");
+        assert_eq!(
+            exceeded.to_string(),
+            "Spend limit exceeded: daily spend for 2025-04-10 is $1.50, over the $1.00 limit; \
+             monthly spend for 2025-04 is $4.50, over the $4.00 limit"
+        );
+    }
+}
@@ -1,5 +1,24 @@
+//! Timestamp parsing with a per-thread cache
+//!
+//! Entries of one conversation are written within the same seconds, so a log
+//! repeats the same timestamp strings many times in a row. Each thread keeps
+//! the last few strings it parsed, most recently used first, and returns
+//! their cached result instead of parsing them again. Failed parses are not
+//! cached. `cargo bench --bench parser_benchmark timestamp_parser` compares
+//! cached and uncached parsing.
+
 use anyhow::Result;
 use chrono::{DateTime, NaiveDateTime, Utc};
+use std::cell::RefCell;
+
+/// Timestamp strings remembered by each thread
+const CACHE_SIZE: usize = 16;
+
+thread_local! {
+    /// Recently parsed strings and their result, most recently used first
+    static CACHE: RefCell<Vec<(Box<str>, DateTime<Utc>)>> =
+        RefCell::new(Vec::with_capacity(CACHE_SIZE));
+}
 
 /// Handles parsing timestamps from various formats used in Claude usage data
 pub struct TimestampParser;
@@ -8,6 +27,30 @@ impl TimestampParser {
     /// Parse a timestamp string into a DateTime<Utc>
     /// Handles both Z suffix and timezone info formats
     pub fn parse(timestamp_str: &str) -> Result<DateTime<Utc>> {
+        let cached = CACHE.with(|cache| {
+            let mut cache = cache.borrow_mut();
+            let position = cache.iter().position(|(key, _)| **key == *timestamp_str)?;
+            // Move the hit to the front so the least recently used is evicted
+            cache[..=position].rotate_right(1);
+            Some(cache[0].1)
+        });
+        if let Some(parsed) = cached {
+            return Ok(parsed);
+        }
+
+        let parsed = Self::parse_uncached(timestamp_str)?;
+        CACHE.with(|cache| {
+            let mut cache = cache.borrow_mut();
+            if cache.len() == CACHE_SIZE {
+                cache.pop();
+            }
+            cache.insert(0, (timestamp_str.into(), parsed));
+        });
+        Ok(parsed)
+    }
+
+    /// [`parse`](Self::parse) without the cache
+    pub fn parse_uncached(timestamp_str: &str) -> Result<DateTime<Utc>> {
         // Handle both Z suffix and timezone info
         let timestamp = if timestamp_str.ends_with('Z') {
             timestamp_str.replace('Z', "+00:00")
@@ -56,4 +99,21 @@ mod tests {
         let result = TimestampParser::parse("invalid");
         assert!(result.is_err());
     }
+
+    #[test]
+    fn test_cache_returns_same_result_after_eviction() {
+        let first = "2024-01-01T12:00:00.000Z";
+        let expected = TimestampParser::parse_uncached(first).unwrap();
+        assert_eq!(TimestampParser::parse(first).unwrap(), expected);
+        assert_eq!(TimestampParser::parse(first).unwrap(), expected);
+
+        // Push the first string out of the cache, then parse it again
+        for second in 0..CACHE_SIZE * 2 {
+            let other = format!("2024-01-01T12:00:{:02}.500Z", second % 60);
+            TimestampParser::parse(&other).unwrap();
+        }
+        CACHE.with(|cache| assert_eq!(cache.borrow().len(), CACHE_SIZE));
+        assert_eq!(TimestampParser::parse(first).unwrap(), expected);
+        assert!(TimestampParser::parse("invalid").is_err());
+    }
 }
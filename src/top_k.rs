@@ -0,0 +1,203 @@
+//! Bounded-memory heaviest projects
+//!
+//! `daily --approx-top N` and `monthly --approx-top N` report the N most
+//! expensive projects without building a map of every project. Session costs
+//! are streamed into a weighted Space-Saving summary (Metwally et al., 2005)
+//! holding [`COUNTERS_PER_ROW`]` × N` counters; when a new project arrives and
+//! every counter is taken, the smallest counter is handed over to it and its
+//! count becomes the new project's error.
+//!
+//! Error bounds, with `W` the total cost streamed and `m` the counter count:
+//!
+//! - every reported cost overestimates the true cost by at most its `error`,
+//!   and every `error` is at most `W / m`;
+//! - every project whose true cost exceeds `W / m` is reported.
+
+use crate::models::{MicroDollars, SessionOutput};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// Counters kept for each requested row
+pub const COUNTERS_PER_ROW: usize = 10;
+
+/// Weighted Space-Saving summary of the heaviest keys in a stream
+#[derive(Debug, Clone)]
+pub struct SpaceSaving {
+    capacity: usize,
+    counters: HashMap<String, Counter>,
+    total: i64,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Counter {
+    count: i64,
+    error: i64,
+}
+
+impl SpaceSaving {
+    /// Summary holding at most `capacity` counters (at least one)
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            counters: HashMap::new(),
+            total: 0,
+        }
+    }
+
+    /// Add `weight` to `key`
+    pub fn add(&mut self, key: &str, weight: i64) {
+        self.total += weight;
+        if let Some(counter) = self.counters.get_mut(key) {
+            counter.count += weight;
+            return;
+        }
+        if self.counters.len() < self.capacity {
+            self.counters.insert(
+                key.to_string(),
+                Counter {
+                    count: weight,
+                    error: 0,
+                },
+            );
+            return;
+        }
+
+        // Ties go to the greatest key so the result does not depend on hashing
+        let (evicted, min) = self
+            .counters
+            .iter()
+            .min_by(|(a_key, a), (b_key, b)| a.count.cmp(&b.count).then(b_key.cmp(a_key)))
+            .map(|(key, counter)| (key.clone(), *counter))
+            .expect("capacity is at least one");
+        self.counters.remove(&evicted);
+        self.counters.insert(
+            key.to_string(),
+            Counter {
+                count: min.count + weight,
+                error: min.count,
+            },
+        );
+    }
+
+    /// Total weight added
+    pub fn total(&self) -> i64 {
+        self.total
+    }
+
+    /// Largest possible overestimate of any count: `total / capacity`
+    pub fn error_bound(&self) -> i64 {
+        self.total / self.capacity as i64
+    }
+
+    /// The `n` largest estimates as `(key, count, error)`, largest first
+    pub fn top(&self, n: usize) -> Vec<(String, i64, i64)> {
+        let mut rows: Vec<(String, i64, i64)> = self
+            .counters
+            .iter()
+            .map(|(key, counter)| (key.clone(), counter.count, counter.error))
+            .collect();
+        rows.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        rows.truncate(n);
+        rows
+    }
+}
+
+/// One project of an approximate top-N report
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ApproxProject {
+    pub project: String,
+    /// Estimated cost, never below the true cost
+    pub cost: MicroDollars,
+    /// The true cost is at least `cost - maxError`
+    pub max_error: MicroDollars,
+}
+
+/// Heaviest projects with the bounds that apply to them
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ApproxTopReport {
+    pub projects: Vec<ApproxProject>,
+    /// Counters kept while streaming
+    pub counters: usize,
+    pub total_cost: MicroDollars,
+    /// Largest overestimate any project can have; every project costing more is listed
+    pub error_bound: MicroDollars,
+}
+
+/// Stream each session's cost within `since..=until` into a summary of
+/// `COUNTERS_PER_ROW × n` counters and report the `n` heaviest projects
+pub fn approx_top_projects(
+    sessions: &[SessionOutput],
+    n: usize,
+    since: Option<DateTime<Utc>>,
+    until: Option<DateTime<Utc>>,
+) -> ApproxTopReport {
+    let since = since.map(|since| since.format("%Y-%m-%d").to_string());
+    let until = until.map(|until| until.format("%Y-%m-%d").to_string());
+    let counters = n.saturating_mul(COUNTERS_PER_ROW).max(1);
+    let mut summary = SpaceSaving::new(counters);
+
+    for session in sessions {
+        let cost: i64 = session
+            .daily_usage
+            .iter()
+            .filter(|(day, _)| since.as_ref().map_or(true, |since| *day >= since))
+            .filter(|(day, _)| until.as_ref().map_or(true, |until| *day <= until))
+            .map(|(_, usage)| usage.cost.0)
+            .sum();
+        if cost > 0 {
+            summary.add(&session.project_path, cost);
+        }
+    }
+
+    ApproxTopReport {
+        projects: summary
+            .top(n)
+            .into_iter()
+            .map(|(project, cost, error)| ApproxProject {
+                project,
+                cost: MicroDollars(cost),
+                max_error: MicroDollars(error),
+            })
+            .collect(),
+        counters,
+        total_cost: MicroDollars(summary.total()),
+        error_bound: MicroDollars(summary.error_bound()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_space_saving_keeps_heavy_keys_within_bounds() {
+        let mut summary = SpaceSaving::new(4);
+        let mut exact: HashMap<String, i64> = HashMap::new();
+        // Two heavy projects among many light ones
+        for round in 0..50 {
+            for (key, weight) in [("heavy-a", 40), ("heavy-b", 25)] {
+                summary.add(key, weight);
+                *exact.entry(key.to_string()).or_default() += weight;
+            }
+            let light = format!("light-{}", round);
+            summary.add(&light, 3);
+            *exact.entry(light).or_default() += 3;
+        }
+
+        let total: i64 = exact.values().sum();
+        assert_eq!(summary.total(), total);
+        assert_eq!(summary.error_bound(), total / 4);
+
+        let top = summary.top(2);
+        assert_eq!(top[0].0, "heavy-a");
+        assert_eq!(top[1].0, "heavy-b");
+        for (key, count, error) in summary.top(4) {
+            let truth = exact[&key];
+            assert!(count >= truth && count - error <= truth, "{}", key);
+            assert!(error <= summary.error_bound());
+        }
+    }
+}
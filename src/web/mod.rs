@@ -0,0 +1,528 @@
+//! Local Web Dashboard
+//!
+//! `claude-usage web` serves a small dashboard on localhost for teammates who
+//! would rather not use the CLI. The page is a single static HTML file that
+//! polls JSON endpoints:
+//!
+//! - `GET /api/daily?limit=N` - same document as `daily --json`
+//! - `GET /api/monthly?limit=N` - same document as `monthly --json`
+//! - `GET /api/top?limit=N` - the most expensive sessions (default 10)
+//! - `GET /api/live` - today's totals read straight from the JSONL logs
+//!
+//! Report data is loaded once and reloaded when older than the refresh
+//! interval, so page loads do not rescan the backups. The server only speaks
+//! enough HTTP/1.1 for a browser and `curl`, and is built with the `web`
+//! feature so the default binary carries no server code.
+//!
+//! `claude-usage serve --prometheus` reuses the same server to answer
+//! `GET /metrics` for Prometheus, see [`prometheus`], and
+//! `claude-usage status --listen` to answer the status bar endpoints, see
+//! [`crate::status`].
+
+#[cfg(feature = "web")]
+pub mod prometheus;
+
+#[cfg(feature = "web")]
+use crate::analyzer::ClaudeUsageAnalyzer;
+#[cfg(feature = "web")]
+use crate::dedup::ProcessOptions;
+#[cfg(feature = "web")]
+use crate::models::SessionOutput;
+#[cfg(feature = "web")]
+use crate::parser::FileParser;
+#[cfg(feature = "web")]
+use crate::reports::ReportDisplayManager;
+#[cfg(feature = "web")]
+use crate::request::{AnalyzerRequest, ReportKind};
+#[cfg(feature = "web")]
+use crate::status;
+#[cfg(feature = "web")]
+use anyhow::Context;
+use anyhow::Result;
+#[cfg(feature = "web")]
+use std::future::Future;
+#[cfg(feature = "web")]
+use std::sync::Arc;
+#[cfg(feature = "web")]
+use std::time::{Duration, Instant};
+#[cfg(feature = "web")]
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+#[cfg(feature = "web")]
+use tokio::net::{TcpListener, TcpStream};
+#[cfg(feature = "web")]
+use tokio::sync::Mutex;
+#[cfg(feature = "web")]
+use tracing::{debug, info, warn};
+
+#[cfg(feature = "web")]
+/// The dashboard page, polling the JSON endpoints
+const DASHBOARD_HTML: &str = include_str!("dashboard.html");
+
+#[cfg(feature = "web")]
+/// Sessions listed by `/api/top` when no limit is given
+const DEFAULT_TOP_LIMIT: usize = 10;
+
+#[cfg(feature = "web")]
+/// Largest request head read before giving up on a client
+const MAX_REQUEST_BYTES: usize = 8 * 1024;
+
+#[cfg(feature = "web")]
+/// A response ready to be written to the client
+#[derive(Debug, PartialEq)]
+pub struct Response {
+    pub status: u16,
+    pub content_type: &'static str,
+    pub body: String,
+}
+
+#[cfg(feature = "web")]
+impl Response {
+    fn json(status: u16, value: &serde_json::Value) -> Self {
+        Self {
+            status,
+            content_type: "application/json",
+            body: value.to_string(),
+        }
+    }
+
+    fn error(status: u16, message: &str) -> Self {
+        Self::json(status, &serde_json::json!({ "error": message }))
+    }
+
+    fn reason(&self) -> &'static str {
+        match self.status {
+            200 => "OK",
+            400 => "Bad Request",
+            404 => "Not Found",
+            405 => "Method Not Allowed",
+            _ => "Internal Server Error",
+        }
+    }
+}
+
+#[cfg(feature = "web")]
+/// Session data shared by requests, reloaded once older than `refresh`
+struct SessionCache {
+    options: ProcessOptions,
+    refresh: Duration,
+    loaded: Option<(Instant, Arc<Vec<SessionOutput>>)>,
+}
+
+#[cfg(feature = "web")]
+impl SessionCache {
+    async fn sessions(&mut self) -> Result<Arc<Vec<SessionOutput>>> {
+        if let Some((loaded_at, sessions)) = &self.loaded {
+            if loaded_at.elapsed() < self.refresh {
+                return Ok(sessions.clone());
+            }
+        }
+
+        let analyzer = ClaudeUsageAnalyzer::new();
+        let sessions = Arc::new(
+            analyzer
+                .sessions(AnalyzerRequest::with_options(
+                    ReportKind::Daily,
+                    self.options.clone(),
+                ))
+                .await?,
+        );
+        info!(
+            sessions = sessions.len(),
+            "Loaded sessions for web dashboard"
+        );
+        self.loaded = Some((Instant::now(), sessions.clone()));
+        Ok(sessions)
+    }
+}
+
+#[cfg(feature = "web")]
+/// Serve the dashboard on `127.0.0.1:port` until the process is stopped
+pub async fn run_web_server(port: u16, refresh: Duration, options: ProcessOptions) -> Result<()> {
+    let listener = TcpListener::bind(("127.0.0.1", port))
+        .await
+        .with_context(|| format!("Failed to listen on 127.0.0.1:{}", port))?;
+    let cache = Arc::new(Mutex::new(SessionCache {
+        options,
+        refresh,
+        loaded: None,
+    }));
+
+    println!("🌐 Dashboard running at http://127.0.0.1:{}/", port);
+    println!("💡 Use Ctrl+C to stop");
+    info!(port, "Web dashboard listening");
+
+    loop {
+        let (stream, peer) = listener.accept().await?;
+        let cache = cache.clone();
+        tokio::spawn(async move {
+            let route = |target: String| async move { answer(&target, &cache).await };
+            if let Err(e) = handle_connection(stream, route).await {
+                warn!(peer = %peer, error = %e, "Failed to answer web request");
+            }
+        });
+    }
+}
+
+#[cfg(feature = "web")]
+/// Sessions last loaded from the logs for `/metrics` or the status
+/// endpoints, with the state of the refresh loop
+struct LogSessions {
+    sessions: Vec<SessionOutput>,
+    status: prometheus::RefreshStatus,
+}
+
+#[cfg(feature = "web")]
+/// Serve Prometheus gauges at `http://bind:port/metrics`, reloading the
+/// sessions from the JSONL logs every `refresh` until the process is stopped
+///
+/// The logs are read instead of the backups so today's gauges stay current.
+pub async fn run_prometheus_server(
+    bind: &str,
+    port: u16,
+    refresh: Duration,
+    exclude_vms: bool,
+) -> Result<()> {
+    // Load once up front so the first scrape already sees data
+    let sessions = load_sessions(exclude_vms).await?;
+    let status = prometheus::RefreshStatus {
+        last_refresh: Some(chrono::Utc::now()),
+        failures: 0,
+    };
+    let state = Arc::new(Mutex::new(LogSessions { sessions, status }));
+
+    let listener = TcpListener::bind((bind, port))
+        .await
+        .with_context(|| format!("Failed to listen on {}:{}", bind, port))?;
+
+    tokio::spawn(refresh_sessions(state.clone(), refresh, exclude_vms));
+
+    println!("📈 Prometheus metrics at http://{}:{}/metrics", bind, port);
+    println!("💡 Use Ctrl+C to stop");
+    info!(bind, port, "Prometheus exporter listening");
+
+    loop {
+        let (stream, peer) = listener.accept().await?;
+        let state = state.clone();
+        tokio::spawn(async move {
+            let route = |target: String| async move {
+                let (path, _) = target.split_once('?').unwrap_or((&target, ""));
+                match path {
+                    "/metrics" => {
+                        let state = state.lock().await;
+                        Response {
+                            status: 200,
+                            content_type: prometheus::CONTENT_TYPE,
+                            body: render_metrics(&state.sessions, state.status),
+                        }
+                    }
+                    _ => Response::error(404, "Not found"),
+                }
+            };
+            if let Err(e) = handle_connection(stream, route).await {
+                warn!(peer = %peer, error = %e, "Failed to answer metrics request");
+            }
+        });
+    }
+}
+
+#[cfg(not(feature = "web"))]
+pub async fn run_prometheus_server(
+    _bind: &str,
+    _port: u16,
+    _refresh: std::time::Duration,
+    _exclude_vms: bool,
+) -> Result<()> {
+    anyhow::bail!("Prometheus exporter not available. Rebuild with --features web")
+}
+
+#[cfg(feature = "web")]
+/// Serve status bar totals at `http://bind:port/{today,month,status}`,
+/// reloading the sessions from the JSONL logs every `refresh` until the
+/// process is stopped
+pub async fn run_status_server(
+    bind: &str,
+    port: u16,
+    refresh: Duration,
+    exclude_vms: bool,
+    monthly_budget: Option<f64>,
+) -> Result<()> {
+    let sessions = load_sessions(exclude_vms).await?;
+    let status = prometheus::RefreshStatus {
+        last_refresh: Some(chrono::Utc::now()),
+        failures: 0,
+    };
+    let state = Arc::new(Mutex::new(LogSessions { sessions, status }));
+
+    let listener = TcpListener::bind((bind, port))
+        .await
+        .with_context(|| format!("Failed to listen on {}:{}", bind, port))?;
+
+    tokio::spawn(refresh_sessions(state.clone(), refresh, exclude_vms));
+
+    println!("📊 Status endpoints at http://{}:{}/status", bind, port);
+    println!("💡 Use Ctrl+C to stop");
+    info!(bind, port, "Status server listening");
+
+    loop {
+        let (stream, peer) = listener.accept().await?;
+        let state = state.clone();
+        tokio::spawn(async move {
+            let route = |target: String| async move {
+                let state = state.lock().await;
+                status_response(&target, &state, monthly_budget)
+            };
+            if let Err(e) = handle_connection(stream, route).await {
+                warn!(peer = %peer, error = %e, "Failed to answer status request");
+            }
+        });
+    }
+}
+
+#[cfg(not(feature = "web"))]
+pub async fn run_status_server(
+    _bind: &str,
+    _port: u16,
+    _refresh: std::time::Duration,
+    _exclude_vms: bool,
+    _monthly_budget: Option<f64>,
+) -> Result<()> {
+    anyhow::bail!("Status server not available. Rebuild with --features web")
+}
+
+#[cfg(feature = "web")]
+/// Answer a status endpoint from the sessions last loaded
+fn status_response(target: &str, state: &LogSessions, monthly_budget: Option<f64>) -> Response {
+    let (path, _) = target.split_once('?').unwrap_or((target, ""));
+    let today = chrono::Local::now().date_naive();
+    let current = status::status(&state.sessions, today, chrono::Utc::now(), monthly_budget);
+    match path {
+        "/today" => Response::json(200, &serde_json::json!(current.today)),
+        "/month" => {
+            let mut month = serde_json::json!(current.month);
+            if let Some(budget) = &current.budget {
+                month["budget"] = serde_json::json!(budget);
+            }
+            Response::json(200, &month)
+        }
+        "/status" => {
+            let mut body = serde_json::json!(current);
+            body["lastRefresh"] = serde_json::json!(state.status.last_refresh);
+            body["refreshFailures"] = serde_json::json!(state.status.failures);
+            Response::json(200, &body)
+        }
+        _ => Response::error(404, "Not found"),
+    }
+}
+
+#[cfg(feature = "web")]
+/// Reload the sessions every `refresh`, keeping the last ones when a reload fails
+async fn refresh_sessions(state: Arc<Mutex<LogSessions>>, refresh: Duration, exclude_vms: bool) {
+    loop {
+        tokio::time::sleep(refresh).await;
+        let loaded = load_sessions(exclude_vms).await;
+
+        let mut state = state.lock().await;
+        match loaded {
+            Ok(sessions) => {
+                state.sessions = sessions;
+                state.status.last_refresh = Some(chrono::Utc::now());
+            }
+            Err(e) => {
+                warn!(error = %e, "Failed to reload sessions from the logs");
+                state.status.failures += 1;
+            }
+        }
+    }
+}
+
+#[cfg(feature = "web")]
+async fn load_sessions(exclude_vms: bool) -> Result<Vec<SessionOutput>> {
+    let sessions = tokio::task::spawn_blocking(move || {
+        let claude_paths = FileParser::new().discover_claude_paths(exclude_vms)?;
+        ClaudeUsageAnalyzer::sessions_from_logs(&claude_paths)
+    })
+    .await??;
+    debug!(sessions = sessions.len(), "Loaded sessions from the logs");
+    Ok(sessions)
+}
+
+#[cfg(feature = "web")]
+/// Render the gauges for the current local day
+fn render_metrics(sessions: &[SessionOutput], status: prometheus::RefreshStatus) -> String {
+    let today = chrono::Local::now().date_naive();
+    prometheus::render(sessions, today, chrono::Utc::now(), status)
+}
+
+#[cfg(not(feature = "web"))]
+pub async fn run_web_server(
+    _port: u16,
+    _refresh: std::time::Duration,
+    _options: crate::dedup::ProcessOptions,
+) -> Result<()> {
+    anyhow::bail!("Web dashboard not available. Rebuild with --features web")
+}
+
+#[cfg(feature = "web")]
+/// Read one request and write the response `route` gives for its target
+async fn handle_connection<F, Fut>(mut stream: TcpStream, route: F) -> Result<()>
+where
+    F: FnOnce(String) -> Fut,
+    Fut: Future<Output = Response>,
+{
+    let mut request = Vec::new();
+    let mut buf = [0u8; 1024];
+    while !request.windows(4).any(|w| w == b"\r\n\r\n") {
+        let read = stream.read(&mut buf).await?;
+        if read == 0 {
+            break;
+        }
+        request.extend_from_slice(&buf[..read]);
+        if request.len() > MAX_REQUEST_BYTES {
+            break;
+        }
+    }
+
+    let request = String::from_utf8_lossy(&request);
+    let response = match parse_request_line(&request) {
+        Some(("GET", target)) => {
+            debug!(target, "Web request");
+            route(target.to_string()).await
+        }
+        Some(_) => Response::error(405, "Only GET is supported"),
+        None => Response::error(400, "Malformed request"),
+    };
+
+    let head = format!(
+        "HTTP/1.1 {} {}\r\nContent-Type: {}; charset=utf-8\r\nContent-Length: {}\r\nCache-Control: no-store\r\nConnection: close\r\n\r\n",
+        response.status,
+        response.reason(),
+        response.content_type,
+        response.body.len()
+    );
+    stream.write_all(head.as_bytes()).await?;
+    stream.write_all(response.body.as_bytes()).await?;
+    stream.shutdown().await?;
+    Ok(())
+}
+
+#[cfg(feature = "web")]
+/// Method and target of an HTTP request line
+fn parse_request_line(request: &str) -> Option<(&str, &str)> {
+    let mut parts = request.lines().next()?.split_whitespace();
+    let method = parts.next()?;
+    let target = parts.next()?;
+    parts
+        .next()?
+        .starts_with("HTTP/")
+        .then_some((method, target))
+}
+
+#[cfg(feature = "web")]
+/// Value of the `limit` query parameter, if given
+fn limit_param(query: &str) -> Result<Option<usize>, String> {
+    query
+        .split('&')
+        .filter_map(|pair| pair.split_once('='))
+        .find(|(key, _)| *key == "limit")
+        .map(|(_, value)| {
+            value
+                .parse()
+                .map_err(|_| format!("Invalid limit: {}", value))
+        })
+        .transpose()
+}
+
+#[cfg(feature = "web")]
+async fn answer(target: &str, cache: &Mutex<SessionCache>) -> Response {
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    let limit = match limit_param(query) {
+        Ok(limit) => limit,
+        Err(message) => return Response::error(400, &message),
+    };
+
+    match path {
+        "/" | "/index.html" => Response {
+            status: 200,
+            content_type: "text/html",
+            body: DASHBOARD_HTML.to_string(),
+        },
+        "/api/live" => {
+            let today = chrono::Utc::now().date_naive();
+            match ClaudeUsageAnalyzer::new().cost_for_date(today) {
+                Ok(totals) => Response::json(200, &serde_json::json!(totals)),
+                Err(e) => Response::error(500, &format!("{:#}", e)),
+            }
+        }
+        "/api/daily" | "/api/monthly" | "/api/top" => {
+            let sessions = match cache.lock().await.sessions().await {
+                Ok(sessions) => sessions,
+                Err(e) => return Response::error(500, &format!("{:#}", e)),
+            };
+            Response::json(200, &report(path, &sessions, limit))
+        }
+        _ => Response::error(404, "Not found"),
+    }
+}
+
+#[cfg(feature = "web")]
+/// Build the JSON document of a report endpoint
+fn report(path: &str, sessions: &[SessionOutput], limit: Option<usize>) -> serde_json::Value {
+    let manager = ReportDisplayManager::new();
+    let today = chrono::Local::now().date_naive();
+    match path {
+        "/api/daily" => manager.daily_json(sessions, limit, None, today),
+        "/api/monthly" => manager.monthly_json(sessions, limit, None, today),
+        _ => {
+            let mut top: Vec<&SessionOutput> = sessions.iter().collect();
+            top.sort_by_key(|session| std::cmp::Reverse(session.total_cost));
+            top.truncate(limit.unwrap_or(DEFAULT_TOP_LIMIT));
+            serde_json::json!({ "sessions": top })
+        }
+    }
+}
+
+#[cfg(all(test, feature = "web"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_request_line_and_limit() {
+        let request = "GET /api/daily?limit=7 HTTP/1.1\r\nHost: localhost\r\n\r\n";
+        assert_eq!(
+            parse_request_line(request),
+            Some(("GET", "/api/daily?limit=7"))
+        );
+        assert_eq!(parse_request_line("garbage\r\n\r\n"), None);
+
+        assert_eq!(limit_param("limit=7"), Ok(Some(7)));
+        assert_eq!(limit_param("x=1"), Ok(None));
+        assert!(limit_param("limit=seven").is_err());
+    }
+
+    #[test]
+    fn test_report_endpoints_render_json() {
+        let daily = report("/api/daily", &[], Some(3));
+        assert_eq!(daily["daily"].as_array().unwrap().len(), 3);
+
+        let top = report("/api/top", &[], None);
+        assert_eq!(top["sessions"], serde_json::json!([]));
+    }
+
+    #[test]
+    fn test_status_endpoints_render_json() {
+        let state = LogSessions {
+            sessions: Vec::new(),
+            status: prometheus::RefreshStatus::default(),
+        };
+        let today = status_response("/today", &state, None);
+        assert_eq!(today.status, 200);
+        let today: serde_json::Value = serde_json::from_str(&today.body).unwrap();
+        assert_eq!(today["cost"], 0.0);
+
+        let month = status_response("/month", &state, Some(100.0));
+        assert!(month.body.contains("\"budget\""));
+        let full = status_response("/status?x=1", &state, None);
+        assert!(full.body.contains("\"refreshFailures\":0"));
+        assert_eq!(status_response("/nope", &state, None).status, 404);
+    }
+}
@@ -0,0 +1,190 @@
+//! Prometheus exposition of usage gauges
+//!
+//! `claude-usage serve --prometheus` answers `GET /metrics` with the text
+//! rendered here from the sessions in the JSONL logs:
+//!
+//! - `claude_usage_cost_today_usd` - cost of today's usage
+//! - `claude_usage_tokens_today{type}` - today's input, output, cache creation
+//!   and cache read tokens
+//! - `claude_usage_active_sessions` - sessions active within the last hour
+//! - `claude_usage_project_cost_today_usd{project}` - today's cost per project
+//! - `claude_usage_last_refresh_timestamp_seconds` and
+//!   `claude_usage_refresh_failures_total` - health of the refresh loop
+//!
+//! "Today" is the local day, matching `daily`.
+
+use crate::models::{MicroDollars, SessionOutput};
+use crate::timestamp_parser::TimestampParser;
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+/// Content type of the text exposition format
+pub const CONTENT_TYPE: &str = "text/plain; version=0.0.4";
+
+/// How recently a session must have been active to count as active
+pub const ACTIVE_WINDOW_MINUTES: i64 = 60;
+
+/// Refresh state reported alongside the usage gauges
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RefreshStatus {
+    /// When the sessions were last loaded successfully
+    pub last_refresh: Option<DateTime<Utc>>,
+    /// Failed reloads since the server started
+    pub failures: u64,
+}
+
+/// Render the gauges for `today` in the Prometheus text format
+pub fn render(
+    sessions: &[SessionOutput],
+    today: NaiveDate,
+    now: DateTime<Utc>,
+    status: RefreshStatus,
+) -> String {
+    let day = today.format("%Y-%m-%d").to_string();
+    let mut cost = MicroDollars::ZERO;
+    let mut tokens = [0u64; 4];
+    let mut projects: BTreeMap<&str, MicroDollars> = BTreeMap::new();
+    for session in sessions {
+        let Some(usage) = session.daily_usage.get(&day) else {
+            continue;
+        };
+        cost += usage.cost;
+        tokens[0] += u64::from(usage.input_tokens);
+        tokens[1] += u64::from(usage.output_tokens);
+        tokens[2] += u64::from(usage.cache_creation_tokens);
+        tokens[3] += u64::from(usage.cache_read_tokens);
+        *projects.entry(&session.project_path).or_default() += usage.cost;
+    }
+    let window = Duration::minutes(ACTIVE_WINDOW_MINUTES);
+    let active = sessions
+        .iter()
+        .filter(|session| {
+            TimestampParser::parse(&session.last_activity).is_ok_and(|last| now - last <= window)
+        })
+        .count();
+
+    let mut out = String::new();
+    header(
+        &mut out,
+        "claude_usage_cost_today_usd",
+        "Cost of today's usage in US dollars",
+        "gauge",
+    );
+    let _ = writeln!(out, "claude_usage_cost_today_usd {}", cost.to_dollars());
+
+    header(
+        &mut out,
+        "claude_usage_tokens_today",
+        "Tokens used today by type",
+        "gauge",
+    );
+    let types = ["input", "output", "cache_creation", "cache_read"];
+    for (kind, count) in types.iter().zip(tokens) {
+        let _ = writeln!(
+            out,
+            "claude_usage_tokens_today{{type=\"{}\"}} {}",
+            kind, count
+        );
+    }
+
+    header(
+        &mut out,
+        "claude_usage_active_sessions",
+        "Sessions with activity in the last hour",
+        "gauge",
+    );
+    let _ = writeln!(out, "claude_usage_active_sessions {}", active);
+
+    header(
+        &mut out,
+        "claude_usage_project_cost_today_usd",
+        "Cost of today's usage per project in US dollars",
+        "gauge",
+    );
+    for (project, cost) in &projects {
+        let _ = writeln!(
+            out,
+            "claude_usage_project_cost_today_usd{{project=\"{}\"}} {}",
+            escape_label(project),
+            cost.to_dollars()
+        );
+    }
+
+    header(
+        &mut out,
+        "claude_usage_last_refresh_timestamp_seconds",
+        "Unix time of the last successful data refresh",
+        "gauge",
+    );
+    let last_refresh = status.last_refresh.map_or(0, |at| at.timestamp());
+    let _ = writeln!(
+        out,
+        "claude_usage_last_refresh_timestamp_seconds {}",
+        last_refresh
+    );
+
+    header(
+        &mut out,
+        "claude_usage_refresh_failures_total",
+        "Data refreshes that failed since the server started",
+        "counter",
+    );
+    let _ = writeln!(
+        out,
+        "claude_usage_refresh_failures_total {}",
+        status.failures
+    );
+    out
+}
+
+fn header(out: &mut String, name: &str, help: &str, kind: &str) {
+    let _ = writeln!(out, "# HELP {} {}", name, help);
+    let _ = writeln!(out, "# TYPE {} {}", name, kind);
+}
+
+/// Escape a label value: backslash, double quote and newline
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::test_support::session;
+
+    #[test]
+    fn test_render_today_gauges() {
+        let today = NaiveDate::from_ymd_opt(2025, 3, 1).unwrap();
+        let now = "2025-03-01T12:00:00Z".parse::<DateTime<Utc>>().unwrap();
+        let sessions = vec![
+            session("s")
+                .project("/work/app")
+                .last_activity("2025-03-01T11:30:00Z")
+                .day("2025-03-01", 1.5)
+                .build(),
+            session("s")
+                .project("/work/\"lib\"")
+                .last_activity("2025-03-01T09:00:00Z")
+                .day("2025-03-01", 0.25)
+                .build(),
+            session("s")
+                .project("/work/app")
+                .last_activity("2025-02-28T10:00:00Z")
+                .day("2025-02-28", 9.0)
+                .build(),
+        ];
+
+        let text = render(&sessions, today, now, RefreshStatus::default());
+        assert!(text.contains("claude_usage_cost_today_usd 1.75\n"));
+        assert!(text.contains("claude_usage_tokens_today{type=\"input\"} 200\n"));
+        assert!(text.contains("claude_usage_tokens_today{type=\"cache_read\"} 0\n"));
+        assert!(text.contains("claude_usage_active_sessions 1\n"));
+        assert!(text.contains("{project=\"/work/app\"} 1.5\n"));
+        assert!(text.contains("{project=\"/work/\\\"lib\\\"\"} 0.25\n"));
+        assert!(text.contains("# TYPE claude_usage_refresh_failures_total counter\n"));
+    }
+}
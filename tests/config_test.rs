@@ -5,7 +5,7 @@ use tempfile::tempdir;
 #[cfg(test)]
 mod config_tests {
     use super::*;
-    use claude_usage::config::Config;
+    use claude_usage::config::{find_workspace_config, Config};
 
     #[test]
     fn test_default_config_values() {
@@ -153,6 +153,63 @@ claude_keeper_path = "/custom/claude-keeper"
         assert_eq!(config.output.json_pretty, true);
     }
 
+    #[test]
+    fn test_yaml_config_file_loading() {
+        let temp_dir = tempdir().expect("Failed to create temp dir");
+        let test_config = r#"
+logging:
+  level: DEBUG
+processing:
+  batch_size: 5
+dedup:
+  enabled: false
+paths:
+  claude_home: /custom/claude
+"#;
+
+        for name in ["test-config.yaml", "test-config.yml"] {
+            let config_path = temp_dir.path().join(name);
+            fs::write(&config_path, test_config).expect("Failed to write test config");
+
+            let config = Config::load_from_file(&config_path).expect("Failed to load config");
+
+            assert_eq!(config.logging.level, "DEBUG");
+            assert_eq!(config.processing.batch_size, 5);
+            assert!(!config.dedup.enabled);
+            assert_eq!(config.paths.claude_home.to_str(), Some("/custom/claude"));
+            // Untouched settings keep their defaults
+            assert_eq!(config.memory.max_memory_mb, 512);
+        }
+
+        // An empty YAML file changes nothing
+        let empty = temp_dir.path().join("empty.yaml");
+        fs::write(&empty, "").unwrap();
+        let config = Config::load_from_file(&empty).expect("Failed to load config");
+        assert_eq!(config.processing.batch_size, 10);
+    }
+
+    #[test]
+    fn test_json_config_file_loading() {
+        let temp_dir = tempdir().expect("Failed to create temp dir");
+        let config_path = temp_dir.path().join("test-config.json");
+        fs::write(
+            &config_path,
+            r#"{"logging": {"level": "DEBUG"}, "processing": {"batch_size": 5}, "dedup": {"enabled": false}}"#,
+        )
+        .expect("Failed to write test config");
+
+        let config = Config::load_from_file(&config_path).expect("Failed to load config");
+
+        assert_eq!(config.logging.level, "DEBUG");
+        assert_eq!(config.processing.batch_size, 5);
+        assert!(!config.dedup.enabled);
+        assert_eq!(config.memory.max_memory_mb, 512);
+
+        // Settings are checked the same way as in TOML
+        fs::write(&config_path, r#"{"processing": {"batch_size": "five"}}"#).unwrap();
+        assert!(Config::load_from_file(&config_path).is_err());
+    }
+
     #[test]
     fn test_config_serialization() {
         let config = Config::default();
@@ -179,4 +236,74 @@ claude_keeper_path = "/custom/claude-keeper"
             deserialized.memory.max_memory_mb
         );
     }
+
+    #[test]
+    fn test_workspace_config_found_in_ancestor() {
+        let temp_dir = tempdir().expect("Failed to create temp dir");
+        let repo = temp_dir.path().join("repo");
+        let nested = repo.join("src").join("deep");
+        fs::create_dir_all(&nested).unwrap();
+
+        assert_eq!(find_workspace_config(&nested), None);
+
+        fs::write(repo.join(".claude-usage.toml"), "").unwrap();
+        assert_eq!(
+            find_workspace_config(&nested),
+            Some(repo.join(".claude-usage.toml"))
+        );
+
+        // The nearest directory wins, and the visible name wins within it
+        fs::write(nested.join(".claude-usage.toml"), "").unwrap();
+        fs::write(nested.join("claude-usage.toml"), "").unwrap();
+        assert_eq!(
+            find_workspace_config(&nested),
+            Some(nested.join("claude-usage.toml"))
+        );
+    }
+
+    #[test]
+    fn test_workspace_config_overrides_global_per_setting() {
+        let temp_dir = tempdir().expect("Failed to create temp dir");
+        let global = temp_dir.path().join("config.toml");
+        let workspace = temp_dir.path().join("claude-usage.toml");
+        fs::write(
+            &global,
+            "[processing]\nbatch_size = 5\nparallel_chunks = 2\n\n[logging]\nlevel = \"INFO\"\n",
+        )
+        .unwrap();
+        fs::write(&workspace, "[processing]\nbatch_size = 7\n").unwrap();
+
+        let config = Config::load_layered(&[global, workspace]).expect("Failed to load config");
+
+        assert_eq!(config.processing.batch_size, 7);
+        assert_eq!(config.processing.parallel_chunks, 2);
+        assert_eq!(config.logging.level, "INFO");
+        // Untouched settings keep their defaults
+        assert_eq!(config.memory.max_memory_mb, 512);
+    }
+
+    #[test]
+    fn test_layers_in_different_formats() {
+        let temp_dir = tempdir().expect("Failed to create temp dir");
+        let global = temp_dir.path().join("config.yaml");
+        let workspace = temp_dir.path().join("claude-usage.json");
+        fs::write(
+            &global,
+            "processing:\n  batch_size: 5\n  parallel_chunks: 2\n",
+        )
+        .unwrap();
+        fs::write(&workspace, r#"{"processing": {"batch_size": 7}}"#).unwrap();
+
+        let config = Config::load_layered(&[global, workspace]).expect("Failed to load config");
+
+        assert_eq!(config.processing.batch_size, 7);
+        assert_eq!(config.processing.parallel_chunks, 2);
+
+        // TOML wins over the other formats in the same directory
+        fs::write(temp_dir.path().join("claude-usage.toml"), "").unwrap();
+        assert_eq!(
+            find_workspace_config(temp_dir.path()),
+            Some(temp_dir.path().join("claude-usage.toml"))
+        );
+    }
 }
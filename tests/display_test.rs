@@ -8,6 +8,8 @@ use claude_usage::display::{LiveDisplay, RunningTotals, SessionActivity};
 #[cfg(feature = "live")]
 use claude_usage::live::{BaselineSummary, LiveUpdate};
 use claude_usage::models::{MessageData, UsageData, UsageEntry, SessionData};
+#[cfg(feature = "live")]
+use claude_usage::models::MicroDollars;
 use std::time::SystemTime;
 
 #[cfg(feature = "live")]
@@ -34,6 +36,7 @@ fn create_test_update(session_id: &str, project: &str, tokens: u32, cost: f64) -
                     cache_creation_input_tokens: 0,
                     cache_read_input_tokens: 0,
                 }),
+                secondary_usage: None,
             },
             cost_usd: Some(cost),
             request_id: "req1".to_string(),
@@ -42,9 +45,10 @@ fn create_test_update(session_id: &str, project: &str, tokens: u32, cost: f64) -
             let mut data = SessionData::new(session_id.to_string(), project.to_string());
             data.input_tokens = tokens;
             data.output_tokens = tokens / 2;
-            data.total_cost = cost;
+            data.total_cost = MicroDollars::from_dollars(cost);
             data
         },
+        instance: None,
         timestamp: SystemTime::now(),
     }
 }
@@ -0,0 +1,296 @@
+//! Golden-file tests for report output
+//!
+//! Each directory under `tests/fixtures/golden` is one case: a synthetic
+//! `.claude` tree in `claude/` (VM instances under `claude/vms/<name>`) and the
+//! expected JSON reports in `expected/`. The runner aggregates the tree, renders
+//! the daily, monthly and session reports and diffs them against the goldens,
+//! so aggregation changes cannot silently alter report totals.
+//!
+//! The reports the CLI prints are aggregated from claude-keeper backups
+//! instead, so each case also holds the same logs as backed up, in
+//! `backup/`: one JSON array of messages per parquet file, with the
+//! `session_id`, `project_name` and `source_file` columns claude-keeper
+//! adds. Their reports go through the parquet reader's aggregation into
+//! `expected/backup/`.
+//!
+//! Reports are also rendered in every older `--output-version` still
+//! supported, into `daily.v1.json` and so on, locking the layout each
+//! version promises to its consumers.
+//!
+//! After an intentional change to report output, regenerate the goldens and
+//! review the diff before committing:
+//!
+//! ```bash
+//! cargo test --test golden_test -- --bless
+//! ```
+//!
+//! Setting `BLESS=1` has the same effect.
+
+use anyhow::{Context, Result};
+use chrono::NaiveDate;
+use claude_usage::analyzer::ClaudeUsageAnalyzer;
+use claude_usage::output_version;
+use claude_usage::parquet::reader::ParquetSummaryReader;
+use claude_usage::project_names;
+use claude_usage::models::SessionOutput;
+use claude_usage::reports::ReportDisplayManager;
+use serde_json::Value;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::sync::Arc;
+
+/// Reports are rendered as if run on this date so daily windows and
+/// month-to-date comparisons stay stable
+const TODAY: (i32, u32, u32) = (2025, 2, 15);
+
+/// Relative tolerance for comparing floating point costs
+const COST_TOLERANCE: f64 = 1e-9;
+
+const REPORTS: [&str; 3] = ["daily", "monthly", "session"];
+
+fn fixtures_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/golden")
+}
+
+/// Instance roots of a case: the main tree plus every VM under `vms/`
+fn instance_roots(claude_dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut roots = vec![claude_dir.to_path_buf()];
+    let vms_dir = claude_dir.join("vms");
+    if vms_dir.is_dir() {
+        let mut vms: Vec<PathBuf> = fs::read_dir(&vms_dir)?
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|path| path.is_dir())
+            .collect();
+        vms.sort();
+        roots.extend(vms);
+    }
+    Ok(roots)
+}
+
+/// Sessions of a case's backup, aggregated as the reports aggregate
+/// claude-keeper's parquet files
+fn sessions_from_backup(backup_dir: &Path) -> Result<Vec<SessionOutput>> {
+    let mut files: Vec<PathBuf> = fs::read_dir(backup_dir)?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+        .collect();
+    files.sort();
+    let files: Vec<(PathBuf, Result<Arc<Vec<Value>>>)> = files
+        .into_iter()
+        .map(|file| {
+            let messages = fs::read_to_string(&file)
+                .map_err(anyhow::Error::from)
+                .and_then(|content| Ok(Arc::new(serde_json::from_str(&content)?)));
+            (file, messages)
+        })
+        .collect();
+    let reader = ParquetSummaryReader::new(backup_dir.to_path_buf())?;
+    Ok(reader.sessions_from_messages(files)?.0)
+}
+
+/// Render every report for a case in every output version, named after
+/// their golden files
+fn render_reports(case_dir: &Path) -> Result<Vec<(String, Value)>> {
+    let today = NaiveDate::from_ymd_opt(TODAY.0, TODAY.1, TODAY.2).context("Invalid date")?;
+    let roots = instance_roots(&case_dir.join("claude"))?;
+    let sessions = ClaudeUsageAnalyzer::sessions_from_logs(&roots).unwrap();
+    let mut display = ReportDisplayManager::new();
+    let mut reports = Vec::new();
+
+    // Backups in the latest version only, the older layouts are locked below
+    let backup_sessions = sessions_from_backup(&case_dir.join("backup"))?;
+    display.set_output_version(output_version::LATEST);
+    for report in REPORTS {
+        let value = match report {
+            "daily" => display.daily_json(&backup_sessions, None, None, today),
+            "monthly" => display.monthly_json(&backup_sessions, None, None, today),
+            _ => display.session_json(&backup_sessions, None),
+        };
+        reports.push((format!("backup/{}", report), value));
+    }
+
+    for version in (output_version::OLDEST..=output_version::LATEST).rev() {
+        display.set_output_version(version);
+        for report in REPORTS {
+            let value = match report {
+                "daily" => display.daily_json(&sessions, None, None, today),
+                "monthly" => display.monthly_json(&sessions, None, None, today),
+                _ => display.session_json(&sessions, None),
+            };
+            let name = if version == output_version::LATEST {
+                report.to_string()
+            } else {
+                format!("{}.v{}", report, version)
+            };
+            reports.push((name, value));
+        }
+    }
+    Ok(reports)
+}
+
+/// Collect differences between expected and actual JSON as `path: message`
+fn diff_values(path: &str, expected: &Value, actual: &Value, diffs: &mut Vec<String>) {
+    match (expected, actual) {
+        (Value::Number(e), Value::Number(a)) => {
+            let (e, a) = (e.as_f64().unwrap_or(0.0), a.as_f64().unwrap_or(0.0));
+            if (e - a).abs() > COST_TOLERANCE * e.abs().max(1.0) {
+                diffs.push(format!("{}: expected {}, got {}", path, e, a));
+            }
+        }
+        (Value::Object(e), Value::Object(a)) => {
+            for (key, e_value) in e {
+                let child = format!("{}/{}", path, key);
+                match a.get(key) {
+                    Some(a_value) => diff_values(&child, e_value, a_value, diffs),
+                    None => diffs.push(format!("{}: missing", child)),
+                }
+            }
+            for key in a.keys().filter(|key| !e.contains_key(*key)) {
+                diffs.push(format!("{}/{}: unexpected", path, key));
+            }
+        }
+        (Value::Array(e), Value::Array(a)) => {
+            if e.len() != a.len() {
+                diffs.push(format!(
+                    "{}: expected {} items, got {}",
+                    path,
+                    e.len(),
+                    a.len()
+                ));
+            }
+            for (index, (e_item, a_item)) in e.iter().zip(a).enumerate() {
+                diff_values(&format!("{}/{}", path, index), e_item, a_item, diffs);
+            }
+        }
+        _ => {
+            if expected != actual {
+                diffs.push(format!("{}: expected {}, got {}", path, expected, actual));
+            }
+        }
+    }
+}
+
+/// Compare (or, when blessing, rewrite) the goldens for one case
+///
+/// Returns the list of failures for the case.
+fn run_case(case_dir: &Path, bless: bool) -> Result<Vec<String>> {
+    let expected_dir = case_dir.join("expected");
+    let mut failures = Vec::new();
+    let reports = render_reports(case_dir)?;
+
+    // The fixtures' project directories are well formed, so any name the
+    // guard rails distrust is a regression in the extraction heuristics
+    let low_confidence = project_names::low_confidence_projects();
+    if !low_confidence.is_empty() {
+        let names: Vec<&str> = low_confidence
+            .iter()
+            .map(|p| p.extracted.as_str())
+            .collect();
+        failures.push(format!(
+            "{} low-confidence project name(s): {}",
+            names.len(),
+            names.join(", ")
+        ));
+    }
+
+    for (report, actual) in reports {
+        let golden = expected_dir.join(format!("{}.json", report));
+
+        if bless {
+            if let Some(dir) = golden.parent() {
+                fs::create_dir_all(dir)?;
+            }
+            fs::write(&golden, serde_json::to_string_pretty(&actual)? + "\n")?;
+            continue;
+        }
+
+        let expected: Value = match fs::read_to_string(&golden) {
+            Ok(content) => serde_json::from_str(&content)
+                .with_context(|| format!("Invalid golden file: {}", golden.display()))?,
+            Err(_) => {
+                failures.push(format!("{}: missing golden, run with --bless", report));
+                continue;
+            }
+        };
+
+        let mut diffs = Vec::new();
+        diff_values("", &expected, &actual, &mut diffs);
+        failures.extend(diffs.into_iter().map(|diff| format!("{}.json {}", report, diff)));
+    }
+
+    Ok(failures)
+}
+
+fn main() -> ExitCode {
+    // Parse the fixtures every time instead of caching them in the user's cache
+    std::env::set_var("CLAUDE_USAGE_PARSE_CACHE", "false");
+    // Price with the built-in rates, not a snapshot the user downloaded
+    std::env::set_var(
+        "CLAUDE_USAGE_PRICING_SNAPSHOT",
+        fixtures_dir().join("no-pricing-snapshot.json"),
+    );
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let bless = args.iter().any(|arg| arg == "--bless")
+        || std::env::var("BLESS").is_ok_and(|value| value == "1");
+    // Positional arguments filter cases by name, like libtest's test filter
+    let filters: Vec<&String> = args.iter().filter(|arg| !arg.starts_with('-')).collect();
+
+    let mut cases: Vec<PathBuf> = match fs::read_dir(fixtures_dir()) {
+        Ok(entries) => entries
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|path| path.is_dir())
+            .collect(),
+        Err(e) => {
+            eprintln!("Failed to read golden fixtures: {}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+    cases.sort();
+
+    let mut failed = 0;
+    let mut ran = 0;
+    for case_dir in &cases {
+        let name = case_dir
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default();
+        if !filters.is_empty() && !filters.iter().any(|f| name.contains(f.as_str())) {
+            continue;
+        }
+        ran += 1;
+
+        match run_case(case_dir, bless) {
+            Ok(failures) if failures.is_empty() => {
+                println!("golden {} ... {}", name, if bless { "blessed" } else { "ok" });
+            }
+            Ok(failures) => {
+                failed += 1;
+                println!("golden {} ... FAILED", name);
+                for failure in failures.iter().take(20) {
+                    println!("    {}", failure);
+                }
+                if failures.len() > 20 {
+                    println!("    ... and {} more", failures.len() - 20);
+                }
+            }
+            Err(e) => {
+                failed += 1;
+                println!("golden {} ... ERROR: {:#}", name, e);
+            }
+        }
+    }
+
+    println!(
+        "\ngolden result: {} cases, {} passed, {} failed",
+        ran,
+        ran - failed,
+        failed
+    );
+    if failed > 0 {
+        println!("Run `cargo test --test golden_test -- --bless` to accept the new output");
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    }
+}
@@ -2,7 +2,9 @@
 
 use claude_usage::analyzer::ClaudeUsageAnalyzer;
 // Note: Test isolation removed for simplicity
-use claude_usage::dedup::ProcessOptions;
+use claude_usage::request::AnalyzerRequest;
+use claude_usage::models::MicroDollars;
+use chrono::{DateTime, Utc};
 use std::fs;
 use std::io::Write;
 use std::path::Path;
@@ -95,18 +97,10 @@ async fn test_e2e_basic_analysis() {
 
     // Set up analyzer
     let mut analyzer = ClaudeUsageAnalyzer::new();
-    let options = ProcessOptions {
-        command: "daily".to_string(),
-        json_output: false,
-        limit: None,
-        since_date: None,
-        until_date: None,
-        snapshot: false,
-        exclude_vms: false,
-    };
+    let request = AnalyzerRequest::daily();
 
     // Run analysis - this uses UnifiedParser internally
-    let result = analyzer.aggregate_data("daily", options).await;
+    let result = analyzer.sessions(request).await;
 
     assert!(result.is_ok(), "Analysis should succeed");
     let sessions = result.unwrap();
@@ -130,18 +124,10 @@ async fn test_e2e_with_malformed_data() {
     std::env::set_var("CLAUDE_HOME", temp_dir.path());
 
     let mut analyzer = ClaudeUsageAnalyzer::new();
-    let options = ProcessOptions {
-        command: "monthly".to_string(),
-        json_output: false,
-        limit: None,
-        since_date: None,
-        until_date: None,
-        snapshot: false,
-        exclude_vms: false,
-    };
+    let request = AnalyzerRequest::monthly();
 
     // Should handle malformed data gracefully
-    let result = analyzer.aggregate_data("monthly", options).await;
+    let result = analyzer.sessions(request).await;
 
     assert!(result.is_ok(), "Should handle malformed data gracefully");
     let sessions = result.unwrap();
@@ -177,34 +163,18 @@ async fn test_e2e_vm_exclusion() {
     let mut analyzer = ClaudeUsageAnalyzer::new();
 
     // Test with VMs included
-    let options_with_vms = ProcessOptions {
-        command: "daily".to_string(),
-        json_output: false,
-        limit: None,
-        since_date: None,
-        until_date: None,
-        snapshot: false,
-        exclude_vms: false,
-    };
+    let request_with_vms = AnalyzerRequest::daily();
 
     let result_with_vms = analyzer
-        .aggregate_data("daily", options_with_vms)
+        .sessions(request_with_vms)
         .await
         .unwrap();
 
     // Test with VMs excluded
-    let options_without_vms = ProcessOptions {
-        command: "daily".to_string(),
-        json_output: false,
-        limit: None,
-        since_date: None,
-        until_date: None,
-        snapshot: false,
-        exclude_vms: true,
-    };
+    let request_without_vms = AnalyzerRequest::daily().exclude_vms(true);
 
     let result_without_vms = analyzer
-        .aggregate_data("daily", options_without_vms)
+        .sessions(request_without_vms)
         .await
         .unwrap();
 
@@ -241,18 +211,10 @@ async fn test_e2e_keeper_schema_resilience() {
     std::env::set_var("CLAUDE_HOME", temp_dir.path());
 
     let mut analyzer = ClaudeUsageAnalyzer::new();
-    let options = ProcessOptions {
-        command: "daily".to_string(),
-        json_output: false,
-        limit: None,
-        since_date: None,
-        until_date: None,
-        snapshot: false,
-        exclude_vms: false,
-    };
+    let request = AnalyzerRequest::daily();
 
     // Keeper integration should handle all variations
-    let result = analyzer.aggregate_data("daily", options).await;
+    let result = analyzer.sessions(request).await;
 
     assert!(result.is_ok(), "Keeper should handle schema variations");
     let sessions = result.unwrap();
@@ -262,8 +224,8 @@ async fn test_e2e_keeper_schema_resilience() {
     // Cleanup removed
 }
 
-#[tokio::test]
-async fn test_e2e_date_filtering() {
+#[test]
+fn test_e2e_date_filtering() {
     // Config reset removed
     let temp_dir = create_mock_claude_structure();
     let claude_path = temp_dir.path().join(".claude");
@@ -277,37 +239,41 @@ async fn test_e2e_date_filtering() {
 
     // Entries from different dates
     writeln!(file, r#"{{"timestamp":"2024-01-10T10:00:00Z","message":{{"id":"msg_old","model":"claude-3-5-sonnet-20241022","usage":{{"input_tokens":100,"output_tokens":200}}}},"costUSD":0.003,"requestId":"req_old"}}"#).unwrap();
-    writeln!(file, r#"{{"timestamp":"2025-01-15T10:00:00Z","message":{{"id":"msg_mid","model":"claude-3-5-sonnet-20241022","usage":{{"input_tokens":100,"output_tokens":200}}}},"costUSD":0.003,"requestId":"req_mid"}}"#).unwrap();
+    writeln!(file, r#"{{"timestamp":"2024-01-15T10:00:00Z","message":{{"id":"msg_mid","model":"claude-3-5-sonnet-20241022","usage":{{"input_tokens":100,"output_tokens":200}}}},"costUSD":0.003,"requestId":"req_mid"}}"#).unwrap();
     writeln!(file, r#"{{"timestamp":"2024-01-20T10:00:00Z","message":{{"id":"msg_new","model":"claude-3-5-sonnet-20241022","usage":{{"input_tokens":100,"output_tokens":200}}}},"costUSD":0.003,"requestId":"req_new"}}"#).unwrap();
 
-    // Set up test environment
-    std::env::set_var("CLAUDE_HOME", temp_dir.path());
-
-    let mut analyzer = ClaudeUsageAnalyzer::new();
+    let analyzer = ClaudeUsageAnalyzer::new();
 
     // Test with date range
-    let options = ProcessOptions {
-        command: "daily".to_string(),
-        json_output: false,
-        limit: None,
-        since_date: Some(
-            chrono::DateTime::parse_from_rfc3339("2024-01-14T00:00:00Z")
-                .unwrap()
-                .with_timezone(&chrono::Utc),
-        ),
-        until_date: Some(
-            chrono::DateTime::parse_from_rfc3339("2024-01-16T23:59:59Z")
-                .unwrap()
-                .with_timezone(&chrono::Utc),
-        ),
-        snapshot: false,
-        exclude_vms: false,
-    };
-
-    let result = analyzer.aggregate_data("daily", options).await;
+    let since = DateTime::parse_from_rfc3339("2024-01-14T00:00:00Z")
+        .unwrap()
+        .with_timezone(&Utc);
+    let until = DateTime::parse_from_rfc3339("2024-01-16T23:59:59Z")
+        .unwrap()
+        .with_timezone(&Utc);
+    let request = AnalyzerRequest::daily().since(since).until(until);
+
+    // Read the fixture's logs rather than the machine's backups
+    let result = analyzer.sessions_from_roots(request, &[claude_path]);
 
     assert!(result.is_ok(), "Date filtering should work");
+    let sessions = result.unwrap();
+
     // Should only include the middle entry
+    let in_range: Vec<_> = sessions
+        .iter()
+        .flat_map(|s| s.daily_usage.iter())
+        .filter(|(date, _)| date.as_str() >= "2024-01-14" && date.as_str() <= "2024-01-16")
+        .collect();
+    assert_eq!(in_range.len(), 1, "Only 2024-01-15 is in range");
+    assert_eq!(in_range[0].0, "2024-01-15");
+    assert_eq!(in_range[0].1.cost, MicroDollars::from_dollars(0.003));
+    assert!(
+        sessions
+            .iter()
+            .all(|s| s.daily_usage.contains_key("2024-01-15")),
+        "Sessions without usage in range should be filtered out"
+    );
 
     // Clean up
     // Cleanup removed
@@ -343,23 +309,19 @@ async fn test_e2e_deduplication() {
     std::env::set_var("CLAUDE_HOME", temp_dir.path());
 
     let mut analyzer = ClaudeUsageAnalyzer::new();
-    let options = ProcessOptions {
-        command: "daily".to_string(),
-        json_output: false,
-        limit: None,
-        since_date: None,
-        until_date: None,
-        snapshot: false,
-        exclude_vms: false,
-    };
-
-    let result = analyzer.aggregate_data("daily", options).await;
+    let request = AnalyzerRequest::daily();
+
+    let result = analyzer.sessions(request).await;
 
     assert!(result.is_ok(), "Deduplication should work");
     let sessions = result.unwrap();
 
     // Should have deduplicated the duplicate entries
-    let total_cost: f64 = sessions.iter().map(|s| s.total_cost).sum();
+    let total_cost = sessions
+        .iter()
+        .map(|s| s.total_cost)
+        .sum::<MicroDollars>()
+        .to_dollars();
     // Verify that we actually processed the right data by checking details
     if !sessions.is_empty() {
         println!("Session cost: {}, expected: ~0.007", total_cost);
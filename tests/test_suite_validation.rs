@@ -26,15 +26,7 @@ fn test_analyzer_import() {
 fn test_process_options_import() {
     // Validate ProcessOptions can be imported and created
     use claude_usage::dedup::ProcessOptions;
-    let _options = ProcessOptions {
-        command: "test".to_string(),
-        json_output: false,
-        limit: None,
-        since_date: None,
-        until_date: None,
-        snapshot: false,
-        exclude_vms: false,
-    };
+    let _options = ProcessOptions::new("test");
     assert!(true, "ProcessOptions should be importable and creatable");
 }
 